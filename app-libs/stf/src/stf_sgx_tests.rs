@@ -59,6 +59,45 @@ pub fn shield_funds_increments_signer_account_nonce() {
 	assert_eq!(1, StfState::get_account_nonce(&mut state, &enclave_signer_account_id));
 }
 
+pub fn tip_is_charged_from_sender() {
+	let sender = AccountId::new([2u8; 32]);
+	let receiver = AccountId::new([3u8; 32]);
+	let mut state = StfState::init_state(sender.clone());
+
+	let transfer_call = TrustedCallSigned {
+		call: TrustedCall::balance_transfer(sender.clone(), receiver.clone(), 100u128),
+		nonce: 0,
+		tip: 10u128,
+		signature: Signature::Ed25519(Ed25519Signature([0u8; 64])),
+	};
+
+	StfState::execute_call(&mut state, transfer_call, &mut Vec::new(), [0u8, 1u8]).unwrap();
+	assert_eq!(890, StfState::get_account_data(&mut state, &sender).free);
+	assert_eq!(100, StfState::get_account_data(&mut state, &receiver).free);
+}
+
+pub fn failed_call_uses_up_nonce_and_tip() {
+	let sender = AccountId::new([2u8; 32]);
+	let receiver = AccountId::new([3u8; 32]);
+	let mut state = StfState::init_state(sender.clone());
+
+	let transfer_call = TrustedCallSigned {
+		call: TrustedCall::balance_transfer(sender.clone(), receiver.clone(), 5000u128),
+		nonce: 0,
+		tip: 10u128,
+		signature: Signature::Ed25519(Ed25519Signature([0u8; 64])),
+	};
+
+	assert!(StfState::execute_call(&mut state, transfer_call.clone(), &mut Vec::new(), [0u8, 1u8])
+		.is_err());
+	assert_eq!(1, StfState::get_account_nonce(&mut state, &sender));
+	assert_eq!(990, StfState::get_account_data(&mut state, &sender).free);
+
+	// Executing the call again fails on the nonce, without charging the tip again.
+	assert!(StfState::execute_call(&mut state, transfer_call, &mut Vec::new(), [0u8, 1u8]).is_err());
+	assert_eq!(990, StfState::get_account_data(&mut state, &sender).free);
+}
+
 pub fn test_root_account_exists_after_initialization() {
 	let enclave_account = AccountId::new([2u8; 32]);
 	let mut state = StfState::init_state(enclave_account);
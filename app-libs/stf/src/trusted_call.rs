@@ -32,9 +32,12 @@ use encointer_primitives::{
 	communities::{CommunityIdentifier, Location},
 	scheduler::CeremonyPhaseType,
 };
-use frame_support::{ensure, traits::UnfilteredDispatchable};
+use frame_support::{
+	ensure,
+	traits::{Currency, ExistenceRequirement, Get, UnfilteredDispatchable, WithdrawReasons},
+};
 pub use ita_sgx_runtime::{Balance, Index};
-use ita_sgx_runtime::{Runtime, System};
+use ita_sgx_runtime::{Balances, ExtrinsicBaseWeight, Runtime, System, Weight};
 use itp_stf_interface::ExecuteCall;
 use itp_storage::{storage_map_key, storage_value_key, StorageHasher};
 use itp_types::OpaqueCall;
@@ -48,7 +51,9 @@ use crate::encointer_helpers::{
 	is_ceremony_master, is_private_community, private_community_ids_storage_key,
 };
 #[cfg(feature = "evm")]
-use ita_sgx_runtime::{AddressMapping, HashedAddressMapping};
+use ita_sgx_runtime::{
+	AddressMapping, FixedGasWeightMapping, GasWeightMapping, HashedAddressMapping,
+};
 
 #[cfg(feature = "evm")]
 use crate::evm_helpers::{create_code_hash, evm_create2_address, evm_create_address};
@@ -181,6 +186,21 @@ impl TrustedCall {
 		}
 	}
 
+	/// Dispatch weight of the call, limits the calls included in a sidechain block.
+	///
+	/// EVM calls are weighted by their gas limit on top of the extrinsic base weight.
+	pub fn weight(&self) -> Weight {
+		let base_weight = ExtrinsicBaseWeight::get();
+		match self {
+			#[cfg(feature = "evm")]
+			TrustedCall::evm_call(_, _, _, _, _, gas_limit, ..)
+			| TrustedCall::evm_create(_, _, _, _, gas_limit, ..)
+			| TrustedCall::evm_create2(_, _, _, _, _, gas_limit, ..) =>
+				base_weight.saturating_add(FixedGasWeightMapping::gas_to_weight(*gas_limit)),
+			_ => base_weight,
+		}
+	}
+
 	pub fn sign(
 		&self,
 		pair: &KeyPair,
@@ -188,12 +208,27 @@ impl TrustedCall {
 		mrenclave: &[u8; 32],
 		shard: &ShardIdentifier,
 	) -> TrustedCallSigned {
-		let mut payload = self.encode();
-		payload.append(&mut nonce.encode());
-		payload.append(&mut mrenclave.encode());
-		payload.append(&mut shard.encode());
+		self.sign_with_tip(pair, nonce, 0, mrenclave, shard)
+	}
 
-		TrustedCallSigned { call: self.clone(), nonce, signature: pair.sign(payload.as_slice()) }
+	/// Signs the call together with a tip, which is charged from the sender when the call is
+	/// executed. Calls with a higher tip are prioritized in the trusted operation pool.
+	pub fn sign_with_tip(
+		&self,
+		pair: &KeyPair,
+		nonce: Index,
+		tip: Balance,
+		mrenclave: &[u8; 32],
+		shard: &ShardIdentifier,
+	) -> TrustedCallSigned {
+		let payload = signature_payload(self, nonce, tip, mrenclave, shard);
+
+		TrustedCallSigned {
+			call: self.clone(),
+			nonce,
+			tip,
+			signature: pair.sign(payload.as_slice()),
+		}
 	}
 }
 
@@ -201,19 +236,18 @@ impl TrustedCall {
 pub struct TrustedCallSigned {
 	pub call: TrustedCall,
 	pub nonce: Index,
+	/// Tip paid by the sender on top of the call, determines the priority in the pool.
+	pub tip: Balance,
 	pub signature: Signature,
 }
 
 impl TrustedCallSigned {
 	pub fn new(call: TrustedCall, nonce: Index, signature: Signature) -> Self {
-		TrustedCallSigned { call, nonce, signature }
+		TrustedCallSigned { call, nonce, tip: 0, signature }
 	}
 
 	pub fn verify_signature(&self, mrenclave: &[u8; 32], shard: &ShardIdentifier) -> bool {
-		let mut payload = self.call.encode();
-		payload.append(&mut self.nonce.encode());
-		payload.append(&mut mrenclave.encode());
-		payload.append(&mut shard.encode());
+		let payload = signature_payload(&self.call, self.nonce, self.tip, mrenclave, shard);
 		self.signature.verify(payload.as_slice(), self.call.sender_account())
 	}
 
@@ -249,6 +283,10 @@ impl ExecuteCall for TrustedCallSigned {
 			self.nonce == System::account_nonce(&sender),
 			Self::Error::InvalidNonce(self.nonce)
 		);
+		charge_tip(&sender, self.tip)?;
+		// The nonce is used up together with the tip, also if the call fails. Otherwise the call
+		// could be executed again and again, charging the tip each time.
+		System::inc_account_nonce(&sender);
 		match self.call {
 			TrustedCall::balance_set_balance(root, who, free_balance, reserved_balance) => {
 				ensure!(is_root::<Runtime, AccountId>(&root), Self::Error::MissingPrivileges(root));
@@ -843,9 +881,7 @@ impl ExecuteCall for TrustedCallSigned {
 				info!("Trying to create evm contract with address {:?}", contract_address);
 				Ok(())
 			},
-		}?;
-		System::inc_account_nonce(&sender);
-		Ok(())
+		}
 	}
 
 	fn get_storage_hashes_to_update(self) -> Vec<Vec<u8>> {
@@ -1012,6 +1048,46 @@ fn shield_funds(account: AccountId, amount: u128) -> Result<(), StfError> {
 	Ok(())
 }
 
+/// Version of the payload that is signed for a trusted call, it is part of the payload.
+///
+/// Version 1 added the tip to the payload (and to `TrustedCallSigned`). This is a breaking
+/// change: calls signed in the unversioned format without tip no longer verify, clients have
+/// to sign with the current version.
+pub const TRUSTED_CALL_SIGNATURE_VERSION: u8 = 1;
+
+fn signature_payload(
+	call: &TrustedCall,
+	nonce: Index,
+	tip: Balance,
+	mrenclave: &[u8; 32],
+	shard: &ShardIdentifier,
+) -> Vec<u8> {
+	let mut payload = TRUSTED_CALL_SIGNATURE_VERSION.encode();
+	payload.append(&mut call.encode());
+	payload.append(&mut nonce.encode());
+	payload.append(&mut tip.encode());
+	payload.append(&mut mrenclave.encode());
+	payload.append(&mut shard.encode());
+	payload
+}
+
+/// Withdraws the tip from the sender. Like the transaction fees of the sgx-runtime,
+/// the tip is burned.
+fn charge_tip(account: &AccountId, tip: Balance) -> Result<(), StfError> {
+	if tip == 0 {
+		return Ok(())
+	}
+	debug!("charge tip of {} from {}", tip, account_id_to_string(account));
+	<Balances as Currency<AccountId>>::withdraw(
+		account,
+		tip,
+		WithdrawReasons::TIP,
+		ExistenceRequirement::KeepAlive,
+	)
+	.map_err(|_| StfError::MissingFunds)?;
+	Ok(())
+}
+
 fn is_root<Runtime, AccountId>(account: &AccountId) -> bool
 where
 	Runtime: frame_system::Config<AccountId = AccountId> + pallet_sudo::Config,
@@ -1042,4 +1118,49 @@ mod tests {
 
 		assert!(signed_call.verify_signature(&mrenclave, &shard));
 	}
+
+	#[test]
+	fn verify_signature_fails_for_tampered_tip() {
+		let mrenclave = [0u8; 32];
+		let shard = ShardIdentifier::default();
+
+		let call = TrustedCall::balance_transfer(
+			AccountKeyring::Alice.public().into(),
+			AccountKeyring::Bob.public().into(),
+			42,
+		);
+		let mut signed_call = call.sign_with_tip(
+			&KeyPair::Sr25519(AccountKeyring::Alice.pair()),
+			0,
+			10,
+			&mrenclave,
+			&shard,
+		);
+		assert!(signed_call.verify_signature(&mrenclave, &shard));
+
+		signed_call.tip = 0;
+		assert!(!signed_call.verify_signature(&mrenclave, &shard));
+	}
+
+	#[test]
+	fn verify_signature_fails_for_unversioned_payload() {
+		let nonce = 21;
+		let mrenclave = [0u8; 32];
+		let shard = ShardIdentifier::default();
+		let pair = KeyPair::Sr25519(AccountKeyring::Alice.pair());
+
+		let call = TrustedCall::balance_transfer(
+			AccountKeyring::Alice.public().into(),
+			AccountKeyring::Bob.public().into(),
+			42,
+		);
+		let mut unversioned_payload = call.encode();
+		unversioned_payload.append(&mut nonce.encode());
+		unversioned_payload.append(&mut mrenclave.encode());
+		unversioned_payload.append(&mut shard.encode());
+		let signed_call =
+			TrustedCallSigned::new(call, nonce, pair.sign(unversioned_payload.as_slice()));
+
+		assert!(!signed_call.verify_signature(&mrenclave, &shard));
+	}
 }
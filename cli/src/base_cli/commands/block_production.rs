@@ -0,0 +1,139 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+use crate::{
+	command_utils::{get_pair_from_str, get_worker_api_direct},
+	Cli,
+};
+use base58::FromBase58;
+use clap::{Parser, Subcommand};
+use codec::Decode;
+use itc_rpc_client::direct_client::DirectApi;
+use itp_rpc::{RpcRequest, RpcResponse, RpcReturnValue};
+use itp_time_utils::now_as_u64;
+use itp_types::{
+	BlockProductionAdminRequest, BlockProductionCommand, BlockProductionStatus,
+	DirectRequestStatus, ShardIdentifier, SignedBlockProductionAdminRequest,
+};
+use itp_utils::{FromHexPrefixed, ToHexPrefixed};
+use log::*;
+use sp_core::{sr25519 as sr25519_core, Pair};
+
+/// Admin commands to control the sidechain block production of a worker.
+#[derive(Subcommand)]
+pub enum BlockProductionSubCommand {
+	/// suspend the sidechain block production of a shard, signed by the admin account of the worker
+	Suspend(BlockProductionControlArgs),
+
+	/// resume the sidechain block production of a shard, signed by the admin account of the worker
+	Resume(BlockProductionControlArgs),
+
+	/// query whether the sidechain block production of a shard is suspended
+	Status(BlockProductionStatusArgs),
+}
+
+#[derive(Parser)]
+pub struct BlockProductionControlArgs {
+	/// admin AccountId in ss58check format
+	admin: String,
+
+	/// shard identifier base58 encoded
+	shard: String,
+}
+
+#[derive(Parser)]
+pub struct BlockProductionStatusArgs {
+	/// shard identifier base58 encoded
+	shard: String,
+}
+
+impl BlockProductionSubCommand {
+	pub(crate) fn run(&self, cli: &Cli) {
+		match self {
+			BlockProductionSubCommand::Suspend(args) =>
+				control_block_production(cli, args, BlockProductionCommand::Suspend),
+			BlockProductionSubCommand::Resume(args) =>
+				control_block_production(cli, args, BlockProductionCommand::Resume),
+			BlockProductionSubCommand::Status(args) => print_block_production_status(cli, args),
+		}
+	}
+}
+
+fn control_block_production(
+	cli: &Cli,
+	args: &BlockProductionControlArgs,
+	command: BlockProductionCommand,
+) {
+	let admin = sr25519_core::Pair::from(get_pair_from_str(&args.admin));
+	let shard = shard_from_base58(&args.shard);
+	let nonce = match get_block_production_status(cli, &shard) {
+		Some(status) => status.next_admin_nonce,
+		None => return,
+	};
+	let request = BlockProductionAdminRequest::new(shard, command, nonce, now_as_u64());
+	let signature = admin.sign(request.signature_payload().as_slice());
+	let signed_request =
+		SignedBlockProductionAdminRequest::new(request, admin.public().into(), signature.into());
+
+	let rpc_method = match command {
+		BlockProductionCommand::Suspend => "sidechain_suspendBlockProduction",
+		BlockProductionCommand::Resume => "sidechain_resumeBlockProduction",
+	};
+
+	if send_rpc_request(cli, rpc_method, signed_request.to_hex()).is_some() {
+		println!("{:?} block production of shard {}: ok", command, args.shard);
+	}
+}
+
+fn print_block_production_status(cli: &Cli, args: &BlockProductionStatusArgs) {
+	if let Some(status) = get_block_production_status(cli, &shard_from_base58(&args.shard)) {
+		println!("suspended: {}", status.is_suspended());
+		println!("  suspended by admin: {}", status.suspended_by_admin);
+		println!("  suspended for sync: {}", status.suspended_for_sync);
+		println!("next admin nonce: {}", status.next_admin_nonce);
+	}
+}
+
+fn get_block_production_status(
+	cli: &Cli,
+	shard: &ShardIdentifier,
+) -> Option<BlockProductionStatus> {
+	send_rpc_request(cli, "sidechain_getBlockProductionStatus", shard.to_hex())
+		.map(|value| BlockProductionStatus::decode(&mut value.as_slice()).unwrap())
+}
+
+/// Sends the rpc request to the worker and returns the value of the response, or `None` in case
+/// the worker returned an error.
+fn send_rpc_request(cli: &Cli, rpc_method: &str, hex_encoded_param: String) -> Option<Vec<u8>> {
+	let direct_api = get_worker_api_direct(cli);
+	let jsonrpc_call: String =
+		RpcRequest::compose_jsonrpc_call(rpc_method.to_owned(), vec![hex_encoded_param]).unwrap();
+
+	let rpc_response_str = direct_api.get(&jsonrpc_call).unwrap();
+	let rpc_response: RpcResponse = serde_json::from_str(&rpc_response_str).unwrap();
+	let rpc_return_value = RpcReturnValue::from_hex(&rpc_response.result).unwrap();
+	debug!("{} returned status {:?}", rpc_method, rpc_return_value.status);
+
+	if rpc_return_value.status == DirectRequestStatus::Error {
+		println!("[Error] {}", String::decode(&mut rpc_return_value.value.as_slice()).unwrap());
+		return None
+	}
+	Some(rpc_return_value.value)
+}
+
+fn shard_from_base58(shard: &str) -> ShardIdentifier {
+	ShardIdentifier::from_slice(&shard.from_base58().expect("shard has to be base58 encoded"))
+}
@@ -1,4 +1,5 @@
 pub mod balance;
+pub mod block_production;
 pub mod faucet;
 pub mod listen;
 pub mod shield_funds;
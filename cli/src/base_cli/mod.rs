@@ -17,8 +17,9 @@
 
 use crate::{
 	base_cli::commands::{
-		balance::BalanceCommand, faucet::FaucetCommand, listen::ListenCommand,
-		shield_funds::ShieldFundsCommand, transfer::TransferCommand,
+		balance::BalanceCommand, block_production::BlockProductionSubCommand,
+		faucet::FaucetCommand, listen::ListenCommand, shield_funds::ShieldFundsCommand,
+		transfer::TransferCommand,
 	},
 	command_utils::*,
 	Cli,
@@ -70,6 +71,10 @@ pub enum BaseCli {
 
 	/// Transfer funds from an parentchain account to an incognito account
 	ShieldFunds(ShieldFundsCommand),
+
+	/// suspend, resume or query the sidechain block production of a worker (admin only)
+	#[clap(subcommand)]
+	BlockProduction(BlockProductionSubCommand),
 }
 
 impl BaseCli {
@@ -85,6 +90,7 @@ impl BaseCli {
 			BaseCli::ListWorkers => list_workers(cli),
 			BaseCli::Listen(cmd) => cmd.run(cli),
 			BaseCli::ShieldFunds(cmd) => cmd.run(cli),
+			BaseCli::BlockProduction(cmd) => cmd.run(cli),
 		}
 	}
 }
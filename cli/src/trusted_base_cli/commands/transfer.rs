@@ -43,6 +43,10 @@ pub struct TransferCommand {
 
 	/// Optional Community Id. If it is supplied, transfers balance in that community currency. Otherwise send parentchain native token"
 	community_id: Option<String>,
+
+	/// tip paid to prioritize the transfer over others in the trusted operation pool
+	#[clap(long, default_value_t = 0)]
+	tip: Balance,
 }
 
 impl TransferCommand {
@@ -66,11 +70,11 @@ impl TransferCommand {
 					cid,
 					BalanceType::from_num(self.amount),
 				)
-				.sign(&KeyPair::Sr25519(from), nonce, &mrenclave, &shard)
+				.sign_with_tip(&KeyPair::Sr25519(from), nonce, self.tip, &mrenclave, &shard)
 				.into_trusted_operation(trusted_args.direct)
 			},
 			None => TrustedCall::balance_transfer(from.public().into(), to, self.amount)
-				.sign(&KeyPair::Sr25519(from), nonce, &mrenclave, &shard)
+				.sign_with_tip(&KeyPair::Sr25519(from), nonce, self.tip, &mrenclave, &shard)
 				.into_trusted_operation(trusted_args.direct),
 		};
 		let _ = perform_trusted_operation(cli, trusted_args, &top);
@@ -69,6 +69,13 @@ extern "C" {
 		node_metadata_size: u32,
 	) -> sgx_status_t;
 
+	pub fn set_admin_account(
+		eid: sgx_enclave_id_t,
+		retval: *mut sgx_status_t,
+		account: *const u8,
+		account_size: u32,
+	) -> sgx_status_t;
+
 	pub fn get_rsa_encryption_pubkey(
 		eid: sgx_enclave_id_t,
 		retval: *mut sgx_status_t,
@@ -24,6 +24,7 @@ use itp_enclave_api_ffi as ffi;
 use itp_settings::worker::{
 	HEADER_MAX_SIZE, MR_ENCLAVE_SIZE, SHIELDING_KEY_SIZE, SIGNING_KEY_SIZE,
 };
+use itp_types::AccountId;
 use log::*;
 use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
 use sgx_types::*;
@@ -58,6 +59,9 @@ pub trait EnclaveBase: Send + Sync + 'static {
 
 	fn set_node_metadata(&self, metadata: Vec<u8>) -> EnclaveResult<()>;
 
+	/// Set the admin account, which is authorized to suspend and resume block production.
+	fn set_admin_account(&self, account: &AccountId) -> EnclaveResult<()>;
+
 	fn get_rsa_shielding_pubkey(&self) -> EnclaveResult<Rsa3072PubKey>;
 
 	fn get_ecc_signing_pubkey(&self) -> EnclaveResult<ed25519::Public>;
@@ -181,6 +185,26 @@ impl EnclaveBase for Enclave {
 		Ok(())
 	}
 
+	fn set_admin_account(&self, account: &AccountId) -> EnclaveResult<()> {
+		let mut retval = sgx_status_t::SGX_SUCCESS;
+
+		let encoded_account = account.encode();
+
+		let result = unsafe {
+			ffi::set_admin_account(
+				self.eid,
+				&mut retval,
+				encoded_account.as_ptr(),
+				encoded_account.len() as u32,
+			)
+		};
+
+		ensure!(result == sgx_status_t::SGX_SUCCESS, Error::Sgx(result));
+		ensure!(retval == sgx_status_t::SGX_SUCCESS, Error::Sgx(retval));
+
+		Ok(())
+	}
+
 	fn get_rsa_shielding_pubkey(&self) -> EnclaveResult<Rsa3072PubKey> {
 		let mut retval = sgx_status_t::SGX_SUCCESS;
 
@@ -36,6 +36,23 @@ pub enum EnclaveMetric {
 	TopPoolSizeSet(u64),
 	TopPoolSizeIncrement,
 	TopPoolSizeDecrement,
+	/// Number of stale trusted operations evicted from the top pool, decreases its size
+	TopPoolOperationsEvicted(u64),
+	/// Increment the number of equivocations of a sidechain authority (Authority)
+	SidechainEquivocationIncrement(String),
+	/// Increment the number of slots claimed by this validateer
+	SidechainSlotClaimed,
+	/// Increment the number of slots skipped, because they were not ours to claim
+	SidechainSlotSkipped,
+	/// Increment the number of sidechain blocks proposed (Shard)
+	SidechainBlockProposed(String),
+	/// Time it took to propose a block and the time that was available for it in [ms].
+	/// (ProposalTime, ProposalBudget)
+	SidechainBlockProposalTime(u64, u64),
+	/// Number of trusted operations included in a proposed sidechain block
+	SidechainBlockOperations(u64),
+	/// Time between the production of a sidechain block and its import in [ms]
+	SidechainBlockImportLatency(u64),
 	ExchangeRateOracle(ExchangeRateOracleMetric),
 }
 
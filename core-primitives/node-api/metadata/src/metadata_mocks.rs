@@ -31,6 +31,7 @@ pub struct NodeMetadataMock {
 	unshield_funds: u8,
 	sidechain_module: u8,
 	imported_sidechain_block: u8,
+	report_equivocation: u8,
 	runtime_spec_version: u32,
 	runtime_transaction_version: u32,
 }
@@ -47,6 +48,7 @@ impl NodeMetadataMock {
 			unshield_funds: 5u8,
 			sidechain_module: 53u8,
 			imported_sidechain_block: 0u8,
+			report_equivocation: 1u8,
 			runtime_spec_version: 25,
 			runtime_transaction_version: 4,
 		}
@@ -83,4 +85,8 @@ impl SidechainCallIndexes for NodeMetadataMock {
 	fn confirm_imported_sidechain_block_indexes(&self) -> Result<[u8; 2]> {
 		Ok([self.sidechain_module, self.imported_sidechain_block])
 	}
+
+	fn report_equivocation_indexes(&self) -> Result<[u8; 2]> {
+		Ok([self.sidechain_module, self.report_equivocation])
+	}
 }
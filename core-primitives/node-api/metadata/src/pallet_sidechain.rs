@@ -21,10 +21,16 @@ const SIDECHAIN: &str = "Sidechain";
 
 pub trait SidechainCallIndexes {
 	fn confirm_imported_sidechain_block_indexes(&self) -> Result<[u8; 2]>;
+
+	fn report_equivocation_indexes(&self) -> Result<[u8; 2]>;
 }
 
 impl SidechainCallIndexes for NodeMetadata {
 	fn confirm_imported_sidechain_block_indexes(&self) -> Result<[u8; 2]> {
 		self.call_indexes(SIDECHAIN, "confirm_imported_sidechain_block")
 	}
+
+	fn report_equivocation_indexes(&self) -> Result<[u8; 2]> {
+		self.call_indexes(SIDECHAIN, "report_equivocation")
+	}
 }
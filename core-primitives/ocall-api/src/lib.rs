@@ -25,8 +25,8 @@ use core::result::Result as StdResult;
 use derive_more::{Display, From};
 use itp_storage::Error as StorageError;
 use itp_types::{
-	storage::StorageEntryVerified, BlockHash, ShardIdentifier, TrustedOperationStatus,
-	WorkerRequest, WorkerResponse,
+	storage::StorageEntryVerified, BlockHash, PeerBlockRangeRequest, Request, ShardIdentifier,
+	TrustedOperationStatus, WorkerRequest, WorkerResponse,
 };
 use sgx_types::*;
 use sp_core::H256;
@@ -126,6 +126,37 @@ pub trait EnclaveSidechainOCallApi: Clone + Send + Sync {
 		maybe_until_block_hash: Option<BlockHash>,
 		shard_identifier: ShardIdentifier,
 	) -> SgxResult<Vec<SignedSidechainBlock>>;
+
+	/// Fetch ranges of sidechain blocks from specific peers. The requests are served concurrently.
+	///
+	/// Returns one entry per request, in the order of the requests. An entry is `None` if the
+	/// blocks could not be fetched from the respective peer.
+	fn fetch_sidechain_block_ranges_from_peers<SignedSidechainBlock: Decode>(
+		&self,
+		requests: Vec<PeerBlockRangeRequest>,
+		shard_identifier: ShardIdentifier,
+	) -> SgxResult<Vec<Option<Vec<SignedSidechainBlock>>>>;
+
+	fn propose_finality_votes<SignedFinalityVote: Encode>(
+		&self,
+		signed_votes: Vec<SignedFinalityVote>,
+	) -> SgxResult<()>;
+
+	/// Gossip encrypted trusted operations to the peer validateers.
+	fn propose_trusted_operations(&self, trusted_operations: Vec<Request>) -> SgxResult<()>;
+
+	fn finalize_sidechain_block(
+		&self,
+		shard_identifier: ShardIdentifier,
+		block_hash: BlockHash,
+	) -> SgxResult<()>;
+
+	/// Store the encrypted pending trusted operations on the untrusted file system,
+	/// replacing any previously stored ones.
+	fn store_trusted_operation_pool(&self, encrypted_pool: Vec<u8>) -> SgxResult<()>;
+
+	/// Load the encrypted pending trusted operations stored last, if there are any.
+	fn load_trusted_operation_pool(&self) -> SgxResult<Option<Vec<u8>>>;
 }
 
 /// Newtype for IPFS CID
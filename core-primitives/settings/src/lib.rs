@@ -39,7 +39,9 @@ pub mod files {
 	/// sidechain database path
 	pub static SIDECHAIN_STORAGE_PATH: &str = "sidechain_db";
 	pub static SIDECHAIN_PURGE_INTERVAL: u64 = 7200; // purge sidechain every .. s
-	pub static SIDECHAIN_PURGE_LIMIT: u64 = 100; // keep the last.. sidechainblocks when purging
+	pub static SIDECHAIN_PURGE_LIMIT: u64 = 100; // default: keep the last.. sidechainblocks when purging
+	/// encrypted pending trusted operations, kept across restarts
+	pub static TOP_POOL_FILE: &str = "top_pool.bin";
 
 	// used by enclave
 	pub const RSA3072_SEALED_KEY_FILE: &str = "rsa3072_key_sealed.bin";
@@ -95,7 +97,60 @@ pub mod worker {
 pub mod sidechain {
 	use core::time::Duration;
 
+	/// Default slot duration, applies as long as no slot duration is configured on the
+	/// parentchain. The slot worker ticks relative to it, so the configured slot duration
+	/// should not be shorter.
 	pub static SLOT_DURATION: Duration = Duration::from_millis(300);
+	/// Portion of a slot the slot author may spend proposing its block. The rest of the slot is
+	/// left to send the block to the fellow validateers.
+	pub const BLOCK_PROPOSAL_SLOT_PORTION: f32 = 0.7;
+	/// Portion of a slot the first backup author waits after the proposal time of the slot author
+	/// ended, for the block of the slot author to reach it.
+	pub const BACKUP_AUTHOR_PROPAGATION_SLOT_PORTION: f32 = 0.1;
+	/// Portion of a slot each further backup author waits after the previous one before claiming
+	/// the slot of an unresponsive slot author. Should divide the slot evenly, the slot worker
+	/// ticks at this rate.
+	pub static BACKUP_AUTHOR_SLOT_PORTION: f32 = 0.1;
+	/// Maximum number of shards blocks are produced for in parallel within a slot. Each one
+	/// occupies an enclave thread, so keep it well below the `TCSNum` of the enclave config.
+	pub static MAX_PARALLEL_SHARD_PRODUCTION: usize = 3;
+	/// Number of blocks requested from a single peer when syncing missing blocks from
+	/// several peers. All chunks of a sync round are returned in one o-call buffer, which
+	/// reserves `MAX_BLOCK_ENCODED_SIZE` per requested block.
+	pub static PEER_SYNC_CHUNK_SIZE: u64 = 4;
+	/// Maximum number of chunks of missing blocks requested from peers at once. Bounds the
+	/// o-call buffer of a sync round to `PEER_SYNC_MAX_PARALLEL_REQUESTS * PEER_SYNC_CHUNK_SIZE`
+	/// blocks.
+	pub static PEER_SYNC_MAX_PARALLEL_REQUESTS: usize = 2;
+	/// Number of peers a chunk of missing blocks is requested from before peer sync fails.
+	pub static PEER_SYNC_MAX_ATTEMPTS_PER_CHUNK: usize = 3;
+	/// Maximum number of trusted operations included in a sidechain block.
+	pub static MAX_TRUSTED_OPERATIONS_PER_BLOCK: usize = 1000;
+	/// Maximum size of an encoded sidechain block in bytes. Blocks are broadcast hex encoded,
+	/// which doubles their size, and have to fit into the 10 MiB request limit of the peers.
+	pub static MAX_BLOCK_ENCODED_SIZE: usize = 4 * 1024 * 1024;
+	/// Maximum dispatch weight of the trusted calls included in a sidechain block, i.e. half a
+	/// second of execution time.
+	pub static MAX_BLOCK_WEIGHT: u64 = 500_000_000_000;
+	/// Maximum deviation in milliseconds of the timestamp of a signed admin request to suspend or
+	/// resume block production from the enclave time. Bounds the time such a request can be replayed.
+	pub static BLOCK_PRODUCTION_ADMIN_REQUEST_VALIDITY: u64 = 60_000;
+	/// Maximum deviation in milliseconds of the timestamp of a signed request to cancel a pending
+	/// trusted operation from the enclave time. Bounds the time such a request can be replayed.
+	pub static TRUSTED_OPERATION_CANCEL_REQUEST_VALIDITY: u64 = 60_000;
+	/// Maximum size of the encrypted pending trusted operations persisted across restarts.
+	/// The whole pool is loaded back into the enclave in one o-call buffer of this size.
+	pub static MAX_PERSISTED_TOP_POOL_SIZE: usize = 4 * 1024 * 1024;
+	/// Maximum number of trusted calls of a single sender in the future queue of the TOP pool.
+	pub static MAX_FUTURE_OPERATIONS_PER_SENDER: usize = 64;
+	/// Maximum accumulated encoded size in bytes of the trusted calls of a single sender in the
+	/// future queue of the TOP pool.
+	pub static MAX_FUTURE_BYTES_PER_SENDER: usize = 64 * 1024;
+	/// Number of consecutive trusted calls with an invalid signature after which the IP address
+	/// of the RPC client they were submitted by is banned.
+	pub static MAX_SIGNATURE_FAILURES_PER_CONNECTION: u32 = 3;
+	/// Duration an RPC client is banned for, after repeatedly submitting invalid signatures.
+	pub static CONNECTION_BAN_DURATION: Duration = Duration::from_secs(600);
 }
 
 /// Settings concerning the enclave
@@ -36,7 +36,7 @@ use itp_stf_interface::{
 };
 use itp_stf_state_handler::{handle_state::HandleState, query_shard_state::QueryShardState};
 use itp_time_utils::duration_now;
-use itp_types::{storage::StorageEntryVerified, OpaqueCall, H256};
+use itp_types::{storage::StorageEntryVerified, BlockLimits, OpaqueCall, H256};
 use log::*;
 use sp_runtime::traits::Header as HeaderTrait;
 use std::{
@@ -251,7 +251,7 @@ impl<OCallApi, StateHandler, NodeMetadataRepository, Stf> StateUpdateProposer
 where
 	OCallApi: EnclaveAttestationOCallApi + EnclaveOnChainOCallApi,
 	StateHandler: HandleState<HashType = H256>,
-	StateHandler::StateT: SgxExternalitiesTrait + Encode + StateHash,
+	StateHandler::StateT: SgxExternalitiesTrait + Encode + StateHash + Clone,
 	<StateHandler::StateT as SgxExternalitiesTrait>::SgxExternalitiesType: Encode,
 	NodeMetadataRepository: AccessNodeMetadata,
 	NodeMetadataRepository::MetadataType: TeerexCallIndexes,
@@ -262,7 +262,7 @@ where
 	<StateHandler::StateT as SgxExternalitiesTrait>::SgxExternalitiesDiffType:
 		IntoIterator<Item = (Vec<u8>, Option<Vec<u8>>)>,
 	<StateHandler::StateT as SgxExternalitiesTrait>::SgxExternalitiesDiffType:
-		From<BTreeMap<Vec<u8>, Option<Vec<u8>>>>,
+		From<BTreeMap<Vec<u8>, Option<Vec<u8>>>> + Encode + Clone,
 	<Stf as StateCallInterface<TrustedCallSigned, StateHandler::StateT>>::Error: Debug,
 {
 	type Externalities = StateHandler::StateT;
@@ -273,6 +273,7 @@ where
 		header: &PH,
 		shard: &ShardIdentifier,
 		max_exec_duration: Duration,
+		block_limits: &BlockLimits,
 		prepare_state_function: F,
 	) -> Result<BatchExecutionResult<Self::Externalities>>
 	where
@@ -287,14 +288,33 @@ where
 		// Execute any pre-processing steps.
 		let mut state = prepare_state_function(state);
 		let mut executed_and_failed_calls = Vec::<ExecutedOperation>::new();
+		let mut number_of_executed_calls: usize = 0;
+		let mut executed_calls_weight: u64 = 0;
 
-		// Iterate through all calls until time is over.
+		// Iterate through all calls until time is over or the block limits are reached.
+		// Calls are not skipped, a later call of the same sender would fail on its nonce.
 		for trusted_call_signed in trusted_calls.into_iter() {
 			// Break if allowed time window is over.
 			if ends_at < duration_now() {
 				break
 			}
 
+			if number_of_executed_calls >= block_limits.max_trusted_operations {
+				debug!("Reached the maximum number of trusted operations per block");
+				break
+			}
+
+			let call_weight = trusted_call_signed.to_call().map_or(0, |c| c.call.weight());
+			if executed_calls_weight.saturating_add(call_weight) > block_limits.max_weight {
+				debug!("Reached the maximum weight per block");
+				break
+			}
+
+			// The state diff only grows by executing the call, so we have to be able to revert it.
+			// Only the diff is kept, cloning the whole state for every call is too expensive.
+			let state_diff_before_call: BTreeMap<Vec<u8>, Option<Vec<u8>>> =
+				state.state_diff().clone().into_iter().collect();
+
 			match self.execute_trusted_call_on_stf(
 				&mut state,
 				&trusted_call_signed,
@@ -303,6 +323,23 @@ where
 				StatePostProcessing::None,
 			) {
 				Ok(executed_or_failed_call) => {
+					if executed_or_failed_call.is_success() {
+						let encoded_size = (number_of_executed_calls + 1)
+							* H256::default().encoded_size()
+							+ state.state_diff().encoded_size();
+						if encoded_size > block_limits.max_encoded_size {
+							debug!("Reached the maximum encoded size per block");
+							self.revert_call(
+								&mut state,
+								state_diff_before_call,
+								shard,
+								state_hash_before_execution,
+							)?;
+							break
+						}
+						number_of_executed_calls += 1;
+						executed_calls_weight = executed_calls_weight.saturating_add(call_weight);
+					}
 					executed_and_failed_calls.push(executed_or_failed_call);
 				},
 				Err(e) => {
@@ -319,6 +356,59 @@ where
 	}
 }
 
+impl<OCallApi, StateHandler, NodeMetadataRepository, Stf>
+	StfExecutor<OCallApi, StateHandler, NodeMetadataRepository, Stf>
+where
+	StateHandler: HandleState<HashType = H256>,
+	StateHandler::StateT: SgxExternalitiesTrait + StateHash,
+	<StateHandler::StateT as SgxExternalitiesTrait>::SgxExternalitiesDiffType:
+		IntoIterator<Item = (Vec<u8>, Option<Vec<u8>>)> + Clone,
+{
+	/// Reverts the changes of the last executed call, given the state diff from before the call.
+	///
+	/// Keys in that diff are restored from it. All other keys the call changed were not changed
+	/// by the batch before, so they are restored from the state the batch was executed on, which
+	/// is only loaded again here.
+	fn revert_call(
+		&self,
+		state: &mut StateHandler::StateT,
+		state_diff_before_call: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+		shard: &ShardIdentifier,
+		state_hash_before_execution: H256,
+	) -> Result<()> {
+		let state_before_execution = self.state_handler.load(shard)?;
+		if state_before_execution.hash() != state_hash_before_execution {
+			return Err(Error::Other(
+				"State changed during the batch execution, can not revert the last call".into(),
+			))
+		}
+
+		let changed_keys: Vec<Vec<u8>> = state
+			.state_diff()
+			.clone()
+			.into_iter()
+			.map(|(key, _)| key)
+			.filter(|key| !state_diff_before_call.contains_key(key))
+			.collect();
+		for key in changed_keys {
+			match state_before_execution.get(&key) {
+				Some(value) => state.insert(key, value.clone()),
+				None => state.remove(&key),
+			};
+		}
+
+		// Restoring the diff before the call also restores the values of its keys.
+		state.prune_state_diff();
+		for (key, value) in state_diff_before_call {
+			match value {
+				Some(value) => state.insert(key, value),
+				None => state.remove(&key),
+			};
+		}
+		Ok(())
+	}
+}
+
 impl<OCallApi, StateHandler, NodeMetadataRepository, Stf> StfExecuteGenericUpdate
 	for StfExecutor<OCallApi, StateHandler, NodeMetadataRepository, Stf>
 where
@@ -24,7 +24,7 @@ use codec::Encode;
 use ita_stf::{
 	stf_sgx_tests::StfState,
 	test_genesis::{endowed_account, test_genesis_setup},
-	ShardIdentifier, State, TrustedCall,
+	ShardIdentifier, State, TrustedCall, TrustedOperation,
 };
 use itc_parentchain_test::parentchain_header_builder::ParentchainHeaderBuilder;
 use itp_node_api::metadata::{metadata_mocks::NodeMetadataMock, provider::NodeMetadataRepository};
@@ -32,7 +32,7 @@ use itp_ocall_api::EnclaveAttestationOCallApi;
 use itp_sgx_externalities::{SgxExternalitiesTrait, StateHash};
 use itp_stf_state_handler::handle_state::HandleState;
 use itp_test::mock::{handle_state_mock::HandleStateMock, onchain_mock::OnchainMock};
-use itp_types::H256;
+use itp_types::{BlockLimits, H256};
 use sp_core::Pair;
 use sp_runtime::app_crypto::sp_core::blake2_256;
 use std::{sync::Arc, time::Duration, vec};
@@ -71,6 +71,7 @@ pub fn propose_state_update_executes_all_calls_given_enough_time() {
 			&ParentchainHeaderBuilder::default().build(),
 			&shard,
 			Duration::from_secs(1000),
+			&BlockLimits::unlimited(),
 			|state| state,
 		)
 		.unwrap();
@@ -118,6 +119,7 @@ pub fn propose_state_update_executes_only_one_trusted_call_given_not_enough_time
 			&ParentchainHeaderBuilder::default().build(),
 			&shard,
 			Duration::from_nanos(50_000),
+			&BlockLimits::unlimited(),
 			|state| state,
 		)
 		.unwrap();
@@ -161,6 +163,7 @@ pub fn propose_state_update_executes_no_trusted_calls_given_no_time() {
 			&ParentchainHeaderBuilder::default().build(),
 			&shard,
 			Duration::ZERO,
+			&BlockLimits::unlimited(),
 			|state| state,
 		)
 		.unwrap();
@@ -171,6 +174,119 @@ pub fn propose_state_update_executes_no_trusted_calls_given_no_time() {
 	assert_eq!(batch_execution_result.get_executed_operation_hashes(), vec![]);
 }
 
+pub fn propose_state_update_executes_no_more_than_max_trusted_operations() {
+	// given
+	let (stf_executor, ocall_api, state_handler) = stf_executor();
+	let mrenclave = ocall_api.get_mrenclave_of_self().unwrap().m;
+	let (_, shard) = init_state_and_shard_with_state_handler(state_handler.as_ref());
+	let (trusted_operation_1, trusted_operation_2) = two_balance_transfers(&mrenclave, &shard);
+	let call_operation_hash_1: H256 = blake2_256(&trusted_operation_1.encode()).into();
+	let block_limits = BlockLimits::new(1, usize::MAX, u64::MAX);
+
+	// when
+	let batch_execution_result = stf_executor
+		.propose_state_update(
+			&vec![trusted_operation_1, trusted_operation_2],
+			&ParentchainHeaderBuilder::default().build(),
+			&shard,
+			Duration::from_secs(1000),
+			&block_limits,
+			|state| state,
+		)
+		.unwrap();
+
+	// then
+	assert_eq!(batch_execution_result.executed_operations.len(), 1);
+	assert_eq!(batch_execution_result.get_executed_operation_hashes(), vec![call_operation_hash_1]);
+}
+
+pub fn propose_state_update_executes_no_calls_exceeding_max_weight() {
+	// given
+	let (stf_executor, ocall_api, state_handler) = stf_executor();
+	let mrenclave = ocall_api.get_mrenclave_of_self().unwrap().m;
+	let (_, shard) = init_state_and_shard_with_state_handler(state_handler.as_ref());
+	let (trusted_operation_1, trusted_operation_2) = two_balance_transfers(&mrenclave, &shard);
+	let call_operation_hash_1: H256 = blake2_256(&trusted_operation_1.encode()).into();
+	let call_weight = trusted_operation_1.to_call().unwrap().call.weight();
+	let block_limits = BlockLimits::new(usize::MAX, usize::MAX, call_weight * 2 - 1);
+
+	// when
+	let batch_execution_result = stf_executor
+		.propose_state_update(
+			&vec![trusted_operation_1, trusted_operation_2],
+			&ParentchainHeaderBuilder::default().build(),
+			&shard,
+			Duration::from_secs(1000),
+			&block_limits,
+			|state| state,
+		)
+		.unwrap();
+
+	// then
+	assert_eq!(batch_execution_result.executed_operations.len(), 1);
+	assert_eq!(batch_execution_result.get_executed_operation_hashes(), vec![call_operation_hash_1]);
+}
+
+pub fn propose_state_update_reverts_call_exceeding_max_encoded_size() {
+	// given
+	let (stf_executor, ocall_api, state_handler) = stf_executor();
+	let mrenclave = ocall_api.get_mrenclave_of_self().unwrap().m;
+	let (_, shard) = init_state_and_shard_with_state_handler(state_handler.as_ref());
+	let (trusted_operation_1, trusted_operation_2) = two_balance_transfers(&mrenclave, &shard);
+	let block_limits = BlockLimits::new(usize::MAX, 0, u64::MAX);
+
+	// when
+	let batch_execution_result = stf_executor
+		.propose_state_update(
+			&vec![trusted_operation_1, trusted_operation_2],
+			&ParentchainHeaderBuilder::default().build(),
+			&shard,
+			Duration::from_secs(1000),
+			&block_limits,
+			|state| state,
+		)
+		.unwrap();
+
+	// then
+	assert_eq!(batch_execution_result.executed_operations.len(), 0);
+	// Ensure that the exceeding call has been reverted.
+	assert_eq!(state_handler.load(&shard).unwrap(), batch_execution_result.state_after_execution);
+}
+
+pub fn propose_state_update_reverts_only_the_call_exceeding_max_encoded_size() {
+	// given
+	let (stf_executor, ocall_api, state_handler) = stf_executor();
+	let mrenclave = ocall_api.get_mrenclave_of_self().unwrap().m;
+	let (_, shard) = init_state_and_shard_with_state_handler(state_handler.as_ref());
+	let (trusted_operation_1, trusted_operation_2) = two_balance_transfers(&mrenclave, &shard);
+	let propose = |trusted_operations: Vec<TrustedOperation>, block_limits: &BlockLimits| {
+		stf_executor
+			.propose_state_update(
+				&trusted_operations,
+				&ParentchainHeaderBuilder::default().build(),
+				&shard,
+				Duration::from_secs(1000),
+				block_limits,
+				|state| state,
+			)
+			.unwrap()
+	};
+	let state_after_first_call =
+		propose(vec![trusted_operation_1.clone()], &BlockLimits::unlimited()).state_after_execution;
+	let size_after_first_call =
+		H256::default().encoded_size() + state_after_first_call.state_diff().encoded_size();
+
+	// when
+	let batch_execution_result = propose(
+		vec![trusted_operation_1, trusted_operation_2],
+		&BlockLimits::new(usize::MAX, size_after_first_call, u64::MAX),
+	);
+
+	// then
+	assert_eq!(batch_execution_result.executed_operations.len(), 1);
+	assert_eq!(state_after_first_call, batch_execution_result.state_after_execution);
+}
+
 pub fn propose_state_update_always_executes_preprocessing_step() {
 	// given
 	let shard = ShardIdentifier::default();
@@ -187,6 +303,7 @@ pub fn propose_state_update_always_executes_preprocessing_step() {
 			&ParentchainHeaderBuilder::default().build(),
 			&shard,
 			Duration::ZERO,
+			&BlockLimits::unlimited(),
 			|mut state| {
 				state.insert(key.clone(), value.clone());
 				state
@@ -259,3 +376,19 @@ pub(crate) fn init_state_and_shard_with_state_handler<S: HandleState<StateT = St
 
 	(state, shard)
 }
+
+fn two_balance_transfers(
+	mrenclave: &[u8; 32],
+	shard: &ShardIdentifier,
+) -> (TrustedOperation, TrustedOperation) {
+	let sender = endowed_account();
+	let trusted_operation_1 =
+		TrustedCall::balance_transfer(sender.public().into(), sender.public().into(), 42)
+			.sign(&sender.clone().into(), 0, mrenclave, shard)
+			.into_trusted_operation(true);
+	let trusted_operation_2 =
+		TrustedCall::balance_transfer(sender.public().into(), sender.public().into(), 100)
+			.sign(&sender.clone().into(), 1, mrenclave, shard)
+			.into_trusted_operation(true);
+	(trusted_operation_1, trusted_operation_2)
+}
@@ -27,7 +27,7 @@ use ita_stf::{
 	AccountId, Getter, KeyPair, ShardIdentifier, TrustedCall, TrustedCallSigned, TrustedOperation,
 };
 use itp_sgx_externalities::SgxExternalitiesTrait;
-use itp_types::H256;
+use itp_types::{BlockLimits, H256};
 use sp_core::Pair;
 use sp_runtime::traits::Header as HeaderTrait;
 use std::{marker::PhantomData, ops::Deref, time::Duration, vec::Vec};
@@ -66,6 +66,7 @@ where
 		_header: &PH,
 		_shard: &ShardIdentifier,
 		_max_exec_duration: Duration,
+		block_limits: &BlockLimits,
 		prepare_state_function: F,
 	) -> Result<BatchExecutionResult<Self::Externalities>>
 	where
@@ -80,6 +81,7 @@ where
 
 		let executed_operations: Vec<ExecutedOperation> = trusted_calls
 			.iter()
+			.take(block_limits.max_trusted_operations)
 			.map(|c| {
 				let operation_hash = c.hash();
 				let top_or_hash = TrustedOperationOrHash::from_top(c.clone());
@@ -21,7 +21,7 @@ use ita_stf::{
 	AccountId, ParentchainHeader, ShardIdentifier, TrustedCall, TrustedCallSigned, TrustedOperation,
 };
 use itp_sgx_externalities::SgxExternalitiesTrait;
-use itp_types::H256;
+use itp_types::{BlockLimits, H256};
 use sp_runtime::traits::Header as HeaderTrait;
 use std::{fmt::Debug, result::Result as StdResult, time::Duration};
 
@@ -51,13 +51,16 @@ pub trait StateUpdateProposer {
 	/// Executes trusted calls within a given time frame without permanent state mutation.
 	///
 	/// All executed call hashes and the mutated state are returned.
-	/// If the time expires, any remaining trusted calls within the batch will be ignored.
+	/// If the time expires or the block limits are reached, any remaining trusted calls within
+	/// the batch will be ignored. The encoded size limit applies to the executed call hashes
+	/// and the state diff, so `prepare_state_function` must not prune the diff.
 	fn propose_state_update<PH, F>(
 		&self,
 		trusted_calls: &[TrustedOperation],
 		header: &PH,
 		shard: &ShardIdentifier,
 		max_exec_duration: Duration,
+		block_limits: &BlockLimits,
 		prepare_state_function: F,
 	) -> Result<BatchExecutionResult<Self::Externalities>>
 	where
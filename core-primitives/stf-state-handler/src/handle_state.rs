@@ -60,6 +60,18 @@ pub trait HandleState {
 		shard: &ShardIdentifier,
 	) -> Result<Self::HashType>;
 
+	/// Get the hash of the latest state version of a shard.
+	fn latest_state_hash(&self, shard: &ShardIdentifier) -> Result<Self::HashType>;
+
+	/// Revert the state of a shard to the version identified by a state hash.
+	///
+	/// All state versions newer than the one reverted to are discarded.
+	fn revert_to(
+		&self,
+		shard: &ShardIdentifier,
+		state_hash: &Self::HashType,
+	) -> Result<Self::StateT>;
+
 	/// Reset (or override) a state.
 	///
 	/// Use in cases where the previous state is of no interest. Otherwise use `load_for_mutation` and `write_after_mutation`.
@@ -97,6 +97,28 @@ where
 		Ok(state_hash)
 	}
 
+	fn latest_state_hash(&self, shard: &ShardIdentifier) -> Result<Self::HashType> {
+		self.state_snapshot_repository
+			.read()
+			.map_err(|_| Error::LockPoisoning)?
+			.latest_state_hash(shard)
+	}
+
+	fn revert_to(
+		&self,
+		shard: &ShardIdentifier,
+		state_hash: &Self::HashType,
+	) -> Result<Self::StateT> {
+		let mut state_write_lock =
+			self.state_snapshot_repository.write().map_err(|_| Error::LockPoisoning)?;
+
+		let reverted_state = state_write_lock.revert_to(shard, state_hash)?;
+		drop(state_write_lock); // Drop the write lock as early as possible.
+
+		self.state_observer.queue_state_update(*shard, reverted_state.clone())?;
+		Ok(reverted_state)
+	}
+
 	fn reset(&self, state: Self::StateT, shard: &ShardIdentifier) -> Result<Self::HashType> {
 		let mut state_write_lock =
 			self.state_snapshot_repository.write().map_err(|_| Error::LockPoisoning)?;
@@ -187,6 +209,20 @@ mod tests {
 		assert_eq!((shard_id, reset_state), observer_updates[1]);
 	}
 
+	#[test]
+	fn revert_to_queues_observer_update() {
+		let shard_id = ShardIdentifier::default();
+		let state_observer = Arc::new(TestStateObserver::default());
+		let state_handler =
+			Arc::new(TestStateHandler::new(default_repository(&shard_id), state_observer.clone()));
+
+		let reverted_state = state_handler.revert_to(&shard_id, &TestHash::default()).unwrap();
+
+		let observer_updates = state_observer.queued_updates.read().unwrap().clone();
+		assert_eq!(1, observer_updates.len());
+		assert_eq!((shard_id, reverted_state), observer_updates[0]);
+	}
+
 	#[test]
 	fn load_initialized_works() {
 		let shard_id = ShardIdentifier::random();
@@ -36,6 +36,9 @@ pub trait VersionedStateAccess {
 	/// Load the latest version of the state.
 	fn load_latest(&self, shard_identifier: &ShardIdentifier) -> Result<Self::StateType>;
 
+	/// Get the hash of the latest version of the state.
+	fn latest_state_hash(&self, shard_identifier: &ShardIdentifier) -> Result<Self::HashType>;
+
 	/// Update the state, returning the hash of the state.
 	fn update(
 		&mut self,
@@ -195,6 +198,10 @@ where
 		self.file_io.load(shard_identifier, latest_snapshot_metadata.state_id)
 	}
 
+	fn latest_state_hash(&self, shard_identifier: &ShardIdentifier) -> Result<Self::HashType> {
+		Ok(self.get_latest_snapshot_metadata(shard_identifier)?.state_hash)
+	}
+
 	fn update(
 		&mut self,
 		shard_identifier: &ShardIdentifier,
@@ -375,6 +382,18 @@ mod tests {
 		assert_eq!(1, file_io.get_states_for_shard(&shard_id).unwrap().len());
 	}
 
+	#[test]
+	fn latest_state_hash_returns_hash_of_latest_update() {
+		let shard_id = ShardIdentifier::random();
+		let (_, mut state_snapshot_repository) = create_state_snapshot_repository(&[shard_id], 3);
+
+		let _ = state_snapshot_repository.update(&shard_id, 1u64).unwrap();
+		let latest_hash = state_snapshot_repository.update(&shard_id, 2u64).unwrap();
+
+		assert_eq!(latest_hash, state_snapshot_repository.latest_state_hash(&shard_id).unwrap());
+		assert!(state_snapshot_repository.latest_state_hash(&ShardIdentifier::random()).is_err());
+	}
+
 	#[test]
 	fn revert_to_removes_version_newer_than_target_hash() {
 		let shard_id = ShardIdentifier::random();
@@ -57,6 +57,13 @@ where
 			.ok_or(Error::StateNotFoundInRepository("".to_string()))
 	}
 
+	fn latest_state_hash(&self, shard_identifier: &ShardIdentifier) -> Result<Self::HashType> {
+		self.state_history
+			.get(shard_identifier)
+			.ok_or(Error::InvalidShard(*shard_identifier))?;
+		Ok(Hash::default())
+	}
+
 	fn update(
 		&mut self,
 		shard_identifier: &ShardIdentifier,
@@ -33,3 +33,21 @@ impl<S: StoragePrefix> TeerexStorageKeys for S {
 		storage_map_key(Self::prefix(), "EnclaveRegistry", &index, &StorageHasher::Blake2_128Concat)
 	}
 }
+
+pub struct SidechainPalletStorage;
+
+impl StoragePrefix for SidechainPalletStorage {
+	fn prefix() -> &'static str {
+		"Sidechain"
+	}
+}
+
+pub trait SidechainStorageKeys {
+	fn slot_duration() -> Vec<u8>;
+}
+
+impl SidechainStorageKeys for SidechainPalletStorage {
+	fn slot_duration() -> Vec<u8> {
+		storage_value_key(Self::prefix(), "SlotDuration")
+	}
+}
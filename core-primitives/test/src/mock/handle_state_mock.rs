@@ -38,11 +38,12 @@ use std::{collections::HashMap, format, vec::Vec};
 #[derive(Default)]
 pub struct HandleStateMock {
 	state_map: RwLock<HashMap<ShardIdentifier, StfState>>,
+	state_history: RwLock<HashMap<H256, StfState>>,
 }
 
 impl HandleStateMock {
 	pub fn from_shard(shard: ShardIdentifier) -> Result<Self> {
-		let state_handler = HandleStateMock { state_map: Default::default() };
+		let state_handler = HandleStateMock::default();
 		state_handler.initialize_shard(shard)?;
 		Ok(state_handler)
 	}
@@ -81,8 +82,26 @@ impl HandleState for HandleStateMock {
 		mut state_lock: RwLockWriteGuard<'_, Self::WriteLockPayload>,
 		shard: &ShardIdentifier,
 	) -> Result<Self::HashType> {
-		state_lock.insert(*shard, state.clone());
-		Ok(state.using_encoded(blake2_256).into())
+		let state_hash: H256 = state.using_encoded(blake2_256).into();
+		self.state_history.write().unwrap().insert(state_hash, state.clone());
+		state_lock.insert(*shard, state);
+		Ok(state_hash)
+	}
+
+	fn latest_state_hash(&self, shard: &ShardIdentifier) -> Result<Self::HashType> {
+		Ok(self.load(shard)?.using_encoded(blake2_256).into())
+	}
+
+	fn revert_to(&self, shard: &ShardIdentifier, state_hash: &Self::HashType) -> Result<StfState> {
+		let state = self
+			.state_history
+			.read()
+			.unwrap()
+			.get(state_hash)
+			.cloned()
+			.ok_or_else(|| Error::StateNotFoundInRepository(format!("{:?}", state_hash)))?;
+		self.state_map.write().unwrap().insert(*shard, state.clone());
+		Ok(state)
 	}
 
 	fn reset(&self, state: Self::StateT, shard: &ShardIdentifier) -> Result<Self::HashType> {
@@ -165,6 +184,21 @@ pub mod tests {
 		assert_eq!(*inserted_value, value.encode());
 	}
 
+	pub fn revert_to_restores_previous_state() {
+		let shard = ShardIdentifier::default();
+		let state_handler = HandleStateMock::from_shard(shard).unwrap();
+		let initial_state_hash = state_handler.latest_state_hash(&shard).unwrap();
+
+		let (lock, mut state) = state_handler.load_for_mutation(&shard).unwrap();
+		state.insert(b"my_key".to_vec(), b"my_value".to_vec());
+		state_handler.write_after_mutation(state, lock, &shard).unwrap();
+		assert_ne!(initial_state_hash, state_handler.latest_state_hash(&shard).unwrap());
+
+		state_handler.revert_to(&shard, &initial_state_hash).unwrap();
+
+		assert_eq!(initial_state_hash, state_handler.latest_state_hash(&shard).unwrap());
+	}
+
 	pub fn ensure_subsequent_state_loads_have_same_hash() {
 		let state_handler = HandleStateMock::default();
 		let shard = ShardIdentifier::default();
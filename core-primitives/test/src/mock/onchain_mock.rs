@@ -16,6 +16,12 @@
 
 */
 
+#[cfg(feature = "sgx")]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
 use codec::{Decode, Encode};
 use core::fmt::Debug;
 use itp_ocall_api::{
@@ -25,8 +31,8 @@ use itp_ocall_api::{
 use itp_storage::Error::StorageValueUnavailable;
 use itp_teerex_storage::{TeeRexStorage, TeerexStorageKeys};
 use itp_types::{
-	storage::StorageEntryVerified, BlockHash, Enclave, ShardIdentifier, WorkerRequest,
-	WorkerResponse,
+	storage::StorageEntryVerified, BlockHash, Enclave, PeerBlockRangeRequest, Request,
+	ShardIdentifier, WorkerRequest, WorkerResponse,
 };
 use sgx_types::{
 	sgx_epid_group_id_t, sgx_measurement_t, sgx_platform_info_t, sgx_quote_nonce_t,
@@ -36,12 +42,16 @@ use sgx_types::{
 use sp_core::H256;
 use sp_runtime::{traits::Header as HeaderTrait, AccountId32, OpaqueExtrinsic};
 use sp_std::prelude::*;
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 #[derive(Default, Clone, Debug)]
 pub struct OnchainMock {
 	inner: HashMap<Vec<u8>, Vec<u8>>,
 	mr_enclave: [u8; SGX_HASH_SIZE],
+	/// Shared among clones, so the operations proposed by any component using a clone are visible.
+	proposed_trusted_operations: Arc<RwLock<Vec<Request>>>,
+	/// Shared among clones, like the untrusted file system it mocks.
+	stored_trusted_operation_pool: Arc<RwLock<Option<Vec<u8>>>>,
 }
 
 impl OnchainMock {
@@ -81,6 +91,14 @@ impl OnchainMock {
 		self.inner.insert(key_with_header, value);
 	}
 
+	pub fn proposed_trusted_operations(&self) -> Vec<Request> {
+		self.proposed_trusted_operations.read().unwrap().clone()
+	}
+
+	pub fn stored_trusted_operation_pool(&self) -> Option<Vec<u8>> {
+		self.stored_trusted_operation_pool.read().unwrap().clone()
+	}
+
 	pub fn get_at_header<Header: HeaderTrait<Hash = H256>>(
 		&self,
 		header: &Header,
@@ -147,6 +165,43 @@ impl EnclaveSidechainOCallApi for OnchainMock {
 	) -> SgxResult<Vec<SignedSidechainBlock>> {
 		Ok(Vec::new())
 	}
+
+	fn fetch_sidechain_block_ranges_from_peers<SignedSidechainBlock: Decode>(
+		&self,
+		requests: Vec<PeerBlockRangeRequest>,
+		_shard_identifier: ShardIdentifier,
+	) -> SgxResult<Vec<Option<Vec<SignedSidechainBlock>>>> {
+		Ok(requests.iter().map(|_| None).collect())
+	}
+
+	fn propose_finality_votes<SignedFinalityVote: Encode>(
+		&self,
+		_signed_votes: Vec<SignedFinalityVote>,
+	) -> SgxResult<()> {
+		Ok(())
+	}
+
+	fn propose_trusted_operations(&self, trusted_operations: Vec<Request>) -> SgxResult<()> {
+		self.proposed_trusted_operations.write().unwrap().extend(trusted_operations);
+		Ok(())
+	}
+
+	fn finalize_sidechain_block(
+		&self,
+		_shard_identifier: ShardIdentifier,
+		_block_hash: BlockHash,
+	) -> SgxResult<()> {
+		Ok(())
+	}
+
+	fn store_trusted_operation_pool(&self, encrypted_pool: Vec<u8>) -> SgxResult<()> {
+		*self.stored_trusted_operation_pool.write().unwrap() = Some(encrypted_pool);
+		Ok(())
+	}
+
+	fn load_trusted_operation_pool(&self) -> SgxResult<Option<Vec<u8>>> {
+		Ok(self.stored_trusted_operation_pool.read().unwrap().clone())
+	}
 }
 
 impl EnclaveMetricsOCallApi for OnchainMock {
@@ -24,13 +24,19 @@ use std::sync::RwLock;
 use codec::{Decode, Encode};
 use core::marker::PhantomData;
 use itp_ocall_api::EnclaveSidechainOCallApi;
-use itp_types::{BlockHash, ShardIdentifier};
+use itp_types::{BlockHash, PalletString, PeerBlockRangeRequest, Request, ShardIdentifier};
 use sgx_types::{sgx_status_t, SgxResult};
-use std::vec::Vec;
+use std::{collections::HashMap, vec::Vec};
 
 pub struct SidechainOCallApiMock<SignedSidechainBlockType> {
 	fetch_from_peer_blocks: Option<Vec<SignedSidechainBlockType>>,
+	/// Blocks served by peer (url), together with the block number of the first block.
+	peer_block_ranges: HashMap<PalletString, (u64, Vec<SignedSidechainBlockType>)>,
 	number_of_fetch_calls: RwLock<usize>,
+	block_range_requests: RwLock<Vec<PeerBlockRangeRequest>>,
+	proposed_finality_votes: RwLock<Vec<Vec<u8>>>,
+	proposed_trusted_operations: RwLock<Vec<Request>>,
+	finalized_blocks: RwLock<Vec<(ShardIdentifier, BlockHash)>>,
 	_phantom: PhantomData<SignedSidechainBlockType>,
 }
 
@@ -43,16 +49,55 @@ where
 		self
 	}
 
+	/// Let the peer with `peer_url` serve `blocks`, the first one having `first_block_number`.
+	///
+	/// Block range requests to peers without any blocks fail.
+	pub fn with_peer_block_range(
+		mut self,
+		peer_url: PalletString,
+		first_block_number: u64,
+		blocks: Vec<SignedSidechainBlockType>,
+	) -> Self {
+		self.peer_block_ranges.insert(peer_url, (first_block_number, blocks));
+		self
+	}
+
 	pub fn number_of_fetch_calls(&self) -> usize {
 		*self.number_of_fetch_calls.read().unwrap()
 	}
+
+	pub fn block_range_requests(&self) -> Vec<PeerBlockRangeRequest> {
+		self.block_range_requests.read().unwrap().clone()
+	}
+
+	pub fn proposed_finality_votes<SignedFinalityVote: Decode>(&self) -> Vec<SignedFinalityVote> {
+		self.proposed_finality_votes
+			.read()
+			.unwrap()
+			.iter()
+			.map(|v| SignedFinalityVote::decode(&mut v.as_slice()).unwrap())
+			.collect()
+	}
+
+	pub fn proposed_trusted_operations(&self) -> Vec<Request> {
+		self.proposed_trusted_operations.read().unwrap().clone()
+	}
+
+	pub fn finalized_blocks(&self) -> Vec<(ShardIdentifier, BlockHash)> {
+		self.finalized_blocks.read().unwrap().clone()
+	}
 }
 
 impl<SignedSidechainBlockType> Default for SidechainOCallApiMock<SignedSidechainBlockType> {
 	fn default() -> Self {
 		SidechainOCallApiMock {
 			fetch_from_peer_blocks: None,
+			peer_block_ranges: HashMap::new(),
 			number_of_fetch_calls: RwLock::new(0),
+			block_range_requests: RwLock::new(Vec::new()),
+			proposed_finality_votes: RwLock::new(Vec::new()),
+			proposed_trusted_operations: RwLock::new(Vec::new()),
+			finalized_blocks: RwLock::new(Vec::new()),
 			_phantom: Default::default(),
 		}
 	}
@@ -65,7 +110,16 @@ where
 	fn clone(&self) -> Self {
 		SidechainOCallApiMock {
 			fetch_from_peer_blocks: self.fetch_from_peer_blocks.clone(),
+			peer_block_ranges: self.peer_block_ranges.clone(),
 			number_of_fetch_calls: RwLock::new(*self.number_of_fetch_calls.read().unwrap()),
+			block_range_requests: RwLock::new(self.block_range_requests.read().unwrap().clone()),
+			proposed_finality_votes: RwLock::new(
+				self.proposed_finality_votes.read().unwrap().clone(),
+			),
+			proposed_trusted_operations: RwLock::new(
+				self.proposed_trusted_operations.read().unwrap().clone(),
+			),
+			finalized_blocks: RwLock::new(self.finalized_blocks.read().unwrap().clone()),
 			_phantom: self._phantom,
 		}
 	}
@@ -107,4 +161,58 @@ where
 			None => Err(sgx_status_t::SGX_ERROR_UNEXPECTED),
 		}
 	}
+
+	fn fetch_sidechain_block_ranges_from_peers<SignedSidechainBlock: Decode>(
+		&self,
+		requests: Vec<PeerBlockRangeRequest>,
+		_shard_identifier: ShardIdentifier,
+	) -> SgxResult<Vec<Option<Vec<SignedSidechainBlock>>>> {
+		self.block_range_requests.write().unwrap().extend(requests.iter().cloned());
+
+		Ok(requests
+			.iter()
+			.map(|request| {
+				let (first_block_number, blocks) = self.peer_block_ranges.get(&request.peer_url)?;
+				Some(
+					blocks
+						.iter()
+						.skip(request.from_block_number.saturating_sub(*first_block_number) as usize)
+						.take(request.limit as usize)
+						.map(|b| SignedSidechainBlock::decode(&mut b.encode().as_slice()).unwrap())
+						.collect(),
+				)
+			})
+			.collect())
+	}
+
+	fn propose_finality_votes<SignedFinalityVote: Encode>(
+		&self,
+		signed_votes: Vec<SignedFinalityVote>,
+	) -> SgxResult<()> {
+		let mut proposed_finality_votes_lock = self.proposed_finality_votes.write().unwrap();
+		proposed_finality_votes_lock.extend(signed_votes.iter().map(|v| v.encode()));
+		Ok(())
+	}
+
+	fn propose_trusted_operations(&self, trusted_operations: Vec<Request>) -> SgxResult<()> {
+		self.proposed_trusted_operations.write().unwrap().extend(trusted_operations);
+		Ok(())
+	}
+
+	fn finalize_sidechain_block(
+		&self,
+		shard_identifier: ShardIdentifier,
+		block_hash: BlockHash,
+	) -> SgxResult<()> {
+		self.finalized_blocks.write().unwrap().push((shard_identifier, block_hash));
+		Ok(())
+	}
+
+	fn store_trusted_operation_pool(&self, _encrypted_pool: Vec<u8>) -> SgxResult<()> {
+		Ok(())
+	}
+
+	fn load_trusted_operation_pool(&self) -> SgxResult<Option<Vec<u8>>> {
+		Ok(None)
+	}
 }
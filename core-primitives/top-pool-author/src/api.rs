@@ -23,8 +23,11 @@ use crate::sgx_reexport_prelude::*;
 use crate::error;
 use codec::Encode;
 use ita_stf::{
-	Getter, ShardIdentifier, TrustedCallSigned, TrustedOperation as StfTrustedOperation,
+	AccountId, Getter, Index, ShardIdentifier, TrustedCallSigned,
+	TrustedOperation as StfTrustedOperation,
 };
+use itp_stf_interface::system_pallet::SystemPalletAccountInterface;
+use itp_stf_state_observer::traits::ObserveState;
 use itp_top_pool::{
 	pool::{ChainApi, ExtrinsicHash, NumberFor},
 	primitives::TrustedOperationSource,
@@ -34,45 +37,76 @@ use jsonrpc_core::futures::future::{ready, Future, Ready};
 use log::*;
 use sp_runtime::{
 	generic::BlockId,
-	traits::{Block as BlockT, Hash as HashT, Header as HeaderT},
+	traits::{Block as BlockT, Hash as HashT, Header as HeaderT, SaturatedConversion},
 	transaction_validity::{
-		TransactionValidity, TransactionValidityError, UnknownTransaction, ValidTransaction,
+		InvalidTransaction, TransactionPriority, TransactionValidity, TransactionValidityError,
+		UnknownTransaction, ValidTransaction,
 	},
 };
-use std::{boxed::Box, marker::PhantomData, pin::Pin, vec, vec::Vec};
+use std::{boxed::Box, marker::PhantomData, pin::Pin, sync::Arc, vec, vec::Vec};
 
 /// Future that resolves to account nonce.
 pub type Result<T> = core::result::Result<T, ()>;
 
+/// Priority of trusted operations without a tip. Trusted calls are prioritized by their tip on top.
+const BASE_PRIORITY: TransactionPriority = 1 << 20;
+
 /// The operation pool logic for full client.
-pub struct SidechainApi<Block> {
-	_marker: PhantomData<Block>,
+///
+/// Trusted calls of a sender depend on each other by nonce: a call requires the call with the
+/// previous nonce, unless its nonce is the current account nonce of the sender in the state.
+pub struct SidechainApi<Block, StateObserver, Stf> {
+	state_observer: Arc<StateObserver>,
+	_marker: PhantomData<(Block, Stf)>,
 }
 
-impl<Block> SidechainApi<Block> {
+impl<Block, StateObserver, Stf> SidechainApi<Block, StateObserver, Stf>
+where
+	StateObserver: ObserveState,
+	Stf: SystemPalletAccountInterface<StateObserver::StateType, AccountId>,
+	Stf::Index: Into<Index>,
+{
 	/// Create new operation pool logic.
-	pub fn new() -> Self {
-		SidechainApi { _marker: Default::default() }
+	pub fn new(state_observer: Arc<StateObserver>) -> Self {
+		SidechainApi { state_observer, _marker: Default::default() }
 	}
 
-	fn validate_trusted_call(trusted_call_signed: TrustedCallSigned) -> ValidTransaction {
-		let from = trusted_call_signed.call.sender_account();
-		let requires = vec![];
-		let provides = vec![(from, trusted_call_signed.nonce).encode()];
+	fn validate_trusted_call(
+		&self,
+		trusted_call_signed: TrustedCallSigned,
+		shard: &ShardIdentifier,
+	) -> TransactionValidity {
+		let from = trusted_call_signed.call.sender_account().clone();
+		let nonce = trusted_call_signed.nonce;
 
-		ValidTransaction { priority: 1 << 20, requires, provides, longevity: 64, propagate: true }
-	}
-}
+		let sender = from.clone();
+		let account_nonce: Index = self
+			.state_observer
+			.observe_state(shard, move |state| Stf::get_account_nonce(state, &sender).into())
+			.map_err(|e| {
+				debug!("Failed to observe the state of shard {:?}: {:?}", shard, e);
+				TransactionValidityError::Unknown(UnknownTransaction::CannotLookup)
+			})?;
 
-impl<Block> Default for SidechainApi<Block> {
-	fn default() -> Self {
-		Self::new()
+		if nonce < account_nonce {
+			return Err(TransactionValidityError::Invalid(InvalidTransaction::Stale))
+		}
+
+		let requires =
+			if nonce > account_nonce { vec![(&from, nonce - 1).encode()] } else { vec![] };
+		let provides = vec![(&from, nonce).encode()];
+		let priority = BASE_PRIORITY.saturating_add(trusted_call_signed.tip.saturated_into());
+
+		Ok(ValidTransaction { priority, requires, provides, longevity: 64, propagate: true })
 	}
 }
 
-impl<Block> ChainApi for SidechainApi<Block>
+impl<Block, StateObserver, Stf> ChainApi for SidechainApi<Block, StateObserver, Stf>
 where
 	Block: BlockT,
+	StateObserver: ObserveState + Send + Sync,
+	Stf: SystemPalletAccountInterface<StateObserver::StateType, AccountId> + Send + Sync,
+	Stf::Index: Into<Index>,
 {
 	type Block = Block;
 	type Error = error::Error;
@@ -84,28 +118,26 @@ where
 		&self,
 		_source: TrustedOperationSource,
 		uxt: StfTrustedOperation,
-		_shard: ShardIdentifier,
+		shard: ShardIdentifier,
 	) -> Self::ValidationFuture {
-		let operation = match uxt {
+		let validity = match uxt {
 			StfTrustedOperation::direct_call(signed_call) =>
-				Self::validate_trusted_call(signed_call),
+				self.validate_trusted_call(signed_call, &shard),
 			StfTrustedOperation::indirect_call(signed_call) =>
-				Self::validate_trusted_call(signed_call),
+				self.validate_trusted_call(signed_call, &shard),
 			StfTrustedOperation::get(getter) => match getter {
 				Getter::public(_) =>
-					return Box::pin(ready(Ok(Err(TransactionValidityError::Unknown(
-						UnknownTransaction::CannotLookup,
-					))))),
-				Getter::trusted(trusted_getter) => ValidTransaction {
-					priority: 1 << 20,
+					Err(TransactionValidityError::Unknown(UnknownTransaction::CannotLookup)),
+				Getter::trusted(trusted_getter) => Ok(ValidTransaction {
+					priority: BASE_PRIORITY,
 					requires: vec![],
 					provides: vec![trusted_getter.signature.encode()],
 					longevity: 64,
 					propagate: true,
-				},
+				}),
 			},
 		};
-		Box::pin(ready(Ok(Ok(operation))))
+		Box::pin(ready(Ok(validity)))
 	}
 
 	fn block_id_to_number(
@@ -143,20 +175,22 @@ where
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::test_fixtures::{NonceState, NonceStfMock};
 	use futures::executor;
 	use ita_stf::{KeyPair, PublicGetter, TrustedCall, TrustedOperation};
+	use itp_stf_state_observer::mock::ObserveStateMock;
 	use itp_types::Block as ParentchainBlock;
 	use sp_core::{ed25519, Pair};
 	use sp_keyring::AccountKeyring;
 
-	type TestChainApi = SidechainApi<ParentchainBlock>;
+	type TestChainApi = SidechainApi<ParentchainBlock, ObserveStateMock<NonceState>, NonceStfMock>;
 
 	type Seed = [u8; 32];
 	const TEST_SEED: Seed = *b"12345678901234567890123456789012";
 
 	#[test]
 	fn indirect_calls_are_valid() {
-		let chain_api = TestChainApi::default();
+		let chain_api = create_chain_api(0);
 		let operation = create_indirect_trusted_operation();
 
 		let validation = executor::block_on(chain_api.validate_transaction(
@@ -169,9 +203,51 @@ mod tests {
 		assert!(validation.is_ok());
 	}
 
+	#[test]
+	fn trusted_calls_are_prioritized_by_tip() {
+		let chain_api = create_chain_api(0);
+
+		let untipped_priority = validated_priority(&chain_api, create_direct_trusted_operation(0));
+		let tipped_priority = validated_priority(&chain_api, create_direct_trusted_operation(100));
+
+		assert_eq!(untipped_priority, BASE_PRIORITY);
+		assert_eq!(tipped_priority, BASE_PRIORITY + 100);
+	}
+
+	#[test]
+	fn trusted_call_requires_the_previous_nonce_of_its_sender() {
+		let chain_api = create_chain_api(0);
+
+		let validation = validate(&chain_api, create_direct_trusted_operation(0)).unwrap();
+
+		let (previous_nonce, nonce): (Index, Index) = (0, 1);
+		assert_eq!(validation.requires, vec![(sender(), previous_nonce).encode()]);
+		assert_eq!(validation.provides, vec![(sender(), nonce).encode()]);
+	}
+
+	#[test]
+	fn trusted_call_with_the_account_nonce_has_no_requirements() {
+		let chain_api = create_chain_api(1);
+
+		let validation = validate(&chain_api, create_direct_trusted_operation(0)).unwrap();
+
+		let nonce: Index = 1;
+		assert!(validation.requires.is_empty());
+		assert_eq!(validation.provides, vec![(sender(), nonce).encode()]);
+	}
+
+	#[test]
+	fn trusted_call_with_an_outdated_nonce_is_stale() {
+		let chain_api = create_chain_api(2);
+
+		let validation = validate(&chain_api, create_direct_trusted_operation(0));
+
+		assert_eq!(validation, Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)));
+	}
+
 	#[test]
 	fn public_getters_are_not_valid() {
-		let chain_api = TestChainApi::default();
+		let chain_api = create_chain_api(0);
 		let public_getter = TrustedOperation::get(Getter::public(PublicGetter::some_value));
 
 		let validation = executor::block_on(chain_api.validate_transaction(
@@ -194,6 +270,39 @@ mod tests {
 		TrustedOperation::indirect_call(trusted_call_signed)
 	}
 
+	fn create_direct_trusted_operation(tip: u128) -> TrustedOperation {
+		TrustedCall::balance_transfer(
+			AccountKeyring::Alice.public().into(),
+			AccountKeyring::Bob.public().into(),
+			1000u128,
+		)
+		.sign_with_tip(&KeyPair::Ed25519(signer()), 1, tip, &[1u8; 32], &ShardIdentifier::default())
+		.into_trusted_operation(true)
+	}
+
+	fn validated_priority(chain_api: &TestChainApi, operation: TrustedOperation) -> u64 {
+		validate(chain_api, operation).unwrap().priority
+	}
+
+	fn validate(chain_api: &TestChainApi, operation: TrustedOperation) -> TransactionValidity {
+		executor::block_on(chain_api.validate_transaction(
+			TrustedOperationSource::Local,
+			operation,
+			ShardIdentifier::default(),
+		))
+		.unwrap()
+	}
+
+	/// Chain api with a state in which the sender of the test operations has the given nonce.
+	fn create_chain_api(account_nonce: Index) -> TestChainApi {
+		let state = NonceState::from([(sender(), account_nonce)]);
+		TestChainApi::new(Arc::new(ObserveStateMock::new(state)))
+	}
+
+	fn sender() -> AccountId {
+		AccountKeyring::Alice.public().into()
+	}
+
 	fn signer() -> ed25519::Pair {
 		ed25519::Pair::from_seed(&TEST_SEED)
 	}
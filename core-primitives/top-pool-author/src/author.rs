@@ -21,15 +21,20 @@ use crate::sgx_reexport_prelude::*;
 use crate::{
 	client_error::Error as ClientError,
 	error::{Error as StateRpcError, Result},
+	sender_limits::{ClientBanList, RpcClient, SenderLimits, SubmissionSource},
 	top_filter::Filter,
-	traits::{AuthorApi, OnBlockImported},
+	traits::{
+		AuthorApi, EvictStaleOperations, OnBlockImported, PendingOperationsSnapshot,
+		SnapshotTrustedOperations,
+	},
 };
 use codec::{Decode, Encode};
-use ita_stf::{hash, Getter, TrustedOperation};
+use ita_stf::{hash, AccountId, Getter, TrustedCallSigned, TrustedOperation};
 use itp_enclave_metrics::EnclaveMetric;
-use itp_ocall_api::EnclaveMetricsOCallApi;
+use itp_ocall_api::{EnclaveAttestationOCallApi, EnclaveMetricsOCallApi, EnclaveSidechainOCallApi};
 use itp_sgx_crypto::{key_repository::AccessKey, ShieldingCryptoDecrypt};
 use itp_stf_state_handler::query_shard_state::QueryShardState;
+use itp_time_utils::now_as_u64;
 use itp_top_pool::{
 	error::{Error as PoolError, IntoPoolError},
 	primitives::{
@@ -37,14 +42,18 @@ use itp_top_pool::{
 		TxHash,
 	},
 };
-use itp_types::{BlockHash as SidechainBlockHash, ShardIdentifier};
+use itp_types::{
+	BlockHash as SidechainBlockHash, Request, ShardIdentifier, TrustedOperationPoolStatus,
+	TrustedOperationStatus,
+};
+use its_primitives::traits::NotifyFinalizedBlocks;
 use jsonrpc_core::{
 	futures::future::{ready, TryFutureExt},
 	Error as RpcError,
 };
 use log::*;
 use sp_runtime::generic;
-use std::{boxed::Box, sync::Arc, vec::Vec};
+use std::{boxed::Box, collections::HashMap, sync::Arc, vec::Vec};
 
 /// Define type of TOP filter that is used in the Author
 #[cfg(feature = "sidechain")]
@@ -80,6 +89,8 @@ where
 	state_facade: Arc<StateFacade>,
 	shielding_key_repo: Arc<ShieldingKeyRepository>,
 	ocall_api: Arc<OCallApi>,
+	sender_limits: SenderLimits,
+	client_ban_list: ClientBanList,
 }
 
 impl<TopPool, TopFilter, StateFacade, ShieldingKeyRepository, OCallApi>
@@ -90,7 +101,12 @@ where
 	StateFacade: QueryShardState,
 	ShieldingKeyRepository: AccessKey,
 	<ShieldingKeyRepository as AccessKey>::KeyType: ShieldingCryptoDecrypt,
-	OCallApi: EnclaveMetricsOCallApi + Send + Sync + 'static,
+	OCallApi: EnclaveMetricsOCallApi
+		+ EnclaveSidechainOCallApi
+		+ EnclaveAttestationOCallApi
+		+ Send
+		+ Sync
+		+ 'static,
 {
 	/// Create new instance of Authoring API.
 	pub fn new(
@@ -100,13 +116,39 @@ where
 		encryption_key: Arc<ShieldingKeyRepository>,
 		ocall_api: Arc<OCallApi>,
 	) -> Self {
-		Author { top_pool, top_filter, state_facade, shielding_key_repo: encryption_key, ocall_api }
+		Author {
+			top_pool,
+			top_filter,
+			state_facade,
+			shielding_key_repo: encryption_key,
+			ocall_api,
+			sender_limits: SenderLimits::default(),
+			client_ban_list: ClientBanList::default(),
+		}
+	}
+
+	/// Set the limits a single sender of trusted calls and a single RPC connection are subject to.
+	pub fn with_sender_limits(mut self, sender_limits: SenderLimits) -> Self {
+		self.sender_limits = sender_limits;
+		self
 	}
 }
 
 enum TopSubmissionMode {
 	Submit,
-	SubmitWatch,
+	SubmitWatch(SubmissionSource),
+	/// Import an operation gossiped by a peer validateer.
+	Import,
+}
+
+impl TopSubmissionMode {
+	fn source(&self) -> SubmissionSource {
+		match self {
+			TopSubmissionMode::Submit => SubmissionSource::Local,
+			TopSubmissionMode::SubmitWatch(source) => *source,
+			TopSubmissionMode::Import => SubmissionSource::Peer,
+		}
+	}
 }
 
 impl<TopPool, TopFilter, StateFacade, ShieldingKeyRepository, OCallApi>
@@ -117,7 +159,12 @@ where
 	StateFacade: QueryShardState,
 	ShieldingKeyRepository: AccessKey,
 	<ShieldingKeyRepository as AccessKey>::KeyType: ShieldingCryptoDecrypt,
-	OCallApi: EnclaveMetricsOCallApi + Send + Sync + 'static,
+	OCallApi: EnclaveMetricsOCallApi
+		+ EnclaveSidechainOCallApi
+		+ EnclaveAttestationOCallApi
+		+ Send
+		+ Sync
+		+ 'static,
 {
 	fn process_top(
 		&self,
@@ -125,14 +172,9 @@ where
 		shard: ShardIdentifier,
 		submission_mode: TopSubmissionMode,
 	) -> PoolFuture<TxHash<TopPool>, RpcError> {
-		// check if shard exists
-		match self.state_facade.shard_exists(&shard) {
-			Err(_) => return Box::pin(ready(Err(ClientError::InvalidShard.into()))),
-			Ok(shard_exists) =>
-				if !shard_exists {
-					return Box::pin(ready(Err(ClientError::InvalidShard.into())))
-				},
-		};
+		if let Err(e) = self.check_shard_exists(&shard) {
+			return Box::pin(ready(Err(e.into())))
+		}
 
 		// decrypt call
 		let shielding_key = match self.shielding_key_repo.retrieve_key() {
@@ -149,10 +191,10 @@ where
 			Err(_) => return Box::pin(ready(Err(ClientError::BadFormat.into()))),
 		};
 
-		// apply top filter - return error if this specific type of trusted operation
-		// is not allowed by the filter
-		if !self.top_filter.filter(&trusted_operation) {
-			return Box::pin(ready(Err(ClientError::UnsupportedOperation.into())))
+		if let Err(e) =
+			self.check_trusted_operation(&trusted_operation, shard, submission_mode.source())
+		{
+			return Box::pin(ready(Err(e.into())))
 		}
 
 		//let best_block_hash = self.client.info().best_hash;
@@ -180,8 +222,13 @@ where
 			);
 		}
 
-		match submission_mode {
-			TopSubmissionMode::Submit => Box::pin(
+		// Direct calls are gossiped to the peer validateers, so whoever holds the next slot can
+		// include them. Operations gossiped by a peer are not gossiped any further.
+		let gossip_operation = matches!(trusted_operation, TrustedOperation::direct_call(_))
+			&& !matches!(submission_mode, TopSubmissionMode::Import);
+
+		let submission_future: PoolFuture<TxHash<TopPool>, RpcError> = match submission_mode {
+			TopSubmissionMode::Submit | TopSubmissionMode::Import => Box::pin(
 				self.top_pool
 					.submit_one(
 						&generic::BlockId::hash(best_block_hash),
@@ -192,7 +239,7 @@ where
 					.map_err(map_top_error::<TopPool>),
 			),
 
-			TopSubmissionMode::SubmitWatch => Box::pin(
+			TopSubmissionMode::SubmitWatch(_) => Box::pin(
 				self.top_pool
 					.submit_and_watch(
 						&generic::BlockId::hash(best_block_hash),
@@ -202,9 +249,113 @@ where
 					)
 					.map_err(map_top_error::<TopPool>),
 			),
+		};
+
+		if !gossip_operation {
+			return submission_future
+		}
+
+		// Only gossip operations that were accepted by our own pool.
+		let top_pool = self.top_pool.clone();
+		let ocall_api = self.ocall_api.clone();
+		let encrypted_operation = Request { shard, cyphertext: ext };
+		Box::pin(submission_future.map_ok(move |hash| {
+			gossip_trusted_operation(
+				top_pool.as_ref(),
+				ocall_api.as_ref(),
+				hash.clone(),
+				encrypted_operation,
+			);
+			hash
+		}))
+	}
+
+	fn check_shard_exists(&self, shard: &ShardIdentifier) -> core::result::Result<(), ClientError> {
+		match self.state_facade.shard_exists(shard) {
+			Ok(true) => Ok(()),
+			Ok(false) | Err(_) => Err(ClientError::InvalidShard),
 		}
 	}
 
+	fn check_trusted_operation(
+		&self,
+		trusted_operation: &TrustedOperation,
+		shard: ShardIdentifier,
+		source: SubmissionSource,
+	) -> core::result::Result<(), ClientError> {
+		// apply top filter - return error if this specific type of trusted operation
+		// is not allowed by the filter
+		if !self.top_filter.filter(trusted_operation) {
+			return Err(ClientError::UnsupportedOperation)
+		}
+
+		if let Some(trusted_call_signed) = trusted_operation.to_call() {
+			self.check_sender_limits(trusted_call_signed, shard, source)?;
+		}
+		Ok(())
+	}
+
+	/// Rejects trusted calls submitted by a banned RPC client, with an invalid signature,
+	/// or of senders that already have too many trusted calls in the future queue.
+	///
+	/// Signature failures are recorded against the RPC client, never against the claimed
+	/// sender, and the limits of the sender only apply once the signature is verified.
+	fn check_sender_limits(
+		&self,
+		trusted_call_signed: &TrustedCallSigned,
+		shard: ShardIdentifier,
+		source: SubmissionSource,
+	) -> core::result::Result<(), ClientError> {
+		let now = now_as_u64();
+		let client = source.client();
+
+		if let Some(client) = client {
+			if self.client_ban_list.is_banned(&client.address, now) {
+				debug!("Rejecting trusted call submitted by banned client {}", client.address);
+				return Err(ClientError::ConnectionTemporarilyBanned)
+			}
+		}
+
+		let mrenclave = self.ocall_api.get_mrenclave_of_self().map_err(|e| {
+			error!("Failed to get mrenclave for the signature check: {:?}", e);
+			ClientError::Verification
+		})?;
+		if !trusted_call_signed.verify_signature(&mrenclave.m, &shard) {
+			if let Some(client) = client {
+				if self.client_ban_list.record_signature_failure(&client, now, &self.sender_limits)
+				{
+					warn!(
+						"Temporarily banned client {} for repeatedly invalid signatures",
+						client.address
+					);
+				}
+			}
+			return Err(ClientError::BadSignature)
+		}
+		if let Some(client) = client {
+			self.client_ban_list.record_valid_signature(&client);
+		}
+
+		let sender = trusted_call_signed.call.sender_account();
+		let (future_operations, future_bytes) = self
+			.top_pool
+			.futures(shard)
+			.iter()
+			.filter(|o| o.data().to_call().map_or(false, |c| c.call.sender_account() == sender))
+			.fold((0usize, 0usize), |(count, bytes), o| {
+				(count + 1, bytes + o.data().encoded_size())
+			});
+
+		if future_operations >= self.sender_limits.max_future_operations {
+			return Err(ClientError::TooManyFutureOperations)
+		}
+		if future_bytes + trusted_call_signed.encoded_size() > self.sender_limits.max_pending_bytes
+		{
+			return Err(ClientError::TooManyPendingBytes)
+		}
+		Ok(())
+	}
+
 	fn remove_top(
 		&self,
 		bytes_or_hash: hash::TrustedOperationOrHash<TxHash<TopPool>>,
@@ -244,6 +395,26 @@ where
 	}
 }
 
+/// Gossip an encrypted trusted operation to the peer validateers and mark it as broadcast.
+fn gossip_trusted_operation<TopPool, OCallApi>(
+	top_pool: &TopPool,
+	ocall_api: &OCallApi,
+	hash: TxHash<TopPool>,
+	encrypted_operation: Request,
+) where
+	TopPool: TrustedOperationPool,
+	OCallApi: EnclaveSidechainOCallApi,
+{
+	match ocall_api.propose_trusted_operations(vec![encrypted_operation]) {
+		Ok(_) => {
+			debug!("Gossiped trusted operation {:?} to peers", hash);
+			top_pool
+				.on_broadcasted(vec![(hash, Vec::new())].into_iter().collect::<HashMap<_, _>>());
+		},
+		Err(e) => warn!("Failed to gossip trusted operation {:?} to peers: {:?}", hash, e),
+	}
+}
+
 fn map_top_error<P: TrustedOperationPool>(error: P::Error) -> RpcError {
 	StateRpcError::PoolError(
 		error
@@ -263,7 +434,12 @@ where
 	StateFacade: QueryShardState,
 	ShieldingKeyRepository: AccessKey,
 	<ShieldingKeyRepository as AccessKey>::KeyType: ShieldingCryptoDecrypt,
-	OCallApi: EnclaveMetricsOCallApi + Send + Sync + 'static,
+	OCallApi: EnclaveMetricsOCallApi
+		+ EnclaveSidechainOCallApi
+		+ EnclaveAttestationOCallApi
+		+ Send
+		+ Sync
+		+ 'static,
 {
 	fn submit_top(
 		&self,
@@ -273,6 +449,14 @@ where
 		self.process_top(ext, shard, TopSubmissionMode::Submit)
 	}
 
+	fn import_top(
+		&self,
+		ext: Vec<u8>,
+		shard: ShardIdentifier,
+	) -> PoolFuture<TxHash<TopPool>, RpcError> {
+		self.process_top(ext, shard, TopSubmissionMode::Import)
+	}
+
 	/// Get hash of TrustedOperation
 	fn hash_of(&self, xt: &TrustedOperation) -> TxHash<TopPool> {
 		self.top_pool.hash_of(xt)
@@ -292,6 +476,7 @@ where
 	}
 
 	fn get_pending_trusted_calls(&self, shard: ShardIdentifier) -> Vec<TrustedOperation> {
+		// The pool yields the calls of a sender in nonce order, since each requires its predecessor.
 		self.top_pool
 			.ready(shard)
 			.map(|o| o.data().clone())
@@ -307,6 +492,53 @@ where
 		self.top_pool.shards()
 	}
 
+	fn get_pool_status(&self, shard: ShardIdentifier) -> TrustedOperationPoolStatus {
+		let status = self.top_pool.status(shard);
+		TrustedOperationPoolStatus {
+			ready: status.ready as u64,
+			ready_bytes: status.ready_bytes as u64,
+			future: status.future as u64,
+			future_bytes: status.future_bytes as u64,
+		}
+	}
+
+	fn get_top_status(
+		&self,
+		hash: &TxHash<TopPool>,
+		shard: ShardIdentifier,
+	) -> Option<TrustedOperationStatus> {
+		if self.top_pool.ready_transaction(hash, shard).is_some() {
+			return Some(TrustedOperationStatus::Ready)
+		}
+		self.top_pool
+			.futures(shard)
+			.iter()
+			.any(|o| o.hash() == hash)
+			.then(|| TrustedOperationStatus::Future)
+	}
+
+	fn cancel_top(
+		&self,
+		hash: TxHash<TopPool>,
+		shard: ShardIdentifier,
+		sender: &AccountId,
+	) -> Result<TxHash<TopPool>> {
+		let pending_operation = self
+			.top_pool
+			.ready_transaction(&hash, shard)
+			.or_else(|| self.top_pool.futures(shard).into_iter().find(|o| o.hash() == &hash))
+			.ok_or(ClientError::OperationNotPending)?;
+
+		// Getters have no sender to authorize the cancellation.
+		match pending_operation.data().to_call() {
+			Some(trusted_call_signed) if trusted_call_signed.call.sender_account() == sender => (),
+			_ => return Err(ClientError::NotSenderOfOperation.into()),
+		}
+
+		debug!("Cancelling trusted operation {:?} on request of its sender", hash);
+		self.remove_top(hash::TrustedOperationOrHash::Hash(hash), shard, false)
+	}
+
 	fn remove_calls_from_pool(
 		&self,
 		shard: ShardIdentifier,
@@ -328,8 +560,74 @@ where
 		&self,
 		ext: Vec<u8>,
 		shard: ShardIdentifier,
+		source: SubmissionSource,
 	) -> PoolFuture<TxHash<TopPool>, RpcError> {
-		self.process_top(ext, shard, TopSubmissionMode::SubmitWatch)
+		self.process_top(ext, shard, TopSubmissionMode::SubmitWatch(source))
+	}
+
+	fn on_connection_closed(&self, client: RpcClient) {
+		self.client_ban_list.remove_connection(&client);
+	}
+}
+
+impl<TopPool, TopFilter, StateFacade, ShieldingKeyRepository, OCallApi>
+	SnapshotTrustedOperations<TxHash<TopPool>>
+	for Author<TopPool, TopFilter, StateFacade, ShieldingKeyRepository, OCallApi>
+where
+	TopPool: TrustedOperationPool + Sync + Send + 'static,
+	TopFilter: Filter<Value = TrustedOperation>,
+	StateFacade: QueryShardState,
+	ShieldingKeyRepository: AccessKey,
+	<ShieldingKeyRepository as AccessKey>::KeyType: ShieldingCryptoDecrypt,
+	OCallApi: EnclaveMetricsOCallApi
+		+ EnclaveSidechainOCallApi
+		+ EnclaveAttestationOCallApi
+		+ Send
+		+ Sync
+		+ 'static,
+{
+	fn snapshot_pending_operations(&self) -> PendingOperationsSnapshot {
+		self.top_pool
+			.shards()
+			.into_iter()
+			.map(|shard| {
+				let trusted_operations = self
+					.top_pool
+					.ready(shard)
+					.chain(self.top_pool.futures(shard))
+					.filter(|o| o.data().to_call().is_some())
+					.map(|o| o.data().clone())
+					.collect();
+				(shard, trusted_operations)
+			})
+			.collect()
+	}
+
+	fn restore_top(
+		&self,
+		trusted_operation: TrustedOperation,
+		shard: ShardIdentifier,
+	) -> PoolFuture<TxHash<TopPool>, RpcError> {
+		if let Err(e) = self.check_shard_exists(&shard).and_then(|_| {
+			self.check_trusted_operation(&trusted_operation, shard, SubmissionSource::Local)
+		}) {
+			return Box::pin(ready(Err(e.into())))
+		}
+
+		if let Err(e) = self.ocall_api.update_metric(EnclaveMetric::TopPoolSizeIncrement) {
+			warn!("Failed to update metric for top pool size: {:?}", e);
+		}
+
+		Box::pin(
+			self.top_pool
+				.submit_one(
+					&generic::BlockId::hash(Default::default()),
+					TX_SOURCE,
+					trusted_operation,
+					shard,
+				)
+				.map_err(map_top_error::<TopPool>),
+		)
 	}
 }
 
@@ -341,11 +639,101 @@ where
 	StateFacade: QueryShardState,
 	ShieldingKeyRepository: AccessKey,
 	<ShieldingKeyRepository as AccessKey>::KeyType: ShieldingCryptoDecrypt,
-	OCallApi: EnclaveMetricsOCallApi + Send + Sync + 'static,
+	OCallApi: EnclaveMetricsOCallApi + EnclaveSidechainOCallApi + Send + Sync + 'static,
 {
 	type Hash = <TopPool as TrustedOperationPool>::Hash;
 
 	fn on_block_imported(&self, hashes: &[Self::Hash], block_hash: SidechainBlockHash) {
 		self.top_pool.on_block_imported(hashes, block_hash)
 	}
+
+	fn on_block_retracted(&self, block_hash: SidechainBlockHash) {
+		let re_added_hashes = self.top_pool.on_block_retracted(block_hash);
+		debug!(
+			"Re-added {} trusted operations of retracted block {:?} to the pool",
+			re_added_hashes.len(),
+			block_hash
+		);
+
+		// Update metric
+		for _ in re_added_hashes {
+			if let Err(e) = self.ocall_api.update_metric(EnclaveMetric::TopPoolSizeIncrement) {
+				warn!("Failed to update metric for top pool size: {:?}", e);
+			}
+		}
+	}
+}
+
+impl<TopPool, TopFilter, StateFacade, ShieldingKeyRepository, OCallApi> EvictStaleOperations
+	for Author<TopPool, TopFilter, StateFacade, ShieldingKeyRepository, OCallApi>
+where
+	TopPool: TrustedOperationPool + Sync + Send + 'static,
+	TopFilter: Filter<Value = TrustedOperation>,
+	StateFacade: QueryShardState,
+	ShieldingKeyRepository: AccessKey,
+	<ShieldingKeyRepository as AccessKey>::KeyType: ShieldingCryptoDecrypt,
+	OCallApi: EnclaveMetricsOCallApi + EnclaveSidechainOCallApi + Send + Sync + 'static,
+{
+	fn evict_expired_operations(&self, shard: ShardIdentifier, block_number: u64) -> usize {
+		let number_of_evicted = self.top_pool.remove_stale(block_number, shard).len();
+		if number_of_evicted > 0 {
+			debug!(
+				"Evicted {} trusted operations beyond their longevity at block {}",
+				number_of_evicted, block_number
+			);
+			update_evicted_operations_metric(self.ocall_api.as_ref(), number_of_evicted);
+		}
+		number_of_evicted
+	}
+
+	fn evict_outdated_calls(
+		&self,
+		shard: ShardIdentifier,
+		is_outdated: &mut dyn FnMut(&TrustedCallSigned) -> bool,
+	) -> usize {
+		let outdated_hashes: Vec<_> = self
+			.top_pool
+			.ready(shard)
+			.chain(self.top_pool.futures(shard))
+			.filter(|o| match o.data().to_call() {
+				Some(trusted_call_signed) => is_outdated(trusted_call_signed),
+				None => false,
+			})
+			.map(|o| o.hash().clone())
+			.collect();
+
+		let number_of_evicted = self.top_pool.remove_invalid(&outdated_hashes, shard, false).len();
+		if number_of_evicted > 0 {
+			debug!("Evicted {} trusted operations with an outdated nonce", number_of_evicted);
+			update_evicted_operations_metric(self.ocall_api.as_ref(), number_of_evicted);
+		}
+		number_of_evicted
+	}
+}
+
+fn update_evicted_operations_metric<OCallApi: EnclaveMetricsOCallApi>(
+	ocall_api: &OCallApi,
+	number_of_evicted: usize,
+) {
+	if let Err(e) =
+		ocall_api.update_metric(EnclaveMetric::TopPoolOperationsEvicted(number_of_evicted as u64))
+	{
+		warn!("Failed to update metric for evicted trusted operations: {:?}", e);
+	}
+}
+
+impl<TopPool, TopFilter, StateFacade, ShieldingKeyRepository, OCallApi>
+	NotifyFinalizedBlocks<ShardIdentifier>
+	for Author<TopPool, TopFilter, StateFacade, ShieldingKeyRepository, OCallApi>
+where
+	TopPool: TrustedOperationPool + Sync + Send + 'static,
+	TopFilter: Filter<Value = TrustedOperation>,
+	StateFacade: QueryShardState,
+	ShieldingKeyRepository: AccessKey,
+	<ShieldingKeyRepository as AccessKey>::KeyType: ShieldingCryptoDecrypt,
+	OCallApi: EnclaveMetricsOCallApi + EnclaveSidechainOCallApi + Send + Sync + 'static,
+{
+	fn notify_finalized_block(&self, _shard: &ShardIdentifier, block_hash: &SidechainBlockHash) {
+		self.top_pool.on_block_finalized(*block_hash)
+	}
 }
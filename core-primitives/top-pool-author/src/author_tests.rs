@@ -17,32 +17,42 @@
 
 use crate::{
 	author::Author,
+	client_error::Error as ClientError,
+	error::Error as StateRpcError,
+	sender_limits::{RpcClient, SenderLimits, SubmissionSource},
 	test_fixtures::{
-		create_indirect_trusted_operation, shard_id, trusted_call_signed, trusted_getter_signed,
+		alice_pair, bob_pair, create_direct_trusted_operation,
+		create_direct_trusted_operation_with_invalid_signature, create_indirect_trusted_operation,
+		mr_enclave, shard_id, trusted_call_signed, trusted_getter_signed,
 	},
-	test_utils::submit_operation_to_top_pool,
+	test_utils::{submit_operation_to_top_pool, submit_operation_to_top_pool_from},
 	top_filter::{AllowAllTopsFilter, Filter, GettersOnlyFilter},
-	traits::AuthorApi,
+	traits::{AuthorApi, EvictStaleOperations},
 };
 use codec::{Decode, Encode};
 use ita_stf::TrustedOperation;
 use itp_sgx_crypto::{mocks::KeyRepositoryMock, ShieldingCryptoDecrypt, ShieldingCryptoEncrypt};
 use itp_stf_state_handler::handle_state::HandleState;
 use itp_test::mock::{
-	handle_state_mock::HandleStateMock, metrics_ocall_mock::MetricsOCallMock,
+	handle_state_mock::HandleStateMock, onchain_mock::OnchainMock,
 	shielding_crypto_mock::ShieldingCryptoMock,
 };
 use itp_top_pool::mocks::trusted_operation_pool_mock::TrustedOperationPoolMock;
+use itp_types::{AccountId, TrustedOperationStatus};
+use jsonrpc_core::{futures::executor, Error as RpcError};
 use sgx_crypto_helper::{rsa3072::Rsa3072KeyPair, RsaKeyPair};
-use sp_core::H256;
-use std::sync::Arc;
+use sp_core::{Pair, H256};
+use std::{
+	net::{IpAddr, Ipv4Addr},
+	sync::Arc,
+};
 
 type TestAuthor<Filter> = Author<
 	TrustedOperationPoolMock,
 	Filter,
 	HandleStateMock,
 	KeyRepositoryMock<ShieldingCryptoMock>,
-	MetricsOCallMock,
+	OnchainMock,
 >;
 
 #[test]
@@ -123,8 +133,301 @@ fn submitting_indirect_call_works() {
 	assert_eq!(1, author.get_pending_trusted_calls(shard_id()).len());
 }
 
+#[test]
+fn submitting_direct_call_gossips_it_to_peers() {
+	let ocall_api = OnchainMock::default();
+	let (author, top_pool, shielding_key) =
+		create_author_with_filter_and_ocall_api(AllowAllTopsFilter, ocall_api.clone());
+	let trusted_operation = TrustedOperation::direct_call(trusted_call_signed());
+
+	let top_hash =
+		submit_operation_to_top_pool(&author, &trusted_operation, &shielding_key, shard_id())
+			.unwrap();
+
+	let gossiped_operations = ocall_api.proposed_trusted_operations();
+	assert_eq!(1, gossiped_operations.len());
+	assert_eq!(shard_id(), gossiped_operations[0].shard);
+	assert_eq!(trusted_operation, decrypt_top(&shielding_key, &gossiped_operations[0].cyphertext));
+	assert_eq!(vec![top_hash], top_pool.get_broadcasted_transactions());
+}
+
+#[test]
+fn submitting_getter_does_not_gossip_it() {
+	let ocall_api = OnchainMock::default();
+	let (author, top_pool, shielding_key) =
+		create_author_with_filter_and_ocall_api(AllowAllTopsFilter, ocall_api.clone());
+	let top = TrustedOperation::from(trusted_getter_signed());
+
+	let _ = submit_operation_to_top_pool(&author, &top, &shielding_key, shard_id()).unwrap();
+
+	assert!(ocall_api.proposed_trusted_operations().is_empty());
+	assert!(top_pool.get_broadcasted_transactions().is_empty());
+}
+
+#[test]
+fn importing_gossiped_direct_call_does_not_gossip_it_again() {
+	let ocall_api = OnchainMock::default();
+	let (author, top_pool, shielding_key) =
+		create_author_with_filter_and_ocall_api(AllowAllTopsFilter, ocall_api.clone());
+	let trusted_operation = TrustedOperation::direct_call(trusted_call_signed());
+	let encrypted_operation = shielding_key.encrypt(&trusted_operation.encode()).unwrap();
+
+	let import_result = executor::block_on(author.import_top(encrypted_operation, shard_id()));
+
+	assert!(import_result.is_ok());
+	assert_eq!(1, author.get_pending_trusted_calls(shard_id()).len());
+	assert!(ocall_api.proposed_trusted_operations().is_empty());
+	assert!(top_pool.get_broadcasted_transactions().is_empty());
+}
+
+#[test]
+fn submitting_call_with_invalid_signature_returns_error() {
+	let (author, top_pool, shielding_key) = create_author_with_filter(AllowAllTopsFilter);
+	let trusted_operation = create_direct_trusted_operation_with_invalid_signature(&alice_pair());
+
+	let submit_response =
+		submit_operation_to_top_pool(&author, &trusted_operation, &shielding_key, shard_id());
+
+	assert_rpc_error(submit_response, ClientError::BadSignature);
+	assert!(top_pool.get_last_submitted_transactions().is_empty());
+}
+
+#[test]
+fn client_is_banned_after_repeated_invalid_signatures() {
+	let (author, top_pool, shielding_key) = create_author_with_filter(AllowAllTopsFilter);
+	let author =
+		author.with_sender_limits(SenderLimits { max_signature_failures: 2, ..Default::default() });
+	let connection = rpc_source(10, 1);
+	let invalid_operation = create_direct_trusted_operation_with_invalid_signature(&alice_pair());
+
+	for _ in 0..2 {
+		let submit_response = submit_operation_to_top_pool_from(
+			&author,
+			&invalid_operation,
+			&shielding_key,
+			shard_id(),
+			connection,
+		);
+		assert_rpc_error(submit_response, ClientError::BadSignature);
+	}
+
+	// Reconnecting does not lift the ban of the client.
+	let valid_operation = create_direct_trusted_operation(&bob_pair(), 0);
+	let submit_response = submit_operation_to_top_pool_from(
+		&author,
+		&valid_operation,
+		&shielding_key,
+		shard_id(),
+		rpc_source(12, 1),
+	);
+	assert_rpc_error(submit_response, ClientError::ConnectionTemporarilyBanned);
+
+	// The claimed sender of the invalid calls is not banned, its calls are accepted from other
+	// clients.
+	let alice_operation = create_direct_trusted_operation(&alice_pair(), 0);
+	submit_operation_to_top_pool_from(
+		&author,
+		&alice_operation,
+		&shielding_key,
+		shard_id(),
+		rpc_source(11, 2),
+	)
+	.unwrap();
+	assert_eq!(1, top_pool.get_last_submitted_transactions()[&shard_id()].xts.len());
+}
+
+#[test]
+fn invalid_signatures_of_peers_do_not_lead_to_a_ban() {
+	let (author, _top_pool, shielding_key) = create_author_with_filter(AllowAllTopsFilter);
+	let author =
+		author.with_sender_limits(SenderLimits { max_signature_failures: 1, ..Default::default() });
+	let invalid_operation = create_direct_trusted_operation_with_invalid_signature(&alice_pair());
+
+	for _ in 0..2 {
+		let encrypted_operation = shielding_key.encrypt(&invalid_operation.encode()).unwrap();
+		let import_result = executor::block_on(author.import_top(encrypted_operation, shard_id()));
+		assert_rpc_error(import_result, ClientError::BadSignature);
+	}
+}
+
+#[test]
+fn submitting_more_than_max_future_operations_of_a_sender_returns_error() {
+	let (author, top_pool, shielding_key) = create_author_with_filter(AllowAllTopsFilter);
+	let author =
+		author.with_sender_limits(SenderLimits { max_future_operations: 2, ..Default::default() });
+
+	for nonce in 1..3 {
+		top_pool.add_future_transaction(
+			create_direct_trusted_operation(&alice_pair(), nonce),
+			shard_id(),
+		);
+	}
+
+	let trusted_operation = create_direct_trusted_operation(&alice_pair(), 3);
+	let submit_response =
+		submit_operation_to_top_pool(&author, &trusted_operation, &shielding_key, shard_id());
+	assert_rpc_error(submit_response, ClientError::TooManyFutureOperations);
+
+	let other_sender_operation = create_direct_trusted_operation(&bob_pair(), 0);
+	assert!(submit_operation_to_top_pool(
+		&author,
+		&other_sender_operation,
+		&shielding_key,
+		shard_id()
+	)
+	.is_ok());
+}
+
+#[test]
+fn ready_operations_do_not_count_towards_the_future_operations_limit() {
+	let (author, _top_pool, shielding_key) = create_author_with_filter(AllowAllTopsFilter);
+	let author =
+		author.with_sender_limits(SenderLimits { max_future_operations: 2, ..Default::default() });
+
+	for nonce in 0..3 {
+		let trusted_operation = create_direct_trusted_operation(&alice_pair(), nonce);
+		submit_operation_to_top_pool(&author, &trusted_operation, &shielding_key, shard_id())
+			.unwrap();
+	}
+}
+
+#[test]
+fn submitting_more_than_max_pending_bytes_of_a_sender_returns_error() {
+	let (author, top_pool, shielding_key) = create_author_with_filter(AllowAllTopsFilter);
+	let future_operation = create_direct_trusted_operation(&alice_pair(), 1);
+	let operation_size = future_operation.to_call().unwrap().encoded_size();
+	let author = author.with_sender_limits(SenderLimits {
+		max_pending_bytes: operation_size * 3 / 2,
+		..Default::default()
+	});
+
+	top_pool.add_future_transaction(future_operation, shard_id());
+
+	let second_operation = create_direct_trusted_operation(&alice_pair(), 2);
+	let submit_response =
+		submit_operation_to_top_pool(&author, &second_operation, &shielding_key, shard_id());
+	assert_rpc_error(submit_response, ClientError::TooManyPendingBytes);
+}
+
+#[test]
+fn pool_status_counts_pending_operations() {
+	let (author, _top_pool, shielding_key) = create_author_with_filter(AllowAllTopsFilter);
+
+	for nonce in 0..2 {
+		let trusted_operation = create_direct_trusted_operation(&alice_pair(), nonce);
+		submit_operation_to_top_pool(&author, &trusted_operation, &shielding_key, shard_id())
+			.unwrap();
+	}
+
+	let pool_status = author.get_pool_status(shard_id());
+	assert_eq!(2, pool_status.ready);
+	assert_eq!(0, pool_status.future);
+}
+
+#[test]
+fn status_of_pending_operation_is_ready() {
+	let (author, _top_pool, shielding_key) = create_author_with_filter(AllowAllTopsFilter);
+	let trusted_operation = create_direct_trusted_operation(&alice_pair(), 0);
+
+	let top_hash =
+		submit_operation_to_top_pool(&author, &trusted_operation, &shielding_key, shard_id())
+			.unwrap();
+
+	assert_eq!(Some(TrustedOperationStatus::Ready), author.get_top_status(&top_hash, shard_id()));
+	assert_eq!(None, author.get_top_status(&H256::from_low_u64_be(1), shard_id()));
+}
+
+#[test]
+fn sender_can_cancel_own_pending_call() {
+	let (author, _top_pool, shielding_key) = create_author_with_filter(AllowAllTopsFilter);
+	let trusted_operation = create_direct_trusted_operation(&alice_pair(), 0);
+	let top_hash =
+		submit_operation_to_top_pool(&author, &trusted_operation, &shielding_key, shard_id())
+			.unwrap();
+
+	let sender: AccountId = alice_pair().public().into();
+	let cancelled_hash = author.cancel_top(top_hash, shard_id(), &sender).unwrap();
+
+	assert_eq!(top_hash, cancelled_hash);
+	assert!(author.get_pending_trusted_calls(shard_id()).is_empty());
+	assert_eq!(None, author.get_top_status(&top_hash, shard_id()));
+}
+
+#[test]
+fn cancelling_call_of_other_sender_returns_error() {
+	let (author, _top_pool, shielding_key) = create_author_with_filter(AllowAllTopsFilter);
+	let trusted_operation = create_direct_trusted_operation(&alice_pair(), 0);
+	let top_hash =
+		submit_operation_to_top_pool(&author, &trusted_operation, &shielding_key, shard_id())
+			.unwrap();
+
+	let other_account: AccountId = bob_pair().public().into();
+	let cancel_result = author.cancel_top(top_hash, shard_id(), &other_account);
+
+	assert!(matches!(
+		cancel_result,
+		Err(StateRpcError::ClientError(ClientError::NotSenderOfOperation))
+	));
+	assert_eq!(1, author.get_pending_trusted_calls(shard_id()).len());
+}
+
+#[test]
+fn cancelling_unknown_operation_returns_error() {
+	let (author, _top_pool, _shielding_key) = create_author_with_filter(AllowAllTopsFilter);
+
+	let sender: AccountId = alice_pair().public().into();
+	let cancel_result = author.cancel_top(H256::from_low_u64_be(1), shard_id(), &sender);
+
+	assert!(matches!(
+		cancel_result,
+		Err(StateRpcError::ClientError(ClientError::OperationNotPending))
+	));
+}
+
+#[test]
+fn calls_with_outdated_nonce_are_evicted() {
+	let (author, _top_pool, shielding_key) = create_author_with_filter(AllowAllTopsFilter);
+	let outdated_call = create_direct_trusted_operation(&alice_pair(), 0);
+	let current_call = create_direct_trusted_operation(&alice_pair(), 1);
+	let getter = TrustedOperation::from(trusted_getter_signed());
+	for trusted_operation in [&outdated_call, &current_call, &getter] {
+		submit_operation_to_top_pool(&author, trusted_operation, &shielding_key, shard_id())
+			.unwrap();
+	}
+
+	let number_of_evicted = author
+		.evict_outdated_calls(shard_id(), &mut |trusted_call_signed| trusted_call_signed.nonce < 1);
+
+	assert_eq!(1, number_of_evicted);
+	assert_eq!(vec![current_call], author.get_pending_trusted_calls(shard_id()));
+	assert_eq!(vec![getter], author.get_pending_trusted_getters(shard_id()));
+}
+
+fn assert_rpc_error(submit_response: Result<H256, RpcError>, expected_error: ClientError) {
+	assert_eq!(submit_response.unwrap_err().code, RpcError::from(expected_error).code);
+}
+
+fn rpc_source(connection: usize, address: u8) -> SubmissionSource {
+	SubmissionSource::Rpc(Some(RpcClient {
+		connection,
+		address: IpAddr::V4(Ipv4Addr::new(10, 0, 0, address)),
+	}))
+}
+
+fn decrypt_top(shielding_key: &ShieldingCryptoMock, encrypted_top: &[u8]) -> TrustedOperation {
+	let decrypted_top = shielding_key.decrypt(encrypted_top).unwrap();
+	TrustedOperation::decode(&mut decrypted_top.as_slice()).unwrap()
+}
+
 fn create_author_with_filter<F: Filter<Value = TrustedOperation>>(
 	filter: F,
+) -> (TestAuthor<F>, Arc<TrustedOperationPoolMock>, ShieldingCryptoMock) {
+	create_author_with_filter_and_ocall_api(filter, OnchainMock::default())
+}
+
+fn create_author_with_filter_and_ocall_api<F: Filter<Value = TrustedOperation>>(
+	filter: F,
+	ocall_api: OnchainMock,
 ) -> (TestAuthor<F>, Arc<TrustedOperationPoolMock>, ShieldingCryptoMock) {
 	let top_pool = Arc::new(TrustedOperationPoolMock::default());
 
@@ -135,15 +438,13 @@ fn create_author_with_filter<F: Filter<Value = TrustedOperation>>(
 	let encryption_key = ShieldingCryptoMock::default();
 	let shielding_key_repo =
 		Arc::new(KeyRepositoryMock::<ShieldingCryptoMock>::new(encryption_key.clone()));
-	let ocall_mock = Arc::new(MetricsOCallMock::default());
-
 	(
 		Author::new(
 			top_pool.clone(),
 			filter,
 			Arc::new(state_facade),
 			shielding_key_repo,
-			ocall_mock,
+			Arc::new(ocall_api.with_mr_enclave(mr_enclave())),
 		),
 		top_pool,
 		encryption_key,
@@ -68,6 +68,24 @@ pub enum Error {
 	/// Unsupported trusted operation (in case we allow only certain types of operations, using filters)
 	#[display(fmt = "Unsupported operation type")]
 	UnsupportedOperation,
+	/// Signature of the trusted call is invalid.
+	#[display(fmt = "Invalid trusted call signature")]
+	BadSignature,
+	/// Sender has reached the maximum number of trusted calls in the future queue.
+	#[display(fmt = "Too many future trusted calls of the sender")]
+	TooManyFutureOperations,
+	/// Sender has reached the maximum size of trusted calls in the future queue.
+	#[display(fmt = "Too many pending bytes of the sender")]
+	TooManyPendingBytes,
+	/// RPC client is temporarily banned, because of repeatedly invalid signatures.
+	#[display(fmt = "Connection is temporarily banned")]
+	ConnectionTemporarilyBanned,
+	/// Trusted operation to be cancelled is not pending in the pool.
+	#[display(fmt = "Trusted operation is not pending in the pool")]
+	OperationNotPending,
+	/// Account requesting the cancellation is not the sender of the trusted operation.
+	#[display(fmt = "Not the sender of the trusted operation")]
+	NotSenderOfOperation,
 }
 
 impl std::error::Error for Error {
@@ -87,6 +105,8 @@ const BASE_ERROR: i64 = 1000;
 const BAD_FORMAT: i64 = BASE_ERROR + 1;
 /// Error during operation verification in runtime.
 const VERIFICATION_ERROR: i64 = BASE_ERROR + 2;
+/// Trusted call has an invalid signature.
+const BAD_SIGNATURE: i64 = BASE_ERROR + 3;
 
 /// Pool rejected the operation as invalid
 const POOL_INVALID_TX: i64 = BASE_ERROR + 10;
@@ -105,6 +125,22 @@ const POOL_IMMEDIATELY_DROPPED: i64 = POOL_INVALID_TX + 6;
 /// The key type crypto is not known.
 const UNSUPPORTED_KEY_TYPE: i64 = POOL_INVALID_TX + 7;
 
+/// Base code for all errors of the per-sender limits.
+const SENDER_LIMIT_ERROR: i64 = BASE_ERROR + 20;
+/// The sender has too many future operations in the pool.
+const SENDER_TOO_MANY_FUTURE_OPERATIONS: i64 = SENDER_LIMIT_ERROR + 1;
+/// The pending operations of the sender exceed the size limit.
+const SENDER_TOO_MANY_PENDING_BYTES: i64 = SENDER_LIMIT_ERROR + 2;
+/// The RPC connection is temporarily banned.
+const CONNECTION_TEMPORARILY_BANNED: i64 = SENDER_LIMIT_ERROR + 3;
+
+/// Base code for all errors of the trusted operation cancellation.
+const CANCEL_ERROR: i64 = BASE_ERROR + 30;
+/// The operation to be cancelled is not pending in the pool.
+const CANCEL_OPERATION_NOT_PENDING: i64 = CANCEL_ERROR + 1;
+/// The cancellation was not requested by the sender of the operation.
+const CANCEL_NOT_SENDER: i64 = CANCEL_ERROR + 2;
+
 impl From<Error> for rpc_core::Error {
 	fn from(e: Error) -> Self {
 		use itp_top_pool::error::Error as PoolError;
@@ -125,6 +161,36 @@ impl From<Error> for rpc_core::Error {
 				message: "Verification Error".into(),
 				data: Some(format!("{:?}", e).into()),
 			},
+			Error::BadSignature => rpc_core::Error {
+				code: rpc_core::ErrorCode::ServerError(BAD_SIGNATURE),
+				message: "Trusted call has an invalid signature".into(),
+				data: None,
+			},
+			Error::TooManyFutureOperations => rpc_core::Error {
+				code: rpc_core::ErrorCode::ServerError(SENDER_TOO_MANY_FUTURE_OPERATIONS),
+				message: "Too many future trusted calls of the sender".into(),
+				data: Some("The sender has reached the maximum number of trusted calls in the future queue.".into()),
+			},
+			Error::TooManyPendingBytes => rpc_core::Error {
+				code: rpc_core::ErrorCode::ServerError(SENDER_TOO_MANY_PENDING_BYTES),
+				message: "Too many pending bytes of the sender".into(),
+				data: Some("The trusted calls of the sender in the future queue exceed the size limit.".into()),
+			},
+			Error::ConnectionTemporarilyBanned => rpc_core::Error {
+				code: rpc_core::ErrorCode::ServerError(CONNECTION_TEMPORARILY_BANNED),
+				message: "Connection is temporarily banned".into(),
+				data: Some("Trusted calls submitted from this address repeatedly had an invalid signature.".into()),
+			},
+			Error::OperationNotPending => rpc_core::Error {
+				code: rpc_core::ErrorCode::ServerError(CANCEL_OPERATION_NOT_PENDING),
+				message: "Trusted operation is not pending in the pool".into(),
+				data: None,
+			},
+			Error::NotSenderOfOperation => rpc_core::Error {
+				code: rpc_core::ErrorCode::ServerError(CANCEL_NOT_SENDER),
+				message: "Not the sender of the trusted operation".into(),
+				data: Some("Only the sender of a trusted call can cancel it.".into()),
+			},
 			Error::InvalidShard => rpc_core::Error {
 				code: rpc_core::ErrorCode::ServerError(VERIFICATION_ERROR),
 				message: "Shard does not exist".into(),
@@ -65,6 +65,14 @@ pub enum Error {
 
 	#[display(fmt = "Codec error: {}", _0)]
 	CodecError(codec::Error),
+
+	/// Persisting or restoring the pending trusted operations failed.
+	#[display(fmt = "Trusted operation pool persistence error: {}", _0)]
+	Persistence(String),
+
+	/// Evicting the stale trusted operations failed.
+	#[display(fmt = "Stale trusted operation eviction error: {}", _0)]
+	Eviction(String),
 }
 
 impl error::Error for Error {
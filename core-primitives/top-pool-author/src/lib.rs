@@ -36,7 +36,10 @@ pub mod api;
 pub mod author;
 pub mod client_error;
 pub mod error;
+pub mod sender_limits;
+pub mod stale_operations_evictor;
 pub mod top_filter;
+pub mod top_pool_persistence;
 pub mod traits;
 
 #[cfg(test)]
@@ -25,7 +25,9 @@ use std::sync::SgxRwLock as RwLock;
 use std::sync::RwLock;
 
 use crate::{
+	client_error::Error as ClientError,
 	error::Result,
+	sender_limits::{RpcClient, SubmissionSource},
 	traits::{AuthorApi, OnBlockImported},
 };
 use codec::Decode;
@@ -34,7 +36,7 @@ use ita_stf::{
 	Getter, TrustedGetterSigned, TrustedOperation,
 };
 use itp_top_pool::primitives::PoolFuture;
-use itp_types::ShardIdentifier;
+use itp_types::{AccountId, ShardIdentifier, TrustedOperationPoolStatus, TrustedOperationStatus};
 use jsonrpc_core::{futures::future::ready, Error as RpcError};
 use sp_core::{blake2_256, H256};
 use std::{boxed::Box, collections::HashMap, marker::PhantomData, vec, vec::Vec};
@@ -44,6 +46,7 @@ pub struct AuthorApiMock<Hash, BlockHash> {
 	tops: RwLock<HashMap<ShardIdentifier, Vec<Vec<u8>>>>,
 	_phantom: PhantomData<(Hash, BlockHash)>,
 	pub remove_attempts: RwLock<usize>,
+	pub retracted_blocks: RwLock<Vec<H256>>,
 }
 
 impl<Hash, BlockHash> AuthorApiMock<Hash, BlockHash> {
@@ -97,6 +100,10 @@ impl AuthorApi<H256, H256> for AuthorApiMock<H256, H256> {
 		Box::pin(ready(Ok(H256::default())))
 	}
 
+	fn import_top(&self, extrinsic: Vec<u8>, shard: ShardIdentifier) -> PoolFuture<H256, RpcError> {
+		self.submit_top(extrinsic, shard)
+	}
+
 	fn hash_of(&self, xt: &TrustedOperation) -> H256 {
 		xt.hash()
 	}
@@ -144,6 +151,54 @@ impl AuthorApi<H256, H256> for AuthorApiMock<H256, H256> {
 		self.tops.read().unwrap().keys().cloned().collect()
 	}
 
+	fn get_pool_status(&self, shard: ShardIdentifier) -> TrustedOperationPoolStatus {
+		self.tops
+			.read()
+			.unwrap()
+			.get(&shard)
+			.map(|encoded_operations| TrustedOperationPoolStatus {
+				ready: encoded_operations.len() as u64,
+				ready_bytes: encoded_operations.iter().map(|o| o.len() as u64).sum(),
+				..Default::default()
+			})
+			.unwrap_or_default()
+	}
+
+	fn get_top_status(
+		&self,
+		hash: &H256,
+		shard: ShardIdentifier,
+	) -> Option<TrustedOperationStatus> {
+		self.tops
+			.read()
+			.unwrap()
+			.get(&shard)?
+			.iter()
+			.any(|t| &H256::from(blake2_256(t)) == hash)
+			.then(|| TrustedOperationStatus::Ready)
+	}
+
+	fn cancel_top(&self, hash: H256, shard: ShardIdentifier, sender: &AccountId) -> Result<H256> {
+		let pending_operation = self
+			.tops
+			.read()
+			.unwrap()
+			.get(&shard)
+			.and_then(|tops| tops.iter().find(|t| H256::from(blake2_256(t)) == hash).cloned())
+			.ok_or(ClientError::OperationNotPending)?;
+
+		match Self::decode_trusted_operation(&pending_operation)
+			.as_ref()
+			.and_then(|o| o.to_call())
+		{
+			Some(trusted_call_signed) if trusted_call_signed.call.sender_account() == sender => (),
+			_ => return Err(ClientError::NotSenderOfOperation.into()),
+		}
+
+		self.remove_top(vec![TrustedOperationOrHash::Hash(hash)], shard, false)?;
+		Ok(hash)
+	}
+
 	fn remove_calls_from_pool(
 		&self,
 		shard: ShardIdentifier,
@@ -161,15 +216,26 @@ impl AuthorApi<H256, H256> for AuthorApiMock<H256, H256> {
 		failed_to_remove
 	}
 
-	fn watch_top(&self, _ext: Vec<u8>, _shard: ShardIdentifier) -> PoolFuture<H256, RpcError> {
+	fn watch_top(
+		&self,
+		_ext: Vec<u8>,
+		_shard: ShardIdentifier,
+		_source: SubmissionSource,
+	) -> PoolFuture<H256, RpcError> {
 		todo!()
 	}
+
+	fn on_connection_closed(&self, _client: RpcClient) {}
 }
 
 impl OnBlockImported for AuthorApiMock<H256, H256> {
 	type Hash = H256;
 
 	fn on_block_imported(&self, _hashes: &[Self::Hash], _block_hash: H256) {}
+
+	fn on_block_retracted(&self, block_hash: H256) {
+		self.retracted_blocks.write().unwrap().push(block_hash);
+	}
 }
 
 #[cfg(test)]
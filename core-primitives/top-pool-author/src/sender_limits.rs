@@ -0,0 +1,266 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Per-sender limits, protecting the pool from being flooded by a single account, and
+//! temporary bans of RPC clients that keep submitting trusted calls with invalid signatures.
+
+#[cfg(feature = "sgx")]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use log::*;
+use std::{
+	collections::{BTreeSet, HashMap},
+	net::IpAddr,
+	time::Duration,
+};
+
+/// Limits the trusted calls a single sender can have waiting in the pool, and the invalid
+/// trusted calls a single RPC client can submit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SenderLimits {
+	/// Maximum number of trusted calls of a sender in the future queue, i.e. with a nonce
+	/// ahead of the nonce of the sender in the state.
+	pub max_future_operations: usize,
+	/// Maximum accumulated encoded size of the trusted calls of a sender in the future queue.
+	pub max_pending_bytes: usize,
+	/// Number of consecutive failed signature checks after which an RPC client is banned.
+	pub max_signature_failures: u32,
+	/// Duration an RPC client is banned for.
+	pub ban_duration: Duration,
+}
+
+impl Default for SenderLimits {
+	fn default() -> Self {
+		SenderLimits {
+			max_future_operations: 64,
+			max_pending_bytes: 64 * 1024,
+			max_signature_failures: 3,
+			ban_duration: Duration::from_secs(600),
+		}
+	}
+}
+
+/// Client that submitted a trusted operation over a direct RPC connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RpcClient {
+	/// Id of the connection, which changes whenever the client reconnects.
+	pub connection: usize,
+	/// IP address of the client.
+	pub address: IpAddr,
+}
+
+/// Origin of a trusted operation submitted to the pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmissionSource {
+	/// Submitted over a direct RPC connection, by the given client if known.
+	Rpc(Option<RpcClient>),
+	/// Gossiped by a peer validateer.
+	Peer,
+	/// Submitted from within the enclave, e.g. a shielded call of the parentchain.
+	Local,
+}
+
+impl SubmissionSource {
+	/// Client that submitted the operation over an RPC connection.
+	pub fn client(&self) -> Option<RpcClient> {
+		match self {
+			SubmissionSource::Rpc(client) => *client,
+			SubmissionSource::Peer | SubmissionSource::Local => None,
+		}
+	}
+}
+
+#[derive(Default, Debug, Clone)]
+struct SignatureFailures {
+	count: u32,
+	/// Unix epoch in milliseconds until which the client is banned.
+	banned_until: Option<u64>,
+	/// Open connections of the client over which the failed calls were submitted.
+	connections: BTreeSet<usize>,
+}
+
+/// Temporary ban list of RPC clients that repeatedly submitted trusted calls with an
+/// invalid signature.
+///
+/// Clients are identified by their IP address, so a banned client cannot evade the ban by
+/// reconnecting. Failures are deliberately not recorded against the sender account claimed
+/// by the call: anyone can forge a call in the name of any account, so that would let them
+/// get a victim banned.
+#[derive(Default)]
+pub struct ClientBanList {
+	signature_failures: RwLock<HashMap<IpAddr, SignatureFailures>>,
+}
+
+impl ClientBanList {
+	/// Returns true if the client address is banned at the time `now` (unix epoch in milliseconds).
+	pub fn is_banned(&self, address: &IpAddr, now: u64) -> bool {
+		let signature_failures = match self.signature_failures.read() {
+			Ok(f) => f,
+			Err(e) => {
+				error!("Failed to acquire client ban list lock: {:?}", e);
+				return false
+			},
+		};
+		signature_failures
+			.get(address)
+			.and_then(|failures| failures.banned_until)
+			.map_or(false, |banned_until| now < banned_until)
+	}
+
+	/// Records a failed signature check of a trusted call submitted by the client and
+	/// bans the client once the limit of consecutive failures is reached.
+	/// Returns true if the client has been banned.
+	pub fn record_signature_failure(
+		&self,
+		client: &RpcClient,
+		now: u64,
+		limits: &SenderLimits,
+	) -> bool {
+		let mut signature_failures = match self.signature_failures.write() {
+			Ok(f) => f,
+			Err(e) => {
+				error!("Failed to acquire client ban list lock: {:?}", e);
+				return false
+			},
+		};
+
+		// Drop expired bans, so the map does not grow with every client ever banned.
+		signature_failures
+			.retain(|_, failures| failures.banned_until.map_or(true, |until| now < until));
+
+		let failures = signature_failures.entry(client.address).or_default();
+		failures.connections.insert(client.connection);
+		failures.count = failures.count.saturating_add(1);
+		if failures.count < limits.max_signature_failures {
+			return false
+		}
+
+		failures.count = 0;
+		failures.banned_until = Some(now.saturating_add(limits.ban_duration.as_millis() as u64));
+		true
+	}
+
+	/// Resets the failed signature checks of a client, which is not banned.
+	pub fn record_valid_signature(&self, client: &RpcClient) {
+		if let Ok(mut signature_failures) = self.signature_failures.write() {
+			if signature_failures
+				.get(&client.address)
+				.map_or(false, |f| f.banned_until.is_none())
+			{
+				signature_failures.remove(&client.address);
+			}
+		}
+	}
+
+	/// Forgets the connection of the client once it is closed. The failed signature checks
+	/// of a client, which is not banned, are dropped with its last connection.
+	pub fn remove_connection(&self, client: &RpcClient) {
+		if let Ok(mut signature_failures) = self.signature_failures.write() {
+			let is_released = match signature_failures.get_mut(&client.address) {
+				Some(failures) => {
+					failures.connections.remove(&client.connection);
+					failures.connections.is_empty() && failures.banned_until.is_none()
+				},
+				None => false,
+			};
+			if is_released {
+				signature_failures.remove(&client.address);
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::net::Ipv4Addr;
+
+	fn limits() -> SenderLimits {
+		SenderLimits {
+			max_signature_failures: 2,
+			ban_duration: Duration::from_millis(1_000),
+			..Default::default()
+		}
+	}
+
+	fn client(connection: usize, address: u8) -> RpcClient {
+		RpcClient { connection, address: IpAddr::V4(Ipv4Addr::new(10, 0, 0, address)) }
+	}
+
+	#[test]
+	fn client_is_banned_after_repeated_signature_failures() {
+		let ban_list = ClientBanList::default();
+
+		assert!(!ban_list.record_signature_failure(&client(1, 1), 0, &limits()));
+		assert!(!ban_list.is_banned(&client(1, 1).address, 0));
+
+		assert!(ban_list.record_signature_failure(&client(1, 1), 0, &limits()));
+		assert!(ban_list.is_banned(&client(1, 1).address, 0));
+		assert!(!ban_list.is_banned(&client(2, 2).address, 0));
+	}
+
+	#[test]
+	fn ban_applies_to_new_connections_of_the_client() {
+		let ban_list = ClientBanList::default();
+
+		ban_list.record_signature_failure(&client(1, 1), 0, &limits());
+		ban_list.record_signature_failure(&client(2, 1), 0, &limits());
+		ban_list.remove_connection(&client(1, 1));
+		ban_list.remove_connection(&client(2, 1));
+
+		assert!(ban_list.is_banned(&client(3, 1).address, 0));
+	}
+
+	#[test]
+	fn ban_expires_after_ban_duration() {
+		let ban_list = ClientBanList::default();
+
+		ban_list.record_signature_failure(&client(1, 1), 0, &limits());
+		ban_list.record_signature_failure(&client(1, 1), 0, &limits());
+
+		assert!(ban_list.is_banned(&client(1, 1).address, 999));
+		assert!(!ban_list.is_banned(&client(1, 1).address, 1_000));
+	}
+
+	#[test]
+	fn valid_signature_resets_signature_failures() {
+		let ban_list = ClientBanList::default();
+
+		ban_list.record_signature_failure(&client(1, 1), 0, &limits());
+		ban_list.record_valid_signature(&client(1, 1));
+
+		assert!(!ban_list.record_signature_failure(&client(1, 1), 0, &limits()));
+		assert!(!ban_list.is_banned(&client(1, 1).address, 0));
+	}
+
+	#[test]
+	fn signature_failures_are_dropped_with_the_last_connection_of_the_client() {
+		let ban_list = ClientBanList::default();
+		let limits = SenderLimits { max_signature_failures: 3, ..limits() };
+
+		ban_list.record_signature_failure(&client(1, 1), 0, &limits);
+		ban_list.record_signature_failure(&client(2, 1), 0, &limits);
+
+		ban_list.remove_connection(&client(1, 1));
+		assert_eq!(1, ban_list.signature_failures.read().unwrap().len());
+
+		ban_list.remove_connection(&client(2, 1));
+		assert!(ban_list.signature_failures.read().unwrap().is_empty());
+	}
+}
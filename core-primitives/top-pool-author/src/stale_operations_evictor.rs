@@ -0,0 +1,153 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Eviction of the trusted operations that can no longer be included in a sidechain block.
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use crate::sgx_reexport_prelude::*;
+
+use crate::{
+	error::{Error, Result},
+	traits::EvictStaleOperations,
+};
+use core::marker::PhantomData;
+use ita_stf::{AccountId, Index};
+use itp_stf_interface::system_pallet::SystemPalletAccountInterface;
+use itp_stf_state_observer::traits::ObserveState;
+use itp_types::ShardIdentifier;
+use std::{format, sync::Arc};
+
+/// Evicts the pending trusted operations beyond their longevity, and the trusted calls
+/// whose nonce the sender has already used in the current state.
+pub struct StaleOperationsEvictor<Author, StateObserver, Stf> {
+	author: Arc<Author>,
+	state_observer: Arc<StateObserver>,
+	_phantom: PhantomData<Stf>,
+}
+
+impl<Author, StateObserver, Stf> StaleOperationsEvictor<Author, StateObserver, Stf>
+where
+	Author: EvictStaleOperations,
+	StateObserver: ObserveState,
+	Stf: SystemPalletAccountInterface<StateObserver::StateType, AccountId>,
+	Stf::Index: Into<Index>,
+{
+	pub fn new(author: Arc<Author>, state_observer: Arc<StateObserver>) -> Self {
+		StaleOperationsEvictor { author, state_observer, _phantom: Default::default() }
+	}
+
+	/// Evict the stale operations of a shard, given the number of its latest sidechain block.
+	///
+	/// Returns the number of evicted operations.
+	pub fn evict(&self, shard: &ShardIdentifier, block_number: u64) -> Result<usize> {
+		let number_of_expired = self.author.evict_expired_operations(*shard, block_number);
+
+		let number_of_outdated = self
+			.state_observer
+			.observe_state(shard, |state| {
+				self.author.evict_outdated_calls(*shard, &mut |trusted_call_signed| {
+					let sender = trusted_call_signed.call.sender_account();
+					trusted_call_signed.nonce < Stf::get_account_nonce(state, sender).into()
+				})
+			})
+			.map_err(|e| Error::Eviction(format!("{:?}", e)))?;
+
+		Ok(number_of_expired + number_of_outdated)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_fixtures::{
+		alice_pair, bob_pair, create_direct_trusted_operation, shard_id, NonceState, NonceStfMock,
+	};
+	use ita_stf::{TrustedCallSigned, TrustedOperation};
+	use itp_stf_state_observer::mock::ObserveStateMock;
+	use sp_core::Pair;
+	use std::sync::RwLock;
+
+	#[derive(Default)]
+	struct EvictStaleOperationsMock {
+		pending_calls: RwLock<Vec<TrustedCallSigned>>,
+		expired_at_block_numbers: RwLock<Vec<u64>>,
+	}
+
+	impl EvictStaleOperations for EvictStaleOperationsMock {
+		fn evict_expired_operations(&self, _shard: ShardIdentifier, block_number: u64) -> usize {
+			self.expired_at_block_numbers.write().unwrap().push(block_number);
+			0
+		}
+
+		fn evict_outdated_calls(
+			&self,
+			_shard: ShardIdentifier,
+			is_outdated: &mut dyn FnMut(&TrustedCallSigned) -> bool,
+		) -> usize {
+			let mut pending_calls = self.pending_calls.write().unwrap();
+			let number_of_pending_calls = pending_calls.len();
+			pending_calls.retain(|c| !is_outdated(c));
+			number_of_pending_calls - pending_calls.len()
+		}
+	}
+
+	type TestEvictor = StaleOperationsEvictor<
+		EvictStaleOperationsMock,
+		ObserveStateMock<NonceState>,
+		NonceStfMock,
+	>;
+
+	#[test]
+	fn calls_with_a_nonce_below_the_account_nonce_are_evicted() {
+		let outdated_call = call_of(create_direct_trusted_operation(&alice_pair(), 0));
+		let current_call = call_of(create_direct_trusted_operation(&alice_pair(), 1));
+		let other_sender_call = call_of(create_direct_trusted_operation(&bob_pair(), 0));
+		let state = NonceState::from([(alice_pair().public().into(), 1)]);
+		let evictor = create_evictor(
+			vec![outdated_call, current_call.clone(), other_sender_call.clone()],
+			state,
+		);
+
+		assert_eq!(1, evictor.evict(&shard_id(), 5).unwrap());
+		assert_eq!(
+			*evictor.author.pending_calls.read().unwrap(),
+			vec![current_call, other_sender_call]
+		);
+	}
+
+	#[test]
+	fn operations_beyond_their_longevity_are_evicted_at_the_given_block_number() {
+		let evictor = create_evictor(vec![], NonceState::new());
+
+		evictor.evict(&shard_id(), 5).unwrap();
+
+		assert_eq!(*evictor.author.expired_at_block_numbers.read().unwrap(), vec![5]);
+	}
+
+	fn create_evictor(pending_calls: Vec<TrustedCallSigned>, state: NonceState) -> TestEvictor {
+		StaleOperationsEvictor::new(
+			Arc::new(EvictStaleOperationsMock {
+				pending_calls: RwLock::new(pending_calls),
+				expired_at_block_numbers: Default::default(),
+			}),
+			Arc::new(ObserveStateMock::new(state)),
+		)
+	}
+
+	fn call_of(trusted_operation: TrustedOperation) -> TrustedCallSigned {
+		trusted_operation.to_call().unwrap().clone()
+	}
+}
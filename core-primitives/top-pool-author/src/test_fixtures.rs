@@ -17,12 +17,30 @@
 
 use codec::Encode;
 use ita_stf::{
-	Getter, KeyPair, ShardIdentifier, TrustedCall, TrustedCallSigned, TrustedGetter,
-	TrustedOperation,
+	AccountId, Getter, Index, KeyPair, ShardIdentifier, TrustedCall, TrustedCallSigned,
+	TrustedGetter, TrustedOperation,
 };
+use itp_stf_interface::system_pallet::SystemPalletAccountInterface;
 use sp_core::{ed25519, Pair};
 use sp_runtime::traits::{BlakeTwo256, Hash};
-use std::vec;
+use std::{collections::HashMap, vec};
+
+/// State that only holds the account nonces.
+pub(crate) type NonceState = HashMap<AccountId, Index>;
+
+/// STF that reads the account nonces of a `NonceState`.
+pub(crate) struct NonceStfMock;
+
+impl SystemPalletAccountInterface<NonceState, AccountId> for NonceStfMock {
+	type Index = Index;
+	type AccountData = ();
+
+	fn get_account_nonce(state: &mut NonceState, account_id: &AccountId) -> Self::Index {
+		state.get(account_id).cloned().unwrap_or_default()
+	}
+
+	fn get_account_data(_state: &mut NonceState, _account_id: &AccountId) -> Self::AccountData {}
+}
 
 type Seed = [u8; 32];
 const TEST_SEED: Seed = *b"12345678901234567890123456789012";
@@ -41,16 +59,33 @@ pub(crate) fn trusted_getter_signed() -> Getter {
 }
 
 pub(crate) fn create_indirect_trusted_operation() -> TrustedOperation {
-	let account = ed25519::Pair::from_seed(&TEST_SEED);
 	let trusted_call_signed = TrustedCall::balance_transfer(
 		alice_pair().public().into(),
 		bob_pair().public().into(),
 		1000u128,
 	)
-	.sign(&KeyPair::Ed25519(account), 1, &mr_enclave(), &shard_id());
+	.sign(&KeyPair::Ed25519(alice_pair()), 1, &mr_enclave(), &shard_id());
 	TrustedOperation::indirect_call(trusted_call_signed)
 }
 
+pub(crate) fn create_direct_trusted_operation(
+	sender: &ed25519::Pair,
+	nonce: u32,
+) -> TrustedOperation {
+	TrustedCall::balance_transfer(sender.public().into(), bob_pair().public().into(), 1000u128)
+		.sign(&KeyPair::Ed25519(sender.clone()), nonce, &mr_enclave(), &shard_id())
+		.into_trusted_operation(true)
+}
+
+/// Trusted operation signed for another enclave, so its signature is invalid in this enclave.
+pub(crate) fn create_direct_trusted_operation_with_invalid_signature(
+	sender: &ed25519::Pair,
+) -> TrustedOperation {
+	TrustedCall::balance_transfer(sender.public().into(), bob_pair().public().into(), 1000u128)
+		.sign(&KeyPair::Ed25519(sender.clone()), 0, &[9u8; 32], &shard_id())
+		.into_trusted_operation(true)
+}
+
 pub(crate) fn mr_enclave() -> [u8; 32] {
 	[1u8; 32]
 }
@@ -59,10 +94,10 @@ pub(crate) fn shard_id() -> ShardIdentifier {
 	BlakeTwo256::hash(vec![1u8, 2u8, 3u8].as_slice().encode().as_slice())
 }
 
-fn alice_pair() -> ed25519::Pair {
+pub(crate) fn alice_pair() -> ed25519::Pair {
 	ed25519::Pair::from_seed(b"22222678901234567890123456789012")
 }
 
-fn bob_pair() -> ed25519::Pair {
+pub(crate) fn bob_pair() -> ed25519::Pair {
 	ed25519::Pair::from_seed(b"33333378901234567890123456789012")
 }
@@ -18,7 +18,7 @@
 #[cfg(all(not(feature = "std"), feature = "sgx"))]
 use crate::sgx_reexport_prelude::*;
 
-use crate::traits::AuthorApi;
+use crate::{sender_limits::SubmissionSource, traits::AuthorApi};
 use codec::Encode;
 use ita_stf::{ShardIdentifier, TrustedOperation};
 use itp_sgx_crypto::ShieldingCryptoEncrypt;
@@ -33,12 +33,34 @@ pub fn submit_operation_to_top_pool<R, S>(
 	shielding_key: &S,
 	shard: ShardIdentifier,
 ) -> Result<H256, jsonrpc_core::Error>
+where
+	R: AuthorApi<H256, H256>,
+	S: ShieldingCryptoEncrypt,
+	S::Error: Debug,
+{
+	submit_operation_to_top_pool_from(
+		author,
+		top,
+		shielding_key,
+		shard,
+		SubmissionSource::Rpc(None),
+	)
+}
+
+/// Test utility function to submit a trusted operation on an RPC author, from the given source
+pub fn submit_operation_to_top_pool_from<R, S>(
+	author: &R,
+	top: &TrustedOperation,
+	shielding_key: &S,
+	shard: ShardIdentifier,
+	source: SubmissionSource,
+) -> Result<H256, jsonrpc_core::Error>
 where
 	R: AuthorApi<H256, H256>,
 	S: ShieldingCryptoEncrypt,
 	S::Error: Debug,
 {
 	let top_encrypted = shielding_key.encrypt(&top.encode()).unwrap();
-	let submit_future = async { author.watch_top(top_encrypted, shard).await };
+	let submit_future = async { author.watch_top(top_encrypted, shard, source).await };
 	executor::block_on(submit_future)
 }
@@ -0,0 +1,416 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Persistence of the pending trusted operations across restarts of the worker.
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use crate::sgx_reexport_prelude::*;
+
+#[cfg(feature = "sgx")]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use crate::{
+	error::{Error, Result},
+	traits::{PendingOperationsSnapshot, SnapshotTrustedOperations},
+};
+use codec::{Decode, Encode};
+use core::marker::PhantomData;
+use ita_stf::{AccountId, Index, TrustedOperation};
+use itp_ocall_api::EnclaveSidechainOCallApi;
+use itp_settings::sidechain::MAX_PERSISTED_TOP_POOL_SIZE;
+use itp_sgx_crypto::{key_repository::AccessKey, Aes, StateCrypto};
+use itp_stf_interface::system_pallet::SystemPalletAccountInterface;
+use itp_stf_state_observer::traits::ObserveState;
+use itp_types::{ShardIdentifier, H256};
+use jsonrpc_core::futures::executor;
+use log::*;
+use sp_core::blake2_256;
+use std::{format, sync::Arc, vec::Vec};
+
+/// Bytes the o-call buffer needs on top of the encrypted pool, for its `Option` and length prefix.
+const ENCODING_OVERHEAD: usize = 16;
+
+/// Length of the initialization vector, which is stored in front of each encrypted snapshot.
+const INIT_VEC_LENGTH: usize = 16;
+
+/// Stores the pending trusted operations, encrypted with the state key, on the untrusted
+/// file system and submits them to the pool again after a restart.
+///
+/// Each snapshot is encrypted with a fresh, random initialization vector, so the key stream
+/// of the state key is never reused for different snapshots.
+pub struct TopPoolPersistence<Author, StateObserver, StateKeyRepository, OCallApi, Stf> {
+	author: Arc<Author>,
+	state_observer: Arc<StateObserver>,
+	state_key_repository: Arc<StateKeyRepository>,
+	ocall_api: Arc<OCallApi>,
+	/// Hash of the last persisted snapshot, an unchanged pool is not persisted again.
+	last_snapshot_hash: RwLock<Option<[u8; 32]>>,
+	_phantom: PhantomData<Stf>,
+}
+
+impl<Author, StateObserver, StateKeyRepository, OCallApi, Stf>
+	TopPoolPersistence<Author, StateObserver, StateKeyRepository, OCallApi, Stf>
+where
+	Author: SnapshotTrustedOperations<H256>,
+	StateObserver: ObserveState,
+	StateKeyRepository: AccessKey<KeyType = Aes>,
+	OCallApi: EnclaveSidechainOCallApi,
+	Stf: SystemPalletAccountInterface<StateObserver::StateType, AccountId>,
+	Stf::Index: Into<Index>,
+{
+	pub fn new(
+		author: Arc<Author>,
+		state_observer: Arc<StateObserver>,
+		state_key_repository: Arc<StateKeyRepository>,
+		ocall_api: Arc<OCallApi>,
+	) -> Self {
+		TopPoolPersistence {
+			author,
+			state_observer,
+			state_key_repository,
+			ocall_api,
+			last_snapshot_hash: Default::default(),
+			_phantom: Default::default(),
+		}
+	}
+
+	/// Persist all pending trusted calls, replacing the ones persisted before.
+	///
+	/// Nothing is written if the pending trusted calls did not change since the last call.
+	/// If they exceed the maximum persisted size, the persisted calls are cleared instead,
+	/// so that no outdated snapshot is restored after a restart.
+	pub fn persist(&self) -> Result<()> {
+		let snapshot_encoded = self.author.snapshot_pending_operations().encode();
+		let snapshot_hash = blake2_256(&snapshot_encoded);
+		if *self.last_snapshot_hash.read().map_err(lock_error)? == Some(snapshot_hash) {
+			return Ok(())
+		}
+
+		if snapshot_encoded.len() + INIT_VEC_LENGTH + ENCODING_OVERHEAD
+			> MAX_PERSISTED_TOP_POOL_SIZE
+		{
+			self.store(PendingOperationsSnapshot::new().encode())?;
+			// Not retried either, until the pending trusted calls change.
+			*self.last_snapshot_hash.write().map_err(lock_error)? = Some(snapshot_hash);
+			return Err(Error::Persistence(format!(
+				"Pending trusted operations exceed the maximum persisted size ({} bytes), \
+				cleared the persisted ones",
+				snapshot_encoded.len()
+			)))
+		}
+
+		self.store(snapshot_encoded)?;
+		*self.last_snapshot_hash.write().map_err(lock_error)? = Some(snapshot_hash);
+		Ok(())
+	}
+
+	fn store(&self, mut snapshot_encoded: Vec<u8>) -> Result<()> {
+		let init_vec = random_init_vec()?;
+		let key = self
+			.state_key_repository
+			.retrieve_key()
+			.map_err(|e| Error::Persistence(format!("{:?}", e)))?;
+		Aes::new(key.key, init_vec)
+			.encrypt(&mut snapshot_encoded)
+			.map_err(|e| Error::Persistence(format!("{:?}", e)))?;
+
+		let mut encrypted_snapshot = init_vec.to_vec();
+		encrypted_snapshot.append(&mut snapshot_encoded);
+		self.ocall_api
+			.store_trusted_operation_pool(encrypted_snapshot)
+			.map_err(|e| Error::Persistence(format!("{:?}", e)))
+	}
+
+	/// Submit the persisted trusted operations to the pool again.
+	///
+	/// Operations are validated like newly submitted ones. Trusted calls with a nonce the sender
+	/// has already used in the current state are dropped. Returns the number of restored operations.
+	pub fn restore(&self) -> Result<usize> {
+		let encrypted_snapshot = match self
+			.ocall_api
+			.load_trusted_operation_pool()
+			.map_err(|e| Error::Persistence(format!("{:?}", e)))?
+		{
+			Some(encrypted_snapshot) => encrypted_snapshot,
+			None => return Ok(0),
+		};
+		if encrypted_snapshot.len() < INIT_VEC_LENGTH {
+			return Err(Error::Persistence(
+				"Persisted snapshot lacks its initialization vector".into(),
+			))
+		}
+
+		let (init_vec, snapshot_encoded) = encrypted_snapshot.split_at(INIT_VEC_LENGTH);
+		let mut snapshot_encoded = snapshot_encoded.to_vec();
+		let key = self
+			.state_key_repository
+			.retrieve_key()
+			.map_err(|e| Error::Persistence(format!("{:?}", e)))?;
+		let mut init_vec_array = [0u8; INIT_VEC_LENGTH];
+		init_vec_array.copy_from_slice(init_vec);
+		Aes::new(key.key, init_vec_array)
+			.decrypt(&mut snapshot_encoded)
+			.map_err(|e| Error::Persistence(format!("{:?}", e)))?;
+
+		let snapshot = PendingOperationsSnapshot::decode(&mut snapshot_encoded.as_slice())?;
+
+		let mut number_of_restored_operations = 0;
+		for (shard, trusted_operations) in snapshot {
+			for trusted_operation in trusted_operations {
+				if self.is_outdated(&trusted_operation, &shard) {
+					debug!("Dropping persisted trusted operation, its nonce is outdated");
+					continue
+				}
+
+				match executor::block_on(self.author.restore_top(trusted_operation, shard)) {
+					Ok(_) => number_of_restored_operations += 1,
+					Err(e) => debug!("Dropping persisted trusted operation: {:?}", e),
+				}
+			}
+		}
+		Ok(number_of_restored_operations)
+	}
+
+	fn is_outdated(&self, trusted_operation: &TrustedOperation, shard: &ShardIdentifier) -> bool {
+		let trusted_call_signed = match trusted_operation.to_call() {
+			Some(c) => c,
+			None => return false,
+		};
+		let sender = trusted_call_signed.call.sender_account().clone();
+
+		match self
+			.state_observer
+			.observe_state(shard, move |state| Stf::get_account_nonce(state, &sender))
+		{
+			Ok(nonce) => trusted_call_signed.nonce < nonce.into(),
+			Err(e) => {
+				debug!("Failed to observe the state of shard {:?}: {:?}", shard, e);
+				true
+			},
+		}
+	}
+}
+
+fn lock_error<E>(_: E) -> Error {
+	Error::Persistence("Lock of the last persisted snapshot hash is poisoned".into())
+}
+
+#[cfg(feature = "sgx")]
+fn random_init_vec() -> Result<[u8; INIT_VEC_LENGTH]> {
+	use sgx_rand::{os, Rng};
+
+	let mut init_vec = [0u8; INIT_VEC_LENGTH];
+	os::SgxRng::new()
+		.map_err(|e| Error::Persistence(format!("{:?}", e)))?
+		.fill_bytes(&mut init_vec);
+	Ok(init_vec)
+}
+
+#[cfg(feature = "std")]
+fn random_init_vec() -> Result<[u8; INIT_VEC_LENGTH]> {
+	use rand::{rngs::OsRng, RngCore};
+
+	let mut init_vec = [0u8; INIT_VEC_LENGTH];
+	OsRng
+		.try_fill_bytes(&mut init_vec)
+		.map_err(|e| Error::Persistence(format!("{:?}", e)))?;
+	Ok(init_vec)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_fixtures::{
+		alice_pair, bob_pair, create_direct_trusted_operation, shard_id, NonceState, NonceStfMock,
+	};
+	use ita_stf::hash::Hash;
+	use itp_sgx_crypto::{mocks::KeyRepositoryMock, Aes};
+	use itp_stf_state_observer::mock::ObserveStateMock;
+	use itp_test::mock::onchain_mock::OnchainMock;
+	use itp_top_pool::primitives::PoolFuture;
+	use jsonrpc_core::{futures::future::ready, Error as RpcError};
+	use sp_core::Pair;
+	use std::sync::RwLock;
+
+	#[derive(Default)]
+	struct SnapshotTrustedOperationsMock {
+		pending_operations: PendingOperationsSnapshot,
+		restored_operations: RwLock<PendingOperationsSnapshot>,
+	}
+
+	impl SnapshotTrustedOperations<H256> for SnapshotTrustedOperationsMock {
+		fn snapshot_pending_operations(&self) -> PendingOperationsSnapshot {
+			self.pending_operations.clone()
+		}
+
+		fn restore_top(
+			&self,
+			trusted_operation: TrustedOperation,
+			shard: ShardIdentifier,
+		) -> PoolFuture<H256, RpcError> {
+			let hash = trusted_operation.hash();
+			self.restored_operations.write().unwrap().push((shard, vec![trusted_operation]));
+			Box::pin(ready(Ok(hash)))
+		}
+	}
+
+	type TestPersistence = TopPoolPersistence<
+		SnapshotTrustedOperationsMock,
+		ObserveStateMock<NonceState>,
+		KeyRepositoryMock<Aes>,
+		OnchainMock,
+		NonceStfMock,
+	>;
+
+	#[test]
+	fn persisted_operations_are_encrypted_and_restored() {
+		let pending_operations = vec![(
+			shard_id(),
+			vec![
+				create_direct_trusted_operation(&alice_pair(), 0),
+				create_direct_trusted_operation(&bob_pair(), 3),
+			],
+		)];
+		let ocall_api = OnchainMock::default();
+
+		create_persistence(pending_operations.clone(), NonceState::new(), ocall_api.clone())
+			.persist()
+			.unwrap();
+		assert_ne!(Some(pending_operations.encode()), ocall_api.stored_trusted_operation_pool());
+
+		let restoring_persistence = create_persistence(vec![], NonceState::new(), ocall_api);
+		assert_eq!(2, restoring_persistence.restore().unwrap());
+		assert_eq!(restored_operations(&restoring_persistence), pending_operations[0].1);
+	}
+
+	#[test]
+	fn calls_with_outdated_nonce_are_dropped_on_restore() {
+		let outdated_call = create_direct_trusted_operation(&alice_pair(), 0);
+		let current_call = create_direct_trusted_operation(&alice_pair(), 1);
+		let ocall_api = OnchainMock::default();
+		create_persistence(
+			vec![(shard_id(), vec![outdated_call, current_call.clone()])],
+			NonceState::new(),
+			ocall_api.clone(),
+		)
+		.persist()
+		.unwrap();
+
+		let state = NonceState::from([(alice_pair().public().into(), 1)]);
+		let restoring_persistence = create_persistence(vec![], state, ocall_api);
+
+		assert_eq!(1, restoring_persistence.restore().unwrap());
+		assert_eq!(restored_operations(&restoring_persistence), vec![current_call]);
+	}
+
+	#[test]
+	fn unchanged_operations_are_not_persisted_again() {
+		let ocall_api = OnchainMock::default();
+		let persistence = create_persistence(
+			vec![(shard_id(), vec![create_direct_trusted_operation(&alice_pair(), 0)])],
+			NonceState::new(),
+			ocall_api.clone(),
+		);
+
+		persistence.persist().unwrap();
+		let first_snapshot = ocall_api.stored_trusted_operation_pool();
+		persistence.persist().unwrap();
+
+		// A new snapshot would be encrypted with another initialization vector.
+		assert_eq!(first_snapshot, ocall_api.stored_trusted_operation_pool());
+	}
+
+	#[test]
+	fn each_snapshot_is_encrypted_with_a_fresh_initialization_vector() {
+		let pending_operations =
+			vec![(shard_id(), vec![create_direct_trusted_operation(&alice_pair(), 0)])];
+		let first_ocall_api = OnchainMock::default();
+		let second_ocall_api = OnchainMock::default();
+
+		create_persistence(pending_operations.clone(), NonceState::new(), first_ocall_api.clone())
+			.persist()
+			.unwrap();
+		create_persistence(pending_operations, NonceState::new(), second_ocall_api.clone())
+			.persist()
+			.unwrap();
+
+		assert_ne!(
+			first_ocall_api.stored_trusted_operation_pool(),
+			second_ocall_api.stored_trusted_operation_pool()
+		);
+	}
+
+	#[test]
+	fn oversized_operations_clear_the_persisted_ones() {
+		let ocall_api = OnchainMock::default();
+		create_persistence(
+			vec![(shard_id(), vec![create_direct_trusted_operation(&alice_pair(), 0)])],
+			NonceState::new(),
+			ocall_api.clone(),
+		)
+		.persist()
+		.unwrap();
+
+		let trusted_operation = create_direct_trusted_operation(&bob_pair(), 0);
+		let number_of_operations = MAX_PERSISTED_TOP_POOL_SIZE / trusted_operation.encoded_size();
+		let oversized_persistence = create_persistence(
+			vec![(shard_id(), vec![trusted_operation; number_of_operations + 1])],
+			NonceState::new(),
+			ocall_api.clone(),
+		);
+		assert!(oversized_persistence.persist().is_err());
+
+		let restoring_persistence = create_persistence(vec![], NonceState::new(), ocall_api);
+		assert_eq!(0, restoring_persistence.restore().unwrap());
+	}
+
+	#[test]
+	fn restore_without_persisted_operations_restores_nothing() {
+		let persistence = create_persistence(vec![], NonceState::new(), OnchainMock::default());
+
+		assert_eq!(0, persistence.restore().unwrap());
+	}
+
+	fn create_persistence(
+		pending_operations: PendingOperationsSnapshot,
+		state: NonceState,
+		ocall_api: OnchainMock,
+	) -> TestPersistence {
+		TopPoolPersistence::new(
+			Arc::new(SnapshotTrustedOperationsMock {
+				pending_operations,
+				restored_operations: Default::default(),
+			}),
+			Arc::new(ObserveStateMock::new(state)),
+			Arc::new(KeyRepositoryMock::new(Aes::new([3u8; 16], [4u8; 16]))),
+			Arc::new(ocall_api),
+		)
+	}
+
+	fn restored_operations(persistence: &TestPersistence) -> Vec<TrustedOperation> {
+		persistence
+			.author
+			.restored_operations
+			.read()
+			.unwrap()
+			.iter()
+			.flat_map(|(_, operations)| operations.clone())
+			.collect()
+	}
+}
@@ -18,10 +18,16 @@
 #[cfg(all(not(feature = "std"), feature = "sgx"))]
 use crate::sgx_reexport_prelude::*;
 
-use crate::error::Result;
-use ita_stf::{hash, TrustedOperation};
+use crate::{
+	error::Result,
+	sender_limits::{RpcClient, SubmissionSource},
+};
+use ita_stf::{hash, TrustedCallSigned, TrustedOperation};
 use itp_top_pool::primitives::PoolFuture;
-use itp_types::{BlockHash as SidechainBlockHash, ShardIdentifier, H256};
+use itp_types::{
+	AccountId, BlockHash as SidechainBlockHash, ShardIdentifier, TrustedOperationPoolStatus,
+	TrustedOperationStatus, H256,
+};
 use jsonrpc_core::Error as RpcError;
 use std::vec::Vec;
 
@@ -30,9 +36,14 @@ pub trait FullAuthor = AuthorApi<H256, H256> + OnBlockImported<Hash = H256> + Se
 
 /// Authoring RPC API
 pub trait AuthorApi<Hash, BlockHash> {
-	/// Submit encoded extrinsic for inclusion in block.
+	/// Submit encoded extrinsic for inclusion in block, originating from within the enclave.
 	fn submit_top(&self, extrinsic: Vec<u8>, shard: ShardIdentifier) -> PoolFuture<Hash, RpcError>;
 
+	/// Import an encoded extrinsic gossiped by a peer validateer.
+	///
+	/// Other than submitted extrinsics, imported ones are not gossiped any further.
+	fn import_top(&self, extrinsic: Vec<u8>, shard: ShardIdentifier) -> PoolFuture<Hash, RpcError>;
+
 	/// Return hash of Trusted Operation
 	fn hash_of(&self, xt: &TrustedOperation) -> Hash;
 
@@ -47,6 +58,21 @@ pub trait AuthorApi<Hash, BlockHash> {
 
 	fn get_shards(&self) -> Vec<ShardIdentifier>;
 
+	/// Returns the number and size of the ready and future operations of a shard.
+	fn get_pool_status(&self, shard: ShardIdentifier) -> TrustedOperationPoolStatus;
+
+	/// Returns whether an operation is in the ready or future queue, or `None` if it is not
+	/// pending in the pool.
+	fn get_top_status(&self, hash: &Hash, shard: ShardIdentifier)
+		-> Option<TrustedOperationStatus>;
+
+	/// Cancel a pending trusted call of `sender`, together with the operations depending on it.
+	///
+	/// The call is only removed from the pool of this validateer, the cancellation is not
+	/// gossiped to the peers the call was gossiped to.
+	/// The caller is responsible for authenticating `sender`.
+	fn cancel_top(&self, hash: Hash, shard: ShardIdentifier, sender: &AccountId) -> Result<Hash>;
+
 	/// Remove a collection of trusted operations from the pool.
 	/// Return operations that were not successfully removed.
 	fn remove_calls_from_pool(
@@ -58,8 +84,56 @@ pub trait AuthorApi<Hash, BlockHash> {
 	/// Submit an extrinsic to watch.
 	///
 	/// See [`TrustedOperationStatus`](sp_transaction_pool::TrustedOperationStatus) for details on transaction
-	/// life cycle.
-	fn watch_top(&self, ext: Vec<u8>, shard: ShardIdentifier) -> PoolFuture<Hash, RpcError>;
+	/// life cycle. RPC connections that repeatedly submit invalid extrinsics are temporarily banned.
+	fn watch_top(
+		&self,
+		ext: Vec<u8>,
+		shard: ShardIdentifier,
+		source: SubmissionSource,
+	) -> PoolFuture<Hash, RpcError>;
+
+	/// Release what is recorded for the connection of an RPC client, once it is closed.
+	fn on_connection_closed(&self, client: RpcClient);
+}
+
+/// Snapshot of the pending trusted operations, grouped by shard.
+pub type PendingOperationsSnapshot = Vec<(ShardIdentifier, Vec<TrustedOperation>)>;
+
+/// Trait to take a snapshot of the pending trusted operations and to submit them again,
+/// so they survive a restart of the worker.
+pub trait SnapshotTrustedOperations<Hash> {
+	/// Returns all pending trusted calls, ready and future ones.
+	///
+	/// Trusted getters are not included, they are only useful to the client waiting for them.
+	fn snapshot_pending_operations(&self) -> PendingOperationsSnapshot;
+
+	/// Submit a trusted operation of a snapshot to the pool again.
+	///
+	/// The operation is validated like a newly submitted one, but neither watched nor gossiped.
+	fn restore_top(
+		&self,
+		trusted_operation: TrustedOperation,
+		shard: ShardIdentifier,
+	) -> PoolFuture<Hash, RpcError>;
+}
+
+/// Trait to evict trusted operations from the pool that can no longer be included in a block.
+pub trait EvictStaleOperations {
+	/// Remove the operations of a shard that are beyond their longevity at the given sidechain
+	/// block number, their watchers are notified with `Dropped`.
+	///
+	/// Returns the number of evicted operations.
+	fn evict_expired_operations(&self, shard: ShardIdentifier, block_number: u64) -> usize;
+
+	/// Remove the trusted calls of a shard that are outdated, e.g. because their sender already
+	/// used the nonce in the state. Their watchers are notified with `Invalid`.
+	///
+	/// Returns the number of evicted operations, including the ones depending on them.
+	fn evict_outdated_calls(
+		&self,
+		shard: ShardIdentifier,
+		is_outdated: &mut dyn FnMut(&TrustedCallSigned) -> bool,
+	) -> usize;
 }
 
 /// Trait to notify listeners/observer of a newly created block
@@ -67,4 +141,7 @@ pub trait OnBlockImported {
 	type Hash;
 
 	fn on_block_imported(&self, hashes: &[Self::Hash], block_hash: SidechainBlockHash);
+
+	/// Notify of a retracted block, the operations it included are put back into the pool.
+	fn on_block_retracted(&self, block_hash: SidechainBlockHash);
 }
@@ -204,6 +204,10 @@ where
 		Box::new(self.pool.validated_pool().ready(shard))
 	}
 
+	fn futures(&self, shard: ShardIdentifier) -> Vec<Arc<Self::InPoolOperation>> {
+		self.pool.validated_pool().futures(shard)
+	}
+
 	fn shards(&self) -> Vec<ShardIdentifier> {
 		self.pool.validated_pool().shards()
 	}
@@ -217,6 +221,14 @@ where
 		self.pool.validated_pool().remove_invalid(hashes, shard, inblock)
 	}
 
+	fn remove_stale(
+		&self,
+		block_number: u64,
+		shard: ShardIdentifier,
+	) -> Vec<Arc<Self::InPoolOperation>> {
+		self.pool.validated_pool().remove_stale(block_number, shard)
+	}
+
 	fn status(&self, shard: ShardIdentifier) -> PoolStatus {
 		self.pool.validated_pool().status(shard)
 	}
@@ -244,4 +256,12 @@ where
 	fn on_block_imported(&self, hashes: &[Self::Hash], block_hash: SidechainBlockHash) {
 		self.pool.validated_pool().on_block_imported(hashes, block_hash);
 	}
+
+	fn on_block_finalized(&self, block_hash: SidechainBlockHash) {
+		self.pool.validated_pool().on_block_finalized(block_hash);
+	}
+
+	fn on_block_retracted(&self, block_hash: SidechainBlockHash) -> Vec<TxHash<Self>> {
+		self.pool.validated_pool().on_block_retracted(block_hash)
+	}
 }
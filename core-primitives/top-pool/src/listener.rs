@@ -101,7 +101,7 @@ where
 		self.fire(tx, |watcher| watcher.future());
 	}
 
-	/// TrustedOperation was dropped from the pool because of the limit.
+	/// TrustedOperation was dropped from the pool because of the limit or its longevity.
 	pub fn dropped(&mut self, tx: &H, by: Option<&H>) {
 		trace!(target: "txpool", "[{:?}] Dropped (replaced with {:?})", tx, by);
 		self.fire(tx, |watcher| match by {
@@ -118,6 +118,13 @@ where
 	/// TrustedOperation was pruned from the pool.
 	pub fn pruned(&mut self, block_hash: SidechainBlockHash, tx: &H) {
 		debug!(target: "txpool", "[{:?}] Pruned at {:?}", tx, block_hash);
+		self.in_block(tx, block_hash);
+	}
+
+	/// TrustedOperation in block.
+	///
+	/// The operation is watched until the block is finalized.
+	pub fn in_block(&mut self, tx: &H, block_hash: SidechainBlockHash) {
 		self.fire(tx, |s| s.in_block(block_hash));
 		self.finality_watchers.entry(block_hash).or_insert(vec![]).push(tx.clone());
 
@@ -130,11 +137,6 @@ where
 		}
 	}
 
-	/// TrustedOperation in block.
-	pub fn in_block(&mut self, tx: &H, block_hash: SidechainBlockHash) {
-		self.fire(tx, |s| s.in_block(block_hash));
-	}
-
 	/// The block this operation was included in has been retracted.
 	pub fn retracted(&mut self, block_hash: SidechainBlockHash) {
 		if let Some(hashes) = self.finality_watchers.remove(&block_hash) {
@@ -15,17 +15,31 @@
 
 */
 
+#[cfg(feature = "sgx")]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
 use itc_direct_rpc_server::{DirectRpcResult, RpcHash, SendRpcResponse};
 use itp_types::TrustedOperationStatus;
 use std::{marker::PhantomData, vec::Vec};
 
 pub struct RpcResponderMock<Hash> {
+	status_updates: RwLock<Vec<(Hash, TrustedOperationStatus)>>,
 	_hash: PhantomData<Hash>,
 }
 
 impl<Hash> RpcResponderMock<Hash> {
 	pub fn new() -> Self {
-		RpcResponderMock { _hash: PhantomData }
+		RpcResponderMock { status_updates: RwLock::new(Vec::new()), _hash: PhantomData }
+	}
+}
+
+impl<Hash: Clone> RpcResponderMock<Hash> {
+	/// Status updates sent to the watchers of trusted operations, in the order they were sent.
+	pub fn status_updates(&self) -> Vec<(Hash, TrustedOperationStatus)> {
+		self.status_updates.read().unwrap().clone()
 	}
 }
 
@@ -43,9 +57,10 @@ where
 
 	fn update_status_event(
 		&self,
-		_hash: Self::Hash,
-		_status_update: TrustedOperationStatus,
+		hash: Self::Hash,
+		status_update: TrustedOperationStatus,
 	) -> DirectRpcResult<()> {
+		self.status_updates.write().unwrap().push((hash, status_update));
 		Ok(())
 	}
 
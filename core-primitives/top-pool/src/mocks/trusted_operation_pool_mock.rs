@@ -49,6 +49,8 @@ use std::{boxed::Box, collections::HashMap, string::String, sync::Arc, vec, vec:
 /// To be used in unit tests
 pub struct TrustedOperationPoolMock {
 	submitted_transactions: RwLock<HashMap<ShardIdentifier, TxPayload>>,
+	future_transactions: RwLock<HashMap<ShardIdentifier, Vec<StfTrustedOperation>>>,
+	broadcasted_transactions: RwLock<Vec<TxHash<TrustedOperationPoolMock>>>,
 }
 
 /// Transaction payload
@@ -62,7 +64,11 @@ pub struct TxPayload {
 
 impl Default for TrustedOperationPoolMock {
 	fn default() -> Self {
-		TrustedOperationPoolMock { submitted_transactions: RwLock::new(HashMap::new()) }
+		TrustedOperationPoolMock {
+			submitted_transactions: RwLock::new(HashMap::new()),
+			future_transactions: RwLock::new(HashMap::new()),
+			broadcasted_transactions: RwLock::new(Vec::new()),
+		}
 	}
 }
 
@@ -72,6 +78,15 @@ impl TrustedOperationPoolMock {
 		transactions.clone()
 	}
 
+	/// Put a trusted operation into the future queue, submitted operations are always ready.
+	pub fn add_future_transaction(&self, xt: StfTrustedOperation, shard: ShardIdentifier) {
+		self.future_transactions.write().unwrap().entry(shard).or_default().push(xt);
+	}
+
+	pub fn get_broadcasted_transactions(&self) -> Vec<TxHash<TrustedOperationPoolMock>> {
+		self.broadcasted_transactions.read().unwrap().clone()
+	}
+
 	fn map_stf_top_to_tx(
 		stf_top: &StfTrustedOperation,
 	) -> Arc<TrustedOperation<TxHash<Self>, StfTrustedOperation>> {
@@ -121,7 +136,9 @@ impl TrustedOperationPool for TrustedOperationPoolMock {
 	) -> PoolFuture<TxHash<Self>, Self::Error> {
 		let mut transactions = self.submitted_transactions.write().unwrap();
 		transactions
-			.insert(shard, TxPayload { block_id: *at, source, xts: vec![xt.clone()], shard });
+			.entry(shard)
+			.and_modify(|payload| payload.xts.push(xt.clone()))
+			.or_insert_with(|| TxPayload { block_id: *at, source, xts: vec![xt.clone()], shard });
 
 		let top_hash = hash_of_top(&xt);
 
@@ -164,6 +181,14 @@ impl TrustedOperationPool for TrustedOperationPoolMock {
 		Box::new(ready_transactions.into_iter())
 	}
 
+	fn futures(&self, shard: ShardIdentifier) -> Vec<Arc<Self::InPoolOperation>> {
+		let future_transactions = self.future_transactions.read().unwrap();
+		future_transactions
+			.get(&shard)
+			.map(|xts| xts.iter().map(Self::map_stf_top_to_tx).collect())
+			.unwrap_or_default()
+	}
+
 	fn shards(&self) -> Vec<ShardIdentifier> {
 		let transactions = self.submitted_transactions.read().unwrap();
 		transactions.iter().map(|(shard, _)| *shard).collect()
@@ -171,32 +196,52 @@ impl TrustedOperationPool for TrustedOperationPoolMock {
 
 	fn remove_invalid(
 		&self,
-		_hashes: &[TxHash<Self>],
-		_shard: ShardIdentifier,
+		hashes: &[TxHash<Self>],
+		shard: ShardIdentifier,
 		_inblock: bool,
+	) -> Vec<Arc<Self::InPoolOperation>> {
+		let mut transactions = self.submitted_transactions.write().unwrap();
+		let payload = match transactions.get_mut(&shard) {
+			Some(payload) => payload,
+			None => return Vec::new(),
+		};
+		let (removed, retained): (Vec<_>, Vec<_>) =
+			payload.xts.drain(..).partition(|xt| hashes.contains(&hash_of_top(xt)));
+		payload.xts = retained;
+		removed.iter().map(Self::map_stf_top_to_tx).collect()
+	}
+
+	fn remove_stale(
+		&self,
+		_block_number: u64,
+		_shard: ShardIdentifier,
 	) -> Vec<Arc<Self::InPoolOperation>> {
 		Vec::new()
 	}
 
 	fn status(&self, shard: ShardIdentifier) -> PoolStatus {
 		let transactions = self.submitted_transactions.read().unwrap();
+		let future = self.future_transactions.read().unwrap().get(&shard).map_or(0, Vec::len);
 		transactions
 			.get(&shard)
 			.map(|payload| PoolStatus {
 				ready: payload.xts.len(),
 				ready_bytes: 0,
-				future: 0,
+				future,
 				future_bytes: 0,
 			})
-			.unwrap_or_else(default_pool_status)
+			.unwrap_or_else(|| PoolStatus { future, ..default_pool_status() })
 	}
 
 	fn import_notification_stream(&self) -> ImportNotificationStream<TxHash<Self>> {
 		unimplemented!()
 	}
 
-	fn on_broadcasted(&self, _propagations: HashMap<TxHash<Self>, Vec<String>>) {
-		unimplemented!()
+	fn on_broadcasted(&self, propagations: HashMap<TxHash<Self>, Vec<String>>) {
+		self.broadcasted_transactions
+			.write()
+			.unwrap()
+			.extend(propagations.keys().cloned());
 	}
 
 	fn hash_of(&self, xt: &StfTrustedOperation) -> TxHash<Self> {
@@ -205,13 +250,19 @@ impl TrustedOperationPool for TrustedOperationPoolMock {
 
 	fn ready_transaction(
 		&self,
-		_hash: &TxHash<Self>,
-		_shard: ShardIdentifier,
+		hash: &TxHash<Self>,
+		shard: ShardIdentifier,
 	) -> Option<Arc<Self::InPoolOperation>> {
-		unimplemented!()
+		self.ready(shard).find(|o| &o.hash == hash)
 	}
 
 	fn on_block_imported(&self, _hashes: &[Self::Hash], _block_hash: SidechainBlockHash) {}
+
+	fn on_block_finalized(&self, _block_hash: SidechainBlockHash) {}
+
+	fn on_block_retracted(&self, _block_hash: SidechainBlockHash) -> Vec<TxHash<Self>> {
+		Vec::new()
+	}
 }
 
 fn default_pool_status() -> PoolStatus {
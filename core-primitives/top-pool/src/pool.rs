@@ -31,7 +31,7 @@ use itp_types::BlockHash as SidechainBlockHash;
 use jsonrpc_core::futures::{channel::mpsc::Receiver, future, Future};
 use sp_runtime::{
 	generic::BlockId,
-	traits::{self, Block as BlockT, SaturatedConversion},
+	traits::{self, Block as BlockT},
 	transaction_validity::{TransactionTag as Tag, TransactionValidity, TransactionValidityError},
 };
 use std::{collections::HashMap, format, sync::Arc, time::Instant, vec::Vec};
@@ -196,10 +196,7 @@ where
 		xt: StfTrustedOperation,
 		shard: ShardIdentifier,
 	) -> Result<ExtrinsicHash<B>, B::Error> {
-		//TODO
-		//let block_number = self.resolve_block_number(at)?;
-		// dummy value:
-		let block_number = 0;
+		let block_number = self.validated_pool.block_number(shard);
 		let (_, tx) = self
 			.verify_one(at, block_number, source, xt, CheckBannedBeforeVerify::Yes, shard)
 			.await;
@@ -387,12 +384,8 @@ where
 		check: CheckBannedBeforeVerify,
 		shard: ShardIdentifier,
 	) -> Result<HashMap<ExtrinsicHash<B>, ValidatedOperationFor<B>>, B::Error> {
-		//FIXME: Nicer verify
-		// we need a block number to compute tx validity
-		//let block_number = self.resolve_block_number(at)?;
-		// dummy blocknumber
-		//pub type NumberFor<A> = traits::NumberFor<<A as ChainApi>::Block>;
-		let block_number = 0;
+		// Operations are valid from the latest sidechain block of the shard on.
+		let block_number = self.validated_pool.block_number(shard);
 
 		let res = future::join_all(
 			xts.into_iter()
@@ -409,8 +402,7 @@ where
 	async fn verify_one(
 		&self,
 		_block_id: &BlockId<B::Block>,
-		//block_number: NumberFor<B>,
-		block_number: i8,
+		block_number: u64,
 		source: TrustedOperationSource,
 		xt: StfTrustedOperation,
 		check: CheckBannedBeforeVerify,
@@ -438,14 +430,7 @@ where
 				if validity.provides.is_empty() {
 					ValidatedOperation::Invalid(hash, error::Error::NoTagsProvided.into())
 				} else {
-					ValidatedOperation::valid_at(
-						block_number.saturated_into::<u64>(),
-						hash,
-						source,
-						xt,
-						bytes,
-						validity,
-					)
+					ValidatedOperation::valid_at(block_number, hash, source, xt, bytes, validity)
 				},
 			Err(TransactionValidityError::Invalid(_e)) =>
 				ValidatedOperation::Invalid(hash, error::Error::InvalidTrustedOperation.into()),
@@ -482,7 +467,7 @@ pub mod tests {
 	};
 	use codec::{Decode, Encode};
 	use ita_stf::{Index, TrustedCall, TrustedCallSigned, TrustedOperation};
-	use itp_types::Header;
+	use itp_types::{Header, TrustedOperationStatus};
 	use jsonrpc_core::{futures, futures::executor::block_on};
 	use parity_util_mem::MallocSizeOf;
 	use serde::Serialize;
@@ -971,4 +956,179 @@ pub mod tests {
 		assert_eq!(pool.validated_pool().status(shard).future, 0);
 		assert!(matches!(err, error::Error::NoTagsProvided));
 	}
+
+	#[test]
+	pub fn test_should_re_add_operations_of_retracted_block() {
+		// given
+		let rpc_responder = Arc::new(TestRpcResponder::new());
+		let pool = Pool::new(Default::default(), TestApi::default().into(), rpc_responder.clone());
+		let shard = ShardIdentifier::default();
+		let block_hash = H256::from_low_u64_be(1);
+		let hash = block_on(pool.submit_and_watch(
+			&BlockId::Number(0),
+			SOURCE,
+			to_top(
+				TrustedCall::balance_transfer(
+					tests::AccountId::from_h256(from_low_u64_to_be_h256(1)).into(),
+					tests::AccountId::from_h256(from_low_u64_to_be_h256(2)).into(),
+					5,
+				),
+				0,
+			),
+			shard,
+		))
+		.unwrap();
+		pool.validated_pool().on_block_imported(&[hash], block_hash);
+		pool.validated_pool().remove_invalid(&[hash], shard, true);
+		assert_eq!(pool.validated_pool().status(shard).ready, 0);
+
+		// when
+		let re_added_hashes = pool.validated_pool().on_block_retracted(block_hash);
+
+		// then
+		assert_eq!(re_added_hashes, vec![hash]);
+		assert_eq!(
+			pool.validated_pool().ready(shard).map(|v| v.hash).collect::<Vec<_>>(),
+			vec![hash]
+		);
+		assert_eq!(
+			rpc_responder.status_updates(),
+			vec![
+				(hash, TrustedOperationStatus::InSidechainBlock(block_hash)),
+				(hash, TrustedOperationStatus::Retracted),
+				(hash, TrustedOperationStatus::Ready),
+			]
+		);
+	}
+
+	#[test]
+	pub fn test_should_keep_dependent_operations_of_included_operations() {
+		// given
+		let pool = test_pool();
+		let shard = ShardIdentifier::default();
+		let submit_with_nonce = |nonce| {
+			block_on(pool.submit_one(
+				&BlockId::Number(0),
+				SOURCE,
+				to_top(
+					TrustedCall::balance_transfer(
+						tests::AccountId::from_h256(from_low_u64_to_be_h256(1)).into(),
+						tests::AccountId::from_h256(from_low_u64_to_be_h256(2)).into(),
+						5,
+					),
+					nonce,
+				),
+				shard,
+			))
+			.unwrap()
+		};
+		let included_hash = submit_with_nonce(0);
+		let dependent_hash = submit_with_nonce(1);
+
+		// when
+		let removed = pool.validated_pool().remove_invalid(&[included_hash], shard, true);
+
+		// then
+		assert_eq!(removed.into_iter().map(|o| o.hash).collect::<Vec<_>>(), vec![included_hash]);
+		assert_eq!(
+			pool.validated_pool().ready(shard).map(|v| v.hash).collect::<Vec<_>>(),
+			vec![dependent_hash]
+		);
+	}
+
+	#[test]
+	pub fn test_should_not_re_add_operations_of_finalized_block() {
+		// given
+		let pool = test_pool();
+		let shard = ShardIdentifier::default();
+		let block_hash = H256::from_low_u64_be(1);
+		let hash = block_on(pool.submit_one(
+			&BlockId::Number(0),
+			SOURCE,
+			to_top(
+				TrustedCall::balance_transfer(
+					tests::AccountId::from_h256(from_low_u64_to_be_h256(1)).into(),
+					tests::AccountId::from_h256(from_low_u64_to_be_h256(2)).into(),
+					5,
+				),
+				0,
+			),
+			shard,
+		))
+		.unwrap();
+		pool.validated_pool().on_block_imported(&[hash], block_hash);
+		pool.validated_pool().remove_invalid(&[hash], shard, true);
+		pool.validated_pool().on_block_finalized(block_hash);
+
+		// when
+		let re_added_hashes = pool.validated_pool().on_block_retracted(block_hash);
+
+		// then
+		assert!(re_added_hashes.is_empty());
+		assert_eq!(pool.validated_pool().status(shard).ready, 0);
+	}
+
+	#[test]
+	pub fn test_should_drop_operations_beyond_their_longevity() {
+		// given
+		let rpc_responder = Arc::new(TestRpcResponder::new());
+		let pool = Pool::new(Default::default(), TestApi::default().into(), rpc_responder.clone());
+		let shard = ShardIdentifier::default();
+		let hash = block_on(pool.submit_and_watch(
+			&BlockId::Number(0),
+			SOURCE,
+			to_top(
+				TrustedCall::balance_transfer(
+					tests::AccountId::from_h256(from_low_u64_to_be_h256(1)).into(),
+					tests::AccountId::from_h256(from_low_u64_to_be_h256(2)).into(),
+					5,
+				),
+				0,
+			),
+			shard,
+		))
+		.unwrap();
+
+		// when
+		let still_valid = pool.validated_pool().remove_stale(2, shard);
+		let stale = pool.validated_pool().remove_stale(3, shard);
+
+		// then
+		assert!(still_valid.is_empty());
+		assert_eq!(stale.iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![hash]);
+		assert_eq!(pool.validated_pool().status(shard).ready, 0);
+		assert!(pool.validated_pool().is_banned(&hash));
+		assert_eq!(
+			rpc_responder.status_updates().last(),
+			Some(&(hash, TrustedOperationStatus::Dropped))
+		);
+	}
+
+	#[test]
+	pub fn test_should_count_longevity_from_latest_block_number() {
+		// given
+		let pool = test_pool();
+		let shard = ShardIdentifier::default();
+		pool.validated_pool().remove_stale(10, shard);
+
+		// when
+		block_on(pool.submit_one(
+			&BlockId::Number(0),
+			SOURCE,
+			to_top(
+				TrustedCall::balance_transfer(
+					tests::AccountId::from_h256(from_low_u64_to_be_h256(1)).into(),
+					tests::AccountId::from_h256(from_low_u64_to_be_h256(2)).into(),
+					5,
+				),
+				0,
+			),
+			shard,
+		))
+		.unwrap();
+
+		// then
+		assert!(pool.validated_pool().remove_stale(12, shard).is_empty());
+		assert_eq!(pool.validated_pool().remove_stale(13, shard).len(), 1);
+	}
 }
@@ -106,7 +106,7 @@ pub enum TrustedOperationStatus<Hash, BlockHash> {
 	/// TrustedOperation has been replaced in the pool, by another operation
 	/// that provides the same tags. (e.g. same (sender, nonce)).
 	Usurped(Hash),
-	/// TrustedOperation has been dropped from the pool because of the limit.
+	/// TrustedOperation has been dropped from the pool because of the limit or its longevity.
 	Dropped,
 	/// TrustedOperation is no longer valid in the current state.
 	Invalid,
@@ -223,6 +223,9 @@ pub trait TrustedOperationPool: Send + Sync {
 		shard: ShardIdentifier,
 	) -> Box<dyn Iterator<Item = Arc<Self::InPoolOperation>> + Send>;
 
+	/// Get all future operations, i.e. those waiting for the tags they require.
+	fn futures(&self, shard: ShardIdentifier) -> Vec<Arc<Self::InPoolOperation>>;
+
 	/// Get an iterator over all shards.
 	fn shards(&self) -> Vec<ShardIdentifier>;
 
@@ -235,6 +238,14 @@ pub trait TrustedOperationPool: Send + Sync {
 		inblock: bool,
 	) -> Vec<Arc<Self::InPoolOperation>>;
 
+	/// Remove the operations of a shard that are beyond their longevity at the given sidechain
+	/// block number, watchers are notified with `Dropped`.
+	fn remove_stale(
+		&self,
+		block_number: u64,
+		shard: ShardIdentifier,
+	) -> Vec<Arc<Self::InPoolOperation>>;
+
 	// *** logging
 	/// Returns pool status.
 	fn status(&self, shard: ShardIdentifier) -> PoolStatus;
@@ -259,6 +270,14 @@ pub trait TrustedOperationPool: Send + Sync {
 
 	/// Notify the listener of top inclusion in sidechain block
 	fn on_block_imported(&self, hashes: &[Self::Hash], block_hash: SidechainBlockHash);
+
+	/// Notify the listener of the finalization of a sidechain block
+	fn on_block_finalized(&self, block_hash: SidechainBlockHash);
+
+	/// Notify the listener of a retracted sidechain block and put its operations back into the pool.
+	///
+	/// Returns the hashes of the operations that were re-added.
+	fn on_block_retracted(&self, block_hash: SidechainBlockHash) -> Vec<TxHash<Self>>;
 }
 
 /// The source of the transaction.
@@ -44,6 +44,7 @@ use ita_stf::{ShardIdentifier, TrustedOperation as StfTrustedOperation};
 use itc_direct_rpc_server::SendRpcResponse;
 use itp_types::BlockHash as SidechainBlockHash;
 use jsonrpc_core::futures::channel::mpsc::{channel, Sender};
+use linked_hash_map::LinkedHashMap;
 use sp_runtime::{
 	generic::BlockId,
 	traits::{self, SaturatedConversion},
@@ -100,6 +101,13 @@ impl<Hash, Ex, Error> ValidatedOperation<Hash, Ex, Error> {
 pub type ValidatedOperationFor<B> =
 	ValidatedOperation<ExtrinsicHash<B>, StfTrustedOperation, <B as ChainApi>::Error>;
 
+/// Maximum number of recently imported blocks whose operations are kept, in case the block is
+/// retracted.
+const MAX_RETRACTABLE_BLOCKS: usize = 64;
+
+/// Operations included in a sidechain block, together with the shard they belong to.
+type InBlockOperations<B> = Vec<(ShardIdentifier, TransactionFor<B>)>;
+
 /// Pool that deals with validated operations.
 pub struct ValidatedPool<B: ChainApi, R: SendRpcResponse>
 where
@@ -111,6 +119,11 @@ where
 	pool: RwLock<base::BasePool<ExtrinsicHash<B>, StfTrustedOperation>>,
 	import_notification_sinks: Mutex<Vec<Sender<ExtrinsicHash<B>>>>,
 	rotator: PoolRotator<ExtrinsicHash<B>>,
+	/// Operations of recently imported, not yet finalized blocks, so they can be
+	/// put back into the pool if the block is retracted.
+	in_block_operations: RwLock<LinkedHashMap<SidechainBlockHash, InBlockOperations<B>>>,
+	/// Latest sidechain block number of each shard, newly submitted operations are valid from.
+	block_numbers: RwLock<HashMap<ShardIdentifier, u64>>,
 }
 
 impl<B: ChainApi, R> ValidatedPool<B, R>
@@ -127,6 +140,8 @@ where
 			pool: RwLock::new(base_pool),
 			import_notification_sinks: Default::default(),
 			rotator: Default::default(),
+			in_block_operations: RwLock::new(LinkedHashMap::new()),
+			block_numbers: Default::default(),
 		}
 	}
 
@@ -555,16 +570,30 @@ where
 			.block_id_to_number(at)?
 			.ok_or_else(|| error::Error::InvalidBlockId(format!("{:?}", at)))?
 			.saturated_into::<u64>();
+		self.remove_stale(block_number, shard);
+		Ok(())
+	}
+
+	/// Removes the operations of a shard that are beyond their longevity at the given
+	/// sidechain block number.
+	///
+	/// The stale operations are temporarily banned and their watchers are notified with `Dropped`.
+	/// Operations submitted afterwards are valid from `block_number` on.
+	pub fn remove_stale(
+		&self,
+		block_number: u64,
+		shard: ShardIdentifier,
+	) -> Vec<TransactionFor<B>> {
+		self.block_numbers.write().unwrap().insert(shard, block_number);
+
 		let now = Instant::now();
-		let to_remove = {
-			self.ready(shard)
+		let stale_hashes = {
+			let p = self.pool.read().unwrap();
+			let mut hashes: Vec<ExtrinsicHash<B>> = p
+				.ready(shard)
 				.filter(|tx| self.rotator.ban_if_stale(&now, block_number, tx))
 				.map(|tx| tx.hash)
-				.collect::<Vec<_>>()
-		};
-		let futures_to_remove: Vec<ExtrinsicHash<B>> = {
-			let p = self.pool.read().unwrap();
-			let mut hashes = Vec::new();
+				.collect();
 			for tx in p.futures(shard) {
 				if self.rotator.ban_if_stale(&now, block_number, tx) {
 					hashes.push(tx.hash);
@@ -572,13 +601,29 @@ where
 			}
 			hashes
 		};
-		// removing old operations
-		self.remove_invalid(&to_remove, shard, false);
-		self.remove_invalid(&futures_to_remove, shard, false);
+
+		let stale = if stale_hashes.is_empty() {
+			Vec::new()
+		} else {
+			self.pool.write().unwrap().remove_subtree(&stale_hashes, shard)
+		};
+		if !stale.is_empty() {
+			log::debug!(target: "txpool", "Removed stale operations: {:?}", stale);
+		}
+
+		let mut listener = self.listener.write().unwrap();
+		for tx in &stale {
+			listener.dropped(&tx.hash, None);
+		}
 		// clear banned operations timeouts
 		self.rotator.clear_timeouts(&now);
 
-		Ok(())
+		stale
+	}
+
+	/// Latest sidechain block number of the shard, as given to `remove_stale`.
+	pub fn block_number(&self, shard: ShardIdentifier) -> u64 {
+		self.block_numbers.read().unwrap().get(&shard).copied().unwrap_or_default()
 	}
 
 	/// Get rotator reference.
@@ -618,6 +663,9 @@ where
 	/// to prevent them from entering the pool right away.
 	/// Note this is not the case for the dependent operations - those may
 	/// still be valid so we want to be able to re-import them.
+	///
+	/// Operations that were included in a block (`inblock`) are pruned by the tags they provide
+	/// instead, so the operations depending on them stay in the pool.
 	pub fn remove_invalid(
 		&self,
 		hashes: &[ExtrinsicHash<B>],
@@ -629,6 +677,10 @@ where
 			return vec![]
 		}
 
+		if inblock {
+			return self.remove_included(hashes, shard)
+		}
+
 		let invalid = self.pool.write().unwrap().remove_subtree(hashes, shard);
 
 		log::debug!(target: "txpool", "Removed invalid operations: {:?}", invalid);
@@ -649,11 +701,43 @@ where
 		invalid
 	}
 
+	/// Remove operations that were included in a block, by pruning the tags they provide.
+	fn remove_included(
+		&self,
+		hashes: &[ExtrinsicHash<B>],
+		shard: ShardIdentifier,
+	) -> Vec<TransactionFor<B>> {
+		let provided_tags: Vec<Tag> = self
+			.pool
+			.read()
+			.unwrap()
+			.by_hashes(hashes, shard)
+			.into_iter()
+			.flatten()
+			.flat_map(|operation| operation.provides.clone())
+			.collect();
+
+		let mut removed = self
+			.prune_tags(provided_tags, shard)
+			.map(|status| status.pruned)
+			.unwrap_or_default();
+		// Operations in the future queue are not pruned by their tags.
+		removed.extend(self.pool.write().unwrap().remove_subtree(hashes, shard));
+
+		log::debug!(target: "txpool", "Removed included operations: {:?}", removed);
+		removed
+	}
+
 	/// Get an iterator for ready operations ordered by priority
 	pub fn ready(&self, shard: ShardIdentifier) -> impl Iterator<Item = TransactionFor<B>> + Send {
 		self.pool.read().unwrap().ready(shard)
 	}
 
+	/// Get all future operations, i.e. those waiting for the tags they require
+	pub fn futures(&self, shard: ShardIdentifier) -> Vec<TransactionFor<B>> {
+		self.pool.read().unwrap().futures(shard).cloned().map(Arc::new).collect()
+	}
+
 	/// Get an iterator for all shards
 	pub fn shards(&self) -> Vec<ShardIdentifier> {
 		let mut shards = vec![];
@@ -671,25 +755,86 @@ where
 	}
 
 	/// Notify all watchers that operations in the block with hash have been finalized
-	pub async fn on_block_finalized(&self, block_hash: SidechainBlockHash) -> Result<(), B::Error>
-	where
-		<<B as ChainApi>::Block as sp_runtime::traits::Block>::Hash: core::fmt::Display,
-	{
+	pub fn on_block_finalized(&self, block_hash: SidechainBlockHash) {
 		log::trace!(target: "txpool", "Attempting to notify watchers of finalization for {}", block_hash);
 		self.listener.write().unwrap().finalized(block_hash);
-		Ok(())
+		self.in_block_operations.write().unwrap().remove(&block_hash);
 	}
 
-	/// Notify the listener of retracted blocks
-	pub fn on_block_retracted(&self, block_hash: SidechainBlockHash) {
-		self.listener.write().unwrap().retracted(block_hash)
+	/// Notify the listener of a retracted block and put its operations back into the pool.
+	///
+	/// Watchers are notified with `Retracted`, followed by the status of the re-added operation.
+	/// Returns the hashes of the operations that were re-added.
+	pub fn on_block_retracted(&self, block_hash: SidechainBlockHash) -> Vec<ExtrinsicHash<B>> {
+		self.listener.write().unwrap().retracted(block_hash);
+
+		let retracted_operations = self
+			.in_block_operations
+			.write()
+			.unwrap()
+			.remove(&block_hash)
+			.unwrap_or_default();
+
+		let mut operations_per_shard: HashMap<ShardIdentifier, Vec<ValidatedOperationFor<B>>> =
+			HashMap::new();
+		for (shard, operation) in retracted_operations {
+			let operation = match Arc::try_unwrap(operation) {
+				Ok(operation) => operation,
+				Err(operation) => operation.duplicate(),
+			};
+			operations_per_shard
+				.entry(shard)
+				.or_default()
+				.push(ValidatedOperation::Valid(operation));
+		}
+
+		let mut re_added_hashes = Vec::new();
+		for (shard, operations) in operations_per_shard {
+			for result in self.submit(operations, shard) {
+				match result {
+					Ok(hash) => re_added_hashes.push(hash),
+					Err(e) => log::debug!(
+						target: "txpool",
+						"Could not re-add operation of retracted block {:?}: {:?}",
+						block_hash,
+						e
+					),
+				}
+			}
+		}
+		re_added_hashes
 	}
 
 	/// Notify the listener of top inclusion in sidechain block
+	///
+	/// The included operations are kept until the block is finalized, in case it is retracted.
 	pub fn on_block_imported(&self, hashes: &[ExtrinsicHash<B>], block_hash: SidechainBlockHash) {
 		for top_hash in hashes.iter() {
 			self.listener.write().unwrap().in_block(top_hash, block_hash);
 		}
+
+		let included_operations: InBlockOperations<B> = {
+			let pool = self.pool.read().unwrap();
+			let shards: Vec<ShardIdentifier> = pool.get_shards().cloned().collect();
+			shards
+				.into_iter()
+				.flat_map(|shard| {
+					pool.by_hashes(hashes, shard)
+						.into_iter()
+						.flatten()
+						.map(move |operation| (shard, operation))
+				})
+				.collect()
+		};
+		if included_operations.is_empty() {
+			return
+		}
+
+		let mut in_block_operations = self.in_block_operations.write().unwrap();
+		in_block_operations.insert(block_hash, included_operations);
+		while in_block_operations.len() > MAX_RETRACTABLE_BLOCKS {
+			in_block_operations.pop_front();
+		}
 	}
 }
 
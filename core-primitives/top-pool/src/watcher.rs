@@ -70,9 +70,10 @@ where
 	}
 
 	/// Extrinsic has been included in block with given hash.
+	///
+	/// Further updates follow once the block is finalized (or retracted).
 	pub fn in_block(&mut self, block_hash: SidechainBlockHash) {
 		self.send(TrustedOperationStatus::InSidechainBlock(block_hash));
-		self.is_in_block = true;
 	}
 
 	/// Extrinsic has been finalized by a finality gadget.
@@ -102,7 +103,7 @@ where
 		self.is_in_block = true;
 	}
 
-	/// TrustedOperation has been dropped from the pool because of the limit.
+	/// TrustedOperation has been dropped from the pool because of the limit or its longevity.
 	pub fn dropped(&mut self) {
 		self.send(TrustedOperationStatus::Dropped);
 		self.is_in_block = true;
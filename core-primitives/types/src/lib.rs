@@ -22,6 +22,7 @@ use crate::storage::StorageEntry;
 use codec::{Decode, Encode};
 #[cfg(feature = "sgx")]
 use sgx_tstd as std;
+use sp_runtime::traits::Verify;
 use sp_std::vec::Vec;
 
 pub mod storage;
@@ -72,6 +73,191 @@ pub struct Request {
 	pub cyphertext: Vec<u8>,
 }
 
+/// Request to fetch a range of sidechain blocks (by block number) from a specific peer.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct PeerBlockRangeRequest {
+	/// Url of the peer, as registered on the parentchain.
+	pub peer_url: PalletString,
+	/// Number of the first block to fetch.
+	pub from_block_number: SidechainBlockNumber,
+	/// Maximum number of blocks to fetch.
+	pub limit: u64,
+}
+
+/// Limits the content of a single sidechain block must not exceed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BlockLimits {
+	/// Maximum number of trusted operations included in a block.
+	pub max_trusted_operations: usize,
+	/// Maximum size of the encoded signed block in bytes.
+	pub max_encoded_size: usize,
+	/// Maximum accumulated dispatch weight of the trusted calls included in a block.
+	pub max_weight: u64,
+}
+
+impl BlockLimits {
+	pub fn new(max_trusted_operations: usize, max_encoded_size: usize, max_weight: u64) -> Self {
+		BlockLimits { max_trusted_operations, max_encoded_size, max_weight }
+	}
+
+	/// Limits that are never exceeded.
+	pub fn unlimited() -> Self {
+		BlockLimits::new(usize::MAX, usize::MAX, u64::MAX)
+	}
+}
+
+/// Operator command to control the sidechain block production of a shard.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BlockProductionCommand {
+	Suspend,
+	Resume,
+}
+
+/// Domain tag of the payload signed for a [`BlockProductionAdminRequest`], so that a signature of
+/// the admin account can not be reused in any other context.
+pub const BLOCK_PRODUCTION_ADMIN_REQUEST_DOMAIN: &[u8] = b"integritee:block-production-admin";
+
+/// Admin request to suspend or resume the sidechain block production of a shard.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct BlockProductionAdminRequest {
+	pub shard: ShardIdentifier,
+	pub command: BlockProductionCommand,
+	/// Has to be the next admin nonce of the validateer, see
+	/// [`BlockProductionStatus::next_admin_nonce`]. A signed request is accepted only once.
+	pub nonce: u64,
+	/// Unix epoch in milliseconds, limits the time a signed request is valid.
+	pub timestamp: u64,
+}
+
+impl BlockProductionAdminRequest {
+	pub fn new(
+		shard: ShardIdentifier,
+		command: BlockProductionCommand,
+		nonce: u64,
+		timestamp: u64,
+	) -> Self {
+		BlockProductionAdminRequest { shard, command, nonce, timestamp }
+	}
+
+	/// Payload signed by the admin account, the request prefixed with its domain tag.
+	pub fn signature_payload(&self) -> Vec<u8> {
+		(BLOCK_PRODUCTION_ADMIN_REQUEST_DOMAIN, self).encode()
+	}
+}
+
+/// [`BlockProductionAdminRequest`] signed by the admin account of a validateer.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct SignedBlockProductionAdminRequest {
+	pub request: BlockProductionAdminRequest,
+	pub signer: AccountId,
+	pub signature: Signature,
+}
+
+impl SignedBlockProductionAdminRequest {
+	pub fn new(
+		request: BlockProductionAdminRequest,
+		signer: AccountId,
+		signature: Signature,
+	) -> Self {
+		SignedBlockProductionAdminRequest { request, signer, signature }
+	}
+
+	/// Verifies that the request has been signed by `admin` and that its timestamp does not
+	/// deviate more than `validity` milliseconds from `now`.
+	///
+	/// The nonce has to be checked by the receiver, it knows the next admin nonce.
+	pub fn verify(&self, admin: &AccountId, now: u64, validity: u64) -> bool {
+		&self.signer == admin
+			&& self.request.timestamp.abs_diff(now) <= validity
+			&& self.signature.verify(self.request.signature_payload().as_slice(), &self.signer)
+	}
+}
+
+/// Sidechain block production state of a shard.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct BlockProductionStatus {
+	/// Block production has been suspended by the admin of the validateer.
+	pub suspended_by_admin: bool,
+	/// Block production is suspended while the validateer syncs sidechain blocks.
+	pub suspended_for_sync: bool,
+	/// Nonce the next admin request to the validateer has to use.
+	pub next_admin_nonce: u64,
+}
+
+impl BlockProductionStatus {
+	pub fn is_suspended(&self) -> bool {
+		self.suspended_by_admin || self.suspended_for_sync
+	}
+}
+
+/// Domain tag of the payload signed for a [`TrustedOperationCancelRequest`], so that a signature
+/// of the sender can not be reused in any other context.
+pub const TRUSTED_OPERATION_CANCEL_REQUEST_DOMAIN: &[u8] = b"integritee:trusted-operation-cancel";
+
+/// Request to cancel a pending trusted operation in the pool of a validateer.
+///
+/// Cancelling is local to the validateer the request is sent to. The operation has been gossiped
+/// to the other validateers when it was submitted, so the request has to be sent to each of them.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct TrustedOperationCancelRequest {
+	pub shard: ShardIdentifier,
+	/// Hash of the trusted operation to be cancelled.
+	pub operation_hash: H256,
+	/// Unix epoch in milliseconds, limits the time a signed request is valid.
+	pub timestamp: u64,
+}
+
+impl TrustedOperationCancelRequest {
+	pub fn new(shard: ShardIdentifier, operation_hash: H256, timestamp: u64) -> Self {
+		TrustedOperationCancelRequest { shard, operation_hash, timestamp }
+	}
+
+	/// Payload signed by the sender, the request prefixed with its domain tag.
+	pub fn signature_payload(&self) -> Vec<u8> {
+		(TRUSTED_OPERATION_CANCEL_REQUEST_DOMAIN, self).encode()
+	}
+}
+
+/// [`TrustedOperationCancelRequest`] signed by the sender of the trusted operation.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct SignedTrustedOperationCancelRequest {
+	pub request: TrustedOperationCancelRequest,
+	pub signer: AccountId,
+	pub signature: Signature,
+}
+
+impl SignedTrustedOperationCancelRequest {
+	pub fn new(
+		request: TrustedOperationCancelRequest,
+		signer: AccountId,
+		signature: Signature,
+	) -> Self {
+		SignedTrustedOperationCancelRequest { request, signer, signature }
+	}
+
+	/// Verifies that the request has been signed by `signer` and that its timestamp does not
+	/// deviate more than `validity` milliseconds from `now`.
+	///
+	/// Whether `signer` is the sender of the trusted operation has to be checked by the pool.
+	pub fn verify(&self, now: u64, validity: u64) -> bool {
+		self.request.timestamp.abs_diff(now) <= validity
+			&& self.signature.verify(self.request.signature_payload().as_slice(), &self.signer)
+	}
+}
+
+/// Number and size of the trusted operations in the pool of a shard.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct TrustedOperationPoolStatus {
+	/// Number of operations in the ready queue.
+	pub ready: u64,
+	/// Sum of bytes of the ready operation encodings.
+	pub ready_bytes: u64,
+	/// Number of operations in the future queue.
+	pub future: u64,
+	/// Sum of bytes of the future operation encodings.
+	pub future_bytes: u64,
+}
+
 // Todo: move this improved enclave definition into a primitives crate in the pallet_teerex repo.
 #[derive(Encode, Decode, Clone, PartialEq, sp_core::RuntimeDebug)]
 pub struct EnclaveGen<AccountId> {
@@ -121,7 +307,7 @@ pub enum TrustedOperationStatus {
 	/// TrustedOperation has been replaced in the pool, by another operation
 	/// that provides the same tags. (e.g. same (sender, nonce)).
 	Usurped,
-	/// TrustedOperation has been dropped from the pool because of the limit.
+	/// TrustedOperation has been dropped from the pool because of the limit or its longevity.
 	Dropped,
 	/// TrustedOperation is no longer valid in the current state.
 	Invalid,
@@ -148,6 +334,7 @@ impl From<WorkerResponse<Vec<u8>>> for StorageEntry<Vec<u8>> {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use sp_core::{sr25519, Pair};
 
 	#[test]
 	fn opaque_call_encodes_correctly() {
@@ -155,4 +342,89 @@ mod tests {
 		let call = OpaqueCall::from_tuple(&call_tuple);
 		assert_eq!(call.encode(), call_tuple.encode())
 	}
+
+	#[test]
+	fn admin_request_signed_by_admin_verifies() {
+		let admin = admin_pair();
+		let signed_request = sign_admin_request(&admin, 1_000);
+
+		assert!(signed_request.verify(&admin.public().into(), 1_500, 1_000));
+	}
+
+	#[test]
+	fn admin_request_signed_by_other_account_does_not_verify() {
+		let signed_request = sign_admin_request(&sr25519::Pair::from_seed(&[2u8; 32]), 1_000);
+
+		assert!(!signed_request.verify(&admin_pair().public().into(), 1_000, 1_000));
+	}
+
+	#[test]
+	fn admin_request_with_tampered_payload_does_not_verify() {
+		let admin = admin_pair();
+		let mut signed_request = sign_admin_request(&admin, 1_000);
+		signed_request.request.command = BlockProductionCommand::Resume;
+
+		assert!(!signed_request.verify(&admin.public().into(), 1_000, 1_000));
+	}
+
+	#[test]
+	fn admin_request_signed_without_domain_tag_does_not_verify() {
+		let admin = admin_pair();
+		let mut signed_request = sign_admin_request(&admin, 1_000);
+		signed_request.signature = admin.sign(signed_request.request.encode().as_slice()).into();
+
+		assert!(!signed_request.verify(&admin.public().into(), 1_000, 1_000));
+	}
+
+	#[test]
+	fn outdated_admin_request_does_not_verify() {
+		let admin = admin_pair();
+		let signed_request = sign_admin_request(&admin, 1_000);
+
+		assert!(!signed_request.verify(&admin.public().into(), 2_001, 1_000));
+	}
+
+	#[test]
+	fn cancel_request_signed_by_signer_verifies() {
+		let signer = admin_pair();
+		let signed_request = sign_cancel_request(&signer, signer.public().into());
+
+		assert!(signed_request.verify_signature());
+	}
+
+	#[test]
+	fn cancel_request_signed_for_other_account_does_not_verify() {
+		let other_account = sr25519::Pair::from_seed(&[2u8; 32]).public().into();
+		let signed_request = sign_cancel_request(&admin_pair(), other_account);
+
+		assert!(!signed_request.verify_signature());
+	}
+
+	fn admin_pair() -> sr25519::Pair {
+		sr25519::Pair::from_seed(&[1u8; 32])
+	}
+
+	fn sign_admin_request(
+		signer: &sr25519::Pair,
+		timestamp: u64,
+	) -> SignedBlockProductionAdminRequest {
+		let request = BlockProductionAdminRequest::new(
+			H256::from_low_u64_be(1),
+			BlockProductionCommand::Suspend,
+			0,
+			timestamp,
+		);
+		let signature = signer.sign(request.signature_payload().as_slice());
+		SignedBlockProductionAdminRequest::new(request, signer.public().into(), signature.into())
+	}
+
+	fn sign_cancel_request(
+		signer: &sr25519::Pair,
+		signer_account: AccountId,
+	) -> SignedTrustedOperationCancelRequest {
+		let request =
+			TrustedOperationCancelRequest::new(H256::from_low_u64_be(1), H256::from_low_u64_be(2));
+		let signature = signer.sign(request.encode().as_slice());
+		SignedTrustedOperationCancelRequest::new(request, signer_account, signature.into())
+	}
 }
@@ -35,12 +35,12 @@ use crate::sgx_reexport_prelude::*;
 
 use crate::rpc_watch_extractor::RpcWatchExtractor;
 use codec::{Encode, Error as CodecError};
-use itc_tls_websocket_server::error::WebSocketError;
+use itc_tls_websocket_server::{error::WebSocketError, ConnectionToken};
 use itp_rpc::RpcResponse;
 use itp_types::TrustedOperationStatus;
 use serde_json::error::Error as SerdeJsonError;
 use sp_runtime::traits;
-use std::{boxed::Box, fmt::Debug, vec::Vec};
+use std::{boxed::Box, fmt::Debug, net::IpAddr, vec::Vec};
 
 #[cfg(any(test, feature = "mocks"))]
 pub mod mocks;
@@ -53,6 +53,7 @@ pub mod rpc_connection_registry;
 pub mod rpc_responder;
 pub mod rpc_watch_extractor;
 pub mod rpc_ws_handler;
+pub mod subscription_registry;
 
 /// General web-socket error type
 #[derive(Debug, thiserror::Error)]
@@ -71,6 +72,37 @@ pub enum DirectRpcError {
 
 pub type DirectRpcResult<T> = Result<T, DirectRpcError>;
 
+/// Web-socket connection an RPC request was received on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RpcConnection {
+	pub token: ConnectionToken,
+	/// IP address of the client. Unlike the token, it does not change when the client reconnects.
+	pub peer_address: IpAddr,
+}
+
+/// RPC request metadata, identifying the connection a request was received on.
+///
+/// Requests that do not come in over a web-socket connection (e.g. the untrusted worker
+/// forwarding a sidechain request) have no connection.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RpcConnectionMetadata(pub Option<RpcConnection>);
+
+impl RpcConnectionMetadata {
+	pub const fn new(token: ConnectionToken, peer_address: IpAddr) -> Self {
+		RpcConnectionMetadata(Some(RpcConnection { token, peer_address }))
+	}
+}
+
+impl jsonrpc_core::Metadata for RpcConnectionMetadata {}
+
+/// Observes RPC connections being closed, to release what is kept for them.
+pub trait ObserveConnectionClosed: Send + Sync {
+	fn on_connection_closed(&self, connection: RpcConnection);
+}
+
+/// RPC IO handler, whose methods have access to the connection a request was received on.
+pub type RpcIoHandler = jsonrpc_core::IoHandler<RpcConnectionMetadata>;
+
 /// trait helper to mix-in all necessary traits for a hash
 pub trait RpcHash: std::hash::Hash + traits::Member + Encode {}
 impl<T: std::hash::Hash + traits::Member + Encode> RpcHash for T {}
@@ -98,6 +130,22 @@ pub trait SendRpcResponse: Send + Sync {
 	fn send_state(&self, hash: Self::Hash, state_encoded: Vec<u8>) -> DirectRpcResult<()>;
 }
 
+/// Sends notifications to clients that hold a subscription (e.g. to new sidechain block headers).
+///
+/// The subscription id is used as hash in the connection registry, so the connection
+/// is kept alive until the subscription is closed.
+pub trait SendSubscriptionNotification: Send + Sync {
+	type Hash: RpcHash;
+
+	fn send_notification(
+		&self,
+		subscription: Self::Hash,
+		value_encoded: Vec<u8>,
+	) -> DirectRpcResult<()>;
+
+	fn close_subscription(&self, subscription: &Self::Hash) -> DirectRpcResult<()>;
+}
+
 /// Determines if a given connection must be watched (i.e. kept alive),
 /// based on the information in the RpcResponse.
 pub trait DetermineWatch: Send + Sync {
@@ -18,3 +18,4 @@
 pub mod determine_watch_mock;
 pub mod response_channel_mock;
 pub mod send_rpc_response_mock;
+pub mod send_subscription_notification_mock;
@@ -0,0 +1,55 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+#[cfg(feature = "sgx")]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use crate::{DirectRpcResult, RpcHash, SendSubscriptionNotification};
+use std::vec::Vec;
+
+/// Send subscription notification mock.
+#[derive(Default)]
+pub struct SendSubscriptionNotificationMock<HashType> {
+	pub sent_notifications: RwLock<Vec<(HashType, Vec<u8>)>>,
+	pub closed_subscriptions: RwLock<Vec<HashType>>,
+}
+
+impl<HashType> SendSubscriptionNotification for SendSubscriptionNotificationMock<HashType>
+where
+	HashType: RpcHash,
+{
+	type Hash = HashType;
+
+	fn send_notification(
+		&self,
+		subscription: Self::Hash,
+		value_encoded: Vec<u8>,
+	) -> DirectRpcResult<()> {
+		let mut notifications_lock = self.sent_notifications.write().unwrap();
+		notifications_lock.push((subscription, value_encoded));
+		Ok(())
+	}
+
+	fn close_subscription(&self, subscription: &Self::Hash) -> DirectRpcResult<()> {
+		let mut closed_lock = self.closed_subscriptions.write().unwrap();
+		closed_lock.push(subscription.clone());
+		Ok(())
+	}
+}
@@ -17,7 +17,7 @@
 
 use crate::{
 	response_channel::ResponseChannel, DirectRpcError, DirectRpcResult, RpcConnectionRegistry,
-	RpcHash, SendRpcResponse,
+	RpcHash, SendRpcResponse, SendSubscriptionNotification,
 };
 use itp_rpc::{RpcResponse, RpcReturnValue};
 use itp_types::{DirectRequestStatus, TrustedOperationStatus};
@@ -129,12 +129,50 @@ where
 	}
 }
 
+impl<Registry, Hash, ResponseChannelType> SendSubscriptionNotification
+	for RpcResponder<Registry, Hash, ResponseChannelType>
+where
+	Registry: RpcConnectionRegistry<Hash = Hash>,
+	Hash: RpcHash,
+	ResponseChannelType: ResponseChannel<Registry::Connection>,
+{
+	type Hash = Hash;
+
+	fn send_notification(&self, subscription: Hash, value_encoded: Vec<u8>) -> DirectRpcResult<()> {
+		debug!("sending subscription notification");
+
+		// withdraw removes it from the registry
+		let (connection_token, mut response) = self
+			.connection_registry
+			.withdraw(&subscription)
+			.ok_or(DirectRpcError::InvalidConnectionHash)?;
+
+		let result = RpcReturnValue::new(value_encoded, true, DirectRequestStatus::Ok);
+		response.result = result.to_hex();
+
+		// In case sending fails, the connection is not stored again, which ends the subscription.
+		self.encode_and_send_response(connection_token, &response)?;
+
+		self.connection_registry.store(subscription, connection_token, response);
+
+		debug!("sending subscription notification successful");
+		Ok(())
+	}
+
+	fn close_subscription(&self, subscription: &Hash) -> DirectRpcResult<()> {
+		self.connection_registry
+			.withdraw(subscription)
+			.map(|_| ())
+			.ok_or(DirectRpcError::InvalidConnectionHash)
+	}
+}
+
 fn continue_watching(status: &TrustedOperationStatus) -> bool {
 	!matches!(
 		status,
 		TrustedOperationStatus::Invalid
-			| TrustedOperationStatus::InSidechainBlock(_)
 			| TrustedOperationStatus::Finalized
+			| TrustedOperationStatus::FinalityTimeout
 			| TrustedOperationStatus::Usurped
 	)
 }
@@ -236,10 +274,48 @@ pub mod tests {
 		assert_eq!(1, websocket_responder.number_of_updates());
 	}
 
+	#[test]
+	fn sending_notification_keeps_connection_and_sends_update() {
+		let subscription = String::from("subscription_id");
+		let connection_registry = create_registry_with_single_connection(subscription.clone());
+
+		let websocket_responder = Arc::new(TestResponseChannel::default());
+		let rpc_responder =
+			RpcResponder::new(connection_registry.clone(), websocket_responder.clone());
+
+		assert!(rpc_responder.send_notification(subscription.clone(), vec![1u8]).is_ok());
+		assert!(rpc_responder.send_notification(subscription.clone(), vec![2u8]).is_ok());
+
+		verify_open_connection(&subscription, connection_registry);
+		assert_eq!(2, websocket_responder.number_of_updates());
+	}
+
+	#[test]
+	fn closing_subscription_removes_connection() {
+		let subscription = String::from("subscription_id");
+		let connection_registry = create_registry_with_single_connection(subscription.clone());
+
+		let websocket_responder = Arc::new(TestResponseChannel::default());
+		let rpc_responder =
+			RpcResponder::new(connection_registry.clone(), websocket_responder.clone());
+
+		assert!(rpc_responder.close_subscription(&subscription).is_ok());
+		assert_matches!(
+			rpc_responder.send_notification(subscription.clone(), vec![1u8]),
+			Err(DirectRpcError::InvalidConnectionHash)
+		);
+
+		verify_closed_connection(&subscription, connection_registry);
+		assert_eq!(0, websocket_responder.number_of_updates());
+	}
+
 	#[test]
 	fn test_continue_watching() {
 		assert!(!continue_watching(&TrustedOperationStatus::Invalid));
 		assert!(!continue_watching(&TrustedOperationStatus::Usurped));
+		assert!(!continue_watching(&TrustedOperationStatus::Finalized));
+		assert!(!continue_watching(&TrustedOperationStatus::FinalityTimeout));
+		assert!(continue_watching(&TrustedOperationStatus::InSidechainBlock(Default::default())));
 		assert!(continue_watching(&TrustedOperationStatus::Future));
 		assert!(continue_watching(&TrustedOperationStatus::Broadcast));
 		assert!(continue_watching(&TrustedOperationStatus::Dropped));
@@ -62,7 +62,7 @@ where
 		}
 
 		match rpc_return_value.status {
-			DirectRequestStatus::TrustedOperationStatus(_) =>
+			DirectRequestStatus::TrustedOperationStatus(_) | DirectRequestStatus::Ok =>
 				Self::Hash::decode(&mut rpc_return_value.value.as_slice())
 					.map(Some)
 					.map_err(DirectRpcError::EncodingError),
@@ -119,4 +119,33 @@ pub mod tests {
 
 		assert_eq!(Some(hash.clone()), do_watch);
 	}
+
+	#[test]
+	fn rpc_response_of_subscription_must_be_watched() {
+		let subscription = String::from("subscription_id");
+		let watch_extractor = RpcWatchExtractor::<String>::new();
+		let rpc_return_value = RpcReturnValueBuilder::new()
+			.with_do_watch(true)
+			.with_value(subscription.encode())
+			.with_status(DirectRequestStatus::Ok)
+			.build();
+		let rpc_response = RpcResponseBuilder::new().with_result(rpc_return_value).build();
+
+		let do_watch = watch_extractor.must_be_watched(&rpc_response).unwrap();
+
+		assert_eq!(Some(subscription), do_watch);
+	}
+
+	#[test]
+	fn rpc_response_with_error_status_must_not_be_watched() {
+		let watch_extractor = RpcWatchExtractor::<String>::new();
+		let rpc_return_value = RpcReturnValueBuilder::new()
+			.with_do_watch(true)
+			.with_value(String::from("hash").encode())
+			.with_status(DirectRequestStatus::Error)
+			.build();
+		let rpc_response = RpcResponseBuilder::new().with_result(rpc_return_value).build();
+
+		assert_eq!(None, watch_extractor.must_be_watched(&rpc_response).unwrap());
+	}
 }
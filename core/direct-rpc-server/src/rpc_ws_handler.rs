@@ -18,11 +18,14 @@
 #[cfg(all(not(feature = "std"), feature = "sgx"))]
 use crate::sgx_reexport_prelude::*;
 
-use crate::{DetermineWatch, RpcConnectionRegistry, RpcHash};
+use crate::{
+	DetermineWatch, ObserveConnectionClosed, RpcConnection, RpcConnectionMetadata,
+	RpcConnectionRegistry, RpcHash, RpcIoHandler,
+};
 use itc_tls_websocket_server::{error::WebSocketResult, ConnectionToken, WebSocketMessageHandler};
-use jsonrpc_core::IoHandler;
+use jsonrpc_core::MetaIoHandler;
 use log::*;
-use std::{string::String, sync::Arc};
+use std::{net::IpAddr, string::String, sync::Arc, vec::Vec};
 
 pub struct RpcWsHandler<Watcher, Registry, Hash>
 where
@@ -30,9 +33,10 @@ where
 	Registry: RpcConnectionRegistry<Hash = Hash>,
 	Hash: RpcHash,
 {
-	rpc_io_handler: IoHandler,
+	rpc_io_handler: RpcIoHandler,
 	connection_watcher: Arc<Watcher>,
 	connection_registry: Arc<Registry>,
+	connection_close_observers: Vec<Arc<dyn ObserveConnectionClosed>>,
 }
 
 impl<Watcher, Registry, Hash> RpcWsHandler<Watcher, Registry, Hash>
@@ -42,11 +46,25 @@ where
 	Hash: RpcHash,
 {
 	pub fn new(
-		rpc_io_handler: IoHandler,
+		rpc_io_handler: RpcIoHandler,
 		connection_watcher: Arc<Watcher>,
 		connection_registry: Arc<Registry>,
 	) -> Self {
-		RpcWsHandler { rpc_io_handler, connection_watcher, connection_registry }
+		RpcWsHandler {
+			rpc_io_handler,
+			connection_watcher,
+			connection_registry,
+			connection_close_observers: Vec::new(),
+		}
+	}
+
+	/// Notify the observer of every connection that is closed.
+	pub fn with_connection_close_observer(
+		mut self,
+		observer: Arc<dyn ObserveConnectionClosed>,
+	) -> Self {
+		self.connection_close_observers.push(observer);
+		self
 	}
 }
 
@@ -60,9 +78,15 @@ where
 	fn handle_message(
 		&self,
 		connection_token: ConnectionToken,
+		peer_address: IpAddr,
 		message: String,
 	) -> WebSocketResult<Option<String>> {
-		let maybe_rpc_response = self.rpc_io_handler.handle_request_sync(message.as_str());
+		// `IoHandler::handle_request_sync` would use the default (empty) metadata.
+		let maybe_rpc_response = MetaIoHandler::handle_request_sync(
+			&*self.rpc_io_handler,
+			message.as_str(),
+			RpcConnectionMetadata::new(connection_token, peer_address),
+		);
 
 		debug!("RPC response string: {:?}", maybe_rpc_response);
 
@@ -82,6 +106,13 @@ where
 
 		Ok(maybe_rpc_response)
 	}
+
+	fn handle_connection_closed(&self, connection_token: ConnectionToken, peer_address: IpAddr) {
+		let connection = RpcConnection { token: connection_token, peer_address };
+		self.connection_close_observers
+			.iter()
+			.for_each(|observer| observer.on_connection_closed(connection));
+	}
 }
 
 #[cfg(test)]
@@ -99,12 +130,14 @@ pub mod tests {
 	use itp_utils::ToHexPrefixed;
 	use jsonrpc_core::Params;
 	use serde_json::json;
+	use std::{net::Ipv4Addr, sync::Mutex};
 
 	type TestConnectionRegistry = ConnectionRegistry<String, ConnectionToken>;
 	type TestConnectionWatcher = DetermineWatchMock<String>;
 	type TestWsHandler = RpcWsHandler<TestConnectionWatcher, TestConnectionRegistry, String>;
 
 	const RPC_METHOD_NAME: &str = "test_call";
+	const PEER_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
 
 	#[test]
 	fn valid_rpc_call_without_watch_runs_successfully() {
@@ -114,7 +147,7 @@ pub mod tests {
 
 		let (ws_handler, connection_registry) = create_ws_handler(io_handler, None);
 
-		let handle_result = ws_handler.handle_message(connection_token, message);
+		let handle_result = ws_handler.handle_message(connection_token, PEER_ADDRESS, message);
 
 		assert!(handle_result.is_ok());
 		assert!(connection_registry.is_empty());
@@ -130,7 +163,7 @@ pub mod tests {
 		let (ws_handler, connection_registry) =
 			create_ws_handler(io_handler, Some(connection_hash.clone()));
 
-		let handle_result = ws_handler.handle_message(connection_token, message);
+		let handle_result = ws_handler.handle_message(connection_token, PEER_ADDRESS, message);
 
 		assert!(handle_result.is_ok());
 		assert!(connection_registry.withdraw(&connection_hash).is_some());
@@ -146,7 +179,7 @@ pub mod tests {
 		let (ws_handler, connection_registry) =
 			create_ws_handler(io_handler, Some(connection_hash.clone()));
 
-		let handle_result = ws_handler.handle_message(connection_token, message);
+		let handle_result = ws_handler.handle_message(connection_token, PEER_ADDRESS, message);
 
 		assert!(handle_result.is_ok());
 		assert!(connection_registry.withdraw(&connection_hash).is_some());
@@ -159,12 +192,63 @@ pub mod tests {
 
 		let (ws_handler, connection_registry) = create_ws_handler(io_handler, None);
 
-		let handle_result = ws_handler.handle_message(connection_token, message).unwrap().unwrap();
+		let handle_result = ws_handler
+			.handle_message(connection_token, PEER_ADDRESS, message)
+			.unwrap()
+			.unwrap();
 
 		assert_eq!(handle_result, "{\"jsonrpc\":\"2.0\",\"error\":{\"code\":-32601,\"message\":\"Method not found\"},\"id\":1}");
 		assert!(connection_registry.is_empty());
 	}
 
+	#[test]
+	fn rpc_method_receives_the_connection_of_the_request() {
+		let mut io_handler = RpcIoHandler::new();
+		io_handler.add_method_with_meta(
+			RPC_METHOD_NAME,
+			|_: Params, meta: RpcConnectionMetadata| {
+				let connection = meta.0.map(|connection| connection.token.0);
+				jsonrpc_core::futures::future::ready(Ok(json!(connection)))
+			},
+		);
+		let (connection_token, message) = create_message_to_handle(RPC_METHOD_NAME);
+
+		let (ws_handler, _) = create_ws_handler(io_handler, None);
+
+		let handle_result = ws_handler
+			.handle_message(connection_token, PEER_ADDRESS, message)
+			.unwrap()
+			.unwrap();
+
+		assert!(handle_result.contains(&format!("\"result\":{}", connection_token.0)));
+	}
+
+	#[test]
+	fn connection_close_observers_are_notified_of_closed_connections() {
+		let observer = Arc::new(ConnectionCloseObserverMock::default());
+		let (ws_handler, _) =
+			create_ws_handler(create_io_handler_with_method(RPC_METHOD_NAME), None);
+		let ws_handler = ws_handler.with_connection_close_observer(observer.clone());
+
+		ws_handler.handle_connection_closed(ConnectionToken(23), PEER_ADDRESS);
+
+		assert_eq!(
+			vec![RpcConnection { token: ConnectionToken(23), peer_address: PEER_ADDRESS }],
+			*observer.closed_connections.lock().unwrap()
+		);
+	}
+
+	#[derive(Default)]
+	struct ConnectionCloseObserverMock {
+		closed_connections: Mutex<Vec<RpcConnection>>,
+	}
+
+	impl ObserveConnectionClosed for ConnectionCloseObserverMock {
+		fn on_connection_closed(&self, connection: RpcConnection) {
+			self.closed_connections.lock().unwrap().push(connection);
+		}
+	}
+
 	fn create_message_to_handle(method_name: &str) -> (ConnectionToken, String) {
 		let json_rpc_pre_method = r#"{"jsonrpc": "2.0", "method": ""#;
 		let json_rpc_post_method = r#"", "params": {}, "id": 1}"#;
@@ -176,7 +260,7 @@ pub mod tests {
 	}
 
 	fn create_ws_handler(
-		io_handler: IoHandler,
+		io_handler: RpcIoHandler,
 		watch_connection: Option<String>,
 	) -> (TestWsHandler, Arc<TestConnectionRegistry>) {
 		let watcher = match watch_connection {
@@ -192,7 +276,7 @@ pub mod tests {
 		)
 	}
 
-	fn create_io_handler_with_method(method_name: &str) -> IoHandler {
+	fn create_io_handler_with_method(method_name: &str) -> RpcIoHandler {
 		create_io_handler(
 			method_name,
 			RpcReturnValue {
@@ -203,7 +287,7 @@ pub mod tests {
 		)
 	}
 
-	fn create_io_handler_with_error(method_name: &str) -> IoHandler {
+	fn create_io_handler_with_error(method_name: &str) -> RpcIoHandler {
 		create_io_handler(
 			method_name,
 			RpcReturnValue {
@@ -214,11 +298,11 @@ pub mod tests {
 		)
 	}
 
-	fn create_io_handler<ReturnValue>(method_name: &str, return_value: ReturnValue) -> IoHandler
+	fn create_io_handler<ReturnValue>(method_name: &str, return_value: ReturnValue) -> RpcIoHandler
 	where
 		ReturnValue: Encode + Send + Sync + 'static,
 	{
-		let mut io_handler = IoHandler::new();
+		let mut io_handler = RpcIoHandler::new();
 		io_handler.add_sync_method(method_name, move |_: Params| Ok(json!(return_value.to_hex())));
 		io_handler
 	}
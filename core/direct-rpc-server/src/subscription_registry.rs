@@ -0,0 +1,132 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use crate::{RpcConnectionMetadata, RpcHash};
+use std::{collections::HashMap, vec::Vec};
+
+/// Keeps track of the open subscriptions of a subscription RPC method, together with the
+/// connection that opened them.
+///
+/// The connections themselves are kept in the connection registry, with the subscription id as hash.
+pub struct SubscriptionRegistry<Hash>
+where
+	Hash: RpcHash,
+{
+	subscriptions: RwLock<HashMap<Hash, RpcConnectionMetadata>>,
+}
+
+impl<Hash> SubscriptionRegistry<Hash>
+where
+	Hash: RpcHash,
+{
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn subscribe(&self, subscription: Hash, owner: RpcConnectionMetadata) {
+		let mut subscriptions = self.subscriptions.write().expect("Lock poisoning");
+		subscriptions.insert(subscription, owner);
+	}
+
+	/// Unsubscribe on behalf of the connection the request came from.
+	///
+	/// Returns false if there was no such subscription, or if it was opened by another connection.
+	pub fn unsubscribe(&self, subscription: &Hash, requester: RpcConnectionMetadata) -> bool {
+		let mut subscriptions = self.subscriptions.write().expect("Lock poisoning");
+		if subscriptions.get(subscription) != Some(&requester) {
+			return false
+		}
+		subscriptions.remove(subscription).is_some()
+	}
+
+	/// Remove a subscription regardless of its owner, e.g. because its connection is gone.
+	///
+	/// Returns false if there was no such subscription.
+	pub fn remove(&self, subscription: &Hash) -> bool {
+		let mut subscriptions = self.subscriptions.write().expect("Lock poisoning");
+		subscriptions.remove(subscription).is_some()
+	}
+
+	pub fn subscriptions(&self) -> Vec<Hash> {
+		let subscriptions = self.subscriptions.read().expect("Lock poisoning");
+		subscriptions.keys().cloned().collect()
+	}
+}
+
+impl<Hash> Default for SubscriptionRegistry<Hash>
+where
+	Hash: RpcHash,
+{
+	fn default() -> Self {
+		SubscriptionRegistry { subscriptions: RwLock::new(HashMap::default()) }
+	}
+}
+
+#[cfg(test)]
+pub mod tests {
+	use super::*;
+	use itc_tls_websocket_server::ConnectionToken;
+	use std::net::{IpAddr, Ipv4Addr};
+
+	type TestRegistry = SubscriptionRegistry<String>;
+
+	const PEER_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+	const OWNER: RpcConnectionMetadata =
+		RpcConnectionMetadata::new(ConnectionToken(10), PEER_ADDRESS);
+
+	#[test]
+	pub fn subscribing_and_unsubscribing_works() {
+		let registry = TestRegistry::new();
+		let subscription = "subscription".to_string();
+
+		registry.subscribe(subscription.clone(), OWNER);
+		assert_eq!(vec![subscription.clone()], registry.subscriptions());
+
+		assert!(registry.unsubscribe(&subscription, OWNER));
+		assert!(registry.subscriptions().is_empty());
+	}
+
+	#[test]
+	pub fn unsubscribing_unknown_subscription_returns_false() {
+		let registry = TestRegistry::new();
+
+		assert!(!registry.unsubscribe(&"subscription".to_string(), OWNER));
+	}
+
+	#[test]
+	pub fn only_the_owning_connection_can_unsubscribe() {
+		let registry = TestRegistry::new();
+		let subscription = "subscription".to_string();
+		registry.subscribe(subscription.clone(), OWNER);
+
+		assert!(!registry.unsubscribe(
+			&subscription,
+			RpcConnectionMetadata::new(ConnectionToken(11), PEER_ADDRESS)
+		));
+		assert!(!registry.unsubscribe(&subscription, RpcConnectionMetadata(None)));
+		assert_eq!(vec![subscription.clone()], registry.subscriptions());
+
+		assert!(registry.remove(&subscription));
+		assert!(registry.subscriptions().is_empty());
+	}
+}
@@ -26,7 +26,7 @@ use itp_stf_executor::{traits::StateUpdateProposer, ExecutedOperation};
 use itp_stf_interface::system_pallet::SystemPalletEventInterface;
 use itp_stf_state_handler::{handle_state::HandleState, query_shard_state::QueryShardState};
 use itp_top_pool_author::traits::AuthorApi;
-use itp_types::{OpaqueCall, ShardIdentifier, H256};
+use itp_types::{BlockLimits, OpaqueCall, ShardIdentifier, H256};
 use log::*;
 use sp_runtime::traits::Block;
 use std::{marker::PhantomData, sync::Arc, time::Duration, vec::Vec};
@@ -118,6 +118,8 @@ impl<
 				&latest_parentchain_header,
 				&shard,
 				max_duration,
+				// No sidechain blocks are produced, so there is nothing to limit.
+				&BlockLimits::unlimited(),
 				|mut state| {
 					Stf::reset_events(&mut state);
 					state
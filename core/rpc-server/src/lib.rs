@@ -19,17 +19,23 @@ use itp_enclave_api::direct_request::DirectRequest;
 use itp_rpc::RpcRequest;
 use itp_utils::ToHexPrefixed;
 use its_peer_fetch::block_fetch_server::BlockFetchServerModuleBuilder;
-use its_primitives::types::block::SignedBlock;
-use its_rpc_handler::constants::RPC_METHOD_NAME_IMPORT_BLOCKS;
-use its_storage::interface::FetchBlocks;
+use its_primitives::types::{block::SignedBlock, finality::SignedFinalityVote};
+use its_rpc_handler::constants::{
+	RPC_METHOD_NAME_IMPORT_BLOCKS, RPC_METHOD_NAME_IMPORT_FINALITY_VOTES,
+	RPC_METHOD_NAME_IMPORT_TRUSTED_OPERATIONS,
+};
+use its_storage::{interface::FetchBlocks, LookupBlocks};
 use jsonrpsee::{
 	types::error::CallError,
 	ws_server::{RpcModule, WsServerBuilder},
 };
 use log::debug;
+use sidechain_explorer::SidechainExplorerModuleBuilder;
 use std::{net::SocketAddr, sync::Arc};
 use tokio::net::ToSocketAddrs;
 
+pub mod sidechain_explorer;
+
 #[cfg(test)]
 mod mock;
 #[cfg(test)]
@@ -42,7 +48,8 @@ pub async fn run_server<Enclave, FetchSidechainBlocks>(
 ) -> anyhow::Result<SocketAddr>
 where
 	Enclave: DirectRequest,
-	FetchSidechainBlocks: FetchBlocks<SignedBlock> + Send + Sync + 'static,
+	FetchSidechainBlocks:
+		FetchBlocks<SignedBlock> + LookupBlocks<SignedBlock> + Send + Sync + 'static,
 {
 	let mut server = WsServerBuilder::default().build(addr).await?;
 
@@ -64,8 +71,45 @@ where
 				.map_err(|e| CallError::Failed(e.into()))
 		},
 	)?;
+	import_sidechain_block_module.register_method(
+		RPC_METHOD_NAME_IMPORT_FINALITY_VOTES,
+		|params, enclave| {
+			debug!("{} params: {:?}", RPC_METHOD_NAME_IMPORT_FINALITY_VOTES, params);
+
+			let enclave_req = RpcRequest::compose_jsonrpc_call(
+				RPC_METHOD_NAME_IMPORT_FINALITY_VOTES.into(),
+				vec![params.one::<Vec<SignedFinalityVote>>()?.to_hex()],
+			)
+			.unwrap();
+
+			enclave
+				.rpc(enclave_req.as_bytes().to_vec())
+				.map_err(|e| CallError::Failed(e.into()))
+		},
+	)?;
+	import_sidechain_block_module.register_method(
+		RPC_METHOD_NAME_IMPORT_TRUSTED_OPERATIONS,
+		|params, enclave| {
+			debug!("{} params: {:?}", RPC_METHOD_NAME_IMPORT_TRUSTED_OPERATIONS, params);
+
+			// The trusted operations are encrypted and already hex encoded by the sending peer.
+			let enclave_req = RpcRequest::compose_jsonrpc_call(
+				RPC_METHOD_NAME_IMPORT_TRUSTED_OPERATIONS.into(),
+				vec![params.one::<String>()?],
+			)
+			.unwrap();
+
+			enclave
+				.rpc(enclave_req.as_bytes().to_vec())
+				.map_err(|e| CallError::Failed(e.into()))
+		},
+	)?;
 	server.register_module(import_sidechain_block_module).unwrap();
 
+	let sidechain_explorer_module =
+		SidechainExplorerModuleBuilder::new(sidechain_block_fetcher.clone()).build()?;
+	server.register_module(sidechain_explorer_module).unwrap();
+
 	let fetch_sidechain_blocks_module = BlockFetchServerModuleBuilder::new(sidechain_block_fetcher)
 		.build()
 		.map_err(|e| CallError::Failed(e.to_string().into()))?; // `to_string` necessary due to no all errors implementing Send + Sync.
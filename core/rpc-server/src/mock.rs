@@ -20,9 +20,13 @@ use itp_rpc::RpcResponse;
 use itp_utils::ToHexPrefixed;
 use its_primitives::{
 	traits::ShardIdentifierFor,
-	types::{BlockHash, SignedBlock, SignedBlock as SignedSidechainBlock},
+	types::{
+		block_metadata::OperationInclusion, BlockHash, BlockNumber, SignedBlock,
+		SignedBlock as SignedSidechainBlock,
+	},
 };
-use its_storage::interface::FetchBlocks;
+use its_storage::{interface::FetchBlocks, LookupBlocks};
+use its_test::sidechain_block_builder::SidechainBlockBuilder;
 use parity_scale_codec::Encode;
 
 pub struct TestEnclave;
@@ -53,3 +57,40 @@ impl FetchBlocks<SignedSidechainBlock> for MockSidechainBlockFetcher {
 		Ok(Vec::new())
 	}
 }
+
+impl LookupBlocks<SignedSidechainBlock> for MockSidechainBlockFetcher {
+	fn fetch_block_by_hash(
+		&self,
+		_block_hash: &BlockHash,
+	) -> its_storage::Result<Option<SignedBlock>> {
+		Ok(None)
+	}
+
+	fn fetch_block_by_number(
+		&self,
+		_shard_identifier: &ShardIdentifierFor<SignedBlock>,
+		_block_number: BlockNumber,
+	) -> its_storage::Result<Option<SignedBlock>> {
+		Ok(None)
+	}
+
+	fn fetch_last_blocks(&self) -> its_storage::Result<Vec<SignedBlock>> {
+		Ok(vec![SidechainBlockBuilder::default().build_signed()])
+	}
+
+	fn fetch_blocks_by_number(
+		&self,
+		_shard_identifier: &ShardIdentifierFor<SignedBlock>,
+		_from: BlockNumber,
+		_limit: u64,
+	) -> its_storage::Result<Vec<SignedBlock>> {
+		Ok(Vec::new())
+	}
+
+	fn fetch_operation_inclusion(
+		&self,
+		_operation_hash: &BlockHash,
+	) -> its_storage::Result<Option<OperationInclusion<ShardIdentifierFor<SignedBlock>>>> {
+		Ok(None)
+	}
+}
@@ -0,0 +1,117 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! RPC module to look up sidechain blocks, headers and included trusted operations from storage,
+//! e.g. for block explorers, monitoring dashboards and wallets.
+
+use its_primitives::types::{
+	block_metadata::SidechainBlockMetadata, BlockHash, BlockNumber, ShardIdentifier, SignedBlock,
+};
+use its_rpc_handler::constants::{
+	RPC_METHOD_NAME_GET_BLOCK_BY_HASH, RPC_METHOD_NAME_GET_BLOCK_BY_NUMBER,
+	RPC_METHOD_NAME_GET_HEADERS, RPC_METHOD_NAME_GET_LATEST_HEADERS,
+	RPC_METHOD_NAME_GET_OPERATION_INCLUSION,
+};
+use its_storage::LookupBlocks;
+use jsonrpsee::{types::error::CallError, RpcModule};
+use log::*;
+use std::sync::Arc;
+
+/// Maximum number of headers returned by a single `sidechain_getHeaders` call.
+pub const MAX_HEADERS_PAGE_SIZE: u64 = 100;
+
+/// RPC server module builder for looking up sidechain blocks and headers.
+pub struct SidechainExplorerModuleBuilder<LookupBlocksFromStorage> {
+	sidechain_block_lookup: Arc<LookupBlocksFromStorage>,
+}
+
+impl<LookupBlocksFromStorage> SidechainExplorerModuleBuilder<LookupBlocksFromStorage>
+where
+	LookupBlocksFromStorage: LookupBlocks<SignedBlock> + Send + Sync + 'static,
+{
+	pub fn new(sidechain_block_lookup: Arc<LookupBlocksFromStorage>) -> Self {
+		SidechainExplorerModuleBuilder { sidechain_block_lookup }
+	}
+
+	pub fn build(self) -> anyhow::Result<RpcModule<Arc<LookupBlocksFromStorage>>> {
+		let mut explorer_module = RpcModule::new(self.sidechain_block_lookup);
+
+		explorer_module.register_method(
+			RPC_METHOD_NAME_GET_BLOCK_BY_HASH,
+			|params, sidechain_block_lookup| {
+				debug!("{}: {:?}", RPC_METHOD_NAME_GET_BLOCK_BY_HASH, params);
+				let block_hash = params.one::<BlockHash>()?;
+				sidechain_block_lookup.fetch_block_by_hash(&block_hash).map_err(map_storage_err)
+			},
+		)?;
+
+		explorer_module.register_method(
+			RPC_METHOD_NAME_GET_BLOCK_BY_NUMBER,
+			|params, sidechain_block_lookup| {
+				debug!("{}: {:?}", RPC_METHOD_NAME_GET_BLOCK_BY_NUMBER, params);
+				let (shard, block_number) = params.one::<(ShardIdentifier, BlockNumber)>()?;
+				sidechain_block_lookup
+					.fetch_block_by_number(&shard, block_number)
+					.map_err(map_storage_err)
+			},
+		)?;
+
+		explorer_module.register_method(
+			RPC_METHOD_NAME_GET_LATEST_HEADERS,
+			|_params, sidechain_block_lookup| {
+				debug!("{}", RPC_METHOD_NAME_GET_LATEST_HEADERS);
+				sidechain_block_lookup
+					.fetch_last_blocks()
+					.map(|blocks| to_metadata(&blocks))
+					.map_err(map_storage_err)
+			},
+		)?;
+
+		explorer_module.register_method(
+			RPC_METHOD_NAME_GET_HEADERS,
+			|params, sidechain_block_lookup| {
+				debug!("{}: {:?}", RPC_METHOD_NAME_GET_HEADERS, params);
+				let (shard, from, limit) = params.one::<(ShardIdentifier, BlockNumber, u64)>()?;
+				sidechain_block_lookup
+					.fetch_blocks_by_number(&shard, from, limit.min(MAX_HEADERS_PAGE_SIZE))
+					.map(|blocks| to_metadata(&blocks))
+					.map_err(map_storage_err)
+			},
+		)?;
+
+		explorer_module.register_method(
+			RPC_METHOD_NAME_GET_OPERATION_INCLUSION,
+			|params, sidechain_block_lookup| {
+				debug!("{}: {:?}", RPC_METHOD_NAME_GET_OPERATION_INCLUSION, params);
+				let operation_hash = params.one::<BlockHash>()?;
+				sidechain_block_lookup
+					.fetch_operation_inclusion(&operation_hash)
+					.map_err(map_storage_err)
+			},
+		)?;
+
+		Ok(explorer_module)
+	}
+}
+
+fn to_metadata(blocks: &[SignedBlock]) -> Vec<SidechainBlockMetadata> {
+	blocks.iter().map(SidechainBlockMetadata::from).collect()
+}
+
+fn map_storage_err(e: its_storage::Error) -> CallError {
+	error!("Failed to look up sidechain blocks in storage: {:?}", e);
+	CallError::Failed(e.into())
+}
@@ -18,7 +18,10 @@
 use super::*;
 use crate::mock::MockSidechainBlockFetcher;
 use itp_rpc::RpcResponse;
-use its_rpc_handler::constants::RPC_METHOD_NAME_IMPORT_BLOCKS;
+use its_primitives::types::block_metadata::SidechainBlockMetadata;
+use its_rpc_handler::constants::{
+	RPC_METHOD_NAME_GET_LATEST_HEADERS, RPC_METHOD_NAME_IMPORT_BLOCKS,
+};
 use its_test::sidechain_block_builder::SidechainBlockBuilder;
 use jsonrpsee::{
 	types::{to_json_value, traits::Client},
@@ -54,3 +57,21 @@ async fn test_client_calls() {
 
 	assert!(RpcResponse::decode(&mut response.as_slice()).is_ok());
 }
+
+#[tokio::test]
+async fn get_latest_headers_returns_block_metadata() {
+	init();
+	let addr =
+		run_server("127.0.0.1:0", Arc::new(TestEnclave), Arc::new(MockSidechainBlockFetcher))
+			.await
+			.unwrap();
+
+	let url = format!("ws://{}", addr);
+	let client = WsClientBuilder::default().build(&url).await.unwrap();
+	let response: Vec<SidechainBlockMetadata> = client
+		.request(RPC_METHOD_NAME_GET_LATEST_HEADERS, Vec::<serde_json::Value>::new().into())
+		.await
+		.unwrap();
+
+	assert_eq!(1, response.len());
+}
@@ -27,6 +27,7 @@ use mio::{event::Event, net::TcpStream, Poll, Ready, Token};
 use rustls::{ServerSession, Session};
 use std::{
 	format,
+	net::IpAddr,
 	string::{String, ToString},
 	sync::Arc,
 	time::Instant,
@@ -37,6 +38,7 @@ use tungstenite::Message;
 pub struct TungsteniteWsConnection<Handler> {
 	stream_state: StreamState,
 	connection_token: Token,
+	peer_address: IpAddr,
 	connection_handler: Arc<Handler>,
 	is_closed: bool,
 }
@@ -49,6 +51,7 @@ where
 		tcp_stream: TcpStream,
 		server_session: ServerSession,
 		connection_token: Token,
+		peer_address: IpAddr,
 		handler: Arc<Handler>,
 	) -> WebSocketResult<Self> {
 		Ok(TungsteniteWsConnection {
@@ -57,11 +60,17 @@ where
 				tcp_stream,
 			)),
 			connection_token,
+			peer_address,
 			connection_handler: handler,
 			is_closed: false,
 		})
 	}
 
+	/// IP address of the client the connection is from.
+	pub fn peer_address(&self) -> IpAddr {
+		self.peer_address
+	}
+
 	fn do_tls_read(&mut self) -> ConnectionState {
 		let tls_stream = match self.stream_state.internal_stream_mut() {
 			None => return ConnectionState::Closing,
@@ -172,10 +181,11 @@ where
 					self.connection_token.0
 				);
 				let message_handled_timer = Instant::now();
-				if let Some(reply) = self
-					.connection_handler
-					.handle_message(self.connection_token.into(), string_message)?
-				{
+				if let Some(reply) = self.connection_handler.handle_message(
+					self.connection_token.into(),
+					self.peer_address,
+					string_message,
+				)? {
 					trace!(
 						"Handling message yielded a reply, sending it now to connection {}..",
 						self.connection_token.0
@@ -46,6 +46,7 @@ use crate::{
 use mio::{event::Evented, Token};
 use std::{
 	fmt::Debug,
+	net::IpAddr,
 	string::{String, ToString},
 	sync::Arc,
 };
@@ -80,11 +81,16 @@ impl From<Token> for ConnectionToken {
 
 /// Handles a web-socket connection message.
 pub trait WebSocketMessageHandler: Send + Sync {
+	/// Handles a message received on the connection from a client with the given IP address.
 	fn handle_message(
 		&self,
 		connection_token: ConnectionToken,
+		peer_address: IpAddr,
 		message: String,
 	) -> WebSocketResult<Option<String>>;
+
+	/// Called once the connection is closed and removed from the server.
+	fn handle_connection_closed(&self, _connection_token: ConnectionToken, _peer_address: IpAddr) {}
 }
 
 /// Allows to send response messages to a specific connection.
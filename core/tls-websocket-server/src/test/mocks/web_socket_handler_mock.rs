@@ -23,7 +23,7 @@ use std::sync::RwLock;
 
 use crate::{ConnectionToken, WebSocketMessageHandler, WebSocketResult};
 use log::debug;
-use std::{collections::HashMap, string::String, vec::Vec};
+use std::{collections::HashMap, net::IpAddr, string::String, vec::Vec};
 
 pub struct WebSocketHandlerMock {
 	pub responses: Vec<String>,
@@ -49,6 +49,7 @@ impl WebSocketMessageHandler for WebSocketHandlerMock {
 	fn handle_message(
 		&self,
 		connection_token: ConnectionToken,
+		_peer_address: IpAddr,
 		message: String,
 	) -> WebSocketResult<Option<String>> {
 		let mut handled_messages_lock = self.messages_handled.write().unwrap();
@@ -98,6 +98,7 @@ where
 			socket,
 			tls_session,
 			token,
+			addr.ip(),
 			self.connection_handler.clone(),
 		)?;
 
@@ -123,7 +124,9 @@ where
 
 			if connection.is_closed() {
 				trace!("Connection {:?} is closed, removing", token);
+				let peer_address = connection.peer_address();
 				connections_lock.remove(&token);
+				self.connection_handler.handle_connection_closed(token.into(), peer_address);
 				trace!(
 					"Closed {:?}, {} active connections remaining",
 					token,
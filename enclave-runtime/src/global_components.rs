@@ -64,8 +64,13 @@ use itp_top_pool::basic_pool::BasicPool;
 use itp_top_pool_author::{
 	api::SidechainApi,
 	author::{Author, AuthorTopFilter},
+	stale_operations_evictor::StaleOperationsEvictor,
+	top_pool_persistence::TopPoolPersistence,
+};
+use itp_types::{
+	AccountId, Block as ParentchainBlock, Header as ParentchainHeader,
+	SignedBlock as SignedParentchainBlock,
 };
-use itp_types::{Block as ParentchainBlock, SignedBlock as SignedParentchainBlock};
 use its_primitives::{
 	traits::{Block as SidechainBlockTrait, SignedBlock as SignedSidechainBlockTrait},
 	types::block::SignedBlock as SignedSidechainBlock,
@@ -73,7 +78,11 @@ use its_primitives::{
 use its_sidechain::{
 	aura::block_importer::BlockImporter as SidechainBlockImporter,
 	block_composer::BlockComposer,
-	consensus_common::{BlockImportConfirmationHandler, BlockImportQueueWorker, PeerBlockSync},
+	consensus_common::{
+		BlockImportConfirmationHandler, BlockImportQueueWorker, BlockProductionSuspender,
+		EquivocationReporter, FinalityGadget, PeerBlockSync,
+	},
+	rpc_handler::head_subscription_api::HeadSubscriptions,
 	state::SidechainDB,
 };
 use sgx_crypto_helper::rsa3072::Rsa3072KeyPair;
@@ -132,7 +141,8 @@ pub type EnclaveRpcWsHandler =
 	RpcWsHandler<RpcWatchExtractor<Hash>, EnclaveRpcConnectionRegistry, Hash>;
 pub type EnclaveWebSocketServer = TungsteniteWsServer<EnclaveRpcWsHandler, FromFileConfigProvider>;
 pub type EnclaveRpcResponder = RpcResponder<EnclaveRpcConnectionRegistry, Hash, RpcResponseChannel>;
-pub type EnclaveSidechainApi = SidechainApi<ParentchainBlock>;
+pub type EnclaveHeadSubscriptions = HeadSubscriptions<EnclaveRpcResponder>;
+pub type EnclaveSidechainApi = SidechainApi<ParentchainBlock, EnclaveStateObserver, EnclaveStf>;
 
 /// Sidechain types
 pub type EnclaveSidechainState =
@@ -146,6 +156,15 @@ pub type EnclaveTopPoolAuthor = Author<
 	EnclaveShieldingKeyRepository,
 	EnclaveOCallApi,
 >;
+pub type EnclaveTopPoolPersistence = TopPoolPersistence<
+	EnclaveTopPoolAuthor,
+	EnclaveStateObserver,
+	EnclaveStateKeyRepository,
+	EnclaveOCallApi,
+	EnclaveStf,
+>;
+pub type EnclaveStaleOperationsEvictor =
+	StaleOperationsEvictor<EnclaveTopPoolAuthor, EnclaveStateObserver, EnclaveStf>;
 pub type EnclaveSidechainBlockComposer =
 	BlockComposer<ParentchainBlock, SignedSidechainBlock, Pair, EnclaveStateKeyRepository>;
 pub type EnclaveSidechainBlockImporter = SidechainBlockImporter<
@@ -158,6 +177,19 @@ pub type EnclaveSidechainBlockImporter = SidechainBlockImporter<
 	EnclaveStateKeyRepository,
 	EnclaveTopPoolAuthor,
 	EnclaveTriggeredParentchainBlockImportDispatcher,
+	EnclaveHeadSubscriptions,
+	EnclaveSidechainFinalityGadget,
+	EnclaveSidechainEquivocationReporter,
+>;
+pub type EnclaveSidechainFinalityGadget =
+	FinalityGadget<ParentchainHeader, EnclaveOCallApi, EnclaveOCallApi, EnclaveTopPoolAuthor>;
+pub type EnclaveSidechainEquivocationReporter = EquivocationReporter<
+	ParentchainBlock,
+	SignedSidechainBlock,
+	EnclaveNodeMetadataRepository,
+	EnclaveExtrinsicsFactory,
+	EnclaveValidatorAccessor,
+	EnclaveOCallApi,
 >;
 pub type EnclaveSidechainBlockImportQueue = BlockImportQueue<SignedSidechainBlock>;
 pub type EnclaveBlockImportConfirmationHandler = BlockImportConfirmationHandler<
@@ -172,6 +204,7 @@ pub type EnclaveSidechainBlockSyncer = PeerBlockSync<
 	SignedSidechainBlock,
 	EnclaveSidechainBlockImporter,
 	EnclaveOCallApi,
+	EnclaveOCallApi,
 	EnclaveBlockImportConfirmationHandler,
 >;
 pub type EnclaveSidechainBlockImportQueueWorker = BlockImportQueueWorker<
@@ -180,6 +213,7 @@ pub type EnclaveSidechainBlockImportQueueWorker = BlockImportQueueWorker<
 	EnclaveSidechainBlockImportQueue,
 	EnclaveSidechainBlockSyncer,
 >;
+pub type EnclaveBlockProductionSuspender = BlockProductionSuspender;
 pub type EnclaveSealHandler =
 	SealHandler<EnclaveShieldingKeyRepository, EnclaveStateKeyRepository, EnclaveStateHandler>;
 pub type EnclaveOffchainWorkerExecutor = itc_offchain_worker_executor::executor::Executor<
@@ -265,6 +299,10 @@ pub static GLOBAL_NODE_METADATA_REPOSITORY_COMPONENT: ComponentContainer<
 pub static GLOBAL_RPC_WS_HANDLER_COMPONENT: ComponentContainer<EnclaveRpcWsHandler> =
 	ComponentContainer::new("rpc_ws_handler");
 
+/// Subscriptions to new sidechain block headers.
+pub static GLOBAL_HEAD_SUBSCRIPTIONS_COMPONENT: ComponentContainer<EnclaveHeadSubscriptions> =
+	ComponentContainer::new("head_subscriptions");
+
 /// Sidechain import queue.
 pub static GLOBAL_SIDECHAIN_IMPORT_QUEUE_COMPONENT: ComponentContainer<
 	EnclaveSidechainBlockImportQueue,
@@ -280,7 +318,30 @@ pub static GLOBAL_SIDECHAIN_BLOCK_COMPOSER_COMPONENT: ComponentContainer<
 	EnclaveSidechainBlockComposer,
 > = ComponentContainer::new("sidechain_block_composer");
 
+/// Sidechain finality gadget.
+pub static GLOBAL_SIDECHAIN_FINALITY_GADGET_COMPONENT: ComponentContainer<
+	EnclaveSidechainFinalityGadget,
+> = ComponentContainer::new("sidechain_finality_gadget");
+
 /// Sidechain block syncer.
 pub static GLOBAL_SIDECHAIN_BLOCK_SYNCER_COMPONENT: ComponentContainer<
 	EnclaveSidechainBlockSyncer,
 > = ComponentContainer::new("sidechain_block_syncer");
+
+/// Persistence of the pending trusted operations across restarts.
+pub static GLOBAL_TOP_POOL_PERSISTENCE_COMPONENT: ComponentContainer<EnclaveTopPoolPersistence> =
+	ComponentContainer::new("top_pool_persistence");
+
+/// Eviction of the pending trusted operations that can no longer be included in a block.
+pub static GLOBAL_STALE_OPERATIONS_EVICTOR_COMPONENT: ComponentContainer<
+	EnclaveStaleOperationsEvictor,
+> = ComponentContainer::new("stale_operations_evictor");
+
+/// Sidechain block production suspender, suspends block production per shard.
+pub static GLOBAL_BLOCK_PRODUCTION_SUSPENDER_COMPONENT: ComponentContainer<
+	EnclaveBlockProductionSuspender,
+> = ComponentContainer::new("block_production_suspender");
+
+/// Admin account of the validateer, authorized to suspend and resume block production.
+pub static GLOBAL_ADMIN_ACCOUNT_COMPONENT: ComponentContainer<AccountId> =
+	ComponentContainer::new("admin_account");
@@ -17,28 +17,34 @@
 use crate::{
 	error::{Error, Result as EnclaveResult},
 	global_components::{
-		EnclaveBlockImportConfirmationHandler, EnclaveGetterExecutor, EnclaveOCallApi,
+		EnclaveBlockImportConfirmationHandler, EnclaveBlockProductionSuspender,
+		EnclaveGetterExecutor, EnclaveHeadSubscriptions, EnclaveOCallApi,
 		EnclaveOffchainWorkerExecutor, EnclaveRpcConnectionRegistry, EnclaveRpcResponder,
 		EnclaveShieldingKeyRepository, EnclaveSidechainApi, EnclaveSidechainBlockImportQueue,
 		EnclaveSidechainBlockImportQueueWorker, EnclaveSidechainBlockImporter,
-		EnclaveSidechainBlockSyncer, EnclaveStateFileIo, EnclaveStateHandler,
-		EnclaveStateKeyRepository, EnclaveStateObserver, EnclaveStateSnapshotRepository,
-		EnclaveStfEnclaveSigner, EnclaveStfExecutor, EnclaveTopPool, EnclaveTopPoolAuthor,
-		EnclaveValidatorAccessor, GLOBAL_ATTESTATION_HANDLER_COMPONENT,
-		GLOBAL_EXTRINSICS_FACTORY_COMPONENT,
+		EnclaveSidechainBlockSyncer, EnclaveSidechainEquivocationReporter,
+		EnclaveSidechainFinalityGadget, EnclaveStaleOperationsEvictor, EnclaveStateFileIo,
+		EnclaveStateHandler, EnclaveStateKeyRepository, EnclaveStateObserver,
+		EnclaveStateSnapshotRepository, EnclaveStfEnclaveSigner, EnclaveStfExecutor,
+		EnclaveTopPool, EnclaveTopPoolAuthor, EnclaveTopPoolPersistence, EnclaveValidatorAccessor,
+		GLOBAL_ATTESTATION_HANDLER_COMPONENT, GLOBAL_BLOCK_PRODUCTION_SUSPENDER_COMPONENT,
+		GLOBAL_EXTRINSICS_FACTORY_COMPONENT, GLOBAL_HEAD_SUBSCRIPTIONS_COMPONENT,
 		GLOBAL_IMMEDIATE_PARENTCHAIN_IMPORT_DISPATCHER_COMPONENT,
 		GLOBAL_NODE_METADATA_REPOSITORY_COMPONENT, GLOBAL_OCALL_API_COMPONENT,
 		GLOBAL_PARENTCHAIN_BLOCK_VALIDATOR_ACCESS_COMPONENT, GLOBAL_RPC_WS_HANDLER_COMPONENT,
 		GLOBAL_SHIELDING_KEY_REPOSITORY_COMPONENT, GLOBAL_SIDECHAIN_BLOCK_COMPOSER_COMPONENT,
-		GLOBAL_SIDECHAIN_BLOCK_SYNCER_COMPONENT, GLOBAL_SIDECHAIN_IMPORT_QUEUE_COMPONENT,
-		GLOBAL_SIDECHAIN_IMPORT_QUEUE_WORKER_COMPONENT, GLOBAL_STATE_HANDLER_COMPONENT,
+		GLOBAL_SIDECHAIN_BLOCK_SYNCER_COMPONENT, GLOBAL_SIDECHAIN_FINALITY_GADGET_COMPONENT,
+		GLOBAL_SIDECHAIN_IMPORT_QUEUE_COMPONENT, GLOBAL_SIDECHAIN_IMPORT_QUEUE_WORKER_COMPONENT,
+		GLOBAL_STALE_OPERATIONS_EVICTOR_COMPONENT, GLOBAL_STATE_HANDLER_COMPONENT,
 		GLOBAL_STATE_KEY_REPOSITORY_COMPONENT, GLOBAL_STATE_OBSERVER_COMPONENT,
 		GLOBAL_STF_EXECUTOR_COMPONENT, GLOBAL_TOP_POOL_AUTHOR_COMPONENT,
+		GLOBAL_TOP_POOL_PERSISTENCE_COMPONENT,
 		GLOBAL_TRIGGERED_PARENTCHAIN_IMPORT_DISPATCHER_COMPONENT,
 		GLOBAL_WEB_SOCKET_SERVER_COMPONENT,
 	},
 	ocall::OcallApi,
 	rpc::{rpc_response_channel::RpcResponseChannel, worker_api_direct::public_api_rpc_handler},
+	top_pool_execution::evict_stale_operations,
 	Hash,
 };
 use base58::ToBase58;
@@ -71,6 +77,11 @@ use itp_nonce_cache::GLOBAL_NONCE_CACHE;
 use itp_primitives_cache::GLOBAL_PRIMITIVES_CACHE;
 use itp_settings::{
 	files::STATE_SNAPSHOTS_CACHE_SIZE,
+	sidechain::{
+		CONNECTION_BAN_DURATION, MAX_FUTURE_BYTES_PER_SENDER, MAX_FUTURE_OPERATIONS_PER_SENDER,
+		MAX_SIGNATURE_FAILURES_PER_CONNECTION, PEER_SYNC_CHUNK_SIZE,
+		PEER_SYNC_MAX_ATTEMPTS_PER_CHUNK, PEER_SYNC_MAX_PARALLEL_REQUESTS, SLOT_DURATION,
+	},
 	worker_mode::{ProvideWorkerMode, WorkerMode},
 };
 use itp_sgx_crypto::{aes, ed25519, rsa3072, AesSeal, Ed25519Seal, Rsa3072Seal};
@@ -81,9 +92,12 @@ use itp_stf_state_handler::{
 	state_snapshot_repository_loader::StateSnapshotRepositoryLoader, StateHandler,
 };
 use itp_top_pool::pool::Options as PoolOptions;
-use itp_top_pool_author::author::AuthorTopFilter;
+use itp_top_pool_author::{author::AuthorTopFilter, sender_limits::SenderLimits};
 use itp_types::{Block, Header, ShardIdentifier, SignedBlock};
-use its_sidechain::block_composer::BlockComposer;
+use its_sidechain::{
+	block_composer::BlockComposer, consensus_common::PeerSyncMode,
+	rpc_handler::direct_top_pool_api::TopPoolConnectionObserver,
+};
 use log::*;
 use sp_core::crypto::Pair;
 use std::{collections::HashMap, string::String, sync::Arc};
@@ -168,14 +182,31 @@ pub(crate) fn init_enclave(mu_ra_url: String, untrusted_worker_url: String) -> E
 	let top_pool_author = create_top_pool_author(
 		connection_registry.clone(),
 		state_handler,
+		state_observer.clone(),
 		ocall_api.clone(),
 		shielding_key_repository,
 	);
 	GLOBAL_TOP_POOL_AUTHOR_COMPONENT.initialize(top_pool_author.clone());
 
+	let head_subscriptions = create_head_subscriptions(connection_registry.clone());
+	GLOBAL_HEAD_SUBSCRIPTIONS_COMPONENT.initialize(head_subscriptions.clone());
+
+	let block_production_suspender = Arc::new(EnclaveBlockProductionSuspender::default());
+	GLOBAL_BLOCK_PRODUCTION_SUSPENDER_COMPONENT.initialize(block_production_suspender.clone());
+
 	let getter_executor = Arc::new(EnclaveGetterExecutor::new(state_observer));
-	let io_handler = public_api_rpc_handler(top_pool_author, getter_executor);
-	let rpc_handler = Arc::new(RpcWsHandler::new(io_handler, watch_extractor, connection_registry));
+	let top_pool_connection_observer =
+		Arc::new(TopPoolConnectionObserver::new(top_pool_author.clone()));
+	let io_handler = public_api_rpc_handler(
+		top_pool_author,
+		getter_executor,
+		head_subscriptions,
+		block_production_suspender,
+	);
+	let rpc_handler = Arc::new(
+		RpcWsHandler::new(io_handler, watch_extractor, connection_registry)
+			.with_connection_close_observer(top_pool_connection_observer),
+	);
 	GLOBAL_RPC_WS_HANDLER_COMPONENT.initialize(rpc_handler);
 
 	let sidechain_block_import_queue = Arc::new(EnclaveSidechainBlockImportQueue::default());
@@ -206,6 +237,7 @@ pub(crate) fn init_enclave_sidechain_components() -> EnclaveResult<()> {
 	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get()?;
 	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
 	let top_pool_author = GLOBAL_TOP_POOL_AUTHOR_COMPONENT.get()?;
+	let head_subscriptions = GLOBAL_HEAD_SUBSCRIPTIONS_COMPONENT.get()?;
 
 	let parentchain_block_import_dispatcher =
 		GLOBAL_TRIGGERED_PARENTCHAIN_IMPORT_DISPATCHER_COMPONENT.get()?;
@@ -214,18 +246,39 @@ pub(crate) fn init_enclave_sidechain_components() -> EnclaveResult<()> {
 
 	let signer = Ed25519Seal::unseal_from_static_file()?;
 
+	let finality_gadget = Arc::new(EnclaveSidechainFinalityGadget::new(
+		signer.clone(),
+		ocall_api.clone(),
+		ocall_api.clone(),
+		top_pool_author.clone(),
+	));
+	GLOBAL_SIDECHAIN_FINALITY_GADGET_COMPONENT.initialize(finality_gadget.clone());
+
+	let metadata_repository = GLOBAL_NODE_METADATA_REPOSITORY_COMPONENT.get()?;
+	let extrinsics_factory = GLOBAL_EXTRINSICS_FACTORY_COMPONENT.get()?;
+	let validator_accessor = GLOBAL_PARENTCHAIN_BLOCK_VALIDATOR_ACCESS_COMPONENT.get()?;
+
+	let equivocation_reporter = Arc::new(EnclaveSidechainEquivocationReporter::new(
+		SLOT_DURATION,
+		signer.public(),
+		metadata_repository.clone(),
+		extrinsics_factory.clone(),
+		validator_accessor.clone(),
+		ocall_api.clone(),
+	));
+
 	let sidechain_block_importer = Arc::new(EnclaveSidechainBlockImporter::new(
 		state_handler,
 		state_key_repository.clone(),
 		top_pool_author,
 		parentchain_block_import_dispatcher,
 		ocall_api.clone(),
+		head_subscriptions,
+		finality_gadget,
+		equivocation_reporter,
 	));
 
 	let sidechain_block_import_queue = GLOBAL_SIDECHAIN_IMPORT_QUEUE_COMPONENT.get()?;
-	let metadata_repository = GLOBAL_NODE_METADATA_REPOSITORY_COMPONENT.get()?;
-	let extrinsics_factory = GLOBAL_EXTRINSICS_FACTORY_COMPONENT.get()?;
-	let validator_accessor = GLOBAL_PARENTCHAIN_BLOCK_VALIDATOR_ACCESS_COMPONENT.get()?;
 
 	let sidechain_block_import_confirmation_handler =
 		Arc::new(EnclaveBlockImportConfirmationHandler::new(
@@ -234,11 +287,20 @@ pub(crate) fn init_enclave_sidechain_components() -> EnclaveResult<()> {
 			validator_accessor,
 		));
 
-	let sidechain_block_syncer = Arc::new(EnclaveSidechainBlockSyncer::new(
-		sidechain_block_importer,
-		ocall_api,
-		sidechain_block_import_confirmation_handler,
-	));
+	let sidechain_block_syncer = Arc::new(
+		EnclaveSidechainBlockSyncer::new(
+			sidechain_block_importer,
+			ocall_api.clone(),
+			ocall_api,
+			sidechain_block_import_confirmation_handler,
+		)
+		.with_sync_mode(PeerSyncMode::MultiPeer {
+			chunk_size: PEER_SYNC_CHUNK_SIZE,
+			max_attempts_per_chunk: PEER_SYNC_MAX_ATTEMPTS_PER_CHUNK,
+			max_parallel_requests: PEER_SYNC_MAX_PARALLEL_REQUESTS,
+		})
+		.with_own_account(signer.public().into()),
+	);
 	GLOBAL_SIDECHAIN_BLOCK_SYNCER_COMPONENT.initialize(sidechain_block_syncer.clone());
 
 	let sidechain_block_import_queue_worker =
@@ -248,9 +310,32 @@ pub(crate) fn init_enclave_sidechain_components() -> EnclaveResult<()> {
 		));
 	GLOBAL_SIDECHAIN_IMPORT_QUEUE_WORKER_COMPONENT.initialize(sidechain_block_import_queue_worker);
 
-	let block_composer = Arc::new(BlockComposer::new(signer, state_key_repository));
+	let block_composer = Arc::new(BlockComposer::new(signer, state_key_repository.clone()));
 	GLOBAL_SIDECHAIN_BLOCK_COMPOSER_COMPONENT.initialize(block_composer);
 
+	let stale_operations_evictor = Arc::new(EnclaveStaleOperationsEvictor::new(
+		GLOBAL_TOP_POOL_AUTHOR_COMPONENT.get()?,
+		GLOBAL_STATE_OBSERVER_COMPONENT.get()?,
+	));
+	GLOBAL_STALE_OPERATIONS_EVICTOR_COMPONENT.initialize(stale_operations_evictor);
+
+	// Learn the latest block number of each shard, so restored operations are valid from it on.
+	let state_handler = GLOBAL_STATE_HANDLER_COMPONENT.get()?;
+	evict_stale_operations(state_handler.as_ref(), &state_handler.list_shards()?);
+
+	let top_pool_persistence = Arc::new(EnclaveTopPoolPersistence::new(
+		GLOBAL_TOP_POOL_AUTHOR_COMPONENT.get()?,
+		GLOBAL_STATE_OBSERVER_COMPONENT.get()?,
+		state_key_repository,
+		GLOBAL_OCALL_API_COMPONENT.get()?,
+	));
+	match top_pool_persistence.restore() {
+		Ok(number_of_operations) =>
+			info!("Restored {} persisted trusted operations", number_of_operations),
+		Err(e) => warn!("Failed to restore persisted trusted operations: {:?}", e),
+	}
+	GLOBAL_TOP_POOL_PERSISTENCE_COMPONENT.initialize(top_pool_persistence);
+
 	Ok(())
 }
 
@@ -393,21 +478,41 @@ pub(crate) fn init_shard(shard: ShardIdentifier) -> EnclaveResult<()> {
 pub fn create_top_pool_author(
 	connection_registry: Arc<EnclaveRpcConnectionRegistry>,
 	state_handler: Arc<EnclaveStateHandler>,
+	state_observer: Arc<EnclaveStateObserver>,
 	ocall_api: Arc<EnclaveOCallApi>,
 	shielding_key_repository: Arc<EnclaveShieldingKeyRepository>,
 ) -> Arc<EnclaveTopPoolAuthor> {
 	let response_channel = Arc::new(RpcResponseChannel::default());
 	let rpc_responder = Arc::new(EnclaveRpcResponder::new(connection_registry, response_channel));
 
-	let side_chain_api = Arc::new(EnclaveSidechainApi::new());
+	let side_chain_api = Arc::new(EnclaveSidechainApi::new(state_observer));
 	let top_pool =
 		Arc::new(EnclaveTopPool::create(PoolOptions::default(), side_chain_api, rpc_responder));
 
-	Arc::new(EnclaveTopPoolAuthor::new(
-		top_pool,
-		AuthorTopFilter {},
-		state_handler,
-		shielding_key_repository,
-		ocall_api,
-	))
+	let sender_limits = SenderLimits {
+		max_future_operations: MAX_FUTURE_OPERATIONS_PER_SENDER,
+		max_pending_bytes: MAX_FUTURE_BYTES_PER_SENDER,
+		max_signature_failures: MAX_SIGNATURE_FAILURES_PER_CONNECTION,
+		ban_duration: CONNECTION_BAN_DURATION,
+	};
+
+	Arc::new(
+		EnclaveTopPoolAuthor::new(
+			top_pool,
+			AuthorTopFilter {},
+			state_handler,
+			shielding_key_repository,
+			ocall_api,
+		)
+		.with_sender_limits(sender_limits),
+	)
+}
+
+pub fn create_head_subscriptions(
+	connection_registry: Arc<EnclaveRpcConnectionRegistry>,
+) -> Arc<EnclaveHeadSubscriptions> {
+	let response_channel = Arc::new(RpcResponseChannel::default());
+	let rpc_responder = Arc::new(EnclaveRpcResponder::new(connection_registry, response_channel));
+
+	Arc::new(EnclaveHeadSubscriptions::new(rpc_responder))
 }
@@ -32,9 +32,12 @@ extern crate sgx_tstd as std;
 use crate::{
 	error::{Error, Result},
 	global_components::{
-		GLOBAL_IMMEDIATE_PARENTCHAIN_IMPORT_DISPATCHER_COMPONENT,
-		GLOBAL_NODE_METADATA_REPOSITORY_COMPONENT, GLOBAL_SIDECHAIN_IMPORT_QUEUE_COMPONENT,
-		GLOBAL_STATE_HANDLER_COMPONENT, GLOBAL_TRIGGERED_PARENTCHAIN_IMPORT_DISPATCHER_COMPONENT,
+		GLOBAL_ADMIN_ACCOUNT_COMPONENT, GLOBAL_IMMEDIATE_PARENTCHAIN_IMPORT_DISPATCHER_COMPONENT,
+		GLOBAL_NODE_METADATA_REPOSITORY_COMPONENT,
+		GLOBAL_PARENTCHAIN_BLOCK_VALIDATOR_ACCESS_COMPONENT,
+		GLOBAL_SIDECHAIN_FINALITY_GADGET_COMPONENT, GLOBAL_SIDECHAIN_IMPORT_QUEUE_COMPONENT,
+		GLOBAL_STATE_HANDLER_COMPONENT, GLOBAL_TOP_POOL_AUTHOR_COMPONENT,
+		GLOBAL_TRIGGERED_PARENTCHAIN_IMPORT_DISPATCHER_COMPONENT,
 	},
 	rpc::worker_api_direct::sidechain_io_handler,
 	utils::{utf8_str_from_raw, DecodeRaw},
@@ -44,21 +47,27 @@ use itc_parentchain::{
 	block_import_dispatcher::{
 		triggered_dispatcher::TriggerParentchainBlockImport, DispatchBlockImport,
 	},
-	light_client::light_client_init_params::LightClientInitParams,
+	light_client::{
+		concurrent_access::ValidatorAccess, light_client_init_params::LightClientInitParams,
+		LightClientState,
+	},
 };
 use itp_block_import_queue::PushToBlockQueue;
-use itp_component_container::ComponentGetter;
+use itp_component_container::{ComponentGetter, ComponentInitializer};
 use itp_node_api::metadata::NodeMetadata;
 use itp_nonce_cache::{MutateNonce, Nonce, GLOBAL_NONCE_CACHE};
 use itp_settings::worker_mode::{ProvideWorkerMode, WorkerMode, WorkerModeProvider};
 use itp_sgx_crypto::{ed25519, Ed25519Seal, Rsa3072Seal};
 use itp_sgx_io::StaticSealedIO;
-use itp_types::{Header, ShardIdentifier, SignedBlock};
+use itp_top_pool_author::traits::AuthorApi;
+use itp_types::{AccountId, Header, Request, ShardIdentifier, SignedBlock};
 use itp_utils::write_slice_and_whitespace_pad;
+use its_sidechain::consensus_common::HandleFinalityVotes;
+use jsonrpc_core::futures::executor;
 use log::*;
 use sgx_types::sgx_status_t;
 use sp_core::crypto::Pair;
-use std::{boxed::Box, slice, vec::Vec};
+use std::{boxed::Box, slice, sync::Arc, vec::Vec};
 
 mod attestation;
 mod empty_impls;
@@ -207,6 +216,25 @@ pub unsafe extern "C" fn set_node_metadata(
 	sgx_status_t::SGX_SUCCESS
 }
 
+/// Sets the admin account of this validateer, which is authorized to suspend and resume the
+/// sidechain block production of a shard.
+#[no_mangle]
+pub unsafe extern "C" fn set_admin_account(account: *const u8, account_size: u32) -> sgx_status_t {
+	let mut account_slice = slice::from_raw_parts(account, account_size as usize);
+	let admin_account = match AccountId::decode(&mut account_slice).map_err(Error::Codec) {
+		Err(e) => {
+			error!("Failed to decode admin account: {:?}", e);
+			return sgx_status_t::SGX_ERROR_UNEXPECTED
+		},
+		Ok(a) => a,
+	};
+
+	info!("Set admin account: {:?}", admin_account);
+	GLOBAL_ADMIN_ACCOUNT_COMPONENT.initialize(Arc::new(admin_account));
+
+	sgx_status_t::SGX_SUCCESS
+}
+
 /// This is reduced to the sidechain block import RPC interface (i.e. worker-worker communication).
 /// The entire rest of the RPC server is run inside the enclave and does not use this e-call function anymore.
 #[no_mangle]
@@ -243,9 +271,29 @@ pub unsafe extern "C" fn call_rpc_methods(
 fn sidechain_rpc_int(request: &str) -> Result<String> {
 	let sidechain_block_import_queue = GLOBAL_SIDECHAIN_IMPORT_QUEUE_COMPONENT.get()?;
 
-	let io = sidechain_io_handler(move |signed_block| {
-		sidechain_block_import_queue.push_single(signed_block)
-	});
+	let io = sidechain_io_handler(
+		move |signed_block| sidechain_block_import_queue.push_single(signed_block),
+		|signed_vote| -> Result<()> {
+			// The finality gadget is only available once the sidechain components are initialized.
+			let finality_gadget = GLOBAL_SIDECHAIN_FINALITY_GADGET_COMPONENT.get()?;
+			// Votes are checked against the validateer set of the latest imported parentchain block.
+			let latest_parentchain_header = GLOBAL_PARENTCHAIN_BLOCK_VALIDATOR_ACCESS_COMPONENT
+				.get()?
+				.execute_on_validator(|v| v.latest_finalized_header(v.num_relays()))?;
+			finality_gadget.import_vote(signed_vote, &latest_parentchain_header)?;
+			Ok(())
+		},
+		|encrypted_operation: Request| -> Result<()> {
+			// Trusted operations gossiped by a peer validateer are not gossiped any further.
+			let top_pool_author = GLOBAL_TOP_POOL_AUTHOR_COMPONENT.get()?;
+			executor::block_on(
+				top_pool_author
+					.import_top(encrypted_operation.cyphertext, encrypted_operation.shard),
+			)
+			.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+			Ok(())
+		},
+	);
 
 	// note: errors are still returned as Option<String>
 	Ok(io
@@ -88,6 +88,48 @@ extern "C" {
 		sidechain_blocks_size: u32,
 	) -> sgx_status_t;
 
+	pub fn ocall_fetch_sidechain_block_ranges_from_peers(
+		ret_val: *mut sgx_status_t,
+		requests: *const u8,
+		requests_size: u32,
+		shard_identifier: *const u8,
+		shard_identifier_size: u32,
+		sidechain_block_ranges: *mut u8,
+		sidechain_block_ranges_size: u32,
+	) -> sgx_status_t;
+
+	pub fn ocall_propose_finality_votes(
+		ret_val: *mut sgx_status_t,
+		signed_votes: *const u8,
+		signed_votes_size: u32,
+	) -> sgx_status_t;
+
+	pub fn ocall_propose_trusted_operations(
+		ret_val: *mut sgx_status_t,
+		trusted_operations: *const u8,
+		trusted_operations_size: u32,
+	) -> sgx_status_t;
+
+	pub fn ocall_finalize_sidechain_block(
+		ret_val: *mut sgx_status_t,
+		shard_identifier: *const u8,
+		shard_identifier_size: u32,
+		block_hash: *const u8,
+		block_hash_size: u32,
+	) -> sgx_status_t;
+
+	pub fn ocall_store_trusted_operation_pool(
+		ret_val: *mut sgx_status_t,
+		encrypted_pool: *const u8,
+		encrypted_pool_size: u32,
+	) -> sgx_status_t;
+
+	pub fn ocall_load_trusted_operation_pool(
+		ret_val: *mut sgx_status_t,
+		encrypted_pool: *mut u8,
+		encrypted_pool_size: u32,
+	) -> sgx_status_t;
+
 	pub fn ocall_send_to_parentchain(
 		ret_val: *mut sgx_status_t,
 		extrinsics: *const u8,
@@ -20,11 +20,15 @@ use crate::ocall::{ffi, OcallApi};
 use codec::{Decode, Encode};
 use frame_support::ensure;
 use itp_ocall_api::EnclaveSidechainOCallApi;
-use itp_types::{BlockHash, ShardIdentifier};
+use itp_settings::sidechain::{MAX_BLOCK_ENCODED_SIZE, MAX_PERSISTED_TOP_POOL_SIZE};
+use itp_types::{BlockHash, PeerBlockRangeRequest, Request, ShardIdentifier};
 use log::*;
 use sgx_types::{sgx_status_t, SgxResult};
 use std::vec::Vec;
 
+/// Space reserved per requested range for the encoding of the option and vector prefixes.
+const BLOCK_RANGES_ENCODING_OVERHEAD: usize = 1024;
+
 impl EnclaveSidechainOCallApi for OcallApi {
 	fn propose_sidechain_blocks<SignedSidechainBlock: Encode>(
 		&self,
@@ -107,4 +111,151 @@ impl EnclaveSidechainOCallApi for OcallApi {
 
 		Ok(decoded_signed_blocks)
 	}
+
+	fn fetch_sidechain_block_ranges_from_peers<SignedSidechainBlock: Decode>(
+		&self,
+		requests: Vec<PeerBlockRangeRequest>,
+		shard_identifier: ShardIdentifier,
+	) -> SgxResult<Vec<Option<Vec<SignedSidechainBlock>>>> {
+		let mut rt: sgx_status_t = sgx_status_t::SGX_ERROR_UNEXPECTED;
+		let requests_encoded = requests.encode();
+		let shard_identifier_encoded = shard_identifier.encode();
+
+		// We have to pre-allocate the vector, so reserve space for every requested block to be
+		// of maximum size, plus the encoding overhead of the ranges.
+		let requested_blocks: usize = requests.iter().map(|r| r.limit as usize).sum();
+		let buffer_size = requested_blocks.max(1) * MAX_BLOCK_ENCODED_SIZE
+			+ requests.len().max(1) * BLOCK_RANGES_ENCODING_OVERHEAD;
+		let mut signed_block_ranges_encoded: Vec<u8> = vec![0; buffer_size];
+
+		let res = unsafe {
+			ffi::ocall_fetch_sidechain_block_ranges_from_peers(
+				&mut rt as *mut sgx_status_t,
+				requests_encoded.as_ptr(),
+				requests_encoded.len() as u32,
+				shard_identifier_encoded.as_ptr(),
+				shard_identifier_encoded.len() as u32,
+				signed_block_ranges_encoded.as_mut_ptr(),
+				signed_block_ranges_encoded.len() as u32,
+			)
+		};
+
+		ensure!(rt == sgx_status_t::SGX_SUCCESS, rt);
+		ensure!(res == sgx_status_t::SGX_SUCCESS, res);
+
+		let decoded_signed_block_ranges: Vec<Option<Vec<SignedSidechainBlock>>> =
+			Decode::decode(&mut signed_block_ranges_encoded.as_slice()).map_err(|e| {
+				error!("Failed to decode sidechain block ranges: {}", e);
+				sgx_status_t::SGX_ERROR_UNEXPECTED
+			})?;
+
+		Ok(decoded_signed_block_ranges)
+	}
+
+	fn propose_finality_votes<SignedFinalityVote: Encode>(
+		&self,
+		signed_votes: Vec<SignedFinalityVote>,
+	) -> SgxResult<()> {
+		let mut rt: sgx_status_t = sgx_status_t::SGX_ERROR_UNEXPECTED;
+		let signed_votes_encoded = signed_votes.encode();
+
+		let res = unsafe {
+			ffi::ocall_propose_finality_votes(
+				&mut rt as *mut sgx_status_t,
+				signed_votes_encoded.as_ptr(),
+				signed_votes_encoded.len() as u32,
+			)
+		};
+
+		ensure!(rt == sgx_status_t::SGX_SUCCESS, rt);
+		ensure!(res == sgx_status_t::SGX_SUCCESS, res);
+
+		Ok(())
+	}
+
+	fn propose_trusted_operations(&self, trusted_operations: Vec<Request>) -> SgxResult<()> {
+		let mut rt: sgx_status_t = sgx_status_t::SGX_ERROR_UNEXPECTED;
+		let trusted_operations_encoded = trusted_operations.encode();
+
+		let res = unsafe {
+			ffi::ocall_propose_trusted_operations(
+				&mut rt as *mut sgx_status_t,
+				trusted_operations_encoded.as_ptr(),
+				trusted_operations_encoded.len() as u32,
+			)
+		};
+
+		ensure!(rt == sgx_status_t::SGX_SUCCESS, rt);
+		ensure!(res == sgx_status_t::SGX_SUCCESS, res);
+
+		Ok(())
+	}
+
+	fn finalize_sidechain_block(
+		&self,
+		shard_identifier: ShardIdentifier,
+		block_hash: BlockHash,
+	) -> SgxResult<()> {
+		let mut rt: sgx_status_t = sgx_status_t::SGX_ERROR_UNEXPECTED;
+		let shard_identifier_encoded = shard_identifier.encode();
+		let block_hash_encoded = block_hash.encode();
+
+		let res = unsafe {
+			ffi::ocall_finalize_sidechain_block(
+				&mut rt as *mut sgx_status_t,
+				shard_identifier_encoded.as_ptr(),
+				shard_identifier_encoded.len() as u32,
+				block_hash_encoded.as_ptr(),
+				block_hash_encoded.len() as u32,
+			)
+		};
+
+		ensure!(rt == sgx_status_t::SGX_SUCCESS, rt);
+		ensure!(res == sgx_status_t::SGX_SUCCESS, res);
+
+		Ok(())
+	}
+
+	fn store_trusted_operation_pool(&self, encrypted_pool: Vec<u8>) -> SgxResult<()> {
+		let mut rt: sgx_status_t = sgx_status_t::SGX_ERROR_UNEXPECTED;
+
+		let res = unsafe {
+			ffi::ocall_store_trusted_operation_pool(
+				&mut rt as *mut sgx_status_t,
+				encrypted_pool.as_ptr(),
+				encrypted_pool.len() as u32,
+			)
+		};
+
+		ensure!(rt == sgx_status_t::SGX_SUCCESS, rt);
+		ensure!(res == sgx_status_t::SGX_SUCCESS, res);
+
+		Ok(())
+	}
+
+	fn load_trusted_operation_pool(&self) -> SgxResult<Option<Vec<u8>>> {
+		let mut rt: sgx_status_t = sgx_status_t::SGX_ERROR_UNEXPECTED;
+
+		// The stored pool is SCALE encoded as an `Option`, padded to the size of the buffer.
+		let mut encrypted_pool_encoded: Vec<u8> = vec![0; MAX_PERSISTED_TOP_POOL_SIZE];
+
+		let res = unsafe {
+			ffi::ocall_load_trusted_operation_pool(
+				&mut rt as *mut sgx_status_t,
+				encrypted_pool_encoded.as_mut_ptr(),
+				encrypted_pool_encoded.len() as u32,
+			)
+		};
+
+		ensure!(rt == sgx_status_t::SGX_SUCCESS, rt);
+		ensure!(res == sgx_status_t::SGX_SUCCESS, res);
+
+		let maybe_encrypted_pool: Option<Vec<u8>> =
+			Decode::decode(&mut encrypted_pool_encoded.as_slice()).map_err(|e| {
+				error!("Failed to decode persisted trusted operation pool: {}", e);
+				sgx_status_t::SGX_ERROR_UNEXPECTED
+			})?;
+
+		Ok(maybe_encrypted_pool)
+	}
 }
@@ -15,26 +15,41 @@
 
 */
 
+use crate::global_components::GLOBAL_ADMIN_ACCOUNT_COMPONENT;
 use codec::Encode;
 use core::result::Result;
 use ita_sgx_runtime::Runtime;
+use itc_direct_rpc_server::{RpcIoHandler, SendSubscriptionNotification};
+use itp_component_container::ComponentGetter;
 use itp_primitives_cache::{GetPrimitives, GLOBAL_PRIMITIVES_CACHE};
 use itp_rpc::RpcReturnValue;
+use itp_settings::sidechain::BLOCK_PRODUCTION_ADMIN_REQUEST_VALIDITY;
 use itp_sgx_crypto::Rsa3072Seal;
 use itp_stf_executor::getter_executor::ExecuteGetter;
+use itp_time_utils::now_as_u64;
 use itp_top_pool_author::traits::AuthorApi;
-use itp_types::{DirectRequestStatus, Request, ShardIdentifier, H256};
+use itp_types::{
+	BlockProductionCommand, DirectRequestStatus, Request, ShardIdentifier,
+	SignedBlockProductionAdminRequest, H256,
+};
 use itp_utils::{FromHexPrefixed, ToHexPrefixed};
-use its_primitives::types::block::SignedBlock;
-use its_sidechain::rpc_handler::{direct_top_pool_api, import_block_api};
-use jsonrpc_core::{serde_json::json, IoHandler, Params, Value};
+use its_primitives::types::{block::SignedBlock, finality::SignedFinalityVote};
+use its_sidechain::{
+	consensus_common::{IsShardBlockProductionSuspended, SuspendShardBlockProduction},
+	rpc_handler::{
+		block_production_api, direct_top_pool_api, finality_vote_api,
+		head_subscription_api::{self, HeadSubscriptions},
+		import_block_api, import_trusted_operations_api,
+	},
+};
+use jsonrpc_core::{serde_json::json, Params, Value};
 use std::{borrow::ToOwned, format, str, string::String, sync::Arc, vec::Vec};
 
 fn compute_hex_encoded_return_error(error_msg: &str) -> String {
 	RpcReturnValue::from_error_message(error_msg).to_hex()
 }
 
-fn get_all_rpc_methods_string(io_handler: &IoHandler) -> String {
+fn get_all_rpc_methods_string(io_handler: &RpcIoHandler) -> String {
 	let method_string = io_handler
 		.iter()
 		.map(|rp_tuple| rp_tuple.0.to_owned())
@@ -44,12 +59,19 @@ fn get_all_rpc_methods_string(io_handler: &IoHandler) -> String {
 	format!("methods: [{}]", method_string)
 }
 
-pub fn public_api_rpc_handler<R, G>(top_pool_author: Arc<R>, getter_executor: Arc<G>) -> IoHandler
+pub fn public_api_rpc_handler<R, G, S, B>(
+	top_pool_author: Arc<R>,
+	getter_executor: Arc<G>,
+	head_subscriptions: Arc<HeadSubscriptions<S>>,
+	block_production_suspender: Arc<B>,
+) -> RpcIoHandler
 where
 	R: AuthorApi<H256, H256> + Send + Sync + 'static,
 	G: ExecuteGetter + Send + Sync + 'static,
+	S: SendSubscriptionNotification<Hash = H256> + 'static,
+	B: SuspendShardBlockProduction + IsShardBlockProductionSuspended + Send + Sync + 'static,
 {
-	let io = IoHandler::new();
+	let io = RpcIoHandler::new();
 
 	// Add direct TOP pool rpc methods
 	let mut io = direct_top_pool_api::add_top_pool_direct_rpc_methods(top_pool_author, io);
@@ -106,12 +128,18 @@ where
 		Ok(json!(json_value.to_hex()))
 	});
 
-	// chain_subscribeAllHeads
-	let chain_subscribe_all_heads_name: &str = "chain_subscribeAllHeads";
-	io.add_sync_method(chain_subscribe_all_heads_name, |_: Params| {
-		let parsed = "world";
-		Ok(Value::String(format!("hello, {}", parsed)))
-	});
+	// chain_subscribeAllHeads and chain_unsubscribeAllHeads
+	let io = head_subscription_api::add_head_subscription_rpc_methods(head_subscriptions, io);
+
+	// sidechain_suspendBlockProduction, sidechain_resumeBlockProduction and sidechain_getBlockProductionStatus
+	let control_suspender = block_production_suspender.clone();
+	let mut io = block_production_api::add_block_production_rpc_methods(
+		move |signed_request| control_block_production(control_suspender.as_ref(), signed_request),
+		move |shard| {
+			block_production_suspender.shard_status(&shard).map_err(|e| format!("{:?}", e))
+		},
+		io,
+	);
 
 	// state_getMetadata
 	let state_get_metadata_name: &str = "state_getMetadata";
@@ -173,6 +201,31 @@ where
 	io
 }
 
+/// Suspends or resumes the block production of a shard, if the request is signed by the admin
+/// account of this validateer.
+fn control_block_production<B: SuspendShardBlockProduction>(
+	block_production_suspender: &B,
+	signed_request: SignedBlockProductionAdminRequest,
+) -> Result<(), String> {
+	let admin = GLOBAL_ADMIN_ACCOUNT_COMPONENT
+		.get()
+		.map_err(|_| "No admin account is configured for this validateer".to_owned())?;
+
+	if !signed_request.verify(&admin, now_as_u64(), BLOCK_PRODUCTION_ADMIN_REQUEST_VALIDITY) {
+		return Err("Admin request is not signed by the admin account or has expired".to_owned())
+	}
+	block_production_suspender
+		.use_admin_nonce(signed_request.request.nonce)
+		.map_err(|e| format!("{:?}", e))?;
+
+	let shard = signed_request.request.shard;
+	match signed_request.request.command {
+		BlockProductionCommand::Suspend => block_production_suspender.suspend_shard(&shard),
+		BlockProductionCommand::Resume => block_production_suspender.resume_shard(&shard),
+	}
+	.map_err(|e| format!("{:?}", e))
+}
+
 fn execute_getter_inner<G: ExecuteGetter>(
 	getter_executor: &G,
 	params: Params,
@@ -192,13 +245,23 @@ fn execute_getter_inner<G: ExecuteGetter>(
 	Ok(getter_result)
 }
 
-pub fn sidechain_io_handler<ImportFn, Error>(import_fn: ImportFn) -> IoHandler
+pub fn sidechain_io_handler<ImportFn, ImportVoteFn, ImportTopFn, Error, VoteError, TopError>(
+	import_fn: ImportFn,
+	import_vote_fn: ImportVoteFn,
+	import_top_fn: ImportTopFn,
+) -> RpcIoHandler
 where
 	ImportFn: Fn(SignedBlock) -> Result<(), Error> + Sync + Send + 'static,
+	ImportVoteFn: Fn(SignedFinalityVote) -> Result<(), VoteError> + Sync + Send + 'static,
+	ImportTopFn: Fn(Request) -> Result<(), TopError> + Sync + Send + 'static,
 	Error: std::fmt::Debug,
+	VoteError: std::fmt::Debug,
+	TopError: std::fmt::Debug,
 {
-	let io = IoHandler::new();
-	import_block_api::add_import_block_rpc_method(import_fn, io)
+	let io = RpcIoHandler::new();
+	let io = import_block_api::add_import_block_rpc_method(import_fn, io);
+	let io = finality_vote_api::add_import_finality_votes_rpc_method(import_vote_fn, io);
+	import_trusted_operations_api::add_import_trusted_operations_rpc_method(import_top_fn, io)
 }
 
 #[cfg(feature = "test")]
@@ -207,7 +270,7 @@ pub mod tests {
 	use std::string::ToString;
 
 	pub fn test_given_io_handler_methods_then_retrieve_all_names_as_string() {
-		let mut io = IoHandler::new();
+		let mut io = RpcIoHandler::new();
 		let method_names: [&str; 4] = ["method1", "another_method", "fancy_thing", "solve_all"];
 
 		for method_name in method_names.iter() {
@@ -16,7 +16,11 @@
 
 */
 
-use crate::{rpc::worker_api_direct::public_api_rpc_handler, Hash};
+use crate::{
+	rpc::worker_api_direct::public_api_rpc_handler,
+	test::mocks::types::{TestHeadSubscriptions, TestRpcResponder},
+	Hash,
+};
 use codec::{Decode, Encode};
 use ita_stf::{Getter, TrustedGetter, TrustedGetterSigned};
 use itc_direct_rpc_server::{
@@ -30,9 +34,15 @@ use itp_stf_state_observer::mock::ObserveStateMock;
 use itp_top_pool_author::mocks::AuthorApiMock;
 use itp_types::{AccountId, DirectRequestStatus, Request, ShardIdentifier};
 use itp_utils::{FromHexPrefixed, ToHexPrefixed};
+use its_sidechain::consensus_common::BlockProductionSuspender;
 use sp_core::ed25519::Signature;
 use sp_runtime::MultiSignature;
-use std::{string::ToString, sync::Arc, vec::Vec};
+use std::{
+	net::{IpAddr, Ipv4Addr},
+	string::ToString,
+	sync::Arc,
+	vec::Vec,
+};
 
 pub fn get_state_request_works() {
 	type TestState = u64;
@@ -45,7 +55,15 @@ pub fn get_state_request_works() {
 	let getter_executor =
 		Arc::new(GetterExecutor::<_, GetStateMock<TestState>>::new(state_observer));
 	let top_pool_author = Arc::new(AuthorApiMock::default());
-	let io_handler = public_api_rpc_handler(top_pool_author, getter_executor);
+	let head_subscriptions =
+		Arc::new(TestHeadSubscriptions::new(Arc::new(TestRpcResponder::new())));
+	let block_production_suspender = Arc::new(BlockProductionSuspender::default());
+	let io_handler = public_api_rpc_handler(
+		top_pool_author,
+		getter_executor,
+		head_subscriptions,
+		block_production_suspender,
+	);
 	let rpc_handler = Arc::new(RpcWsHandler::new(io_handler, watch_extractor, connection_registry));
 
 	let getter = Getter::trusted(TrustedGetterSigned::new(
@@ -59,8 +77,10 @@ pub fn get_state_request_works() {
 		RpcRequest::compose_jsonrpc_call("state_executeGetter".to_string(), vec![request.to_hex()])
 			.unwrap();
 
-	let response_string =
-		rpc_handler.handle_message(ConnectionToken(1), request_string).unwrap().unwrap();
+	let response_string = rpc_handler
+		.handle_message(ConnectionToken(1), IpAddr::V4(Ipv4Addr::LOCALHOST), request_string)
+		.unwrap()
+		.unwrap();
 
 	assert!(!response_string.is_empty());
 
@@ -16,21 +16,26 @@
 
 */
 
-use crate::test::mocks::types::{TestOCallApi, TestRpcResponder, TestSigner, TestTopPool};
+use crate::test::mocks::types::{
+	TestOCallApi, TestRpcResponder, TestSidechainApi, TestSigner, TestTopPool,
+};
 use codec::Encode;
 use ita_stf::{KeyPair, TrustedCall, TrustedCallSigned, TrustedOperation};
 use itp_ocall_api::EnclaveAttestationOCallApi;
 use itp_sgx_crypto::ShieldingCryptoEncrypt;
+use itp_sgx_externalities::SgxExternalities;
+use itp_stf_state_observer::mock::ObserveStateMock;
 use itp_top_pool::pool::Options as PoolOptions;
-use itp_top_pool_author::api::SidechainApi;
-use itp_types::{Block as ParentchainBlock, Enclave, ShardIdentifier};
+use itp_types::{Enclave, ShardIdentifier};
 use sp_core::{ed25519, Pair, H256};
 use sp_runtime::traits::Header as HeaderTrait;
 use std::{sync::Arc, vec::Vec};
 
 pub(crate) fn create_top_pool() -> Arc<TestTopPool> {
 	let rpc_responder = Arc::new(TestRpcResponder::new());
-	let sidechain_api = Arc::new(SidechainApi::<ParentchainBlock>::new());
+	// All accounts have nonce 0 in the default state.
+	let state_observer = Arc::new(ObserveStateMock::new(SgxExternalities::default()));
+	let sidechain_api = Arc::new(TestSidechainApi::new(state_observer));
 	Arc::new(TestTopPool::create(PoolOptions::default(), sidechain_api, rpc_responder))
 }
 
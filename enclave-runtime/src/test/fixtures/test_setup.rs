@@ -27,8 +27,9 @@ use itp_ocall_api::EnclaveAttestationOCallApi;
 use itp_sgx_crypto::{ed25519_derivation::DeriveEd25519, mocks::KeyRepositoryMock};
 use itp_sgx_externalities::SgxExternalities;
 use itp_stf_executor::executor::StfExecutor;
+use itp_stf_state_observer::mock::ObserveStateMock;
 use itp_test::mock::{
-	handle_state_mock::HandleStateMock, metrics_ocall_mock::MetricsOCallMock,
+	handle_state_mock::HandleStateMock, onchain_mock::OnchainMock,
 	shielding_crypto_mock::ShieldingCryptoMock,
 };
 use itp_top_pool::{basic_pool::BasicPool, pool::ExtrinsicHash};
@@ -37,16 +38,12 @@ use itp_types::{Block, MrEnclave};
 use sp_core::{crypto::Pair, ed25519 as spEd25519};
 use std::sync::Arc;
 
-pub type TestRpcResponder = RpcResponderMock<ExtrinsicHash<SidechainApi<Block>>>;
-pub type TestTopPool = BasicPool<SidechainApi<Block>, Block, TestRpcResponder>;
+pub type TestSidechainApi = SidechainApi<Block, ObserveStateMock<SgxExternalities>, TestStf>;
+pub type TestRpcResponder = RpcResponderMock<ExtrinsicHash<TestSidechainApi>>;
+pub type TestTopPool = BasicPool<TestSidechainApi, Block, TestRpcResponder>;
 pub type TestShieldingKeyRepo = KeyRepositoryMock<ShieldingCryptoMock>;
-pub type TestTopPoolAuthor = Author<
-	TestTopPool,
-	AllowAllTopsFilter,
-	HandleStateMock,
-	TestShieldingKeyRepo,
-	MetricsOCallMock,
->;
+pub type TestTopPoolAuthor =
+	Author<TestTopPool, AllowAllTopsFilter, HandleStateMock, TestShieldingKeyRepo, OnchainMock>;
 pub type TestStf = Stf<TrustedCallSigned, Getter, SgxExternalities, Runtime>;
 
 pub type TestStfExecutor =
@@ -85,7 +82,7 @@ pub fn test_setup() -> (
 			AllowAllTopsFilter,
 			state_handler.clone(),
 			shielding_key_repo,
-			Arc::new(MetricsOCallMock::default()),
+			Arc::new(OnchainMock::default().with_mr_enclave(mrenclave)),
 		)),
 		state,
 		shard,
@@ -97,7 +94,9 @@ pub fn test_setup() -> (
 }
 
 pub fn test_top_pool() -> TestTopPool {
-	let chain_api = Arc::new(SidechainApi::<Block>::new());
+	// All accounts have nonce 0 in the default state.
+	let state_observer = Arc::new(ObserveStateMock::new(SgxExternalities::default()));
+	let chain_api = Arc::new(TestSidechainApi::new(state_observer));
 	let top_pool =
 		BasicPool::create(Default::default(), chain_api, Arc::new(TestRpcResponder::new()));
 
@@ -20,8 +20,8 @@ use crate::test::mocks::types::TestBlockImporter;
 use codec::{Decode, Encode};
 use itp_ocall_api::{EnclaveOnChainOCallApi, EnclaveSidechainOCallApi, Result};
 use itp_types::{
-	storage::StorageEntryVerified, BlockHash, Header as ParentchainHeader, ShardIdentifier,
-	WorkerRequest, WorkerResponse, H256,
+	storage::StorageEntryVerified, BlockHash, Header as ParentchainHeader, PeerBlockRangeRequest,
+	Request, ShardIdentifier, WorkerRequest, WorkerResponse, H256,
 };
 use its_primitives::types::block::SignedBlock as SignedSidechainBlockType;
 use its_sidechain::consensus_common::BlockImport;
@@ -120,4 +120,39 @@ impl EnclaveSidechainOCallApi for ProposeToImportOCallApi {
 	) -> SgxResult<Vec<SignedSidechainBlock>> {
 		Ok(Vec::new())
 	}
+
+	fn fetch_sidechain_block_ranges_from_peers<SignedSidechainBlock: Decode>(
+		&self,
+		requests: Vec<PeerBlockRangeRequest>,
+		_shard_identifier: ShardIdentifier,
+	) -> SgxResult<Vec<Option<Vec<SignedSidechainBlock>>>> {
+		Ok(requests.iter().map(|_| None).collect())
+	}
+
+	fn propose_finality_votes<SignedFinalityVote: Encode>(
+		&self,
+		_signed_votes: Vec<SignedFinalityVote>,
+	) -> SgxResult<()> {
+		Ok(())
+	}
+
+	fn propose_trusted_operations(&self, _trusted_operations: Vec<Request>) -> SgxResult<()> {
+		Ok(())
+	}
+
+	fn finalize_sidechain_block(
+		&self,
+		_shard_identifier: ShardIdentifier,
+		_block_hash: BlockHash,
+	) -> SgxResult<()> {
+		Ok(())
+	}
+
+	fn store_trusted_operation_pool(&self, _encrypted_pool: Vec<u8>) -> SgxResult<()> {
+		Ok(())
+	}
+
+	fn load_trusted_operation_pool(&self) -> SgxResult<Option<Vec<u8>>> {
+		Ok(None)
+	}
 }
@@ -15,7 +15,9 @@
 
 */
 
-use itc_direct_rpc_server::{DirectRpcResult, RpcHash, SendRpcResponse};
+use itc_direct_rpc_server::{
+	DirectRpcResult, RpcHash, SendRpcResponse, SendSubscriptionNotification,
+};
 use itp_types::TrustedOperationStatus;
 use std::{marker::PhantomData, vec::Vec};
 
@@ -47,3 +49,22 @@ where
 		Ok(())
 	}
 }
+
+impl<Hash> SendSubscriptionNotification for RpcResponderMock<Hash>
+where
+	Hash: RpcHash,
+{
+	type Hash = Hash;
+
+	fn send_notification(
+		&self,
+		_subscription: Self::Hash,
+		_value_encoded: Vec<u8>,
+	) -> DirectRpcResult<()> {
+		Ok(())
+	}
+
+	fn close_subscription(&self, _subscription: &Self::Hash) -> DirectRpcResult<()> {
+		Ok(())
+	}
+}
@@ -21,21 +21,32 @@
 use crate::test::mocks::rpc_responder_mock::RpcResponderMock;
 use ita_sgx_runtime::Runtime;
 use ita_stf::{Getter, Stf, TrustedCallSigned};
-use itc_parentchain::block_import_dispatcher::trigger_parentchain_block_import_mock::TriggerParentchainBlockImportMock;
+use itc_parentchain::{
+	block_import_dispatcher::trigger_parentchain_block_import_mock::TriggerParentchainBlockImportMock,
+	light_client::mocks::validator_access_mock::ValidatorAccessMock,
+};
+use itp_extrinsics_factory::mock::ExtrinsicsFactoryMock;
 use itp_node_api::metadata::{metadata_mocks::NodeMetadataMock, provider::NodeMetadataRepository};
 use itp_sgx_crypto::{mocks::KeyRepositoryMock, Aes};
 use itp_sgx_externalities::SgxExternalities;
 use itp_stf_executor::executor::StfExecutor;
+use itp_stf_state_observer::mock::ObserveStateMock;
 use itp_test::mock::{
 	handle_state_mock::HandleStateMock, metrics_ocall_mock::MetricsOCallMock,
 	onchain_mock::OnchainMock,
 };
 use itp_top_pool::basic_pool::BasicPool;
 use itp_top_pool_author::{api::SidechainApi, author::Author, top_filter::AllowAllTopsFilter};
-use itp_types::{Block as ParentchainBlock, SignedBlock as SignedParentchainBlock};
+use itp_types::{
+	Block as ParentchainBlock, Header as ParentchainHeader, SignedBlock as SignedParentchainBlock,
+};
 use its_primitives::types::{Block as SidechainBlock, SignedBlock as SignedSidechainBlock};
 use its_sidechain::{
-	aura::block_importer::BlockImporter, block_composer::BlockComposer, state::SidechainDB,
+	aura::block_importer::BlockImporter,
+	block_composer::BlockComposer,
+	consensus_common::{EquivocationReporter, FinalityGadget},
+	rpc_handler::head_subscription_api::HeadSubscriptions,
+	state::SidechainDB,
 };
 use primitive_types::H256;
 use sgx_crypto_helper::rsa3072::Rsa3072KeyPair;
@@ -69,20 +80,31 @@ pub type TestStfExecutor =
 
 pub type TestRpcResponder = RpcResponderMock<H256>;
 
-pub type TestTopPool =
-	BasicPool<SidechainApi<ParentchainBlock>, ParentchainBlock, TestRpcResponder>;
+pub type TestHeadSubscriptions = HeadSubscriptions<TestRpcResponder>;
 
-pub type TestTopPoolAuthor = Author<
-	TestTopPool,
-	AllowAllTopsFilter,
-	TestStateHandler,
-	TestShieldingKeyRepo,
-	MetricsOCallMock,
->;
+pub type TestSidechainApi =
+	SidechainApi<ParentchainBlock, ObserveStateMock<SgxExternalities>, TestStf>;
+
+pub type TestTopPool = BasicPool<TestSidechainApi, ParentchainBlock, TestRpcResponder>;
+
+pub type TestTopPoolAuthor =
+	Author<TestTopPool, AllowAllTopsFilter, TestStateHandler, TestShieldingKeyRepo, TestOCallApi>;
 
 pub type TestBlockComposer =
 	BlockComposer<ParentchainBlock, SignedSidechainBlock, TestSigner, TestStateKeyRepo>;
 
+pub type TestFinalityGadget =
+	FinalityGadget<ParentchainHeader, TestOCallApi, TestOCallApi, TestTopPoolAuthor>;
+
+pub type TestEquivocationReporter = EquivocationReporter<
+	ParentchainBlock,
+	SignedSidechainBlock,
+	TestNodeMetadataRepository,
+	ExtrinsicsFactoryMock,
+	ValidatorAccessMock,
+	MetricsOCallMock,
+>;
+
 pub type TestBlockImporter = BlockImporter<
 	TestSigner,
 	ParentchainBlock,
@@ -93,4 +115,7 @@ pub type TestBlockImporter = BlockImporter<
 	TestStateKeyRepo,
 	TestTopPoolAuthor,
 	TestParentchainBlockImportTrigger,
+	TestHeadSubscriptions,
+	TestFinalityGadget,
+	TestEquivocationReporter,
 >;
@@ -107,15 +107,32 @@ pub fn produce_sidechain_block_and_import_it() {
 		AllowAllTopsFilter {},
 		state_handler.clone(),
 		shielding_key_repo,
-		Arc::new(MetricsOCallMock::default()),
+		ocall_api.clone(),
 	));
 	let parentchain_block_import_trigger = Arc::new(TestParentchainBlockImportTrigger::default());
+	let finality_gadget = Arc::new(TestFinalityGadget::new(
+		signer.clone(),
+		ocall_api.clone(),
+		ocall_api.clone(),
+		top_pool_author.clone(),
+	));
+	let equivocation_reporter = Arc::new(TestEquivocationReporter::new(
+		SLOT_DURATION,
+		signer.public(),
+		node_metadata_repo.clone(),
+		Arc::new(ExtrinsicsFactoryMock::default()),
+		Arc::new(ValidatorAccessMock::default()),
+		Arc::new(MetricsOCallMock::default()),
+	));
 	let block_importer = Arc::new(TestBlockImporter::new(
 		state_handler.clone(),
 		state_key_repo.clone(),
 		top_pool_author.clone(),
 		parentchain_block_import_trigger.clone(),
 		ocall_api.clone(),
+		Arc::new(TestHeadSubscriptions::new(Arc::new(TestRpcResponder::new()))),
+		finality_gadget,
+		equivocation_reporter,
 	));
 	let block_composer = Arc::new(TestBlockComposer::new(signer.clone(), state_key_repo.clone()));
 	let proposer_environment =
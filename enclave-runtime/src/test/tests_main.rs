@@ -53,7 +53,7 @@ use itp_stf_interface::{
 use itp_stf_state_handler::handle_state::HandleState;
 use itp_test::mock::handle_state_mock;
 use itp_top_pool_author::{test_utils::submit_operation_to_top_pool, traits::AuthorApi};
-use itp_types::{AccountId, Block, Header};
+use itp_types::{AccountId, Block, BlockLimits, Header};
 use its_primitives::{
 	traits::{
 		Block as BlockTrait, BlockData, Header as SidechainHeaderTrait,
@@ -77,6 +77,8 @@ pub extern "C" fn test_main_entrance() -> size_t {
 		itp_attestation_handler::attestation_handler::tests::decode_spid_works,
 		stf_sgx_tests::enclave_account_initialization_works,
 		stf_sgx_tests::shield_funds_increments_signer_account_nonce,
+		stf_sgx_tests::tip_is_charged_from_sender,
+		stf_sgx_tests::failed_call_uses_up_nonce_and_tip,
 		stf_sgx_tests::test_root_account_exists_after_initialization,
 		itp_stf_state_handler::test::sgx_tests::test_write_and_load_state_works,
 		itp_stf_state_handler::test::sgx_tests::test_sgx_state_decode_encode_works,
@@ -112,6 +114,7 @@ pub extern "C" fn test_main_entrance() -> size_t {
 		handle_state_mock::tests::from_shard_works,
 		handle_state_mock::tests::initialize_creates_default_state,
 		handle_state_mock::tests::load_mutate_and_write_works,
+		handle_state_mock::tests::revert_to_restores_previous_state,
 		handle_state_mock::tests::ensure_subsequent_state_loads_have_same_hash,
 		handle_state_mock::tests::ensure_encode_and_encrypt_does_not_affect_state_hash,
 		// mra cert tests
@@ -127,6 +130,10 @@ pub extern "C" fn test_main_entrance() -> size_t {
         stf_executor_tests::propose_state_update_executes_no_trusted_calls_given_no_time,
 		stf_executor_tests::propose_state_update_executes_only_one_trusted_call_given_not_enough_time,
 		stf_executor_tests::propose_state_update_executes_all_calls_given_enough_time,
+		stf_executor_tests::propose_state_update_executes_no_more_than_max_trusted_operations,
+		stf_executor_tests::propose_state_update_executes_no_calls_exceeding_max_weight,
+		stf_executor_tests::propose_state_update_reverts_call_exceeding_max_encoded_size,
+		stf_executor_tests::propose_state_update_reverts_only_the_call_exceeding_max_encoded_size,
 		enclave_signer_tests::enclave_signer_signatures_are_valid,
 		enclave_signer_tests::derive_key_is_deterministic,
 		state_getter_tests::state_getter_works,
@@ -145,8 +152,15 @@ pub extern "C" fn test_main_entrance() -> size_t {
 		tls_ra::seal_handler::test::seal_state_works,
 		tls_ra::seal_handler::test::seal_state_fails_for_invalid_state,
 		tls_ra::seal_handler::test::unseal_seal_state_works,
+		tls_ra::seal_handler::test::unseal_seal_state_snapshot_works,
+		tls_ra::seal_handler::test::unseal_state_snapshot_without_sidechain_block_has_no_header,
+		tls_ra::seal_handler::test::seal_state_snapshot_fails_for_state_at_other_block,
+		tls_ra::seal_handler::test::seal_state_snapshot_fails_for_state_not_matching_block,
+		tls_ra::seal_handler::test::seal_state_snapshot_fails_for_invalid_block_signature,
+		tls_ra::seal_handler::test::seal_state_snapshot_fails_for_other_shard,
 		tls_ra::tests::test_tls_ra_server_client_networking,
 		tls_ra::tests::test_state_and_key_provisioning,
+		tls_ra::tests::test_state_snapshot_provisioning,
 		// RPC tests
 		direct_rpc_tests::get_state_request_works,
 
@@ -478,7 +492,7 @@ fn test_signature_must_match_public_sender_in_call() {
 
 	let trusted_operation =
 		TrustedCall::balance_transfer(receiver.into(), sender.public().into(), 1000)
-			.sign(&sender.clone().into(), 10, &mrenclave, &shard)
+			.sign(&sender.clone().into(), 0, &mrenclave, &shard)
 			.into_trusted_operation(true);
 
 	submit_operation_to_top_pool(
@@ -521,7 +535,8 @@ fn test_invalid_nonce_call_is_not_executed() {
 	let executed_batch = execute_trusted_calls(&shard, stf_executor.as_ref(), &top_pool_author);
 
 	// then
-	assert!(!executed_batch.executed_operations[0].is_success());
+	// The call waits in the pool for the calls with the preceding nonces.
+	assert!(executed_batch.executed_operations.is_empty());
 }
 
 fn test_non_root_shielding_call_is_not_executed() {
@@ -667,6 +682,7 @@ fn execute_trusted_calls(
 			&latest_parentchain_header(),
 			&shard,
 			Duration::from_millis(600),
+			&BlockLimits::unlimited(),
 			|s| {
 				let mut sidechain_db = SidechainDB::<SignedBlock, SgxExternalities>::new(s);
 				sidechain_db
@@ -52,7 +52,6 @@ use itp_ocall_api::EnclaveAttestationOCallApi;
 use itp_sgx_crypto::ShieldingCryptoEncrypt;
 use itp_stf_executor::enclave_signer::StfEnclaveSigner;
 use itp_stf_state_observer::mock::ObserveStateMock;
-use itp_test::mock::metrics_ocall_mock::MetricsOCallMock;
 use itp_top_pool_author::{top_filter::AllowAllTopsFilter, traits::AuthorApi};
 use itp_types::{AccountId, Block, ShardIdentifier, ShieldFundsFn, H256};
 use jsonrpc_core::futures::executor;
@@ -84,7 +83,7 @@ pub fn process_indirect_call_in_top_pool() {
 		AllowAllTopsFilter {},
 		state_handler.clone(),
 		shielding_key_repo,
-		Arc::new(MetricsOCallMock::default()),
+		ocall_api.clone(),
 	));
 
 	let encrypted_indirect_call =
@@ -117,7 +116,7 @@ pub fn submit_shielding_call_to_top_pool() {
 		AllowAllTopsFilter {},
 		state_handler,
 		shielding_key_repo.clone(),
-		Arc::new(MetricsOCallMock::default()),
+		ocall_api.clone(),
 	));
 
 	let enclave_signer = Arc::new(StfEnclaveSigner::<_, _, _, TestStf>::new(
@@ -28,6 +28,7 @@ pub struct SealHandlerMock {
 	pub shielding_key: Arc<RwLock<Vec<u8>>>,
 	pub state_key: Arc<RwLock<Vec<u8>>>,
 	pub state: Arc<RwLock<Vec<u8>>>,
+	pub state_snapshot_header: Arc<RwLock<Option<Vec<u8>>>>,
 }
 
 impl SealHandlerMock {
@@ -36,7 +37,12 @@ impl SealHandlerMock {
 		state_key: Arc<RwLock<Vec<u8>>>,
 		state: Arc<RwLock<Vec<u8>>>,
 	) -> Self {
-		Self { shielding_key, state_key, state }
+		Self { shielding_key, state_key, state, state_snapshot_header: Default::default() }
+	}
+
+	pub fn with_state_snapshot_header(self, snapshot_header: Vec<u8>) -> Self {
+		*self.state_snapshot_header.write().unwrap() = Some(snapshot_header);
+		self
 	}
 }
 
@@ -56,6 +62,17 @@ impl SealStateAndKeys for SealHandlerMock {
 		Ok(())
 	}
 
+	fn seal_state_snapshot(
+		&self,
+		state_bytes: &[u8],
+		snapshot_header_bytes: &[u8],
+		_shard: &ShardIdentifier,
+	) -> EnclaveResult<()> {
+		*self.state.write().unwrap() = state_bytes.to_vec();
+		*self.state_snapshot_header.write().unwrap() = Some(snapshot_header_bytes.to_vec());
+		Ok(())
+	}
+
 	fn seal_new_empty_state(&self, _shard: &ShardIdentifier) -> EnclaveResult<()> {
 		Ok(())
 	}
@@ -73,4 +90,11 @@ impl UnsealStateAndKeys for SealHandlerMock {
 	fn unseal_state(&self, _shard: &ShardIdentifier) -> EnclaveResult<Vec<u8>> {
 		Ok(self.state.read().unwrap().clone())
 	}
+
+	fn unseal_state_snapshot(
+		&self,
+		_shard: &ShardIdentifier,
+	) -> EnclaveResult<(Vec<u8>, Option<Vec<u8>>)> {
+		Ok((self.state.read().unwrap().clone(), self.state_snapshot_header.read().unwrap().clone()))
+	}
 }
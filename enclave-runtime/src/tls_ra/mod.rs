@@ -18,6 +18,9 @@
 //! Contains all logic of the state provisioning mechanism
 //! including the remote attestation and tls / tcp connection part.
 
+use codec::{Decode, Encode};
+use its_primitives::types::block::SignedBlock as SignedSidechainBlock;
+
 mod authentication;
 pub mod seal_handler;
 mod tls_ra_client;
@@ -49,6 +52,7 @@ pub enum Opcode {
 	ShieldingKey = 0,
 	StateKey = 1,
 	State = 2,
+	StateSnapshotHeader = 3,
 }
 
 impl From<u8> for Opcode {
@@ -57,6 +61,7 @@ impl From<u8> for Opcode {
 			0 => Opcode::ShieldingKey,
 			1 => Opcode::StateKey,
 			2 => Opcode::State,
+			3 => Opcode::StateSnapshotHeader,
 			_ => unimplemented!("Unsupported/unknown Opcode for MU-RA exchange"),
 		}
 	}
@@ -67,3 +72,20 @@ impl Opcode {
 		(self as u8).to_be_bytes()
 	}
 }
+
+/// Header of a provisioned state snapshot.
+///
+/// Identifies the sidechain block the provisioned state is at. The receiving validateer verifies
+/// the signature of the block and the state against the state hash in the block's encrypted
+/// payload, and only imports the sidechain blocks following it.
+#[derive(Clone, Debug, Eq, PartialEq, Encode, Decode)]
+pub struct StateSnapshotHeader {
+	/// Last sidechain block imported into the state.
+	pub signed_block: SignedSidechainBlock,
+}
+
+impl StateSnapshotHeader {
+	pub fn new(signed_block: SignedSidechainBlock) -> Self {
+		Self { signed_block }
+	}
+}
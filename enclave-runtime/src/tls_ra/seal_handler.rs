@@ -18,19 +18,31 @@
 //! Abstraction of the reading (unseal) and storing (seal) part of the
 //! shielding key, state key and state.
 
-use crate::error::{Error as EnclaveError, Result as EnclaveResult};
+use super::StateSnapshotHeader;
+use crate::{
+	error::{Error as EnclaveError, Result as EnclaveResult},
+	global_components::EnclaveSidechainState,
+};
 use codec::{Decode, Encode};
 use ita_stf::{State as StfState, StateType as StfStateType};
 use itp_sgx_crypto::{
 	key_repository::{AccessKey, MutateKey},
-	Aes,
+	Aes, StateCrypto,
 };
-use itp_sgx_externalities::SgxExternalitiesTrait;
+use itp_sgx_externalities::{SgxExternalitiesTrait, StateHash};
 use itp_stf_state_handler::handle_state::HandleState;
 use itp_types::ShardIdentifier;
+use its_primitives::{
+	traits::{
+		Block as BlockTrait, BlockData as BlockDataTrait, Header as HeaderTrait,
+		SignedBlock as SignedBlockTrait,
+	},
+	types::block::SignedBlock as SignedSidechainBlock,
+};
+use its_sidechain::state::{LastBlockExt, LastBlockImportExt, StateUpdate};
 use log::*;
 use sgx_crypto_helper::rsa3072::Rsa3072KeyPair;
-use std::{sync::Arc, vec::Vec};
+use std::{string::String, sync::Arc, vec::Vec};
 
 /// Handles the sealing and unsealing of the shielding key, state key and the state.
 #[derive(Default)]
@@ -67,6 +79,12 @@ pub trait SealStateAndKeys {
 	fn seal_shielding_key(&self, bytes: &[u8]) -> EnclaveResult<()>;
 	fn seal_state_key(&self, bytes: &[u8]) -> EnclaveResult<()>;
 	fn seal_state(&self, bytes: &[u8], shard: &ShardIdentifier) -> EnclaveResult<()>;
+	fn seal_state_snapshot(
+		&self,
+		state_bytes: &[u8],
+		snapshot_header_bytes: &[u8],
+		shard: &ShardIdentifier,
+	) -> EnclaveResult<()>;
 	fn seal_new_empty_state(&self, shard: &ShardIdentifier) -> EnclaveResult<()>;
 }
 
@@ -74,6 +92,10 @@ pub trait UnsealStateAndKeys {
 	fn unseal_shielding_key(&self) -> EnclaveResult<Vec<u8>>;
 	fn unseal_state_key(&self) -> EnclaveResult<Vec<u8>>;
 	fn unseal_state(&self, shard: &ShardIdentifier) -> EnclaveResult<Vec<u8>>;
+	fn unseal_state_snapshot(
+		&self,
+		shard: &ShardIdentifier,
+	) -> EnclaveResult<(Vec<u8>, Option<Vec<u8>>)>;
 }
 
 impl<ShieldingKeyRepository, StateKeyRepository, StateHandler> SealStateAndKeys
@@ -109,6 +131,34 @@ where
 		Ok(())
 	}
 
+	/// Seal a state snapshot, after verifying the state against the signed block of the snapshot
+	/// header.
+	///
+	/// Requires the state key to be sealed before calling this, it is needed to decrypt the state
+	/// payload of the block. The sealed state is at the sidechain block of the snapshot header,
+	/// so only the blocks following it are imported afterwards.
+	fn seal_state_snapshot(
+		&self,
+		mut state_bytes: &[u8],
+		mut snapshot_header_bytes: &[u8],
+		shard: &ShardIdentifier,
+	) -> EnclaveResult<()> {
+		let state = StfState::new(StfStateType::decode(&mut state_bytes)?);
+		let snapshot_header = StateSnapshotHeader::decode(&mut snapshot_header_bytes)?;
+		let sidechain_state = EnclaveSidechainState::new(state);
+		let state_key = self.state_key_repository.retrieve_key()?;
+
+		verify_state_snapshot(&sidechain_state, &snapshot_header, shard, &state_key)?;
+
+		self.state_handler.reset(sidechain_state.ext, shard)?;
+		info!(
+			"Successfully updated shard {:?} with state snapshot at sidechain block {}",
+			shard,
+			snapshot_header.signed_block.block().header().block_number()
+		);
+		Ok(())
+	}
+
 	/// Seal an empty, newly initialized state.
 	///
 	/// Requires the shielding key to be sealed and updated before calling this.
@@ -149,6 +199,96 @@ where
 		let state = self.state_handler.load(shard)?;
 		Ok(state.state.encode())
 	}
+
+	/// Unseal the state along with the header of the snapshot it represents.
+	///
+	/// There is no snapshot header as long as no sidechain block has been imported into the state.
+	fn unseal_state_snapshot(
+		&self,
+		shard: &ShardIdentifier,
+	) -> EnclaveResult<(Vec<u8>, Option<Vec<u8>>)> {
+		let state = self.state_handler.load(shard)?;
+		let sidechain_state = EnclaveSidechainState::new(state);
+		let snapshot_header =
+			match (sidechain_state.get_last_block(), sidechain_state.get_last_block_signature()) {
+				(Some(block), Some(signature)) => Some(
+					StateSnapshotHeader::new(SignedSidechainBlock::new(block, signature)).encode(),
+				),
+				_ => None,
+			};
+		Ok((sidechain_state.ext.state.encode(), snapshot_header))
+	}
+}
+
+/// Verify that the provisioned state of a shard is the one of the signed snapshot block.
+///
+/// The state hash after the execution of the block is part of the block's encrypted state
+/// payload. It refers to the state before the import of the block, so the import records are
+/// reverted before comparing the state with it. A state that was updated from the parentchain
+/// after the import of its last block no longer matches and is rejected.
+fn verify_state_snapshot(
+	sidechain_state: &EnclaveSidechainState,
+	snapshot_header: &StateSnapshotHeader,
+	shard: &ShardIdentifier,
+	state_key: &Aes,
+) -> EnclaveResult<()> {
+	let signed_block = &snapshot_header.signed_block;
+	let block = signed_block.block();
+
+	if block.header().shard_id() != *shard {
+		return Err(invalid_snapshot(format!(
+			"Snapshot block is of shard {:?}, expected {:?}",
+			block.header().shard_id(),
+			shard
+		)))
+	}
+
+	if block.header().block_data_hash() != block.block_data().hash() {
+		return Err(invalid_snapshot(format!(
+			"Snapshot block data does not match the header of block {}",
+			block.header().block_number()
+		)))
+	}
+
+	if !signed_block.verify_signature() {
+		return Err(invalid_snapshot(format!(
+			"Invalid signature of snapshot block {}",
+			block.header().block_number()
+		)))
+	}
+
+	// The block import records the last imported block in the state.
+	let last_block = sidechain_state.get_last_block();
+	if last_block.as_ref() != Some(block) {
+		return Err(invalid_snapshot(format!(
+			"State is not at the snapshot block {} (last block of state: {:?})",
+			block.header().block_number(),
+			last_block.map(|b| b.header().block_number())
+		)))
+	}
+
+	let mut payload = block.block_data().encrypted_state_diff().clone();
+	state_key.decrypt(&mut payload)?;
+	let state_update = StateUpdate::decode(&mut payload.as_slice())?;
+
+	let mut state_at_block = sidechain_state.clone();
+	state_at_block.revert_last_block_import();
+	let state_hash = state_at_block.ext.hash();
+	if state_hash != state_update.state_hash_aposteriori() {
+		return Err(invalid_snapshot(format!(
+			"State hash {:?} does not match the state hash {:?} of snapshot block {}",
+			state_hash,
+			state_update.state_hash_aposteriori(),
+			block.header().block_number()
+		)))
+	}
+
+	Ok(())
+}
+
+fn invalid_snapshot(reason: String) -> EnclaveError {
+	error!("    [Enclave] Received invalid state snapshot: {}", reason);
+	EnclaveError::Other(format!("Invalid state snapshot: {}", reason).into())
 }
 
 #[cfg(feature = "test")]
@@ -156,6 +296,11 @@ pub mod test {
 	use super::*;
 	use itp_sgx_crypto::mocks::KeyRepositoryMock;
 	use itp_test::mock::handle_state_mock::HandleStateMock;
+	use its_primitives::{
+		traits::SignBlock,
+		types::{block::Block as SidechainBlock, block_data::BlockData, header::SidechainHeader},
+	};
+	use sp_core::{ed25519, Pair, H256};
 
 	type StateKeyRepositoryMock = KeyRepositoryMock<Aes>;
 	type ShieldingKeyRepositoryMock = KeyRepositoryMock<Rsa3072KeyPair>;
@@ -252,4 +397,155 @@ pub mod test {
 
 		assert!(result.is_ok());
 	}
+
+	pub fn unseal_seal_state_snapshot_works() {
+		let shard = ShardIdentifier::from([1u8; 32]);
+		let server_seal_handler = seal_handler_with_state_at_block(&shard, 3);
+		let client_seal_handler = SealHandlerMock::default();
+
+		let (state_in_bytes, snapshot_header) =
+			server_seal_handler.unseal_state_snapshot(&shard).unwrap();
+
+		let result = client_seal_handler.seal_state_snapshot(
+			&state_in_bytes,
+			&snapshot_header.unwrap(),
+			&shard,
+		);
+
+		assert!(result.is_ok());
+		let sealed_state = client_seal_handler.state_handler.load(&shard).unwrap();
+		let last_block = EnclaveSidechainState::new(sealed_state).get_last_block().unwrap();
+		assert_eq!(last_block.header().block_number(), 3);
+	}
+
+	pub fn unseal_state_snapshot_without_sidechain_block_has_no_header() {
+		let seal_handler = SealHandlerMock::default();
+		let shard = ShardIdentifier::default();
+		seal_handler.state_handler.initialize_shard(shard).unwrap();
+
+		let (_state_in_bytes, snapshot_header) =
+			seal_handler.unseal_state_snapshot(&shard).unwrap();
+
+		assert!(snapshot_header.is_none());
+	}
+
+	pub fn seal_state_snapshot_fails_for_state_at_other_block() {
+		let shard = ShardIdentifier::from([1u8; 32]);
+		let server_seal_handler = seal_handler_with_state_at_block(&shard, 3);
+		let client_seal_handler = SealHandlerMock::default();
+
+		let (state_in_bytes, _) = server_seal_handler.unseal_state_snapshot(&shard).unwrap();
+		let server_state =
+			EnclaveSidechainState::new(server_seal_handler.state_handler.load(&shard).unwrap());
+		let snapshot_header =
+			StateSnapshotHeader::new(signed_sidechain_block(&shard, 4, &server_state));
+
+		let result = client_seal_handler.seal_state_snapshot(
+			&state_in_bytes,
+			&snapshot_header.encode(),
+			&shard,
+		);
+
+		assert!(result.is_err());
+	}
+
+	pub fn seal_state_snapshot_fails_for_state_not_matching_block() {
+		let shard = ShardIdentifier::from([1u8; 32]);
+		let server_seal_handler = seal_handler_with_state_at_block(&shard, 3);
+		let client_seal_handler = SealHandlerMock::default();
+
+		let (state_in_bytes, snapshot_header) =
+			server_seal_handler.unseal_state_snapshot(&shard).unwrap();
+		let mut state = StfStateType::decode(&mut state_in_bytes.as_slice()).unwrap();
+		state.insert(b"my_key".to_vec(), b"my_value".to_vec());
+
+		let result = client_seal_handler.seal_state_snapshot(
+			&state.encode(),
+			&snapshot_header.unwrap(),
+			&shard,
+		);
+
+		assert!(result.is_err());
+	}
+
+	pub fn seal_state_snapshot_fails_for_invalid_block_signature() {
+		let shard = ShardIdentifier::from([1u8; 32]);
+		let server_seal_handler = seal_handler_with_state_at_block(&shard, 3);
+		let client_seal_handler = SealHandlerMock::default();
+
+		let (state_in_bytes, snapshot_header) =
+			server_seal_handler.unseal_state_snapshot(&shard).unwrap();
+		let mut snapshot_header =
+			StateSnapshotHeader::decode(&mut snapshot_header.unwrap().as_slice()).unwrap();
+		let block = snapshot_header.signed_block.block.clone();
+		snapshot_header.signed_block.signature = other_signer().sign(&block.encode()).into();
+
+		let result = client_seal_handler.seal_state_snapshot(
+			&state_in_bytes,
+			&snapshot_header.encode(),
+			&shard,
+		);
+
+		assert!(result.is_err());
+	}
+
+	pub fn seal_state_snapshot_fails_for_other_shard() {
+		let shard = ShardIdentifier::from([1u8; 32]);
+		let server_seal_handler = seal_handler_with_state_at_block(&shard, 3);
+		let client_seal_handler = SealHandlerMock::default();
+
+		let (state_in_bytes, snapshot_header) =
+			server_seal_handler.unseal_state_snapshot(&shard).unwrap();
+
+		let result = client_seal_handler.seal_state_snapshot(
+			&state_in_bytes,
+			&snapshot_header.unwrap(),
+			&ShardIdentifier::from([2u8; 32]),
+		);
+
+		assert!(result.is_err());
+	}
+
+	/// Seal handler with a state that imported a block with the given number.
+	fn seal_handler_with_state_at_block(
+		shard: &ShardIdentifier,
+		block_number: u64,
+	) -> SealHandlerMock {
+		let seal_handler = SealHandlerMock::default();
+		seal_handler.state_handler.initialize_shard(*shard).unwrap();
+		let (lock, state) = seal_handler.state_handler.load_for_mutation(shard).unwrap();
+		let mut sidechain_state = EnclaveSidechainState::new(state);
+		let signed_block = signed_sidechain_block(shard, block_number, &sidechain_state);
+		sidechain_state.set_last_imported_block(signed_block.block(), signed_block.signature());
+		seal_handler
+			.state_handler
+			.write_after_mutation(sidechain_state.ext, lock, shard)
+			.unwrap();
+		seal_handler
+	}
+
+	/// Signed block whose execution resulted in the given state.
+	fn signed_sidechain_block(
+		shard: &ShardIdentifier,
+		block_number: u64,
+		state: &EnclaveSidechainState,
+	) -> SignedSidechainBlock {
+		let state_hash = state.ext.hash();
+		let mut payload = StateUpdate::new(state_hash, state_hash, Default::default()).encode();
+		Aes::default().encrypt(&mut payload).unwrap();
+
+		let block_data =
+			BlockData::new(signer().public(), H256::default(), Vec::new(), payload, block_number);
+		let header =
+			SidechainHeader::new(block_number, H256::default(), *shard, block_data.hash(), 1);
+		SidechainBlock::new(header, block_data).sign_block(&signer())
+	}
+
+	fn signer() -> ed25519::Pair {
+		ed25519::Pair::from_seed(&[1u8; 32])
+	}
+
+	fn other_signer() -> ed25519::Pair {
+		ed25519::Pair::from_seed(&[2u8; 32])
+	}
 }
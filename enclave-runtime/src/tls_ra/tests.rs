@@ -121,6 +121,60 @@ pub fn test_tls_ra_server_client_networking() {
 	}
 }
 
+pub fn test_state_snapshot_provisioning() {
+	let shard = ShardIdentifier::default();
+	let state_encoded = vec![1, 2, 3, 4];
+	let snapshot_header_encoded = vec![5, 6, 7];
+
+	let server_seal_handler = SealHandlerMock::new(
+		Arc::new(RwLock::new(vec![1, 2, 3])),
+		Arc::new(RwLock::new(vec![5, 2, 3, 7])),
+		Arc::new(RwLock::new(state_encoded.clone())),
+	)
+	.with_state_snapshot_header(snapshot_header_encoded.clone());
+
+	let client_state = Arc::new(RwLock::new(Vec::new()));
+	let client_seal_handler = SealHandlerMock::new(
+		Arc::new(RwLock::new(Vec::new())),
+		Arc::new(RwLock::new(Vec::new())),
+		client_state.clone(),
+	);
+
+	let port: u16 = 3151;
+
+	// Start server.
+	let server_thread_handle = thread::spawn(move || {
+		run_state_provisioning_server(server_seal_handler, port);
+	});
+	thread::sleep(Duration::from_secs(1));
+
+	// Start client.
+	let socket = TcpStream::connect(server_addr(port)).unwrap();
+	let result = request_state_provisioning_internal(
+		socket.as_raw_fd(),
+		SIGN_TYPE,
+		shard,
+		SKIP_RA,
+		client_seal_handler.clone(),
+	);
+
+	// Ensure server thread has finished.
+	server_thread_handle.join().unwrap();
+
+	assert!(result.is_ok());
+
+	// The state (and with it the snapshot header) is provisioned only in sidechain mode.
+	if WorkerModeProvider::worker_mode() == WorkerMode::Sidechain {
+		assert_eq!(*client_state.read().unwrap(), state_encoded);
+		assert_eq!(
+			*client_seal_handler.state_snapshot_header.read().unwrap(),
+			Some(snapshot_header_encoded)
+		);
+	} else {
+		assert!(client_seal_handler.state_snapshot_header.read().unwrap().is_none());
+	}
+}
+
 // Test state and key provisioning with 'real' data structures.
 pub fn test_state_and_key_provisioning() {
 	let state_key = Aes::new([3u8; 16], [0u8; 16]);
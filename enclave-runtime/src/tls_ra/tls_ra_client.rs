@@ -55,6 +55,8 @@ where
 	tls_stream: Stream<'a, ClientSession, TcpStream>,
 	seal_handler: StateAndKeySealer,
 	shard: ShardIdentifier,
+	received_state: Option<Vec<u8>>,
+	received_state_snapshot_header: Option<Vec<u8>>,
 }
 
 impl<'a, StateAndKeySealer> TlsClient<'a, StateAndKeySealer>
@@ -66,13 +68,20 @@ where
 		seal_handler: StateAndKeySealer,
 		shard: ShardIdentifier,
 	) -> TlsClient<StateAndKeySealer> {
-		TlsClient { tls_stream, seal_handler, shard }
+		TlsClient {
+			tls_stream,
+			seal_handler,
+			shard,
+			received_state: None,
+			received_state_snapshot_header: None,
+		}
 	}
 
 	/// Read all data sent by the server of the specific shard.
 	///
 	/// We trust here that the server sends us the correct data, as
-	/// we do not have any way to test it.
+	/// we do not have any way to test it. The exception is a state
+	/// snapshot, which is verified against its header before sealing.
 	fn read_shard(&mut self) -> EnclaveResult<()> {
 		self.write_shard()?;
 		self.read_and_seal_all()
@@ -97,6 +106,9 @@ where
 				},
 			}
 		}
+		if let Some(state) = self.received_state.take() {
+			self.seal_received_state(&state)?;
+		}
 		info!("Successfully read and sealed all data sent by the state provisioning server.");
 
 		// In case we receive a shielding key, but no state, we need to reset our state
@@ -123,11 +135,22 @@ where
 		match header.opcode {
 			Opcode::ShieldingKey => self.seal_handler.seal_shielding_key(&bytes)?,
 			Opcode::StateKey => self.seal_handler.seal_state_key(&bytes)?,
-			Opcode::State => self.seal_handler.seal_state(&bytes, &self.shard)?,
+			// The state is sealed only once all data is read, it might be followed by a snapshot header.
+			Opcode::State => self.received_state = Some(bytes),
+			Opcode::StateSnapshotHeader => self.received_state_snapshot_header = Some(bytes),
 		};
 		Ok(Some(header.opcode))
 	}
 
+	/// Seal the received state, as snapshot if the server sent a snapshot header along with it.
+	fn seal_received_state(&mut self, state: &[u8]) -> EnclaveResult<()> {
+		match self.received_state_snapshot_header.take() {
+			Some(snapshot_header) =>
+				self.seal_handler.seal_state_snapshot(state, &snapshot_header, &self.shard),
+			None => self.seal_handler.seal_state(state, &self.shard),
+		}
+	}
+
 	/// Reads the payload header, indicating the sent payload length and type.
 	fn read_header(&mut self, start_byte: u8) -> EnclaveResult<TcpHeader> {
 		debug!("Read first byte: {:?}", start_byte);
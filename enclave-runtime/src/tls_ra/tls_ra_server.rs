@@ -124,9 +124,13 @@ where
 		Ok(())
 	}
 
+	/// Sends the state, followed by the header of the snapshot it represents (if any).
 	fn write_state(&mut self, shard: &ShardIdentifier) -> EnclaveResult<()> {
-		let state = self.seal_handler.unseal_state(shard)?;
+		let (state, maybe_snapshot_header) = self.seal_handler.unseal_state_snapshot(shard)?;
 		self.write(Opcode::State, &state)?;
+		if let Some(snapshot_header) = maybe_snapshot_header {
+			self.write(Opcode::StateSnapshotHeader, &snapshot_header)?;
+		}
 		Ok(())
 	}
 
@@ -18,11 +18,14 @@
 use crate::{
 	error::Result,
 	global_components::{
-		GLOBAL_EXTRINSICS_FACTORY_COMPONENT, GLOBAL_OCALL_API_COMPONENT,
-		GLOBAL_PARENTCHAIN_BLOCK_VALIDATOR_ACCESS_COMPONENT,
-		GLOBAL_SIDECHAIN_BLOCK_COMPOSER_COMPONENT, GLOBAL_SIDECHAIN_IMPORT_QUEUE_WORKER_COMPONENT,
+		EnclaveSidechainState, GLOBAL_BLOCK_PRODUCTION_SUSPENDER_COMPONENT,
+		GLOBAL_EXTRINSICS_FACTORY_COMPONENT, GLOBAL_HEAD_SUBSCRIPTIONS_COMPONENT,
+		GLOBAL_OCALL_API_COMPONENT, GLOBAL_PARENTCHAIN_BLOCK_VALIDATOR_ACCESS_COMPONENT,
+		GLOBAL_SIDECHAIN_BLOCK_COMPOSER_COMPONENT, GLOBAL_SIDECHAIN_FINALITY_GADGET_COMPONENT,
+		GLOBAL_SIDECHAIN_IMPORT_QUEUE_WORKER_COMPONENT, GLOBAL_STALE_OPERATIONS_EVICTOR_COMPONENT,
 		GLOBAL_STATE_HANDLER_COMPONENT, GLOBAL_STF_EXECUTOR_COMPONENT,
-		GLOBAL_TOP_POOL_AUTHOR_COMPONENT, GLOBAL_TRIGGERED_PARENTCHAIN_IMPORT_DISPATCHER_COMPONENT,
+		GLOBAL_TOP_POOL_AUTHOR_COMPONENT, GLOBAL_TOP_POOL_PERSISTENCE_COMPONENT,
+		GLOBAL_TRIGGERED_PARENTCHAIN_IMPORT_DISPATCHER_COMPONENT,
 	},
 	sync::{EnclaveLock, EnclaveStateRWLock},
 };
@@ -36,23 +39,36 @@ use itc_parentchain::{
 };
 use itp_component_container::ComponentGetter;
 use itp_extrinsics_factory::CreateExtrinsics;
-use itp_ocall_api::{EnclaveOnChainOCallApi, EnclaveSidechainOCallApi};
-use itp_settings::sidechain::SLOT_DURATION;
+use itp_ocall_api::{EnclaveMetricsOCallApi, EnclaveOnChainOCallApi, EnclaveSidechainOCallApi};
+use itp_settings::sidechain::{
+	BACKUP_AUTHOR_SLOT_PORTION, MAX_BLOCK_ENCODED_SIZE, MAX_BLOCK_WEIGHT,
+	MAX_PARALLEL_SHARD_PRODUCTION, MAX_TRUSTED_OPERATIONS_PER_BLOCK, SLOT_DURATION,
+};
 use itp_sgx_crypto::Ed25519Seal;
+use itp_sgx_externalities::SgxExternalities;
 use itp_sgx_io::StaticSealedIO;
-use itp_stf_state_handler::query_shard_state::QueryShardState;
+use itp_stf_state_handler::{handle_state::HandleState, query_shard_state::QueryShardState};
 use itp_time_utils::duration_now;
-use itp_types::{Block, OpaqueCall, H256};
+use itp_types::{Block, BlockLimits, OpaqueCall, H256};
+use its_block_verification::slot::slot_from_timestamp_and_duration;
 use its_primitives::{
 	traits::{
-		Block as SidechainBlockTrait, Header as HeaderTrait, ShardIdentifierFor, SignedBlock,
+		Block as SidechainBlockTrait, BlockData, Header as HeaderTrait, NotifyHeads,
+		ShardIdentifierFor, SignedBlock,
 	},
 	types::block::SignedBlock as SignedSidechainBlock,
 };
 use its_sidechain::{
-	aura::{proposer_factory::ProposerFactory, Aura, SlotClaimStrategy},
-	consensus_common::{Environment, Error as ConsensusError, ProcessBlockImportQueue},
-	slots::{sgx::LastSlotSeal, yield_next_slot, PerShardSlotWorkerScheduler, SlotInfo},
+	aura::{proposer_factory::ProposerFactory, slot_duration_at, Aura, SlotClaimStrategy},
+	consensus_common::{
+		Environment, Error as ConsensusError, HandleFinalityVotes, IsShardBlockProductionSuspended,
+		ProcessBlockImportQueue,
+	},
+	slots::{
+		current_slot, sgx::LastSlotSeal, yield_next_slot, PerShardSlotWorkerScheduler, Slot,
+		SlotInfo,
+	},
+	state::LastBlockExt,
 	validateer_fetch::ValidateerFetch,
 };
 use log::*;
@@ -61,7 +77,11 @@ use sp_core::Pair;
 use sp_runtime::{
 	generic::SignedBlock as SignedParentchainBlock, traits::Block as BlockTrait, MultiSignature,
 };
-use std::{sync::Arc, time::Instant, vec::Vec};
+use std::{
+	sync::Arc,
+	time::{Duration, Instant},
+	vec::Vec,
+};
 
 #[no_mangle]
 pub unsafe extern "C" fn execute_trusted_calls() -> sgx_status_t {
@@ -76,6 +96,9 @@ pub unsafe extern "C" fn execute_trusted_calls() -> sgx_status_t {
 ///
 /// Executes `Aura::on_slot() for `slot` if it is this enclave's `Slot`.
 ///
+/// The worker is triggered several times per slot. Once the slot has been yielded, it is run
+/// again for the shards without a block in this slot, so backup authors can take over.
+///
 /// This function makes an ocall that does the following:
 ///
 /// *   Import all pending parentchain blocks.
@@ -128,65 +151,163 @@ fn execute_top_pool_trusted_calls_internal() -> Result<()> {
 
 	let ocall_api = GLOBAL_OCALL_API_COMPONENT.get()?;
 
+	let head_subscriptions = GLOBAL_HEAD_SUBSCRIPTIONS_COMPONENT.get()?;
+
+	let finality_gadget = GLOBAL_SIDECHAIN_FINALITY_GADGET_COMPONENT.get()?;
+
 	let authority = Ed25519Seal::unseal_from_static_file()?;
 
-	match yield_next_slot(
+	let slot_duration =
+		slot_duration_at(ocall_api.as_ref(), &latest_parentchain_header, SLOT_DURATION)?;
+
+	// Once the slot has been yielded, we run it again, backup authors might be allowed to claim it by now.
+	let slot = match yield_next_slot(
 		slot_beginning_timestamp,
-		SLOT_DURATION,
-		latest_parentchain_header,
+		slot_duration,
+		latest_parentchain_header.clone(),
 		&mut LastSlotSeal,
 	)? {
-		Some(slot) => {
-			if slot.duration_remaining().is_none() {
-				warn!("No time remaining in slot, skipping AURA execution");
-				return Ok(())
-			}
+		Some(slot) => slot,
+		None => current_slot(slot_beginning_timestamp, slot_duration, latest_parentchain_header)?,
+	};
 
-			log_remaining_slot_duration(&slot, "Before AURA");
+	if slot.duration_remaining().is_none() {
+		warn!("No time remaining in slot, skipping AURA execution");
+		return Ok(())
+	}
 
-			let shards = state_handler.list_shards()?;
-			let env = ProposerFactory::<Block, _, _, _>::new(
-				top_pool_author,
-				stf_executor,
-				block_composer,
-			);
+	log_remaining_slot_duration(&slot, "Before AURA");
 
-			let (blocks, opaque_calls) = exec_aura_on_slot::<_, _, SignedSidechainBlock, _, _, _>(
-				slot.clone(),
-				authority,
-				ocall_api.clone(),
-				parentchain_import_dispatcher,
-				env,
-				shards,
-			)?;
+	let block_production_suspender = GLOBAL_BLOCK_PRODUCTION_SUSPENDER_COMPONENT.get()?;
 
-			debug!("Aura executed successfully");
+	let all_shards = state_handler.list_shards()?;
 
-			// Drop lock as soon as we don't need it anymore.
-			drop(_enclave_write_lock);
+	// Stale operations must neither be proposed nor wait in the pool forever.
+	evict_stale_operations(state_handler.as_ref(), &all_shards);
 
-			log_remaining_slot_duration(&slot, "After AURA");
+	let shards: Vec<_> = all_shards
+		.into_iter()
+		.filter(|shard| !has_block_in_slot(state_handler.as_ref(), shard, slot.slot, slot.duration))
+		.filter(|shard| !is_block_production_suspended(block_production_suspender.as_ref(), shard))
+		.collect();
 
-			send_blocks_and_extrinsics::<Block, _, _, _, _>(
-				blocks,
-				opaque_calls,
-				ocall_api,
-				validator_access.as_ref(),
-				extrinsics_factory.as_ref(),
-			)?;
+	if shards.is_empty() {
+		debug!("No shard without a block in slot {:?} to produce a block for", slot.slot);
+		persist_top_pool();
+		return Ok(())
+	}
 
-			log_remaining_slot_duration(&slot, "After broadcasting and sending extrinsic");
-		},
-		None => {
-			debug!("No slot yielded. Skipping block production.");
-			return Ok(())
-		},
-	};
+	let env = ProposerFactory::<Block, _, _, _>::new(top_pool_author, stf_executor, block_composer)
+		.with_block_limits(BlockLimits::new(
+			MAX_TRUSTED_OPERATIONS_PER_BLOCK,
+			MAX_BLOCK_ENCODED_SIZE,
+			MAX_BLOCK_WEIGHT,
+		));
+
+	let (blocks, opaque_calls) = exec_aura_on_slot::<_, _, SignedSidechainBlock, _, _, _>(
+		slot.clone(),
+		authority,
+		ocall_api.clone(),
+		parentchain_import_dispatcher,
+		env,
+		shards,
+	)?;
+
+	debug!("Aura executed successfully");
+
+	// Produced blocks are not imported by ourselves, so we notify the head subscribers here.
+	for block in blocks.iter() {
+		head_subscriptions.notify_new_head(block.block().header());
+	}
+	let produced_blocks: Vec<_> = blocks
+		.iter()
+		.map(|b| {
+			let header = b.block().header();
+			(header.shard_id(), header.block_number(), b.hash())
+		})
+		.collect();
+
+	// Drop lock as soon as we don't need it anymore.
+	drop(_enclave_write_lock);
+
+	log_remaining_slot_duration(&slot, "After AURA");
+
+	send_blocks_and_extrinsics::<Block, _, _, _, _>(
+		blocks,
+		opaque_calls,
+		ocall_api,
+		validator_access.as_ref(),
+		extrinsics_factory.as_ref(),
+	)?;
+
+	log_remaining_slot_duration(&slot, "After broadcasting and sending extrinsic");
+
+	// Vote only once the blocks are stored, our vote might already finalize them.
+	for (shard, block_number, block_hash) in produced_blocks {
+		if let Err(e) = finality_gadget.vote_for_block(
+			shard,
+			block_number,
+			block_hash,
+			&slot.last_imported_parentchain_head,
+		) {
+			warn!("Failed to vote for finality of produced block {}: {:?}", block_number, e);
+		}
+	}
+
+	persist_top_pool();
 
 	debug!("End sidechain block production cycle");
 	Ok(())
 }
 
+/// Persists the pending trusted calls, so they survive a restart of the worker.
+/// Nothing is written if they did not change since the last slot.
+fn persist_top_pool() {
+	match GLOBAL_TOP_POOL_PERSISTENCE_COMPONENT.get() {
+		Ok(top_pool_persistence) =>
+			if let Err(e) = top_pool_persistence.persist() {
+				warn!("Failed to persist the pending trusted operations: {:?}", e);
+			},
+		Err(e) => warn!("Failed to get the TOP pool persistence component: {:?}", e),
+	}
+}
+
+/// Evicts the pending trusted operations of each shard that can no longer be included in a block.
+pub(crate) fn evict_stale_operations<StateHandler>(state_handler: &StateHandler, shards: &[H256])
+where
+	StateHandler: HandleState<StateT = SgxExternalities>,
+{
+	let stale_operations_evictor = match GLOBAL_STALE_OPERATIONS_EVICTOR_COMPONENT.get() {
+		Ok(evictor) => evictor,
+		Err(e) => {
+			warn!("Failed to get the stale operations evictor component: {:?}", e);
+			return
+		},
+	};
+
+	for shard in shards {
+		let last_block_number = match state_handler.load(shard) {
+			Ok(state) => EnclaveSidechainState::new(state)
+				.get_last_block()
+				.map_or(0, |last_block| last_block.header().block_number()),
+			Err(e) => {
+				warn!("Failed to load state of shard {:?}: {:?}", shard, e);
+				continue
+			},
+		};
+
+		match stale_operations_evictor.evict(shard, last_block_number) {
+			Ok(0) => {},
+			Ok(number_of_evicted) => debug!(
+				"Evicted {} stale trusted operations of shard {:?}",
+				number_of_evicted, shard
+			),
+			Err(e) =>
+				warn!("Failed to evict stale trusted operations of shard {:?}: {:?}", shard, e),
+		}
+	}
+}
+
 /// Executes aura for the given `slot`.
 pub(crate) fn exec_aura_on_slot<
 	Authority,
@@ -213,11 +334,17 @@ where
 	SignedSidechainBlock::Signature: From<Authority::Signature>,
 	Authority: Pair<Public = sp_core::ed25519::Public>,
 	Authority::Public: Encode,
-	OCallApi: ValidateerFetch + EnclaveOnChainOCallApi + Send + 'static,
+	OCallApi: ValidateerFetch + EnclaveOnChainOCallApi + EnclaveMetricsOCallApi + Send + 'static,
 	NumberFor<ParentchainBlock>: BlockNumberOps,
-	PEnvironment:
-		Environment<ParentchainBlock, SignedSidechainBlock, Error = ConsensusError> + Send + Sync,
-	BlockImportTrigger: TriggerParentchainBlockImport<SignedParentchainBlock<ParentchainBlock>>,
+	PEnvironment: Environment<ParentchainBlock, SignedSidechainBlock, Error = ConsensusError>
+		+ Clone
+		+ Send
+		+ Sync
+		+ 'static,
+	BlockImportTrigger: TriggerParentchainBlockImport<SignedParentchainBlock<ParentchainBlock>>
+		+ Send
+		+ Sync
+		+ 'static,
 {
 	debug!("[Aura] Executing aura for slot: {:?}", slot);
 
@@ -227,7 +354,10 @@ where
 		block_import_trigger,
 		proposer_environment,
 	)
-	.with_claim_strategy(SlotClaimStrategy::RoundRobin);
+	.with_claim_strategy(SlotClaimStrategy::RoundRobinWithFallback {
+		backup_slot_portion: BACKUP_AUTHOR_SLOT_PORTION,
+	})
+	.with_max_parallel_shards(MAX_PARALLEL_SHARD_PRODUCTION);
 
 	let (blocks, xts): (Vec<_>, Vec<_>) =
 		PerShardSlotWorkerScheduler::on_slot(&mut aura, slot, shards)
@@ -272,6 +402,51 @@ where
 	Ok(())
 }
 
+/// Whether the last block of the shard has already been produced in the given slot.
+fn has_block_in_slot<StateHandler>(
+	state_handler: &StateHandler,
+	shard: &H256,
+	slot: Slot,
+	slot_duration: Duration,
+) -> bool
+where
+	StateHandler: HandleState<StateT = SgxExternalities>,
+{
+	let state = match state_handler.load(shard) {
+		Ok(state) => state,
+		Err(e) => {
+			warn!("Failed to load state of shard {:?}: {:?}", shard, e);
+			return false
+		},
+	};
+
+	EnclaveSidechainState::new(state).get_last_block().map_or(false, |last_block| {
+		let last_block_time = Duration::from_millis(last_block.block_data().timestamp());
+		slot_from_timestamp_and_duration(last_block_time, slot_duration) >= slot
+	})
+}
+
+fn is_block_production_suspended<Suspender>(
+	block_production_suspender: &Suspender,
+	shard: &H256,
+) -> bool
+where
+	Suspender: IsShardBlockProductionSuspended,
+{
+	match block_production_suspender.is_shard_suspended(shard) {
+		Ok(is_suspended) => {
+			if is_suspended {
+				debug!("Block production of shard {:?} is suspended", shard);
+			}
+			is_suspended
+		},
+		Err(e) => {
+			warn!("Failed to query block production suspension of shard {:?}: {:?}", shard, e);
+			false
+		},
+	}
+}
+
 fn log_remaining_slot_duration<B: BlockTrait<Hash = H256>>(
 	slot_info: &SlotInfo<B>,
 	stage_name: &str,
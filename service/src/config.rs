@@ -16,6 +16,8 @@
 */
 
 use clap::ArgMatches;
+use itp_settings::files::SIDECHAIN_PURGE_LIMIT;
+use its_storage::PruningPolicy;
 use parse_duration::parse;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
@@ -183,6 +185,10 @@ pub struct RunConfig {
 	pub shard: Option<String>,
 	/// Optional teeracle update interval
 	pub teeracle_update_interval: Option<Duration>,
+	/// Pruning policy of the sidechain block storage.
+	pub sidechain_pruning_policy: PruningPolicy,
+	/// Ss58 encoded account authorized to suspend and resume the sidechain block production.
+	pub admin_account: Option<String>,
 }
 
 impl From<&ArgMatches<'_>> for RunConfig {
@@ -195,10 +201,41 @@ impl From<&ArgMatches<'_>> for RunConfig {
 			parse(i).unwrap_or_else(|e| panic!("teeracle-interval parsing error {:?}", e))
 		});
 
-		Self { skip_ra, dev, request_state, shard, teeracle_update_interval }
+		let sidechain_pruning_policy = sidechain_pruning_policy_from(m);
+		let admin_account = m.value_of("admin-account").map(|a| a.to_string());
+
+		Self {
+			skip_ra,
+			dev,
+			request_state,
+			shard,
+			teeracle_update_interval,
+			sidechain_pruning_policy,
+			admin_account,
+		}
 	}
 }
 
+fn sidechain_pruning_policy_from(m: &ArgMatches<'_>) -> PruningPolicy {
+	if m.is_present("sidechain-archive") {
+		return PruningPolicy::Archive
+	}
+	if let Some(duration) = m.value_of("sidechain-keep-duration") {
+		return PruningPolicy::KeepByAge(
+			parse(duration)
+				.unwrap_or_else(|e| panic!("sidechain-keep-duration parsing error {:?}", e)),
+		)
+	}
+	let number_of_blocks = m.value_of("sidechain-keep-blocks").map_or(SIDECHAIN_PURGE_LIMIT, |n| {
+		n.parse()
+			.unwrap_or_else(|e| panic!("sidechain-keep-blocks parsing error {:?}", e))
+	});
+	if number_of_blocks == 0 {
+		panic!("sidechain-keep-blocks must keep at least 1 block")
+	}
+	PruningPolicy::KeepLast(number_of_blocks)
+}
+
 fn add_port_if_necessary(url: &str, port: &str) -> String {
 	// [Option("ws(s)"), ip, Option(port)]
 	match url.split(':').count() {
@@ -313,11 +350,46 @@ mod test {
 		assert_eq!(run_config.skip_ra, false);
 		assert!(run_config.shard.is_none());
 		assert!(run_config.teeracle_update_interval.is_none());
+		assert!(run_config.admin_account.is_none());
+		assert_eq!(
+			run_config.sidechain_pruning_policy,
+			PruningPolicy::KeepLast(SIDECHAIN_PURGE_LIMIT)
+		);
+	}
+
+	#[test]
+	fn sidechain_pruning_policy_parsing_works() {
+		let mut args = ArgMatches::default();
+		args.args = HashMap::from([("sidechain-keep-blocks", Default::default())]);
+		args.args.get_mut("sidechain-keep-blocks").unwrap().vals = vec!["42".into()];
+		assert_eq!(RunConfig::from(&args).sidechain_pruning_policy, PruningPolicy::KeepLast(42));
+
+		let mut args = ArgMatches::default();
+		args.args = HashMap::from([("sidechain-keep-duration", Default::default())]);
+		args.args.get_mut("sidechain-keep-duration").unwrap().vals = vec!["2h".into()];
+		assert_eq!(
+			RunConfig::from(&args).sidechain_pruning_policy,
+			PruningPolicy::KeepByAge(Duration::from_secs(7200))
+		);
+
+		let mut args = ArgMatches::default();
+		args.args = HashMap::from([("sidechain-archive", Default::default())]);
+		assert_eq!(RunConfig::from(&args).sidechain_pruning_policy, PruningPolicy::Archive);
+	}
+
+	#[test]
+	#[should_panic]
+	fn sidechain_pruning_policy_keeping_no_blocks_is_rejected() {
+		let mut args = ArgMatches::default();
+		args.args = HashMap::from([("sidechain-keep-blocks", Default::default())]);
+		args.args.get_mut("sidechain-keep-blocks").unwrap().vals = vec!["0".into()];
+		RunConfig::from(&args);
 	}
 
 	#[test]
 	fn run_config_parsing_works() {
 		let shard_identifier = "shard-identifier";
+		let admin_account = "admin-account";
 
 		let mut args = ArgMatches::default();
 		args.args = HashMap::from([
@@ -326,10 +398,12 @@ mod test {
 			("skip-ra", Default::default()),
 			("shard", Default::default()),
 			("teeracle-interval", Default::default()),
+			("admin-account", Default::default()),
 		]);
 		// Workaround because MatchedArg is private.
 		args.args.get_mut("shard").unwrap().vals = vec![shard_identifier.into()];
 		args.args.get_mut("teeracle-interval").unwrap().vals = vec!["42s".into()];
+		args.args.get_mut("admin-account").unwrap().vals = vec![admin_account.into()];
 
 		let run_config = RunConfig::from(&args);
 
@@ -338,6 +412,7 @@ mod test {
 		assert_eq!(run_config.skip_ra, true);
 		assert_eq!(run_config.shard.unwrap(), shard_identifier.to_string());
 		assert_eq!(run_config.teeracle_update_interval.unwrap(), Duration::from_secs(42));
+		assert_eq!(run_config.admin_account.unwrap(), admin_account.to_string());
 	}
 
 	#[test]
@@ -32,7 +32,10 @@ use crate::{
 	},
 	parentchain_handler::{HandleParentchain, ParentchainHandler},
 	prometheus_metrics::{start_metrics_server, EnclaveMetricsReceiver, MetricsHandler},
-	sidechain_setup::{sidechain_init_block_production, sidechain_start_untrusted_rpc_server},
+	sidechain_setup::{
+		sidechain_export_storage, sidechain_import_storage, sidechain_init_block_production,
+		sidechain_start_untrusted_rpc_server,
+	},
 	sync_block_broadcaster::SyncBlockBroadcaster,
 	utils::{check_files, extract_shard},
 	worker::Worker,
@@ -67,7 +70,9 @@ use its_peer_fetch::{
 	block_fetch_client::BlockFetcher, untrusted_peer_fetch::UntrustedPeerFetcher,
 };
 use its_primitives::types::block::SignedBlock as SignedSidechainBlock;
-use its_storage::{interface::FetchBlocks, BlockPruner, SidechainStorageLock};
+use its_storage::{
+	interface::FetchBlocks, BlockPruner, LookupBlocks, RocksDbBackend, SidechainStorageLock,
+};
 use log::*;
 use my_node_runtime::{Event, Hash, Header};
 use sgx_types::*;
@@ -139,8 +144,10 @@ fn main() {
 	// build the entire dependency tree
 	let tokio_handle = Arc::new(GlobalTokioHandle {});
 	let sidechain_blockstorage = Arc::new(
-		SidechainStorageLock::<SignedSidechainBlock>::new(PathBuf::from(&SIDECHAIN_STORAGE_PATH))
-			.unwrap(),
+		SidechainStorageLock::<SignedSidechainBlock, RocksDbBackend>::new(PathBuf::from(
+			&SIDECHAIN_STORAGE_PATH,
+		))
+		.unwrap(),
 	);
 	let node_api_factory =
 		Arc::new(NodeApiFactory::new(config.node_url(), AccountKeyring::Alice.pair()));
@@ -227,6 +234,12 @@ fn main() {
 			enclave.as_ref(),
 			&extract_shard(&sub_matches.value_of("shard").map(|s| s.to_string()), enclave.as_ref()),
 		);
+	} else if let Some(sub_matches) = matches.subcommand_matches("export-sidechain-db") {
+		let file = PathBuf::from(sub_matches.value_of("file").unwrap());
+		sidechain_export_storage(sidechain_blockstorage.as_ref(), &file).unwrap();
+	} else if let Some(sub_matches) = matches.subcommand_matches("import-sidechain-db") {
+		let file = PathBuf::from(sub_matches.value_of("file").unwrap());
+		sidechain_import_storage(sidechain_blockstorage.as_ref(), &file).unwrap();
 	} else if let Some(sub_matches) = matches.subcommand_matches("test") {
 		if sub_matches.is_present("provisioning-server") {
 			println!("*** Running Enclave MU-RA TLS server\n");
@@ -279,7 +292,12 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 		+ TlsRemoteAttestation
 		+ TeeracleApi
 		+ Clone,
-	D: BlockPruner + FetchBlocks<SignedSidechainBlock> + Sync + Send + 'static,
+	D: BlockPruner
+		+ FetchBlocks<SignedSidechainBlock>
+		+ LookupBlocks<SignedSidechainBlock>
+		+ Sync
+		+ Send
+		+ 'static,
 	InitializationHandler: TrackInitialization + IsInitialized + Sync + Send + 'static,
 	WorkerModeProvider: ProvideWorkerMode,
 {
@@ -403,6 +421,15 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 		)
 		.expect("Could not set the node metadata in the enclave");
 
+	if let Some(admin_account) = &run_config.admin_account {
+		let admin_account = AccountId32::from_ss58check(admin_account)
+			.unwrap_or_else(|e| panic!("Invalid admin account {}: {:?}", admin_account, e));
+		println!("Admin account {}", admin_account.to_ss58check());
+		enclave
+			.set_admin_account(&admin_account)
+			.expect("Could not set the admin account in the enclave");
+	}
+
 	// ------------------------------------------------------------------------
 	// Perform a remote attestation and get an unchecked extrinsic back.
 	let trusted_url = config.trusted_worker_url_external();
@@ -473,6 +500,7 @@ fn start_worker<E, T, D, InitializationHandler, WorkerModeProvider>(
 				we_are_primary_validateer,
 				parentchain_handler.clone(),
 				sidechain_storage,
+				run_config.sidechain_pruning_policy,
 				&last_synced_header,
 			)
 			.unwrap();
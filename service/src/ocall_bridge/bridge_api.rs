@@ -133,6 +133,14 @@ pub enum OCallBridgeError {
 	ProposeSidechainBlock(String),
 	#[error("Failed to fetch sidechain blocks from peer: {0}")]
 	FetchSidechainBlocksFromPeer(String),
+	#[error("Propose finality votes failed: {0}")]
+	ProposeFinalityVotes(String),
+	#[error("Propose trusted operations failed: {0}")]
+	ProposeTrustedOperations(String),
+	#[error("Finalize sidechain block failed: {0}")]
+	FinalizeSidechainBlock(String),
+	#[error("Persisting trusted operation pool failed: {0}")]
+	PersistTrustedOperationPool(String),
 	#[error("Sending extrinsics to parentchain failed: {0}")]
 	SendExtrinsicsToParentchain(String),
 	#[error("IPFS Error: {0}")]
@@ -210,6 +218,30 @@ pub trait SidechainBridge {
 		maybe_until_block_hash_encoded: Vec<u8>,
 		shard_identifier_encoded: Vec<u8>,
 	) -> OCallBridgeResult<Vec<u8>>;
+
+	fn fetch_sidechain_block_ranges_from_peers(
+		&self,
+		requests_encoded: Vec<u8>,
+		shard_identifier_encoded: Vec<u8>,
+	) -> OCallBridgeResult<Vec<u8>>;
+
+	fn propose_finality_votes(&self, signed_votes_encoded: Vec<u8>) -> OCallBridgeResult<()>;
+
+	fn propose_trusted_operations(
+		&self,
+		trusted_operations_encoded: Vec<u8>,
+	) -> OCallBridgeResult<()>;
+
+	fn finalize_sidechain_block(
+		&self,
+		shard_identifier_encoded: Vec<u8>,
+		block_hash_encoded: Vec<u8>,
+	) -> OCallBridgeResult<()>;
+
+	fn store_trusted_operation_pool(&self, encrypted_pool: Vec<u8>) -> OCallBridgeResult<()>;
+
+	/// Returns the encoded `Option` of the encrypted trusted operation pool stored last.
+	fn load_trusted_operation_pool(&self) -> OCallBridgeResult<Vec<u8>>;
 }
 
 /// type for IPFS
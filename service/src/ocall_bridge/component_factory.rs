@@ -37,7 +37,7 @@ use itp_enclave_api::remote_attestation::RemoteAttestationCallBacks;
 use itp_node_api::node_api_factory::CreateNodeApi;
 use its_peer_fetch::FetchBlocksFromPeer;
 use its_primitives::types::block::SignedBlock as SignedSidechainBlock;
-use its_storage::BlockStorage;
+use its_storage::{BlockStorage, FinalizeBlocks};
 use std::sync::Arc;
 
 /// Concrete implementation, should be moved out of the OCall Bridge, into the worker
@@ -131,7 +131,7 @@ impl<
 	NodeApi: CreateNodeApi + 'static,
 	Broadcaster: BroadcastBlocks + 'static,
 	EnclaveApi: RemoteAttestationCallBacks + 'static,
-	Storage: BlockStorage<SignedSidechainBlock> + 'static,
+	Storage: BlockStorage<SignedSidechainBlock> + FinalizeBlocks<SignedSidechainBlock> + 'static,
 	PeerUpdater: UpdateWorkerPeers + 'static,
 	PeerBlockFetcher: FetchBlocksFromPeer<SignedBlockType = SignedSidechainBlock> + 'static,
 	TokioHandle: GetTokioHandle + 'static,
@@ -0,0 +1,170 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+	Copyright (C) 2017-2019 Baidu, Inc. All Rights Reserved.
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::ocall_bridge::bridge_api::{Bridge, SidechainBridge};
+use itp_utils::write_slice_and_whitespace_pad;
+use log::*;
+use sgx_types::sgx_status_t;
+use std::{slice, sync::Arc};
+
+/// # Safety
+///
+/// FFI are always unsafe
+#[no_mangle]
+pub unsafe extern "C" fn ocall_fetch_sidechain_block_ranges_from_peers(
+	requests_ptr: *const u8,
+	requests_size: u32,
+	shard_identifier_ptr: *const u8,
+	shard_identifier_size: u32,
+	sidechain_block_ranges_ptr: *mut u8,
+	sidechain_block_ranges_size: u32,
+) -> sgx_status_t {
+	fetch_sidechain_block_ranges_from_peers(
+		requests_ptr,
+		requests_size,
+		shard_identifier_ptr,
+		shard_identifier_size,
+		sidechain_block_ranges_ptr,
+		sidechain_block_ranges_size,
+		Bridge::get_sidechain_api(),
+	)
+}
+
+fn fetch_sidechain_block_ranges_from_peers(
+	requests_ptr: *const u8,
+	requests_size: u32,
+	shard_identifier_ptr: *const u8,
+	shard_identifier_size: u32,
+	sidechain_block_ranges_ptr: *mut u8,
+	sidechain_block_ranges_size: u32,
+	sidechain_api: Arc<dyn SidechainBridge>,
+) -> sgx_status_t {
+	let requests_encoded =
+		unsafe { Vec::from(slice::from_raw_parts(requests_ptr, requests_size as usize)) };
+	let shard_identifier_encoded = unsafe {
+		Vec::from(slice::from_raw_parts(shard_identifier_ptr, shard_identifier_size as usize))
+	};
+
+	let sidechain_block_ranges_encoded = match sidechain_api
+		.fetch_sidechain_block_ranges_from_peers(requests_encoded, shard_identifier_encoded)
+	{
+		Ok(r) => r,
+		Err(e) => {
+			error!("fetch sidechain block ranges from peers failed: {:?}", e);
+			return sgx_status_t::SGX_ERROR_UNEXPECTED
+		},
+	};
+
+	let sidechain_block_ranges_encoded_slice = unsafe {
+		slice::from_raw_parts_mut(sidechain_block_ranges_ptr, sidechain_block_ranges_size as usize)
+	};
+	if let Err(e) = write_slice_and_whitespace_pad(
+		sidechain_block_ranges_encoded_slice,
+		sidechain_block_ranges_encoded,
+	) {
+		error!("Failed to transfer encoded sidechain block ranges to o-call buffer: {:?}", e);
+		return sgx_status_t::SGX_ERROR_UNEXPECTED
+	}
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use crate::ocall_bridge::test::mocks::sidechain_bridge_mock::SidechainBridgeMock;
+	use codec::{Decode, Encode};
+	use itp_types::PeerBlockRangeRequest;
+	use its_primitives::types::block::SignedBlock;
+	use its_test::sidechain_block_builder::SidechainBlockBuilder;
+	use primitive_types::H256;
+
+	#[test]
+	fn fetch_sidechain_block_ranges_from_peers_works() {
+		let block_ranges = vec![
+			Some(vec![
+				SidechainBlockBuilder::random().build_signed(),
+				SidechainBlockBuilder::random().build_signed(),
+			]),
+			None,
+		];
+
+		let sidechain_bridge_mock =
+			Arc::new(SidechainBridgeMock::default().with_peer_blocks(block_ranges.encode()));
+
+		let mut buffer = vec![0; 16 * 4096];
+
+		let result =
+			call_fetch_sidechain_block_ranges_from_peers(&mut buffer, sidechain_bridge_mock);
+
+		let decoded_block_ranges: Vec<Option<Vec<SignedBlock>>> =
+			Decode::decode(&mut buffer.as_slice()).unwrap();
+
+		assert_eq!(result, sgx_status_t::SGX_SUCCESS);
+		assert_eq!(block_ranges, decoded_block_ranges);
+	}
+
+	#[test]
+	fn returns_error_if_buffer_is_too_small() {
+		let block_ranges = vec![Some(vec![
+			SidechainBlockBuilder::random().build_signed(),
+			SidechainBlockBuilder::random().build_signed(),
+		])];
+
+		let sidechain_bridge_mock =
+			Arc::new(SidechainBridgeMock::default().with_peer_blocks(block_ranges.encode()));
+
+		let mut buffer = vec![0; 16]; // way too small to hold the encoded blocks
+
+		let result =
+			call_fetch_sidechain_block_ranges_from_peers(&mut buffer, sidechain_bridge_mock);
+
+		assert_eq!(result, sgx_status_t::SGX_ERROR_UNEXPECTED);
+	}
+
+	fn call_fetch_sidechain_block_ranges_from_peers(
+		buffer: &mut Vec<u8>,
+		sidechain_bridge: Arc<dyn SidechainBridge>,
+	) -> sgx_status_t {
+		let requests_encoded = vec![
+			PeerBlockRangeRequest {
+				peer_url: "wss://peer-1".to_string(),
+				from_block_number: 1,
+				limit: 2,
+			},
+			PeerBlockRangeRequest {
+				peer_url: "wss://peer-2".to_string(),
+				from_block_number: 3,
+				limit: 2,
+			},
+		]
+		.encode();
+		let shard_identifier_encoded = H256::random().encode();
+
+		fetch_sidechain_block_ranges_from_peers(
+			requests_encoded.as_ptr(),
+			requests_encoded.len() as u32,
+			shard_identifier_encoded.as_ptr(),
+			shard_identifier_encoded.len() as u32,
+			buffer.as_mut_ptr(),
+			buffer.len() as u32,
+			sidechain_bridge,
+		)
+	}
+}
@@ -21,14 +21,19 @@
 //! These should just be wrappers that transform the C-API structures and call the
 //! actual implementation of the OCalls (using the traits defined in the bridge_api).
 
+pub mod fetch_sidechain_block_ranges_from_peers;
 pub mod fetch_sidechain_blocks_from_peer;
+pub mod finalize_sidechain_block;
 pub mod get_ias_socket;
 pub mod get_quote;
 pub mod get_update_info;
 pub mod init_quote;
 pub mod ipfs;
+pub mod propose_finality_votes;
 pub mod propose_sidechain_blocks;
+pub mod propose_trusted_operations;
 pub mod send_to_parentchain;
 pub mod store_sidechain_blocks;
+pub mod trusted_operation_pool;
 pub mod update_metric;
 pub mod worker_request;
@@ -0,0 +1,146 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+	Copyright (C) 2017-2019 Baidu, Inc. All Rights Reserved.
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::ocall_bridge::bridge_api::{Bridge, SidechainBridge};
+use itp_utils::write_slice_and_whitespace_pad;
+use log::*;
+use sgx_types::sgx_status_t;
+use std::{slice, sync::Arc};
+
+/// # Safety
+///
+/// FFI are always unsafe
+#[no_mangle]
+pub unsafe extern "C" fn ocall_store_trusted_operation_pool(
+	encrypted_pool_ptr: *const u8,
+	encrypted_pool_size: u32,
+) -> sgx_status_t {
+	store_trusted_operation_pool(
+		encrypted_pool_ptr,
+		encrypted_pool_size,
+		Bridge::get_sidechain_api(),
+	)
+}
+
+/// # Safety
+///
+/// FFI are always unsafe
+#[no_mangle]
+pub unsafe extern "C" fn ocall_load_trusted_operation_pool(
+	encrypted_pool_ptr: *mut u8,
+	encrypted_pool_size: u32,
+) -> sgx_status_t {
+	load_trusted_operation_pool(
+		encrypted_pool_ptr,
+		encrypted_pool_size,
+		Bridge::get_sidechain_api(),
+	)
+}
+
+fn store_trusted_operation_pool(
+	encrypted_pool_ptr: *const u8,
+	encrypted_pool_size: u32,
+	sidechain_api: Arc<dyn SidechainBridge>,
+) -> sgx_status_t {
+	let encrypted_pool: Vec<u8> = unsafe {
+		Vec::from(slice::from_raw_parts(encrypted_pool_ptr, encrypted_pool_size as usize))
+	};
+
+	match sidechain_api.store_trusted_operation_pool(encrypted_pool) {
+		Ok(_) => sgx_status_t::SGX_SUCCESS,
+		Err(e) => {
+			error!("store trusted operation pool failed: {:?}", e);
+			sgx_status_t::SGX_ERROR_UNEXPECTED
+		},
+	}
+}
+
+fn load_trusted_operation_pool(
+	encrypted_pool_ptr: *mut u8,
+	encrypted_pool_size: u32,
+	sidechain_api: Arc<dyn SidechainBridge>,
+) -> sgx_status_t {
+	let encrypted_pool_encoded = match sidechain_api.load_trusted_operation_pool() {
+		Ok(r) => r,
+		Err(e) => {
+			error!("load trusted operation pool failed: {:?}", e);
+			return sgx_status_t::SGX_ERROR_UNEXPECTED
+		},
+	};
+
+	let encrypted_pool_slice =
+		unsafe { slice::from_raw_parts_mut(encrypted_pool_ptr, encrypted_pool_size as usize) };
+	if let Err(e) = write_slice_and_whitespace_pad(encrypted_pool_slice, encrypted_pool_encoded) {
+		error!("Failed to transfer encrypted trusted operation pool to o-call buffer: {:?}", e);
+		return sgx_status_t::SGX_ERROR_UNEXPECTED
+	}
+
+	sgx_status_t::SGX_SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use crate::ocall_bridge::test::mocks::sidechain_bridge_mock::SidechainBridgeMock;
+	use codec::Decode;
+
+	#[test]
+	fn stored_trusted_operation_pool_is_loaded_again() {
+		let sidechain_bridge_mock = Arc::new(SidechainBridgeMock::default());
+		let encrypted_pool = vec![7u8; 64];
+		let mut buffer = vec![0u8; 1024];
+
+		let store_result = store_trusted_operation_pool(
+			encrypted_pool.as_ptr(),
+			encrypted_pool.len() as u32,
+			sidechain_bridge_mock.clone(),
+		);
+		let load_result = load_trusted_operation_pool(
+			buffer.as_mut_ptr(),
+			buffer.len() as u32,
+			sidechain_bridge_mock,
+		);
+
+		let loaded_pool: Option<Vec<u8>> = Decode::decode(&mut buffer.as_slice()).unwrap();
+
+		assert_eq!(store_result, sgx_status_t::SGX_SUCCESS);
+		assert_eq!(load_result, sgx_status_t::SGX_SUCCESS);
+		assert_eq!(loaded_pool, Some(encrypted_pool));
+	}
+
+	#[test]
+	fn load_returns_error_if_buffer_is_too_small() {
+		let sidechain_bridge_mock = Arc::new(SidechainBridgeMock::default());
+		let encrypted_pool = vec![7u8; 64];
+		let mut buffer = vec![0u8; 16];
+
+		store_trusted_operation_pool(
+			encrypted_pool.as_ptr(),
+			encrypted_pool.len() as u32,
+			sidechain_bridge_mock.clone(),
+		);
+		let load_result = load_trusted_operation_pool(
+			buffer.as_mut_ptr(),
+			buffer.len() as u32,
+			sidechain_bridge_mock,
+		);
+
+		assert_eq!(load_result, sgx_status_t::SGX_ERROR_UNEXPECTED);
+	}
+}
@@ -23,12 +23,17 @@ use crate::{
 	GetTokioHandle,
 };
 use codec::{Decode, Encode};
-use itp_types::{BlockHash, ShardIdentifier};
+use futures::future::join_all;
+use itp_settings::files::TOP_POOL_FILE;
+use itp_types::{BlockHash, PeerBlockRangeRequest, Request, ShardIdentifier};
 use its_peer_fetch::FetchBlocksFromPeer;
-use its_primitives::{traits::Block, types::SignedBlock as SignedSidechainBlock};
-use its_storage::BlockStorage;
+use its_primitives::{
+	traits::Block,
+	types::{finality::SignedFinalityVote, SignedBlock as SignedSidechainBlock},
+};
+use its_storage::{BlockStorage, FinalizeBlocks};
 use log::*;
-use std::sync::Arc;
+use std::{fs, path::PathBuf, sync::Arc};
 
 pub struct SidechainOCall<BlockBroadcaster, Storage, PeerUpdater, PeerBlockFetcher, TokioHandle> {
 	block_broadcaster: Arc<BlockBroadcaster>,
@@ -36,6 +41,7 @@ pub struct SidechainOCall<BlockBroadcaster, Storage, PeerUpdater, PeerBlockFetch
 	peer_updater: Arc<PeerUpdater>,
 	peer_block_fetcher: Arc<PeerBlockFetcher>,
 	tokio_handle: Arc<TokioHandle>,
+	top_pool_file: PathBuf,
 }
 
 impl<BlockBroadcaster, Storage, PeerUpdater, PeerBlockFetcher, TokioHandle>
@@ -54,15 +60,22 @@ impl<BlockBroadcaster, Storage, PeerUpdater, PeerBlockFetcher, TokioHandle>
 			peer_updater,
 			peer_block_fetcher,
 			tokio_handle,
+			top_pool_file: PathBuf::from(TOP_POOL_FILE),
 		}
 	}
+
+	/// Set the file the encrypted trusted operation pool is persisted to.
+	pub fn with_top_pool_file(mut self, top_pool_file: PathBuf) -> Self {
+		self.top_pool_file = top_pool_file;
+		self
+	}
 }
 
 impl<BlockBroadcaster, Storage, PeerUpdater, PeerBlockFetcher, TokioHandle> SidechainBridge
 	for SidechainOCall<BlockBroadcaster, Storage, PeerUpdater, PeerBlockFetcher, TokioHandle>
 where
 	BlockBroadcaster: BroadcastBlocks,
-	Storage: BlockStorage<SignedSidechainBlock>,
+	Storage: BlockStorage<SignedSidechainBlock> + FinalizeBlocks<SignedSidechainBlock>,
 	PeerUpdater: UpdateWorkerPeers,
 	PeerBlockFetcher: FetchBlocksFromPeer<SignedBlockType = SignedSidechainBlock>,
 	TokioHandle: GetTokioHandle,
@@ -187,6 +200,134 @@ where
 
 		Ok(signed_sidechain_blocks.encode())
 	}
+
+	fn fetch_sidechain_block_ranges_from_peers(
+		&self,
+		requests_encoded: Vec<u8>,
+		shard_identifier_encoded: Vec<u8>,
+	) -> OCallBridgeResult<Vec<u8>> {
+		let requests: Vec<PeerBlockRangeRequest> = Decode::decode(&mut requests_encoded.as_slice())
+			.map_err(|_| {
+				OCallBridgeError::FetchSidechainBlocksFromPeer(
+					"Failed to decode block range requests".to_string(),
+				)
+			})?;
+
+		let shard_identifier: ShardIdentifier =
+			Decode::decode(&mut shard_identifier_encoded.as_slice()).map_err(|_| {
+				OCallBridgeError::FetchSidechainBlocksFromPeer(
+					"Failed to decode shard identifier".to_string(),
+				)
+			})?;
+
+		info!("[O-call] fetching {} block ranges from peers..", requests.len());
+
+		let tokio_handle = self.tokio_handle.get_handle();
+
+		let block_ranges: Vec<Option<Vec<SignedSidechainBlock>>> =
+			tokio_handle.block_on(join_all(requests.into_iter().map(|request| async move {
+				self.peer_block_fetcher
+					.fetch_block_range_from_peer(
+						request.peer_url.clone(),
+						request.from_block_number,
+						request.limit,
+						shard_identifier,
+					)
+					.await
+					.map_err(|e| {
+						warn!(
+							"Failed to fetch blocks from block number {} from peer {}: {:?}",
+							request.from_block_number, request.peer_url, e
+						)
+					})
+					.ok()
+			})));
+
+		info!(
+			"[O-call] successfully fetched {} of {} block ranges from peers",
+			block_ranges.iter().filter(|r| r.is_some()).count(),
+			block_ranges.len()
+		);
+
+		Ok(block_ranges.encode())
+	}
+
+	fn propose_finality_votes(&self, signed_votes_encoded: Vec<u8>) -> OCallBridgeResult<()> {
+		let signed_votes: Vec<SignedFinalityVote> =
+			Decode::decode(&mut signed_votes_encoded.as_slice()).map_err(|_| {
+				OCallBridgeError::ProposeFinalityVotes(
+					"Could not decode signed finality votes".to_string(),
+				)
+			})?;
+
+		debug!("Broadcasting {} finality votes ...", signed_votes.len());
+		if let Err(e) = self.block_broadcaster.broadcast_finality_votes(signed_votes) {
+			error!("Error broadcasting finality votes: {:?}", e);
+		}
+
+		Ok(())
+	}
+
+	fn propose_trusted_operations(
+		&self,
+		trusted_operations_encoded: Vec<u8>,
+	) -> OCallBridgeResult<()> {
+		let trusted_operations: Vec<Request> =
+			Decode::decode(&mut trusted_operations_encoded.as_slice()).map_err(|_| {
+				OCallBridgeError::ProposeTrustedOperations(
+					"Could not decode trusted operations".to_string(),
+				)
+			})?;
+
+		// The peers are kept up to date by the peer updater on every produced sidechain block.
+		debug!("Broadcasting {} trusted operations ...", trusted_operations.len());
+		self.block_broadcaster
+			.broadcast_trusted_operations(trusted_operations)
+			.map_err(|e| OCallBridgeError::ProposeTrustedOperations(format!("{:?}", e)))
+	}
+
+	fn finalize_sidechain_block(
+		&self,
+		shard_identifier_encoded: Vec<u8>,
+		block_hash_encoded: Vec<u8>,
+	) -> OCallBridgeResult<()> {
+		let shard_identifier: ShardIdentifier =
+			Decode::decode(&mut shard_identifier_encoded.as_slice()).map_err(|_| {
+				OCallBridgeError::FinalizeSidechainBlock(
+					"Failed to decode shard identifier".to_string(),
+				)
+			})?;
+
+		let block_hash: BlockHash =
+			Decode::decode(&mut block_hash_encoded.as_slice()).map_err(|_| {
+				OCallBridgeError::FinalizeSidechainBlock("Failed to decode block hash".to_string())
+			})?;
+
+		if let Err(e) = self.block_storage.finalize_block(&shard_identifier, &block_hash) {
+			error!("Error finalizing block {:?}: {:?}", block_hash, e);
+		}
+
+		Ok(())
+	}
+
+	fn store_trusted_operation_pool(&self, encrypted_pool: Vec<u8>) -> OCallBridgeResult<()> {
+		// Write to a temporary file first, so a crash while writing does not corrupt the pool.
+		let temporary_file = self.top_pool_file.with_extension("tmp");
+		fs::write(&temporary_file, encrypted_pool)
+			.and_then(|_| fs::rename(&temporary_file, &self.top_pool_file))
+			.map_err(|e| OCallBridgeError::PersistTrustedOperationPool(format!("{:?}", e)))
+	}
+
+	fn load_trusted_operation_pool(&self) -> OCallBridgeResult<Vec<u8>> {
+		if !self.top_pool_file.exists() {
+			debug!("No persisted trusted operation pool found at {:?}", self.top_pool_file);
+			return Ok(None::<Vec<u8>>.encode())
+		}
+
+		let encrypted_pool = fs::read(&self.top_pool_file)
+			.map_err(|e| OCallBridgeError::PersistTrustedOperationPool(format!("{:?}", e)))?;
+		Ok(Some(encrypted_pool).encode())
+	}
 }
 
 #[cfg(test)]
@@ -202,8 +343,14 @@ mod tests {
 	use codec::Decode;
 	use its_peer_fetch::mocks::fetch_blocks_from_peer_mock::FetchBlocksFromPeerMock;
 	use its_primitives::types::block::SignedBlock as SignedSidechainBlock;
-	use its_storage::{interface::BlockStorage, Result as StorageResult};
-	use its_test::sidechain_block_builder::SidechainBlockBuilder;
+	use its_storage::{
+		interface::{BlockStorage, FinalizeBlocks},
+		Result as StorageResult,
+	};
+	use its_test::{
+		sidechain_block_builder::SidechainBlockBuilder,
+		sidechain_header_builder::SidechainHeaderBuilder,
+	};
 	use primitive_types::H256;
 	use std::{collections::HashMap, vec::Vec};
 
@@ -213,6 +360,11 @@ mod tests {
 			Ok(())
 		}
 	}
+	impl FinalizeBlocks<SignedSidechainBlock> for BlockStorageMock {
+		fn finalize_block(&self, _shard: &H256, _block_hash: &H256) -> StorageResult<()> {
+			Ok(())
+		}
+	}
 
 	type TestSidechainOCall = SidechainOCall<
 		BroadcastBlocksMock,
@@ -248,6 +400,76 @@ mod tests {
 		assert_eq!(blocks, fetched_blocks_decoded);
 	}
 
+	#[test]
+	fn fetch_sidechain_block_ranges_from_peers_works() {
+		let shard_identifier = H256::random();
+		let blocks = (1..=4)
+			.map(|n| {
+				SidechainBlockBuilder::default()
+					.with_header(SidechainHeaderBuilder::default().with_block_number(n).build())
+					.build_signed()
+			})
+			.collect::<Vec<_>>();
+		let peer_blocks_map = HashMap::from([(shard_identifier, blocks.clone())]);
+		let sidechain_ocall = setup_sidechain_ocall_with_peer_blocks(peer_blocks_map);
+
+		let requests = vec![
+			PeerBlockRangeRequest {
+				peer_url: "wss://peer-1".to_string(),
+				from_block_number: 1,
+				limit: 2,
+			},
+			PeerBlockRangeRequest {
+				peer_url: "wss://peer-2".to_string(),
+				from_block_number: 3,
+				limit: 2,
+			},
+		];
+
+		let fetched_block_ranges_encoded = sidechain_ocall
+			.fetch_sidechain_block_ranges_from_peers(requests.encode(), shard_identifier.encode())
+			.unwrap();
+
+		let fetched_block_ranges: Vec<Option<Vec<SignedSidechainBlock>>> =
+			Decode::decode(&mut fetched_block_ranges_encoded.as_slice()).unwrap();
+
+		assert_eq!(
+			fetched_block_ranges,
+			vec![Some(blocks[0..2].to_vec()), Some(blocks[2..4].to_vec())]
+		);
+	}
+
+	#[test]
+	fn propose_trusted_operations_works() {
+		let sidechain_ocall = setup_sidechain_ocall_with_peer_blocks(HashMap::new());
+		let trusted_operations =
+			vec![Request { shard: H256::random(), cyphertext: vec![1u8, 2u8, 3u8] }];
+
+		assert!(sidechain_ocall.propose_trusted_operations(trusted_operations.encode()).is_ok());
+	}
+
+	#[test]
+	fn propose_trusted_operations_fails_for_invalid_encoding() {
+		let sidechain_ocall = setup_sidechain_ocall_with_peer_blocks(HashMap::new());
+
+		assert!(sidechain_ocall.propose_trusted_operations(vec![1u8]).is_err());
+	}
+
+	#[test]
+	fn stored_trusted_operation_pool_is_loaded_again() {
+		let top_pool_file = std::env::temp_dir().join(format!("top_pool_{:?}.bin", H256::random()));
+		let sidechain_ocall = setup_sidechain_ocall_with_peer_blocks(HashMap::new())
+			.with_top_pool_file(top_pool_file.clone());
+
+		let loaded_before_store = sidechain_ocall.load_trusted_operation_pool().unwrap();
+		sidechain_ocall.store_trusted_operation_pool(vec![4u8, 5u8, 6u8]).unwrap();
+		let loaded_after_store = sidechain_ocall.load_trusted_operation_pool().unwrap();
+		fs::remove_file(top_pool_file).unwrap();
+
+		assert_eq!(None::<Vec<u8>>.encode(), loaded_before_store);
+		assert_eq!(Some(vec![4u8, 5u8, 6u8]).encode(), loaded_after_store);
+	}
+
 	fn setup_sidechain_ocall_with_peer_blocks(
 		peer_blocks_map: HashMap<ShardIdentifier, Vec<SignedSidechainBlock>>,
 	) -> TestSidechainOCall {
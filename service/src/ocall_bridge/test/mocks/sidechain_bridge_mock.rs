@@ -17,10 +17,13 @@
 */
 
 use crate::ocall_bridge::bridge_api::{OCallBridgeResult, SidechainBridge};
+use codec::Encode;
+use std::sync::RwLock;
 
 #[derive(Default)]
 pub struct SidechainBridgeMock {
 	peer_blocks_encoded: Vec<u8>,
+	trusted_operation_pool: RwLock<Option<Vec<u8>>>,
 }
 
 impl SidechainBridgeMock {
@@ -47,4 +50,40 @@ impl SidechainBridge for SidechainBridgeMock {
 	) -> OCallBridgeResult<Vec<u8>> {
 		Ok(self.peer_blocks_encoded.clone())
 	}
+
+	fn fetch_sidechain_block_ranges_from_peers(
+		&self,
+		_requests_encoded: Vec<u8>,
+		_shard_identifier_encoded: Vec<u8>,
+	) -> OCallBridgeResult<Vec<u8>> {
+		Ok(self.peer_blocks_encoded.clone())
+	}
+
+	fn propose_finality_votes(&self, _signed_votes_encoded: Vec<u8>) -> OCallBridgeResult<()> {
+		Ok(())
+	}
+
+	fn propose_trusted_operations(
+		&self,
+		_trusted_operations_encoded: Vec<u8>,
+	) -> OCallBridgeResult<()> {
+		Ok(())
+	}
+
+	fn finalize_sidechain_block(
+		&self,
+		_shard_identifier_encoded: Vec<u8>,
+		_block_hash_encoded: Vec<u8>,
+	) -> OCallBridgeResult<()> {
+		Ok(())
+	}
+
+	fn store_trusted_operation_pool(&self, encrypted_pool: Vec<u8>) -> OCallBridgeResult<()> {
+		*self.trusted_operation_pool.write().unwrap() = Some(encrypted_pool);
+		Ok(())
+	}
+
+	fn load_trusted_operation_pool(&self) -> OCallBridgeResult<Vec<u8>> {
+		Ok(self.trusted_operation_pool.read().unwrap().encode())
+	}
 }
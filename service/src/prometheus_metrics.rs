@@ -26,9 +26,14 @@ use crate::{
 };
 use async_trait::async_trait;
 use itp_enclave_metrics::EnclaveMetric;
+use its_storage::PruningPolicy;
 use lazy_static::lazy_static;
 use log::*;
-use prometheus::{proto::MetricFamily, register_int_gauge, IntGauge};
+use prometheus::{
+	proto::MetricFamily, register_gauge, register_histogram, register_int_counter,
+	register_int_counter_vec, register_int_gauge, register_int_gauge_vec, Gauge, Histogram,
+	IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+};
 use std::{net::SocketAddr, sync::Arc};
 use warp::{Filter, Rejection, Reply};
 
@@ -44,6 +49,54 @@ lazy_static! {
 	static ref ENCLAVE_SIDECHAIN_TOP_POOL_SIZE: IntGauge =
 		register_int_gauge!("integritee_worker_enclave_sidechain_top_pool_size", "Enclave sidechain top pool size")
 			.unwrap();
+	static ref ENCLAVE_SIDECHAIN_TOP_POOL_OPERATIONS_EVICTED: IntCounter =
+		register_int_counter!("integritee_worker_enclave_sidechain_top_pool_operations_evicted", "Stale trusted operations evicted from the top pool")
+			.unwrap();
+	static ref ENCLAVE_SIDECHAIN_EQUIVOCATIONS: IntCounterVec =
+		register_int_counter_vec!("integritee_worker_enclave_sidechain_equivocations", "Sidechain equivocations detected per authority", &["authority"])
+			.unwrap();
+	static ref ENCLAVE_SIDECHAIN_SLOTS_CLAIMED: IntCounter =
+		register_int_counter!("integritee_worker_enclave_sidechain_slots_claimed", "Sidechain slots claimed by this validateer")
+			.unwrap();
+	static ref ENCLAVE_SIDECHAIN_SLOTS_SKIPPED: IntCounter =
+		register_int_counter!("integritee_worker_enclave_sidechain_slots_skipped", "Sidechain slots (per shard) not claimed by this validateer")
+			.unwrap();
+	static ref ENCLAVE_SIDECHAIN_BLOCKS_PROPOSED: IntCounterVec =
+		register_int_counter_vec!("integritee_worker_enclave_sidechain_blocks_proposed", "Sidechain blocks proposed per shard", &["shard"])
+			.unwrap();
+	static ref ENCLAVE_SIDECHAIN_BLOCK_PROPOSAL_TIME: Histogram =
+		register_histogram!("integritee_worker_enclave_sidechain_block_proposal_time", "Time to propose a sidechain block, in seconds")
+			.unwrap();
+	static ref ENCLAVE_SIDECHAIN_BLOCK_PROPOSAL_BUDGET_USAGE: Gauge =
+		register_gauge!("integritee_worker_enclave_sidechain_block_proposal_budget_usage", "Fraction of the slot budget used by the last block proposal")
+			.unwrap();
+	static ref ENCLAVE_SIDECHAIN_BLOCK_OPERATIONS: Histogram =
+		register_histogram!("integritee_worker_enclave_sidechain_block_operations", "Number of trusted operations per proposed sidechain block", vec![0.0, 1.0, 5.0, 10.0, 50.0, 100.0, 500.0, 1000.0])
+			.unwrap();
+	static ref ENCLAVE_SIDECHAIN_BLOCK_IMPORT_LATENCY: Histogram =
+		register_histogram!("integritee_worker_enclave_sidechain_block_import_latency", "Latency between the production and the import of a sidechain block, in seconds")
+			.unwrap();
+	static ref SIDECHAIN_PRUNING_POLICY: IntGaugeVec =
+		register_int_gauge_vec!("integritee_worker_sidechain_pruning_policy", "Active sidechain storage pruning policy (1 = active)", &["policy"])
+			.unwrap();
+	static ref SIDECHAIN_PRUNING_RETENTION: IntGauge =
+		register_int_gauge!("integritee_worker_sidechain_pruning_retention", "Sidechain pruning retention (number of blocks or seconds, depending on the policy)")
+			.unwrap();
+}
+
+/// Sets the metrics of the active sidechain storage pruning policy.
+pub fn set_sidechain_pruning_policy_metrics(pruning_policy: &PruningPolicy) {
+	for policy in [
+		PruningPolicy::KeepLast(Default::default()),
+		PruningPolicy::KeepByAge(Default::default()),
+		PruningPolicy::Archive,
+	] {
+		let is_active = policy.name() == pruning_policy.name();
+		SIDECHAIN_PRUNING_POLICY
+			.with_label_values(&[policy.name()])
+			.set(is_active as i64);
+	}
+	SIDECHAIN_PRUNING_RETENTION.set(pruning_policy.retention() as i64);
 }
 
 pub async fn start_metrics_server<MetricsHandler>(
@@ -160,6 +213,35 @@ impl ReceiveEnclaveMetrics for EnclaveMetricsReceiver {
 			EnclaveMetric::TopPoolSizeDecrement => {
 				ENCLAVE_SIDECHAIN_TOP_POOL_SIZE.dec();
 			},
+			EnclaveMetric::TopPoolOperationsEvicted(count) => {
+				ENCLAVE_SIDECHAIN_TOP_POOL_SIZE.sub(count as i64);
+				ENCLAVE_SIDECHAIN_TOP_POOL_OPERATIONS_EVICTED.inc_by(count);
+			},
+			EnclaveMetric::SidechainEquivocationIncrement(authority) => {
+				ENCLAVE_SIDECHAIN_EQUIVOCATIONS.with_label_values(&[authority.as_str()]).inc();
+			},
+			EnclaveMetric::SidechainSlotClaimed => {
+				ENCLAVE_SIDECHAIN_SLOTS_CLAIMED.inc();
+			},
+			EnclaveMetric::SidechainSlotSkipped => {
+				ENCLAVE_SIDECHAIN_SLOTS_SKIPPED.inc();
+			},
+			EnclaveMetric::SidechainBlockProposed(shard) => {
+				ENCLAVE_SIDECHAIN_BLOCKS_PROPOSED.with_label_values(&[shard.as_str()]).inc();
+			},
+			EnclaveMetric::SidechainBlockProposalTime(proposal_millis, budget_millis) => {
+				ENCLAVE_SIDECHAIN_BLOCK_PROPOSAL_TIME.observe(proposal_millis as f64 / 1000.0);
+				if budget_millis > 0 {
+					ENCLAVE_SIDECHAIN_BLOCK_PROPOSAL_BUDGET_USAGE
+						.set(proposal_millis as f64 / budget_millis as f64);
+				}
+			},
+			EnclaveMetric::SidechainBlockOperations(count) => {
+				ENCLAVE_SIDECHAIN_BLOCK_OPERATIONS.observe(count as f64);
+			},
+			EnclaveMetric::SidechainBlockImportLatency(latency_millis) => {
+				ENCLAVE_SIDECHAIN_BLOCK_IMPORT_LATENCY.observe(latency_millis as f64 / 1000.0);
+			},
 			#[cfg(feature = "teeracle")]
 			EnclaveMetric::ExchangeRateOracle(m) => update_teeracle_metrics(m)?,
 			#[cfg(not(feature = "teeracle"))]
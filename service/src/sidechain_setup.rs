@@ -18,6 +18,7 @@
 use crate::{
 	error::{Error, ServiceResult},
 	parentchain_handler::HandleParentchain,
+	prometheus_metrics::set_sidechain_pruning_policy_metrics,
 	Config,
 };
 use futures::executor::block_on;
@@ -25,15 +26,18 @@ use itp_enclave_api::{
 	direct_request::DirectRequest, enclave_base::EnclaveBase, sidechain::Sidechain,
 };
 use itp_settings::{
-	files::{SIDECHAIN_PURGE_INTERVAL, SIDECHAIN_PURGE_LIMIT},
-	sidechain::SLOT_DURATION,
+	files::SIDECHAIN_PURGE_INTERVAL,
+	sidechain::{BACKUP_AUTHOR_SLOT_PORTION, SLOT_DURATION},
 };
 use itp_types::Header;
 use its_consensus_slots::start_slot_worker;
 use its_primitives::types::block::SignedBlock as SignedSidechainBlock;
-use its_storage::{interface::FetchBlocks, start_sidechain_pruning_loop, BlockPruner};
+use its_storage::{
+	backup::SidechainBackup, interface::FetchBlocks, start_sidechain_pruning_loop, BackupBlocks,
+	BlockPruner, LookupBlocks, PruningPolicy,
+};
 use log::*;
-use std::{sync::Arc, thread};
+use std::{path::Path, sync::Arc, thread};
 use tokio::runtime::Handle;
 
 pub(crate) fn sidechain_start_untrusted_rpc_server<Enclave, SidechainStorage>(
@@ -43,7 +47,12 @@ pub(crate) fn sidechain_start_untrusted_rpc_server<Enclave, SidechainStorage>(
 	tokio_handle: Handle,
 ) where
 	Enclave: DirectRequest + Clone,
-	SidechainStorage: BlockPruner + FetchBlocks<SignedSidechainBlock> + Sync + Send + 'static,
+	SidechainStorage: BlockPruner
+		+ FetchBlocks<SignedSidechainBlock>
+		+ LookupBlocks<SignedSidechainBlock>
+		+ Sync
+		+ Send
+		+ 'static,
 {
 	let untrusted_url = config.untrusted_worker_url();
 	println!("[+] Untrusted RPC server listening on {}", &untrusted_url);
@@ -60,6 +69,7 @@ pub(crate) fn sidechain_init_block_production<Enclave, SidechainStorage, Parentc
 	we_are_primary_validateer: bool,
 	parentchain_handler: Arc<ParentchainHandler>,
 	sidechain_storage: Arc<SidechainStorage>,
+	sidechain_pruning_policy: PruningPolicy,
 	last_synced_header: &Header,
 ) -> ServiceResult<Header>
 where
@@ -92,9 +102,11 @@ where
 	thread::Builder::new()
 		.name("interval_block_production_timer".to_owned())
 		.spawn(move || {
+			// Tick several times per slot, such that backup authors can claim a slot
+			// of an unresponsive slot author.
 			let future = start_slot_worker(
 				|| execute_trusted_calls(sidechain_enclave_api.as_ref()),
-				SLOT_DURATION,
+				SLOT_DURATION.mul_f32(BACKUP_AUTHOR_SLOT_PORTION),
 			);
 			block_on(future);
 			println!("[!] Sidechain block production loop has terminated");
@@ -103,13 +115,15 @@ where
 
 	// ------------------------------------------------------------------------
 	// start sidechain pruning loop
+	println!("[+] Sidechain pruning policy: {}", sidechain_pruning_policy);
+	set_sidechain_pruning_policy_metrics(&sidechain_pruning_policy);
 	thread::Builder::new()
 		.name("sidechain_pruning_loop".to_owned())
 		.spawn(move || {
 			start_sidechain_pruning_loop(
 				&sidechain_storage,
 				SIDECHAIN_PURGE_INTERVAL,
-				SIDECHAIN_PURGE_LIMIT,
+				sidechain_pruning_policy,
 			);
 		})
 		.map_err(|e| Error::Custom(Box::new(e)))?;
@@ -117,6 +131,46 @@ where
 	Ok(updated_header.unwrap_or_else(|| last_synced_header.clone()))
 }
 
+/// Export all sidechain blocks to a backup file.
+pub(crate) fn sidechain_export_storage<SidechainStorage>(
+	sidechain_storage: &SidechainStorage,
+	file: &Path,
+) -> ServiceResult<()>
+where
+	SidechainStorage: BackupBlocks<SignedSidechainBlock>,
+{
+	let backup = sidechain_storage.export_backup().map_err(|e| Error::Custom(Box::new(e)))?;
+	backup.write_to_file(file).map_err(|e| Error::Custom(Box::new(e)))?;
+
+	for shard_backup in backup.shards.iter() {
+		println!(
+			"[+] Exported {} blocks of shard {:?} (last block: {})",
+			shard_backup.blocks.len(),
+			shard_backup.shard,
+			shard_backup.last_block.number
+		);
+	}
+	Ok(())
+}
+
+/// Import sidechain blocks from a backup file into an empty sidechain storage.
+pub(crate) fn sidechain_import_storage<SidechainStorage>(
+	sidechain_storage: &SidechainStorage,
+	file: &Path,
+) -> ServiceResult<()>
+where
+	SidechainStorage: BackupBlocks<SignedSidechainBlock>,
+{
+	let backup = SidechainBackup::read_from_file(file).map_err(|e| Error::Custom(Box::new(e)))?;
+	let number_of_shards = backup.shards.len();
+	sidechain_storage
+		.import_backup(backup)
+		.map_err(|e| Error::Custom(Box::new(e)))?;
+
+	println!("[+] Imported sidechain blocks of {} shard(s)", number_of_shards);
+	Ok(())
+}
+
 /// Execute trusted operations in the enclave.
 fn execute_trusted_calls<E: Sidechain>(enclave_api: &E) {
 	if let Err(e) = enclave_api.execute_trusted_calls() {
@@ -25,13 +25,20 @@ use crate::{
 	globals::tokio_handle::GetTokioHandle,
 	worker::{AsyncBlockBroadcaster, WorkerResult},
 };
-use its_primitives::types::block::SignedBlock as SignedSidechainBlock;
+use itp_types::Request;
+use its_primitives::types::{
+	block::SignedBlock as SignedSidechainBlock, finality::SignedFinalityVote,
+};
 use std::sync::Arc;
 
 /// Allows to broadcast blocks, does it in a synchronous (i.e. blocking) manner
 #[cfg_attr(test, automock)]
 pub trait BroadcastBlocks {
 	fn broadcast_blocks(&self, blocks: Vec<SignedSidechainBlock>) -> WorkerResult<()>;
+
+	fn broadcast_finality_votes(&self, votes: Vec<SignedFinalityVote>) -> WorkerResult<()>;
+
+	fn broadcast_trusted_operations(&self, operations: Vec<Request>) -> WorkerResult<()>;
 }
 
 pub struct SyncBlockBroadcaster<T, W> {
@@ -54,4 +61,14 @@ where
 		let handle = self.tokio_handle.get_handle();
 		handle.block_on(self.worker.broadcast_blocks(blocks))
 	}
+
+	fn broadcast_finality_votes(&self, votes: Vec<SignedFinalityVote>) -> WorkerResult<()> {
+		let handle = self.tokio_handle.get_handle();
+		handle.block_on(self.worker.broadcast_finality_votes(votes))
+	}
+
+	fn broadcast_trusted_operations(&self, operations: Vec<Request>) -> WorkerResult<()> {
+		let handle = self.tokio_handle.get_handle();
+		handle.block_on(self.worker.broadcast_trusted_operations(operations))
+	}
 }
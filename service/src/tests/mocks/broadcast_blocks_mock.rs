@@ -16,7 +16,10 @@
 */
 
 use crate::{sync_block_broadcaster::BroadcastBlocks, worker::WorkerResult};
-use its_primitives::types::block::SignedBlock as SignedSidechainBlock;
+use itp_types::Request;
+use its_primitives::types::{
+	block::SignedBlock as SignedSidechainBlock, finality::SignedFinalityVote,
+};
 use std::vec::Vec;
 
 pub struct BroadcastBlocksMock;
@@ -25,4 +28,12 @@ impl BroadcastBlocks for BroadcastBlocksMock {
 	fn broadcast_blocks(&self, _blocks: Vec<SignedSidechainBlock>) -> WorkerResult<()> {
 		Ok(())
 	}
+
+	fn broadcast_finality_votes(&self, _votes: Vec<SignedFinalityVote>) -> WorkerResult<()> {
+		Ok(())
+	}
+
+	fn broadcast_trusted_operations(&self, _operations: Vec<Request>) -> WorkerResult<()> {
+		Ok(())
+	}
 }
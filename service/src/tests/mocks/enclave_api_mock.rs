@@ -22,6 +22,7 @@ use itc_parentchain_light_client::light_client_init_params::{
 };
 use itp_enclave_api::{enclave_base::EnclaveBase, sidechain::Sidechain, EnclaveResult};
 use itp_settings::worker::MR_ENCLAVE_SIZE;
+use itp_types::AccountId;
 use sgx_crypto_helper::rsa3072::Rsa3072PubKey;
 use sp_core::ed25519;
 use sp_runtime::traits::Header;
@@ -68,6 +69,10 @@ impl EnclaveBase for EnclaveMock {
 		todo!()
 	}
 
+	fn set_admin_account(&self, _account: &AccountId) -> EnclaveResult<()> {
+		unimplemented!()
+	}
+
 	fn get_rsa_shielding_pubkey(&self) -> EnclaveResult<Rsa3072PubKey> {
 		unreachable!()
 	}
@@ -24,8 +24,13 @@ use crate::{config::Config, error::Error, TrackInitialization};
 use async_trait::async_trait;
 use itc_rpc_client::direct_client::{DirectApi, DirectClient as DirectWorkerApi};
 use itp_node_api::{api_client::PalletTeerexApi, node_api_factory::CreateNodeApi};
-use its_primitives::types::SignedBlock as SignedSidechainBlock;
-use its_rpc_handler::constants::RPC_METHOD_NAME_IMPORT_BLOCKS;
+use itp_types::Request;
+use itp_utils::ToHexPrefixed;
+use its_primitives::types::{finality::SignedFinalityVote, SignedBlock as SignedSidechainBlock};
+use its_rpc_handler::constants::{
+	RPC_METHOD_NAME_IMPORT_BLOCKS, RPC_METHOD_NAME_IMPORT_FINALITY_VOTES,
+	RPC_METHOD_NAME_IMPORT_TRUSTED_OPERATIONS,
+};
 use jsonrpsee::{
 	types::{to_json_value, traits::Client},
 	ws_client::WsClientBuilder,
@@ -65,9 +70,54 @@ impl<Config, NodeApiFactory, Enclave, InitializationHandler>
 }
 
 #[async_trait]
-/// Broadcast Sidechain blocks to peers.
+/// Broadcast Sidechain blocks, finality votes and encrypted trusted operations to peers.
 pub trait AsyncBlockBroadcaster {
 	async fn broadcast_blocks(&self, blocks: Vec<SignedSidechainBlock>) -> WorkerResult<()>;
+
+	async fn broadcast_finality_votes(&self, votes: Vec<SignedFinalityVote>) -> WorkerResult<()>;
+
+	async fn broadcast_trusted_operations(&self, operations: Vec<Request>) -> WorkerResult<()>;
+}
+
+impl<Config, NodeApiFactory, Enclave, InitializationHandler>
+	Worker<Config, NodeApiFactory, Enclave, InitializationHandler>
+{
+	fn peers(&self) -> WorkerResult<Vec<Url>> {
+		self.peers
+			.read()
+			.map_err(|e| {
+				Error::Custom(format!("Encountered poisoned lock for peers: {:?}", e).into())
+			})
+			.map(|l| l.clone())
+	}
+
+	/// Sends the request to all peers, without waiting for the responses.
+	fn send_to_peers(
+		&self,
+		method: &'static str,
+		params: Vec<serde_json::Value>,
+	) -> WorkerResult<()> {
+		for url in self.peers()? {
+			let params = params.clone();
+
+			tokio::spawn(async move {
+				debug!("Broadcasting {} to peer with address: {:?}", method, url);
+				// FIXME: Websocket connection to a worker should stay, once established.
+				let client = match WsClientBuilder::default().build(&url).await {
+					Ok(c) => c,
+					Err(e) => {
+						error!("Failed to create websocket client for broadcasting (target url: {}): {:?}", url, e);
+						return
+					},
+				};
+
+				if let Err(e) = client.request::<Vec<u8>>(method, params.into()).await {
+					error!("Broadcast request ({}) to {} failed: {:?}", method, url, e);
+				}
+			});
+		}
+		Ok(())
+	}
 }
 
 #[async_trait]
@@ -85,41 +135,31 @@ where
 		}
 
 		let blocks_json = vec![to_json_value(blocks)?];
-		let peers = self
-			.peers
-			.read()
-			.map_err(|e| {
-				Error::Custom(format!("Encountered poisoned lock for peers: {:?}", e).into())
-			})
-			.map(|l| l.clone())?;
 
 		self.initialization_handler.sidechain_block_produced();
 
-		for url in peers {
-			let blocks = blocks_json.clone();
+		self.send_to_peers(RPC_METHOD_NAME_IMPORT_BLOCKS, blocks_json)
+	}
 
-			tokio::spawn(async move {
-				debug!("Broadcasting block to peer with address: {:?}", url);
-				// FIXME: Websocket connection to a worker should stay, once established.
-				let client = match WsClientBuilder::default().build(&url).await {
-					Ok(c) => c,
-					Err(e) => {
-						error!("Failed to create websocket client for block broadcasting (target url: {}): {:?}", url, e);
-						return
-					},
-				};
+	async fn broadcast_finality_votes(&self, votes: Vec<SignedFinalityVote>) -> WorkerResult<()> {
+		if votes.is_empty() {
+			debug!("No finality votes to broadcast, returning");
+			return Ok(())
+		}
 
-				if let Err(e) =
-					client.request::<Vec<u8>>(RPC_METHOD_NAME_IMPORT_BLOCKS, blocks.into()).await
-				{
-					error!(
-						"Broadcast block request ({}) to {} failed: {:?}",
-						RPC_METHOD_NAME_IMPORT_BLOCKS, url, e
-					);
-				}
-			});
+		let votes_json = vec![to_json_value(votes)?];
+		self.send_to_peers(RPC_METHOD_NAME_IMPORT_FINALITY_VOTES, votes_json)
+	}
+
+	async fn broadcast_trusted_operations(&self, operations: Vec<Request>) -> WorkerResult<()> {
+		if operations.is_empty() {
+			debug!("No trusted operations to broadcast, returning");
+			return Ok(())
 		}
-		Ok(())
+
+		// Trusted operations are not serde serializable, so they are sent hex encoded.
+		let operations_json = vec![to_json_value(operations.to_hex())?];
+		self.send_to_peers(RPC_METHOD_NAME_IMPORT_TRUSTED_OPERATIONS, operations_json)
 	}
 }
 
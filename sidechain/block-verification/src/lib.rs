@@ -27,9 +27,12 @@ compile_error!("feature \"std\" and feature \"sgx\" cannot be disabled at the sa
 #[macro_use]
 extern crate sgx_tstd as std;
 
-use crate::slot::{slot_author, slot_from_timestamp_and_duration};
+use crate::slot::{
+	author_rank, claim_offset, slot_author, slot_from_timestamp_and_duration, slot_start,
+};
 use error::Error as ConsensusError;
 use frame_support::ensure;
+use itp_time_utils::duration_now;
 use itp_utils::stringify::public_to_string;
 use its_primitives::{
 	traits::{
@@ -51,9 +54,20 @@ pub mod slot;
 
 type AuthorityId<P> = <P as Pair>::Public;
 
+/// How far the timestamp of a backup author's block may be ahead of the local time.
+///
+/// Tolerates the clock drift between validateers, without letting a backup author claim a slot
+/// by dating its block into the future.
+pub const MAX_BACKUP_CLAIM_CLOCK_DRIFT: Duration = Duration::from_millis(1_000);
+
+/// Verifies a sidechain block against the last imported block and the authorities.
+///
+/// If a `backup_slot_portion` is given, blocks of backup authors are accepted too, see
+/// [`slot::claim_offset`].
 pub fn verify_sidechain_block<AuthorityPair, ParentchainBlock, SignedSidechainBlock>(
 	signed_block: SignedSidechainBlock,
 	slot_duration: Duration,
+	backup_slot_portion: Option<f32>,
 	last_block: &Option<<SignedSidechainBlock as SignedBlock>::Block>,
 	parentchain_header: &ParentchainBlock::Header,
 	authorities: &[AuthorityId<AuthorityPair>],
@@ -88,7 +102,22 @@ where
 		signed_block.block(),
 		parentchain_header,
 		authorities,
-	) {
+	)
+	.or_else(|e| match e {
+		ConsensusError::InvalidAuthority(_)
+			if backup_slot_portion.map_or(false, |backup_slot_portion| {
+				is_valid_backup_claim::<AuthorityPair, SignedSidechainBlock>(
+					&slot,
+					signed_block.block(),
+					last_block,
+					authorities,
+					slot_duration,
+					backup_slot_portion,
+				)
+			}) =>
+			Ok(()),
+		e => Err(e),
+	}) {
 		error!(
 			"Author verification for block (number: {}) failed, block will be discarded",
 			signed_block.block().header().block_number()
@@ -135,6 +164,52 @@ where
 	Ok(())
 }
 
+/// Verify that a block that was not authored by the slot author is a valid claim of a backup
+/// author: The claim offset of the backup author has passed when the block was produced, the
+/// block is not dated further into the future than [`MAX_BACKUP_CLAIM_CLOCK_DRIFT`], and no
+/// block was produced in this slot before.
+fn is_valid_backup_claim<AuthorityPair, SignedSidechainBlock>(
+	slot: &Slot,
+	block: &SignedSidechainBlock::Block,
+	last_block: &Option<SignedSidechainBlock::Block>,
+	authorities: &[AuthorityId<AuthorityPair>],
+	slot_duration: Duration,
+	backup_slot_portion: f32,
+) -> bool
+where
+	AuthorityPair: Pair,
+	SignedSidechainBlock: SignedSidechainBlockTrait<Public = AuthorityPair::Public> + 'static,
+{
+	let rank =
+		match author_rank::<AuthorityPair>(*slot, authorities, block.block_data().block_author()) {
+			Some(rank) if rank > 0 => rank,
+			_ => return false,
+		};
+	let claim_offset = match claim_offset(rank, slot_duration, backup_slot_portion) {
+		Some(claim_offset) => claim_offset,
+		None => return false,
+	};
+
+	let block_time = Duration::from_millis(block.block_data().timestamp());
+	if block_time < slot_start(*slot, slot_duration) + claim_offset {
+		return false
+	}
+
+	let now = duration_now();
+	if block_time > now + MAX_BACKUP_CLAIM_CLOCK_DRIFT {
+		warn!(
+			"Backup author block is dated {:?} into the future, rejecting its claim",
+			block_time - now
+		);
+		return false
+	}
+
+	last_block.as_ref().map_or(true, |last_block| {
+		let last_block_time = Duration::from_millis(last_block.block_data().timestamp());
+		slot_from_timestamp_and_duration(last_block_time, slot_duration) < *slot
+	})
+}
+
 fn verify_block_ancestry<SidechainBlock: SidechainBlockTrait>(
 	block: &SidechainBlock,
 	last_block: &SidechainBlock,
@@ -206,7 +281,10 @@ mod tests {
 	use frame_support::assert_ok;
 	use itc_parentchain_test::parentchain_header_builder::ParentchainHeaderBuilder;
 	use itp_types::{AccountId, Block as ParentchainBlock};
-	use its_primitives::types::{block::SignedBlock, header::SidechainHeader as Header};
+	use its_primitives::types::{
+		block::{Block as SidechainBlock, SignedBlock},
+		header::SidechainHeader as Header,
+	};
 	use its_test::{
 		sidechain_block_builder::SidechainBlockBuilder,
 		sidechain_block_data_builder::SidechainBlockDataBuilder,
@@ -328,6 +406,7 @@ mod tests {
 		assert_ok!(verify_sidechain_block::<Pair, ParentchainBlock, _>(
 			curr_block,
 			SLOT_DURATION,
+			None,
 			&Some(last_block),
 			&parentchain_header,
 			&authorities,
@@ -346,6 +425,7 @@ mod tests {
 		assert_ok!(verify_sidechain_block::<Pair, ParentchainBlock, _>(
 			curr_block,
 			SLOT_DURATION,
+			None,
 			&None,
 			&parentchain_header,
 			&authorities,
@@ -370,6 +450,7 @@ mod tests {
 			verify_sidechain_block::<Pair, ParentchainBlock, _>(
 				curr_block,
 				SLOT_DURATION,
+				None,
 				&Some(last_block),
 				&parentchain_header,
 				&authorities,
@@ -392,6 +473,7 @@ mod tests {
 		assert_ancestry_mismatch_err(verify_sidechain_block::<Pair, ParentchainBlock, _>(
 			curr_block,
 			SLOT_DURATION,
+			None,
 			&Some(last_block),
 			&parentchain_header,
 			&authorities,
@@ -411,6 +493,7 @@ mod tests {
 			verify_sidechain_block::<Pair, ParentchainBlock, _>(
 				curr_block,
 				SLOT_DURATION,
+				None,
 				&None,
 				&parentchain_header,
 				&authorities,
@@ -436,6 +519,7 @@ mod tests {
 			verify_sidechain_block::<Pair, ParentchainBlock, _>(
 				curr_block,
 				SLOT_DURATION,
+				None,
 				&Some(last_block),
 				&parentchain_header,
 				&authorities,
@@ -480,6 +564,7 @@ mod tests {
 			verify_sidechain_block::<Pair, ParentchainBlock, _>(
 				signed_block_to_verify,
 				SLOT_DURATION,
+				None,
 				&Some(last_block),
 				&parentchain_header_2,
 				&authorities,
@@ -488,4 +573,131 @@ mod tests {
 			ConsensusError::BlockAlreadyImported(1, 1)
 		);
 	}
+
+	fn backup_authority_block(timestamp: u64, last_block: &SidechainBlock) -> SignedBlock {
+		let parentchain_header = ParentchainHeaderBuilder::default().build();
+		let header = SidechainHeaderBuilder::default()
+			.with_parent_hash(last_block.hash())
+			.with_block_number(2)
+			.build();
+		let block_data = SidechainBlockDataBuilder::default()
+			.with_signer(Keyring::Alice.pair())
+			.with_timestamp(timestamp)
+			.with_layer_one_head(parentchain_header.hash())
+			.build();
+
+		SidechainBlockBuilder::default()
+			.with_header(header)
+			.with_block_data(block_data)
+			.with_signer(Keyring::Alice.pair())
+			.build_signed()
+	}
+
+	/// Bob authors the even slots, with Alice as backup author.
+	fn bob_and_alice() -> [AuthorityId<Pair>; 2] {
+		[Keyring::Bob.public(), Keyring::Alice.public()]
+	}
+
+	#[test]
+	fn verify_accepts_backup_author_after_claim_offset() {
+		let parentchain_header = ParentchainHeaderBuilder::default().build();
+		let last_block = SidechainBlockBuilder::default().build();
+		// Slot 2 starts at 600 ms, the backup may claim it from 840 ms on.
+		let curr_block = backup_authority_block(850, &last_block);
+
+		assert_ok!(verify_sidechain_block::<Pair, ParentchainBlock, _>(
+			curr_block,
+			SLOT_DURATION,
+			Some(0.5),
+			&Some(last_block),
+			&parentchain_header,
+			&bob_and_alice(),
+		));
+	}
+
+	#[test]
+	fn verify_errs_on_backup_author_before_claim_offset() {
+		let parentchain_header = ParentchainHeaderBuilder::default().build();
+		let last_block = SidechainBlockBuilder::default().build();
+		let curr_block = backup_authority_block(820, &last_block);
+
+		assert_matches!(
+			verify_sidechain_block::<Pair, ParentchainBlock, _>(
+				curr_block,
+				SLOT_DURATION,
+				Some(0.5),
+				&Some(last_block),
+				&parentchain_header,
+				&bob_and_alice(),
+			)
+			.unwrap_err(),
+			ConsensusError::InvalidAuthority(_)
+		);
+	}
+
+	#[test]
+	fn verify_errs_on_backup_author_if_slot_already_has_a_block() {
+		let parentchain_header = ParentchainHeaderBuilder::default().build();
+		let last_block_data = SidechainBlockDataBuilder::default().with_timestamp(610).build();
+		let last_block = SidechainBlockBuilder::default().with_block_data(last_block_data).build();
+		let curr_block = backup_authority_block(850, &last_block);
+
+		assert_matches!(
+			verify_sidechain_block::<Pair, ParentchainBlock, _>(
+				curr_block,
+				SLOT_DURATION,
+				Some(0.5),
+				&Some(last_block),
+				&parentchain_header,
+				&bob_and_alice(),
+			)
+			.unwrap_err(),
+			ConsensusError::InvalidAuthority(_)
+		);
+	}
+
+	#[test]
+	fn verify_errs_on_backup_author_with_future_timestamp() {
+		let parentchain_header = ParentchainHeaderBuilder::default().build();
+		let last_block = SidechainBlockBuilder::default().build();
+		// An even slot an hour from now, after the claim offset of the backup author.
+		let slot_duration_millis = SLOT_DURATION.as_millis() as u64;
+		let future_slot = (duration_now().as_millis() as u64 + 3_600_000) / slot_duration_millis;
+		let future_slot = future_slot - future_slot % 2;
+		let curr_block =
+			backup_authority_block(future_slot * slot_duration_millis + 250, &last_block);
+
+		assert_matches!(
+			verify_sidechain_block::<Pair, ParentchainBlock, _>(
+				curr_block,
+				SLOT_DURATION,
+				Some(0.5),
+				&Some(last_block),
+				&parentchain_header,
+				&bob_and_alice(),
+			)
+			.unwrap_err(),
+			ConsensusError::InvalidAuthority(_)
+		);
+	}
+
+	#[test]
+	fn verify_errs_on_backup_author_without_backup_slot_portion() {
+		let parentchain_header = ParentchainHeaderBuilder::default().build();
+		let last_block = SidechainBlockBuilder::default().build();
+		let curr_block = backup_authority_block(850, &last_block);
+
+		assert_matches!(
+			verify_sidechain_block::<Pair, ParentchainBlock, _>(
+				curr_block,
+				SLOT_DURATION,
+				None,
+				&Some(last_block),
+				&parentchain_header,
+				&bob_and_alice(),
+			)
+			.unwrap_err(),
+			ConsensusError::InvalidAuthority(_)
+		);
+	}
 }
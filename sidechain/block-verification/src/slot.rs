@@ -16,6 +16,9 @@
 */
 
 use crate::AuthorityId;
+use itp_settings::sidechain::{
+	BACKUP_AUTHOR_PROPAGATION_SLOT_PORTION, BLOCK_PROPOSAL_SLOT_PORTION,
+};
 pub use sp_consensus_slots::Slot;
 use sp_runtime::app_crypto::Pair;
 use std::time::Duration;
@@ -40,6 +43,110 @@ pub fn slot_author<P: Pair>(slot: Slot, authorities: &[AuthorityId<P>]) -> Optio
 	Some(current_author)
 }
 
+/// Rank of `author` in the backup order of `slot`: 0 for the slot author, `n` for the `n`-th
+/// backup author. The backup order continues round-robin after the slot author.
+pub fn author_rank<P: Pair>(
+	slot: Slot,
+	authorities: &[AuthorityId<P>],
+	author: &AuthorityId<P>,
+) -> Option<usize> {
+	let author_idx = authorities.iter().position(|a| a == author)?;
+	let slot_author_idx = (*slot % authorities.len() as u64) as usize;
+
+	Some((author_idx + authorities.len() - slot_author_idx) % authorities.len())
+}
+
+/// Time after the start of a slot from which on the author of the given rank may claim the slot.
+///
+/// The first backup author waits until the slot author had the time to propose its block
+/// ([`BLOCK_PROPOSAL_SLOT_PORTION`]) and to send it ([`BACKUP_AUTHOR_PROPAGATION_SLOT_PORTION`]).
+/// Each further backup author has to wait an additional `backup_slot_portion` of the slot.
+///
+/// Returns `None` if an author of this rank cannot claim the slot at all.
+pub fn claim_offset(
+	rank: usize,
+	slot_duration: Duration,
+	backup_slot_portion: f32,
+) -> Option<Duration> {
+	if rank == 0 {
+		return Some(Duration::default())
+	}
+	if !(backup_slot_portion > 0.0 && backup_slot_portion <= 1.0) {
+		return None
+	}
+
+	let slot_duration_millis = slot_duration.as_millis() as f32;
+	let first_backup_delay_millis = (slot_duration_millis
+		* (BLOCK_PROPOSAL_SLOT_PORTION + BACKUP_AUTHOR_PROPAGATION_SLOT_PORTION))
+		as u64;
+	let backup_delay_millis = (slot_duration_millis * backup_slot_portion) as u64;
+	let offset = Duration::from_millis(
+		first_backup_delay_millis
+			.saturating_add(backup_delay_millis.saturating_mul(rank as u64 - 1)),
+	);
+	if offset >= slot_duration {
+		return None
+	}
+	Some(offset)
+}
+
+/// Time at which the given slot starts.
+pub fn slot_start(slot: Slot, duration: Duration) -> Duration {
+	Duration::from_millis((*slot).saturating_mul(duration.as_millis() as u64))
+}
+
 pub fn slot_from_timestamp_and_duration(timestamp: Duration, duration: Duration) -> Slot {
 	((timestamp.as_millis() / duration.as_millis()) as u64).into()
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::ed25519::Pair;
+	use sp_keyring::ed25519::Keyring;
+
+	const SLOT_DURATION: Duration = Duration::from_millis(300);
+
+	#[test]
+	fn author_rank_follows_round_robin_order_after_slot_author() {
+		let authorities =
+			[Keyring::Alice.public(), Keyring::Bob.public(), Keyring::Charlie.public()];
+
+		assert_eq!(Some(0), author_rank::<Pair>(1.into(), &authorities, &Keyring::Bob.public()));
+		assert_eq!(
+			Some(1),
+			author_rank::<Pair>(1.into(), &authorities, &Keyring::Charlie.public())
+		);
+		assert_eq!(Some(2), author_rank::<Pair>(1.into(), &authorities, &Keyring::Alice.public()));
+		assert_eq!(None, author_rank::<Pair>(1.into(), &authorities, &Keyring::Dave.public()));
+	}
+
+	#[test]
+	fn claim_offset_grows_with_rank_until_end_of_slot() {
+		assert_eq!(Some(Duration::default()), claim_offset(0, SLOT_DURATION, 0.05));
+		assert_eq!(Some(Duration::from_millis(240)), claim_offset(1, SLOT_DURATION, 0.05));
+		assert_eq!(Some(Duration::from_millis(255)), claim_offset(2, SLOT_DURATION, 0.05));
+		assert_eq!(Some(Duration::from_millis(285)), claim_offset(4, SLOT_DURATION, 0.05));
+		assert_eq!(None, claim_offset(5, SLOT_DURATION, 0.05));
+	}
+
+	#[test]
+	fn first_backup_author_waits_for_proposal_and_propagation_of_slot_author_block() {
+		let proposal_end = SLOT_DURATION.mul_f32(BLOCK_PROPOSAL_SLOT_PORTION);
+
+		assert!(claim_offset(1, SLOT_DURATION, 0.05).unwrap() > proposal_end);
+	}
+
+	#[test]
+	fn backup_authors_cannot_claim_with_invalid_slot_portion() {
+		assert_eq!(Some(Duration::default()), claim_offset(0, SLOT_DURATION, 0.0));
+		assert_eq!(None, claim_offset(1, SLOT_DURATION, 0.0));
+		assert_eq!(None, claim_offset(1, SLOT_DURATION, -0.5));
+		assert_eq!(None, claim_offset(1, SLOT_DURATION, 1.5));
+	}
+
+	#[test]
+	fn slot_start_is_multiple_of_slot_duration() {
+		assert_eq!(Duration::from_millis(900), slot_start(3.into(), SLOT_DURATION));
+	}
+}
@@ -20,22 +20,41 @@
 // Reexport BlockImport trait which implements fn block_import()
 pub use its_consensus_common::BlockImport;
 
-use crate::{AuraVerifier, EnclaveOnChainOCallApi, SidechainBlockTrait};
+#[cfg(feature = "sgx")]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use crate::{
+	authorities, slot_duration_at, AuraVerifier, EnclaveOnChainOCallApi, SidechainBlockTrait,
+};
 use ita_stf::hash::TrustedOperationOrHash;
 use itc_parentchain_block_import_dispatcher::triggered_dispatcher::TriggerParentchainBlockImport;
 use itp_enclave_metrics::EnclaveMetric;
 use itp_ocall_api::{EnclaveMetricsOCallApi, EnclaveSidechainOCallApi};
-use itp_settings::sidechain::SLOT_DURATION;
+use itp_settings::{
+	files::STATE_SNAPSHOTS_CACHE_SIZE,
+	sidechain::{
+		BACKUP_AUTHOR_SLOT_PORTION, MAX_BLOCK_ENCODED_SIZE, MAX_BLOCK_WEIGHT,
+		MAX_TRUSTED_OPERATIONS_PER_BLOCK, SLOT_DURATION,
+	},
+};
 use itp_sgx_crypto::{key_repository::AccessKey, StateCrypto};
 use itp_sgx_externalities::SgxExternalities;
 use itp_stf_state_handler::handle_state::HandleState;
+use itp_time_utils::now_as_u64;
 use itp_top_pool_author::traits::{AuthorApi, OnBlockImported};
-use itp_types::H256;
-use its_consensus_common::Error as ConsensusError;
-use its_primitives::traits::{
-	BlockData, Header as HeaderTrait, ShardIdentifierFor, SignedBlock as SignedBlockTrait,
+use itp_types::{BlockLimits, H256};
+use its_consensus_common::{Error as ConsensusError, HandleFinalityVotes, ReportEquivocation};
+use its_primitives::{
+	traits::{
+		BlockData, Header as HeaderTrait, NotifyHeads, ShardIdentifierFor,
+		SignedBlock as SignedBlockTrait,
+	},
+	types::{BlockHash, BlockNumber},
 };
-use its_state::SidechainDB;
+use its_state::{LastBlockExt, SidechainDB};
 use its_validateer_fetch::ValidateerFetch;
 use log::*;
 use sp_core::Pair;
@@ -43,7 +62,14 @@ use sp_runtime::{
 	generic::SignedBlock as SignedParentchainBlock,
 	traits::{Block as ParentchainBlockTrait, Header},
 };
-use std::{marker::PhantomData, sync::Arc};
+use std::{collections::VecDeque, marker::PhantomData, sync::Arc, vec::Vec};
+
+/// State of a shard before the revert of a block, to restore it if the reorganization fails.
+pub struct RevertedBlock {
+	state: SgxExternalities,
+	/// State hashes of the reverted blocks, as in the importer (newest first).
+	state_hashes_before_import: Vec<(BlockHash, H256)>,
+}
 
 /// Implements `BlockImport`.
 #[derive(Clone)]
@@ -57,12 +83,24 @@ pub struct BlockImporter<
 	StateKeyRepository,
 	TopPoolAuthor,
 	ParentchainBlockImporter,
+	HeadNotifier,
+	FinalityGadget,
+	EquivocationReporter,
 > {
 	state_handler: Arc<StateHandler>,
 	state_key_repository: Arc<StateKeyRepository>,
 	top_pool_author: Arc<TopPoolAuthor>,
 	parentchain_block_importer: Arc<ParentchainBlockImporter>,
 	ocall_api: Arc<OCallApi>,
+	head_notifier: Arc<HeadNotifier>,
+	finality_gadget: Arc<FinalityGadget>,
+	equivocation_reporter: Arc<EquivocationReporter>,
+	/// State hashes before the import of the most recently imported blocks (newest first),
+	/// needed to revert blocks in case of a reorganization. Includes the blocks we produced,
+	/// we import them like any other block once they come back from our own broadcast.
+	///
+	/// Only kept in memory: after a restart, blocks imported before it can no longer be reverted.
+	state_hashes_before_import: Arc<RwLock<VecDeque<(BlockHash, H256)>>>,
 	_phantom: PhantomData<(Authority, ParentchainBlock, SignedSidechainBlock, SidechainState)>,
 }
 
@@ -76,6 +114,9 @@ impl<
 		StateKeyRepository,
 		TopPoolAuthor,
 		ParentchainBlockImporter,
+		HeadNotifier,
+		FinalityGadget,
+		EquivocationReporter,
 	>
 	BlockImporter<
 		Authority,
@@ -87,6 +128,9 @@ impl<
 		StateKeyRepository,
 		TopPoolAuthor,
 		ParentchainBlockImporter,
+		HeadNotifier,
+		FinalityGadget,
+		EquivocationReporter,
 	> where
 	Authority: Pair,
 	Authority::Public: std::fmt::Debug,
@@ -100,12 +144,17 @@ impl<
 		+ EnclaveMetricsOCallApi
 		+ Send
 		+ Sync,
-	StateHandler: HandleState<StateT = SgxExternalities>,
+	StateHandler: HandleState<StateT = SgxExternalities, HashType = H256>,
 	StateKeyRepository: AccessKey,
 	<StateKeyRepository as AccessKey>::KeyType: StateCrypto,
 	TopPoolAuthor: AuthorApi<H256, H256> + OnBlockImported<Hash = H256>,
 	ParentchainBlockImporter:
 		TriggerParentchainBlockImport<SignedParentchainBlock<ParentchainBlock>> + Send + Sync,
+	HeadNotifier: NotifyHeads<
+		<<SignedSidechainBlock as SignedBlockTrait>::Block as SidechainBlockTrait>::HeaderType,
+	>,
+	FinalityGadget: HandleFinalityVotes<ParentchainBlock::Header>,
+	EquivocationReporter: ReportEquivocation<SignedSidechainBlock>,
 {
 	pub fn new(
 		state_handler: Arc<StateHandler>,
@@ -113,6 +162,9 @@ impl<
 		top_pool_author: Arc<TopPoolAuthor>,
 		parentchain_block_importer: Arc<ParentchainBlockImporter>,
 		ocall_api: Arc<OCallApi>,
+		head_notifier: Arc<HeadNotifier>,
+		finality_gadget: Arc<FinalityGadget>,
+		equivocation_reporter: Arc<EquivocationReporter>,
 	) -> Self {
 		Self {
 			state_handler,
@@ -120,10 +172,27 @@ impl<
 			top_pool_author,
 			parentchain_block_importer,
 			ocall_api,
+			head_notifier,
+			finality_gadget,
+			equivocation_reporter,
+			state_hashes_before_import: Default::default(),
 			_phantom: Default::default(),
 		}
 	}
 
+	fn record_state_hash_before_import(&self, block_hash: BlockHash, state_hash: H256) {
+		let mut state_hashes_lock = match self.state_hashes_before_import.write() {
+			Ok(lock) => lock,
+			Err(_) => {
+				error!("Failed to record state hash of imported block, encountered poisoned lock");
+				return
+			},
+		};
+		state_hashes_lock.push_front((block_hash, state_hash));
+		// Older state versions are no longer available in the state snapshot repository anyway.
+		state_hashes_lock.truncate(STATE_SNAPSHOTS_CACHE_SIZE);
+	}
+
 	fn update_top_pool(&self, sidechain_block: &SignedSidechainBlock::Block) {
 		// Notify pool about imported block for status updates of the calls.
 		self.top_pool_author.on_block_imported(
@@ -139,16 +208,15 @@ impl<
 			.map(|hash| (TrustedOperationOrHash::Hash(*hash), true))
 			.collect();
 
-		let _calls_failed_to_remove = self
+		let calls_failed_to_remove = self
 			.top_pool_author
 			.remove_calls_from_pool(sidechain_block.header().shard_id(), executed_operations);
 
-		// In case the executed call did not originate in our own TOP pool, we will not be able to remove it from our TOP pool.
-		// So this error will occur frequently, without it meaning that something really went wrong.
-		// TODO: Once the TOP pools are synchronized, we will want this check again!
-		// for call_failed_to_remove in _calls_failed_to_remove {
-		// 	error!("Could not remove call {:?} from top pool", call_failed_to_remove);
-		// }
+		// Trusted operations are gossiped among the validateers, so every executed call
+		// is expected to be present in our own TOP pool as well.
+		for call_failed_to_remove in calls_failed_to_remove {
+			error!("Could not remove call {:?} from top pool", call_failed_to_remove);
+		}
 	}
 }
 
@@ -161,6 +229,9 @@ impl<
 		StateKeyRepository,
 		TopPoolAuthor,
 		ParentchainBlockImporter,
+		HeadNotifier,
+		FinalityGadget,
+		EquivocationReporter,
 	> BlockImport<ParentchainBlock, SignedSidechainBlock>
 	for BlockImporter<
 		Authority,
@@ -172,6 +243,9 @@ impl<
 		StateKeyRepository,
 		TopPoolAuthor,
 		ParentchainBlockImporter,
+		HeadNotifier,
+		FinalityGadget,
+		EquivocationReporter,
 	> where
 	Authority: Pair,
 	Authority::Public: std::fmt::Debug,
@@ -185,12 +259,17 @@ impl<
 		+ EnclaveMetricsOCallApi
 		+ Send
 		+ Sync,
-	StateHandler: HandleState<StateT = SgxExternalities>,
+	StateHandler: HandleState<StateT = SgxExternalities, HashType = H256>,
 	StateKeyRepository: AccessKey,
 	<StateKeyRepository as AccessKey>::KeyType: StateCrypto,
 	TopPoolAuthor: AuthorApi<H256, H256> + OnBlockImported<Hash = H256>,
 	ParentchainBlockImporter:
 		TriggerParentchainBlockImport<SignedParentchainBlock<ParentchainBlock>> + Send + Sync,
+	HeadNotifier: NotifyHeads<
+		<<SignedSidechainBlock as SignedBlockTrait>::Block as SidechainBlockTrait>::HeaderType,
+	>,
+	FinalityGadget: HandleFinalityVotes<ParentchainBlock::Header>,
+	EquivocationReporter: ReportEquivocation<SignedSidechainBlock>,
 {
 	type Verifier = AuraVerifier<
 		Authority,
@@ -202,9 +281,16 @@ impl<
 	type SidechainState = SidechainDB<SignedSidechainBlock::Block, SgxExternalities>;
 	type StateCrypto = <StateKeyRepository as AccessKey>::KeyType;
 	type Context = OCallApi;
+	type RevertedBlock = RevertedBlock;
 
 	fn verifier(&self, state: Self::SidechainState) -> Self::Verifier {
 		AuraVerifier::<Authority, ParentchainBlock, _, _, _>::new(SLOT_DURATION, state)
+			.with_backup_slot_portion(BACKUP_AUTHOR_SLOT_PORTION)
+			.with_block_limits(BlockLimits::new(
+				MAX_TRUSTED_OPERATIONS_PER_BLOCK,
+				MAX_BLOCK_ENCODED_SIZE,
+				MAX_BLOCK_WEIGHT,
+			))
 	}
 
 	fn apply_state_update<F>(
@@ -215,17 +301,28 @@ impl<
 	where
 		F: FnOnce(Self::SidechainState) -> Result<Self::SidechainState, ConsensusError>,
 	{
+		// Has to be read before acquiring the write lock, `load_for_mutation` blocks any other access.
+		let state_hash_before_import = self
+			.state_handler
+			.latest_state_hash(shard)
+			.map_err(|e| ConsensusError::Other(format!("{:?}", e).into()))?;
+
 		let (write_lock, state) = self
 			.state_handler
 			.load_for_mutation(shard)
 			.map_err(|e| ConsensusError::Other(format!("{:?}", e).into()))?;
 
 		let updated_state = mutating_function(Self::SidechainState::new(state))?;
+		let maybe_imported_block_hash = updated_state.get_last_block().map(|b| b.hash());
 
 		self.state_handler
 			.write_after_mutation(updated_state.ext, write_lock, shard)
 			.map_err(|e| ConsensusError::Other(format!("{:?}", e).into()))?;
 
+		if let Some(imported_block_hash) = maybe_imported_block_hash {
+			self.record_state_hash_before_import(imported_block_hash, state_hash_before_import);
+		}
+
 		Ok(())
 	}
 
@@ -308,6 +405,130 @@ impl<
 			})
 	}
 
+	fn get_last_imported_block(
+		&self,
+		shard: &ShardIdentifierFor<SignedSidechainBlock>,
+	) -> Result<Option<SignedSidechainBlock::Block>, ConsensusError> {
+		let state = self
+			.state_handler
+			.load(shard)
+			.map_err(|e| ConsensusError::Other(format!("{:?}", e).into()))?;
+		Ok(Self::SidechainState::new(state).get_last_block())
+	}
+
+	fn revert_block(
+		&self,
+		shard: &ShardIdentifierFor<SignedSidechainBlock>,
+		block_hash: &BlockHash,
+	) -> Result<RevertedBlock, ConsensusError> {
+		let mut state_hashes_lock = self
+			.state_hashes_before_import
+			.write()
+			.map_err(|_| ConsensusError::LockPoisoning)?;
+
+		let position = state_hashes_lock
+			.iter()
+			.position(|(imported_block_hash, _)| imported_block_hash == block_hash)
+			.ok_or(ConsensusError::CannotRevertBlock(*block_hash))?;
+		let (_, state_hash) = state_hashes_lock[position];
+
+		// Reverting prunes the newer state versions, so we keep the current state to restore it.
+		let state = self
+			.state_handler
+			.load(shard)
+			.map_err(|e| ConsensusError::Other(format!("{:?}", e).into()))?;
+
+		self.state_handler
+			.revert_to(shard, &state_hash)
+			.map_err(|e| ConsensusError::Other(format!("{:?}", e).into()))?;
+
+		// The reverted block and all blocks imported after it are gone, their trusted
+		// operations are put back into the pool (oldest block first).
+		let state_hashes_before_import: Vec<(BlockHash, H256)> =
+			state_hashes_lock.drain(..=position).collect();
+		for (reverted_block_hash, _) in state_hashes_before_import.iter().rev() {
+			self.top_pool_author.on_block_retracted(*reverted_block_hash);
+		}
+		info!("Reverted sidechain block {:?} of shard {:?}", block_hash, shard);
+		Ok(RevertedBlock { state, state_hashes_before_import })
+	}
+
+	fn restore_reverted_block(
+		&self,
+		shard: &ShardIdentifierFor<SignedSidechainBlock>,
+		reverted_block: RevertedBlock,
+	) -> Result<(), ConsensusError> {
+		let mut state_hashes_lock = self
+			.state_hashes_before_import
+			.write()
+			.map_err(|_| ConsensusError::LockPoisoning)?;
+
+		let state = Self::SidechainState::new(reverted_block.state);
+		let maybe_last_block = state.get_last_block();
+		self.state_handler
+			.reset(state.ext, shard)
+			.map_err(|e| ConsensusError::Other(format!("{:?}", e).into()))?;
+
+		for state_hash_before_import in reverted_block.state_hashes_before_import.into_iter().rev()
+		{
+			state_hashes_lock.push_front(state_hash_before_import);
+		}
+		state_hashes_lock.truncate(STATE_SNAPSHOTS_CACHE_SIZE);
+
+		// The operations of the restored block were put back into the pool upon the revert.
+		if let Some(last_block) = maybe_last_block {
+			self.update_top_pool(&last_block);
+			info!("Restored sidechain block {:?} of shard {:?}", last_block.hash(), shard);
+		}
+		Ok(())
+	}
+
+	fn vote_for_finality(
+		&self,
+		sidechain_block: &SignedSidechainBlock::Block,
+		parentchain_header: &ParentchainBlock::Header,
+	) -> Result<(), ConsensusError> {
+		self.finality_gadget.vote_for_block(
+			sidechain_block.header().shard_id(),
+			sidechain_block.header().block_number(),
+			sidechain_block.hash(),
+			parentchain_header,
+		)
+	}
+
+	fn last_finalized_block_number(
+		&self,
+		shard: &ShardIdentifierFor<SignedSidechainBlock>,
+	) -> Option<BlockNumber> {
+		self.finality_gadget.last_finalized_block(shard).map(|(number, _)| number)
+	}
+
+	fn report_equivocation(
+		&self,
+		signed_sidechain_block: &SignedSidechainBlock,
+		parentchain_header: &ParentchainBlock::Header,
+	) -> Result<(), ConsensusError> {
+		// The slot duration is only known for the parentchain block the sidechain block refers to.
+		if parentchain_header.hash() != signed_sidechain_block.block().block_data().layer_one_head()
+		{
+			debug!(
+				"Parentchain block of sidechain block {} is unknown, skipping equivocation check",
+				signed_sidechain_block.block().header().block_number()
+			);
+			return Ok(())
+		}
+
+		let slot_duration =
+			slot_duration_at(self.ocall_api.as_ref(), parentchain_header, SLOT_DURATION)?;
+		let authorities =
+			authorities::<_, Authority, _>(self.ocall_api.as_ref(), parentchain_header)?;
+		self.equivocation_reporter.check_and_report(
+			signed_sidechain_block,
+			slot_duration,
+			&authorities,
+		)
+	}
+
 	fn cleanup(&self, signed_sidechain_block: &SignedSidechainBlock) -> Result<(), ConsensusError> {
 		let sidechain_block = signed_sidechain_block.block();
 
@@ -321,6 +542,16 @@ impl<
 			warn!("Failed to update sidechain block height metric: {:?}", e);
 		}
 
+		// Send metric about the time passed since the block was produced.
+		let import_latency = now_as_u64().saturating_sub(sidechain_block.block_data().timestamp());
+		let import_latency_metric = EnclaveMetric::SidechainBlockImportLatency(import_latency);
+		if let Err(e) = self.ocall_api.update_metric(import_latency_metric) {
+			warn!("Failed to update sidechain block import latency metric: {:?}", e);
+		}
+
+		// Notify the head subscribers about the newly imported block.
+		self.head_notifier.notify_new_head(sidechain_block.header());
+
 		Ok(())
 	}
 }
@@ -31,16 +31,18 @@ extern crate sgx_tstd as std;
 
 use core::marker::PhantomData;
 use itc_parentchain_block_import_dispatcher::triggered_dispatcher::TriggerParentchainBlockImport;
-use itp_ocall_api::EnclaveOnChainOCallApi;
+use itp_enclave_metrics::EnclaveMetric;
+use itp_ocall_api::{EnclaveMetricsOCallApi, EnclaveOnChainOCallApi};
+use itp_settings::sidechain::SLOT_DURATION;
 use itp_time_utils::duration_now;
-use its_block_verification::slot::slot_author;
+use its_block_verification::slot::{author_rank, claim_offset, slot_author, slot_start};
 use its_consensus_common::{Environment, Error as ConsensusError, Proposer};
 use its_consensus_slots::{SimpleSlotWorker, Slot, SlotInfo};
 use its_primitives::{
 	traits::{Block as SidechainBlockTrait, Header as HeaderTrait, SignedBlock},
 	types::block::BlockHash,
 };
-use its_validateer_fetch::ValidateerFetch;
+use its_validateer_fetch::{SlotDurationFetch, ValidateerFetch};
 use sp_core::ByteArray;
 use sp_runtime::{
 	app_crypto::{sp_core::H256, Pair},
@@ -73,6 +75,7 @@ pub struct Aura<
 	parentchain_import_trigger: Arc<ImportTrigger>,
 	environment: Environment,
 	claim_strategy: SlotClaimStrategy,
+	max_parallel_shards: usize,
 	_phantom: PhantomData<(AuthorityPair, ParentchainBlock, SidechainBlock)>,
 }
 
@@ -91,6 +94,7 @@ impl<AuthorityPair, ParentchainBlock, SidechainBlock, Environment, OcallApi, Imp
 			parentchain_import_trigger,
 			environment,
 			claim_strategy: SlotClaimStrategy::RoundRobin,
+			max_parallel_shards: 1,
 			_phantom: Default::default(),
 		}
 	}
@@ -100,19 +104,47 @@ impl<AuthorityPair, ParentchainBlock, SidechainBlock, Environment, OcallApi, Imp
 
 		self
 	}
+
+	/// Produce blocks for up to `max_parallel_shards` shards in parallel.
+	pub fn with_max_parallel_shards(mut self, max_parallel_shards: usize) -> Self {
+		self.max_parallel_shards = max_parallel_shards;
+
+		self
+	}
+}
+
+impl<AuthorityPair, ParentchainBlock, SidechainBlock, Environment, OcallApi, ImportTrigger> Clone
+	for Aura<AuthorityPair, ParentchainBlock, SidechainBlock, Environment, OcallApi, ImportTrigger>
+where
+	AuthorityPair: Clone,
+	Environment: Clone,
+	OcallApi: Clone,
+{
+	fn clone(&self) -> Self {
+		Self {
+			authority_pair: self.authority_pair.clone(),
+			ocall_api: self.ocall_api.clone(),
+			parentchain_import_trigger: self.parentchain_import_trigger.clone(),
+			environment: self.environment.clone(),
+			claim_strategy: self.claim_strategy.clone(),
+			max_parallel_shards: self.max_parallel_shards,
+			_phantom: Default::default(),
+		}
+	}
 }
 
-/// The fraction of total block time we are allowed to be producing the block. So that we have
-/// enough time send create and send the block to fellow validateers.
-pub const BLOCK_PROPOSAL_SLOT_PORTION: f32 = 0.7;
+pub use itp_settings::sidechain::BLOCK_PROPOSAL_SLOT_PORTION;
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum SlotClaimStrategy {
 	/// try to produce a block always even if it's not the authors slot
 	/// Intended for first phase to see if aura production works
 	Always,
 	/// Proper Aura strategy: Only produce blocks, when it's the authors slot.
 	RoundRobin,
+	/// Like `RoundRobin`, but if the slot author did not produce a block, the following
+	/// authorities claim the slot in turn, see [`claim_offset`].
+	RoundRobinWithFallback { backup_slot_portion: f32 },
 }
 
 type AuthorityId<P> = <P as Pair>::Public;
@@ -129,7 +161,7 @@ where
 	E: Environment<ParentchainBlock, SignedSidechainBlock, Error = ConsensusError>,
 	E::Proposer: Proposer<ParentchainBlock, SignedSidechainBlock>,
 	SignedSidechainBlock: SignedBlock + Send + 'static,
-	OcallApi: ValidateerFetch + EnclaveOnChainOCallApi + Send + 'static,
+	OcallApi: ValidateerFetch + EnclaveOnChainOCallApi + EnclaveMetricsOCallApi + Send + 'static,
 	ImportTrigger: TriggerParentchainBlockImport<SignedParentchainBlock<ParentchainBlock>>,
 {
 	type Proposer = E::Proposer;
@@ -153,10 +185,9 @@ where
 		Some(epoch_data.len())
 	}
 
-	// While the header is not used in aura, it is used in different consensus systems, so it should be left there.
 	fn claim_slot(
 		&self,
-		_header: &ParentchainBlock::Header,
+		header: &ParentchainBlock::Header,
 		slot: Slot,
 		epoch_data: &Self::EpochData,
 	) -> Option<Self::Claim> {
@@ -167,6 +198,31 @@ where
 			return Some(self.authority_pair.public())
 		}
 
+		if let SlotClaimStrategy::RoundRobinWithFallback { backup_slot_portion } =
+			self.claim_strategy
+		{
+			let slot_duration = match self.slot_duration(header) {
+				Ok(slot_duration) => slot_duration,
+				Err(e) => {
+					log::warn!(target: self.logging_target(), "Failed to fetch slot duration: {:?}", e);
+					return None
+				},
+			};
+			let rank =
+				author_rank::<AuthorityPair>(slot, epoch_data, &self.authority_pair.public())?;
+			let time_in_slot = duration_now().saturating_sub(slot_start(slot, slot_duration));
+
+			if is_in_claim_window(rank, time_in_slot, slot_duration, backup_slot_portion) {
+				log::info!(
+					target: self.logging_target(),
+					"Claiming slot ({}) as backup author (rank {})",
+					*slot,
+					rank
+				);
+				return Some(self.authority_pair.public())
+			}
+		}
+
 		if self.claim_strategy == SlotClaimStrategy::Always {
 			log::debug!(
 				target: self.logging_target(),
@@ -214,6 +270,20 @@ where
 
 		Ok(maybe_parentchain_block.map(|b| b.block.header().clone()))
 	}
+
+	fn slot_duration(&self, header: &ParentchainBlock::Header) -> Result<Duration, ConsensusError> {
+		slot_duration_at(&self.ocall_api, header, SLOT_DURATION)
+	}
+
+	fn update_metric(&self, metric: EnclaveMetric) {
+		if let Err(e) = self.ocall_api.update_metric(metric) {
+			log::warn!(target: self.logging_target(), "Failed to update metric: {:?}", e);
+		}
+	}
+
+	fn max_parallel_shards(&self) -> usize {
+		self.max_parallel_shards
+	}
 }
 
 /// unit-testable remaining duration fn.
@@ -234,6 +304,47 @@ fn proposing_remaining_duration<ParentchainBlock: ParentchainBlockTrait>(
 	std::cmp::min(slot_remaining, proposing_duration)
 }
 
+/// Whether a backup author of the given rank may claim the slot at `time_in_slot`.
+///
+/// Each rank gets an exclusive window, such that at most one backup author claims a slot.
+fn is_in_claim_window(
+	rank: usize,
+	time_in_slot: Duration,
+	slot_duration: Duration,
+	backup_slot_portion: f32,
+) -> bool {
+	if rank == 0 {
+		return false
+	}
+
+	let window_start = match claim_offset(rank, slot_duration, backup_slot_portion) {
+		Some(offset) => offset,
+		None => return false,
+	};
+	let window_end =
+		claim_offset(rank + 1, slot_duration, backup_slot_portion).unwrap_or(slot_duration);
+
+	window_start <= time_in_slot && time_in_slot < window_end
+}
+
+/// Slot duration in effect at the given parentchain header.
+///
+/// Falls back to `default_slot_duration` if no slot duration is configured on the parentchain.
+pub fn slot_duration_at<OcallApi, ParentchainHeader>(
+	ocall_api: &OcallApi,
+	header: &ParentchainHeader,
+	default_slot_duration: Duration,
+) -> Result<Duration, ConsensusError>
+where
+	OcallApi: EnclaveOnChainOCallApi,
+	ParentchainHeader: ParentchainHeaderTrait<Hash = H256>,
+{
+	Ok(ocall_api
+		.slot_duration(header)
+		.map_err(|e| ConsensusError::Other(e.to_string().into()))?
+		.unwrap_or(default_slot_duration))
+}
+
 fn authorities<ValidateerFetcher, P, ParentchainHeader>(
 	ocall_api: &ValidateerFetcher,
 	header: &ParentchainHeader,
@@ -258,11 +369,13 @@ mod tests {
 		fixtures::{types::TestAura, validateer, SLOT_DURATION},
 		mocks::environment_mock::EnvironmentMock,
 	};
+	use codec::Encode;
 	use itc_parentchain_block_import_dispatcher::trigger_parentchain_block_import_mock::TriggerParentchainBlockImportMock;
 	use itc_parentchain_test::{
 		parentchain_block_builder::ParentchainBlockBuilder,
 		parentchain_header_builder::ParentchainHeaderBuilder,
 	};
+	use itp_teerex_storage::{SidechainPalletStorage, SidechainStorageKeys};
 	use itp_test::mock::onchain_mock::OnchainMock;
 	use itp_types::{
 		Block as ParentchainBlock, Enclave, Header as ParentchainHeader,
@@ -361,6 +474,37 @@ mod tests {
 		assert!(aura.claim_slot(&header, 3.into(), &authorities).is_some());
 	}
 
+	#[test]
+	fn slot_author_is_never_in_backup_claim_window() {
+		assert!(!is_in_claim_window(0, Duration::from_millis(0), SLOT_DURATION, 0.5));
+		assert!(!is_in_claim_window(0, Duration::from_millis(200), SLOT_DURATION, 0.5));
+	}
+
+	#[test]
+	fn backup_authors_claim_windows_do_not_overlap() {
+		let slot_duration = Duration::from_millis(300);
+
+		assert!(!is_in_claim_window(1, Duration::from_millis(239), slot_duration, 0.1));
+		assert!(is_in_claim_window(1, Duration::from_millis(240), slot_duration, 0.1));
+		assert!(!is_in_claim_window(1, Duration::from_millis(270), slot_duration, 0.1));
+		assert!(is_in_claim_window(2, Duration::from_millis(270), slot_duration, 0.1));
+		assert!(is_in_claim_window(2, Duration::from_millis(299), slot_duration, 0.1));
+		assert!(!is_in_claim_window(3, Duration::from_millis(299), slot_duration, 0.1));
+	}
+
+	#[test]
+	fn backup_authors_do_not_claim_while_slot_author_may_still_propose() {
+		let proposal_end = SLOT_DURATION.mul_f32(BLOCK_PROPOSAL_SLOT_PORTION);
+
+		assert!(!is_in_claim_window(1, proposal_end, SLOT_DURATION, 0.1));
+	}
+
+	#[test]
+	fn last_backup_author_claims_until_end_of_slot() {
+		assert!(is_in_claim_window(1, Duration::from_millis(299), SLOT_DURATION, 0.5));
+		assert!(!is_in_claim_window(1, Duration::from_millis(300), SLOT_DURATION, 0.5));
+	}
+
 	#[test]
 	fn on_slot_returns_block() {
 		let _ = env_logger::builder().is_test(true).try_init();
@@ -391,6 +535,24 @@ mod tests {
 		assert_eq!(result.len(), 2);
 	}
 
+	#[test]
+	fn on_slot_for_multiple_shards_in_parallel_returns_blocks_in_shard_order() {
+		let _ = env_logger::builder().is_test(true).try_init();
+
+		let onchain_mock = onchain_mock_with_default_authorities_and_header();
+		let mut aura = get_aura(onchain_mock, Default::default()).with_max_parallel_shards(2);
+
+		let slot_info = now_slot_with_default_header(0.into());
+		let shards: Vec<H256> = (0..3).map(H256::from_low_u64_be).collect();
+
+		let result = PerShardSlotWorkerScheduler::on_slot(&mut aura, slot_info, shards.clone());
+
+		assert_eq!(
+			result.iter().map(|r| r.block.block.header.shard_id).collect::<Vec<_>>(),
+			shards
+		);
+	}
+
 	#[test]
 	fn on_slot_with_nano_second_remaining_duration_does_not_panic() {
 		let _ = env_logger::builder().is_test(true).try_init();
@@ -529,6 +691,52 @@ mod tests {
 		assert!(!parentchain_block_import_trigger.has_import_been_called());
 	}
 
+	#[test]
+	fn on_slot_does_not_claim_slot_if_latest_parentchain_header_in_queue_changes_slot_duration() {
+		let _ = env_logger::builder().is_test(true).try_init();
+		let already_imported_parentchain_header =
+			ParentchainHeaderBuilder::default().with_number(84).build();
+		let latest_parentchain_header = ParentchainHeaderBuilder::default().with_number(85).build();
+		let parentchain_block_import_trigger =
+			create_import_trigger_with_header(latest_parentchain_header.clone());
+		let validateer_set = create_validateer_set_from_publics(default_authorities());
+		let mut onchain_mock = OnchainMock::default()
+			.add_validateer_set(&already_imported_parentchain_header, Some(validateer_set.clone()))
+			.add_validateer_set(&latest_parentchain_header, Some(validateer_set));
+		onchain_mock.insert_at_header(
+			&latest_parentchain_header,
+			SidechainPalletStorage::slot_duration(),
+			600u64.encode(),
+		);
+
+		let mut aura = get_aura(onchain_mock, parentchain_block_import_trigger.clone());
+
+		let slot_info = now_slot(0.into(), &already_imported_parentchain_header);
+		let result = SimpleSlotWorker::on_slot(&mut aura, slot_info, Default::default());
+
+		assert!(result.is_none());
+		assert!(!parentchain_block_import_trigger.has_import_been_called());
+	}
+
+	#[test]
+	fn slot_duration_falls_back_to_default_if_not_configured_on_chain() {
+		let header = ParentchainHeaderBuilder::default().build();
+		let mut onchain_mock = OnchainMock::default();
+
+		assert_eq!(slot_duration_at(&onchain_mock, &header, SLOT_DURATION).unwrap(), SLOT_DURATION);
+
+		onchain_mock.insert_at_header(
+			&header,
+			SidechainPalletStorage::slot_duration(),
+			600u64.encode(),
+		);
+
+		assert_eq!(
+			slot_duration_at(&onchain_mock, &header, SLOT_DURATION).unwrap(),
+			Duration::from_millis(600)
+		);
+	}
+
 	#[test]
 	fn proposing_remaining_duration_works() {
 		let slot_info = now_slot_with_default_header(0.into());
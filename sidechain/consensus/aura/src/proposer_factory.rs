@@ -21,7 +21,7 @@ use finality_grandpa::BlockNumberOps;
 use itp_sgx_externalities::{SgxExternalitiesTrait, StateHash};
 use itp_stf_executor::traits::StateUpdateProposer;
 use itp_top_pool_author::traits::AuthorApi;
-use itp_types::H256;
+use itp_types::{BlockLimits, H256};
 use its_block_composer::ComposeBlock;
 use its_consensus_common::{Environment, Error as ConsensusError};
 use its_primitives::traits::{
@@ -41,6 +41,7 @@ pub struct ProposerFactory<ParentchainBlock: Block, TopPoolAuthor, StfExecutor,
 	top_pool_author: Arc<TopPoolAuthor>,
 	stf_executor: Arc<StfExecutor>,
 	block_composer: Arc<BlockComposer>,
+	block_limits: BlockLimits,
 	_phantom: PhantomData<ParentchainBlock>,
 }
 
@@ -56,6 +57,27 @@ impl<ParentchainBlock: Block, TopPoolAuthor, StfExecutor, BlockComposer>
 			top_pool_author: top_pool_executor,
 			stf_executor,
 			block_composer,
+			block_limits: BlockLimits::unlimited(),
+			_phantom: Default::default(),
+		}
+	}
+
+	/// Limit the content of the proposed sidechain blocks.
+	pub fn with_block_limits(mut self, block_limits: BlockLimits) -> Self {
+		self.block_limits = block_limits;
+		self
+	}
+}
+
+impl<ParentchainBlock: Block, TopPoolAuthor, StfExecutor, BlockComposer> Clone
+	for ProposerFactory<ParentchainBlock, TopPoolAuthor, StfExecutor, BlockComposer>
+{
+	fn clone(&self) -> Self {
+		Self {
+			top_pool_author: self.top_pool_author.clone(),
+			stf_executor: self.stf_executor.clone(),
+			block_composer: self.block_composer.clone(),
+			block_limits: self.block_limits,
 			_phantom: Default::default(),
 		}
 	}
@@ -107,6 +129,7 @@ where
 			top_pool_author: self.top_pool_author.clone(),
 			stf_executor: self.stf_executor.clone(),
 			block_composer: self.block_composer.clone(),
+			block_limits: self.block_limits,
 			parentchain_header: parent_header,
 			shard,
 			_phantom: PhantomData,
@@ -21,14 +21,14 @@ use itp_sgx_externalities::{SgxExternalitiesTrait, StateHash};
 use itp_stf_executor::traits::StateUpdateProposer;
 use itp_time_utils::now_as_u64;
 use itp_top_pool_author::traits::AuthorApi;
-use itp_types::H256;
+use itp_types::{BlockLimits, H256};
 use its_block_composer::ComposeBlock;
 use its_consensus_common::{Error as ConsensusError, Proposal, Proposer};
 use its_primitives::traits::{
 	Block as SidechainBlockTrait, Header as HeaderTrait, ShardIdentifierFor,
 	SignedBlock as SignedSidechainBlockTrait,
 };
-use its_state::{SidechainDB, SidechainState, SidechainSystemExt};
+use its_state::{LastBlockImportExt, SidechainDB, SidechainState, SidechainSystemExt};
 use log::*;
 use sp_runtime::{
 	traits::{Block, NumberFor},
@@ -37,6 +37,11 @@ use sp_runtime::{
 use std::{marker::PhantomData, string::ToString, sync::Arc, time::Duration, vec::Vec};
 
 pub type ExternalitiesFor<T> = <T as StateUpdateProposer>::Externalities;
+
+/// Upper bound of the encoded size of a signed sidechain block without its trusted operation
+/// hashes and state diff, i.e. the header, the signature and the remaining block data.
+const BLOCK_ENCODING_OVERHEAD: usize = 1024;
+
 ///! `SlotProposer` instance that has access to everything needed to propose a sidechain block.
 pub struct SlotProposer<
 	ParentchainBlock: Block,
@@ -48,6 +53,7 @@ pub struct SlotProposer<
 	pub(crate) top_pool_author: Arc<TopPoolAuthor>,
 	pub(crate) stf_executor: Arc<StfExecutor>,
 	pub(crate) block_composer: Arc<BlockComposer>,
+	pub(crate) block_limits: BlockLimits,
 	pub(crate) parentchain_header: ParentchainBlock::Header,
 	pub(crate) shard: ShardIdentifierFor<SignedSidechainBlock>,
 	pub(crate) _phantom: PhantomData<ParentchainBlock>,
@@ -97,6 +103,13 @@ where
 		}
 
 		// 2) Execute trusted calls.
+		let execution_limits = BlockLimits {
+			max_encoded_size: self
+				.block_limits
+				.max_encoded_size
+				.saturating_sub(BLOCK_ENCODING_OVERHEAD),
+			..self.block_limits
+		};
 		let batch_execution_result = self
 			.stf_executor
 			.propose_state_update(
@@ -104,12 +117,14 @@ where
 				latest_parentchain_header,
 				&self.shard,
 				max_duration,
+				&execution_limits,
 				|s| {
 					let mut sidechain_db = SidechainDB::<
 						SignedSidechainBlock::Block,
 						ExternalitiesFor<StfExecutor>,
 					>::new(s);
 					sidechain_db.reset_events();
+					sidechain_db.clear_last_block_import_records();
 					sidechain_db
 						.set_block_number(&sidechain_db.get_block_number().map_or(1, |n| n + 1));
 					sidechain_db.set_timestamp(&now_as_u64());
@@ -149,6 +164,15 @@ where
 			)
 			.map_err(|e| ConsensusError::Other(e.to_string().into()))?;
 
+		let encoded_block_size = sidechain_block.encoded_size();
+		if encoded_block_size > self.block_limits.max_encoded_size {
+			error!(
+				"Proposed sidechain block exceeds the maximum encoded size ({} > {} bytes)",
+				encoded_block_size, self.block_limits.max_encoded_size
+			);
+			return Err(ConsensusError::CannotPropose)
+		}
+
 		info!(
 			"Queue/Timeslot/Transactions: {:?};{};{}",
 			trusted_calls.len(),
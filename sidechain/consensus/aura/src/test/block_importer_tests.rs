@@ -15,7 +15,11 @@
 
 */
 
-use crate::{block_importer::BlockImporter, test::fixtures::validateer, ShardIdentifierFor};
+use crate::{
+	block_importer::BlockImporter,
+	test::{fixtures::validateer, mocks::report_equivocation_mock::ReportEquivocationMock},
+	ShardIdentifierFor,
+};
 use codec::Encode;
 use core::assert_matches::assert_matches;
 use itc_parentchain_block_import_dispatcher::trigger_parentchain_block_import_mock::TriggerParentchainBlockImportMock;
@@ -30,13 +34,16 @@ use itp_test::mock::{handle_state_mock::HandleStateMock, onchain_mock::OnchainMo
 use itp_time_utils::{duration_now, now_as_u64};
 use itp_top_pool_author::mocks::AuthorApiMock;
 use itp_types::{Block as ParentchainBlock, Header as ParentchainHeader, H256};
-use its_consensus_common::{BlockImport, Error as ConsensusError};
+use its_consensus_common::{BlockImport, Error as ConsensusError, FinalityGadget};
 use its_primitives::{
 	traits::{SignBlock, SignedBlock},
-	types::{Block as SidechainBlock, SignedBlock as SignedSidechainBlock},
+	types::{
+		header::SidechainHeader, Block as SidechainBlock, SignedBlock as SignedSidechainBlock,
+	},
 };
 use its_state::{SidechainDB, SidechainState, StateUpdate};
 use its_test::{
+	notify_finalized_blocks_mock::NotifyFinalizedBlocksMock, notify_heads_mock::NotifyHeadsMock,
 	sidechain_block_builder::SidechainBlockBuilder,
 	sidechain_block_data_builder::SidechainBlockDataBuilder,
 	sidechain_header_builder::SidechainHeaderBuilder,
@@ -51,6 +58,9 @@ type TestTopPoolAuthor = AuthorApiMock<H256, H256>;
 type TestParentchainBlockImportTrigger =
 	TriggerParentchainBlockImportMock<SignedParentchainBlock<ParentchainBlock>>;
 type TestStateKeyRepo = KeyRepositoryMock<Aes>;
+type TestHeadNotifier = NotifyHeadsMock<SidechainHeader>;
+type TestFinalityGadget =
+	FinalityGadget<ParentchainHeader, OnchainMock, OnchainMock, NotifyFinalizedBlocksMock<H256>>;
 type TestBlockImporter = BlockImporter<
 	Pair,
 	ParentchainBlock,
@@ -61,6 +71,9 @@ type TestBlockImporter = BlockImporter<
 	TestStateKeyRepo,
 	TestTopPoolAuthor,
 	TestParentchainBlockImportTrigger,
+	TestHeadNotifier,
+	TestFinalityGadget,
+	ReportEquivocationMock,
 >;
 
 fn state_key() -> Aes {
@@ -78,6 +91,32 @@ fn default_authority() -> Pair {
 fn test_fixtures(
 	parentchain_header: &ParentchainHeader,
 	parentchain_block_import_trigger: Arc<TestParentchainBlockImportTrigger>,
+) -> (TestBlockImporter, Arc<HandleStateMock>, Arc<TestTopPoolAuthor>) {
+	test_fixtures_with_head_notifier(
+		parentchain_header,
+		parentchain_block_import_trigger,
+		Arc::new(TestHeadNotifier::default()),
+	)
+}
+
+fn test_fixtures_with_head_notifier(
+	parentchain_header: &ParentchainHeader,
+	parentchain_block_import_trigger: Arc<TestParentchainBlockImportTrigger>,
+	head_notifier: Arc<TestHeadNotifier>,
+) -> (TestBlockImporter, Arc<HandleStateMock>, Arc<TestTopPoolAuthor>) {
+	test_fixtures_with_notifiers(
+		parentchain_header,
+		parentchain_block_import_trigger,
+		head_notifier,
+		Arc::new(ReportEquivocationMock::default()),
+	)
+}
+
+fn test_fixtures_with_notifiers(
+	parentchain_header: &ParentchainHeader,
+	parentchain_block_import_trigger: Arc<TestParentchainBlockImportTrigger>,
+	head_notifier: Arc<TestHeadNotifier>,
+	equivocation_reporter: Arc<ReportEquivocationMock>,
 ) -> (TestBlockImporter, Arc<HandleStateMock>, Arc<TestTopPoolAuthor>) {
 	let state_handler = Arc::new(HandleStateMock::from_shard(shard()).unwrap());
 	let top_pool_author = Arc::new(TestTopPoolAuthor::default());
@@ -86,6 +125,12 @@ fn test_fixtures(
 		Some(vec![validateer(Keyring::Alice.public().into())]),
 	));
 	let state_key_repository = Arc::new(TestStateKeyRepo::new(state_key()));
+	let finality_gadget = Arc::new(TestFinalityGadget::new(
+		default_authority(),
+		ocall_api.clone(),
+		ocall_api.clone(),
+		Arc::new(NotifyFinalizedBlocksMock::default()),
+	));
 
 	let block_importer = TestBlockImporter::new(
 		state_handler.clone(),
@@ -93,6 +138,9 @@ fn test_fixtures(
 		top_pool_author.clone(),
 		parentchain_block_import_trigger,
 		ocall_api,
+		head_notifier,
+		finality_gadget,
+		equivocation_reporter,
 	);
 
 	(block_importer, state_handler, top_pool_author)
@@ -160,6 +208,43 @@ fn simple_block_import_works() {
 		.unwrap();
 }
 
+#[test]
+fn imported_block_is_final_if_we_are_the_only_validateer() {
+	let parentchain_header = ParentchainHeaderBuilder::default().build();
+	let (block_importer, state_handler, _) =
+		test_fixtures_with_default_import_trigger(&parentchain_header);
+	let signed_sidechain_block =
+		default_authority_signed_block(&parentchain_header, state_handler.as_ref());
+	let block_number = signed_sidechain_block.block.header.block_number;
+
+	block_importer
+		.import_block(signed_sidechain_block, &parentchain_header)
+		.unwrap();
+
+	assert_eq!(Some(block_number), block_importer.last_finalized_block_number(&shard()));
+}
+
+#[test]
+fn block_import_checks_block_for_equivocation() {
+	let parentchain_header = ParentchainHeaderBuilder::default().build();
+	let equivocation_reporter = Arc::new(ReportEquivocationMock::default());
+	let (block_importer, state_handler, _) = test_fixtures_with_notifiers(
+		&parentchain_header,
+		Arc::new(TestParentchainBlockImportTrigger::default()),
+		Arc::new(TestHeadNotifier::default()),
+		equivocation_reporter.clone(),
+	);
+	let signed_sidechain_block =
+		default_authority_signed_block(&parentchain_header, state_handler.as_ref());
+	let block_hash = signed_sidechain_block.hash();
+
+	block_importer
+		.import_block(signed_sidechain_block, &parentchain_header)
+		.unwrap();
+
+	assert_eq!(vec![block_hash], equivocation_reporter.checked_blocks());
+}
+
 #[test]
 fn block_import_with_invalid_signature_fails() {
 	let parentchain_header = ParentchainHeaderBuilder::default().build();
@@ -226,6 +311,23 @@ fn cleanup_removes_tops_from_pool() {
 	assert_eq!(2, *top_pool_author.remove_attempts.read().unwrap());
 }
 
+#[test]
+fn cleanup_notifies_new_head() {
+	let parentchain_header = ParentchainHeaderBuilder::default().build();
+	let head_notifier = Arc::new(TestHeadNotifier::default());
+	let (block_importer, state_handler, _) = test_fixtures_with_head_notifier(
+		&parentchain_header,
+		Arc::new(TestParentchainBlockImportTrigger::default()),
+		head_notifier.clone(),
+	);
+	let signed_sidechain_block =
+		default_authority_signed_block(&parentchain_header, state_handler.as_ref());
+
+	block_importer.cleanup(&signed_sidechain_block).unwrap();
+
+	assert_eq!(vec![signed_sidechain_block.block.header], head_notifier.notified_heads());
+}
+
 #[test]
 fn sidechain_block_import_triggers_parentchain_block_import() {
 	let previous_parentchain_header = ParentchainHeaderBuilder::default().with_number(4).build();
@@ -308,3 +410,80 @@ fn peek_parentchain_block_returns_error_if_no_corresponding_block_can_be_found()
 
 	assert_matches!(peek_result, Err(ConsensusError::Other(_)));
 }
+
+#[test]
+fn revert_block_restores_state_before_import() {
+	let parentchain_header = ParentchainHeaderBuilder::default().build();
+	let (block_importer, state_handler, _) =
+		test_fixtures_with_default_import_trigger(&parentchain_header);
+	let state_hash_before_import = state_handler.latest_state_hash(&shard()).unwrap();
+	let signed_sidechain_block =
+		default_authority_signed_block(&parentchain_header, state_handler.as_ref());
+
+	block_importer
+		.import_block(signed_sidechain_block.clone(), &parentchain_header)
+		.unwrap();
+	assert_eq!(
+		Some(signed_sidechain_block.block.clone()),
+		block_importer.get_last_imported_block(&shard()).unwrap()
+	);
+
+	block_importer.revert_block(&shard(), &signed_sidechain_block.hash()).unwrap();
+
+	assert_eq!(state_hash_before_import, state_handler.latest_state_hash(&shard()).unwrap());
+	assert!(block_importer.get_last_imported_block(&shard()).unwrap().is_none());
+}
+
+#[test]
+fn revert_block_puts_operations_of_block_back_into_top_pool() {
+	let parentchain_header = ParentchainHeaderBuilder::default().build();
+	let (block_importer, state_handler, top_pool_author) =
+		test_fixtures_with_default_import_trigger(&parentchain_header);
+	let signed_sidechain_block =
+		default_authority_signed_block(&parentchain_header, state_handler.as_ref());
+
+	block_importer
+		.import_block(signed_sidechain_block.clone(), &parentchain_header)
+		.unwrap();
+	block_importer.revert_block(&shard(), &signed_sidechain_block.hash()).unwrap();
+
+	assert_eq!(
+		*top_pool_author.retracted_blocks.read().unwrap(),
+		vec![signed_sidechain_block.hash()]
+	);
+}
+
+#[test]
+fn restore_reverted_block_undoes_revert() {
+	let parentchain_header = ParentchainHeaderBuilder::default().build();
+	let (block_importer, state_handler, _) =
+		test_fixtures_with_default_import_trigger(&parentchain_header);
+	let signed_sidechain_block =
+		default_authority_signed_block(&parentchain_header, state_handler.as_ref());
+
+	block_importer
+		.import_block(signed_sidechain_block.clone(), &parentchain_header)
+		.unwrap();
+	let reverted_block =
+		block_importer.revert_block(&shard(), &signed_sidechain_block.hash()).unwrap();
+	block_importer.restore_reverted_block(&shard(), reverted_block).unwrap();
+
+	assert_eq!(
+		Some(signed_sidechain_block.block.clone()),
+		block_importer.get_last_imported_block(&shard()).unwrap()
+	);
+	// The restored block can be reverted again.
+	block_importer.revert_block(&shard(), &signed_sidechain_block.hash()).unwrap();
+	assert!(block_importer.get_last_imported_block(&shard()).unwrap().is_none());
+}
+
+#[test]
+fn revert_block_fails_for_unknown_block() {
+	let parentchain_header = ParentchainHeaderBuilder::default().build();
+	let (block_importer, _, _) = test_fixtures_with_default_import_trigger(&parentchain_header);
+
+	assert_matches!(
+		block_importer.revert_block(&shard(), &H256::random()),
+		Err(ConsensusError::CannotRevertBlock(_))
+	);
+}
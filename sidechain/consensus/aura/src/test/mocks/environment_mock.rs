@@ -24,6 +24,7 @@ use its_consensus_common::Environment;
 use its_primitives::types::block::SignedBlock as SignedSidechainBlock;
 
 /// Mock proposer environment.
+#[derive(Clone)]
 pub struct EnvironmentMock;
 
 impl Environment<ParentchainBlock, SignedSidechainBlock> for EnvironmentMock {
@@ -33,8 +34,8 @@ impl Environment<ParentchainBlock, SignedSidechainBlock> for EnvironmentMock {
 	fn init(
 		&mut self,
 		header: Header,
-		_: ShardIdentifierFor<SignedSidechainBlock>,
+		shard: ShardIdentifierFor<SignedSidechainBlock>,
 	) -> Result<Self::Proposer, Self::Error> {
-		Ok(ProposerMock { parentchain_header: header })
+		Ok(ProposerMock { parentchain_header: header, shard })
 	}
 }
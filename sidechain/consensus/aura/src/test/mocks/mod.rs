@@ -17,3 +17,4 @@
 
 pub mod environment_mock;
 pub mod proposer_mock;
+pub mod report_equivocation_mock;
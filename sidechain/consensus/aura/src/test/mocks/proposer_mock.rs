@@ -16,17 +16,19 @@
 */
 
 use crate::ConsensusError;
-use itp_types::{Block as ParentchainBlock, Header};
+use itp_types::{Block as ParentchainBlock, Header, ShardIdentifier};
 use its_consensus_common::{Proposal, Proposer};
 use its_primitives::types::block::SignedBlock as SignedSidechainBlock;
 use its_test::{
 	sidechain_block_builder::SidechainBlockBuilder,
 	sidechain_block_data_builder::SidechainBlockDataBuilder,
+	sidechain_header_builder::SidechainHeaderBuilder,
 };
 use std::time::Duration;
 
 pub struct ProposerMock {
 	pub(crate) parentchain_header: Header,
+	pub(crate) shard: ShardIdentifier,
 }
 
 impl Proposer<ParentchainBlock, SignedSidechainBlock> for ProposerMock {
@@ -39,7 +41,11 @@ impl Proposer<ParentchainBlock, SignedSidechainBlock> for ProposerMock {
 				let block_data = SidechainBlockDataBuilder::random()
 					.with_layer_one_head(self.parentchain_header.hash())
 					.build();
-				SidechainBlockBuilder::random().with_block_data(block_data).build_signed()
+				let header = SidechainHeaderBuilder::random().with_shard(self.shard).build();
+				SidechainBlockBuilder::random()
+					.with_header(header)
+					.with_block_data(block_data)
+					.build_signed()
 			},
 
 			parentchain_effects: Default::default(),
@@ -0,0 +1,46 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use its_consensus_common::{ReportEquivocation, Result};
+use its_primitives::{traits::SignedBlock, types::BlockHash};
+use std::{sync::RwLock, time::Duration};
+
+/// Mock that records the hashes of all blocks checked for equivocation.
+#[derive(Default)]
+pub struct ReportEquivocationMock {
+	checked_blocks: RwLock<Vec<BlockHash>>,
+}
+
+impl ReportEquivocationMock {
+	pub fn checked_blocks(&self) -> Vec<BlockHash> {
+		self.checked_blocks.read().unwrap().clone()
+	}
+}
+
+impl<SignedSidechainBlock: SignedBlock> ReportEquivocation<SignedSidechainBlock>
+	for ReportEquivocationMock
+{
+	fn check_and_report(
+		&self,
+		signed_block: &SignedSidechainBlock,
+		_slot_duration: Duration,
+		_authorities: &[SignedSidechainBlock::Public],
+	) -> Result<()> {
+		self.checked_blocks.write().unwrap().push(signed_block.hash());
+		Ok(())
+	}
+}
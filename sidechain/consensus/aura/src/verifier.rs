@@ -15,12 +15,15 @@
 
 */
 
-use crate::{authorities, EnclaveOnChainOCallApi};
+use crate::{authorities, slot_duration_at, EnclaveOnChainOCallApi};
+use codec::Encode;
 use core::marker::PhantomData;
+use frame_support::ensure;
+use itp_types::BlockLimits;
 use its_block_verification::verify_sidechain_block;
 use its_consensus_common::{Error as ConsensusError, Verifier};
 use its_primitives::{
-	traits::{Block as SidechainBlockTrait, SignedBlock as SignedSidechainBlockTrait},
+	traits::{Block as SidechainBlockTrait, BlockData, SignedBlock as SignedSidechainBlockTrait},
 	types::block::BlockHash,
 };
 use its_state::LastBlockExt;
@@ -30,7 +33,10 @@ use std::{fmt::Debug, time::Duration};
 
 #[derive(Default)]
 pub struct AuraVerifier<AuthorityPair, ParentchainBlock, SidechainBlock, SidechainState, Context> {
-	slot_duration: Duration,
+	/// Applies unless a slot duration is configured on the parentchain.
+	default_slot_duration: Duration,
+	backup_slot_portion: Option<f32>,
+	block_limits: Option<BlockLimits>,
 	sidechain_state: SidechainState,
 	_phantom: PhantomData<(AuthorityPair, ParentchainBlock, SidechainBlock, Context)>,
 }
@@ -38,8 +44,27 @@ pub struct AuraVerifier<AuthorityPair, ParentchainBlock, SidechainBlock, Sidecha
 impl<AuthorityPair, ParentchainBlock, SidechainBlock, SidechainState, Context>
 	AuraVerifier<AuthorityPair, ParentchainBlock, SidechainBlock, SidechainState, Context>
 {
-	pub fn new(slot_duration: Duration, sidechain_state: SidechainState) -> Self {
-		Self { slot_duration, sidechain_state, _phantom: Default::default() }
+	pub fn new(default_slot_duration: Duration, sidechain_state: SidechainState) -> Self {
+		Self {
+			default_slot_duration,
+			backup_slot_portion: None,
+			block_limits: None,
+			sidechain_state,
+			_phantom: Default::default(),
+		}
+	}
+
+	/// Accept blocks of backup authors, who claim a slot after `backup_slot_portion` of
+	/// the slot duration per rank has passed without a block of the slot author.
+	pub fn with_backup_slot_portion(mut self, backup_slot_portion: f32) -> Self {
+		self.backup_slot_portion = Some(backup_slot_portion);
+		self
+	}
+
+	/// Reject blocks that exceed the `block_limits`.
+	pub fn with_block_limits(mut self, block_limits: BlockLimits) -> Self {
+		self.block_limits = Some(block_limits);
+		self
 	}
 }
 
@@ -66,15 +91,108 @@ where
 		parentchain_header: &ParentchainBlock::Header,
 		ctx: &Self::Context,
 	) -> Result<Self::BlockImportParams, ConsensusError> {
+		if let Some(block_limits) = &self.block_limits {
+			verify_block_limits(&signed_block, block_limits)?;
+		}
+
 		let authorities =
 			authorities::<_, AuthorityPair, ParentchainBlock::Header>(ctx, parentchain_header)?;
+		// The block's parentchain header determines the slot duration, like it determines the authorities.
+		let slot_duration = slot_duration_at(ctx, parentchain_header, self.default_slot_duration)?;
 
 		Ok(verify_sidechain_block::<AuthorityPair, ParentchainBlock, SignedSidechainBlock>(
 			signed_block,
-			self.slot_duration,
+			slot_duration,
+			self.backup_slot_portion,
 			&self.sidechain_state.get_last_block(),
 			parentchain_header,
 			&authorities,
 		)?)
 	}
 }
+
+/// Verify that a block does not exceed the block limits.
+///
+/// The dispatch weight of the trusted calls can not be verified, because a block only contains
+/// the hashes of its trusted operations. It is enforced by the proposer only.
+fn verify_block_limits<SignedSidechainBlock: SignedSidechainBlockTrait>(
+	signed_block: &SignedSidechainBlock,
+	block_limits: &BlockLimits,
+) -> Result<(), ConsensusError> {
+	let block = signed_block.block();
+
+	let number_of_operations = block.block_data().signed_top_hashes().len();
+	ensure!(
+		number_of_operations <= block_limits.max_trusted_operations,
+		ConsensusError::BadSidechainBlock(
+			block.hash(),
+			format!(
+				"Block contains {} trusted operations, the maximum is {}",
+				number_of_operations, block_limits.max_trusted_operations
+			)
+		)
+	);
+
+	let encoded_size = signed_block.encoded_size();
+	ensure!(
+		encoded_size <= block_limits.max_encoded_size,
+		ConsensusError::BadSidechainBlock(
+			block.hash(),
+			format!(
+				"Encoded block size is {} bytes, the maximum is {}",
+				encoded_size, block_limits.max_encoded_size
+			)
+		)
+	);
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use core::assert_matches::assert_matches;
+	use itp_types::H256;
+	use its_primitives::types::block::SignedBlock;
+	use its_test::{
+		sidechain_block_builder::SidechainBlockBuilder,
+		sidechain_block_data_builder::SidechainBlockDataBuilder,
+	};
+
+	fn block_with_top_hashes(number_of_top_hashes: u64) -> SignedBlock {
+		let block_data = SidechainBlockDataBuilder::default()
+			.with_signed_top_hashes((0..number_of_top_hashes).map(H256::from_low_u64_be).collect())
+			.build();
+		SidechainBlockBuilder::default().with_block_data(block_data).build_signed()
+	}
+
+	#[test]
+	fn block_within_limits_is_accepted() {
+		let signed_block = block_with_top_hashes(2);
+		let block_limits = BlockLimits::new(2, signed_block.encoded_size(), 0);
+
+		assert!(verify_block_limits(&signed_block, &block_limits).is_ok());
+	}
+
+	#[test]
+	fn block_with_too_many_trusted_operations_is_rejected() {
+		let signed_block = block_with_top_hashes(3);
+		let block_limits = BlockLimits::new(2, usize::MAX, u64::MAX);
+
+		assert_matches!(
+			verify_block_limits(&signed_block, &block_limits),
+			Err(ConsensusError::BadSidechainBlock(_, _))
+		);
+	}
+
+	#[test]
+	fn block_exceeding_max_encoded_size_is_rejected() {
+		let signed_block = block_with_top_hashes(2);
+		let block_limits = BlockLimits::new(usize::MAX, signed_block.encoded_size() - 1, u64::MAX);
+
+		assert_matches!(
+			verify_block_limits(&signed_block, &block_limits),
+			Err(ConsensusError::BadSidechainBlock(_, _))
+		);
+	}
+}
@@ -21,11 +21,14 @@ use crate::{Error, Verifier};
 use codec::Decode;
 use itp_ocall_api::EnclaveSidechainOCallApi;
 use itp_sgx_crypto::StateCrypto;
-use its_primitives::traits::{
-	Block as SidechainBlockTrait, BlockData, Header as HeaderTrait, ShardIdentifierFor,
-	SignedBlock as SignedSidechainBlockTrait,
+use its_primitives::{
+	traits::{
+		Block as SidechainBlockTrait, BlockData, Header as HeaderTrait, ShardIdentifierFor,
+		SignedBlock as SignedSidechainBlockTrait,
+	},
+	types::{BlockHash, BlockNumber},
 };
-use its_state::{LastBlockExt, SidechainState};
+use its_state::{LastBlockImportExt, SidechainState};
 use log::*;
 use sp_runtime::traits::Block as ParentchainBlockTrait;
 use std::{time::Instant, vec::Vec};
@@ -44,7 +47,7 @@ where
 	>;
 
 	/// Context needed to derive verifier relevant data.
-	type SidechainState: SidechainState + LastBlockExt<SignedSidechainBlock::Block>;
+	type SidechainState: SidechainState + LastBlockImportExt<SignedSidechainBlock::Block>;
 
 	/// Provides the cryptographic functions for our the state encryption.
 	type StateCrypto: StateCrypto;
@@ -52,6 +55,9 @@ where
 	/// Context needed to derive verifier relevant data.
 	type Context: EnclaveSidechainOCallApi;
 
+	/// What is needed to undo the revert of a block, see [`BlockImport::revert_block`].
+	type RevertedBlock;
+
 	/// Get a verifier instance.
 	fn verifier(&self, state: Self::SidechainState) -> Self::Verifier;
 
@@ -99,6 +105,56 @@ where
 		sidechain_block: &SignedSidechainBlock::Block,
 		last_imported_parentchain_header: &ParentchainBlock::Header,
 	) -> Result<ParentchainBlock::Header, Error>;
+	/// Get the last sidechain block that was imported into the state of a shard.
+	fn get_last_imported_block(
+		&self,
+		shard: &ShardIdentifierFor<SignedSidechainBlock>,
+	) -> Result<Option<SignedSidechainBlock::Block>, Error>;
+
+	/// Revert the state of a shard to the state it had before the given block was imported.
+	///
+	/// Used to reorganize onto a competing fork. Only possible for recently imported blocks,
+	/// whose preceding state version is still available, fails with
+	/// [`Error::CannotRevertBlock`] otherwise.
+	fn revert_block(
+		&self,
+		shard: &ShardIdentifierFor<SignedSidechainBlock>,
+		block_hash: &BlockHash,
+	) -> Result<Self::RevertedBlock, Error>;
+
+	/// Undo the revert of a block, in case the competing fork could not be imported.
+	///
+	/// Restores the state the shard had before [`BlockImport::revert_block`], discarding any
+	/// block of the fork imported in between.
+	fn restore_reverted_block(
+		&self,
+		shard: &ShardIdentifierFor<SignedSidechainBlock>,
+		reverted_block: Self::RevertedBlock,
+	) -> Result<(), Error>;
+
+	/// Vote for the finality of a block that was just imported and stored.
+	fn vote_for_finality(
+		&self,
+		sidechain_block: &SignedSidechainBlock::Block,
+		parentchain_header: &ParentchainBlock::Header,
+	) -> Result<(), Error>;
+
+	/// Get the number of the last finalized block of a shard, if any block of it is final yet.
+	fn last_finalized_block_number(
+		&self,
+		shard: &ShardIdentifierFor<SignedSidechainBlock>,
+	) -> Option<BlockNumber>;
+
+	/// Check whether the author of the block signed a different block for the same slot and
+	/// shard before, and report the equivocation if so.
+	///
+	/// The `parentchain_header` is the one the sidechain block refers to.
+	fn report_equivocation(
+		&self,
+		signed_sidechain_block: &SignedSidechainBlock,
+		parentchain_header: &ParentchainBlock::Header,
+	) -> Result<(), Error>;
+
 	/// Cleanup task after import is done.
 	fn cleanup(&self, signed_sidechain_block: &SignedSidechainBlock) -> Result<(), Error>;
 
@@ -128,6 +184,13 @@ where
 					parentchain_header.clone()
 				});
 
+		// Checked before verification, the conflicting block is usually rejected by it.
+		if let Err(e) =
+			self.report_equivocation(&signed_sidechain_block, &peeked_parentchain_header)
+		{
+			warn!("Failed to check sidechain block {} for equivocation: {:?}", block_number, e);
+		}
+
 		let block_import_params = self.verify_import(&shard, |state| {
 			let verifier = self.verifier(state);
 			verifier.verify(
@@ -157,7 +220,10 @@ where
 
 			state.apply_state_update(&update).map_err(|e| Error::Other(e.into()))?;
 
-			state.set_last_block(block_import_params.block());
+			state.set_last_imported_block(
+				block_import_params.block(),
+				block_import_params.signature(),
+			);
 
 			Ok(state)
 		})?;
@@ -172,6 +238,11 @@ where
 		// Store block in storage.
 		self.get_context().store_sidechain_blocks(vec![signed_sidechain_block])?;
 
+		// Vote only once the block is stored, our vote might already finalize it.
+		if let Err(e) = self.vote_for_finality(&sidechain_block, &latest_parentchain_header) {
+			warn!("Failed to vote for finality of sidechain block {}: {:?}", block_number, e);
+		}
+
 		info!("Importing block {} took {} ms", block_number, start_time.elapsed().as_millis());
 
 		Ok(latest_parentchain_header)
@@ -24,15 +24,19 @@ use std::sync::SgxRwLock as RwLock;
 use std::sync::RwLock;
 
 use crate::error::{Error, Result};
+use itp_types::{BlockProductionStatus, ShardIdentifier};
 use log::*;
+use std::collections::BTreeSet;
 
 /// Trait to suspend the production of sidechain blocks.
 pub trait SuspendBlockProduction {
 	/// Suspend any sidechain block production.
 	fn suspend_for_sync(&self) -> Result<()>;
 
-	/// Resume block sidechain block production.
-	fn resume(&self) -> Result<()>;
+	/// Resume the sidechain block production suspended for the sync.
+	///
+	/// Shards suspended by the admin stay suspended.
+	fn resume_after_sync(&self) -> Result<()>;
 }
 
 /// Trait to query if sidechain block production is suspended.
@@ -42,11 +46,38 @@ pub trait IsBlockProductionSuspended {
 	fn is_sync_ongoing(&self) -> Result<bool>;
 }
 
+/// Trait to suspend and resume the sidechain block production of a single shard,
+/// e.g. by the operator of a validateer for maintenance.
+pub trait SuspendShardBlockProduction {
+	fn suspend_shard(&self, shard: &ShardIdentifier) -> Result<()>;
+
+	fn resume_shard(&self, shard: &ShardIdentifier) -> Result<()>;
+
+	/// Use up the nonce of an admin request. Fails unless it is the next admin nonce,
+	/// so that a signed admin request can not be replayed.
+	fn use_admin_nonce(&self, nonce: u64) -> Result<()>;
+}
+
+/// Trait to query if the sidechain block production of a single shard is suspended.
+pub trait IsShardBlockProductionSuspended {
+	/// Block production of a shard is suspended if the shard itself or any block production is suspended.
+	fn is_shard_suspended(&self, shard: &ShardIdentifier) -> Result<bool>;
+
+	fn shard_status(&self, shard: &ShardIdentifier) -> Result<BlockProductionStatus>;
+}
+
 /// Implementation for suspending and resuming sidechain block production.
+///
+/// The suspension for the sync and the suspension of single shards by the admin are independent
+/// of each other. Both are kept in memory only: after a restart of the validateer, the block
+/// production of all shards resumes and the admin nonce starts at 0 again. Admin requests signed
+/// before the restart can therefore be replayed until their timestamp expires.
 #[derive(Default)]
 pub struct BlockProductionSuspender {
 	is_suspended: RwLock<bool>,
 	sync_is_ongoing: RwLock<bool>,
+	suspended_shards: RwLock<BTreeSet<ShardIdentifier>>,
+	next_admin_nonce: RwLock<u64>,
 }
 
 impl BlockProductionSuspender {
@@ -54,6 +85,8 @@ impl BlockProductionSuspender {
 		BlockProductionSuspender {
 			is_suspended: RwLock::new(is_suspended),
 			sync_is_ongoing: RwLock::new(false),
+			suspended_shards: Default::default(),
+			next_admin_nonce: Default::default(),
 		}
 	}
 }
@@ -71,9 +104,14 @@ impl SuspendBlockProduction for BlockProductionSuspender {
 		Ok(())
 	}
 
-	fn resume(&self) -> Result<()> {
+	fn resume_after_sync(&self) -> Result<()> {
 		let mut suspended_lock = self.is_suspended.write().map_err(|_| Error::LockPoisoning)?;
 		*suspended_lock = false;
+
+		let mut sync_is_ongoing_lock =
+			self.sync_is_ongoing.write().map_err(|_| Error::LockPoisoning)?;
+		*sync_is_ongoing_lock = false;
+
 		info!("Resume sidechain block production");
 		Ok(())
 	}
@@ -89,6 +127,53 @@ impl IsBlockProductionSuspended for BlockProductionSuspender {
 	}
 }
 
+impl SuspendShardBlockProduction for BlockProductionSuspender {
+	fn suspend_shard(&self, shard: &ShardIdentifier) -> Result<()> {
+		let mut suspended_shards_lock =
+			self.suspended_shards.write().map_err(|_| Error::LockPoisoning)?;
+		suspended_shards_lock.insert(*shard);
+
+		info!("Suspend sidechain block production of shard {:?}", shard);
+		Ok(())
+	}
+
+	fn resume_shard(&self, shard: &ShardIdentifier) -> Result<()> {
+		let mut suspended_shards_lock =
+			self.suspended_shards.write().map_err(|_| Error::LockPoisoning)?;
+		suspended_shards_lock.remove(shard);
+
+		info!("Resume sidechain block production of shard {:?}", shard);
+		Ok(())
+	}
+
+	fn use_admin_nonce(&self, nonce: u64) -> Result<()> {
+		let mut next_admin_nonce_lock =
+			self.next_admin_nonce.write().map_err(|_| Error::LockPoisoning)?;
+		if nonce != *next_admin_nonce_lock {
+			return Err(Error::InvalidAdminNonce(nonce, *next_admin_nonce_lock))
+		}
+		*next_admin_nonce_lock += 1;
+		Ok(())
+	}
+}
+
+impl IsShardBlockProductionSuspended for BlockProductionSuspender {
+	fn is_shard_suspended(&self, shard: &ShardIdentifier) -> Result<bool> {
+		Ok(self.is_suspended()? || self.shard_status(shard)?.is_suspended())
+	}
+
+	fn shard_status(&self, shard: &ShardIdentifier) -> Result<BlockProductionStatus> {
+		let suspended_by_admin =
+			self.suspended_shards.read().map_err(|_| Error::LockPoisoning)?.contains(shard);
+
+		Ok(BlockProductionStatus {
+			suspended_by_admin,
+			suspended_for_sync: self.is_sync_ongoing()?,
+			next_admin_nonce: *self.next_admin_nonce.read().map_err(|_| Error::LockPoisoning)?,
+		})
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -106,7 +191,80 @@ mod tests {
 		block_production_suspender.suspend_for_sync().unwrap();
 		assert!(block_production_suspender.is_suspended().unwrap());
 
-		block_production_suspender.resume().unwrap();
+		block_production_suspender.resume_after_sync().unwrap();
 		assert!(!block_production_suspender.is_suspended().unwrap());
 	}
+
+	#[test]
+	fn resuming_after_sync_keeps_shards_suspended_by_admin() {
+		let block_production_suspender = BlockProductionSuspender::default();
+		let shard = ShardIdentifier::from_low_u64_be(1);
+
+		block_production_suspender.suspend_shard(&shard).unwrap();
+		block_production_suspender.suspend_for_sync().unwrap();
+		block_production_suspender.resume_after_sync().unwrap();
+
+		assert!(block_production_suspender.is_shard_suspended(&shard).unwrap());
+		assert!(!block_production_suspender.shard_status(&shard).unwrap().suspended_for_sync);
+	}
+
+	#[test]
+	fn admin_nonce_can_only_be_used_once() {
+		let block_production_suspender = BlockProductionSuspender::default();
+
+		block_production_suspender.use_admin_nonce(0).unwrap();
+
+		assert!(block_production_suspender.use_admin_nonce(0).is_err());
+		assert!(block_production_suspender.use_admin_nonce(2).is_err());
+		block_production_suspender.use_admin_nonce(1).unwrap();
+		assert_eq!(
+			block_production_suspender
+				.shard_status(&ShardIdentifier::from_low_u64_be(1))
+				.unwrap()
+				.next_admin_nonce,
+			2
+		);
+	}
+
+	#[test]
+	fn suspending_a_shard_does_not_affect_other_shards() {
+		let block_production_suspender = BlockProductionSuspender::default();
+		let shard = ShardIdentifier::from_low_u64_be(1);
+		let other_shard = ShardIdentifier::from_low_u64_be(2);
+
+		block_production_suspender.suspend_shard(&shard).unwrap();
+
+		assert!(block_production_suspender.is_shard_suspended(&shard).unwrap());
+		assert!(!block_production_suspender.is_shard_suspended(&other_shard).unwrap());
+		assert!(!block_production_suspender.is_suspended().unwrap());
+	}
+
+	#[test]
+	fn resuming_a_shard_works() {
+		let block_production_suspender = BlockProductionSuspender::default();
+		let shard = ShardIdentifier::from_low_u64_be(1);
+
+		block_production_suspender.suspend_shard(&shard).unwrap();
+		block_production_suspender.resume_shard(&shard).unwrap();
+
+		assert!(!block_production_suspender.is_shard_suspended(&shard).unwrap());
+	}
+
+	#[test]
+	fn all_shards_are_suspended_during_sync() {
+		let block_production_suspender = BlockProductionSuspender::default();
+		let shard = ShardIdentifier::from_low_u64_be(1);
+
+		block_production_suspender.suspend_for_sync().unwrap();
+
+		assert!(block_production_suspender.is_shard_suspended(&shard).unwrap());
+		assert_eq!(
+			block_production_suspender.shard_status(&shard).unwrap(),
+			BlockProductionStatus {
+				suspended_by_admin: false,
+				suspended_for_sync: true,
+				next_admin_nonce: 0
+			}
+		);
+	}
 }
@@ -0,0 +1,493 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Detection of authorities that sign two different blocks for the same slot and shard.
+//!
+//! Detected equivocations are reported to the parentchain, together with a proof
+//! containing both signed headers. Every validateer detects the equivocation, but only one
+//! of them sends the report, see [`designated_reporter`].
+
+#[cfg(feature = "sgx")]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use crate::error::{Error, Result};
+use codec::Encode;
+use core::time::Duration;
+use itc_parentchain_light_client::{
+	concurrent_access::ValidatorAccess, BlockNumberOps, ExtrinsicSender, NumberFor,
+};
+use itp_enclave_metrics::EnclaveMetric;
+use itp_extrinsics_factory::CreateExtrinsics;
+use itp_node_api_metadata::pallet_sidechain::SidechainCallIndexes;
+use itp_node_api_metadata_provider::AccessNodeMetadata;
+use itp_ocall_api::EnclaveMetricsOCallApi;
+use itp_types::{OpaqueCall, H256};
+use its_block_verification::slot::slot_from_timestamp_and_duration;
+use its_primitives::{
+	traits::{
+		Block as SidechainBlockTrait, BlockData, Header as HeaderTrait,
+		SignedBlock as SignedSidechainBlockTrait,
+	},
+	types::{
+		equivocation::{EquivocationProof, SignedHeader},
+		ShardIdentifier,
+	},
+};
+use log::*;
+use sp_core::{hexdisplay::HexDisplay, ByteArray};
+use sp_runtime::traits::Block as ParentchainBlockTrait;
+use std::{collections::BTreeMap, marker::PhantomData, sync::Arc};
+
+/// Number of slots we keep track of the seen blocks for.
+const SLOTS_TO_TRACK: u64 = 1024;
+
+type HeaderFor<SignedSidechainBlock> =
+	<<SignedSidechainBlock as SignedSidechainBlockTrait>::Block as SidechainBlockTrait>::HeaderType;
+
+type SignedHeaderFor<SignedSidechainBlock> = SignedHeader<
+	HeaderFor<SignedSidechainBlock>,
+	<SignedSidechainBlock as SignedSidechainBlockTrait>::Signature,
+>;
+
+pub type EquivocationProofFor<SignedSidechainBlock> = EquivocationProof<
+	HeaderFor<SignedSidechainBlock>,
+	<SignedSidechainBlock as SignedSidechainBlockTrait>::Public,
+	<SignedSidechainBlock as SignedSidechainBlockTrait>::Signature,
+>;
+
+/// Detects and reports equivocating authorities.
+pub trait ReportEquivocation<SignedSidechainBlock>: Send + Sync {
+	/// Check whether the author of the block already signed a different block for the same slot
+	/// and shard. If so, the equivocation is reported to the parentchain.
+	///
+	/// `slot_duration` is the slot duration in effect for the block and `authorities` are the
+	/// validateers at the parentchain block the sidechain block refers to.
+	fn check_and_report(
+		&self,
+		signed_block: &SignedSidechainBlock,
+		slot_duration: Duration,
+		authorities: &[SignedSidechainBlock::Public],
+	) -> Result<()>;
+}
+
+/// The authority that reports an equivocation of `offender` in `slot`: the first author of
+/// the following slots that is not the offender itself.
+///
+/// Having a single reporter keeps the parentchain from receiving a report of every validateer.
+pub fn designated_reporter<'a, Public: PartialEq>(
+	slot: u64,
+	authorities: &'a [Public],
+	offender: &Public,
+) -> Option<&'a Public> {
+	let number_of_authorities = authorities.len() as u64;
+	(1..=number_of_authorities)
+		.map(|i| &authorities[(slot.saturating_add(i) % number_of_authorities) as usize])
+		.find(|authority| *authority != offender)
+}
+
+/// Keeps track of the blocks seen per slot, shard and author.
+pub struct EquivocationDetector<SignedSidechainBlock>
+where
+	SignedSidechainBlock: SignedSidechainBlockTrait,
+{
+	slot_duration: Duration,
+	seen_blocks: BTreeMap<
+		u64,
+		BTreeMap<
+			(ShardIdentifier, SignedSidechainBlock::Public),
+			SignedHeaderFor<SignedSidechainBlock>,
+		>,
+	>,
+}
+
+impl<SignedSidechainBlock> EquivocationDetector<SignedSidechainBlock>
+where
+	SignedSidechainBlock: SignedSidechainBlockTrait,
+	SignedSidechainBlock::Public: Ord,
+	SignedSidechainBlock::Signature: Clone,
+	HeaderFor<SignedSidechainBlock>: HeaderTrait<ShardIdentifier = H256>,
+{
+	pub fn new(slot_duration: Duration) -> Self {
+		Self { slot_duration, seen_blocks: Default::default() }
+	}
+
+	/// Set the slot duration blocks are assigned to slots with.
+	///
+	/// Slot numbers of different slot durations are not comparable, so all seen blocks are
+	/// discarded when the slot duration changes.
+	pub fn set_slot_duration(&mut self, slot_duration: Duration) {
+		if slot_duration != self.slot_duration {
+			self.slot_duration = slot_duration;
+			self.seen_blocks.clear();
+		}
+	}
+
+	/// Record the block as seen. Returns a proof if its author already signed a different
+	/// block for the same slot and shard.
+	pub fn check_block(
+		&mut self,
+		signed_block: &SignedSidechainBlock,
+	) -> Option<EquivocationProofFor<SignedSidechainBlock>> {
+		let block = signed_block.block();
+		let slot = *slot_from_timestamp_and_duration(
+			Duration::from_millis(block.block_data().timestamp()),
+			self.slot_duration,
+		);
+		let shard = block.header().shard_id();
+		let author = block.block_data().block_author().clone();
+
+		let newest_slot = self.seen_blocks.keys().next_back().copied().unwrap_or_default();
+		if slot.saturating_add(SLOTS_TO_TRACK) <= newest_slot {
+			// Too old to be tracked, we might have pruned the other block already.
+			return None
+		}
+
+		let signed_header = SignedHeader {
+			header: block.header().clone(),
+			block_data: block.block_data().encode(),
+			signature: signed_block.signature().clone(),
+		};
+
+		let seen_in_slot = self.seen_blocks.entry(slot).or_default();
+		let maybe_proof = match seen_in_slot.get(&(shard, author.clone())) {
+			Some(first_header) if first_header.header.hash() != signed_header.header.hash() =>
+				Some(EquivocationProof {
+					offender: author,
+					slot,
+					shard,
+					first_header: first_header.clone(),
+					second_header: signed_header,
+				}),
+			Some(_) => None,
+			None => {
+				seen_in_slot.insert((shard, author), signed_header);
+				None
+			},
+		};
+
+		self.prune(slot);
+		maybe_proof
+	}
+
+	fn prune(&mut self, latest_slot: u64) {
+		let oldest_slot_to_keep = latest_slot.saturating_sub(SLOTS_TO_TRACK - 1);
+		self.seen_blocks = self.seen_blocks.split_off(&oldest_slot_to_keep);
+	}
+}
+
+/// Reports equivocations to the parentchain by extrinsic and counts them in a metric.
+pub struct EquivocationReporter<
+	ParentchainBlock,
+	SignedSidechainBlock,
+	NodeMetadataRepository,
+	ExtrinsicsFactory,
+	ValidatorAccessor,
+	MetricsOCallApi,
+> where
+	SignedSidechainBlock: SignedSidechainBlockTrait,
+{
+	detector: RwLock<EquivocationDetector<SignedSidechainBlock>>,
+	/// Our own authority key, to tell whether we are the designated reporter.
+	reporter: SignedSidechainBlock::Public,
+	metadata_repository: Arc<NodeMetadataRepository>,
+	extrinsics_factory: Arc<ExtrinsicsFactory>,
+	validator_accessor: Arc<ValidatorAccessor>,
+	metrics_ocall_api: Arc<MetricsOCallApi>,
+	_phantom: PhantomData<ParentchainBlock>,
+}
+
+impl<
+		ParentchainBlock,
+		SignedSidechainBlock,
+		NodeMetadataRepository,
+		ExtrinsicsFactory,
+		ValidatorAccessor,
+		MetricsOCallApi,
+	>
+	EquivocationReporter<
+		ParentchainBlock,
+		SignedSidechainBlock,
+		NodeMetadataRepository,
+		ExtrinsicsFactory,
+		ValidatorAccessor,
+		MetricsOCallApi,
+	> where
+	SignedSidechainBlock: SignedSidechainBlockTrait,
+	SignedSidechainBlock::Public: Ord,
+	SignedSidechainBlock::Signature: Clone,
+	HeaderFor<SignedSidechainBlock>: HeaderTrait<ShardIdentifier = H256>,
+{
+	pub fn new(
+		slot_duration: Duration,
+		reporter: SignedSidechainBlock::Public,
+		metadata_repository: Arc<NodeMetadataRepository>,
+		extrinsics_factory: Arc<ExtrinsicsFactory>,
+		validator_accessor: Arc<ValidatorAccessor>,
+		metrics_ocall_api: Arc<MetricsOCallApi>,
+	) -> Self {
+		Self {
+			detector: RwLock::new(EquivocationDetector::new(slot_duration)),
+			reporter,
+			metadata_repository,
+			extrinsics_factory,
+			validator_accessor,
+			metrics_ocall_api,
+			_phantom: Default::default(),
+		}
+	}
+}
+
+impl<
+		ParentchainBlock,
+		SignedSidechainBlock,
+		NodeMetadataRepository,
+		ExtrinsicsFactory,
+		ValidatorAccessor,
+		MetricsOCallApi,
+	> ReportEquivocation<SignedSidechainBlock>
+	for EquivocationReporter<
+		ParentchainBlock,
+		SignedSidechainBlock,
+		NodeMetadataRepository,
+		ExtrinsicsFactory,
+		ValidatorAccessor,
+		MetricsOCallApi,
+	> where
+	ParentchainBlock: ParentchainBlockTrait,
+	NumberFor<ParentchainBlock>: BlockNumberOps,
+	SignedSidechainBlock: SignedSidechainBlockTrait,
+	SignedSidechainBlock::Public: Ord,
+	SignedSidechainBlock::Signature: Clone + Send + Sync,
+	HeaderFor<SignedSidechainBlock>: HeaderTrait<ShardIdentifier = H256> + Send + Sync,
+	EquivocationProofFor<SignedSidechainBlock>: codec::Encode,
+	NodeMetadataRepository: AccessNodeMetadata + Send + Sync,
+	NodeMetadataRepository::MetadataType: SidechainCallIndexes,
+	ExtrinsicsFactory: CreateExtrinsics + Send + Sync,
+	ValidatorAccessor: ValidatorAccess<ParentchainBlock> + Send + Sync + 'static,
+	MetricsOCallApi: EnclaveMetricsOCallApi,
+{
+	fn check_and_report(
+		&self,
+		signed_block: &SignedSidechainBlock,
+		slot_duration: Duration,
+		authorities: &[SignedSidechainBlock::Public],
+	) -> Result<()> {
+		// Without a valid signature, the block does not prove anything about its author.
+		if !signed_block.verify_signature() {
+			return Ok(())
+		}
+
+		let maybe_proof = {
+			let mut detector = self.detector.write().map_err(|_| Error::LockPoisoning)?;
+			detector.set_slot_duration(slot_duration);
+			detector.check_block(signed_block)
+		};
+
+		let proof = match maybe_proof {
+			Some(proof) => proof,
+			None => return Ok(()),
+		};
+
+		let offender = format!("{}", HexDisplay::from(&proof.offender.as_slice()));
+		warn!(
+			"Authority {} signed two different blocks for slot {} of shard {:?}, reporting equivocation",
+			offender, proof.slot, proof.shard
+		);
+
+		if let Err(e) = self
+			.metrics_ocall_api
+			.update_metric(EnclaveMetric::SidechainEquivocationIncrement(offender))
+		{
+			warn!("Failed to update sidechain equivocation metric: {:?}", e);
+		}
+
+		if designated_reporter(proof.slot, authorities, &proof.offender) != Some(&self.reporter) {
+			debug!("Not the designated reporter of the equivocation, leaving the report to others");
+			return Ok(())
+		}
+
+		let call = self
+			.metadata_repository
+			.get_from_metadata(|m| m.report_equivocation_indexes())
+			.map_err(|e| Error::Other(e.into()))?
+			.map_err(|e| Error::Other(format!("{:?}", e).into()))?;
+
+		let opaque_call = OpaqueCall::from_tuple(&(call, proof));
+
+		let xts = self
+			.extrinsics_factory
+			.create_extrinsics(&[opaque_call], None)
+			.map_err(|e| Error::Other(e.into()))?;
+
+		debug!("Sending sidechain equivocation report extrinsic..");
+		self.validator_accessor
+			.execute_mut_on_validator(|v| v.send_extrinsics(xts))
+			.map_err(|e| Error::Other(e.into()))?;
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use its_primitives::types::block::SignedBlock;
+	use its_test::{
+		sidechain_block_builder::SidechainBlockBuilder,
+		sidechain_block_data_builder::SidechainBlockDataBuilder,
+		sidechain_header_builder::SidechainHeaderBuilder,
+	};
+	use sp_keyring::ed25519::Keyring;
+
+	const SLOT_DURATION: Duration = Duration::from_millis(300);
+
+	fn block(signer: Keyring, timestamp: u64, block_number: u64, shard: H256) -> SignedBlock {
+		let header = SidechainHeaderBuilder::default()
+			.with_block_number(block_number)
+			.with_shard(shard)
+			.build();
+		let block_data = SidechainBlockDataBuilder::default()
+			.with_signer(signer.pair())
+			.with_timestamp(timestamp)
+			.build();
+
+		SidechainBlockBuilder::default()
+			.with_header(header)
+			.with_block_data(block_data)
+			.with_signer(signer.pair())
+			.build_signed()
+	}
+
+	fn detector() -> EquivocationDetector<SignedBlock> {
+		EquivocationDetector::new(SLOT_DURATION)
+	}
+
+	#[test]
+	fn two_blocks_of_same_author_in_same_slot_are_detected() {
+		let mut detector = detector();
+		let first_block = block(Keyring::Alice, 310, 1, H256::zero());
+		let second_block = block(Keyring::Alice, 320, 2, H256::zero());
+
+		assert!(detector.check_block(&first_block).is_none());
+		let proof = detector.check_block(&second_block).unwrap();
+
+		assert_eq!(proof.offender, Keyring::Alice.public());
+		assert_eq!(proof.slot, 1);
+		assert_eq!(proof.shard, H256::zero());
+		assert_eq!(proof.first_header.header, first_block.block.header);
+		assert_eq!(proof.first_header.signature, first_block.signature);
+		assert_eq!(proof.first_header.block_data, first_block.block.block_data.encode());
+		assert_eq!(proof.second_header.header, second_block.block.header);
+		assert_eq!(proof.second_header.signature, second_block.signature);
+		assert!(proof.verify());
+	}
+
+	#[test]
+	fn same_block_seen_twice_is_no_equivocation() {
+		let mut detector = detector();
+		let block = block(Keyring::Alice, 310, 1, H256::zero());
+
+		assert!(detector.check_block(&block).is_none());
+		assert!(detector.check_block(&block).is_none());
+	}
+
+	#[test]
+	fn blocks_in_different_slots_shards_or_of_different_authors_are_no_equivocation() {
+		let mut detector = detector();
+
+		assert!(detector.check_block(&block(Keyring::Alice, 310, 1, H256::zero())).is_none());
+		assert!(detector.check_block(&block(Keyring::Alice, 610, 2, H256::zero())).is_none());
+		assert!(detector
+			.check_block(&block(Keyring::Alice, 320, 3, H256::from_low_u64_be(1)))
+			.is_none());
+		assert!(detector.check_block(&block(Keyring::Bob, 330, 4, H256::zero())).is_none());
+	}
+
+	#[test]
+	fn blocks_of_slots_that_are_no_longer_tracked_are_ignored() {
+		let mut detector = detector();
+		let old_timestamp = 310;
+		let recent_timestamp = old_timestamp + SLOT_DURATION.as_millis() as u64 * SLOTS_TO_TRACK;
+
+		assert!(detector
+			.check_block(&block(Keyring::Alice, old_timestamp, 1, H256::zero()))
+			.is_none());
+		assert!(detector
+			.check_block(&block(Keyring::Bob, recent_timestamp, 2, H256::zero()))
+			.is_none());
+		assert!(detector
+			.check_block(&block(Keyring::Alice, old_timestamp, 3, H256::zero()))
+			.is_none());
+		assert!(detector.seen_blocks.get(&1).is_none());
+	}
+
+	#[test]
+	fn detector_only_keeps_recent_slots() {
+		let mut detector = detector();
+
+		for slot in 0..(SLOTS_TO_TRACK + 10) {
+			let timestamp = slot * SLOT_DURATION.as_millis() as u64;
+			detector.check_block(&block(Keyring::Alice, timestamp, slot, H256::zero()));
+		}
+
+		assert_eq!(detector.seen_blocks.len() as u64, SLOTS_TO_TRACK);
+	}
+
+	#[test]
+	fn changing_slot_duration_discards_seen_blocks() {
+		let mut detector = detector();
+
+		assert!(detector.check_block(&block(Keyring::Alice, 310, 1, H256::zero())).is_none());
+
+		detector.set_slot_duration(SLOT_DURATION);
+		assert_eq!(detector.seen_blocks.len(), 1);
+
+		detector.set_slot_duration(SLOT_DURATION * 2);
+		assert!(detector.seen_blocks.is_empty());
+		assert!(detector.check_block(&block(Keyring::Alice, 320, 2, H256::zero())).is_none());
+		assert!(detector.check_block(&block(Keyring::Alice, 590, 3, H256::zero())).is_some());
+	}
+
+	#[test]
+	fn author_of_next_slot_is_designated_reporter() {
+		let authorities =
+			[Keyring::Alice.public(), Keyring::Bob.public(), Keyring::Charlie.public()];
+
+		assert_eq!(
+			Some(&Keyring::Charlie.public()),
+			designated_reporter(1, &authorities, &Keyring::Bob.public())
+		);
+	}
+
+	#[test]
+	fn offender_is_never_designated_reporter() {
+		let authorities = [Keyring::Alice.public(), Keyring::Bob.public()];
+
+		assert_eq!(
+			Some(&Keyring::Alice.public()),
+			designated_reporter(0, &authorities, &Keyring::Bob.public())
+		);
+		assert_eq!(None, designated_reporter(0, &authorities[..1], &Keyring::Alice.public()));
+		assert_eq!(
+			None,
+			designated_reporter::<sp_core::ed25519::Public>(0, &[], &Keyring::Alice.public())
+		);
+	}
+}
@@ -62,8 +62,16 @@ pub enum Error {
 	InvalidFirstBlock(BlockNumber, String),
 	#[error("Could not import block (number: {0}). A block with this number is already imported (current state block number: {1})")]
 	BlockAlreadyImported(BlockNumber, BlockNumber),
+	#[error("Cannot revert block {0}, the state before its import is not available")]
+	CannotRevertBlock(SidechainBlockHash),
 	#[error("Failed to pop from block import queue: {0}")]
 	FailedToPopBlockImportQueue(#[from] itp_block_import_queue::error::Error),
+	#[error("Invalid finality vote: {0}")]
+	InvalidFinalityVote(String),
+	#[error("Invalid admin nonce {0}, expected {1}")]
+	InvalidAdminNonce(u64, u64),
+	#[error("Failed to sync sidechain blocks from peers: {0}")]
+	PeerSyncFailed(String),
 	#[error("Verification Error: {0}")]
 	VerificationError(its_block_verification::error::Error),
 }
@@ -0,0 +1,643 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Finality gadget for sidechain blocks.
+//!
+//! Every validateer votes for the blocks it imports or produces and sends its signed vote to its
+//! peers. Once more than 2/3 of the validateer set voted for a block, the block (and with it all
+//! its ancestors) is final and can no longer be pruned or reverted.
+//!
+//! A validateer only finalizes blocks it imported itself. Votes for other blocks are kept until
+//! the block is imported, e.g. once we reorganized onto its fork or synced it from a peer.
+
+#[cfg(feature = "sgx")]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use crate::error::{Error, Result};
+use itp_ocall_api::EnclaveSidechainOCallApi;
+use itp_types::H256;
+use its_primitives::{
+	traits::NotifyFinalizedBlocks,
+	types::{
+		finality::{is_super_majority, FinalityVote, SignedFinalityVote},
+		BlockHash, BlockNumber, ShardIdentifier,
+	},
+};
+use its_validateer_fetch::ValidateerFetch;
+use log::*;
+use sp_core::{ed25519, ByteArray, Pair};
+use sp_runtime::traits::Header as ParentchainHeaderTrait;
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	marker::PhantomData,
+	string::ToString,
+	sync::Arc,
+	vec::Vec,
+};
+
+/// Maximum number of blocks per shard we keep track of while they are not final.
+const MAX_BLOCKS_AWAITING_FINALITY: usize = 512;
+
+/// Maximum number of votes per validateer and shard we keep while the blocks are not final.
+const MAX_PENDING_VOTES_PER_VALIDATEER: usize = MAX_BLOCKS_AWAITING_FINALITY;
+
+/// Votes for blocks further above our last own block than this are ignored.
+const MAX_VOTE_HEIGHT_ABOVE_HEAD: BlockNumber = 64;
+
+/// Casts, collects and tallies finality votes of the validateers.
+pub trait HandleFinalityVotes<ParentchainHeader>: Send + Sync {
+	/// Vote for a block we imported or produced and send the vote to our peers.
+	fn vote_for_block(
+		&self,
+		shard: ShardIdentifier,
+		block_number: BlockNumber,
+		block_hash: BlockHash,
+		parentchain_header: &ParentchainHeader,
+	) -> Result<()>;
+
+	/// Import a vote of another validateer.
+	///
+	/// The vote is checked against the validateer set at the given (latest imported) parentchain header.
+	fn import_vote(
+		&self,
+		signed_vote: SignedFinalityVote,
+		parentchain_header: &ParentchainHeader,
+	) -> Result<()>;
+
+	/// Number and hash of the last finalized block of a shard, if any.
+	fn last_finalized_block(&self, shard: &ShardIdentifier) -> Option<(BlockNumber, BlockHash)>;
+}
+
+/// Finality state of a single shard.
+#[derive(Default)]
+struct ShardFinality {
+	finalized_block: Option<(BlockNumber, BlockHash)>,
+	votes: BTreeMap<(BlockNumber, BlockHash), BTreeSet<ed25519::Public>>,
+	/// Blocks we imported or produced ourselves, used to notify about implicitly finalized ancestors.
+	own_blocks: BTreeMap<BlockNumber, BlockHash>,
+	/// Highest block number we voted for. We never vote twice for the same height, not even
+	/// for a block of a fork we reorganized onto.
+	last_voted_block_number: Option<BlockNumber>,
+}
+
+impl ShardFinality {
+	fn finalized_block_number(&self) -> Option<BlockNumber> {
+		self.finalized_block.map(|(number, _)| number)
+	}
+
+	fn is_final(&self, block_number: BlockNumber) -> bool {
+		self.finalized_block_number()
+			.map_or(false, |finalized| block_number <= finalized)
+	}
+
+	/// Number of the highest block we know of, i.e. our last own block or the finalized block.
+	fn head_block_number(&self) -> Option<BlockNumber> {
+		self.own_blocks.keys().next_back().copied().max(self.finalized_block_number())
+	}
+
+	fn has_voted_at(&self, block_number: BlockNumber) -> bool {
+		self.last_voted_block_number
+			.map_or(false, |last_voted| block_number <= last_voted)
+	}
+
+	/// Adds a vote and returns the hashes of all blocks that became final with it.
+	fn add_vote(&mut self, vote: FinalityVote, validateers: &[ed25519::Public]) -> Vec<BlockHash> {
+		if self.is_final(vote.block_number) {
+			return vec![]
+		}
+		if let Some(head) = self.head_block_number() {
+			if vote.block_number > head.saturating_add(MAX_VOTE_HEIGHT_ABOVE_HEAD) {
+				debug!(
+					"Ignoring vote for block {}, too far above our head (number: {})",
+					vote.block_number, head
+				);
+				return vec![]
+			}
+		}
+
+		self.votes
+			.entry((vote.block_number, vote.block_hash))
+			.or_default()
+			.insert(vote.validateer);
+		self.prune_votes_of(&vote.validateer);
+
+		self.finalize_if_super_majority(vote.block_number, vote.block_hash, validateers)
+	}
+
+	/// Finalizes the block if we imported it and more than 2/3 of the validateers voted for it.
+	/// Returns the hashes of all blocks that became final.
+	///
+	/// Blocks we did not import are not finalized, they might not be part of our chain.
+	fn finalize_if_super_majority(
+		&mut self,
+		block_number: BlockNumber,
+		block_hash: BlockHash,
+		validateers: &[ed25519::Public],
+	) -> Vec<BlockHash> {
+		if self.is_final(block_number) || self.own_blocks.get(&block_number) != Some(&block_hash) {
+			return vec![]
+		}
+
+		let number_of_votes = self
+			.votes
+			.get(&(block_number, block_hash))
+			.map(|voters| voters.iter().filter(|v| validateers.contains(v)).count())
+			.unwrap_or_default();
+
+		if !is_super_majority(number_of_votes as u64, validateers.len() as u64) {
+			return vec![]
+		}
+
+		self.finalize(block_number, block_hash)
+	}
+
+	/// Removes the lowest votes of a validateer that exceed its limit of pending votes.
+	fn prune_votes_of(&mut self, validateer: &ed25519::Public) {
+		let votes_of_validateer: Vec<(BlockNumber, BlockHash)> = self
+			.votes
+			.iter()
+			.filter(|(_, voters)| voters.contains(validateer))
+			.map(|(key, _)| *key)
+			.collect();
+		let number_of_excess_votes =
+			votes_of_validateer.len().saturating_sub(MAX_PENDING_VOTES_PER_VALIDATEER);
+
+		for key in votes_of_validateer.into_iter().take(number_of_excess_votes) {
+			if let Some(voters) = self.votes.get_mut(&key) {
+				voters.remove(validateer);
+				if voters.is_empty() {
+					self.votes.remove(&key);
+				}
+			}
+		}
+	}
+
+	fn finalize(&mut self, block_number: BlockNumber, block_hash: BlockHash) -> Vec<BlockHash> {
+		let finalized_hashes =
+			self.own_blocks.range(..=block_number).map(|(_, hash)| *hash).collect();
+
+		self.finalized_block = Some((block_number, block_hash));
+		self.own_blocks = self.own_blocks.split_off(&(block_number + 1));
+		self.votes = self.votes.split_off(&(block_number + 1, BlockHash::zero()));
+
+		finalized_hashes
+	}
+
+	fn add_own_block(&mut self, block_number: BlockNumber, block_hash: BlockHash) {
+		self.own_blocks.insert(block_number, block_hash);
+		while self.own_blocks.len() > MAX_BLOCKS_AWAITING_FINALITY {
+			let lowest = *self.own_blocks.keys().next().expect("own blocks are not empty; qed");
+			self.own_blocks.remove(&lowest);
+		}
+	}
+}
+
+#[derive(Default)]
+struct FinalityState {
+	/// Validateer set of the latest parentchain header we have seen.
+	validateers: Vec<ed25519::Public>,
+	/// Hash of the parentchain header the validateer set was fetched at.
+	validateers_parentchain_hash: Option<H256>,
+	shards: BTreeMap<ShardIdentifier, ShardFinality>,
+}
+
+/// Implements `HandleFinalityVotes`.
+///
+/// Finalized blocks are reported to the sidechain storage (by ocall) and to the finality observer.
+pub struct FinalityGadget<ParentchainHeader, ValidateerFetcher, SidechainOCallApi, FinalityObserver>
+{
+	signer: ed25519::Pair,
+	validateer_fetcher: Arc<ValidateerFetcher>,
+	sidechain_ocall_api: Arc<SidechainOCallApi>,
+	finality_observer: Arc<FinalityObserver>,
+	state: RwLock<FinalityState>,
+	_phantom: PhantomData<ParentchainHeader>,
+}
+
+impl<ParentchainHeader, ValidateerFetcher, SidechainOCallApi, FinalityObserver>
+	FinalityGadget<ParentchainHeader, ValidateerFetcher, SidechainOCallApi, FinalityObserver>
+where
+	ParentchainHeader: ParentchainHeaderTrait<Hash = H256>,
+	ValidateerFetcher: ValidateerFetch + Send + Sync,
+	SidechainOCallApi: EnclaveSidechainOCallApi,
+	FinalityObserver: NotifyFinalizedBlocks<ShardIdentifier>,
+{
+	pub fn new(
+		signer: ed25519::Pair,
+		validateer_fetcher: Arc<ValidateerFetcher>,
+		sidechain_ocall_api: Arc<SidechainOCallApi>,
+		finality_observer: Arc<FinalityObserver>,
+	) -> Self {
+		Self {
+			signer,
+			validateer_fetcher,
+			sidechain_ocall_api,
+			finality_observer,
+			state: Default::default(),
+			_phantom: Default::default(),
+		}
+	}
+
+	fn update_validateers(&self, parentchain_header: &ParentchainHeader) -> Result<()> {
+		let parentchain_hash = parentchain_header.hash();
+		if self
+			.state
+			.read()
+			.map_err(|_| Error::LockPoisoning)?
+			.validateers_parentchain_hash
+			== Some(parentchain_hash)
+		{
+			return Ok(())
+		}
+
+		let validateers = self
+			.validateer_fetcher
+			.current_validateers(parentchain_header)
+			.map_err(|e| Error::CouldNotGetAuthorities(e.to_string()))?
+			.into_iter()
+			.filter_map(|e| ed25519::Public::from_slice(e.pubkey.as_ref()).ok())
+			.collect();
+
+		let mut state = self.state.write().map_err(|_| Error::LockPoisoning)?;
+		state.validateers = validateers;
+		state.validateers_parentchain_hash = Some(parentchain_hash);
+		Ok(())
+	}
+
+	fn add_vote(&self, vote: FinalityVote) -> Result<()> {
+		let shard = vote.shard;
+
+		let finalized_hashes = {
+			let mut state = self.state.write().map_err(|_| Error::LockPoisoning)?;
+			if !state.validateers.contains(&vote.validateer) {
+				return Err(Error::InvalidFinalityVote(format!(
+					"{:?} is not part of the validateer set",
+					vote.validateer
+				)))
+			}
+			let FinalityState { validateers, shards, .. } = &mut *state;
+			shards.entry(shard).or_default().add_vote(vote, validateers)
+		};
+
+		self.notify_finalized_blocks(shard, finalized_hashes);
+		Ok(())
+	}
+
+	fn notify_finalized_blocks(&self, shard: ShardIdentifier, finalized_hashes: Vec<BlockHash>) {
+		if let Some(finalized_hash) = finalized_hashes.last() {
+			info!("Sidechain block {:?} of shard {:?} is final", finalized_hash, shard);
+
+			if let Err(e) =
+				self.sidechain_ocall_api.finalize_sidechain_block(shard, *finalized_hash)
+			{
+				error!(
+					"Failed to finalize sidechain block {:?} in storage: {:?}",
+					finalized_hash, e
+				);
+			}
+		}
+
+		for block_hash in finalized_hashes.iter() {
+			self.finality_observer.notify_finalized_block(&shard, block_hash);
+		}
+	}
+}
+
+impl<ParentchainHeader, ValidateerFetcher, SidechainOCallApi, FinalityObserver>
+	HandleFinalityVotes<ParentchainHeader>
+	for FinalityGadget<ParentchainHeader, ValidateerFetcher, SidechainOCallApi, FinalityObserver>
+where
+	ParentchainHeader: ParentchainHeaderTrait<Hash = H256>,
+	ValidateerFetcher: ValidateerFetch + Send + Sync,
+	SidechainOCallApi: EnclaveSidechainOCallApi,
+	FinalityObserver: NotifyFinalizedBlocks<ShardIdentifier>,
+{
+	fn vote_for_block(
+		&self,
+		shard: ShardIdentifier,
+		block_number: BlockNumber,
+		block_hash: BlockHash,
+		parentchain_header: &ParentchainHeader,
+	) -> Result<()> {
+		self.update_validateers(parentchain_header)?;
+
+		{
+			let mut state = self.state.write().map_err(|_| Error::LockPoisoning)?;
+			let FinalityState { validateers, shards, .. } = &mut *state;
+			let shard_finality = shards.entry(shard).or_default();
+			if shard_finality.is_final(block_number) {
+				debug!("Not voting for block {}, it is already final", block_number);
+				return Ok(())
+			}
+			shard_finality.add_own_block(block_number, block_hash);
+			if shard_finality.has_voted_at(block_number) {
+				debug!("Not voting for block {}, we already voted at this height", block_number);
+				// The block might have been voted final by the others before we imported it.
+				let finalized_hashes = shard_finality.finalize_if_super_majority(
+					block_number,
+					block_hash,
+					validateers,
+				);
+				drop(state);
+				self.notify_finalized_blocks(shard, finalized_hashes);
+				return Ok(())
+			}
+			shard_finality.last_voted_block_number = Some(block_number);
+		}
+
+		let vote =
+			FinalityVote { shard, block_number, block_hash, validateer: self.signer.public() };
+		let signed_vote = vote.clone().sign(&self.signer);
+
+		self.sidechain_ocall_api.propose_finality_votes(vec![signed_vote])?;
+
+		self.add_vote(vote)
+	}
+
+	fn import_vote(
+		&self,
+		signed_vote: SignedFinalityVote,
+		parentchain_header: &ParentchainHeader,
+	) -> Result<()> {
+		if !signed_vote.verify_signature() {
+			return Err(Error::InvalidFinalityVote("invalid signature".to_string()))
+		}
+
+		self.update_validateers(parentchain_header)?;
+		self.add_vote(signed_vote.vote)
+	}
+
+	fn last_finalized_block(&self, shard: &ShardIdentifier) -> Option<(BlockNumber, BlockHash)> {
+		self.state.read().ok()?.shards.get(shard)?.finalized_block
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use core::assert_matches::assert_matches;
+	use itc_parentchain_test::parentchain_header_builder::ParentchainHeaderBuilder;
+	use itp_test::mock::{
+		onchain_mock::OnchainMock, sidechain_ocall_api_mock::SidechainOCallApiMock,
+	};
+	use itp_types::{Enclave, Header as ParentchainHeader};
+	use its_primitives::types::block::SignedBlock as SignedSidechainBlock;
+	use its_test::notify_finalized_blocks_mock::NotifyFinalizedBlocksMock;
+	use sp_keyring::Ed25519Keyring as Keyring;
+
+	type TestSidechainOCallApi = SidechainOCallApiMock<SignedSidechainBlock>;
+	type TestFinalityObserver = NotifyFinalizedBlocksMock<ShardIdentifier>;
+	type TestFinalityGadget =
+		FinalityGadget<ParentchainHeader, OnchainMock, TestSidechainOCallApi, TestFinalityObserver>;
+
+	fn shard() -> ShardIdentifier {
+		ShardIdentifier::from_low_u64_be(1)
+	}
+
+	fn block_hash(number: BlockNumber) -> BlockHash {
+		BlockHash::from_low_u64_be(number)
+	}
+
+	fn validateer(keyring: Keyring) -> Enclave {
+		Enclave::new(
+			keyring.public().into(),
+			Default::default(),
+			Default::default(),
+			Default::default(),
+		)
+	}
+
+	fn signed_vote(keyring: Keyring, block_number: BlockNumber) -> SignedFinalityVote {
+		FinalityVote {
+			shard: shard(),
+			block_number,
+			block_hash: block_hash(block_number),
+			validateer: keyring.public(),
+		}
+		.sign(&keyring.pair())
+	}
+
+	fn test_fixtures(
+		validateers: Vec<Keyring>,
+	) -> (
+		TestFinalityGadget,
+		ParentchainHeader,
+		Arc<TestSidechainOCallApi>,
+		Arc<TestFinalityObserver>,
+	) {
+		let parentchain_header = ParentchainHeaderBuilder::default().build();
+		let onchain_mock = OnchainMock::default().add_validateer_set(
+			&parentchain_header,
+			Some(validateers.into_iter().map(validateer).collect()),
+		);
+		let sidechain_ocall_api = Arc::new(TestSidechainOCallApi::default());
+		let finality_observer = Arc::new(TestFinalityObserver::default());
+
+		let gadget = TestFinalityGadget::new(
+			Keyring::Alice.pair(),
+			Arc::new(onchain_mock),
+			sidechain_ocall_api.clone(),
+			finality_observer.clone(),
+		);
+
+		(gadget, parentchain_header, sidechain_ocall_api, finality_observer)
+	}
+
+	#[test]
+	fn vote_for_block_sends_signed_vote_to_peers() {
+		let (gadget, header, ocall_api, _) =
+			test_fixtures(vec![Keyring::Alice, Keyring::Bob, Keyring::Charlie]);
+
+		gadget.vote_for_block(shard(), 1, block_hash(1), &header).unwrap();
+
+		let proposed_votes = ocall_api.proposed_finality_votes::<SignedFinalityVote>();
+		assert_eq!(vec![signed_vote(Keyring::Alice, 1)], proposed_votes);
+		assert!(gadget.last_finalized_block(&shard()).is_none());
+	}
+
+	#[test]
+	fn single_validateer_finalizes_its_own_blocks() {
+		let (gadget, header, ocall_api, finality_observer) = test_fixtures(vec![Keyring::Alice]);
+
+		gadget.vote_for_block(shard(), 1, block_hash(1), &header).unwrap();
+
+		assert_eq!(Some((1, block_hash(1))), gadget.last_finalized_block(&shard()));
+		assert_eq!(vec![(shard(), block_hash(1))], ocall_api.finalized_blocks());
+		assert_eq!(vec![(shard(), block_hash(1))], finality_observer.finalized_blocks());
+	}
+
+	#[test]
+	fn block_is_final_once_more_than_two_thirds_voted() {
+		let (gadget, header, ocall_api, _) =
+			test_fixtures(vec![Keyring::Alice, Keyring::Bob, Keyring::Charlie, Keyring::Dave]);
+
+		gadget.vote_for_block(shard(), 1, block_hash(1), &header).unwrap();
+		gadget.import_vote(signed_vote(Keyring::Bob, 1), &header).unwrap();
+		assert!(gadget.last_finalized_block(&shard()).is_none());
+
+		gadget.import_vote(signed_vote(Keyring::Charlie, 1), &header).unwrap();
+
+		assert_eq!(Some((1, block_hash(1))), gadget.last_finalized_block(&shard()));
+		assert_eq!(vec![(shard(), block_hash(1))], ocall_api.finalized_blocks());
+	}
+
+	#[test]
+	fn finalizing_a_block_notifies_about_its_own_ancestors() {
+		let (gadget, header, _, finality_observer) =
+			test_fixtures(vec![Keyring::Alice, Keyring::Bob]);
+
+		gadget.vote_for_block(shard(), 1, block_hash(1), &header).unwrap();
+		gadget.vote_for_block(shard(), 2, block_hash(2), &header).unwrap();
+		gadget.import_vote(signed_vote(Keyring::Bob, 2), &header).unwrap();
+
+		assert_eq!(Some((2, block_hash(2))), gadget.last_finalized_block(&shard()));
+		assert_eq!(
+			vec![(shard(), block_hash(1)), (shard(), block_hash(2))],
+			finality_observer.finalized_blocks()
+		);
+	}
+
+	#[test]
+	fn votes_for_already_final_blocks_are_ignored() {
+		let (gadget, header, ocall_api, _) = test_fixtures(vec![Keyring::Alice]);
+
+		gadget.vote_for_block(shard(), 2, block_hash(2), &header).unwrap();
+		gadget.vote_for_block(shard(), 1, block_hash(1), &header).unwrap();
+
+		assert_eq!(Some((2, block_hash(2))), gadget.last_finalized_block(&shard()));
+		assert_eq!(1, ocall_api.proposed_finality_votes::<SignedFinalityVote>().len());
+	}
+
+	#[test]
+	fn no_second_vote_is_cast_at_the_same_height() {
+		let (gadget, header, ocall_api, _) =
+			test_fixtures(vec![Keyring::Alice, Keyring::Bob, Keyring::Charlie]);
+
+		gadget.vote_for_block(shard(), 1, block_hash(1), &header).unwrap();
+		// Block of a fork we reorganized onto.
+		gadget
+			.vote_for_block(shard(), 1, BlockHash::from_low_u64_be(42), &header)
+			.unwrap();
+
+		assert_eq!(
+			vec![signed_vote(Keyring::Alice, 1)],
+			ocall_api.proposed_finality_votes::<SignedFinalityVote>()
+		);
+	}
+
+	#[test]
+	fn votes_too_far_above_the_head_are_ignored() {
+		let (gadget, header, _, _) = test_fixtures(vec![Keyring::Alice, Keyring::Bob]);
+		gadget.vote_for_block(shard(), 1, block_hash(1), &header).unwrap();
+
+		let far_ahead = 2 + MAX_VOTE_HEIGHT_ABOVE_HEAD;
+		gadget.import_vote(signed_vote(Keyring::Bob, far_ahead), &header).unwrap();
+
+		let state = gadget.state.read().unwrap();
+		let votes = &state.shards.get(&shard()).unwrap().votes;
+		assert!(!votes.contains_key(&(far_ahead, block_hash(far_ahead))));
+	}
+
+	#[test]
+	fn pending_votes_are_capped_per_validateer() {
+		let (gadget, header, _, _) =
+			test_fixtures(vec![Keyring::Alice, Keyring::Bob, Keyring::Charlie]);
+		let max_pending_votes = MAX_PENDING_VOTES_PER_VALIDATEER as BlockNumber;
+		gadget.vote_for_block(shard(), 1, block_hash(1), &header).unwrap();
+		gadget
+			.vote_for_block(shard(), max_pending_votes, block_hash(max_pending_votes), &header)
+			.unwrap();
+
+		for block_number in 1..=max_pending_votes + 1 {
+			gadget.import_vote(signed_vote(Keyring::Bob, block_number), &header).unwrap();
+		}
+
+		let state = gadget.state.read().unwrap();
+		let votes = &state.shards.get(&shard()).unwrap().votes;
+		let votes_of = |validateer: Keyring| {
+			votes.values().filter(|voters| voters.contains(&validateer.public())).count()
+		};
+		assert_eq!(MAX_PENDING_VOTES_PER_VALIDATEER, votes_of(Keyring::Bob));
+		// The lowest vote of Bob was dropped, the vote of Alice is kept.
+		assert_eq!(
+			Some(&BTreeSet::from([Keyring::Alice.public()])),
+			votes.get(&(1, block_hash(1)))
+		);
+	}
+
+	#[test]
+	fn votes_are_imported_before_we_voted_ourselves() {
+		let (gadget, header, _, _) = test_fixtures(vec![Keyring::Alice, Keyring::Bob]);
+
+		gadget.import_vote(signed_vote(Keyring::Bob, 1), &header).unwrap();
+		gadget.vote_for_block(shard(), 1, block_hash(1), &header).unwrap();
+
+		assert_eq!(Some((1, block_hash(1))), gadget.last_finalized_block(&shard()));
+	}
+
+	#[test]
+	fn block_we_did_not_import_is_not_finalized() {
+		let (gadget, header, ocall_api, _) =
+			test_fixtures(vec![Keyring::Alice, Keyring::Bob, Keyring::Charlie, Keyring::Dave]);
+		gadget.vote_for_block(shard(), 1, block_hash(1), &header).unwrap();
+
+		let fork_block_hash = BlockHash::from_low_u64_be(42);
+		for keyring in [Keyring::Bob, Keyring::Charlie, Keyring::Dave] {
+			let vote = FinalityVote {
+				shard: shard(),
+				block_number: 1,
+				block_hash: fork_block_hash,
+				validateer: keyring.public(),
+			};
+			gadget.import_vote(vote.sign(&keyring.pair()), &header).unwrap();
+		}
+		assert!(gadget.last_finalized_block(&shard()).is_none());
+		assert!(ocall_api.finalized_blocks().is_empty());
+
+		// Once we reorganized onto the fork, its block is final.
+		gadget.vote_for_block(shard(), 1, fork_block_hash, &header).unwrap();
+
+		assert_eq!(Some((1, fork_block_hash)), gadget.last_finalized_block(&shard()));
+		assert_eq!(vec![(shard(), fork_block_hash)], ocall_api.finalized_blocks());
+	}
+
+	#[test]
+	fn vote_of_non_validateer_is_rejected() {
+		let (gadget, header, _, _) = test_fixtures(vec![Keyring::Alice, Keyring::Bob]);
+		gadget.vote_for_block(shard(), 1, block_hash(1), &header).unwrap();
+
+		assert_matches!(
+			gadget.import_vote(signed_vote(Keyring::Eve, 1), &header),
+			Err(Error::InvalidFinalityVote(_))
+		);
+		assert!(gadget.last_finalized_block(&shard()).is_none());
+	}
+
+	#[test]
+	fn vote_with_invalid_signature_is_rejected() {
+		let (gadget, header, _, _) = test_fixtures(vec![Keyring::Alice, Keyring::Bob]);
+		gadget.vote_for_block(shard(), 1, block_hash(1), &header).unwrap();
+
+		let mut vote = signed_vote(Keyring::Bob, 1);
+		vote.vote.validateer = Keyring::Alice.public();
+
+		assert_matches!(gadget.import_vote(vote, &header), Err(Error::InvalidFinalityVote(_)));
+	}
+}
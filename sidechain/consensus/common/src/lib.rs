@@ -35,7 +35,10 @@ use std::{time::Duration, vec::Vec};
 mod block_import;
 mod block_import_confirmation_handler;
 mod block_import_queue_worker;
+mod block_production_suspension;
+mod equivocation;
 mod error;
+mod finality;
 mod peer_block_sync;
 
 #[cfg(test)]
@@ -44,7 +47,10 @@ mod test;
 pub use block_import::*;
 pub use block_import_confirmation_handler::*;
 pub use block_import_queue_worker::*;
+pub use block_production_suspension::*;
+pub use equivocation::*;
 pub use error::*;
+pub use finality::*;
 pub use peer_block_sync::*;
 
 pub trait Verifier<ParentchainBlock, SignedSidechainBlock>: Send + Sync
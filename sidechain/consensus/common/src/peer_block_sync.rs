@@ -16,19 +16,25 @@
 */
 
 use crate::{BlockImport, ConfirmBlockImport, Error, Result};
-use core::marker::PhantomData;
+use core::{cmp::min, marker::PhantomData};
 use itp_ocall_api::EnclaveSidechainOCallApi;
-use itp_types::H256;
+use itp_types::{AccountId, PeerBlockRangeRequest, H256};
 use its_primitives::{
+	fork_choice::is_preferred_fork,
 	traits::{
 		Block as BlockTrait, Header as HeaderTrait, ShardIdentifierFor,
 		SignedBlock as SignedSidechainBlockTrait,
 	},
-	types::BlockHash,
+	types::{BlockHash, BlockNumber},
 };
+use its_validateer_fetch::ValidateerFetch;
 use log::*;
 use sp_runtime::traits::{Block as ParentchainBlockTrait, Header as ParentchainHeaderTrait};
-use std::{sync::Arc, vec::Vec};
+use std::{
+	string::{String, ToString},
+	sync::Arc,
+	vec::Vec,
+};
 
 /// Trait for syncing sidechain blocks from a peer validateer.
 ///
@@ -46,17 +52,50 @@ where
 	) -> Result<ParentchainHeader>;
 }
 
+/// How missing sidechain blocks are fetched from peers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PeerSyncMode {
+	/// Fetch all missing blocks from a single peer.
+	SinglePeer,
+	/// Split the missing blocks into chunks of `chunk_size` blocks, which are fetched from
+	/// several validateers in parallel. A chunk that cannot be fetched, or fails verification
+	/// or import, is requested from the next validateer, up to `max_attempts_per_chunk` times.
+	/// At most `max_parallel_requests` chunks are requested at once.
+	MultiPeer { chunk_size: u64, max_attempts_per_chunk: usize, max_parallel_requests: usize },
+}
+
+/// Range of missing blocks, fetched from a peer as a whole.
+struct BlockChunk<SignedSidechainBlock> {
+	from_block_number: BlockNumber,
+	/// Number of the last block of the chunk (inclusive).
+	to_block_number: BlockNumber,
+	attempts: usize,
+	/// Blocks fetched and verified, but not yet imported.
+	blocks: Option<Vec<SignedSidechainBlock>>,
+}
+
+impl<SignedSidechainBlock> BlockChunk<SignedSidechainBlock> {
+	fn len(&self) -> u64 {
+		self.to_block_number - self.from_block_number + 1
+	}
+}
+
 /// Sidechain peer block sync implementation.
 pub struct PeerBlockSync<
 	ParentchainBlock,
 	SignedSidechainBlock,
 	BlockImporter,
 	SidechainOCallApi,
+	ValidateerFetcher,
 	ImportConfirmationHandler,
 > {
 	importer: Arc<BlockImporter>,
 	sidechain_ocall_api: Arc<SidechainOCallApi>,
+	validateer_fetcher: Arc<ValidateerFetcher>,
 	import_confirmation_handler: Arc<ImportConfirmationHandler>,
+	sync_mode: PeerSyncMode,
+	/// Our own validateer account, which is never asked for blocks.
+	maybe_own_account: Option<AccountId>,
 	_phantom: PhantomData<(ParentchainBlock, SignedSidechainBlock)>,
 }
 
@@ -65,6 +104,7 @@ impl<
 		SignedSidechainBlock,
 		BlockImporter,
 		SidechainOCallApi,
+		ValidateerFetcher,
 		ImportConfirmationHandler,
 	>
 	PeerBlockSync<
@@ -72,14 +112,16 @@ impl<
 		SignedSidechainBlock,
 		BlockImporter,
 		SidechainOCallApi,
+		ValidateerFetcher,
 		ImportConfirmationHandler,
 	> where
-	ParentchainBlock: ParentchainBlockTrait,
+	ParentchainBlock: ParentchainBlockTrait<Hash = H256>,
 	SignedSidechainBlock: SignedSidechainBlockTrait,
 	<<SignedSidechainBlock as SignedSidechainBlockTrait>::Block as BlockTrait>::HeaderType:
 		HeaderTrait<ShardIdentifier = H256>,
 	BlockImporter: BlockImport<ParentchainBlock, SignedSidechainBlock>,
 	SidechainOCallApi: EnclaveSidechainOCallApi,
+	ValidateerFetcher: ValidateerFetch,
 	ImportConfirmationHandler: ConfirmBlockImport<
 		<<SignedSidechainBlock as SignedSidechainBlockTrait>::Block as BlockTrait>::HeaderType,
 	>,
@@ -87,16 +129,64 @@ impl<
 	pub fn new(
 		importer: Arc<BlockImporter>,
 		sidechain_ocall_api: Arc<SidechainOCallApi>,
+		validateer_fetcher: Arc<ValidateerFetcher>,
 		import_confirmation_handler: Arc<ImportConfirmationHandler>,
 	) -> Self {
 		PeerBlockSync {
 			importer,
 			sidechain_ocall_api,
+			validateer_fetcher,
 			import_confirmation_handler,
+			sync_mode: PeerSyncMode::SinglePeer,
+			maybe_own_account: None,
 			_phantom: Default::default(),
 		}
 	}
 
+	pub fn with_sync_mode(mut self, sync_mode: PeerSyncMode) -> Self {
+		self.sync_mode = sync_mode;
+		self
+	}
+
+	/// Exclude our own validateer from the peers blocks are fetched from. We only have the
+	/// blocks we already imported ourselves.
+	pub fn with_own_account(mut self, own_account: AccountId) -> Self {
+		self.maybe_own_account = Some(own_account);
+		self
+	}
+
+	/// Fetches and imports the blocks missing between our last imported block and
+	/// `block_to_import`, according to the sync mode.
+	fn fetch_and_import_missing_blocks(
+		&self,
+		last_imported_block_number: BlockNumber,
+		last_imported_block_hash: BlockHash,
+		block_to_import: &SignedSidechainBlock,
+		current_parentchain_header: &ParentchainBlock::Header,
+	) -> Result<ParentchainBlock::Header> {
+		match self.sync_mode {
+			PeerSyncMode::SinglePeer => self.fetch_and_import_blocks_from_peer(
+				last_imported_block_hash,
+				block_to_import.hash(),
+				current_parentchain_header,
+				block_to_import.block().header().shard_id(),
+			),
+			PeerSyncMode::MultiPeer {
+				chunk_size,
+				max_attempts_per_chunk,
+				max_parallel_requests,
+			} => self.fetch_and_import_blocks_from_peers(
+				last_imported_block_number,
+				last_imported_block_hash,
+				block_to_import,
+				current_parentchain_header,
+				chunk_size,
+				max_attempts_per_chunk,
+				max_parallel_requests,
+			),
+		}
+	}
+
 	fn fetch_and_import_blocks_from_peer(
 		&self,
 		last_imported_sidechain_block_hash: BlockHash,
@@ -143,18 +233,317 @@ impl<
 
 		Ok(latest_imported_parentchain_header)
 	}
+
+	/// Splits the missing blocks into chunks, which are fetched from the current validateers
+	/// in parallel, and imports them in order.
+	///
+	/// Before import, each chunk is verified to consist of consecutive blocks of the shard,
+	/// where each block builds on its predecessor. The first chunk has to build on our last
+	/// imported block and the last chunk has to end with the parent of `block_to_import`.
+	/// Chunks that fail are requested from the next validateer.
+	fn fetch_and_import_blocks_from_peers(
+		&self,
+		last_imported_block_number: BlockNumber,
+		last_imported_block_hash: BlockHash,
+		block_to_import: &SignedSidechainBlock,
+		current_parentchain_header: &ParentchainBlock::Header,
+		chunk_size: u64,
+		max_attempts_per_chunk: usize,
+		max_parallel_requests: usize,
+	) -> Result<ParentchainBlock::Header> {
+		let shard_identifier = block_to_import.block().header().shard_id();
+		let first_missing_block_number = last_imported_block_number + 1;
+		let block_to_import_number = block_to_import.block().header().block_number();
+
+		if block_to_import_number <= first_missing_block_number {
+			// No block is missing by number, `block_to_import` does not build on our last block.
+			// Only a single peer can tell which blocks lead to it.
+			return self.fetch_and_import_blocks_from_peer(
+				last_imported_block_hash,
+				block_to_import.hash(),
+				current_parentchain_header,
+				shard_identifier,
+			)
+		}
+		let last_missing_block_number = block_to_import_number - 1;
+		let number_of_missing_blocks = block_to_import_number - first_missing_block_number;
+
+		let peer_urls: Vec<_> = self
+			.validateer_fetcher
+			.current_validateers(current_parentchain_header)
+			.map_err(|e| Error::CouldNotGetAuthorities(e.to_string()))?
+			.into_iter()
+			.filter(|validateer| Some(&validateer.pubkey) != self.maybe_own_account.as_ref())
+			.map(|validateer| validateer.url)
+			.collect();
+		if peer_urls.is_empty() {
+			return Err(Error::PeerSyncFailed("No validateers to fetch blocks from".into()))
+		}
+
+		let chunk_size = chunk_size.max(1);
+		let mut chunks: Vec<BlockChunk<SignedSidechainBlock>> = (first_missing_block_number
+			..=last_missing_block_number)
+			.step_by(chunk_size as usize)
+			.map(|from_block_number| BlockChunk {
+				from_block_number,
+				to_block_number: min(from_block_number + chunk_size - 1, last_missing_block_number),
+				attempts: 0,
+				blocks: None,
+			})
+			.collect();
+
+		info!(
+			"Syncing {} missing blocks of shard {:?} from {} peers, in {} chunks",
+			number_of_missing_blocks,
+			shard_identifier,
+			peer_urls.len(),
+			chunks.len()
+		);
+
+		let mut latest_imported_parentchain_header = current_parentchain_header.clone();
+		let mut parent_hash = last_imported_block_hash;
+		let mut number_of_imported_blocks = 0u64;
+		let mut next_chunk = 0usize;
+
+		while next_chunk < chunks.len() {
+			// Request the chunks that are not fetched yet, at most one per peer and round.
+			let pending_chunks: Vec<usize> = (next_chunk..chunks.len())
+				.filter(|i| chunks[*i].blocks.is_none())
+				.take(min(peer_urls.len(), max_parallel_requests.max(1)))
+				.collect();
+
+			if !pending_chunks.is_empty() {
+				let requests = pending_chunks
+					.iter()
+					.map(|i| {
+						let chunk = &mut chunks[*i];
+						if chunk.attempts >= max_attempts_per_chunk {
+							return Err(Error::PeerSyncFailed(format!(
+								"Could not fetch blocks {}..={} from {} peers",
+								chunk.from_block_number, chunk.to_block_number, chunk.attempts
+							)))
+						}
+						let peer_url = peer_urls[(i + chunk.attempts) % peer_urls.len()].clone();
+						chunk.attempts += 1;
+						Ok(PeerBlockRangeRequest {
+							peer_url,
+							from_block_number: chunk.from_block_number,
+							limit: chunk.len(),
+						})
+					})
+					.collect::<Result<Vec<_>>>()?;
+
+				let fetched_chunks: Vec<Option<Vec<SignedSidechainBlock>>> = self
+					.sidechain_ocall_api
+					.fetch_sidechain_block_ranges_from_peers(requests, shard_identifier)?;
+
+				for (i, maybe_blocks) in pending_chunks.into_iter().zip(fetched_chunks) {
+					let chunk = &mut chunks[i];
+					let blocks = match maybe_blocks {
+						Some(blocks) => blocks,
+						None => {
+							warn!(
+								"Failed to fetch blocks {}..={} from peer, retrying from another peer",
+								chunk.from_block_number, chunk.to_block_number
+							);
+							continue
+						},
+					};
+					let last_block_hash = if chunk.to_block_number == last_missing_block_number {
+						Some(block_to_import.block().header().parent_hash())
+					} else {
+						None
+					};
+					match verify_chunk_ancestry(
+						&blocks,
+						chunk.from_block_number,
+						chunk.to_block_number,
+						&shard_identifier,
+						last_block_hash,
+					) {
+						Ok(()) => chunk.blocks = Some(blocks),
+						Err(reason) => warn!(
+							"Blocks {}..={} fetched from peer are invalid: {}. Retrying from another peer",
+							chunk.from_block_number, chunk.to_block_number, reason
+						),
+					}
+				}
+			}
+
+			// Import the fetched chunks that connect to our last imported block.
+			while let Some(blocks) = chunks.get_mut(next_chunk).and_then(|c| c.blocks.take()) {
+				if blocks.first().map(|b| b.block().header().parent_hash()) != Some(parent_hash) {
+					warn!(
+						"Blocks {}..={} fetched from peer do not build on block {:?}. Retrying from another peer",
+						chunks[next_chunk].from_block_number,
+						chunks[next_chunk].to_block_number,
+						parent_hash
+					);
+					break
+				}
+
+				let mut chunk_imported = true;
+				for block in blocks {
+					let block_number = block.block().header().block_number();
+					let block_hash = block.hash();
+					match self.importer.import_block(block, &latest_imported_parentchain_header) {
+						Ok(header) => {
+							latest_imported_parentchain_header = header;
+							parent_hash = block_hash;
+							number_of_imported_blocks += 1;
+						},
+						Err(e) => {
+							warn!(
+								"Failed to import block {} fetched from peer: {:?}. Retrying from another peer",
+								block_number, e
+							);
+							// Re-request the rest of the chunk.
+							chunks[next_chunk].from_block_number = block_number;
+							chunk_imported = false;
+							break
+						},
+					}
+				}
+				if !chunk_imported {
+					break
+				}
+				next_chunk += 1;
+
+				info!(
+					"Peer sync of shard {:?}: imported {} of {} missing blocks",
+					shard_identifier, number_of_imported_blocks, number_of_missing_blocks
+				);
+			}
+		}
+
+		Ok(latest_imported_parentchain_header)
+	}
+
+	/// Attempts to reorganize onto a fork that competes with our last imported block.
+	///
+	/// Handles forks that branch off at the parent of our last imported block, i.e. the block
+	/// to import is either
+	/// - a sibling of our last imported block, preferred by the fork choice rule, or
+	/// - a child of such a sibling (the fork is longer than our chain). The sibling is fetched from a peer.
+	///
+	/// The state is reverted to before our last imported block, and the fork is imported on top.
+	/// If the fork fails to import, our last imported block is restored.
+	/// Returns `None` if the block does not belong to such a fork, the fork is not preferred,
+	/// or our last imported block cannot be reverted.
+	///
+	/// Forks that branch off further back are not handled here: the enclave only knows the last
+	/// block of a shard, not the common ancestor with a deeper fork. Such forks are only resolved
+	/// by the untrusted sidechain storage, our state stays on our own branch. Reverting also
+	/// relies on the state hashes the block importer kept in memory, so no reorganization is
+	/// possible for blocks imported before the last restart of the worker.
+	fn reorganize_onto_fork(
+		&self,
+		sidechain_block: &SignedSidechainBlock,
+		current_parentchain_header: &ParentchainBlock::Header,
+	) -> Result<Option<ParentchainBlock::Header>> {
+		let shard_identifier = sidechain_block.block().header().shard_id();
+		let last_block = match self.importer.get_last_imported_block(&shard_identifier)? {
+			Some(block) => block,
+			None => return Ok(None),
+		};
+		if self
+			.importer
+			.last_finalized_block_number(&shard_identifier)
+			.map_or(false, |finalized| finalized >= last_block.header().block_number())
+		{
+			debug!("Not reorganizing shard {:?}, our last block is final", shard_identifier);
+			return Ok(None)
+		}
+		let fork_point = last_block.header().parent_hash();
+		let header = sidechain_block.block().header();
+
+		let fork_blocks: Vec<SignedSidechainBlock> = if header.parent_hash() == fork_point {
+			if !is_preferred_fork(sidechain_block.block(), &last_block) {
+				return Ok(None)
+			}
+			Vec::new()
+		} else if header.block_number() == last_block.header().block_number() + 1
+			&& header.parent_hash() != last_block.hash()
+		{
+			let fetched_blocks: Vec<SignedSidechainBlock> =
+				self.sidechain_ocall_api.fetch_sidechain_blocks_from_peer(
+					fork_point,
+					Some(sidechain_block.hash()),
+					shard_identifier,
+				)?;
+			match fetched_blocks.as_slice() {
+				[sibling]
+					if sibling.block().header().parent_hash() == fork_point
+						&& sibling.hash() == header.parent_hash() => {},
+				_ => return Ok(None),
+			}
+			fetched_blocks
+		} else {
+			return Ok(None)
+		};
+
+		info!(
+			"Reorganizing shard {:?} onto fork with block {:?} (number: {}), replacing block {:?}",
+			shard_identifier,
+			sidechain_block.hash(),
+			header.block_number(),
+			last_block.hash()
+		);
+		let reverted_block = match self.importer.revert_block(&shard_identifier, &last_block.hash())
+		{
+			Ok(reverted_block) => reverted_block,
+			Err(Error::CannotRevertBlock(block_hash)) => {
+				warn!(
+					"Cannot reorganize shard {:?}, the state before block {:?} is not known anymore",
+					shard_identifier, block_hash
+				);
+				return Ok(None)
+			},
+			Err(e) => return Err(e),
+		};
+
+		match self.import_fork_blocks(fork_blocks, sidechain_block, current_parentchain_header) {
+			Ok(latest_imported_parentchain_header) => Ok(Some(latest_imported_parentchain_header)),
+			Err(e) => {
+				warn!(
+					"Failed to import fork of shard {:?}, restoring block {:?}: {:?}",
+					shard_identifier,
+					last_block.hash(),
+					e
+				);
+				self.importer.restore_reverted_block(&shard_identifier, reverted_block)?;
+				Err(e)
+			},
+		}
+	}
+
+	fn import_fork_blocks(
+		&self,
+		fork_blocks: Vec<SignedSidechainBlock>,
+		sidechain_block: &SignedSidechainBlock,
+		current_parentchain_header: &ParentchainBlock::Header,
+	) -> Result<ParentchainBlock::Header> {
+		let mut latest_imported_parentchain_header = current_parentchain_header.clone();
+		for block_to_import in fork_blocks.into_iter().chain(Some(sidechain_block.clone())) {
+			latest_imported_parentchain_header = self
+				.importer
+				.import_block(block_to_import, &latest_imported_parentchain_header)?;
+		}
+		Ok(latest_imported_parentchain_header)
+	}
 }
 
-impl<ParentchainBlock, SignedSidechainBlock, BlockImporter, SidechainOCallApi, ImportConfirmationHandler>
+impl<ParentchainBlock, SignedSidechainBlock, BlockImporter, SidechainOCallApi, ValidateerFetcher, ImportConfirmationHandler>
 	SyncBlockFromPeer<ParentchainBlock::Header, SignedSidechainBlock>
-	for PeerBlockSync<ParentchainBlock, SignedSidechainBlock, BlockImporter, SidechainOCallApi, ImportConfirmationHandler>
+	for PeerBlockSync<ParentchainBlock, SignedSidechainBlock, BlockImporter, SidechainOCallApi, ValidateerFetcher, ImportConfirmationHandler>
 where
-	ParentchainBlock: ParentchainBlockTrait,
+	ParentchainBlock: ParentchainBlockTrait<Hash = H256>,
 	SignedSidechainBlock: SignedSidechainBlockTrait,
 	<<SignedSidechainBlock as its_primitives::traits::SignedBlock>::Block as BlockTrait>::HeaderType:
 	HeaderTrait<ShardIdentifier = H256>,
 	BlockImporter: BlockImport<ParentchainBlock, SignedSidechainBlock>,
 	SidechainOCallApi: EnclaveSidechainOCallApi,
+	ValidateerFetcher: ValidateerFetch,
 	ImportConfirmationHandler: ConfirmBlockImport<<<SignedSidechainBlock as SignedSidechainBlockTrait>::Block as BlockTrait>::HeaderType>,
 {
 	fn sync_block(
@@ -164,19 +553,24 @@ where
 	) -> Result<ParentchainBlock::Header> {
 		let shard_identifier = sidechain_block.block().header().shard_id();
 		let sidechain_block_number = sidechain_block.block().header().block_number();
-		let sidechain_block_hash = sidechain_block.hash();
 
 		// Attempt to import the block - in case we encounter an ancestry error, we go into
 		// peer fetching mode to fetch sidechain blocks from a peer and import those first.
 		match self.importer.import_block(sidechain_block.clone(), current_parentchain_header) {
 			Err(e) => match e {
-				Error::BlockAncestryMismatch(_block_number, block_hash, _) => {
+				Error::BlockAncestryMismatch(block_number, block_hash, _) => {
+					if let Some(updated_parentchain_header) =
+						self.reorganize_onto_fork(&sidechain_block, current_parentchain_header)?
+					{
+						return Ok(updated_parentchain_header)
+					}
+
 					warn!("Got ancestry mismatch error upon block import. Attempting to fetch missing blocks from peer");
-					let updated_parentchain_header = self.fetch_and_import_blocks_from_peer(
+					let updated_parentchain_header = self.fetch_and_import_missing_blocks(
+						block_number,
 						block_hash,
-						sidechain_block_hash,
+						&sidechain_block,
 						current_parentchain_header,
-						shard_identifier,
 					)?;
 
 					self.importer.import_block(sidechain_block, &updated_parentchain_header)
@@ -184,16 +578,22 @@ where
 				Error::InvalidFirstBlock(block_number, _) => {
 					warn!("Got invalid first block error upon block import (expected first block, but got block with number {}). \
 							Attempting to fetch missing blocks from peer", block_number);
-					let updated_parentchain_header = self.fetch_and_import_blocks_from_peer(
+					let updated_parentchain_header = self.fetch_and_import_missing_blocks(
+						0,
 						Default::default(), // This is the parent hash of the first block. So we import everything.
-						sidechain_block_hash,
+						&sidechain_block,
 						current_parentchain_header,
-						shard_identifier,
 					)?;
 
 					self.importer.import_block(sidechain_block, &updated_parentchain_header)
 				},
 				Error::BlockAlreadyImported(to_import_block_number, last_known_block_number) => {
+					if let Some(updated_parentchain_header) =
+						self.reorganize_onto_fork(&sidechain_block, current_parentchain_header)?
+					{
+						return Ok(updated_parentchain_header)
+					}
+
 					warn!("Sidechain block from queue (number: {}) was already imported (current block number: {}). Block will be ignored.", 
 						to_import_block_number, last_known_block_number);
 					Ok(current_parentchain_header.clone())
@@ -216,6 +616,55 @@ where
 	}
 }
 
+/// Verifies that `blocks` are the consecutive blocks `from_block_number..=to_block_number`
+/// of the shard, each building on its predecessor, and ending with `maybe_last_block_hash`.
+fn verify_chunk_ancestry<SignedSidechainBlock>(
+	blocks: &[SignedSidechainBlock],
+	from_block_number: BlockNumber,
+	to_block_number: BlockNumber,
+	shard_identifier: &ShardIdentifierFor<SignedSidechainBlock>,
+	maybe_last_block_hash: Option<BlockHash>,
+) -> core::result::Result<(), String>
+where
+	SignedSidechainBlock: SignedSidechainBlockTrait,
+	<<SignedSidechainBlock as SignedSidechainBlockTrait>::Block as BlockTrait>::HeaderType:
+		HeaderTrait<ShardIdentifier = H256>,
+{
+	let expected_number_of_blocks = to_block_number - from_block_number + 1;
+	if blocks.len() as u64 != expected_number_of_blocks {
+		return Err(format!("expected {} blocks, got {}", expected_number_of_blocks, blocks.len()))
+	}
+
+	let mut maybe_previous_hash: Option<BlockHash> = None;
+	for (block, expected_block_number) in blocks.iter().zip(from_block_number..) {
+		let header = block.block().header();
+		if header.block_number() != expected_block_number {
+			return Err(format!(
+				"expected block {}, got block {}",
+				expected_block_number,
+				header.block_number()
+			))
+		}
+		if &header.shard_id() != shard_identifier {
+			return Err(format!("block {} is of another shard", expected_block_number))
+		}
+		if maybe_previous_hash.map_or(false, |previous_hash| header.parent_hash() != previous_hash)
+		{
+			return Err(format!("block {} does not build on its predecessor", expected_block_number))
+		}
+		if !block.verify_signature() {
+			return Err(format!("block {} has an invalid signature", expected_block_number))
+		}
+		maybe_previous_hash = Some(block.hash());
+	}
+
+	match maybe_last_block_hash {
+		Some(last_block_hash) if maybe_previous_hash != Some(last_block_hash) =>
+			Err("last block is not the parent of the block to import".into()),
+		_ => Ok(()),
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -224,10 +673,18 @@ mod tests {
 	};
 	use core::assert_matches::assert_matches;
 	use itc_parentchain_test::parentchain_header_builder::ParentchainHeaderBuilder;
-	use itp_test::mock::sidechain_ocall_api_mock::SidechainOCallApiMock;
-	use itp_types::Block as ParentchainBlock;
-	use its_primitives::types::block::SignedBlock as SignedSidechainBlock;
-	use its_test::sidechain_block_builder::SidechainBlockBuilder;
+	use itp_test::mock::{
+		onchain_mock::OnchainMock, sidechain_ocall_api_mock::SidechainOCallApiMock,
+	};
+	use itp_types::{Block as ParentchainBlock, Enclave, Header as ParentchainHeader};
+	use its_primitives::{
+		fork_choice::author_slot_hash, types::block::SignedBlock as SignedSidechainBlock,
+	};
+	use its_test::{
+		sidechain_block_builder::SidechainBlockBuilder,
+		sidechain_block_data_builder::SidechainBlockDataBuilder,
+		sidechain_header_builder::SidechainHeaderBuilder,
+	};
 
 	type TestBlockImport = BlockImportMock<ParentchainBlock, SignedSidechainBlock>;
 	type TestOCallApi = SidechainOCallApiMock<SignedSidechainBlock>;
@@ -236,6 +693,7 @@ mod tests {
 		SignedSidechainBlock,
 		TestBlockImport,
 		TestOCallApi,
+		OnchainMock,
 		ConfirmBlockImportMock,
 	>;
 
@@ -310,11 +768,410 @@ mod tests {
 		assert_eq!(1, sidechain_ocall_api.number_of_fetch_calls());
 	}
 
+	#[test]
+	fn preferred_sibling_of_last_imported_block_reorganizes_onto_fork() {
+		let parent_hash = H256::random();
+		let (preferred, not_preferred) = order_by_fork_choice(
+			signed_block(5, parent_hash, 1000),
+			signed_block(5, parent_hash, 2000),
+		);
+
+		let block_importer_mock = Arc::new(
+			BlockImportMock::<ParentchainBlock, _>::default()
+				.with_last_imported_block(not_preferred.block.clone())
+				.with_import_result_once(Err(Error::BlockAlreadyImported(5, 5))),
+		);
+		let sidechain_ocall_api =
+			Arc::new(SidechainOCallApiMock::<SignedSidechainBlock>::default());
+		let peer_syncer =
+			create_peer_syncer(block_importer_mock.clone(), sidechain_ocall_api.clone());
+
+		let parentchain_header = ParentchainHeaderBuilder::default().build();
+		peer_syncer.sync_block(preferred.clone(), &parentchain_header).unwrap();
+
+		assert_eq!(vec![not_preferred.hash()], block_importer_mock.get_reverted_blocks());
+		assert_eq!(vec![preferred.clone(), preferred], block_importer_mock.get_imported_blocks());
+		assert_eq!(0, sidechain_ocall_api.number_of_fetch_calls());
+	}
+
+	#[test]
+	fn not_preferred_sibling_of_last_imported_block_is_ignored() {
+		let parent_hash = H256::random();
+		let (preferred, not_preferred) = order_by_fork_choice(
+			signed_block(5, parent_hash, 1000),
+			signed_block(5, parent_hash, 2000),
+		);
+
+		let block_importer_mock = Arc::new(
+			BlockImportMock::<ParentchainBlock, _>::default()
+				.with_last_imported_block(preferred.block.clone())
+				.with_import_result_once(Err(Error::BlockAlreadyImported(5, 5))),
+		);
+		let sidechain_ocall_api =
+			Arc::new(SidechainOCallApiMock::<SignedSidechainBlock>::default());
+		let peer_syncer =
+			create_peer_syncer(block_importer_mock.clone(), sidechain_ocall_api.clone());
+
+		let parentchain_header = ParentchainHeaderBuilder::default().build();
+		peer_syncer.sync_block(not_preferred, &parentchain_header).unwrap();
+
+		assert!(block_importer_mock.get_reverted_blocks().is_empty());
+		assert_eq!(1, block_importer_mock.get_imported_blocks().len());
+	}
+
+	#[test]
+	fn final_last_imported_block_is_not_reverted_for_preferred_sibling() {
+		let parent_hash = H256::random();
+		let (preferred, not_preferred) = order_by_fork_choice(
+			signed_block(5, parent_hash, 1000),
+			signed_block(5, parent_hash, 2000),
+		);
+
+		let block_importer_mock = Arc::new(
+			BlockImportMock::<ParentchainBlock, _>::default()
+				.with_last_imported_block(not_preferred.block.clone())
+				.with_last_finalized_block_number(5)
+				.with_import_result_once(Err(Error::BlockAlreadyImported(5, 5))),
+		);
+		let sidechain_ocall_api =
+			Arc::new(SidechainOCallApiMock::<SignedSidechainBlock>::default());
+		let peer_syncer =
+			create_peer_syncer(block_importer_mock.clone(), sidechain_ocall_api.clone());
+
+		let parentchain_header = ParentchainHeaderBuilder::default().build();
+		peer_syncer.sync_block(preferred, &parentchain_header).unwrap();
+
+		assert!(block_importer_mock.get_reverted_blocks().is_empty());
+		assert_eq!(1, block_importer_mock.get_imported_blocks().len());
+	}
+
+	#[test]
+	fn longer_fork_is_fetched_from_peer_and_imported_after_revert() {
+		let parent_hash = H256::random();
+		let last_imported_block = signed_block(5, parent_hash, 1000);
+		let fork_block = signed_block(5, parent_hash, 2000);
+		let block_to_import = signed_block(6, fork_block.hash(), 3000);
+
+		let block_importer_mock = Arc::new(
+			BlockImportMock::<ParentchainBlock, _>::default()
+				.with_last_imported_block(last_imported_block.block.clone())
+				.with_import_result_once(Err(Error::BlockAncestryMismatch(
+					5,
+					last_imported_block.hash(),
+					"".to_string(),
+				))),
+		);
+		let sidechain_ocall_api = Arc::new(
+			SidechainOCallApiMock::<SignedSidechainBlock>::default()
+				.with_peer_fetch_blocks(vec![fork_block.clone()]),
+		);
+		let peer_syncer =
+			create_peer_syncer(block_importer_mock.clone(), sidechain_ocall_api.clone());
+
+		let parentchain_header = ParentchainHeaderBuilder::default().build();
+		peer_syncer.sync_block(block_to_import.clone(), &parentchain_header).unwrap();
+
+		assert_eq!(vec![last_imported_block.hash()], block_importer_mock.get_reverted_blocks());
+		assert_eq!(
+			vec![block_to_import.clone(), fork_block, block_to_import],
+			block_importer_mock.get_imported_blocks()
+		);
+		assert_eq!(1, sidechain_ocall_api.number_of_fetch_calls());
+	}
+
+	#[test]
+	fn preferred_sibling_is_ignored_if_last_imported_block_cannot_be_reverted() {
+		let parent_hash = H256::random();
+		let (preferred, not_preferred) = order_by_fork_choice(
+			signed_block(5, parent_hash, 1000),
+			signed_block(5, parent_hash, 2000),
+		);
+
+		let block_importer_mock = Arc::new(
+			BlockImportMock::<ParentchainBlock, _>::default()
+				.with_last_imported_block(not_preferred.block.clone())
+				.with_revert_failing()
+				.with_import_result_once(Err(Error::BlockAlreadyImported(5, 5))),
+		);
+		let sidechain_ocall_api =
+			Arc::new(SidechainOCallApiMock::<SignedSidechainBlock>::default());
+		let peer_syncer =
+			create_peer_syncer(block_importer_mock.clone(), sidechain_ocall_api.clone());
+
+		let parentchain_header = ParentchainHeaderBuilder::default().build();
+		peer_syncer.sync_block(preferred, &parentchain_header).unwrap();
+
+		assert!(block_importer_mock.get_reverted_blocks().is_empty());
+		assert_eq!(1, block_importer_mock.get_imported_blocks().len());
+	}
+
+	#[test]
+	fn last_imported_block_is_restored_if_fork_fails_to_import() {
+		let parent_hash = H256::random();
+		let (preferred, not_preferred) = order_by_fork_choice(
+			signed_block(5, parent_hash, 1000),
+			signed_block(5, parent_hash, 2000),
+		);
+
+		let block_importer_mock = Arc::new(
+			BlockImportMock::<ParentchainBlock, _>::default()
+				.with_last_imported_block(not_preferred.block.clone())
+				.with_import_result_once(Err(Error::BlockAlreadyImported(5, 5)))
+				.with_import_result_once(Err(Error::InvalidAuthority("".to_string()))),
+		);
+		let sidechain_ocall_api =
+			Arc::new(SidechainOCallApiMock::<SignedSidechainBlock>::default());
+		let peer_syncer =
+			create_peer_syncer(block_importer_mock.clone(), sidechain_ocall_api.clone());
+
+		let parentchain_header = ParentchainHeaderBuilder::default().build();
+		assert!(peer_syncer.sync_block(preferred, &parentchain_header).is_err());
+
+		assert_eq!(vec![not_preferred.hash()], block_importer_mock.get_reverted_blocks());
+		assert_eq!(vec![not_preferred.hash()], block_importer_mock.get_restored_blocks());
+	}
+
+	#[test]
+	fn missing_blocks_are_fetched_in_chunks_from_several_peers() {
+		let blocks = chain(9);
+		let block_to_import = blocks[8].clone();
+		let parentchain_header = ParentchainHeaderBuilder::default().build();
+
+		let block_importer_mock =
+			Arc::new(BlockImportMock::<ParentchainBlock, _>::default().with_import_result_once(
+				Err(Error::BlockAncestryMismatch(2, blocks[1].hash(), "".to_string())),
+			));
+		let sidechain_ocall_api = Arc::new(
+			SidechainOCallApiMock::<SignedSidechainBlock>::default()
+				.with_peer_block_range(peer_url(0), 1, blocks.clone())
+				.with_peer_block_range(peer_url(1), 1, blocks.clone()),
+		);
+		let peer_syncer = create_multi_peer_syncer(
+			block_importer_mock.clone(),
+			sidechain_ocall_api.clone(),
+			&parentchain_header,
+			2,
+			2,
+		);
+
+		peer_syncer.sync_block(block_to_import.clone(), &parentchain_header).unwrap();
+
+		let expected_imported_blocks: Vec<_> = Some(block_to_import.clone())
+			.into_iter()
+			.chain(blocks[2..9].iter().cloned())
+			.collect();
+		assert_eq!(expected_imported_blocks, block_importer_mock.get_imported_blocks());
+		assert_eq!(
+			vec![(peer_url(0), 3, 2), (peer_url(1), 5, 2), (peer_url(0), 7, 2)],
+			requested_ranges(&sidechain_ocall_api)
+		);
+		assert_eq!(0, sidechain_ocall_api.number_of_fetch_calls());
+	}
+
+	#[test]
+	fn own_validateer_is_not_asked_for_missing_blocks() {
+		let blocks = chain(5);
+		let parentchain_header = ParentchainHeaderBuilder::default().build();
+
+		let block_importer_mock =
+			Arc::new(BlockImportMock::<ParentchainBlock, _>::default().with_import_result_once(
+				Err(Error::BlockAncestryMismatch(2, blocks[1].hash(), "".to_string())),
+			));
+		let sidechain_ocall_api = Arc::new(
+			SidechainOCallApiMock::<SignedSidechainBlock>::default()
+				.with_peer_block_range(peer_url(0), 1, blocks.clone())
+				.with_peer_block_range(peer_url(1), 1, blocks.clone()),
+		);
+		let peer_syncer = create_multi_peer_syncer(
+			block_importer_mock.clone(),
+			sidechain_ocall_api.clone(),
+			&parentchain_header,
+			1,
+			2,
+		)
+		.with_own_account([0; 32].into());
+
+		peer_syncer.sync_block(blocks[4].clone(), &parentchain_header).unwrap();
+
+		assert_eq!(
+			vec![(peer_url(1), 3, 1), (peer_url(1), 4, 1)],
+			requested_ranges(&sidechain_ocall_api)
+		);
+	}
+
+	#[test]
+	fn chunk_with_broken_ancestry_is_fetched_from_another_peer() {
+		let blocks = chain(9);
+		let forged_blocks: Vec<_> = (1..=8).map(|n| signed_block(n, H256::random(), 0)).collect();
+		let block_to_import = blocks[8].clone();
+		let parentchain_header = ParentchainHeaderBuilder::default().build();
+
+		let block_importer_mock =
+			Arc::new(BlockImportMock::<ParentchainBlock, _>::default().with_import_result_once(
+				Err(Error::BlockAncestryMismatch(2, blocks[1].hash(), "".to_string())),
+			));
+		let sidechain_ocall_api = Arc::new(
+			SidechainOCallApiMock::<SignedSidechainBlock>::default()
+				.with_peer_block_range(peer_url(0), 1, blocks.clone())
+				.with_peer_block_range(peer_url(1), 1, forged_blocks),
+		);
+		let peer_syncer = create_multi_peer_syncer(
+			block_importer_mock.clone(),
+			sidechain_ocall_api.clone(),
+			&parentchain_header,
+			3,
+			2,
+		);
+
+		peer_syncer.sync_block(block_to_import.clone(), &parentchain_header).unwrap();
+
+		let expected_imported_blocks: Vec<_> = Some(block_to_import.clone())
+			.into_iter()
+			.chain(blocks[2..9].iter().cloned())
+			.collect();
+		assert_eq!(expected_imported_blocks, block_importer_mock.get_imported_blocks());
+		assert_eq!(
+			vec![(peer_url(0), 3, 3), (peer_url(1), 6, 3), (peer_url(0), 6, 3)],
+			requested_ranges(&sidechain_ocall_api)
+		);
+	}
+
+	#[test]
+	fn peer_sync_fails_if_chunk_cannot_be_fetched_from_any_peer() {
+		let blocks = chain(6);
+		let parentchain_header = ParentchainHeaderBuilder::default().build();
+
+		let block_importer_mock =
+			Arc::new(BlockImportMock::<ParentchainBlock, _>::default().with_import_result_once(
+				Err(Error::BlockAncestryMismatch(2, blocks[1].hash(), "".to_string())),
+			));
+		// Peer 0 serves blocks up to number 4, peer 1 does not serve any blocks.
+		let sidechain_ocall_api = Arc::new(
+			SidechainOCallApiMock::<SignedSidechainBlock>::default().with_peer_block_range(
+				peer_url(0),
+				1,
+				blocks[..4].to_vec(),
+			),
+		);
+		let peer_syncer = create_multi_peer_syncer(
+			block_importer_mock.clone(),
+			sidechain_ocall_api.clone(),
+			&parentchain_header,
+			1,
+			2,
+		);
+
+		let sync_result = peer_syncer.sync_block(blocks[5].clone(), &parentchain_header);
+
+		assert_matches!(sync_result, Err(Error::PeerSyncFailed(_)));
+		// Blocks 3 and 4 are imported, block 5 cannot be fetched.
+		assert_eq!(
+			vec![blocks[5].clone(), blocks[2].clone(), blocks[3].clone()],
+			block_importer_mock.get_imported_blocks()
+		);
+	}
+
+	#[test]
+	fn chunk_ancestry_verification_detects_gaps_and_wrong_last_block() {
+		let blocks = chain(4);
+		let shard = blocks[0].block.header.shard_id;
+
+		assert!(verify_chunk_ancestry(&blocks[1..4], 2, 4, &shard, None).is_ok());
+		assert!(verify_chunk_ancestry(&blocks[1..4], 2, 4, &shard, Some(blocks[3].hash())).is_ok());
+		assert!(verify_chunk_ancestry(&blocks[1..3], 2, 4, &shard, None).is_err());
+		assert!(verify_chunk_ancestry(&[blocks[1].clone(), blocks[3].clone()], 2, 3, &shard, None)
+			.is_err());
+		assert!(verify_chunk_ancestry(&blocks[1..4], 2, 4, &shard, Some(blocks[2].hash())).is_err());
+		assert!(verify_chunk_ancestry(&blocks[1..4], 2, 4, &H256::random(), None).is_err());
+	}
+
+	/// Consecutive blocks `1..=length`, each building on its predecessor.
+	fn chain(length: u64) -> Vec<SignedSidechainBlock> {
+		let mut parent_hash = H256::default();
+		(1..=length)
+			.map(|n| {
+				let block = signed_block(n, parent_hash, n * 1000);
+				parent_hash = block.hash();
+				block
+			})
+			.collect()
+	}
+
+	fn peer_url(index: u8) -> String {
+		format!("wss://peer-{}:2000", index)
+	}
+
+	fn requested_ranges(ocall_api: &TestOCallApi) -> Vec<(String, u64, u64)> {
+		ocall_api
+			.block_range_requests()
+			.into_iter()
+			.map(|r| (r.peer_url, r.from_block_number, r.limit))
+			.collect()
+	}
+
+	fn signed_block(block_number: u64, parent_hash: H256, timestamp: u64) -> SignedSidechainBlock {
+		let header = SidechainHeaderBuilder::default()
+			.with_block_number(block_number)
+			.with_parent_hash(parent_hash)
+			.build();
+		let block_data = SidechainBlockDataBuilder::default().with_timestamp(timestamp).build();
+		SidechainBlockBuilder::default()
+			.with_header(header)
+			.with_block_data(block_data)
+			.build_signed()
+	}
+
+	/// Returns the given blocks of equal height as (preferred, not preferred).
+	fn order_by_fork_choice(
+		block_a: SignedSidechainBlock,
+		block_b: SignedSidechainBlock,
+	) -> (SignedSidechainBlock, SignedSidechainBlock) {
+		if author_slot_hash(&block_a.block) < author_slot_hash(&block_b.block) {
+			(block_a, block_b)
+		} else {
+			(block_b, block_a)
+		}
+	}
+
 	fn create_peer_syncer(
 		block_importer: Arc<TestBlockImport>,
 		ocall_api: Arc<TestOCallApi>,
 	) -> TestPeerBlockSync {
 		let import_confirmation_handler = Arc::new(ConfirmBlockImportMock {});
-		TestPeerBlockSync::new(block_importer, ocall_api, import_confirmation_handler)
+		TestPeerBlockSync::new(
+			block_importer,
+			ocall_api,
+			Arc::new(OnchainMock::default()),
+			import_confirmation_handler,
+		)
+	}
+
+	/// Creates a multi peer syncer, with validateers `peer_url(0)` and `peer_url(1)`.
+	fn create_multi_peer_syncer(
+		block_importer: Arc<TestBlockImport>,
+		ocall_api: Arc<TestOCallApi>,
+		parentchain_header: &ParentchainHeader,
+		chunk_size: u64,
+		max_attempts_per_chunk: usize,
+	) -> TestPeerBlockSync {
+		let validateers = (0..2)
+			.map(|i| {
+				Enclave::new([i; 32].into(), Default::default(), Default::default(), peer_url(i))
+			})
+			.collect();
+		let validateer_fetcher = Arc::new(
+			OnchainMock::default().add_validateer_set(parentchain_header, Some(validateers)),
+		);
+		TestPeerBlockSync::new(
+			block_importer,
+			ocall_api,
+			validateer_fetcher,
+			Arc::new(ConfirmBlockImportMock {}),
+		)
+		.with_sync_mode(PeerSyncMode::MultiPeer {
+			chunk_size,
+			max_attempts_per_chunk,
+			max_parallel_requests: 2,
+		})
 	}
 }
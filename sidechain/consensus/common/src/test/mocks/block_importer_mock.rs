@@ -21,7 +21,10 @@ use itp_sgx_crypto::aes::Aes;
 use itp_sgx_externalities::SgxExternalities;
 use itp_test::mock::onchain_mock::OnchainMock;
 use itp_types::H256;
-use its_primitives::traits::{ShardIdentifierFor, SignedBlock as SignedSidechainBlockTrait};
+use its_primitives::{
+	traits::{ShardIdentifierFor, SignedBlock as SignedSidechainBlockTrait},
+	types::{BlockHash, BlockNumber},
+};
 use its_state::SidechainDB;
 use sp_core::Pair;
 use sp_runtime::traits::Block as ParentchainBlockTrait;
@@ -36,6 +39,11 @@ where
 {
 	import_result: RwLock<VecDeque<Result<ParentchainBlock::Header>>>,
 	imported_blocks: RwLock<Vec<SignedSidechainBlock>>,
+	last_imported_block: Option<SignedSidechainBlock::Block>,
+	reverted_blocks: RwLock<Vec<BlockHash>>,
+	restored_blocks: RwLock<Vec<BlockHash>>,
+	revert_fails: bool,
+	last_finalized_block_number: Option<BlockNumber>,
 	_phantom: PhantomData<(ParentchainBlock, SignedSidechainBlock)>,
 }
 
@@ -63,9 +71,32 @@ where
 		self
 	}
 
+	pub fn with_last_imported_block(mut self, block: SignedSidechainBlock::Block) -> Self {
+		self.last_imported_block = Some(block);
+		self
+	}
+
+	pub fn with_revert_failing(mut self) -> Self {
+		self.revert_fails = true;
+		self
+	}
+
+	pub fn with_last_finalized_block_number(mut self, block_number: BlockNumber) -> Self {
+		self.last_finalized_block_number = Some(block_number);
+		self
+	}
+
 	pub fn get_imported_blocks(&self) -> Vec<SignedSidechainBlock> {
 		(*self.imported_blocks.read().unwrap()).clone()
 	}
+
+	pub fn get_reverted_blocks(&self) -> Vec<BlockHash> {
+		(*self.reverted_blocks.read().unwrap()).clone()
+	}
+
+	pub fn get_restored_blocks(&self) -> Vec<BlockHash> {
+		(*self.restored_blocks.read().unwrap()).clone()
+	}
 }
 
 impl<ParentchainBlock, SignedSidechainBlock> Default
@@ -79,6 +110,11 @@ where
 		BlockImportMock {
 			import_result: RwLock::default(),
 			imported_blocks: RwLock::default(),
+			last_imported_block: None,
+			reverted_blocks: RwLock::default(),
+			restored_blocks: RwLock::default(),
+			revert_fails: false,
+			last_finalized_block_number: None,
 			_phantom: Default::default(),
 		}
 	}
@@ -96,6 +132,7 @@ where
 	type SidechainState = SidechainDB<SignedSidechainBlock::Block, SgxExternalities>;
 	type StateCrypto = Aes;
 	type Context = OnchainMock;
+	type RevertedBlock = BlockHash;
 
 	fn verifier(&self, _state: Self::SidechainState) -> Self::Verifier {
 		todo!()
@@ -147,6 +184,57 @@ where
 		todo!()
 	}
 
+	fn get_last_imported_block(
+		&self,
+		_shard: &ShardIdentifierFor<SignedSidechainBlock>,
+	) -> Result<Option<SignedSidechainBlock::Block>> {
+		Ok(self.last_imported_block.clone())
+	}
+
+	fn revert_block(
+		&self,
+		_shard: &ShardIdentifierFor<SignedSidechainBlock>,
+		block_hash: &BlockHash,
+	) -> Result<BlockHash> {
+		if self.revert_fails {
+			return Err(Error::CannotRevertBlock(*block_hash))
+		}
+		self.reverted_blocks.write().unwrap().push(*block_hash);
+		Ok(*block_hash)
+	}
+
+	fn restore_reverted_block(
+		&self,
+		_shard: &ShardIdentifierFor<SignedSidechainBlock>,
+		reverted_block: BlockHash,
+	) -> Result<()> {
+		self.restored_blocks.write().unwrap().push(reverted_block);
+		Ok(())
+	}
+
+	fn vote_for_finality(
+		&self,
+		_sidechain_block: &SignedSidechainBlock::Block,
+		_parentchain_header: &ParentchainBlock::Header,
+	) -> Result<()> {
+		Ok(())
+	}
+
+	fn last_finalized_block_number(
+		&self,
+		_shard: &ShardIdentifierFor<SignedSidechainBlock>,
+	) -> Option<BlockNumber> {
+		self.last_finalized_block_number
+	}
+
+	fn report_equivocation(
+		&self,
+		_signed_sidechain_block: &SignedSidechainBlock,
+		_parentchain_header: &ParentchainBlock::Header,
+	) -> Result<()> {
+		Ok(())
+	}
+
 	fn cleanup(&self, _signed_sidechain_block: &SignedSidechainBlock) -> Result<()> {
 		todo!()
 	}
@@ -33,17 +33,32 @@ extern crate sgx_tstd as std;
 
 use codec::Encode;
 use derive_more::From;
+use itp_enclave_metrics::EnclaveMetric;
 use itp_time_utils::{duration_difference, duration_now};
 use itp_types::OpaqueCall;
 use its_consensus_common::{Error as ConsensusError, Proposer};
 use its_primitives::traits::{
-	Block as SidechainBlockTrait, Header as HeaderTrait, ShardIdentifierFor,
+	Block as SidechainBlockTrait, BlockData, Header as HeaderTrait, ShardIdentifierFor,
 	SignedBlock as SignedSidechainBlockTrait,
 };
 use log::*;
 pub use slots::*;
 use sp_runtime::traits::{Block as ParentchainBlockTrait, Header as ParentchainHeaderTrait};
-use std::{fmt::Debug, time::Duration, vec::Vec};
+use std::{
+	collections::VecDeque,
+	fmt::Debug,
+	string::ToString,
+	sync::Arc,
+	thread,
+	time::{Duration, Instant},
+	vec::Vec,
+};
+
+#[cfg(feature = "sgx")]
+use std::sync::SgxMutex as Mutex;
+
+#[cfg(feature = "std")]
+use std::sync::Mutex;
 
 #[cfg(feature = "std")]
 mod slot_stream;
@@ -92,9 +107,11 @@ pub trait SlotWorker<ParentchainBlock: ParentchainBlockTrait> {
 
 /// A slot worker scheduler that should be invoked at every new slot.
 ///
-/// It manages the timeslots of individual per shard `SlotWorker`s. It gives each shard an equal
-/// amount of time to produce it's result, equally distributing leftover time from a previous shard's
-/// slot share to all subsequent slots.
+/// It manages the timeslots of individual per shard `SlotWorker`s. Up to
+/// [`SimpleSlotWorker::max_parallel_shards`] shards are served in parallel, each by its own
+/// worker. Every shard gets an equal amount of time to produce it's result, equally distributing
+/// leftover time from a previous shard's slot share to all subsequent slots. A shard that exceeds
+/// its share only delays the shards served by the same worker.
 pub trait PerShardSlotWorkerScheduler<ParentchainBlock: ParentchainBlockTrait> {
 	/// Output generated after a slot
 	type Output: Send + 'static;
@@ -177,6 +194,18 @@ pub trait SimpleSlotWorker<ParentchainBlock: ParentchainBlockTrait> {
 		&self,
 	) -> Result<Option<ParentchainBlock::Header>, ConsensusError>;
 
+	/// Slot duration in effect at the given parentchain header.
+	fn slot_duration(&self, header: &ParentchainBlock::Header) -> Result<Duration, ConsensusError>;
+
+	/// Record a metric about the slots claimed and the blocks proposed.
+	fn update_metric(&self, metric: EnclaveMetric);
+
+	/// Maximum number of shards to produce blocks for in parallel. All but one of them are
+	/// served on additional threads.
+	fn max_parallel_shards(&self) -> usize {
+		1
+	}
+
 	/// Implements [`SlotWorker::on_slot`]. This is an adaption from
 	/// substrate's sc-consensus-slots implementation. There, the slot worker handles all the
 	/// scheduling itself. Unfortunately, we can't use the same principle in the enclave due to some
@@ -214,6 +243,34 @@ pub trait SimpleSlotWorker<ParentchainBlock: ParentchainBlockTrait> {
 			},
 		};
 
+		// The slot was derived with the slot duration in effect at the last imported parentchain
+		// block. If the block we build upon changes the slot duration, the slot is not valid anymore.
+		if latest_parentchain_header.hash() != slot_info.last_imported_parentchain_head.hash() {
+			let slot_durations = self
+				.slot_duration(&slot_info.last_imported_parentchain_head)
+				.and_then(|current| {
+					self.slot_duration(&latest_parentchain_header).map(|next| (current, next))
+				});
+			match slot_durations {
+				Ok((current, next)) if current != next => {
+					info!(
+						target: logging_target,
+						"Skipping proposal slot {}, the slot duration changes from {} ms to {} ms at parentchain block {:?}",
+						*slot,
+						current.as_millis(),
+						next.as_millis(),
+						latest_parentchain_header.hash(),
+					);
+					return None
+				},
+				Ok(_) => {},
+				Err(e) => {
+					warn!(target: logging_target, "Failed to fetch slot duration: {:?}", e);
+					return None
+				},
+			}
+		}
+
 		let epoch_data = match self.epoch_data(&latest_parentchain_header, slot) {
 			Ok(epoch_data) => epoch_data,
 			Err(e) => {
@@ -237,7 +294,16 @@ pub trait SimpleSlotWorker<ParentchainBlock: ParentchainBlockTrait> {
 			);
 		}
 
-		let _claim = self.claim_slot(&latest_parentchain_header, slot, &epoch_data)?;
+		let _claim = match self.claim_slot(&latest_parentchain_header, slot, &epoch_data) {
+			Some(claim) => {
+				self.update_metric(EnclaveMetric::SidechainSlotClaimed);
+				claim
+			},
+			None => {
+				self.update_metric(EnclaveMetric::SidechainSlotSkipped);
+				return None
+			},
+		};
 
 		// Import the peeked parentchain header(s).
 		let last_imported_header =
@@ -260,6 +326,7 @@ pub trait SimpleSlotWorker<ParentchainBlock: ParentchainBlockTrait> {
 			},
 		};
 
+		let proposing_start = Instant::now();
 		let proposing = match proposer.propose(remaining_duration) {
 			Ok(p) => p,
 			Err(e) => {
@@ -267,6 +334,10 @@ pub trait SimpleSlotWorker<ParentchainBlock: ParentchainBlockTrait> {
 				return None
 			},
 		};
+		self.update_metric(EnclaveMetric::SidechainBlockProposalTime(
+			proposing_start.elapsed().as_millis() as u64,
+			remaining_duration.as_millis() as u64,
+		));
 
 		if !timestamp_within_slot(&slot_info, &proposing.block) {
 			warn!(
@@ -286,6 +357,14 @@ pub trait SimpleSlotWorker<ParentchainBlock: ParentchainBlockTrait> {
 			);
 		}
 
+		self.update_metric(EnclaveMetric::SidechainBlockProposed(format!(
+			"{:?}",
+			proposing.block.block().header().shard_id()
+		)));
+		self.update_metric(EnclaveMetric::SidechainBlockOperations(
+			proposing.block.block().block_data().signed_top_hashes().len() as u64,
+		));
+
 		info!("Proposing sidechain block (number: {}, hash: {}) based on parentchain block (number: {:?}, hash: {:?})",
 			proposing.block.block().header().block_number(), proposing.block.hash(),
 			latest_parentchain_header.number(), latest_parentchain_header.hash()
@@ -312,8 +391,10 @@ impl<ParentchainBlock: ParentchainBlockTrait, T: SimpleSlotWorker<ParentchainBlo
 	}
 }
 
-impl<ParentchainBlock: ParentchainBlockTrait, T: SimpleSlotWorker<ParentchainBlock>>
-	PerShardSlotWorkerScheduler<ParentchainBlock> for T
+impl<ParentchainBlock, T> PerShardSlotWorkerScheduler<ParentchainBlock> for T
+where
+	ParentchainBlock: ParentchainBlockTrait,
+	T: SimpleSlotWorker<ParentchainBlock> + Clone + Send + 'static,
 {
 	type Output = Vec<SlotResult<T::Output>>;
 
@@ -326,46 +407,118 @@ impl<ParentchainBlock: ParentchainBlockTrait, T: SimpleSlotWorker<ParentchainBlo
 	) -> Self::Output {
 		let logging_target = SimpleSlotWorker::logging_target(self);
 
-		let mut remaining_shards = shards.len();
-		let mut slot_results = Vec::with_capacity(remaining_shards);
-
-		for shard in shards.into_iter() {
-			let now = duration_now(); // It's important we have a common `now` for all following computations.
-			let shard_remaining_duration = duration_difference(now, slot_info.ends_at)
-				.and_then(|time| time.checked_div(remaining_shards as u32))
-				.unwrap_or_default();
+		let number_of_workers = self.max_parallel_shards().clamp(1, shards.len().max(1));
+		let shard_queue = Arc::new(Mutex::new(ShardQueue {
+			shards: shards.into_iter().enumerate().collect(),
+			workers: 1,
+		}));
+
+		let mut worker_handles = Vec::with_capacity(number_of_workers - 1);
+		for _ in 1..number_of_workers {
+			let mut worker = self.clone();
+			let slot_info = slot_info.clone();
+			let queue = shard_queue.clone();
+
+			match thread::Builder::new()
+				.name("shard_slot_worker".to_string())
+				.spawn(move || serve_shards(&mut worker, &slot_info, &queue))
+			{
+				Ok(handle) => {
+					worker_handles.push(handle);
+					if let Ok(mut queue) = shard_queue.lock() {
+						queue.workers += 1;
+					}
+				},
+				Err(e) =>
+					warn!(target: logging_target, "Failed to spawn shard slot worker: {:?}", e),
+			}
+		}
 
-			// important to check against millis here. We had the corner-case in production
-			// setup where `shard_remaining_duration` contained only nanos.
-			if shard_remaining_duration.as_millis() == u128::default() {
-				info!(
-					target: logging_target,
-					"⌛️ Could not produce blocks for all shards; block production took too long",
-				);
+		let mut slot_results = serve_shards(self, &slot_info, &shard_queue);
 
-				return slot_results
+		for handle in worker_handles {
+			match handle.join() {
+				Ok(results) => slot_results.extend(results),
+				Err(e) => warn!(target: logging_target, "Shard slot worker panicked: {:?}", e),
 			}
+		}
 
-			let shard_slot_ends_at = now + shard_remaining_duration;
-			let shard_slot = SlotInfo::new(
-				slot_info.slot,
-				now,
-				shard_remaining_duration,
-				shard_slot_ends_at,
-				slot_info.last_imported_parentchain_head.clone(),
-			);
+		// Keep the order of the shards, independent of which worker served them.
+		slot_results.sort_by_key(|(shard_index, _)| *shard_index);
+		slot_results.into_iter().map(|(_, slot_result)| slot_result).collect()
+	}
+}
 
-			match SimpleSlotWorker::on_slot(self, shard_slot, shard) {
-				Some(res) => slot_results.push(res),
-				None => info!(
-					target: logging_target,
-					"Did not produce a block for slot {} in shard {:?}", *slot_info.slot, shard
-				),
+/// Shards that are still to be served in a slot, shared between the workers serving them.
+struct ShardQueue<ShardIdentifier> {
+	shards: VecDeque<(usize, ShardIdentifier)>,
+	workers: usize,
+}
+
+/// Serve shards from the queue until it is empty or the slot time is used up.
+///
+/// Returns the slot results along with the index of the shard they belong to.
+fn serve_shards<ParentchainBlock, Worker>(
+	worker: &mut Worker,
+	slot_info: &SlotInfo<ParentchainBlock>,
+	shard_queue: &Mutex<ShardQueue<ShardIdentifierFor<Worker::Output>>>,
+) -> Vec<(usize, SlotResult<Worker::Output>)>
+where
+	ParentchainBlock: ParentchainBlockTrait,
+	Worker: SimpleSlotWorker<ParentchainBlock>,
+{
+	let logging_target = worker.logging_target();
+	let mut slot_results = Vec::new();
+
+	loop {
+		let (shard_index, shard, remaining_rounds) = {
+			let mut queue = match shard_queue.lock() {
+				Ok(queue) => queue,
+				Err(e) => {
+					warn!(target: logging_target, "Shard queue lock is poisoned: {:?}", e);
+					return slot_results
+				},
+			};
+			let remaining_shards = queue.shards.len();
+			match queue.shards.pop_front() {
+				// Each worker still has to serve this many shards, including this one.
+				Some((index, shard)) =>
+					(index, shard, (remaining_shards + queue.workers - 1) / queue.workers),
+				None => return slot_results,
 			}
+		};
+
+		let now = duration_now(); // It's important we have a common `now` for all following computations.
+		let shard_remaining_duration = duration_difference(now, slot_info.ends_at)
+			.and_then(|time| time.checked_div(remaining_rounds as u32))
+			.unwrap_or_default();
+
+		// important to check against millis here. We had the corner-case in production
+		// setup where `shard_remaining_duration` contained only nanos.
+		if shard_remaining_duration.as_millis() == u128::default() {
+			info!(
+				target: logging_target,
+				"⌛️ Could not produce blocks for all shards; block production took too long",
+			);
 
-			remaining_shards -= 1;
+			return slot_results
 		}
 
-		slot_results
+		let shard_slot_ends_at = now + shard_remaining_duration;
+		let shard_slot = SlotInfo::new(
+			slot_info.slot,
+			now,
+			shard_remaining_duration,
+			shard_slot_ends_at,
+			slot_info.last_imported_parentchain_head.clone(),
+		);
+
+		match SimpleSlotWorker::on_slot(worker, shard_slot, shard) {
+			Some(res) => slot_results.push((shard_index, res)),
+			None => info!(
+				target: logging_target,
+				"Did not produce a block for slot {} in shard {:?}", *slot_info.slot, shard
+			),
+		}
 	}
 }
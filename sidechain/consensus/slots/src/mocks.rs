@@ -16,10 +16,16 @@
 */
 
 use crate::{slots::Slot, SimpleSlotWorker, SlotInfo, SlotResult};
+use itp_enclave_metrics::EnclaveMetric;
 use its_consensus_common::{Proposal, Proposer, Result};
 use its_primitives::{traits::ShardIdentifierFor, types::SignedBlock as SignedSidechainBlock};
 use sp_runtime::traits::{Block as ParentchainBlockTrait, Header as ParentchainHeaderTrait};
-use std::{marker::PhantomData, thread, time::Duration};
+use std::{
+	marker::PhantomData,
+	sync::{Arc, Mutex},
+	thread,
+	time::Duration,
+};
 
 #[derive(Default)]
 pub(crate) struct ProposerMock<ParentchainBlock> {
@@ -35,13 +41,59 @@ where
 	}
 }
 
-#[derive(Default)]
+/// Slot worker mock that records the slot infos it is called with. Clones share the records.
+#[derive(Clone)]
 pub(crate) struct SimpleSlotWorkerMock<B>
 where
 	B: ParentchainBlockTrait,
 {
-	pub slot_infos: Vec<SlotInfo<B>>,
-	pub slot_time_spent: Option<Duration>,
+	slot_infos: Arc<Mutex<Vec<(ShardIdentifierFor<SignedSidechainBlock>, SlotInfo<B>)>>>,
+	slot_time_spent: Option<Duration>,
+	slow_shard: Option<(ShardIdentifierFor<SignedSidechainBlock>, Duration)>,
+	max_parallel_shards: usize,
+}
+
+impl<B> SimpleSlotWorkerMock<B>
+where
+	B: ParentchainBlockTrait,
+{
+	pub fn new(slot_time_spent: Option<Duration>) -> Self {
+		Self {
+			slot_infos: Default::default(),
+			slot_time_spent,
+			slow_shard: None,
+			max_parallel_shards: 1,
+		}
+	}
+
+	pub fn with_max_parallel_shards(mut self, max_parallel_shards: usize) -> Self {
+		self.max_parallel_shards = max_parallel_shards;
+		self
+	}
+
+	/// The given shard spends `time_spent` instead of `slot_time_spent`, regardless of its share
+	/// of the slot.
+	pub fn with_slow_shard(
+		mut self,
+		shard: ShardIdentifierFor<SignedSidechainBlock>,
+		time_spent: Duration,
+	) -> Self {
+		self.slow_shard = Some((shard, time_spent));
+		self
+	}
+
+	pub fn slot_infos(&self) -> Vec<SlotInfo<B>> {
+		self.slot_infos
+			.lock()
+			.unwrap()
+			.iter()
+			.map(|(_, slot_info)| slot_info.clone())
+			.collect()
+	}
+
+	pub fn served_shards(&self) -> Vec<ShardIdentifierFor<SignedSidechainBlock>> {
+		self.slot_infos.lock().unwrap().iter().map(|(shard, _)| *shard).collect()
+	}
 }
 
 impl<B> SimpleSlotWorker<B> for SimpleSlotWorkerMock<B>
@@ -100,14 +152,29 @@ where
 		todo!()
 	}
 
+	fn slot_duration(&self, _header: &B::Header) -> Result<Duration> {
+		todo!()
+	}
+
+	fn update_metric(&self, _metric: EnclaveMetric) {}
+
+	fn max_parallel_shards(&self) -> usize {
+		self.max_parallel_shards
+	}
+
 	fn on_slot(
 		&mut self,
 		slot_info: SlotInfo<B>,
-		_shard: ShardIdentifierFor<Self::Output>,
+		shard: ShardIdentifierFor<Self::Output>,
 	) -> Option<SlotResult<Self::Output>> {
-		self.slot_infos.push(slot_info);
+		let sleep_duration = match self.slow_shard {
+			Some((slow_shard, time_spent)) if slow_shard == shard => Some(time_spent),
+			_ => self.slot_time_spent,
+		};
+
+		self.slot_infos.lock().unwrap().push((shard, slot_info));
 
-		if let Some(sleep_duration) = self.slot_time_spent {
+		if let Some(sleep_duration) = sleep_duration {
 			thread::sleep(sleep_duration);
 		}
 
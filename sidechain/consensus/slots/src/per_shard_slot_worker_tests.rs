@@ -21,14 +21,14 @@ use itp_settings::sidechain::SLOT_DURATION;
 use itp_time_utils::duration_now;
 use itp_types::{Block as ParentchainBlock, ShardIdentifier};
 use its_block_verification::slot::slot_from_timestamp_and_duration;
+use std::time::Instant;
 
 type TestSlotWorker = SimpleSlotWorkerMock<ParentchainBlock>;
 
 #[test]
 fn slot_timings_are_correct_with_multiple_shards() {
 	let slot_info = slot_info_from_now();
-	let mut slot_worker =
-		TestSlotWorker { slot_infos: Vec::new(), slot_time_spent: Some(SLOT_DURATION / 10) };
+	let mut slot_worker = TestSlotWorker::new(Some(SLOT_DURATION / 10));
 
 	let shards =
 		vec![ShardIdentifier::default(), ShardIdentifier::default(), ShardIdentifier::default()];
@@ -36,10 +36,11 @@ fn slot_timings_are_correct_with_multiple_shards() {
 	let _slot_results =
 		PerShardSlotWorkerScheduler::on_slot(&mut slot_worker, slot_info.clone(), shards.clone());
 
-	assert_eq!(slot_worker.slot_infos.len(), shards.len());
+	let shard_slot_infos = slot_worker.slot_infos();
+	assert_eq!(shard_slot_infos.len(), shards.len());
 
 	// end-time of the first shard slot should not exceed timestamp + 1/(n_shards) of the total slot duration
-	let first_shard_slot_end_time = slot_worker.slot_infos.first().unwrap().ends_at.as_millis();
+	let first_shard_slot_end_time = shard_slot_infos.first().unwrap().ends_at.as_millis();
 	let expected_upper_bound = (slot_info.timestamp.as_millis()
 		+ SLOT_DURATION.as_millis().checked_div(shards.len() as u128).unwrap())
 		+ 2u128;
@@ -51,7 +52,7 @@ fn slot_timings_are_correct_with_multiple_shards() {
 	);
 
 	// none of the shard slot end times should exceed the global slot end time
-	for shard_slot_info in slot_worker.slot_infos {
+	for shard_slot_info in shard_slot_infos {
 		assert!(
 			shard_slot_info.ends_at.as_millis() <= slot_info.ends_at.as_millis(),
 			"shard slot info ends at: {} ms, total slot info ends at: {} ms",
@@ -64,8 +65,7 @@ fn slot_timings_are_correct_with_multiple_shards() {
 #[test]
 fn if_shard_takes_up_all_slot_time_subsequent_shards_are_not_served() {
 	let slot_info = slot_info_from_now();
-	let mut slot_worker =
-		TestSlotWorker { slot_infos: Vec::new(), slot_time_spent: Some(SLOT_DURATION) };
+	let mut slot_worker = TestSlotWorker::new(Some(SLOT_DURATION));
 
 	let shards =
 		vec![ShardIdentifier::default(), ShardIdentifier::default(), ShardIdentifier::default()];
@@ -73,7 +73,82 @@ fn if_shard_takes_up_all_slot_time_subsequent_shards_are_not_served() {
 	let _slot_results =
 		PerShardSlotWorkerScheduler::on_slot(&mut slot_worker, slot_info.clone(), shards.clone());
 
-	assert_eq!(1, slot_worker.slot_infos.len());
+	assert_eq!(1, slot_worker.slot_infos().len());
+}
+
+#[test]
+fn shards_are_served_in_parallel() {
+	let slot_info = slot_info_from_now();
+	let mut slot_worker = TestSlotWorker::new(Some(SLOT_DURATION / 2)).with_max_parallel_shards(3);
+
+	let shards = shards(3);
+
+	let start = Instant::now();
+	let _slot_results =
+		PerShardSlotWorkerScheduler::on_slot(&mut slot_worker, slot_info.clone(), shards.clone());
+
+	// Served one after the other, the shards would take 1.5 times the slot duration.
+	assert!(start.elapsed() < SLOT_DURATION, "elapsed: {} ms", start.elapsed().as_millis());
+	assert_served(&slot_worker, &shards);
+}
+
+#[test]
+fn slow_shard_does_not_starve_other_shards() {
+	let slot_info = slot_info_from_now();
+	let shards = shards(3);
+	let mut slot_worker = TestSlotWorker::new(Some(SLOT_DURATION / 10))
+		.with_max_parallel_shards(2)
+		.with_slow_shard(shards[0], SLOT_DURATION * 2);
+
+	let _slot_results =
+		PerShardSlotWorkerScheduler::on_slot(&mut slot_worker, slot_info.clone(), shards.clone());
+
+	assert_served(&slot_worker, &shards);
+}
+
+#[test]
+fn parallel_shard_slots_end_within_slot() {
+	let slot_info = slot_info_from_now();
+	let mut slot_worker = TestSlotWorker::new(Some(SLOT_DURATION / 10)).with_max_parallel_shards(2);
+
+	let shards = shards(5);
+
+	let _slot_results =
+		PerShardSlotWorkerScheduler::on_slot(&mut slot_worker, slot_info.clone(), shards.clone());
+
+	let shard_slot_infos = slot_worker.slot_infos();
+	assert_eq!(shard_slot_infos.len(), shards.len());
+	for shard_slot_info in shard_slot_infos {
+		assert!(
+			shard_slot_info.ends_at.as_millis() <= slot_info.ends_at.as_millis(),
+			"shard slot info ends at: {} ms, total slot info ends at: {} ms",
+			shard_slot_info.ends_at.as_millis(),
+			slot_info.ends_at.as_millis()
+		);
+	}
+}
+
+#[test]
+fn max_parallel_shards_larger_than_number_of_shards_works() {
+	let slot_info = slot_info_from_now();
+	let mut slot_worker = TestSlotWorker::new(None).with_max_parallel_shards(10);
+
+	let shards = shards(2);
+
+	let _slot_results =
+		PerShardSlotWorkerScheduler::on_slot(&mut slot_worker, slot_info.clone(), shards.clone());
+
+	assert_served(&slot_worker, &shards);
+}
+
+fn shards(count: u64) -> Vec<ShardIdentifier> {
+	(0..count).map(ShardIdentifier::from_low_u64_be).collect()
+}
+
+fn assert_served(slot_worker: &TestSlotWorker, shards: &[ShardIdentifier]) {
+	let mut served_shards = slot_worker.served_shards();
+	served_shards.sort();
+	assert_eq!(served_shards, shards.to_vec());
 }
 
 fn slot_info_from_now() -> SlotInfo<ParentchainBlock> {
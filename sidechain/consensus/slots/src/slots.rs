@@ -149,6 +149,25 @@ where
 	Ok(Some(SlotInfo::new(slot, timestamp, duration, slot_ends_time, header)))
 }
 
+/// Slot info of the slot `timestamp` falls into, regardless of whether it has been yielded before.
+///
+/// Used to run a slot again later on, e.g. by backup authors once the slot author missed it.
+pub fn current_slot<ParentchainBlock>(
+	timestamp: Duration,
+	duration: Duration,
+	header: ParentchainBlock::Header,
+) -> Result<SlotInfo<ParentchainBlock>, ConsensusError>
+where
+	ParentchainBlock: ParentchainBlockTrait,
+{
+	if duration == Default::default() {
+		return Err(ConsensusError::Other("Tried to get current slot with 0 duration".into()))
+	}
+
+	let slot = slot_from_timestamp_and_duration(timestamp, duration);
+	Ok(SlotInfo::new(slot, timestamp, duration, slot_ends_at(slot, duration), header))
+}
+
 pub trait GetLastSlot {
 	fn get_last_slot(&self) -> Result<Slot, ConsensusError>;
 	fn set_last_slot(&mut self, slot: Slot) -> Result<(), ConsensusError>;
@@ -415,4 +434,31 @@ mod tests {
 			"Tried to yield next slot with 0 duration",
 		)
 	}
+
+	#[test]
+	fn current_slot_is_returned_even_if_already_yielded() {
+		let now = duration_now();
+		let slot = current_slot::<ParentchainBlock>(
+			now,
+			SLOT_DURATION,
+			ParentchainHeaderBuilder::default().build(),
+		)
+		.unwrap();
+
+		assert_eq!(slot.slot, slot_from_timestamp_and_duration(now, SLOT_DURATION));
+		assert!(slot.ends_at > now);
+		assert!(slot.ends_at <= now + SLOT_DURATION);
+	}
+
+	#[test]
+	fn current_slot_returns_err_on_0_duration() {
+		assert_consensus_other_err(
+			current_slot::<ParentchainBlock>(
+				duration_now(),
+				Default::default(),
+				ParentchainHeaderBuilder::default().build(),
+			),
+			"Tried to get current slot with 0 duration",
+		)
+	}
 }
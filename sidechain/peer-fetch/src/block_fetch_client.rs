@@ -19,9 +19,11 @@ use crate::{error::Result, untrusted_peer_fetch::FetchUntrustedPeers, FetchBlock
 use async_trait::async_trait;
 use its_primitives::{
 	traits::SignedBlock as SignedBlockTrait,
-	types::{BlockHash, ShardIdentifier},
+	types::{BlockHash, BlockNumber, ShardIdentifier},
+};
+use its_rpc_handler::constants::{
+	RPC_METHOD_NAME_FETCH_BLOCKS_FROM_PEER, RPC_METHOD_NAME_FETCH_BLOCK_RANGE_FROM_PEER,
 };
-use its_rpc_handler::constants::RPC_METHOD_NAME_FETCH_BLOCKS_FROM_PEER;
 use jsonrpsee::{
 	types::to_json_value,
 	ws_client::{traits::Client, WsClientBuilder},
@@ -85,6 +87,33 @@ where
 			.await
 			.map_err(|e| e.into())
 	}
+
+	async fn fetch_block_range_from_peer(
+		&self,
+		peer_url: String,
+		from_block_number: BlockNumber,
+		limit: u64,
+		shard_identifier: ShardIdentifier,
+	) -> Result<Vec<Self::SignedBlockType>> {
+		let sync_source_rpc_url = self.peer_fetcher.get_untrusted_peer_url(&peer_url)?;
+
+		let rpc_parameters = vec![to_json_value((from_block_number, limit, shard_identifier))?];
+
+		info!(
+			"Fetching up to {} blocks from block number {} from peer {}",
+			limit, from_block_number, sync_source_rpc_url
+		);
+
+		let client = WsClientBuilder::default().build(sync_source_rpc_url.as_str()).await?;
+
+		client
+			.request::<Vec<SignedBlock>>(
+				RPC_METHOD_NAME_FETCH_BLOCK_RANGE_FROM_PEER,
+				rpc_parameters.into(),
+			)
+			.await
+			.map_err(|e| e.into())
+	}
 }
 
 #[cfg(test)]
@@ -97,7 +126,10 @@ mod tests {
 	};
 	use its_primitives::types::block::SignedBlock;
 	use its_storage::fetch_blocks_mock::FetchBlocksMock;
-	use its_test::sidechain_block_builder::SidechainBlockBuilder;
+	use its_test::{
+		sidechain_block_builder::SidechainBlockBuilder,
+		sidechain_header_builder::SidechainHeaderBuilder,
+	};
 	use jsonrpsee::ws_server::WsServerBuilder;
 	use std::{net::SocketAddr, sync::Arc};
 
@@ -138,4 +170,34 @@ mod tests {
 
 		assert_eq!(blocks_to_fetch, blocks_fetched);
 	}
+
+	#[tokio::test]
+	async fn fetch_block_range_from_peer_works() {
+		const W2_URL: &str = "127.0.0.1:2234";
+
+		let blocks = (1..=5)
+			.map(|n| {
+				SidechainBlockBuilder::default()
+					.with_header(SidechainHeaderBuilder::default().with_block_number(n).build())
+					.build_signed()
+			})
+			.collect::<Vec<_>>();
+		run_server(blocks.clone(), W2_URL).await.unwrap();
+
+		let peer_fetch_mock = UntrustedPeerFetcherMock::new(format!("ws://{}", W2_URL));
+
+		let peer_fetcher_client = BlockFetcher::<SignedBlock, _>::new(peer_fetch_mock);
+
+		let blocks_fetched = peer_fetcher_client
+			.fetch_block_range_from_peer(
+				"wss://trusted.peer".to_string(),
+				2,
+				3,
+				ShardIdentifier::default(),
+			)
+			.await
+			.unwrap();
+
+		assert_eq!(blocks[1..4].to_vec(), blocks_fetched);
+	}
 }
@@ -16,13 +16,18 @@
 */
 
 use crate::error::Result;
-use its_primitives::types::{BlockHash, ShardIdentifier, SignedBlock};
-use its_rpc_handler::constants::RPC_METHOD_NAME_FETCH_BLOCKS_FROM_PEER;
-use its_storage::interface::FetchBlocks;
+use its_primitives::types::{BlockHash, BlockNumber, ShardIdentifier, SignedBlock};
+use its_rpc_handler::constants::{
+	RPC_METHOD_NAME_FETCH_BLOCKS_FROM_PEER, RPC_METHOD_NAME_FETCH_BLOCK_RANGE_FROM_PEER,
+};
+use its_storage::{interface::FetchBlocks, LookupBlocks};
 use jsonrpsee::{types::error::CallError, RpcModule};
 use log::*;
 use std::sync::Arc;
 
+/// Maximum number of blocks returned by a single `sidechain_fetchBlockRangeFromPeer` call.
+pub const MAX_BLOCK_RANGE_SIZE: u64 = 100;
+
 /// RPC server module builder for fetching sidechain blocks from peers.
 pub struct BlockFetchServerModuleBuilder<FetchBlocksFromStorage> {
 	sidechain_block_fetcher: Arc<FetchBlocksFromStorage>,
@@ -32,7 +37,8 @@ impl<FetchBlocksFromStorage> BlockFetchServerModuleBuilder<FetchBlocksFromStorag
 where
 	// Have to use the concrete `SignedBlock` type, because the ShardIdentifier type
 	// does not have the Serialize/Deserialize trait bound.
-	FetchBlocksFromStorage: FetchBlocks<SignedBlock> + Send + Sync + 'static,
+	FetchBlocksFromStorage:
+		FetchBlocks<SignedBlock> + LookupBlocks<SignedBlock> + Send + Sync + 'static,
 {
 	pub fn new(sidechain_block_fetcher: Arc<FetchBlocksFromStorage>) -> Self {
 		BlockFetchServerModuleBuilder { sidechain_block_fetcher }
@@ -71,6 +77,26 @@ where
 				}
 			},
 		)?;
+		fetch_sidechain_blocks_module.register_method(
+			RPC_METHOD_NAME_FETCH_BLOCK_RANGE_FROM_PEER,
+			|params, sidechain_block_fetcher| {
+				debug!("{}: {:?}", RPC_METHOD_NAME_FETCH_BLOCK_RANGE_FROM_PEER, params);
+
+				let (from_block_number, limit, shard_identifier) =
+					params.one::<(BlockNumber, u64, ShardIdentifier)>()?;
+
+				sidechain_block_fetcher
+					.fetch_blocks_by_number(
+						&shard_identifier,
+						from_block_number,
+						limit.min(MAX_BLOCK_RANGE_SIZE),
+					)
+					.map_err(|e| {
+						error!("Failed to fetch sidechain blocks from storage: {:?}", e);
+						CallError::Failed(e.into())
+					})
+			},
+		)?;
 		Ok(fetch_sidechain_blocks_module)
 	}
 }
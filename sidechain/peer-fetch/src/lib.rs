@@ -29,7 +29,7 @@ use crate::error::Result;
 use async_trait::async_trait;
 use its_primitives::{
 	traits::SignedBlock,
-	types::{BlockHash, ShardIdentifier},
+	types::{BlockHash, BlockNumber, ShardIdentifier},
 };
 use std::vec::Vec;
 
@@ -46,4 +46,14 @@ pub trait FetchBlocksFromPeer {
 		maybe_until_block_hash: Option<BlockHash>,
 		shard_identifier: ShardIdentifier,
 	) -> Result<Vec<Self::SignedBlockType>>;
+
+	/// Fetch up to `limit` blocks, starting at block number `from_block_number`, from
+	/// a specific peer, given by the url it registered on the parentchain.
+	async fn fetch_block_range_from_peer(
+		&self,
+		peer_url: String,
+		from_block_number: BlockNumber,
+		limit: u64,
+		shard_identifier: ShardIdentifier,
+	) -> Result<Vec<Self::SignedBlockType>>;
 }
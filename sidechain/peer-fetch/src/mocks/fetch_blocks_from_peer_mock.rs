@@ -18,8 +18,8 @@
 use crate::{FetchBlocksFromPeer, Result};
 use async_trait::async_trait;
 use its_primitives::{
-	traits::SignedBlock as SignedBlockTrait,
-	types::{BlockHash, ShardIdentifier},
+	traits::{Block as BlockTrait, Header as HeaderTrait, SignedBlock as SignedBlockTrait},
+	types::{BlockHash, BlockNumber, ShardIdentifier},
 };
 use std::collections::HashMap;
 
@@ -58,4 +58,25 @@ where
 	) -> Result<Vec<Self::SignedBlockType>> {
 		Ok(self.signed_blocks_map.get(&shard_identifier).cloned().unwrap_or_default())
 	}
+
+	async fn fetch_block_range_from_peer(
+		&self,
+		_peer_url: String,
+		from_block_number: BlockNumber,
+		limit: u64,
+		shard_identifier: ShardIdentifier,
+	) -> Result<Vec<Self::SignedBlockType>> {
+		Ok(self
+			.signed_blocks_map
+			.get(&shard_identifier)
+			.map(|blocks| {
+				blocks
+					.iter()
+					.filter(|b| b.block().header().block_number() >= from_block_number)
+					.take(limit as usize)
+					.cloned()
+					.collect()
+			})
+			.unwrap_or_default())
+	}
 }
@@ -32,4 +32,8 @@ impl FetchUntrustedPeers for UntrustedPeerFetcherMock {
 	fn get_untrusted_peer_url_of_shard(&self, _shard: &ShardIdentifier) -> Result<String> {
 		Ok(self.url.clone())
 	}
+
+	fn get_untrusted_peer_url(&self, _peer_url: &str) -> Result<String> {
+		Ok(self.url.clone())
+	}
 }
@@ -24,6 +24,9 @@ use std::sync::Arc;
 /// Trait to fetch untrusted peer servers.
 pub trait FetchUntrustedPeers {
 	fn get_untrusted_peer_url_of_shard(&self, shard: &ShardIdentifier) -> Result<String>;
+
+	/// Get the untrusted url of a peer, given the (trusted) url it registered on the parentchain.
+	fn get_untrusted_peer_url(&self, peer_url: &str) -> Result<String>;
 }
 
 /// Fetches the untrusted peer servers
@@ -53,7 +56,11 @@ where
 			.worker_for_shard(shard, None)?
 			.ok_or(Error::NoPeerFoundForShard(*shard))?;
 
-		let trusted_worker_client = DirectWorkerApi::new(validateer.url);
+		self.get_untrusted_peer_url(&validateer.url)
+	}
+
+	fn get_untrusted_peer_url(&self, peer_url: &str) -> Result<String> {
+		let trusted_worker_client = DirectWorkerApi::new(peer_url.to_string());
 		Ok(trusted_worker_client.get_untrusted_worker_url()?)
 	}
 }
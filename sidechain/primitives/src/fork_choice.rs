@@ -0,0 +1,103 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Deterministic fork choice rule for competing sidechain blocks.
+//!
+//! The longest chain wins. In case two chains have the same length, the chain whose head
+//! has the lowest author-slot hash wins. The slot is not part of the block, so the block
+//! timestamp (which determines the slot) is hashed together with the block author.
+
+use crate::traits::{Block as BlockTrait, BlockData, Header};
+use sp_core::{crypto::ByteArray, H256};
+use sp_runtime::traits::{BlakeTwo256, Hash};
+
+/// Hash of the block author and the block timestamp, used as a tie-breaker between chains of equal length.
+pub fn author_slot_hash<Block: BlockTrait>(block: &Block) -> H256 {
+	let block_data = block.block_data();
+	BlakeTwo256::hash_of(&(block_data.block_author().as_slice(), block_data.timestamp()))
+}
+
+/// Returns true if the chain with head `candidate` is preferred over the chain with head `current`.
+pub fn is_preferred_fork<Block: BlockTrait>(candidate: &Block, current: &Block) -> bool {
+	let candidate_number = candidate.header().block_number();
+	let current_number = current.header().block_number();
+	if candidate_number != current_number {
+		return candidate_number > current_number
+	}
+	author_slot_hash(candidate) < author_slot_hash(current)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::types::{
+		block::Block, block_data::BlockData as BlockDataType, header::SidechainHeader,
+	};
+	use sp_core::{ed25519, Pair};
+
+	#[test]
+	fn longer_chain_is_preferred() {
+		let shorter = block_with(4, "//Alice", 1000);
+		let longer = block_with(5, "//Bob", 2000);
+
+		assert!(is_preferred_fork(&longer, &shorter));
+		assert!(!is_preferred_fork(&shorter, &longer));
+	}
+
+	#[test]
+	fn lower_author_slot_hash_is_preferred_for_equal_length() {
+		let block_one = block_with(5, "//Alice", 1000);
+		let block_two = block_with(5, "//Bob", 1000);
+
+		let (lower, higher) = if author_slot_hash(&block_one) < author_slot_hash(&block_two) {
+			(block_one, block_two)
+		} else {
+			(block_two, block_one)
+		};
+
+		assert!(is_preferred_fork(&lower, &higher));
+		assert!(!is_preferred_fork(&higher, &lower));
+	}
+
+	#[test]
+	fn block_is_not_preferred_over_itself() {
+		let block = block_with(5, "//Alice", 1000);
+
+		assert!(!is_preferred_fork(&block, &block.clone()));
+	}
+
+	#[test]
+	fn author_slot_hash_differs_for_different_slots() {
+		assert_ne!(
+			author_slot_hash(&block_with(5, "//Alice", 1000)),
+			author_slot_hash(&block_with(5, "//Alice", 2000))
+		);
+	}
+
+	fn block_with(block_number: u64, author_seed: &str, timestamp: u64) -> Block {
+		let header =
+			SidechainHeader::new(block_number, H256::random(), H256::random(), H256::random(), 1);
+		let block_data = BlockDataType::new(
+			ed25519::Pair::from_string(author_seed, None).unwrap().public(),
+			H256::random(),
+			Default::default(),
+			Default::default(),
+			timestamp,
+		);
+		Block::new(header, block_data)
+	}
+}
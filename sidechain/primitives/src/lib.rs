@@ -17,5 +17,6 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub mod fork_choice;
 pub mod traits;
 pub mod types;
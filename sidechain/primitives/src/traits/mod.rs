@@ -120,7 +120,7 @@ pub trait SignedBlock: Encode + Decode + Send + Sync + Debug + Clone {
 	type Public: Public;
 
 	/// Signature type of the [`SignedBlock`]'s signature
-	type Signature;
+	type Signature: Encode + Decode;
 
 	/// create a new block instance
 	fn new(block: Self::Block, signer: Self::Signature) -> Self;
@@ -140,6 +140,16 @@ pub trait SignedBlock: Encode + Decode + Send + Sync + Debug + Clone {
 	fn verify_signature(&self) -> bool;
 }
 
+/// Gets notified about new sidechain block headers, both of imported and of produced blocks.
+pub trait NotifyHeads<HeaderType: Header>: Send + Sync {
+	fn notify_new_head(&self, header: &HeaderType);
+}
+
+/// Gets notified about sidechain blocks that became final.
+pub trait NotifyFinalizedBlocks<ShardIdentifier>: Send + Sync {
+	fn notify_finalized_block(&self, shard: &ShardIdentifier, block_hash: &H256);
+}
+
 #[cfg(feature = "full_crypto")]
 pub use crypto::*;
 
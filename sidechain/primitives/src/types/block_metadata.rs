@@ -0,0 +1,108 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+//! Lightweight summary of a sidechain block, e.g. for block explorers and monitoring.
+
+use crate::{
+	traits::{Block as BlockTrait, BlockData as BlockDataTrait, SignedBlock as SignedBlockTrait},
+	types::{block::SignedBlock, header::SidechainHeader, BlockHash, BlockNumber, Timestamp},
+};
+use codec::{Decode, Encode};
+use sp_core::{ed25519, H256};
+
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+/// Sidechain block header along with the block metadata, without the (encrypted) state diff.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct SidechainBlockMetadata {
+	/// Hash of the block.
+	pub hash: BlockHash,
+	/// Sidechain header.
+	pub header: SidechainHeader,
+	/// Block production timestamp (milliseconds since unix epoch).
+	pub timestamp: Timestamp,
+	/// Parentchain header this block is based on.
+	pub layer_one_head: H256,
+	/// Author of the block.
+	pub block_author: ed25519::Public,
+	/// Number of trusted operations included in the block.
+	pub number_of_operations: u64,
+	/// Size of the encrypted state diff in bytes.
+	pub state_diff_size: u64,
+}
+
+impl From<&SignedBlock> for SidechainBlockMetadata {
+	fn from(signed_block: &SignedBlock) -> Self {
+		let block = signed_block.block();
+		let block_data = block.block_data();
+		SidechainBlockMetadata {
+			hash: signed_block.hash(),
+			header: *block.header(),
+			timestamp: block_data.timestamp(),
+			layer_one_head: block_data.layer_one_head(),
+			block_author: *block_data.block_author(),
+			number_of_operations: block_data.signed_top_hashes().len() as u64,
+			state_diff_size: block_data.encrypted_state_diff().len() as u64,
+		}
+	}
+}
+
+/// Location of a trusted operation in the sidechain, i.e. the block it was included in.
+#[derive(PartialEq, Eq, Clone, Copy, Encode, Decode, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct OperationInclusion<ShardIdentifier> {
+	/// Shard of the including block.
+	pub shard: ShardIdentifier,
+	/// Number of the including block.
+	pub block_number: BlockNumber,
+	/// Hash of the including block.
+	pub block_hash: BlockHash,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		traits::{Header as HeaderTrait, SignBlock},
+		types::block::Block,
+	};
+	use sp_core::Pair;
+
+	#[test]
+	fn metadata_from_signed_block_works() {
+		let signer = ed25519::Pair::from_string("//Alice", None).unwrap();
+		let header = SidechainHeader::new(3, H256::random(), H256::random(), H256::random(), 1);
+		let block_data = crate::types::block_data::BlockData::new(
+			signer.public(),
+			H256::random(),
+			vec![H256::random(), H256::random()],
+			vec![1u8; 10],
+			42,
+		);
+		let signed_block: SignedBlock = Block::new(header, block_data).sign_block(&signer);
+
+		let metadata = SidechainBlockMetadata::from(&signed_block);
+
+		assert_eq!(metadata.hash, signed_block.hash());
+		assert_eq!(metadata.header, header);
+		assert_eq!(metadata.timestamp, 42);
+		assert_eq!(metadata.block_author, signer.public());
+		assert_eq!(metadata.number_of_operations, 2);
+		assert_eq!(metadata.state_diff_size, 10);
+	}
+}
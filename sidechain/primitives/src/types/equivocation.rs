@@ -0,0 +1,168 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Proof of an authority signing two different blocks for the same slot and shard.
+
+use crate::{
+	traits::Header as HeaderTrait,
+	types::{
+		block::{Block, ShardIdentifier, Signature},
+		block_data::BlockData,
+		header::SidechainHeader,
+	},
+};
+use codec::{Decode, Encode};
+use sp_core::ed25519;
+use sp_runtime::traits::Verify;
+use sp_std::vec::Vec;
+
+/// Header of a sidechain block together with the signature of the block author.
+///
+/// The signature covers the full block, so the encoded block data is included as well. It is
+/// needed to check the signature and names the block author.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Debug)]
+pub struct SignedHeader<Header, Signature> {
+	pub header: Header,
+	/// The SCALE encoded block data.
+	pub block_data: Vec<u8>,
+	pub signature: Signature,
+}
+
+/// Two different blocks, signed by the same authority for the same slot and shard.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Debug)]
+pub struct EquivocationProof<Header, Public, Signature> {
+	/// The authority that signed both blocks.
+	pub offender: Public,
+	/// Slot both blocks were produced in.
+	pub slot: u64,
+	/// Shard of both blocks.
+	pub shard: ShardIdentifier,
+	/// The block that was seen first.
+	pub first_header: SignedHeader<Header, Signature>,
+	/// The conflicting block that was seen later.
+	pub second_header: SignedHeader<Header, Signature>,
+}
+
+impl SignedHeader<SidechainHeader, Signature> {
+	/// Check that the block was authored and signed by `author`.
+	pub fn verify(&self, author: &ed25519::Public) -> bool {
+		let block_data = match BlockData::decode(&mut self.block_data.as_slice()) {
+			Ok(block_data) => block_data,
+			Err(_) => return false,
+		};
+		if &block_data.block_author != author {
+			return false
+		}
+
+		let block = Block { header: self.header.clone(), block_data };
+		block.using_encoded(|p| self.signature.verify(p, &(*author).into()))
+	}
+}
+
+impl EquivocationProof<SidechainHeader, ed25519::Public, Signature> {
+	/// Check that the offender signed two different blocks of the proof's shard.
+	///
+	/// The slot of the blocks depends on the slot duration in effect, it has to be checked
+	/// separately.
+	pub fn verify(&self) -> bool {
+		self.first_header.header != self.second_header.header
+			&& self.first_header.header.shard_id() == self.shard
+			&& self.second_header.header.shard_id() == self.shard
+			&& self.first_header.verify(&self.offender)
+			&& self.second_header.verify(&self.offender)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		traits::{Block as BlockTrait, BlockData as BlockDataTrait, SignBlock},
+		types::block::SignedBlock,
+	};
+	use sp_core::{Pair, H256};
+
+	fn signed_block(signer: &ed25519::Pair, block_number: u64) -> SignedBlock {
+		let header =
+			SidechainHeader::new(block_number, H256::random(), H256::zero(), H256::random(), 1);
+		let block_data = BlockData::new(
+			signer.public(),
+			H256::random(),
+			Default::default(),
+			Default::default(),
+			1,
+		);
+		Block::new(header, block_data).sign_block(signer)
+	}
+
+	fn signed_header(signed_block: &SignedBlock) -> SignedHeader<SidechainHeader, Signature> {
+		SignedHeader {
+			header: signed_block.block.header.clone(),
+			block_data: signed_block.block.block_data.encode(),
+			signature: signed_block.signature.clone(),
+		}
+	}
+
+	fn proof(
+		signer: &ed25519::Pair,
+	) -> EquivocationProof<SidechainHeader, ed25519::Public, Signature> {
+		EquivocationProof {
+			offender: signer.public(),
+			slot: 1,
+			shard: H256::zero(),
+			first_header: signed_header(&signed_block(signer, 1)),
+			second_header: signed_header(&signed_block(signer, 2)),
+		}
+	}
+
+	#[test]
+	fn proof_of_two_blocks_signed_by_offender_verifies() {
+		let signer = ed25519::Pair::from_string("//Alice", None).unwrap();
+
+		assert!(proof(&signer).verify());
+	}
+
+	#[test]
+	fn proof_against_other_authority_than_block_author_fails_to_verify() {
+		let signer = ed25519::Pair::from_string("//Alice", None).unwrap();
+		let mut proof = proof(&signer);
+		proof.offender = ed25519::Pair::from_string("//Bob", None).unwrap().public();
+
+		assert!(!proof.verify());
+	}
+
+	#[test]
+	fn proof_with_tampered_block_data_fails_to_verify() {
+		let signer = ed25519::Pair::from_string("//Alice", None).unwrap();
+		let mut proof = proof(&signer);
+		let mut block_data =
+			BlockData::decode(&mut proof.second_header.block_data.as_slice()).unwrap();
+		block_data.timestamp += 1;
+		proof.second_header.block_data = block_data.encode();
+
+		assert!(!proof.verify());
+	}
+
+	#[test]
+	fn proof_with_same_block_twice_fails_to_verify() {
+		let signer = ed25519::Pair::from_string("//Alice", None).unwrap();
+		let mut proof = proof(&signer);
+		proof.second_header = proof.first_header.clone();
+
+		assert!(!proof.verify());
+	}
+}
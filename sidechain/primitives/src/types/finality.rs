@@ -0,0 +1,144 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Finality votes of validateers on sidechain blocks.
+//!
+//! A validateer votes for every block it imports. A block is final once more than 2/3 of the
+//! validateer set voted for it, which implicitly finalizes all of its ancestors.
+
+use crate::types::block::{BlockHash, BlockNumber, ShardIdentifier, Signature};
+use codec::{Decode, Encode};
+use sp_core::ed25519;
+use sp_runtime::traits::Verify;
+use sp_std::vec::Vec;
+
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+/// Domain tag of the payload signed for a [`FinalityVote`], so that a signature of the validateer
+/// can not be reused in any other context.
+pub const FINALITY_VOTE_DOMAIN: &[u8] = b"integritee:sidechain-finality-vote";
+
+/// Vote of a validateer for a sidechain block it imported.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct FinalityVote {
+	/// Shard of the block.
+	pub shard: ShardIdentifier,
+	/// Number of the block.
+	pub block_number: BlockNumber,
+	/// Hash of the block.
+	pub block_hash: BlockHash,
+	/// Validateer casting the vote.
+	pub validateer: ed25519::Public,
+}
+
+/// Finality vote, signed by the voting validateer.
+#[derive(PartialEq, Eq, Clone, Encode, Decode, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct SignedFinalityVote {
+	/// The vote.
+	pub vote: FinalityVote,
+	/// Signature of the validateer.
+	pub signature: Signature,
+}
+
+impl FinalityVote {
+	/// Payload signed by the validateer, the vote prefixed with its domain tag.
+	pub fn signature_payload(&self) -> Vec<u8> {
+		(FINALITY_VOTE_DOMAIN, self).encode()
+	}
+
+	/// Signs the vote with the key of the validateer.
+	#[cfg(feature = "full_crypto")]
+	pub fn sign(self, signer: &ed25519::Pair) -> SignedFinalityVote {
+		use sp_core::Pair;
+		let signature = signer.sign(&self.signature_payload()).into();
+		SignedFinalityVote { vote: self, signature }
+	}
+}
+
+impl SignedFinalityVote {
+	/// Verifies the vote was signed by the validateer it claims to be from.
+	pub fn verify_signature(&self) -> bool {
+		self.signature
+			.verify(self.vote.signature_payload().as_slice(), &self.vote.validateer.into())
+	}
+}
+
+/// Returns true if the votes are a super-majority, i.e. more than 2/3 of the validateer set.
+pub fn is_super_majority(number_of_votes: u64, number_of_validateers: u64) -> bool {
+	number_of_votes.saturating_mul(3) > number_of_validateers.saturating_mul(2)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::{Pair, H256};
+
+	fn vote_of(signer: &ed25519::Pair) -> FinalityVote {
+		FinalityVote {
+			shard: H256::from_low_u64_be(1),
+			block_number: 5,
+			block_hash: H256::from_low_u64_be(2),
+			validateer: signer.public(),
+		}
+	}
+
+	#[test]
+	fn signed_vote_verifies() {
+		let signer = ed25519::Pair::from_string("//Alice", None).unwrap();
+
+		assert!(vote_of(&signer).sign(&signer).verify_signature());
+	}
+
+	#[test]
+	fn vote_signed_by_other_validateer_fails_to_verify() {
+		let alice = ed25519::Pair::from_string("//Alice", None).unwrap();
+		let bob = ed25519::Pair::from_string("//Bob", None).unwrap();
+
+		assert!(!vote_of(&alice).sign(&bob).verify_signature());
+	}
+
+	#[test]
+	fn vote_signed_without_domain_tag_fails_to_verify() {
+		let signer = ed25519::Pair::from_string("//Alice", None).unwrap();
+		let vote = vote_of(&signer);
+		let signature = vote.using_encoded(|v| signer.sign(v)).into();
+
+		assert!(!SignedFinalityVote { vote, signature }.verify_signature());
+	}
+
+	#[test]
+	fn tampered_vote_fails_to_verify() {
+		let signer = ed25519::Pair::from_string("//Alice", None).unwrap();
+		let mut signed_vote = vote_of(&signer).sign(&signer);
+		signed_vote.vote.block_number = 6;
+
+		assert!(!signed_vote.verify_signature());
+	}
+
+	#[test]
+	fn super_majority_requires_more_than_two_thirds() {
+		assert!(is_super_majority(1, 1));
+		assert!(!is_super_majority(2, 3));
+		assert!(is_super_majority(3, 3));
+		assert!(!is_super_majority(2, 4));
+		assert!(is_super_majority(3, 4));
+		assert!(!is_super_majority(0, 0));
+	}
+}
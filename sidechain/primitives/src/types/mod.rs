@@ -17,6 +17,9 @@
 
 pub mod block;
 pub mod block_data;
+pub mod block_metadata;
+pub mod equivocation;
+pub mod finality;
 pub mod header;
 
 pub use block::*;
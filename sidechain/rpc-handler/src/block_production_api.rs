@@ -0,0 +1,262 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Admin RPC methods to suspend and resume the sidechain block production of a shard.
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use crate::sgx_reexport_prelude::*;
+
+use crate::constants::{
+	RPC_METHOD_NAME_GET_BLOCK_PRODUCTION_STATUS, RPC_METHOD_NAME_RESUME_BLOCK_PRODUCTION,
+	RPC_METHOD_NAME_SUSPEND_BLOCK_PRODUCTION,
+};
+use codec::Encode;
+use itc_direct_rpc_server::RpcIoHandler;
+use itp_rpc::RpcReturnValue;
+use itp_types::{
+	BlockProductionCommand, BlockProductionStatus, DirectRequestStatus, ShardIdentifier,
+	SignedBlockProductionAdminRequest,
+};
+use itp_utils::{FromHexPrefixed, ToHexPrefixed};
+use jsonrpc_core::{serde_json::json, Params};
+use log::*;
+use std::{borrow::ToOwned, fmt::Debug, format, string::String, sync::Arc, vec::Vec};
+
+/// Adds the admin RPC methods to suspend and resume the block production of a shard, as well
+/// as to query its current suspension state.
+///
+/// The `control_fn` is responsible for authenticating the signed admin requests.
+pub fn add_block_production_rpc_methods<ControlFn, StatusFn, Error>(
+	control_fn: ControlFn,
+	status_fn: StatusFn,
+	mut io_handler: RpcIoHandler,
+) -> RpcIoHandler
+where
+	ControlFn: Fn(SignedBlockProductionAdminRequest) -> Result<(), Error> + Sync + Send + 'static,
+	StatusFn: Fn(ShardIdentifier) -> Result<BlockProductionStatus, Error> + Sync + Send + 'static,
+	Error: Debug,
+{
+	let control_fn = Arc::new(control_fn);
+	add_block_production_control_method(
+		RPC_METHOD_NAME_SUSPEND_BLOCK_PRODUCTION,
+		BlockProductionCommand::Suspend,
+		control_fn.clone(),
+		&mut io_handler,
+	);
+	add_block_production_control_method(
+		RPC_METHOD_NAME_RESUME_BLOCK_PRODUCTION,
+		BlockProductionCommand::Resume,
+		control_fn,
+		&mut io_handler,
+	);
+
+	io_handler.add_sync_method(
+		RPC_METHOD_NAME_GET_BLOCK_PRODUCTION_STATUS,
+		move |params: Params| {
+			debug!("{} rpc. Params: {:?}", RPC_METHOD_NAME_GET_BLOCK_PRODUCTION_STATUS, params);
+
+			let json_value = match get_block_production_status_inner(&status_fn, params) {
+				Ok(status) =>
+					RpcReturnValue::new(status.encode(), false, DirectRequestStatus::Ok).to_hex(),
+				Err(error) => compute_hex_encoded_return_error(error.as_str()),
+			};
+			Ok(json!(json_value))
+		},
+	);
+
+	io_handler
+}
+
+fn add_block_production_control_method<ControlFn, Error>(
+	method_name: &'static str,
+	command: BlockProductionCommand,
+	control_fn: Arc<ControlFn>,
+	io_handler: &mut RpcIoHandler,
+) where
+	ControlFn: Fn(SignedBlockProductionAdminRequest) -> Result<(), Error> + Sync + Send + 'static,
+	Error: Debug,
+{
+	io_handler.add_sync_method(method_name, move |params: Params| {
+		debug!("{} rpc. Params: {:?}", method_name, params);
+
+		let json_value = match control_block_production_inner(control_fn.as_ref(), command, params)
+		{
+			Ok(()) => RpcReturnValue::new("ok".encode(), false, DirectRequestStatus::Ok).to_hex(),
+			Err(error) => compute_hex_encoded_return_error(error.as_str()),
+		};
+		Ok(json!(json_value))
+	});
+}
+
+fn control_block_production_inner<ControlFn, Error>(
+	control_fn: &ControlFn,
+	command: BlockProductionCommand,
+	params: Params,
+) -> Result<(), String>
+where
+	ControlFn: Fn(SignedBlockProductionAdminRequest) -> Result<(), Error>,
+	Error: Debug,
+{
+	let signed_request: SignedBlockProductionAdminRequest = decode_first_param(params)?;
+
+	if signed_request.request.command != command {
+		return Err(format!(
+			"Admin request command {:?} does not match the rpc method",
+			signed_request.request.command
+		))
+	}
+
+	control_fn(signed_request).map_err(|e| format!("{:?}", e))
+}
+
+fn get_block_production_status_inner<StatusFn, Error>(
+	status_fn: &StatusFn,
+	params: Params,
+) -> Result<BlockProductionStatus, String>
+where
+	StatusFn: Fn(ShardIdentifier) -> Result<BlockProductionStatus, Error>,
+	Error: Debug,
+{
+	let shard: ShardIdentifier = decode_first_param(params)?;
+	status_fn(shard).map_err(|e| format!("{:?}", e))
+}
+
+fn decode_first_param<T: codec::Decode>(params: Params) -> Result<T, String> {
+	let hex_encoded_params = params.parse::<Vec<String>>().map_err(|e| format!("{:?}", e))?;
+	let hex_encoded_param =
+		hex_encoded_params.first().ok_or_else(|| "Missing parameter".to_owned())?;
+	T::from_hex(hex_encoded_param).map_err(|e| format!("Could not decode parameter: {:?}", e))
+}
+
+fn compute_hex_encoded_return_error(error_msg: &str) -> String {
+	RpcReturnValue::from_error_message(error_msg).to_hex()
+}
+
+#[cfg(test)]
+pub mod tests {
+
+	use super::*;
+	use codec::Decode;
+	use itp_rpc::RpcResponse;
+	use itp_types::{AccountId, BlockProductionAdminRequest};
+	use jsonrpc_core::serde_json;
+	use sp_core::sr25519;
+	use std::sync::Mutex;
+
+	fn signed_admin_request(command: BlockProductionCommand) -> SignedBlockProductionAdminRequest {
+		SignedBlockProductionAdminRequest::new(
+			BlockProductionAdminRequest::new(
+				ShardIdentifier::from_low_u64_be(1),
+				command,
+				0,
+				1_000,
+			),
+			AccountId::new([1u8; 32]),
+			sr25519::Signature::from_raw([0u8; 64]).into(),
+		)
+	}
+
+	fn rpc_return_value(io: &RpcIoHandler, method: &str, param: String) -> RpcReturnValue {
+		let request =
+			format!(r#"{{"jsonrpc":"2.0","method":"{}","params":["{}"],"id":1}}"#, method, param);
+		let response_string = io.handle_request_sync(&request).unwrap();
+		let response: RpcResponse = serde_json::from_str(&response_string).unwrap();
+		RpcReturnValue::from_hex(&response.result).unwrap()
+	}
+
+	#[test]
+	pub fn suspend_block_production_passes_request_to_control_fn() {
+		let controlled_requests = Arc::new(Mutex::new(Vec::new()));
+		let controlled_requests_clone = controlled_requests.clone();
+		let io = add_block_production_rpc_methods::<_, _, String>(
+			move |request| {
+				controlled_requests_clone.lock().unwrap().push(request);
+				Ok(())
+			},
+			|_| Ok(BlockProductionStatus::default()),
+			RpcIoHandler::new(),
+		);
+		let request = signed_admin_request(BlockProductionCommand::Suspend);
+
+		let return_value =
+			rpc_return_value(&io, RPC_METHOD_NAME_SUSPEND_BLOCK_PRODUCTION, request.to_hex());
+
+		assert_eq!(return_value.status, DirectRequestStatus::Ok);
+		assert_eq!(*controlled_requests.lock().unwrap(), vec![request]);
+	}
+
+	#[test]
+	pub fn resume_block_production_rejects_suspend_request() {
+		let io = add_block_production_rpc_methods::<_, _, String>(
+			|_| panic!("Request must not be passed on"),
+			|_| Ok(BlockProductionStatus::default()),
+			RpcIoHandler::new(),
+		);
+		let request = signed_admin_request(BlockProductionCommand::Suspend);
+
+		let return_value =
+			rpc_return_value(&io, RPC_METHOD_NAME_RESUME_BLOCK_PRODUCTION, request.to_hex());
+
+		assert_eq!(return_value.status, DirectRequestStatus::Error);
+	}
+
+	#[test]
+	pub fn control_fn_error_is_returned() {
+		let io = add_block_production_rpc_methods::<_, _, String>(
+			|_| Err("Not authorized".to_owned()),
+			|_| Ok(BlockProductionStatus::default()),
+			RpcIoHandler::new(),
+		);
+		let request = signed_admin_request(BlockProductionCommand::Resume);
+
+		let return_value =
+			rpc_return_value(&io, RPC_METHOD_NAME_RESUME_BLOCK_PRODUCTION, request.to_hex());
+
+		assert_eq!(return_value.status, DirectRequestStatus::Error);
+	}
+
+	#[test]
+	pub fn get_block_production_status_returns_status_of_shard() {
+		let io = add_block_production_rpc_methods::<_, _, String>(
+			|_| Ok(()),
+			|shard| {
+				Ok(BlockProductionStatus {
+					suspended_by_admin: shard == ShardIdentifier::from_low_u64_be(1),
+					suspended_for_sync: false,
+					next_admin_nonce: 3,
+				})
+			},
+			RpcIoHandler::new(),
+		);
+
+		let return_value = rpc_return_value(
+			&io,
+			RPC_METHOD_NAME_GET_BLOCK_PRODUCTION_STATUS,
+			ShardIdentifier::from_low_u64_be(1).to_hex(),
+		);
+
+		assert_eq!(return_value.status, DirectRequestStatus::Ok);
+		assert_eq!(
+			BlockProductionStatus::decode(&mut return_value.value.as_slice()).unwrap(),
+			BlockProductionStatus {
+				suspended_by_admin: true,
+				suspended_for_sync: false,
+				next_admin_nonce: 3
+			}
+		);
+	}
+}
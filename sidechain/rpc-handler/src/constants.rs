@@ -19,4 +19,17 @@
 
 // RPC method names.
 pub const RPC_METHOD_NAME_IMPORT_BLOCKS: &str = "sidechain_importBlock";
+pub const RPC_METHOD_NAME_IMPORT_FINALITY_VOTES: &str = "sidechain_importFinalityVotes";
+pub const RPC_METHOD_NAME_IMPORT_TRUSTED_OPERATIONS: &str = "sidechain_importTrustedOperations";
 pub const RPC_METHOD_NAME_FETCH_BLOCKS_FROM_PEER: &str = "sidechain_fetchBlocksFromPeer";
+pub const RPC_METHOD_NAME_FETCH_BLOCK_RANGE_FROM_PEER: &str = "sidechain_fetchBlockRangeFromPeer";
+pub const RPC_METHOD_NAME_GET_BLOCK_BY_HASH: &str = "sidechain_getBlockByHash";
+pub const RPC_METHOD_NAME_GET_BLOCK_BY_NUMBER: &str = "sidechain_getBlockByNumber";
+pub const RPC_METHOD_NAME_GET_LATEST_HEADERS: &str = "sidechain_getLatestHeaders";
+pub const RPC_METHOD_NAME_GET_HEADERS: &str = "sidechain_getHeaders";
+pub const RPC_METHOD_NAME_GET_OPERATION_INCLUSION: &str = "sidechain_getOperationInclusion";
+pub const RPC_METHOD_NAME_SUSPEND_BLOCK_PRODUCTION: &str = "sidechain_suspendBlockProduction";
+pub const RPC_METHOD_NAME_RESUME_BLOCK_PRODUCTION: &str = "sidechain_resumeBlockProduction";
+pub const RPC_METHOD_NAME_GET_BLOCK_PRODUCTION_STATUS: &str = "sidechain_getBlockProductionStatus";
+pub const RPC_METHOD_NAME_SUBSCRIBE_ALL_HEADS: &str = "chain_subscribeAllHeads";
+pub const RPC_METHOD_NAME_UNSUBSCRIBE_ALL_HEADS: &str = "chain_unsubscribeAllHeads";
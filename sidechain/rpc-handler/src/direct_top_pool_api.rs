@@ -25,12 +25,25 @@ use rust_base58::base58::FromBase58;
 use base58::FromBase58;
 
 use codec::{Decode, Encode};
+use itc_direct_rpc_server::{
+	ObserveConnectionClosed, RpcConnection, RpcConnectionMetadata, RpcIoHandler,
+};
 use itp_rpc::RpcReturnValue;
-use itp_top_pool_author::traits::AuthorApi;
-use itp_types::{DirectRequestStatus, Request, ShardIdentifier, TrustedOperationStatus};
+use itp_settings::sidechain::TRUSTED_OPERATION_CANCEL_REQUEST_VALIDITY;
+use itp_time_utils::now_as_u64;
+use itp_top_pool_author::{
+	sender_limits::{RpcClient, SubmissionSource},
+	traits::AuthorApi,
+};
+use itp_types::{
+	DirectRequestStatus, Request, ShardIdentifier, SignedTrustedOperationCancelRequest,
+	TrustedOperationPoolStatus, TrustedOperationStatus,
+};
 use itp_utils::{FromHexPrefixed, ToHexPrefixed};
 use jsonrpc_core::{
-	futures::executor, serde_json::json, Error as RpcError, IoHandler, Params, Value,
+	futures::{executor, future::ready},
+	serde_json::json,
+	Error as RpcError, Params, Value,
 };
 use log::*;
 use std::{borrow::ToOwned, format, string::String, sync::Arc, vec, vec::Vec};
@@ -39,50 +52,58 @@ type Hash = sp_core::H256;
 
 pub fn add_top_pool_direct_rpc_methods<R>(
 	top_pool_author: Arc<R>,
-	mut io_handler: IoHandler,
-) -> IoHandler
+	mut io_handler: RpcIoHandler,
+) -> RpcIoHandler
 where
 	R: AuthorApi<Hash, Hash> + Send + Sync + 'static,
 {
 	// author_submitAndWatchExtrinsic
 	let author_submit_and_watch_extrinsic_name: &str = "author_submitAndWatchExtrinsic";
 	let watch_author = top_pool_author.clone();
-	io_handler.add_sync_method(author_submit_and_watch_extrinsic_name, move |params: Params| {
-		let json_value = match author_submit_extrinsic_inner(watch_author.clone(), params) {
-			Ok(hash_value) => RpcReturnValue {
-				do_watch: true,
-				value: hash_value.encode(),
-				status: DirectRequestStatus::TrustedOperationStatus(
-					TrustedOperationStatus::Submitted,
-				),
-			}
-			.to_hex(),
-			Err(error) => compute_hex_encoded_return_error(error.as_str()),
-		};
-		Ok(json!(json_value))
-	});
+	io_handler.add_method_with_meta(
+		author_submit_and_watch_extrinsic_name,
+		move |params: Params, connection: RpcConnectionMetadata| {
+			let json_value =
+				match author_submit_extrinsic_inner(watch_author.clone(), params, connection) {
+					Ok(hash_value) => RpcReturnValue {
+						do_watch: true,
+						value: hash_value.encode(),
+						status: DirectRequestStatus::TrustedOperationStatus(
+							TrustedOperationStatus::Submitted,
+						),
+					}
+					.to_hex(),
+					Err(error) => compute_hex_encoded_return_error(error.as_str()),
+				};
+			ready(Ok(json!(json_value)))
+		},
+	);
 
 	// author_submitExtrinsic
 	let author_submit_extrinsic_name: &str = "author_submitExtrinsic";
 	let submit_author = top_pool_author.clone();
-	io_handler.add_sync_method(author_submit_extrinsic_name, move |params: Params| {
-		let json_value = match author_submit_extrinsic_inner(submit_author.clone(), params) {
-			Ok(hash_value) => RpcReturnValue {
-				do_watch: false,
-				value: hash_value.encode(),
-				status: DirectRequestStatus::TrustedOperationStatus(
-					TrustedOperationStatus::Submitted,
-				),
-			}
-			.to_hex(),
-			Err(error) => compute_hex_encoded_return_error(error.as_str()),
-		};
-		Ok(json!(json_value))
-	});
+	io_handler.add_method_with_meta(
+		author_submit_extrinsic_name,
+		move |params: Params, connection: RpcConnectionMetadata| {
+			let json_value =
+				match author_submit_extrinsic_inner(submit_author.clone(), params, connection) {
+					Ok(hash_value) => RpcReturnValue {
+						do_watch: false,
+						value: hash_value.encode(),
+						status: DirectRequestStatus::TrustedOperationStatus(
+							TrustedOperationStatus::Submitted,
+						),
+					}
+					.to_hex(),
+					Err(error) => compute_hex_encoded_return_error(error.as_str()),
+				};
+			ready(Ok(json!(json_value)))
+		},
+	);
 
 	// author_pendingExtrinsics
 	let author_pending_extrinsic_name: &str = "author_pendingExtrinsics";
-	let pending_author = top_pool_author;
+	let pending_author = top_pool_author.clone();
 	io_handler.add_sync_method(author_pending_extrinsic_name, move |params: Params| {
 		match params.parse::<Vec<String>>() {
 			Ok(shards) => {
@@ -110,9 +131,72 @@ where
 		}
 	});
 
+	// author_getPoolStatus
+	let author_get_pool_status_name: &str = "author_getPoolStatus";
+	let pool_status_author = top_pool_author.clone();
+	io_handler.add_sync_method(author_get_pool_status_name, move |params: Params| {
+		let json_value = match author_get_pool_status_inner(pool_status_author.as_ref(), params) {
+			Ok(pool_status) =>
+				RpcReturnValue::new(pool_status.encode(), false, DirectRequestStatus::Ok).to_hex(),
+			Err(error) => compute_hex_encoded_return_error(error.as_str()),
+		};
+		Ok(json!(json_value))
+	});
+
+	// author_getTrustedOperationStatus
+	let author_get_top_status_name: &str = "author_getTrustedOperationStatus";
+	let top_status_author = top_pool_author.clone();
+	io_handler.add_sync_method(author_get_top_status_name, move |params: Params| {
+		let json_value = match author_get_top_status_inner(top_status_author.as_ref(), params) {
+			Ok((hash, status)) => RpcReturnValue {
+				do_watch: false,
+				value: hash.encode(),
+				status: DirectRequestStatus::TrustedOperationStatus(status),
+			}
+			.to_hex(),
+			Err(error) => compute_hex_encoded_return_error(error.as_str()),
+		};
+		Ok(json!(json_value))
+	});
+
+	// author_cancelTrustedOperation, only cancels the operation in the pool of this validateer
+	let author_cancel_top_name: &str = "author_cancelTrustedOperation";
+	let cancel_author = top_pool_author;
+	io_handler.add_sync_method(author_cancel_top_name, move |params: Params| {
+		let json_value = match author_cancel_top_inner(cancel_author.as_ref(), params) {
+			Ok(hash) => RpcReturnValue::new(hash.encode(), false, DirectRequestStatus::Ok).to_hex(),
+			Err(error) => compute_hex_encoded_return_error(error.as_str()),
+		};
+		Ok(json!(json_value))
+	});
+
 	io_handler
 }
 
+/// Notifies the top pool author of closed RPC connections.
+pub struct TopPoolConnectionObserver<R> {
+	top_pool_author: Arc<R>,
+}
+
+impl<R> TopPoolConnectionObserver<R> {
+	pub fn new(top_pool_author: Arc<R>) -> Self {
+		TopPoolConnectionObserver { top_pool_author }
+	}
+}
+
+impl<R> ObserveConnectionClosed for TopPoolConnectionObserver<R>
+where
+	R: AuthorApi<Hash, Hash> + Send + Sync,
+{
+	fn on_connection_closed(&self, connection: RpcConnection) {
+		self.top_pool_author.on_connection_closed(rpc_client(connection));
+	}
+}
+
+fn rpc_client(connection: RpcConnection) -> RpcClient {
+	RpcClient { connection: connection.token.0, address: connection.peer_address }
+}
+
 // converts the rpc methods vector to a string and adds commas and brackets for readability
 fn decode_shard_from_base58(shard_base58: &str) -> Result<ShardIdentifier, String> {
 	let shard_vec = match shard_base58.from_base58() {
@@ -133,6 +217,7 @@ fn compute_hex_encoded_return_error(error_msg: &str) -> String {
 fn author_submit_extrinsic_inner<R: AuthorApi<Hash, Hash> + Send + Sync + 'static>(
 	author: Arc<R>,
 	params: Params,
+	connection: RpcConnectionMetadata,
 ) -> Result<Hash, String> {
 	debug!("Author submit and watch trusted operation..");
 
@@ -143,7 +228,8 @@ fn author_submit_extrinsic_inner<R: AuthorApi<Hash, Hash> + Send + Sync + 'stati
 
 	let shard: ShardIdentifier = request.shard;
 	let encrypted_trusted_call: Vec<u8> = request.cyphertext;
-	let result = async { author.watch_top(encrypted_trusted_call, shard).await };
+	let source = SubmissionSource::Rpc(connection.0.map(rpc_client));
+	let result = async { author.watch_top(encrypted_trusted_call, shard, source).await };
 	let response: Result<Hash, RpcError> = executor::block_on(result);
 
 	match &response {
@@ -153,3 +239,173 @@ fn author_submit_extrinsic_inner<R: AuthorApi<Hash, Hash> + Send + Sync + 'stati
 
 	response.map_err(|e| format!("{:?}", e))
 }
+
+fn author_get_pool_status_inner<R: AuthorApi<Hash, Hash>>(
+	author: &R,
+	params: Params,
+) -> Result<TrustedOperationPoolStatus, String> {
+	let shard_base58 = params.parse::<(String,)>().map_err(|e| format!("{:?}", e))?.0;
+	let shard = decode_shard_from_base58(shard_base58.as_str())?;
+	Ok(author.get_pool_status(shard))
+}
+
+fn author_get_top_status_inner<R: AuthorApi<Hash, Hash>>(
+	author: &R,
+	params: Params,
+) -> Result<(Hash, TrustedOperationStatus), String> {
+	let (shard_base58, hex_encoded_hash) =
+		params.parse::<(String, String)>().map_err(|e| format!("{:?}", e))?;
+	let shard = decode_shard_from_base58(shard_base58.as_str())?;
+	let hash = Hash::from_hex(&hex_encoded_hash).map_err(|e| format!("{:?}", e))?;
+
+	author
+		.get_top_status(&hash, shard)
+		.map(|status| (hash, status))
+		.ok_or_else(|| "Trusted operation is not pending in the pool".to_owned())
+}
+
+fn author_cancel_top_inner<R: AuthorApi<Hash, Hash>>(
+	author: &R,
+	params: Params,
+) -> Result<Hash, String> {
+	let hex_encoded_request = params.parse::<(String,)>().map_err(|e| format!("{:?}", e))?.0;
+	let signed_request = SignedTrustedOperationCancelRequest::from_hex(&hex_encoded_request)
+		.map_err(|e| format!("{:?}", e))?;
+
+	if !signed_request.verify(now_as_u64(), TRUSTED_OPERATION_CANCEL_REQUEST_VALIDITY) {
+		return Err("Cancel request has an invalid signature or is expired".to_owned())
+	}
+
+	let request = signed_request.request;
+	author
+		.cancel_top(request.operation_hash, request.shard, &signed_request.signer)
+		.map_err(|e| format!("{:?}", e))
+}
+
+#[cfg(test)]
+pub mod tests {
+
+	use super::*;
+	use itp_rpc::RpcResponse;
+	use itp_top_pool_author::mocks::AuthorApiMock;
+	use itp_types::{AccountId, TrustedOperationCancelRequest};
+	use jsonrpc_core::serde_json;
+	use rust_base58::base58::ToBase58;
+	use sp_core::{sr25519, Pair};
+
+	fn rpc_return_value(io: &RpcIoHandler, method: &str, params: &[String]) -> RpcReturnValue {
+		let request = json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": 1 });
+		let response_string = io.handle_request_sync(&request.to_string()).unwrap();
+		let response: RpcResponse = serde_json::from_str(&response_string).unwrap();
+		RpcReturnValue::from_hex(&response.result).unwrap()
+	}
+
+	fn shard() -> ShardIdentifier {
+		ShardIdentifier::from_low_u64_be(1)
+	}
+
+	fn signed_cancel_request(
+		signer: &sr25519::Pair,
+		signer_account: AccountId,
+		timestamp: u64,
+	) -> SignedTrustedOperationCancelRequest {
+		let request =
+			TrustedOperationCancelRequest::new(shard(), Hash::from_low_u64_be(2), timestamp);
+		let signature = signer.sign(request.signature_payload().as_slice());
+		SignedTrustedOperationCancelRequest::new(request, signer_account, signature.into())
+	}
+
+	fn cancel_error(request: &SignedTrustedOperationCancelRequest) -> Option<String> {
+		let io = add_top_pool_direct_rpc_methods(
+			Arc::new(AuthorApiMock::<Hash, Hash>::default()),
+			RpcIoHandler::new(),
+		);
+		let return_value =
+			rpc_return_value(&io, "author_cancelTrustedOperation", &[request.to_hex()]);
+		(return_value.status == DirectRequestStatus::Error)
+			.then(|| String::decode(&mut return_value.value.as_slice()).unwrap())
+	}
+
+	#[test]
+	pub fn get_pool_status_returns_status_of_shard() {
+		let author = Arc::new(AuthorApiMock::<Hash, Hash>::default());
+		executor::block_on(author.submit_top(vec![1u8, 2u8], shard())).unwrap();
+		let io = add_top_pool_direct_rpc_methods(author, RpcIoHandler::new());
+
+		let return_value =
+			rpc_return_value(&io, "author_getPoolStatus", &[shard().encode().to_base58()]);
+
+		assert_eq!(return_value.status, DirectRequestStatus::Ok);
+		let pool_status =
+			TrustedOperationPoolStatus::decode(&mut return_value.value.as_slice()).unwrap();
+		assert_eq!(1, pool_status.ready);
+		assert_eq!(2, pool_status.ready_bytes);
+	}
+
+	#[test]
+	pub fn get_status_of_unknown_operation_returns_error() {
+		let io = add_top_pool_direct_rpc_methods(
+			Arc::new(AuthorApiMock::<Hash, Hash>::default()),
+			RpcIoHandler::new(),
+		);
+
+		let return_value = rpc_return_value(
+			&io,
+			"author_getTrustedOperationStatus",
+			&[shard().encode().to_base58(), Hash::from_low_u64_be(2).to_hex()],
+		);
+
+		assert_eq!(return_value.status, DirectRequestStatus::Error);
+	}
+
+	#[test]
+	pub fn cancel_request_with_invalid_signature_is_rejected() {
+		let other_account = sr25519::Pair::from_seed(&[2u8; 32]).public().into();
+		let request = signed_cancel_request(
+			&sr25519::Pair::from_seed(&[1u8; 32]),
+			other_account,
+			now_as_u64(),
+		);
+
+		assert_eq!(
+			cancel_error(&request).unwrap(),
+			"Cancel request has an invalid signature or is expired"
+		);
+	}
+
+	#[test]
+	pub fn valid_cancel_request_is_passed_to_the_pool() {
+		let signer = sr25519::Pair::from_seed(&[1u8; 32]);
+		let request = signed_cancel_request(&signer, signer.public().into(), now_as_u64());
+
+		// The operation is not pending in the pool of the mock.
+		assert_ne!(
+			cancel_error(&request).unwrap(),
+			"Cancel request has an invalid signature or is expired"
+		);
+	}
+
+	#[test]
+	pub fn expired_cancel_request_is_rejected() {
+		let signer = sr25519::Pair::from_seed(&[1u8; 32]);
+		let timestamp = now_as_u64() - TRUSTED_OPERATION_CANCEL_REQUEST_VALIDITY - 1_000;
+		let request = signed_cancel_request(&signer, signer.public().into(), timestamp);
+
+		assert_eq!(
+			cancel_error(&request).unwrap(),
+			"Cancel request has an invalid signature or is expired"
+		);
+	}
+
+	#[test]
+	pub fn cancel_request_signed_without_domain_tag_is_rejected() {
+		let signer = sr25519::Pair::from_seed(&[1u8; 32]);
+		let mut request = signed_cancel_request(&signer, signer.public().into(), now_as_u64());
+		request.signature = signer.sign(request.request.encode().as_slice()).into();
+
+		assert_eq!(
+			cancel_error(&request).unwrap(),
+			"Cancel request has an invalid signature or is expired"
+		);
+	}
+}
@@ -0,0 +1,119 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use crate::sgx_reexport_prelude::*;
+
+use crate::constants::RPC_METHOD_NAME_IMPORT_FINALITY_VOTES;
+use itc_direct_rpc_server::RpcIoHandler;
+use itp_utils::FromHexPrefixed;
+use its_primitives::types::finality::SignedFinalityVote;
+use jsonrpc_core::{Params, Value};
+use log::*;
+use std::{borrow::ToOwned, fmt::Debug, string::String, vec::Vec};
+
+pub fn add_import_finality_votes_rpc_method<ImportFn, Error>(
+	import_fn: ImportFn,
+	mut io_handler: RpcIoHandler,
+) -> RpcIoHandler
+where
+	ImportFn: Fn(SignedFinalityVote) -> Result<(), Error> + Sync + Send + 'static,
+	Error: Debug,
+{
+	io_handler.add_sync_method(RPC_METHOD_NAME_IMPORT_FINALITY_VOTES, move |params: Params| {
+		debug!("{} rpc. Params: {:?}", RPC_METHOD_NAME_IMPORT_FINALITY_VOTES, params);
+
+		let hex_encoded_votes: Vec<String> = params.parse()?;
+		let encoded_votes = hex_encoded_votes.first().ok_or_else(|| {
+			jsonrpc_core::error::Error::invalid_params("Missing Vec<SignedFinalityVote>")
+		})?;
+
+		let votes = Vec::<SignedFinalityVote>::from_hex(encoded_votes).map_err(|_| {
+			jsonrpc_core::error::Error::invalid_params_with_details(
+				"Could not decode Vec<SignedFinalityVote>",
+				hex_encoded_votes.clone(),
+			)
+		})?;
+
+		for vote in votes {
+			if let Err(e) = import_fn(vote) {
+				warn!("Failed to import finality vote: {:?}", e);
+			}
+		}
+
+		Ok(Value::String("ok".to_owned()))
+	});
+
+	io_handler
+}
+
+#[cfg(test)]
+pub mod tests {
+
+	use super::*;
+	use itp_utils::ToHexPrefixed;
+	use its_primitives::types::finality::FinalityVote;
+	use sp_core::{ed25519, Pair, H256};
+	use std::sync::{Arc, Mutex};
+
+	fn signed_vote() -> SignedFinalityVote {
+		let signer = ed25519::Pair::from_string("//Alice", None).unwrap();
+		let vote = FinalityVote {
+			shard: H256::from_low_u64_be(1),
+			block_number: 3,
+			block_hash: H256::from_low_u64_be(2),
+			validateer: signer.public(),
+		};
+		let signature = signer.sign(&vote.signature_payload()).into();
+		SignedFinalityVote { vote, signature }
+	}
+
+	#[test]
+	pub fn import_finality_votes_passes_all_votes_to_import_fn() {
+		let imported_votes = Arc::new(Mutex::new(Vec::new()));
+		let imported_votes_clone = imported_votes.clone();
+		let io = add_import_finality_votes_rpc_method::<_, String>(
+			move |vote| {
+				imported_votes_clone.lock().unwrap().push(vote);
+				Ok(())
+			},
+			RpcIoHandler::new(),
+		);
+		let votes = vec![signed_vote(), signed_vote()];
+		let request = format!(
+			r#"{{"jsonrpc":"2.0","method":"{}","params":["{}"],"id":1}}"#,
+			RPC_METHOD_NAME_IMPORT_FINALITY_VOTES,
+			votes.to_hex()
+		);
+
+		let response_string = io.handle_request_sync(&request).unwrap();
+
+		assert_eq!(response_string, r#"{"jsonrpc":"2.0","result":"ok","id":1}"#);
+		assert_eq!(*imported_votes.lock().unwrap(), votes);
+	}
+
+	#[test]
+	pub fn import_finality_votes_returns_decode_err() {
+		let io = add_import_finality_votes_rpc_method::<_, String>(|_| Ok(()), RpcIoHandler::new());
+		let request = r#"{"jsonrpc":"2.0","method":"sidechain_importFinalityVotes","params":["0x11"],"id":1}"#;
+
+		let response_string = io.handle_request_sync(request).unwrap();
+
+		let err_msg = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid parameters: Could not decode Vec<SignedFinalityVote>","data":"[\"0x11\"]"},"id":1}"#;
+		assert_eq!(response_string, err_msg);
+	}
+}
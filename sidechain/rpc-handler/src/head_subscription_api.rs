@@ -0,0 +1,299 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Subscription to the headers of all new sidechain blocks (imported and produced ones).
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use crate::sgx_reexport_prelude::*;
+
+use crate::constants::{
+	RPC_METHOD_NAME_SUBSCRIBE_ALL_HEADS, RPC_METHOD_NAME_UNSUBSCRIBE_ALL_HEADS,
+};
+use codec::{Decode, Encode};
+use itc_direct_rpc_server::{
+	subscription_registry::SubscriptionRegistry, RpcConnectionMetadata, RpcIoHandler,
+	SendSubscriptionNotification,
+};
+use itp_rpc::RpcReturnValue;
+use itp_types::DirectRequestStatus;
+use itp_utils::{FromHexPrefixed, ToHexPrefixed};
+use its_primitives::{traits::NotifyHeads, types::header::SidechainHeader};
+use jsonrpc_core::{futures::future::ready, serde_json::json, Params};
+use log::*;
+use std::{format, string::String, sync::Arc, vec::Vec};
+
+type Hash = sp_core::H256;
+
+/// Keeps track of the head subscriptions and sends every new sidechain header to the subscribers.
+///
+/// The subscription id doubles as hash of the watched connection in the connection registry.
+/// It is random, so it can not be guessed by other clients.
+pub struct HeadSubscriptions<Responder>
+where
+	Responder: SendSubscriptionNotification<Hash = Hash>,
+{
+	subscriptions: SubscriptionRegistry<Hash>,
+	rpc_responder: Arc<Responder>,
+}
+
+impl<Responder> HeadSubscriptions<Responder>
+where
+	Responder: SendSubscriptionNotification<Hash = Hash>,
+{
+	pub fn new(rpc_responder: Arc<Responder>) -> Self {
+		HeadSubscriptions { subscriptions: SubscriptionRegistry::new(), rpc_responder }
+	}
+
+	/// Creates a new, random subscription id and registers it for the given connection.
+	pub fn subscribe(&self, owner: RpcConnectionMetadata) -> Result<Hash, String> {
+		let subscription = random_subscription_id()?;
+		self.subscriptions.subscribe(subscription, owner);
+		Ok(subscription)
+	}
+
+	/// Returns false if there was no such subscription, or if it belongs to another connection.
+	pub fn unsubscribe(&self, subscription: &Hash, requester: RpcConnectionMetadata) -> bool {
+		if !self.subscriptions.unsubscribe(subscription, requester) {
+			return false
+		}
+		if let Err(e) = self.rpc_responder.close_subscription(subscription) {
+			debug!(
+				"Connection of head subscription {:?} was already closed: {:?}",
+				subscription, e
+			);
+		}
+		true
+	}
+}
+
+impl<Responder> NotifyHeads<SidechainHeader> for HeadSubscriptions<Responder>
+where
+	Responder: SendSubscriptionNotification<Hash = Hash>,
+{
+	fn notify_new_head(&self, header: &SidechainHeader) {
+		let header_encoded = header.encode();
+		for subscription in self.subscriptions.subscriptions() {
+			if let Err(e) =
+				self.rpc_responder.send_notification(subscription, header_encoded.clone())
+			{
+				// The connection is gone, so the subscription can not be served anymore.
+				debug!("Dropping head subscription {:?}: {:?}", subscription, e);
+				self.subscriptions.remove(&subscription);
+			}
+		}
+	}
+}
+
+pub fn add_head_subscription_rpc_methods<Responder>(
+	head_subscriptions: Arc<HeadSubscriptions<Responder>>,
+	mut io_handler: RpcIoHandler,
+) -> RpcIoHandler
+where
+	Responder: SendSubscriptionNotification<Hash = Hash> + 'static,
+{
+	// chain_subscribeAllHeads
+	let subscribe_subscriptions = head_subscriptions.clone();
+	io_handler.add_method_with_meta(
+		RPC_METHOD_NAME_SUBSCRIBE_ALL_HEADS,
+		move |_: Params, connection: RpcConnectionMetadata| {
+			let json_value = match subscribe_subscriptions.subscribe(connection) {
+				Ok(subscription) => {
+					debug!(
+						"{} rpc. New subscription: {:?}",
+						RPC_METHOD_NAME_SUBSCRIBE_ALL_HEADS, subscription
+					);
+					RpcReturnValue::new(subscription.encode(), true, DirectRequestStatus::Ok)
+				},
+				Err(error_msg) => RpcReturnValue::from_error_message(error_msg.as_str()),
+			};
+			ready(Ok(json!(json_value.to_hex())))
+		},
+	);
+
+	// chain_unsubscribeAllHeads
+	let unsubscribe_subscriptions = head_subscriptions;
+	io_handler.add_method_with_meta(
+		RPC_METHOD_NAME_UNSUBSCRIBE_ALL_HEADS,
+		move |params: Params, connection: RpcConnectionMetadata| {
+			let json_value = match decode_subscription(params) {
+				Ok(subscription) => RpcReturnValue::new(
+					unsubscribe_subscriptions.unsubscribe(&subscription, connection).encode(),
+					false,
+					DirectRequestStatus::Ok,
+				),
+				Err(error_msg) => RpcReturnValue::from_error_message(error_msg.as_str()),
+			};
+			ready(Ok(json!(json_value.to_hex())))
+		},
+	);
+
+	io_handler
+}
+
+#[cfg(feature = "sgx")]
+fn random_subscription_id() -> Result<Hash, String> {
+	use sgx_rand::{os, Rng};
+
+	let mut subscription = [0u8; 32];
+	os::SgxRng::new().map_err(|e| format!("{:?}", e))?.fill_bytes(&mut subscription);
+	Ok(Hash::from(subscription))
+}
+
+#[cfg(feature = "std")]
+fn random_subscription_id() -> Result<Hash, String> {
+	use rand::{rngs::OsRng, RngCore};
+
+	let mut subscription = [0u8; 32];
+	OsRng.try_fill_bytes(&mut subscription).map_err(|e| format!("{:?}", e))?;
+	Ok(Hash::from(subscription))
+}
+
+fn decode_subscription(params: Params) -> Result<Hash, String> {
+	let hex_encoded_params = params.parse::<Vec<String>>().map_err(|e| format!("{:?}", e))?;
+	let hex_encoded_subscription = hex_encoded_params
+		.get(0)
+		.ok_or_else(|| String::from("Missing subscription id parameter"))?;
+	let subscription_encoded =
+		Vec::<u8>::from_hex(hex_encoded_subscription).map_err(|e| format!("{:?}", e))?;
+	Hash::decode(&mut subscription_encoded.as_slice())
+		.map_err(|_| String::from("Subscription id is not of type H256"))
+}
+
+#[cfg(test)]
+pub mod tests {
+
+	use super::*;
+	use itc_direct_rpc_server::mocks::send_subscription_notification_mock::SendSubscriptionNotificationMock;
+	use itc_tls_websocket_server::ConnectionToken;
+	use its_primitives::traits::Header as HeaderTrait;
+	use jsonrpc_core::MetaIoHandler;
+	use std::net::{IpAddr, Ipv4Addr};
+
+	type TestResponder = SendSubscriptionNotificationMock<Hash>;
+
+	const PEER_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+	const CONNECTION: RpcConnectionMetadata =
+		RpcConnectionMetadata::new(ConnectionToken(10), PEER_ADDRESS);
+	const OTHER_CONNECTION: RpcConnectionMetadata =
+		RpcConnectionMetadata::new(ConnectionToken(11), PEER_ADDRESS);
+
+	fn create_head_subscriptions() -> (Arc<HeadSubscriptions<TestResponder>>, Arc<TestResponder>) {
+		let responder = Arc::new(TestResponder::default());
+		(Arc::new(HeadSubscriptions::new(responder.clone())), responder)
+	}
+
+	fn decode_return_value(response_string: &str) -> RpcReturnValue {
+		let response: jsonrpc_core::Value =
+			jsonrpc_core::serde_json::from_str(response_string).unwrap();
+		RpcReturnValue::from_hex(response["result"].as_str().unwrap()).unwrap()
+	}
+
+	fn handle_request(
+		io: &RpcIoHandler,
+		request: &str,
+		connection: RpcConnectionMetadata,
+	) -> String {
+		MetaIoHandler::handle_request_sync(&**io, request, connection).unwrap()
+	}
+
+	#[test]
+	pub fn subscriptions_are_unique() {
+		let (head_subscriptions, _) = create_head_subscriptions();
+
+		assert_ne!(
+			head_subscriptions.subscribe(CONNECTION).unwrap(),
+			head_subscriptions.subscribe(CONNECTION).unwrap()
+		);
+	}
+
+	#[test]
+	pub fn new_head_is_sent_to_all_subscribers() {
+		let (head_subscriptions, responder) = create_head_subscriptions();
+		let first = head_subscriptions.subscribe(CONNECTION).unwrap();
+		let second = head_subscriptions.subscribe(OTHER_CONNECTION).unwrap();
+		let header = SidechainHeader::new(
+			1,
+			Hash::repeat_byte(1),
+			Hash::repeat_byte(2),
+			Hash::repeat_byte(3),
+			0,
+		);
+
+		head_subscriptions.notify_new_head(&header);
+
+		let notifications = responder.sent_notifications.read().unwrap();
+		assert_eq!(2, notifications.len());
+		assert!(notifications.iter().any(|(s, _)| *s == first));
+		assert!(notifications.iter().any(|(s, _)| *s == second));
+		assert!(notifications.iter().all(|(_, h)| *h == header.encode()));
+	}
+
+	#[test]
+	pub fn unsubscribed_subscription_is_not_notified_anymore() {
+		let (head_subscriptions, responder) = create_head_subscriptions();
+		let subscription = head_subscriptions.subscribe(CONNECTION).unwrap();
+
+		assert!(head_subscriptions.unsubscribe(&subscription, CONNECTION));
+		assert!(!head_subscriptions.unsubscribe(&subscription, CONNECTION));
+
+		head_subscriptions.notify_new_head(&SidechainHeader::default());
+
+		assert!(responder.sent_notifications.read().unwrap().is_empty());
+		assert_eq!(vec![subscription], *responder.closed_subscriptions.read().unwrap());
+	}
+
+	#[test]
+	pub fn subscription_can_not_be_cancelled_by_another_connection() {
+		let (head_subscriptions, responder) = create_head_subscriptions();
+		let subscription = head_subscriptions.subscribe(CONNECTION).unwrap();
+
+		assert!(!head_subscriptions.unsubscribe(&subscription, OTHER_CONNECTION));
+
+		head_subscriptions.notify_new_head(&SidechainHeader::default());
+
+		assert_eq!(1, responder.sent_notifications.read().unwrap().len());
+		assert!(responder.closed_subscriptions.read().unwrap().is_empty());
+	}
+
+	#[test]
+	pub fn subscribe_and_unsubscribe_rpc_methods_work() {
+		let (head_subscriptions, _) = create_head_subscriptions();
+		let io = add_head_subscription_rpc_methods(head_subscriptions, RpcIoHandler::new());
+
+		let subscribe_request =
+			r#"{"jsonrpc":"2.0","method":"chain_subscribeAllHeads","params":[],"id":1}"#;
+		let return_value = decode_return_value(&handle_request(&io, subscribe_request, CONNECTION));
+
+		assert!(return_value.do_watch);
+		assert_eq!(DirectRequestStatus::Ok, return_value.status);
+		let subscription = Hash::decode(&mut return_value.value.as_slice()).unwrap();
+
+		let unsubscribe_request = format!(
+			r#"{{"jsonrpc":"2.0","method":"chain_unsubscribeAllHeads","params":["{}"],"id":1}}"#,
+			subscription.encode().to_hex()
+		);
+		let return_value =
+			decode_return_value(&handle_request(&io, &unsubscribe_request, OTHER_CONNECTION));
+		assert_eq!(false.encode(), return_value.value);
+
+		let return_value =
+			decode_return_value(&handle_request(&io, &unsubscribe_request, CONNECTION));
+
+		assert!(!return_value.do_watch);
+		assert_eq!(true.encode(), return_value.value);
+	}
+}
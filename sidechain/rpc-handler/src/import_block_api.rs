@@ -19,16 +19,17 @@
 use crate::sgx_reexport_prelude::*;
 
 use crate::constants::RPC_METHOD_NAME_IMPORT_BLOCKS;
+use itc_direct_rpc_server::RpcIoHandler;
 use itp_utils::FromHexPrefixed;
 use its_primitives::types::SignedBlock;
-use jsonrpc_core::{IoHandler, Params, Value};
+use jsonrpc_core::{Params, Value};
 use log::*;
 use std::{borrow::ToOwned, fmt::Debug, string::String, vec::Vec};
 
 pub fn add_import_block_rpc_method<ImportFn, Error>(
 	import_fn: ImportFn,
-	mut io_handler: IoHandler,
-) -> IoHandler
+	mut io_handler: RpcIoHandler,
+) -> RpcIoHandler
 where
 	ImportFn: Fn(SignedBlock) -> Result<(), Error> + Sync + Send + 'static,
 	Error: Debug,
@@ -74,8 +75,8 @@ pub mod tests {
 		format!(r#"{{"jsonrpc":"2.0","result":{},"id":1}}"#, result.to_string())
 	}
 
-	fn io_handler() -> IoHandler {
-		let io_handler = IoHandler::new();
+	fn io_handler() -> RpcIoHandler {
+		let io_handler = RpcIoHandler::new();
 		add_import_block_rpc_method::<_, String>(|_| Ok(()), io_handler)
 	}
 
@@ -0,0 +1,111 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use crate::sgx_reexport_prelude::*;
+
+use crate::constants::RPC_METHOD_NAME_IMPORT_TRUSTED_OPERATIONS;
+use itc_direct_rpc_server::RpcIoHandler;
+use itp_types::Request;
+use itp_utils::FromHexPrefixed;
+use jsonrpc_core::{Params, Value};
+use log::*;
+use std::{borrow::ToOwned, fmt::Debug, string::String, vec::Vec};
+
+pub fn add_import_trusted_operations_rpc_method<ImportFn, Error>(
+	import_fn: ImportFn,
+	mut io_handler: RpcIoHandler,
+) -> RpcIoHandler
+where
+	ImportFn: Fn(Request) -> Result<(), Error> + Sync + Send + 'static,
+	Error: Debug,
+{
+	io_handler.add_sync_method(RPC_METHOD_NAME_IMPORT_TRUSTED_OPERATIONS, move |params: Params| {
+		debug!("{} rpc. Params: {:?}", RPC_METHOD_NAME_IMPORT_TRUSTED_OPERATIONS, params);
+
+		let hex_encoded_operations: Vec<String> = params.parse()?;
+		let encoded_operations = hex_encoded_operations
+			.first()
+			.ok_or_else(|| jsonrpc_core::error::Error::invalid_params("Missing Vec<Request>"))?;
+
+		let operations = Vec::<Request>::from_hex(encoded_operations).map_err(|_| {
+			jsonrpc_core::error::Error::invalid_params_with_details(
+				"Could not decode Vec<Request>",
+				hex_encoded_operations.clone(),
+			)
+		})?;
+
+		for operation in operations {
+			if let Err(e) = import_fn(operation) {
+				warn!("Failed to import gossiped trusted operation: {:?}", e);
+			}
+		}
+
+		Ok(Value::String("ok".to_owned()))
+	});
+
+	io_handler
+}
+
+#[cfg(test)]
+pub mod tests {
+
+	use super::*;
+	use itp_utils::ToHexPrefixed;
+	use sp_core::H256;
+	use std::sync::{Arc, Mutex};
+
+	fn encrypted_operation(shard: u64) -> Request {
+		Request { shard: H256::from_low_u64_be(shard), cyphertext: vec![1u8, 2u8, 3u8] }
+	}
+
+	#[test]
+	pub fn import_trusted_operations_passes_all_operations_to_import_fn() {
+		let imported_operations = Arc::new(Mutex::new(Vec::new()));
+		let imported_operations_clone = imported_operations.clone();
+		let io = add_import_trusted_operations_rpc_method::<_, String>(
+			move |operation| {
+				imported_operations_clone.lock().unwrap().push(operation);
+				Ok(())
+			},
+			RpcIoHandler::new(),
+		);
+		let operations = vec![encrypted_operation(1), encrypted_operation(2)];
+		let request = format!(
+			r#"{{"jsonrpc":"2.0","method":"{}","params":["{}"],"id":1}}"#,
+			RPC_METHOD_NAME_IMPORT_TRUSTED_OPERATIONS,
+			operations.to_hex()
+		);
+
+		let response_string = io.handle_request_sync(&request).unwrap();
+
+		assert_eq!(response_string, r#"{"jsonrpc":"2.0","result":"ok","id":1}"#);
+		assert_eq!(*imported_operations.lock().unwrap(), operations);
+	}
+
+	#[test]
+	pub fn import_trusted_operations_returns_decode_err() {
+		let io =
+			add_import_trusted_operations_rpc_method::<_, String>(|_| Ok(()), RpcIoHandler::new());
+		let request = r#"{"jsonrpc":"2.0","method":"sidechain_importTrustedOperations","params":["0x11"],"id":1}"#;
+
+		let response_string = io.handle_request_sync(request).unwrap();
+
+		let err_msg = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid parameters: Could not decode Vec<Request>","data":"[\"0x11\"]"},"id":1}"#;
+		assert_eq!(response_string, err_msg);
+	}
+}
@@ -31,6 +31,10 @@ pub mod sgx_reexport_prelude {
 	pub use rust_base58_sgx as base58;
 }
 
+pub mod block_production_api;
 pub mod constants;
 pub mod direct_top_pool_api;
+pub mod finality_vote_api;
+pub mod head_subscription_api;
 pub mod import_block_api;
+pub mod import_trusted_operations_api;
@@ -184,15 +184,45 @@ where
 #[cfg(test)]
 pub mod tests {
 	use super::*;
-	use crate::{SidechainDB, StateUpdate};
+	use crate::{LastBlockExt, LastBlockImportExt, SidechainDB, StateUpdate};
 	use frame_support::{assert_err, assert_ok};
 	use itp_sgx_externalities::{SgxExternalities, SgxExternalitiesTrait};
-	use sp_core::H256;
+	use its_primitives::{
+		traits::{Block as BlockTrait, BlockData as BlockDataTrait, Header as HeaderTrait},
+		types::{block::Block, block_data::BlockData, header::SidechainHeader},
+	};
+	use sp_core::{ed25519, H256};
 
 	pub fn default_db() -> SidechainDB<(), SgxExternalities> {
 		SidechainDB::<(), SgxExternalities>::default()
 	}
 
+	fn block_db() -> SidechainDB<Block, SgxExternalities> {
+		SidechainDB::new(SgxExternalities::default())
+	}
+
+	fn block(block_number: u64) -> Block {
+		let block_data = BlockData::new(
+			ed25519::Public::from_raw([1u8; 32]),
+			H256::default(),
+			Vec::new(),
+			Vec::new(),
+			block_number,
+		);
+		let header = SidechainHeader::new(
+			block_number,
+			H256::default(),
+			H256::default(),
+			block_data.hash(),
+			1,
+		);
+		Block::new(header, block_data)
+	}
+
+	fn signature() -> ed25519::Signature {
+		ed25519::Signature::from_raw([2u8; 64])
+	}
+
 	#[test]
 	pub fn apply_state_update_works() {
 		let mut state1 = default_db();
@@ -262,4 +292,34 @@ pub mod tests {
 
 		assert_eq!(state1.ext.state_diff.get(&b"hello"[..]).unwrap(), &Some(b"world".encode()));
 	}
+
+	#[test]
+	pub fn revert_last_block_import_restores_state_before_import() {
+		let mut state = block_db();
+		state.set_last_imported_block(&block(1), &signature());
+		// Done by the block production before the calls of the next block are executed.
+		state.clear_last_block_import_records();
+		let state_before_import = state.clone();
+
+		state.set_last_imported_block(&block(2), &signature());
+		assert_eq!(state.get_parent_of_last_block(), Some(block(1)));
+		assert_eq!(state.get_last_block_signature(), Some(signature()));
+
+		state.revert_last_block_import();
+
+		assert_eq!(state.get_last_block(), Some(block(1)));
+		assert_eq!(state.state_hash(), state_before_import.state_hash());
+	}
+
+	#[test]
+	pub fn revert_import_of_first_block_clears_last_block() {
+		let mut state = block_db();
+		let state_before_import = state.clone();
+
+		state.set_last_imported_block(&block(1), &signature());
+		state.revert_last_block_import();
+
+		assert_eq!(state.get_last_block(), None);
+		assert_eq!(state.state_hash(), state_before_import.state_hash());
+	}
 }
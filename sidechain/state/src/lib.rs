@@ -170,6 +170,78 @@ where
 	}
 }
 
+/// trait to record the import of the last sidechain block of the sidechain state
+///
+/// Next to the last block, the import records its signature and the block it was imported on
+/// top of. This allows to restore the state the block was proposed with, which is the state its
+/// `state_hash_aposteriori` refers to. The block production clears the records before executing
+/// the calls, so the state hashes of a block do not cover the records of the previous import.
+pub trait LastBlockImportExt<SidechainBlock: SidechainBlockTrait>:
+	LastBlockExt<SidechainBlock>
+{
+	/// get the signature of the last block of the sidechain state
+	fn get_last_block_signature<Signature: Decode>(&self) -> Option<Signature>;
+
+	/// get the block the last block of the sidechain state was imported on top of
+	fn get_parent_of_last_block(&self) -> Option<SidechainBlock>;
+
+	/// set the last block of the sidechain state and record its import
+	fn set_last_imported_block<Signature: Encode>(
+		&mut self,
+		block: &SidechainBlock,
+		signature: &Signature,
+	);
+
+	/// clear the records of the last block import, keeping the last block itself
+	fn clear_last_block_import_records(&mut self);
+
+	/// restore the state the last block was proposed with, by undoing the records of its import
+	fn revert_last_block_import(&mut self);
+}
+
+impl<SidechainBlock: SidechainBlockTrait, E> LastBlockImportExt<SidechainBlock>
+	for SidechainDB<SidechainBlock, E>
+where
+	SidechainDB<SidechainBlock, E>: SidechainState + SidechainSystemExt,
+{
+	fn get_last_block_signature<Signature: Decode>(&self) -> Option<Signature> {
+		self.get_with_name("System", "LastBlockSignature")
+	}
+
+	fn get_parent_of_last_block(&self) -> Option<SidechainBlock> {
+		self.get_with_name("System", "ParentOfLastBlock")
+	}
+
+	fn set_last_imported_block<Signature: Encode>(
+		&mut self,
+		block: &SidechainBlock,
+		signature: &Signature,
+	) {
+		match self.get_last_block() {
+			Some(parent) => self.set_with_name("System", "ParentOfLastBlock", parent),
+			None => self.clear_with_name("System", "ParentOfLastBlock"),
+		}
+		self.set_with_name("System", "LastBlockSignature", signature);
+		self.set_last_block(block)
+	}
+
+	fn clear_last_block_import_records(&mut self) {
+		self.clear_with_name("System", "LastBlockSignature");
+		self.clear_with_name("System", "ParentOfLastBlock");
+	}
+
+	fn revert_last_block_import(&mut self) {
+		match self.get_parent_of_last_block() {
+			Some(parent) => self.set_last_block(&parent),
+			None => {
+				self.clear_with_name("System", "LastBlock");
+				self.clear_with_name("System", "LastHash");
+			},
+		}
+		self.clear_last_block_import_records();
+	}
+}
+
 /// System extension for the `SidechainDB`.
 pub trait SidechainSystemExt {
 	/// Get the last block number.
@@ -0,0 +1,166 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Key-value backends the sidechain storage can be persisted in.
+
+use super::Result;
+use codec::Encode;
+use parking_lot::RwLock;
+use std::{collections::BTreeMap, sync::Arc};
+
+#[cfg(feature = "rocksdb")]
+pub use rocksdb_backend::RocksDbBackend;
+
+/// Key-value store underneath the sidechain storage.
+pub trait KeyValueBackend {
+	/// Returns the value stored under the key, if there is one.
+	fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>>;
+
+	/// Applies all operations of the batch atomically.
+	fn write(&mut self, batch: WriteBatch) -> Result<()>;
+}
+
+/// Write operation of a [`WriteBatch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WriteOperation {
+	Put(Vec<u8>, Vec<u8>),
+	Delete(Vec<u8>),
+}
+
+/// Ordered set of write operations, applied atomically by a [`KeyValueBackend`].
+#[derive(Debug, Clone, Default)]
+pub struct WriteBatch {
+	operations: Vec<WriteOperation>,
+}
+
+impl WriteBatch {
+	/// adds a given key value pair to the batch
+	pub fn put<K: Encode, V: Encode>(&mut self, key: K, value: V) {
+		self.operations.push(WriteOperation::Put(key.encode(), value.encode()))
+	}
+
+	/// adds a delete key command to the batch
+	pub fn delete<K: Encode>(&mut self, key: K) {
+		self.operations.push(WriteOperation::Delete(key.encode()))
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.operations.is_empty()
+	}
+
+	pub fn into_operations(self) -> Vec<WriteOperation> {
+		self.operations
+	}
+}
+
+/// Backend keeping all data in memory, e.g. for tests or short-lived workers.
+///
+/// Clones share the same data, so a storage can be re-opened from a clone of its backend.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryBackend {
+	data: Arc<RwLock<BTreeMap<Vec<u8>, Vec<u8>>>>,
+}
+
+impl KeyValueBackend for InMemoryBackend {
+	fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+		Ok(self.data.read().get(key).cloned())
+	}
+
+	fn write(&mut self, batch: WriteBatch) -> Result<()> {
+		// Hold the lock for the whole batch, so readers never see it partially applied.
+		let mut data = self.data.write();
+		for operation in batch.into_operations() {
+			match operation {
+				WriteOperation::Put(key, value) => {
+					data.insert(key, value);
+				},
+				WriteOperation::Delete(key) => {
+					data.remove(&key);
+				},
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(feature = "rocksdb")]
+mod rocksdb_backend {
+	use super::{KeyValueBackend, WriteBatch, WriteOperation};
+	use crate::Result;
+	use rocksdb::DB;
+	use std::path::PathBuf;
+
+	/// Backend persisting the data in a RocksDB database.
+	pub struct RocksDbBackend {
+		db: DB,
+	}
+
+	impl RocksDbBackend {
+		pub fn open_default(path: PathBuf) -> Result<RocksDbBackend> {
+			Ok(RocksDbBackend { db: DB::open_default(path)? })
+		}
+	}
+
+	impl KeyValueBackend for RocksDbBackend {
+		fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+			Ok(self.db.get(key)?)
+		}
+
+		fn write(&mut self, batch: WriteBatch) -> Result<()> {
+			let mut rocksdb_batch = rocksdb::WriteBatch::default();
+			for operation in batch.into_operations() {
+				match operation {
+					WriteOperation::Put(key, value) => rocksdb_batch.put(key, value),
+					WriteOperation::Delete(key) => rocksdb_batch.delete(key),
+				}
+			}
+			Ok(self.db.write(rocksdb_batch)?)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn in_memory_backend_applies_batch_in_order() {
+		let mut backend = InMemoryBackend::default();
+		let mut batch = WriteBatch::default();
+		batch.put(1u8, 10u32);
+		batch.put(2u8, 20u32);
+		batch.delete(1u8);
+		batch.put(2u8, 21u32);
+
+		backend.write(batch).unwrap();
+
+		assert_eq!(backend.get(&1u8.encode()).unwrap(), None);
+		assert_eq!(backend.get(&2u8.encode()).unwrap(), Some(21u32.encode()));
+	}
+
+	#[test]
+	fn in_memory_backend_clones_share_data() {
+		let mut backend = InMemoryBackend::default();
+		let clone = backend.clone();
+		let mut batch = WriteBatch::default();
+		batch.put(1u8, 10u32);
+
+		backend.write(batch).unwrap();
+
+		assert_eq!(clone.get(&1u8.encode()).unwrap(), Some(10u32.encode()));
+	}
+}
@@ -0,0 +1,79 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Portable, versioned backup format of the sidechain storage.
+//!
+//! A backup file consists of the magic bytes, the SCALE encoded format version
+//! and the SCALE encoded [`SidechainBackup`].
+
+use crate::{storage::LastSidechainBlock, Error, Result};
+use codec::{Decode, Encode};
+use std::{fs, path::Path};
+
+/// Magic bytes at the beginning of every sidechain backup file.
+pub const BACKUP_FILE_MAGIC: &[u8; 4] = b"ISCB";
+
+/// Current version of the backup format.
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// Backup of the sidechain storage, containing the blocks of every shard.
+#[derive(Encode, Decode, Clone, Debug, PartialEq)]
+pub struct SidechainBackup<ShardIdentifier, SignedBlock> {
+	pub shards: Vec<ShardBackup<ShardIdentifier, SignedBlock>>,
+}
+
+/// Backup of a single shard, blocks are ordered oldest first.
+#[derive(Encode, Decode, Clone, Debug, PartialEq)]
+pub struct ShardBackup<ShardIdentifier, SignedBlock> {
+	pub shard: ShardIdentifier,
+	pub last_block: LastSidechainBlock,
+	pub blocks: Vec<SignedBlock>,
+}
+
+impl<ShardIdentifier: Encode + Decode, SignedBlock: Encode + Decode>
+	SidechainBackup<ShardIdentifier, SignedBlock>
+{
+	/// Encodes the backup including the magic bytes and the format version.
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let mut bytes = BACKUP_FILE_MAGIC.to_vec();
+		BACKUP_FORMAT_VERSION.encode_to(&mut bytes);
+		self.encode_to(&mut bytes);
+		bytes
+	}
+
+	/// Decodes a backup, checking the magic bytes and the format version.
+	pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+		let mut input = bytes
+			.strip_prefix(BACKUP_FILE_MAGIC.as_slice())
+			.ok_or_else(|| Error::InvalidBackup("Not a sidechain backup file".to_string()))?;
+
+		let version = u32::decode(&mut input)?;
+		if version != BACKUP_FORMAT_VERSION {
+			return Err(Error::UnsupportedBackupVersion(version))
+		}
+
+		Ok(Self::decode(&mut input)?)
+	}
+
+	pub fn write_to_file(&self, path: &Path) -> Result<()> {
+		Ok(fs::write(path, self.to_bytes())?)
+	}
+
+	pub fn read_from_file(path: &Path) -> Result<Self> {
+		Self::from_bytes(&fs::read(path)?)
+	}
+}
@@ -15,30 +15,35 @@
 
 */
 
-use super::{Error, Result};
+use super::{
+	backend::{KeyValueBackend, WriteBatch},
+	Result,
+};
 use codec::{Decode, Encode};
-use rocksdb::{WriteBatch, DB};
-use std::path::PathBuf;
 
 /// Sidechain DB Storage structure:
 /// STORED_SHARDS_KEY -> Vec<(Shard)>
 /// (LAST_BLOCK_KEY, Shard) -> (Blockhash, BlockNr) (look up current blockchain state)
 /// (Shard , Block number) -> Blockhash (needed for block pruning)
 /// Blockhash -> Signed Block (actual block storage)
+/// (OPERATION_INDEX_KEY, Operation hash) -> (Shard, Block number, Blockhash) (look up including block)
+/// (FORK_HEADS_KEY, Shard) -> Vec<(Blockhash, BlockNr)> (heads of the non-canonical branches)
+/// (FINALIZED_BLOCK_KEY, Shard) -> (Blockhash, BlockNr) (last finalized block)
+/// SCHEMA_VERSION_KEY -> u32 (version of this layout, see `migration`)
 
-/// Interface struct to rocks DB
-pub struct SidechainDB {
-	db: DB,
+/// Typed interface to the key-value backend
+pub struct SidechainDB<Backend> {
+	backend: Backend,
 }
 
-impl SidechainDB {
-	pub fn open_default(path: PathBuf) -> Result<SidechainDB> {
-		Ok(SidechainDB { db: DB::open_default(path)? })
+impl<Backend: KeyValueBackend> SidechainDB<Backend> {
+	pub fn new(backend: Backend) -> SidechainDB<Backend> {
+		SidechainDB { backend }
 	}
 
 	/// returns the decoded value of the DB entry, if there is one
 	pub fn get<K: Encode, V: Decode>(&self, key: K) -> Result<Option<V>> {
-		match self.db.get(key.encode())? {
+		match self.backend.get(&key.encode())? {
 			None => Ok(None),
 			Some(encoded_hash) => Ok(Some(V::decode(&mut encoded_hash.as_slice())?)),
 		}
@@ -46,22 +51,14 @@ impl SidechainDB {
 
 	/// writes a batch to the DB
 	pub fn write(&mut self, batch: WriteBatch) -> Result<()> {
-		self.db.write(batch).map_err(Error::Operational)
-	}
-
-	/// adds a given key value pair to the batch
-	pub fn add_to_batch<K: Encode, V: Encode>(batch: &mut WriteBatch, key: K, value: V) {
-		batch.put(key.encode(), &value.encode())
-	}
-
-	/// adds a delte key command to the batch
-	pub fn delete_to_batch<K: Encode>(batch: &mut WriteBatch, key: K) {
-		batch.delete(key.encode())
+		self.backend.write(batch)
 	}
 
 	/// add an entry to the DB
 	#[cfg(test)]
 	pub fn put<K: Encode, V: Encode>(&mut self, key: K, value: V) -> Result<()> {
-		self.db.put(key.encode(), value.encode()).map_err(Error::Operational)
+		let mut batch = WriteBatch::default();
+		batch.put(key, value);
+		self.write(batch)
 	}
 }
@@ -15,14 +15,15 @@
 
 */
 
+use its_primitives::types::BlockHash;
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Error, Debug)]
 pub enum Error {
-	#[error("Could not interact with file storage: {0:?}")]
-	Operational(#[from] rocksdb::Error),
+	#[error("Could not interact with file storage: {0}")]
+	Operational(String),
 	#[error("Last Block of shard {0} not found")]
 	LastBlockNotFound(String),
 	#[error("Failed to find parent block")]
@@ -31,4 +32,27 @@ pub enum Error {
 	Decode(#[from] codec::Error),
 	#[error("Given block is not a successor of the last known block")]
 	HeaderAncestryMismatch,
+	#[error("Could not access backup file: {0:?}")]
+	Io(#[from] std::io::Error),
+	#[error("Invalid sidechain backup: {0}")]
+	InvalidBackup(String),
+	#[error("Unsupported sidechain backup format version {0}")]
+	UnsupportedBackupVersion(u32),
+	#[error("Backups can only be imported into an empty storage")]
+	StorageNotEmpty,
+	#[error("Block {0:?} not found")]
+	BlockNotFound(BlockHash),
+	#[error("Block {0:?} is not part of the canonical chain")]
+	NonCanonicalBlock(BlockHash),
+	#[error("Block {0:?} conflicts with the finalized chain")]
+	ConflictsWithFinalizedBlock(BlockHash),
+	#[error("Sidechain db has schema version {0}, only versions up to {1} are supported")]
+	UnsupportedSchemaVersion(u32, u32),
+}
+
+#[cfg(feature = "rocksdb")]
+impl From<rocksdb::Error> for Error {
+	fn from(error: rocksdb::Error) -> Self {
+		Error::Operational(error.into_string())
+	}
 }
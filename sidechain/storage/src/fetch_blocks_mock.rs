@@ -15,11 +15,15 @@
 
 */
 
-use crate::{error::Result, interface::FetchBlocks};
+use crate::{
+	error::Result,
+	interface::{FetchBlocks, LookupBlocks},
+};
 use its_primitives::{
-	traits::ShardIdentifierFor,
-	types::{BlockHash, SignedBlock},
+	traits::{ShardIdentifierFor, SignedBlock as SignedBlockT},
+	types::{block_metadata::OperationInclusion, BlockHash, BlockNumber, SignedBlock},
 };
+use sp_core::H256;
 
 #[derive(Default)]
 pub struct FetchBlocksMock {
@@ -51,3 +55,55 @@ impl FetchBlocks<SignedBlock> for FetchBlocksMock {
 		Ok(self.blocks_to_be_fetched.clone())
 	}
 }
+
+impl LookupBlocks<SignedBlock> for FetchBlocksMock {
+	fn fetch_block_by_hash(&self, block_hash: &BlockHash) -> Result<Option<SignedBlock>> {
+		Ok(self.blocks_to_be_fetched.iter().find(|b| &b.hash() == block_hash).cloned())
+	}
+
+	fn fetch_block_by_number(
+		&self,
+		_shard_identifier: &ShardIdentifierFor<SignedBlock>,
+		block_number: BlockNumber,
+	) -> Result<Option<SignedBlock>> {
+		Ok(self
+			.blocks_to_be_fetched
+			.iter()
+			.find(|b| b.block.header.block_number == block_number)
+			.cloned())
+	}
+
+	fn fetch_last_blocks(&self) -> Result<Vec<SignedBlock>> {
+		Ok(self.blocks_to_be_fetched.last().cloned().into_iter().collect())
+	}
+
+	fn fetch_blocks_by_number(
+		&self,
+		_shard_identifier: &ShardIdentifierFor<SignedBlock>,
+		from: BlockNumber,
+		limit: u64,
+	) -> Result<Vec<SignedBlock>> {
+		Ok(self
+			.blocks_to_be_fetched
+			.iter()
+			.filter(|b| b.block.header.block_number >= from)
+			.take(limit as usize)
+			.cloned()
+			.collect())
+	}
+
+	fn fetch_operation_inclusion(
+		&self,
+		operation_hash: &H256,
+	) -> Result<Option<OperationInclusion<ShardIdentifierFor<SignedBlock>>>> {
+		Ok(self
+			.blocks_to_be_fetched
+			.iter()
+			.find(|b| b.block.block_data.signed_top_hashes.contains(operation_hash))
+			.map(|b| OperationInclusion {
+				shard: b.block.header.shard_id,
+				block_number: b.block.header.block_number,
+				block_hash: b.hash(),
+			}))
+	}
+}
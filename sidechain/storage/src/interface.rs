@@ -20,24 +20,36 @@ use mockall::predicate::*;
 #[cfg(test)]
 use mockall::*;
 
-use super::{storage::SidechainStorage, Result};
+#[cfg(feature = "rocksdb")]
+use super::backend::RocksDbBackend;
+use super::{backend::KeyValueBackend, backup::SidechainBackup, storage::SidechainStorage, Result};
 use its_primitives::{
 	traits::{ShardIdentifierFor, SignedBlock as SignedBlockT},
-	types::{BlockHash, BlockNumber},
+	types::{block_metadata::OperationInclusion, BlockHash, BlockNumber, Timestamp},
 };
 use parking_lot::RwLock;
+use sp_core::H256;
+#[cfg(feature = "rocksdb")]
 use std::path::PathBuf;
 
 /// Lock wrapper around sidechain storage
-pub struct SidechainStorageLock<SignedBlock: SignedBlockT> {
-	storage: RwLock<SidechainStorage<SignedBlock>>,
+pub struct SidechainStorageLock<SignedBlock: SignedBlockT, Backend> {
+	storage: RwLock<SidechainStorage<SignedBlock, Backend>>,
 }
 
-impl<SignedBlock: SignedBlockT> SidechainStorageLock<SignedBlock> {
-	pub fn new(path: PathBuf) -> Result<SidechainStorageLock<SignedBlock>> {
-		Ok(SidechainStorageLock {
-			storage: RwLock::new(SidechainStorage::<SignedBlock>::new(path)?),
-		})
+impl<SignedBlock: SignedBlockT, Backend: KeyValueBackend>
+	SidechainStorageLock<SignedBlock, Backend>
+{
+	pub fn from_backend(backend: Backend) -> Result<SidechainStorageLock<SignedBlock, Backend>> {
+		Ok(SidechainStorageLock { storage: RwLock::new(SidechainStorage::new(backend)?) })
+	}
+}
+
+#[cfg(feature = "rocksdb")]
+impl<SignedBlock: SignedBlockT> SidechainStorageLock<SignedBlock, RocksDbBackend> {
+	/// Opens the RocksDB storage at the given path.
+	pub fn new(path: PathBuf) -> Result<SidechainStorageLock<SignedBlock, RocksDbBackend>> {
+		Self::from_backend(RocksDbBackend::open_default(path)?)
 	}
 }
 
@@ -50,9 +62,41 @@ pub trait BlockStorage<SignedBlock: SignedBlockT> {
 	fn store_blocks(&self, blocks: Vec<SignedBlock>) -> Result<()>;
 }
 
+#[cfg_attr(test, automock)]
 pub trait BlockPruner {
 	/// Prune all blocks except the newest n, where n = `number_of_blocks_to_keep`.
 	fn prune_blocks_except(&self, number_of_blocks_to_keep: u64);
+
+	/// Prune all blocks with a timestamp older than `min_timestamp` (in ms).
+	/// The last block of a shard is always kept.
+	fn prune_blocks_older_than(&self, min_timestamp: Timestamp);
+}
+
+/// Trait to mark blocks as final.
+#[cfg_attr(test, automock)]
+pub trait FinalizeBlocks<SignedBlock: SignedBlockT> {
+	/// Finalize a canonical block, and thereby all its ancestors.
+	///
+	/// Final blocks are neither retracted by a reorganization, nor pruned before they are final.
+	fn finalize_block(
+		&self,
+		shard_identifier: &ShardIdentifierFor<SignedBlock>,
+		block_hash: &BlockHash,
+	) -> Result<()>;
+}
+
+/// Export and import of the whole storage, e.g. to bootstrap a new worker offline.
+pub trait BackupBlocks<SignedBlock: SignedBlockT> {
+	/// Export the blocks of all shards.
+	fn export_backup(
+		&self,
+	) -> Result<SidechainBackup<ShardIdentifierFor<SignedBlock>, SignedBlock>>;
+
+	/// Import a backup into an empty storage, verifying the ancestry of all blocks.
+	fn import_backup(
+		&self,
+		backup: SidechainBackup<ShardIdentifierFor<SignedBlock>, SignedBlock>,
+	) -> Result<()>;
 }
 
 #[cfg_attr(test, automock)]
@@ -79,19 +123,95 @@ pub trait FetchBlocks<SignedBlock: SignedBlockT> {
 	) -> Result<Vec<SignedBlock>>;
 }
 
-impl<SignedBlock: SignedBlockT> BlockStorage<SignedBlock> for SidechainStorageLock<SignedBlock> {
+/// Trait to look up individual blocks, e.g. for a block explorer.
+#[cfg_attr(test, automock)]
+pub trait LookupBlocks<SignedBlock: SignedBlockT> {
+	/// Fetch a block by its hash.
+	///
+	/// Returns `None` if the block cannot be found in storage.
+	fn fetch_block_by_hash(&self, block_hash: &BlockHash) -> Result<Option<SignedBlock>>;
+
+	/// Fetch a block by its shard and block number.
+	///
+	/// Returns `None` if the block cannot be found in storage.
+	fn fetch_block_by_number(
+		&self,
+		shard_identifier: &ShardIdentifierFor<SignedBlock>,
+		block_number: BlockNumber,
+	) -> Result<Option<SignedBlock>>;
+
+	/// Fetch the last block of every shard in storage.
+	fn fetch_last_blocks(&self) -> Result<Vec<SignedBlock>>;
+
+	/// Fetch up to `limit` blocks of a shard, starting at block number `from` (oldest first).
+	fn fetch_blocks_by_number(
+		&self,
+		shard_identifier: &ShardIdentifierFor<SignedBlock>,
+		from: BlockNumber,
+		limit: u64,
+	) -> Result<Vec<SignedBlock>>;
+
+	/// Fetch the location of the block that included the trusted operation of the given hash.
+	///
+	/// Returns `None` if the operation is not included in any block in storage.
+	fn fetch_operation_inclusion(
+		&self,
+		operation_hash: &H256,
+	) -> Result<Option<OperationInclusion<ShardIdentifierFor<SignedBlock>>>>;
+}
+
+impl<SignedBlock: SignedBlockT, Backend: KeyValueBackend> BlockStorage<SignedBlock>
+	for SidechainStorageLock<SignedBlock, Backend>
+{
 	fn store_blocks(&self, blocks: Vec<SignedBlock>) -> Result<()> {
 		self.storage.write().store_blocks(blocks)
 	}
 }
 
-impl<SignedBlock: SignedBlockT> BlockPruner for SidechainStorageLock<SignedBlock> {
+impl<SignedBlock: SignedBlockT, Backend: KeyValueBackend> BlockPruner
+	for SidechainStorageLock<SignedBlock, Backend>
+{
 	fn prune_blocks_except(&self, number_of_blocks_to_keep: BlockNumber) {
 		self.storage.write().prune_shards(number_of_blocks_to_keep);
 	}
+
+	fn prune_blocks_older_than(&self, min_timestamp: Timestamp) {
+		self.storage.write().prune_shards_by_age(min_timestamp);
+	}
+}
+
+impl<SignedBlock: SignedBlockT, Backend: KeyValueBackend> FinalizeBlocks<SignedBlock>
+	for SidechainStorageLock<SignedBlock, Backend>
+{
+	fn finalize_block(
+		&self,
+		shard_identifier: &ShardIdentifierFor<SignedBlock>,
+		block_hash: &BlockHash,
+	) -> Result<()> {
+		self.storage.write().finalize_block(shard_identifier, block_hash)
+	}
 }
 
-impl<SignedBlock: SignedBlockT> FetchBlocks<SignedBlock> for SidechainStorageLock<SignedBlock> {
+impl<SignedBlock: SignedBlockT, Backend: KeyValueBackend> BackupBlocks<SignedBlock>
+	for SidechainStorageLock<SignedBlock, Backend>
+{
+	fn export_backup(
+		&self,
+	) -> Result<SidechainBackup<ShardIdentifierFor<SignedBlock>, SignedBlock>> {
+		self.storage.read().export_backup()
+	}
+
+	fn import_backup(
+		&self,
+		backup: SidechainBackup<ShardIdentifierFor<SignedBlock>, SignedBlock>,
+	) -> Result<()> {
+		self.storage.write().import_backup(backup)
+	}
+}
+
+impl<SignedBlock: SignedBlockT, Backend: KeyValueBackend> FetchBlocks<SignedBlock>
+	for SidechainStorageLock<SignedBlock, Backend>
+{
 	fn fetch_all_blocks_after(
 		&self,
 		block_hash: &BlockHash,
@@ -111,3 +231,39 @@ impl<SignedBlock: SignedBlockT> FetchBlocks<SignedBlock> for SidechainStorageLoc
 			.get_blocks_in_range(block_hash_from, block_hash_until, shard_identifier)
 	}
 }
+
+impl<SignedBlock: SignedBlockT, Backend: KeyValueBackend> LookupBlocks<SignedBlock>
+	for SidechainStorageLock<SignedBlock, Backend>
+{
+	fn fetch_block_by_hash(&self, block_hash: &BlockHash) -> Result<Option<SignedBlock>> {
+		self.storage.read().get_block(block_hash)
+	}
+
+	fn fetch_block_by_number(
+		&self,
+		shard_identifier: &ShardIdentifierFor<SignedBlock>,
+		block_number: BlockNumber,
+	) -> Result<Option<SignedBlock>> {
+		self.storage.read().get_block_by_number(shard_identifier, block_number)
+	}
+
+	fn fetch_last_blocks(&self) -> Result<Vec<SignedBlock>> {
+		self.storage.read().get_last_blocks()
+	}
+
+	fn fetch_blocks_by_number(
+		&self,
+		shard_identifier: &ShardIdentifierFor<SignedBlock>,
+		from: BlockNumber,
+		limit: u64,
+	) -> Result<Vec<SignedBlock>> {
+		self.storage.read().get_blocks_by_number(shard_identifier, from, limit)
+	}
+
+	fn fetch_operation_inclusion(
+		&self,
+		operation_hash: &H256,
+	) -> Result<Option<OperationInclusion<ShardIdentifierFor<SignedBlock>>>> {
+		self.storage.read().get_operation_inclusion(operation_hash)
+	}
+}
@@ -17,40 +17,80 @@
 
 #![cfg_attr(test, feature(assert_matches))]
 
-use its_primitives::types::BlockNumber;
+use log::*;
 use std::{
 	sync::Arc,
 	thread,
 	time::{Duration, SystemTime},
 };
 
+pub mod backend;
+pub mod backup;
 mod db;
 mod error;
 pub mod interface;
+mod migration;
+pub mod pruning;
 mod storage;
 
+#[cfg(test)]
+mod storage_tests_backup;
+
+#[cfg(test)]
+mod storage_tests_finality;
+
+#[cfg(test)]
+mod storage_tests_fork_choice;
+
 #[cfg(test)]
 mod storage_tests_get_blocks_after;
 
+#[cfg(test)]
+mod storage_tests_get_blocks_by_number;
+
 #[cfg(test)]
 mod storage_tests_get_blocks_in_range;
 
+#[cfg(test)]
+mod storage_tests_migration;
+
+#[cfg(test)]
+mod storage_tests_operation_index;
+
+#[cfg(test)]
+mod storage_tests_pruning;
+
 #[cfg(test)]
 mod test_utils;
 
 #[cfg(feature = "mocks")]
 pub mod fetch_blocks_mock;
 
+#[cfg(feature = "rocksdb")]
+pub use backend::RocksDbBackend;
+pub use backend::{InMemoryBackend, KeyValueBackend, WriteBatch};
 pub use error::{Error, Result};
-pub use interface::{BlockPruner, BlockStorage, SidechainStorageLock};
+pub use interface::{
+	BackupBlocks, BlockPruner, BlockStorage, FinalizeBlocks, LookupBlocks, SidechainStorageLock,
+};
+pub use pruning::PruningPolicy;
+pub use storage::LastSidechainBlock;
 
+/// Periodically prunes the storage according to the pruning policy.
+///
+/// Returns immediately in archive mode, since no blocks are ever pruned.
 pub fn start_sidechain_pruning_loop<D>(
 	storage: &Arc<D>,
 	purge_interval: u64,
-	purge_limit: BlockNumber,
+	pruning_policy: PruningPolicy,
 ) where
 	D: BlockPruner,
 {
+	if pruning_policy.is_archive() {
+		info!("Sidechain storage runs in archive mode, blocks are never pruned");
+		return
+	}
+
 	let interval_time = Duration::from_secs(purge_interval);
 	let mut interval_start = SystemTime::now();
 	loop {
@@ -58,7 +98,7 @@ pub fn start_sidechain_pruning_loop<D>(
 			if elapsed >= interval_time {
 				// update interval time
 				interval_start = SystemTime::now();
-				storage.prune_blocks_except(purge_limit);
+				pruning::prune_with_policy(storage.as_ref(), &pruning_policy);
 			} else {
 				// sleep for the rest of the interval
 				let sleep_time = interval_time - elapsed;
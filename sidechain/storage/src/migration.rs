@@ -0,0 +1,189 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Schema versioning and migrations of the sidechain db.
+//!
+//! The schema version is stored in the db. Upon opening, older layouts are upgraded step by
+//! step, each migration with its own write batch (including the version bump). So a migration
+//! is either applied completely or not at all.
+//!
+//! Schema versions:
+//! - 0: Initial layout, without a stored schema version.
+//! - 1: Adds the trusted operation index (`OPERATION_INDEX_KEY`).
+//! - 2: Adds the heads of non-canonical branches (`FORK_HEADS_KEY`).
+//! - 3: Adds the last finalized block of every shard (`FINALIZED_BLOCK_KEY`).
+
+use crate::{
+	backend::{KeyValueBackend, WriteBatch},
+	db::SidechainDB,
+	storage::{LastSidechainBlock, LAST_BLOCK_KEY, OPERATION_INDEX_KEY, STORED_SHARDS_KEY},
+	Error, Result,
+};
+use its_primitives::{
+	traits::{
+		Block as BlockTrait, BlockData as BlockDataTrait, ShardIdentifierFor,
+		SignedBlock as SignedBlockT,
+	},
+	types::{block_metadata::OperationInclusion, BlockHash},
+};
+use log::*;
+
+/// Version of the storage layout.
+pub type SchemaVersion = u32;
+
+/// key value of the schema version of the sidechain db
+pub(crate) const SCHEMA_VERSION_KEY: &[u8] = b"schema_version";
+
+/// Schema version of the storage layout implemented by this crate.
+pub const CURRENT_SCHEMA_VERSION: SchemaVersion = 3;
+
+/// Migrates the db from one schema version to the next.
+trait Migration<SignedBlock: SignedBlockT, Backend: KeyValueBackend> {
+	/// Schema version this migration upgrades from, to `from_version() + 1`.
+	fn from_version(&self) -> SchemaVersion;
+
+	/// Adds all changes of the migration to the batch.
+	fn migrate(&self, db: &SidechainDB<Backend>, batch: &mut WriteBatch) -> Result<()>;
+}
+
+/// Reads the schema version of the db.
+///
+/// A db without a stored version is either new, or of schema version 0.
+pub fn schema_version<SignedBlock: SignedBlockT, Backend: KeyValueBackend>(
+	db: &SidechainDB<Backend>,
+) -> Result<SchemaVersion> {
+	if let Some(version) = db.get(SCHEMA_VERSION_KEY)? {
+		return Ok(version)
+	}
+	let shards: Option<Vec<ShardIdentifierFor<SignedBlock>>> = db.get(STORED_SHARDS_KEY)?;
+	Ok(if shards.is_some() { 0 } else { CURRENT_SCHEMA_VERSION })
+}
+
+/// Upgrades the db to the current schema version, applying all pending migrations in order.
+pub fn migrate_to_current_version<SignedBlock: SignedBlockT, Backend: KeyValueBackend>(
+	db: &mut SidechainDB<Backend>,
+) -> Result<()> {
+	let mut version = schema_version::<SignedBlock, Backend>(db)?;
+	if version > CURRENT_SCHEMA_VERSION {
+		return Err(Error::UnsupportedSchemaVersion(version, CURRENT_SCHEMA_VERSION))
+	}
+
+	for migration in migrations::<SignedBlock, Backend>() {
+		if migration.from_version() != version {
+			continue
+		}
+		info!("Migrating sidechain db from schema version {} to {}", version, version + 1);
+		let mut batch = WriteBatch::default();
+		migration.migrate(db, &mut batch)?;
+		version += 1;
+		batch.put(SCHEMA_VERSION_KEY, version);
+		db.write(batch)?;
+	}
+
+	// Store the version of new dbs as well.
+	if db.get::<_, SchemaVersion>(SCHEMA_VERSION_KEY)?.is_none() {
+		let mut batch = WriteBatch::default();
+		batch.put(SCHEMA_VERSION_KEY, version);
+		db.write(batch)?;
+	}
+	Ok(())
+}
+
+/// All migrations, ordered by the schema version they upgrade from.
+fn migrations<SignedBlock: SignedBlockT, Backend: KeyValueBackend>(
+) -> Vec<Box<dyn Migration<SignedBlock, Backend>>> {
+	vec![Box::new(AddOperationIndex), Box::new(AddForkHeads), Box::new(AddFinalizedBlocks)]
+}
+
+/// Builds the trusted operation index of all stored blocks.
+struct AddOperationIndex;
+
+impl<SignedBlock: SignedBlockT, Backend: KeyValueBackend> Migration<SignedBlock, Backend>
+	for AddOperationIndex
+{
+	fn from_version(&self) -> SchemaVersion {
+		0
+	}
+
+	fn migrate(&self, db: &SidechainDB<Backend>, batch: &mut WriteBatch) -> Result<()> {
+		let shards: Vec<ShardIdentifierFor<SignedBlock>> =
+			db.get(STORED_SHARDS_KEY)?.unwrap_or_default();
+		for shard in shards {
+			let last_block: LastSidechainBlock = match db.get((LAST_BLOCK_KEY, shard))? {
+				Some(last_block) => last_block,
+				None => continue,
+			};
+
+			// Collect the canonical blocks, until we reach the genesis block or the already pruned blocks.
+			let mut block_hashes = Vec::new();
+			let mut block_number = last_block.number;
+			while let Some(block_hash) = db.get::<_, BlockHash>((shard, block_number))? {
+				block_hashes.push((block_number, block_hash));
+				if block_number == 0 {
+					break
+				}
+				block_number -= 1;
+			}
+
+			// Index in ascending order, so operations included more than once point to the latest block.
+			for (block_number, block_hash) in block_hashes.into_iter().rev() {
+				let block: SignedBlock = match db.get(block_hash)? {
+					Some(block) => block,
+					None => {
+						warn!("Block {:?} of shard {:?} not found, skipping it", block_hash, shard);
+						continue
+					},
+				};
+				let inclusion = OperationInclusion { shard, block_number, block_hash };
+				for operation_hash in block.block().block_data().signed_top_hashes() {
+					batch.put((OPERATION_INDEX_KEY, operation_hash), inclusion);
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Non-canonical branches were not stored before, so there are no fork heads to add.
+struct AddForkHeads;
+
+impl<SignedBlock: SignedBlockT, Backend: KeyValueBackend> Migration<SignedBlock, Backend>
+	for AddForkHeads
+{
+	fn from_version(&self) -> SchemaVersion {
+		1
+	}
+
+	fn migrate(&self, _db: &SidechainDB<Backend>, _batch: &mut WriteBatch) -> Result<()> {
+		Ok(())
+	}
+}
+
+/// Blocks were not finalized before, so there are no finalized blocks to add.
+struct AddFinalizedBlocks;
+
+impl<SignedBlock: SignedBlockT, Backend: KeyValueBackend> Migration<SignedBlock, Backend>
+	for AddFinalizedBlocks
+{
+	fn from_version(&self) -> SchemaVersion {
+		2
+	}
+
+	fn migrate(&self, _db: &SidechainDB<Backend>, _batch: &mut WriteBatch) -> Result<()> {
+		Ok(())
+	}
+}
@@ -0,0 +1,134 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Pruning policies of the sidechain storage.
+
+use crate::interface::BlockPruner;
+use its_primitives::types::{BlockNumber, Timestamp};
+use log::*;
+use serde::{Deserialize, Serialize};
+use std::{
+	fmt::{Display, Formatter, Result as FmtResult},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Defines which sidechain blocks are kept in storage when pruning.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PruningPolicy {
+	/// Keep the newest n blocks of every shard.
+	KeepLast(BlockNumber),
+	/// Keep all blocks that are younger than the given age (according to the block timestamp).
+	/// The last block of a shard is always kept.
+	KeepByAge(Duration),
+	/// Archive mode, never prune any blocks.
+	Archive,
+}
+
+impl PruningPolicy {
+	pub fn is_archive(&self) -> bool {
+		matches!(self, PruningPolicy::Archive)
+	}
+
+	/// Short name of the policy, e.g. to be used as metrics label.
+	pub fn name(&self) -> &'static str {
+		match self {
+			PruningPolicy::KeepLast(_) => "keep_last",
+			PruningPolicy::KeepByAge(_) => "keep_by_age",
+			PruningPolicy::Archive => "archive",
+		}
+	}
+
+	/// Retention of the policy: number of blocks for `KeepLast`, seconds for `KeepByAge`
+	/// and 0 for `Archive`.
+	pub fn retention(&self) -> u64 {
+		match self {
+			PruningPolicy::KeepLast(number_of_blocks) => *number_of_blocks,
+			PruningPolicy::KeepByAge(age) => age.as_secs(),
+			PruningPolicy::Archive => 0,
+		}
+	}
+}
+
+impl Display for PruningPolicy {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			PruningPolicy::KeepLast(number_of_blocks) =>
+				write!(f, "keep the last {} blocks", number_of_blocks),
+			PruningPolicy::KeepByAge(age) => write!(f, "keep blocks younger than {:?}", age),
+			PruningPolicy::Archive => write!(f, "archive (never prune)"),
+		}
+	}
+}
+
+/// Prunes the storage once, according to the given policy.
+pub fn prune_with_policy<D: BlockPruner>(storage: &D, policy: &PruningPolicy) {
+	match policy {
+		PruningPolicy::KeepLast(number_of_blocks) => storage.prune_blocks_except(*number_of_blocks),
+		PruningPolicy::KeepByAge(age) => match min_timestamp_for_age(age) {
+			Some(min_timestamp) => storage.prune_blocks_older_than(min_timestamp),
+			None => error!("Failed to compute pruning threshold for age {:?}", age),
+		},
+		PruningPolicy::Archive => {},
+	}
+}
+
+fn min_timestamp_for_age(age: &Duration) -> Option<Timestamp> {
+	let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+	Some(now.saturating_sub(*age).as_millis() as Timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::interface::MockBlockPruner;
+	use mockall::predicate::eq;
+
+	#[test]
+	fn keep_last_policy_prunes_by_block_number() {
+		let mut pruner = MockBlockPruner::new();
+		pruner.expect_prune_blocks_except().with(eq(42)).times(1).return_const(());
+		pruner.expect_prune_blocks_older_than().never();
+
+		prune_with_policy(&pruner, &PruningPolicy::KeepLast(42));
+	}
+
+	#[test]
+	fn keep_by_age_policy_prunes_by_timestamp() {
+		let mut pruner = MockBlockPruner::new();
+		let max_expected_timestamp = min_timestamp_for_age(&Duration::from_secs(60)).unwrap();
+		pruner.expect_prune_blocks_except().never();
+		pruner
+			.expect_prune_blocks_older_than()
+			.withf(move |t| {
+				*t >= max_expected_timestamp - 60_000 && *t <= max_expected_timestamp + 60_000
+			})
+			.times(1)
+			.return_const(());
+
+		prune_with_policy(&pruner, &PruningPolicy::KeepByAge(Duration::from_secs(60)));
+	}
+
+	#[test]
+	fn archive_policy_never_prunes() {
+		let mut pruner = MockBlockPruner::new();
+		pruner.expect_prune_blocks_except().never();
+		pruner.expect_prune_blocks_older_than().never();
+
+		prune_with_policy(&pruner, &PruningPolicy::Archive);
+		assert!(PruningPolicy::Archive.is_archive());
+	}
+}
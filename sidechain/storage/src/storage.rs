@@ -15,20 +15,35 @@
 
 */
 
-use super::{db::SidechainDB, Error, Result};
+use super::{
+	backend::{KeyValueBackend, WriteBatch},
+	backup::{ShardBackup, SidechainBackup},
+	db::SidechainDB,
+	migration::migrate_to_current_version,
+	Error, Result,
+};
 use codec::{Decode, Encode};
 use its_primitives::{
-	traits::{Block as BlockTrait, Header as HeaderTrait, SignedBlock as SignedBlockT},
-	types::{BlockHash, BlockNumber},
+	fork_choice::is_preferred_fork,
+	traits::{
+		Block as BlockTrait, BlockData as BlockDataTrait, Header as HeaderTrait,
+		SignedBlock as SignedBlockT,
+	},
+	types::{block_metadata::OperationInclusion, BlockHash, BlockNumber, Timestamp},
 };
 use log::*;
-use rocksdb::WriteBatch;
 use sp_core::H256;
-use std::{collections::HashMap, fmt::Debug, path::PathBuf};
+use std::{collections::HashMap, fmt::Debug};
 /// key value of sidechain db of last block
-const LAST_BLOCK_KEY: &[u8] = b"last_sidechainblock";
+pub(crate) const LAST_BLOCK_KEY: &[u8] = b"last_sidechainblock";
 /// key value of the stored shards vector
-const STORED_SHARDS_KEY: &[u8] = b"stored_shards";
+pub(crate) const STORED_SHARDS_KEY: &[u8] = b"stored_shards";
+/// key prefix of the trusted operation hash -> including block index
+pub(crate) const OPERATION_INDEX_KEY: &[u8] = b"operation_index";
+/// key prefix of the heads of the non-canonical branches of a shard
+pub(crate) const FORK_HEADS_KEY: &[u8] = b"fork_heads";
+/// key prefix of the last finalized block of a shard
+pub(crate) const FINALIZED_BLOCK_KEY: &[u8] = b"finalized_block";
 
 /// ShardIdentifier type
 type ShardIdentifierFor<B> =
@@ -46,22 +61,33 @@ pub struct LastSidechainBlock {
 
 /// Struct used to insert newly produced sidechainblocks
 /// into the database
-pub struct SidechainStorage<SignedBlock: SignedBlockT> {
+pub struct SidechainStorage<SignedBlock: SignedBlockT, Backend> {
 	/// database
-	db: SidechainDB,
+	db: SidechainDB<Backend>,
 	/// shards in database
 	shards: Vec<ShardIdentifierFor<SignedBlock>>,
 	/// map to last sidechain block of every shard
 	last_blocks: HashMap<ShardIdentifierFor<SignedBlock>, LastSidechainBlock>,
+	/// map to the heads of the non-canonical branches of every shard
+	fork_heads: HashMap<ShardIdentifierFor<SignedBlock>, Vec<LastSidechainBlock>>,
+	/// map to the last finalized block of every shard
+	finalized_blocks: HashMap<ShardIdentifierFor<SignedBlock>, LastSidechainBlock>,
 }
 
-impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
-	/// loads the DB from the given paths and stores the listed shard
+impl<SignedBlock: SignedBlockT, Backend: KeyValueBackend> SidechainStorage<SignedBlock, Backend> {
+	/// loads the DB from the given backend and stores the listed shard
 	/// and their last blocks in memory for better performance
-	pub fn new(path: PathBuf) -> Result<SidechainStorage<SignedBlock>> {
-		// load db
-		let db = SidechainDB::open_default(path)?;
-		let mut storage = SidechainStorage { db, shards: vec![], last_blocks: HashMap::new() };
+	pub fn new(backend: Backend) -> Result<SidechainStorage<SignedBlock, Backend>> {
+		// load db and upgrade it to the current layout
+		let mut db = SidechainDB::new(backend);
+		migrate_to_current_version::<SignedBlock, Backend>(&mut db)?;
+		let mut storage = SidechainStorage {
+			db,
+			shards: vec![],
+			last_blocks: HashMap::new(),
+			fork_heads: HashMap::new(),
+			finalized_blocks: HashMap::new(),
+		};
 		storage.shards = storage.load_shards_from_db()?;
 		// get last block of each shard
 		for shard in storage.shards.iter() {
@@ -71,6 +97,13 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 				// an empty shard sidechain storage should not exist. Consider deleting this shard from the shards list.
 				error!("Sidechain storage of shard {:?} is empty", shard);
 			}
+			let fork_heads = storage.load_fork_heads_from_db(shard)?;
+			if !fork_heads.is_empty() {
+				storage.fork_heads.insert(*shard, fork_heads);
+			}
+			if let Some(finalized_block) = storage.load_finalized_block_from_db(shard)? {
+				storage.finalized_blocks.insert(*shard, finalized_block);
+			}
 		}
 		Ok(storage)
 	}
@@ -88,6 +121,22 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 		self.last_blocks.get(shard)
 	}
 
+	/// gets the heads of all non-canonical branches of the given shard
+	pub fn fork_heads_of_shard(
+		&self,
+		shard: &ShardIdentifierFor<SignedBlock>,
+	) -> Vec<LastSidechainBlock> {
+		self.fork_heads.get(shard).cloned().unwrap_or_default()
+	}
+
+	/// gets the last finalized block of the given shard, if any block was finalized yet
+	pub fn finalized_block_of_shard(
+		&self,
+		shard: &ShardIdentifierFor<SignedBlock>,
+	) -> Option<&LastSidechainBlock> {
+		self.finalized_blocks.get(shard)
+	}
+
 	/// gets the block hash of the sidechain block of the given shard and block number, if there is such a block
 	pub fn get_block_hash(
 		&self,
@@ -98,11 +147,72 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 	}
 
 	/// gets the block of the given blockhash, if there is such a block
-	#[allow(unused)]
 	pub fn get_block(&self, block_hash: &BlockHash) -> Result<Option<SignedBlock>> {
 		self.db.get(block_hash)
 	}
 
+	/// gets the block of the given shard and block number, if there is such a block
+	pub fn get_block_by_number(
+		&self,
+		shard: &ShardIdentifierFor<SignedBlock>,
+		block_number: BlockNumber,
+	) -> Result<Option<SignedBlock>> {
+		match self.get_block_hash(shard, block_number)? {
+			Some(block_hash) => self.get_block(&block_hash),
+			None => Ok(None),
+		}
+	}
+
+	/// Get the last block of every stored shard.
+	pub fn get_last_blocks(&self) -> Result<Vec<SignedBlock>> {
+		let mut last_blocks = Vec::with_capacity(self.last_blocks.len());
+		for shard in self.shards.iter() {
+			if let Some(last_block) = self.last_block_of_shard(shard) {
+				let block = self
+					.get_block(&last_block.hash)?
+					.ok_or_else(|| Error::LastBlockNotFound(format!("{:?}", *shard)))?;
+				last_blocks.push(block);
+			}
+		}
+		Ok(last_blocks)
+	}
+
+	/// Get up to `limit` consecutive blocks of a shard, starting at block number `from` (oldest first).
+	///
+	/// Block numbers that are not (or no longer) in storage are skipped, by starting at the oldest
+	/// stored block if `from` is older. The result never includes blocks beyond the last block
+	/// of the shard.
+	pub fn get_blocks_by_number(
+		&self,
+		shard: &ShardIdentifierFor<SignedBlock>,
+		from: BlockNumber,
+		limit: u64,
+	) -> Result<Vec<SignedBlock>> {
+		let last_block_number = match self.last_block_of_shard(shard) {
+			Some(last_block) => last_block.number,
+			None => return Ok(Vec::new()),
+		};
+
+		let mut blocks = Vec::new();
+		let mut current_block_number =
+			self.oldest_stored_block_number_from(shard, from, last_block_number)?;
+		while current_block_number <= last_block_number && (blocks.len() as u64) < limit {
+			if let Some(block) = self.get_block_by_number(shard, current_block_number)? {
+				blocks.push(block);
+			}
+			current_block_number += 1;
+		}
+		Ok(blocks)
+	}
+
+	/// gets the block that included the trusted operation of the given hash, if there is such a block
+	pub fn get_operation_inclusion(
+		&self,
+		operation_hash: &H256,
+	) -> Result<Option<OperationInclusion<ShardIdentifierFor<SignedBlock>>>> {
+		self.db.get((OPERATION_INDEX_KEY, operation_hash))
+	}
+
 	/// Get all blocks after (i.e. children of) a specified block.
 	pub fn get_blocks_after(
 		&self,
@@ -165,17 +275,28 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 	///
 	/// Blocks are iterated through one by one. In case more than one block per shard is included,
 	/// be sure to give them in the correct order (oldest first).
+	///
+	/// A block that does not extend the last block of its shard, but another stored block,
+	/// is stored as part of a non-canonical branch (see `store_fork_block`).
 	pub fn store_blocks(&mut self, blocks_to_store: Vec<SignedBlock>) -> Result<()> {
 		let mut batch = WriteBatch::default();
 		let mut new_shard = false;
 		for block in blocks_to_store.into_iter() {
-			if let Err(e) = self.add_block_to_batch(&block, &mut new_shard, &mut batch) {
-				error!("Could not store block {:?} due to: {:?}", block, e);
-			};
+			match self.add_block_to_batch(&block, &mut new_shard, &mut batch) {
+				Ok(()) => {},
+				Err(Error::HeaderAncestryMismatch) => {
+					// Forks are resolved based on the stored blocks, so write the preceding blocks first.
+					self.db.write(std::mem::take(&mut batch))?;
+					if let Err(e) = self.store_fork_block(&block) {
+						error!("Could not store block {:?} due to: {:?}", block, e);
+					}
+				},
+				Err(e) => error!("Could not store block {:?} due to: {:?}", block, e),
+			}
 		}
 		// Update stored_shards_key -> vec<shard> only if a new shard was included,
 		if new_shard {
-			SidechainDB::add_to_batch(&mut batch, STORED_SHARDS_KEY, self.shards().clone());
+			batch.put(STORED_SHARDS_KEY, self.shards().clone());
 		}
 		// Store everything.
 		self.db.write(batch)
@@ -188,19 +309,24 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 
 		// remove last block from db storage
 		let mut batch = WriteBatch::default();
-		self.delete_last_block(&mut batch, &last_block, shard);
+		self.delete_last_block(&mut batch, &last_block, shard)?;
 
 		// Remove the rest of the blocks from the db
 		let mut current_block_number = last_block.number;
 		while let Some(previous_block) = self.get_previous_block(shard, current_block_number)? {
 			current_block_number = previous_block.number;
-			self.delete_block(&mut batch, &previous_block.hash, &current_block_number, shard);
+			self.delete_block(&mut batch, &previous_block.hash, &current_block_number, shard)?;
 		}
+		// Remove all non-canonical branches.
+		self.delete_fork_branches(&mut batch, shard, BlockNumber::MAX)?;
+		// Remove the finality of the shard.
+		batch.delete((FINALIZED_BLOCK_KEY, *shard));
+		self.finalized_blocks.remove(shard);
 		// Remove shard from list.
 		// STORED_SHARDS_KEY -> Vec<(Shard)>
 		self.shards.retain(|&x| x != *shard);
 		// Add updated shards to batch.
-		SidechainDB::add_to_batch(&mut batch, STORED_SHARDS_KEY, &self.shards);
+		batch.put(STORED_SHARDS_KEY, &self.shards);
 		// Update DB
 		self.db.write(batch)
 	}
@@ -219,23 +345,39 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 		} else {
 			// iterate through chain and add all blocks to WriteBatch (delete cmd)
 			let mut batch = WriteBatch::default();
-			let mut current_block_number = block_number;
-			// Remove blocks from db until no block anymore
-			while let Some(block_hash) = self.get_block_hash(shard, current_block_number)? {
-				self.delete_block(&mut batch, &block_hash, &current_block_number, shard);
-				current_block_number -= 1;
+			let mut maybe_block_number = Some(block_number);
+			// Remove blocks from db until no block anymore (or block 0 has been removed).
+			while let Some(current_block_number) = maybe_block_number {
+				match self.get_block_hash(shard, current_block_number)? {
+					Some(block_hash) => {
+						self.delete_block(&mut batch, &block_hash, &current_block_number, shard)?;
+						maybe_block_number = current_block_number.checked_sub(1);
+					},
+					None => break,
+				}
 			}
+			// Remove the blocks of the non-canonical branches up to the same block number.
+			self.delete_fork_branches(&mut batch, shard, block_number)?;
 			// Update DB
 			self.db.write(batch)
 		}
 	}
 
 	/// Prunes all shards except for the newest blocks (according to blocknumber).
+	///
+	/// The last block of a shard is always kept, so a shard is never purged completely.
 	pub fn prune_shards(&mut self, number_of_blocks_to_keep: BlockNumber) {
+		let number_of_blocks_to_keep = number_of_blocks_to_keep.max(1);
 		for shard in self.shards().clone() {
 			// get last block:
 			if let Some(last_block) = self.last_block_of_shard(&shard) {
-				let threshold_block = last_block.number - number_of_blocks_to_keep;
+				// Nothing to prune if the shard does not (yet) contain more blocks than we keep.
+				let threshold_block = match last_block.number.checked_sub(number_of_blocks_to_keep)
+				{
+					Some(number) => number,
+					None => continue,
+				};
+				let threshold_block = self.limit_to_finalized_blocks(&shard, threshold_block);
 				if let Err(e) = self.prune_shard_from_block_number(&shard, threshold_block) {
 					error!("Could not purge shard {:?} due to {:?}", shard, e);
 				}
@@ -245,6 +387,372 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 		}
 	}
 
+	/// Prunes all blocks of all shards with a timestamp older than `min_timestamp`.
+	///
+	/// The last block of a shard is always kept, so a shard is never purged completely.
+	pub fn prune_shards_by_age(&mut self, min_timestamp: Timestamp) {
+		for shard in self.shards().clone() {
+			match self.newest_block_number_older_than(&shard, min_timestamp) {
+				Ok(Some(threshold_block)) => {
+					let threshold_block = self.limit_to_finalized_blocks(&shard, threshold_block);
+					if let Err(e) = self.prune_shard_from_block_number(&shard, threshold_block) {
+						error!("Could not purge shard {:?} due to {:?}", shard, e);
+					}
+				},
+				Ok(None) => {},
+				Err(e) =>
+					error!("Could not determine blocks to prune in shard {:?}: {:?}", shard, e),
+			}
+		}
+	}
+
+	/// Marks a canonical block, and thereby all its ancestors, as final.
+	///
+	/// Final blocks are never retracted by a reorganization, so the non-canonical branches
+	/// up to the finalized block are deleted. Finalizing a block at or below the last
+	/// finalized block of the shard has no effect.
+	pub fn finalize_block(
+		&mut self,
+		shard: &ShardIdentifierFor<SignedBlock>,
+		block_hash: &BlockHash,
+	) -> Result<()> {
+		let block = self.get_block(block_hash)?.ok_or(Error::BlockNotFound(*block_hash))?;
+		let block_number = block.block().header().block_number();
+		if block.block().header().shard_id() != *shard
+			|| self.get_block_hash(shard, block_number)? != Some(*block_hash)
+		{
+			return Err(Error::NonCanonicalBlock(*block_hash))
+		}
+
+		if let Some(finalized_block) = self.finalized_block_of_shard(shard) {
+			if finalized_block.number >= block_number {
+				debug!("Block {:?} of shard {:?} is already final", block_hash, shard);
+				return Ok(())
+			}
+		}
+
+		let mut batch = WriteBatch::default();
+		self.delete_fork_branches(&mut batch, shard, block_number)?;
+		let finalized_block = LastSidechainBlock { hash: *block_hash, number: block_number };
+		batch.put((FINALIZED_BLOCK_KEY, *shard), finalized_block);
+		self.finalized_blocks.insert(*shard, finalized_block);
+		debug!("Finalized block {:?} (nr: {}) of shard {:?}", block_hash, block_number, shard);
+		self.db.write(batch)
+	}
+
+	/// Exports the blocks of all shards, e.g. to bootstrap another worker offline.
+	pub fn export_backup(
+		&self,
+	) -> Result<SidechainBackup<ShardIdentifierFor<SignedBlock>, SignedBlock>> {
+		let mut shard_backups = Vec::with_capacity(self.shards.len());
+		for shard in self.shards.iter() {
+			let last_block = self.get_last_block_of_shard(shard)?;
+
+			// Iterate backwards until we reach the genesis block or the already pruned blocks.
+			let mut blocks = Vec::new();
+			let mut block_number = last_block.number;
+			while block_number > 0 {
+				match self.get_block_by_number(shard, block_number)? {
+					Some(block) => blocks.push(block),
+					None => break,
+				}
+				block_number -= 1;
+			}
+			blocks.reverse();
+
+			shard_backups.push(ShardBackup { shard: *shard, last_block, blocks });
+		}
+		Ok(SidechainBackup { shards: shard_backups })
+	}
+
+	/// Imports a backup into an empty storage.
+	///
+	/// The signature of every block is verified, as well as its ancestry: each block has to build
+	/// on the previous one by block number and parent hash. If a single block does not fit,
+	/// the whole import is aborted and nothing is written to the db.
+	pub fn import_backup(
+		&mut self,
+		backup: SidechainBackup<ShardIdentifierFor<SignedBlock>, SignedBlock>,
+	) -> Result<()> {
+		if !self.shards.is_empty() {
+			return Err(Error::StorageNotEmpty)
+		}
+
+		let result = self.import_shard_backups(backup.shards);
+		if result.is_err() {
+			// Nothing has been written to the db, so reset the state kept in memory.
+			self.shards.clear();
+			self.last_blocks.clear();
+		}
+		result
+	}
+
+	fn import_shard_backups(
+		&mut self,
+		shard_backups: Vec<ShardBackup<ShardIdentifierFor<SignedBlock>, SignedBlock>>,
+	) -> Result<()> {
+		let mut batch = WriteBatch::default();
+		for shard_backup in shard_backups.iter() {
+			let shard = &shard_backup.shard;
+			if self.shards.contains(shard) {
+				return Err(Error::InvalidBackup(format!("Shard {:?} is contained twice", shard)))
+			}
+
+			let mut new_shard = false;
+			for block in shard_backup.blocks.iter() {
+				if &block.block().header().shard_id() != shard {
+					return Err(Error::InvalidBackup(format!(
+						"Block {:?} does not belong to shard {:?}",
+						block.hash(),
+						shard
+					)))
+				}
+				if !block.verify_signature() {
+					return Err(Error::InvalidBackup(format!(
+						"Block {:?} has an invalid signature",
+						block.hash()
+					)))
+				}
+				// Checks the block number and the parent hash against the previous block.
+				self.add_block_to_batch(block, &mut new_shard, &mut batch)?;
+			}
+
+			if self.last_block_of_shard(shard) != Some(&shard_backup.last_block) {
+				return Err(Error::InvalidBackup(format!(
+					"Last block of shard {:?} does not match the contained blocks",
+					shard
+				)))
+			}
+		}
+		batch.put(STORED_SHARDS_KEY, self.shards().clone());
+		self.db.write(batch)
+	}
+
+	/// Stores a block that does not extend the last block of its shard, but another stored block.
+	///
+	/// Blocks of such non-canonical branches are only stored by their hash. In case the fork choice
+	/// rule prefers the new branch over the canonical chain, the shard is reorganized onto it.
+	fn store_fork_block(&mut self, signed_block: &SignedBlock) -> Result<()> {
+		let block_hash = signed_block.hash();
+		if self.get_block(&block_hash)?.is_some() {
+			debug!("Block {:?} is already stored", block_hash);
+			return Ok(())
+		}
+
+		let header = signed_block.block().header();
+		let shard = header.shard_id();
+		// Any other block up to the finalized block number conflicts with the finalized chain.
+		if let Some(finalized_block) = self.finalized_block_of_shard(&shard) {
+			if header.block_number() <= finalized_block.number {
+				return Err(Error::ConflictsWithFinalizedBlock(block_hash))
+			}
+		}
+		let parent = self.get_block(&header.parent_hash())?.ok_or(Error::HeaderAncestryMismatch)?;
+		if parent.block().header().shard_id() != shard
+			|| parent.block().header().block_number() + 1 != header.block_number()
+		{
+			return Err(Error::HeaderAncestryMismatch)
+		}
+
+		let last_block = self.get_last_block_of_shard(&shard)?;
+		let canonical_head = self
+			.get_block(&last_block.hash)?
+			.ok_or_else(|| Error::LastBlockNotFound(format!("{:?}", shard)))?;
+
+		let mut batch = WriteBatch::default();
+		let mut fork_heads = self.fork_heads_of_shard(&shard);
+		fork_heads.retain(|fork_head| fork_head.hash != parent.hash());
+
+		if is_preferred_fork(signed_block.block(), canonical_head.block()) {
+			self.reorganize(&mut batch, signed_block, &last_block)?;
+			fork_heads.push(last_block);
+		} else {
+			debug!(
+				"Storing block {:?} (nr: {}) of a non-canonical branch in shard {:?}",
+				block_hash,
+				header.block_number(),
+				shard
+			);
+			batch.put(block_hash, signed_block);
+			fork_heads.push(LastSidechainBlock { hash: block_hash, number: header.block_number() });
+		}
+
+		self.add_fork_heads_to_batch(&mut batch, &shard, fork_heads);
+		self.db.write(batch)
+	}
+
+	/// Reorganizes the canonical chain of a shard onto the branch ending in `new_head`.
+	///
+	/// The retracted blocks are kept as a non-canonical branch. Fails if the reorganization
+	/// would retract a final block.
+	fn reorganize(
+		&mut self,
+		batch: &mut WriteBatch,
+		new_head: &SignedBlock,
+		old_head: &LastSidechainBlock,
+	) -> Result<()> {
+		let shard = new_head.block().header().shard_id();
+
+		// Collect the blocks of the new branch, back to the common ancestor with the canonical chain.
+		let mut enacted = vec![new_head.clone()];
+		let mut parent_hash = new_head.block().header().parent_hash();
+		loop {
+			let parent = self.get_block(&parent_hash)?.ok_or(Error::FailedToFindParentBlock)?;
+			let parent_number = parent.block().header().block_number();
+			if self.get_block_hash(&shard, parent_number)? == Some(parent_hash) {
+				break
+			}
+			parent_hash = parent.block().header().parent_hash();
+			enacted.push(parent);
+		}
+		enacted.reverse();
+
+		// Retract the canonical blocks above the common ancestor.
+		let common_ancestor_number = enacted[0].block().header().block_number() - 1;
+		if let Some(finalized_block) = self.finalized_block_of_shard(&shard) {
+			if finalized_block.number > common_ancestor_number {
+				return Err(Error::ConflictsWithFinalizedBlock(new_head.hash()))
+			}
+		}
+		for block_number in (common_ancestor_number + 1..=old_head.number).rev() {
+			if let Some(block) = self.get_block_by_number(&shard, block_number)? {
+				self.delete_operation_index(batch, &block)?;
+			}
+			batch.delete((shard, block_number));
+		}
+
+		// Enact the blocks of the new branch.
+		for block in enacted.iter() {
+			self.add_last_block(batch, block);
+		}
+
+		info!(
+			"Reorganized shard {:?} from block {:?} (nr: {}) to block {:?} (nr: {}), common ancestor nr: {}",
+			shard,
+			old_head.hash,
+			old_head.number,
+			new_head.hash(),
+			new_head.block().header().block_number(),
+			common_ancestor_number
+		);
+		Ok(())
+	}
+
+	/// Adds delete commands of all non-canonical blocks up to and including `block_number` to the WriteBatch.
+	///
+	/// Fork heads up to and including `block_number` are removed.
+	fn delete_fork_branches(
+		&mut self,
+		batch: &mut WriteBatch,
+		shard: &ShardIdentifierFor<SignedBlock>,
+		block_number: BlockNumber,
+	) -> Result<()> {
+		let mut fork_heads = self.fork_heads_of_shard(shard);
+		if fork_heads.is_empty() {
+			return Ok(())
+		}
+
+		for fork_head in fork_heads.iter() {
+			let mut current_hash = fork_head.hash;
+			// Walk back until we reach the canonical chain (or the already pruned blocks).
+			while let Some(block) = self.get_block(&current_hash)? {
+				let current_number = block.block().header().block_number();
+				if self.get_block_hash(shard, current_number)? == Some(current_hash) {
+					break
+				}
+				if current_number <= block_number {
+					batch.delete(current_hash);
+				}
+				current_hash = block.block().header().parent_hash();
+			}
+		}
+
+		fork_heads.retain(|fork_head| fork_head.number > block_number);
+		self.add_fork_heads_to_batch(batch, shard, fork_heads);
+		Ok(())
+	}
+
+	/// Adds the fork heads of a shard to the WriteBatch and updates them in memory.
+	fn add_fork_heads_to_batch(
+		&mut self,
+		batch: &mut WriteBatch,
+		shard: &ShardIdentifierFor<SignedBlock>,
+		fork_heads: Vec<LastSidechainBlock>,
+	) {
+		if fork_heads.is_empty() {
+			batch.delete((FORK_HEADS_KEY, *shard));
+			self.fork_heads.remove(shard);
+		} else {
+			batch.put((FORK_HEADS_KEY, *shard), &fork_heads);
+			self.fork_heads.insert(*shard, fork_heads);
+		}
+	}
+
+	/// Limits the pruning threshold of a shard to its finalized blocks.
+	///
+	/// Blocks that are not yet final might still be needed, so only shards without any
+	/// finalized block are pruned up to the threshold regardless.
+	fn limit_to_finalized_blocks(
+		&self,
+		shard: &ShardIdentifierFor<SignedBlock>,
+		threshold_block: BlockNumber,
+	) -> BlockNumber {
+		match self.finalized_block_of_shard(shard) {
+			Some(finalized_block) => threshold_block.min(finalized_block.number),
+			None => threshold_block,
+		}
+	}
+
+	/// Returns the number of the oldest stored block in `from..=last_block_number`.
+	///
+	/// Pruning only ever removes the oldest blocks of a shard, so the stored block numbers are
+	/// contiguous up to the last block. This allows a binary search instead of iterating over
+	/// all pruned block numbers.
+	fn oldest_stored_block_number_from(
+		&self,
+		shard: &ShardIdentifierFor<SignedBlock>,
+		from: BlockNumber,
+		last_block_number: BlockNumber,
+	) -> Result<BlockNumber> {
+		let (mut lower, mut upper) = (from, last_block_number);
+		while lower < upper {
+			let middle = lower + (upper - lower) / 2;
+			if self.get_block_hash(shard, middle)?.is_some() {
+				upper = middle;
+			} else {
+				lower = middle + 1;
+			}
+		}
+		Ok(lower)
+	}
+
+	/// Returns the number of the newest block (excluding the last block) that is older than `min_timestamp`.
+	///
+	/// Imported sidechain chains start at block 1, but a stored block 0 (e.g. written by a test or
+	/// an imported backup) is considered like any other block.
+	fn newest_block_number_older_than(
+		&self,
+		shard: &ShardIdentifierFor<SignedBlock>,
+		min_timestamp: Timestamp,
+	) -> Result<Option<BlockNumber>> {
+		let last_block_number = match self.last_block_of_shard(shard) {
+			Some(last_block) => last_block.number,
+			None => return Ok(None),
+		};
+
+		let mut maybe_block_number = last_block_number.checked_sub(1);
+		while let Some(block_number) = maybe_block_number {
+			match self.get_block_by_number(shard, block_number)? {
+				Some(block) if block.block().block_data().timestamp() < min_timestamp =>
+					return Ok(Some(block_number)),
+				Some(_) => maybe_block_number = block_number.checked_sub(1),
+				// Reached the blocks that were already pruned.
+				None => return Ok(None),
+			}
+		}
+		Ok(None)
+	}
+
 	fn add_block_to_batch(
 		&mut self,
 		signed_block: &SignedBlock,
@@ -275,6 +783,11 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 				current_block_nr, last_block.number, *shard);
 				return false
 			}
+			if last_block.hash != block.header().parent_hash() {
+				error!("[Sidechain DB] Sidechainblock (nr: {:?}) does not build on the last block (hash: {:?}) in shard: {:?}",
+				current_block_nr, last_block.hash, *shard);
+				return false
+			}
 		} else {
 			error!(
 				"[Sidechain DB] Shard {:?} does not have a last block. Skipping block (nr: {:?}) inclusion",
@@ -308,6 +821,20 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 		self.db.get((LAST_BLOCK_KEY, *shard))
 	}
 
+	fn load_fork_heads_from_db(
+		&self,
+		shard: &ShardIdentifierFor<SignedBlock>,
+	) -> Result<Vec<LastSidechainBlock>> {
+		Ok(self.db.get((FORK_HEADS_KEY, *shard))?.unwrap_or_default())
+	}
+
+	fn load_finalized_block_from_db(
+		&self,
+		shard: &ShardIdentifierFor<SignedBlock>,
+	) -> Result<Option<LastSidechainBlock>> {
+		self.db.get((FINALIZED_BLOCK_KEY, *shard))
+	}
+
 	fn get_last_block_of_shard(
 		&self,
 		shard: &ShardIdentifierFor<SignedBlock>,
@@ -328,15 +855,21 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 		let block_number = block.block().header().block_number();
 		let shard = block.block().header().shard_id();
 		// Block hash -> Signed Block.
-		SidechainDB::add_to_batch(batch, hash, block);
+		batch.put(hash, block);
 
 		// (Shard, Block number) -> Blockhash (for block pruning).
-		SidechainDB::add_to_batch(batch, (shard, block_number), hash);
+		batch.put((shard, block_number), hash);
+
+		// (operation_index_key, Operation hash) -> (Shard, Block number, Blockhash).
+		let inclusion = OperationInclusion { shard, block_number, block_hash: hash };
+		for operation_hash in block.block().block_data().signed_top_hashes() {
+			batch.put((OPERATION_INDEX_KEY, operation_hash), inclusion);
+		}
 
 		// (last_block_key, shard) -> (Blockhash, BlockNr) current blockchain state.
 		let last_block = LastSidechainBlock { hash, number: block_number };
 		self.last_blocks.insert(shard, last_block); // add in memory
-		SidechainDB::add_to_batch(batch, (LAST_BLOCK_KEY, shard), last_block);
+		batch.put((LAST_BLOCK_KEY, shard), last_block);
 	}
 
 	/// Add delete block to the WriteBatch.
@@ -346,11 +879,31 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 		block_hash: &H256,
 		block_number: &BlockNumber,
 		shard: &ShardIdentifierFor<SignedBlock>,
-	) {
+	) -> Result<()> {
+		// (operation_index_key, Operation hash) -> (Shard, Block number, Blockhash).
+		if let Some(block) = self.get_block(block_hash)? {
+			self.delete_operation_index(batch, &block)?;
+		}
 		// Block hash -> Signed Block.
-		SidechainDB::delete_to_batch(batch, block_hash);
+		batch.delete(block_hash);
 		// (Shard, Block number) -> Blockhash (for block pruning).
-		SidechainDB::delete_to_batch(batch, (shard, block_number));
+		batch.delete((shard, block_number));
+		Ok(())
+	}
+
+	/// Add delete commands of the operation index entries pointing to the given block to the WriteBatch.
+	///
+	/// Entries of operations that have been re-included in another block are kept.
+	fn delete_operation_index(&self, batch: &mut WriteBatch, block: &SignedBlock) -> Result<()> {
+		let block_hash = block.hash();
+		for operation_hash in block.block().block_data().signed_top_hashes() {
+			if let Some(inclusion) = self.get_operation_inclusion(operation_hash)? {
+				if inclusion.block_hash == block_hash {
+					batch.delete((OPERATION_INDEX_KEY, operation_hash));
+				}
+			}
+		}
+		Ok(())
 	}
 
 	/// Add delete command to remove last block to WriteBatch and remove it from memory.
@@ -359,6 +912,7 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 	/// - Block hash -> Signed Block.
 	/// - (Shard, Block number) -> Blockhash (for block pruning).
 	/// - ((LAST_BLOCK_KEY, shard) -> BlockHash) -> Blockhash (for block pruning).
+	/// - (OPERATION_INDEX_KEY, Operation hash) -> Including block, for all operations of the block.
 	///
 	/// Careful usage of this command: In case the last block is deleted, (LAST_BLOCK_KEY, shard) will be empty
 	/// even though there might be a new last block (i.e. the previous block of the removed last block).
@@ -367,17 +921,18 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 		batch: &mut WriteBatch,
 		last_block: &LastSidechainBlock,
 		shard: &ShardIdentifierFor<SignedBlock>,
-	) {
+	) -> Result<()> {
 		// Add delete block to batch.
 		// (LAST_BLOCK_KEY, Shard) -> LastSidechainBlock.
-		SidechainDB::delete_to_batch(batch, (LAST_BLOCK_KEY, *shard));
-		self.delete_block(batch, &last_block.hash, &last_block.number, shard);
+		batch.delete((LAST_BLOCK_KEY, *shard));
+		self.delete_block(batch, &last_block.hash, &last_block.number, shard)?;
 
 		// Delete last block from local memory.
 		// Careful here: This deletes the local memory before db has been actually pruned
 		// (it's only been added to the write batch).
 		// But this can be fixed upon reloading the db / restarting the worker.
 		self.last_blocks.remove(shard);
+		Ok(())
 	}
 }
 
@@ -385,21 +940,29 @@ impl<SignedBlock: SignedBlockT> SidechainStorage<SignedBlock> {
 mod test {
 	use super::*;
 	use crate::test_utils::{
-		create_signed_block_with_shard as create_signed_block, create_temp_dir, get_storage,
+		create_signed_block_with_shard as create_signed_block,
+		create_signed_block_with_shard_and_parenthash, test_all_backends, StorageFixture,
 	};
 	use itp_types::ShardIdentifier;
 	use its_primitives::{traits::SignedBlock as SignedBlockT, types::SignedBlock};
 	use sp_core::H256;
 
-	#[test]
-	fn load_shards_from_db_works() {
+	fn create_child_block(parent: &SignedBlock) -> SignedBlock {
+		create_signed_block_with_shard_and_parenthash(
+			parent.block().header().block_number() + 1,
+			parent.block().header().shard_id(),
+			parent.hash(),
+		)
+	}
+
+	fn load_shards_from_db_works<F: StorageFixture>() {
 		// given
-		let temp_dir = create_temp_dir();
+		let fixture = F::new();
 		let shard_one = H256::from_low_u64_be(1);
 		let shard_two = H256::from_low_u64_be(2);
 		// when
 		{
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			// ensure db starts empty
 			assert_eq!(sidechain_db.load_shards_from_db().unwrap(), vec![]);
 			// write signed_block to db
@@ -409,17 +972,16 @@ mod test {
 		// then
 		{
 			// open new DB of same path:
-			let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let updated_sidechain_db = fixture.storage();
 			let loaded_shards = updated_sidechain_db.load_shards_from_db().unwrap();
 			assert!(loaded_shards.contains(&shard_one));
 			assert!(loaded_shards.contains(&shard_two));
 		}
 	}
 
-	#[test]
-	fn load_last_block_from_db_works() {
+	fn load_last_block_from_db_works<F: StorageFixture>() {
 		// given
-		let temp_dir = create_temp_dir();
+		let fixture = F::new();
 		let shard = H256::from_low_u64_be(1);
 		let signed_block = create_signed_block(20, shard);
 		let signed_last_block = LastSidechainBlock {
@@ -428,7 +990,7 @@ mod test {
 		};
 		// when
 		{
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			// ensure db starts empty
 			assert!(sidechain_db.load_last_block_from_db(&shard).unwrap().is_none());
 			// write signed_block to db
@@ -438,16 +1000,15 @@ mod test {
 		// then
 		{
 			// open new DB of same path:
-			let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let updated_sidechain_db = fixture.storage();
 			let loaded_block =
 				updated_sidechain_db.load_last_block_from_db(&shard).unwrap().unwrap();
 			assert_eq!(loaded_block, signed_last_block);
 		}
 	}
 
-	#[test]
-	fn create_new_sidechain_storage_works() {
-		let temp_dir = create_temp_dir();
+	fn create_new_sidechain_storage_works<F: StorageFixture>() {
+		let fixture = F::new();
 		let shard = H256::from_low_u64_be(1);
 		let shard_vector = vec![shard];
 		let signed_block = create_signed_block(20, shard);
@@ -456,7 +1017,7 @@ mod test {
 			number: signed_block.block().header().block_number(),
 		};
 		{
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			// ensure db starts empty
 			assert!(sidechain_db.load_last_block_from_db(&shard).unwrap().is_none());
 			// write shards to db
@@ -467,20 +1028,19 @@ mod test {
 
 		{
 			// open new DB of same path:
-			let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let updated_sidechain_db = fixture.storage();
 			assert_eq!(updated_sidechain_db.shards, shard_vector);
 			assert_eq!(*updated_sidechain_db.last_blocks.get(&shard).unwrap(), signed_last_block);
 		}
 	}
 
-	#[test]
-	fn add_last_block_works() {
-		let temp_dir = create_temp_dir();
+	fn add_last_block_works<F: StorageFixture>() {
+		let fixture = F::new();
 		let shard = H256::from_low_u64_be(1);
 		let signed_block = create_signed_block(8, shard);
 
 		{
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			let mut batch = WriteBatch::default();
 			sidechain_db.add_last_block(&mut batch, &signed_block);
 			sidechain_db.db.write(batch).unwrap();
@@ -496,14 +1056,13 @@ mod test {
 		}
 	}
 
-	#[test]
-	fn delete_block_works() {
-		let temp_dir = create_temp_dir();
+	fn delete_block_works<F: StorageFixture>() {
+		let fixture = F::new();
 		let shard = H256::from_low_u64_be(1);
 		let signed_block = create_signed_block(8, shard);
 		{
 			// fill db
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			sidechain_db.db.put(signed_block.hash(), signed_block.clone()).unwrap();
 			sidechain_db
 				.db
@@ -527,18 +1086,20 @@ mod test {
 
 			// when
 			let mut batch = WriteBatch::default();
-			sidechain_db.delete_block(
-				&mut batch,
-				&signed_block.hash(),
-				&signed_block.block().header().block_number(),
-				&shard,
-			);
+			sidechain_db
+				.delete_block(
+					&mut batch,
+					&signed_block.hash(),
+					&signed_block.block().header().block_number(),
+					&shard,
+				)
+				.unwrap();
 			sidechain_db.db.write(batch).unwrap();
 		}
 
 		{
 			// open new DB of same path:
-			let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let updated_sidechain_db = fixture.storage();
 			// ensure DB does not contain block anymore:
 			assert!(updated_sidechain_db
 				.db
@@ -556,9 +1117,8 @@ mod test {
 		}
 	}
 
-	#[test]
-	fn delete_last_block_works() {
-		let temp_dir = create_temp_dir();
+	fn delete_last_block_works<F: StorageFixture>() {
+		let fixture = F::new();
 		let shard = H256::from_low_u64_be(1);
 		let signed_block = create_signed_block(8, shard);
 		let last_block = LastSidechainBlock {
@@ -567,7 +1127,7 @@ mod test {
 		};
 		{
 			// fill db
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			sidechain_db.db.put(signed_block.hash(), signed_block.clone()).unwrap();
 			sidechain_db
 				.db
@@ -600,7 +1160,7 @@ mod test {
 
 			// when
 			let mut batch = WriteBatch::default();
-			sidechain_db.delete_last_block(&mut batch, &last_block, &shard);
+			sidechain_db.delete_last_block(&mut batch, &last_block, &shard).unwrap();
 			sidechain_db.db.write(batch).unwrap();
 
 			// then
@@ -626,18 +1186,17 @@ mod test {
 		}
 	}
 
-	#[test]
-	fn verify_block_ancestry_returns_true_if_correct_successor() {
-		let temp_dir = create_temp_dir();
+	fn verify_block_ancestry_returns_true_if_correct_successor<F: StorageFixture>() {
+		let fixture = F::new();
 		let shard = H256::from_low_u64_be(1);
 		let signed_block = create_signed_block(8, shard);
 		let last_block = LastSidechainBlock {
 			hash: signed_block.hash(),
 			number: signed_block.block().header().block_number(),
 		};
-		let signed_block_two = create_signed_block(9, shard);
+		let signed_block_two = create_child_block(&signed_block);
 		{
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			sidechain_db.shards.push(shard);
 			sidechain_db.last_blocks.insert(shard, last_block);
 			// when
@@ -648,9 +1207,8 @@ mod test {
 		}
 	}
 
-	#[test]
-	fn verify_block_ancestry_returns_false_if_not_correct_successor() {
-		let temp_dir = create_temp_dir();
+	fn verify_block_ancestry_returns_false_if_not_correct_successor<F: StorageFixture>() {
+		let fixture = F::new();
 		let shard = H256::from_low_u64_be(1);
 		let signed_block = create_signed_block(8, shard);
 		let last_block = LastSidechainBlock {
@@ -659,7 +1217,29 @@ mod test {
 		};
 		let signed_block_two = create_signed_block(5, shard);
 		{
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
+			sidechain_db.shards.push(shard);
+			sidechain_db.last_blocks.insert(shard, last_block);
+
+			// when
+			let result = sidechain_db.verify_block_ancestry(&signed_block_two.block());
+
+			// then
+			assert!(!result);
+		}
+	}
+
+	fn verify_block_ancestry_returns_false_if_parent_hash_does_not_match<F: StorageFixture>() {
+		let fixture = F::new();
+		let shard = H256::from_low_u64_be(1);
+		let signed_block = create_signed_block(8, shard);
+		let last_block = LastSidechainBlock {
+			hash: signed_block.hash(),
+			number: signed_block.block().header().block_number(),
+		};
+		let signed_block_two = create_signed_block(9, shard);
+		{
+			let mut sidechain_db = fixture.storage();
 			sidechain_db.shards.push(shard);
 			sidechain_db.last_blocks.insert(shard, last_block);
 
@@ -671,13 +1251,12 @@ mod test {
 		}
 	}
 
-	#[test]
-	fn verify_block_ancestry_returns_false_no_last_block_registered() {
-		let temp_dir = create_temp_dir();
+	fn verify_block_ancestry_returns_false_no_last_block_registered<F: StorageFixture>() {
+		let fixture = F::new();
 		let shard = H256::from_low_u64_be(1);
 		let signed_block = create_signed_block(8, shard);
 		{
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			sidechain_db.shards.push(shard);
 			// when
 			let result = sidechain_db.verify_block_ancestry(&signed_block.block());
@@ -687,27 +1266,25 @@ mod test {
 		}
 	}
 
-	#[test]
-	fn verify_block_ancestry_returns_false_if_no_shard() {
-		let temp_dir = create_temp_dir();
+	fn verify_block_ancestry_returns_false_if_no_shard<F: StorageFixture>() {
+		let fixture = F::new();
 		let shard = H256::from_low_u64_be(1);
 		let signed_block = create_signed_block(8, shard);
 		{
-			let sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let sidechain_db = fixture.storage();
 			let result = sidechain_db.verify_block_ancestry(&signed_block.block());
 			assert!(!result);
 		}
 	}
 
-	#[test]
-	fn add_block_to_batch_works_with_new_shard() {
+	fn add_block_to_batch_works_with_new_shard<F: StorageFixture>() {
 		// given
-		let temp_dir = create_temp_dir();
+		let fixture = F::new();
 		let shard = H256::from_low_u64_be(1);
 		let signed_block = create_signed_block(8, shard);
 		let mut new_shard = false;
 		{
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			let mut batch = WriteBatch::default();
 			assert!(batch.is_empty());
 
@@ -720,19 +1297,18 @@ mod test {
 		}
 	}
 
-	#[test]
-	fn add_block_to_batch_does_not_add_shard_if_existent() {
-		let temp_dir = create_temp_dir();
+	fn add_block_to_batch_does_not_add_shard_if_existent<F: StorageFixture>() {
+		let fixture = F::new();
 		let shard = H256::from_low_u64_be(1);
 		let signed_block = create_signed_block(8, shard);
 		let last_block = LastSidechainBlock {
 			hash: signed_block.hash(),
 			number: signed_block.block().header().block_number(),
 		};
-		let signed_block_two = create_signed_block(9, shard);
+		let signed_block_two = create_child_block(&signed_block);
 		let mut new_shard = false;
 		{
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			let mut batch = WriteBatch::default();
 			assert!(batch.is_empty());
 			sidechain_db.shards.push(shard);
@@ -747,9 +1323,8 @@ mod test {
 		}
 	}
 
-	#[test]
-	fn add_block_to_batch_does_not_add_block_if_not_ancestor() {
-		let temp_dir = create_temp_dir();
+	fn add_block_to_batch_does_not_add_block_if_not_ancestor<F: StorageFixture>() {
+		let fixture = F::new();
 		let shard = H256::from_low_u64_be(1);
 		let signed_block = create_signed_block(8, shard);
 		let last_block = LastSidechainBlock {
@@ -759,7 +1334,7 @@ mod test {
 		let signed_block_two = create_signed_block(10, shard);
 		let mut new_shard = false;
 		{
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			let mut batch = WriteBatch::default();
 			sidechain_db.shards.push(shard);
 			sidechain_db.last_blocks.insert(shard, last_block);
@@ -773,15 +1348,14 @@ mod test {
 		}
 	}
 
-	#[test]
-	fn store_block_works() {
-		let temp_dir = create_temp_dir();
+	fn store_block_works<F: StorageFixture>() {
+		let fixture = F::new();
 		let shard = H256::from_low_u64_be(1);
 		let signed_block = create_signed_block(20, shard);
 		let signed_block_vector: Vec<SignedBlock> = vec![signed_block.clone()];
 
 		{
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			// db needs to start empty
 			assert_eq!(sidechain_db.shards, vec![]);
 			sidechain_db.store_blocks(signed_block_vector).unwrap();
@@ -789,7 +1363,7 @@ mod test {
 
 		{
 			// open new DB of same path:
-			let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let updated_sidechain_db = fixture.storage();
 			// ensure DB contains previously stored data:
 			assert_eq!(*updated_sidechain_db.shards(), vec![shard]);
 			let last_block = updated_sidechain_db.last_block_of_shard(&shard).unwrap();
@@ -805,9 +1379,8 @@ mod test {
 		}
 	}
 
-	#[test]
-	fn store_blocks_on_multi_sharding_works() {
-		let temp_dir = create_temp_dir();
+	fn store_blocks_on_multi_sharding_works<F: StorageFixture>() {
+		let fixture = F::new();
 		let shard_one = H256::from_low_u64_be(1);
 		let shard_two = H256::from_low_u64_be(2);
 		let signed_block_one = create_signed_block(20, shard_one);
@@ -817,14 +1390,14 @@ mod test {
 			vec![signed_block_one.clone(), signed_block_two.clone()];
 
 		{
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			// db needs to start empty
 			assert_eq!(sidechain_db.shards, vec![]);
 			sidechain_db.store_blocks(signed_block_vector).unwrap();
 		}
 
 		{
-			let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let updated_sidechain_db = fixture.storage();
 			assert_eq!(updated_sidechain_db.shards()[0], shard_one);
 			assert_eq!(updated_sidechain_db.shards()[1], shard_two);
 			let last_block_one: &LastSidechainBlock =
@@ -838,28 +1411,27 @@ mod test {
 		}
 	}
 
-	#[test]
-	fn store_mulitple_block_on_one_shard_works() {
-		let temp_dir = create_temp_dir();
+	fn store_mulitple_block_on_one_shard_works<F: StorageFixture>() {
+		let fixture = F::new();
 		let shard = H256::from_low_u64_be(1);
 		let signed_block_one = create_signed_block(20, shard);
-		let signed_block_two = create_signed_block(21, shard);
+		let signed_block_two = create_child_block(&signed_block_one);
 		let signed_block_vector_one = vec![signed_block_one.clone()];
 		let signed_block_vector_two = vec![signed_block_two.clone()];
 
 		{
 			// first iteration
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			sidechain_db.store_blocks(signed_block_vector_one).unwrap();
 		}
 		{
 			// second iteration
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			sidechain_db.store_blocks(signed_block_vector_two).unwrap();
 		}
 
 		{
-			let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let updated_sidechain_db = fixture.storage();
 			// last block is really equal to second block:
 			let last_block: &LastSidechainBlock =
 				updated_sidechain_db.last_blocks.get(&shard).unwrap();
@@ -883,9 +1455,8 @@ mod test {
 		}
 	}
 
-	#[test]
-	fn wrong_succession_order_does_not_get_accepted() {
-		let temp_dir = create_temp_dir();
+	fn wrong_succession_order_does_not_get_accepted<F: StorageFixture>() {
+		let fixture = F::new();
 		let shard = H256::from_low_u64_be(1);
 		let signed_block_one = create_signed_block(7, shard);
 		let signed_block_two = create_signed_block(21, shard);
@@ -894,16 +1465,16 @@ mod test {
 
 		{
 			// first iteration
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			sidechain_db.store_blocks(signed_block_vector_one).unwrap();
 		}
 		{
 			// second iteration
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			sidechain_db.store_blocks(signed_block_vector_two).unwrap();
 		}
 		{
-			let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let updated_sidechain_db = fixture.storage();
 			// last block is equal to first block:
 			let last_block: &LastSidechainBlock =
 				updated_sidechain_db.last_blocks.get(&shard).unwrap();
@@ -923,14 +1494,33 @@ mod test {
 		}
 	}
 
-	#[test]
-	fn get_previous_block_returns_correct_block() {
-		let temp_dir = create_temp_dir();
+	fn block_with_wrong_parent_hash_is_not_appended_to_last_block<F: StorageFixture>() {
+		let fixture = F::new();
+		let shard = H256::from_low_u64_be(1);
+		let signed_block_one = create_signed_block(7, shard);
+		let signed_block_two = create_signed_block(8, shard);
+
+		{
+			let mut sidechain_db = fixture.storage();
+			sidechain_db.store_blocks(vec![signed_block_one.clone()]).unwrap();
+			sidechain_db.store_blocks(vec![signed_block_two.clone()]).unwrap();
+		}
+		{
+			let updated_sidechain_db = fixture.storage();
+			let last_block = updated_sidechain_db.last_block_of_shard(&shard).unwrap();
+			assert_eq!(last_block.hash, signed_block_one.hash());
+			assert!(updated_sidechain_db.get_block_hash(&shard, 8).unwrap().is_none());
+			assert!(updated_sidechain_db.get_block(&signed_block_two.hash()).unwrap().is_none());
+		}
+	}
+
+	fn get_previous_block_returns_correct_block<F: StorageFixture>() {
+		let fixture = F::new();
 		let shard = H256::from_low_u64_be(1);
 		let signed_block_one = create_signed_block(1, shard);
 		// create sidechain_db
 		{
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			sidechain_db.store_blocks(vec![signed_block_one.clone()]).unwrap();
 			// create last block one for comparison
 			let last_block = LastSidechainBlock {
@@ -949,12 +1539,11 @@ mod test {
 		}
 	}
 
-	#[test]
-	fn get_previous_block_returns_none_when_no_block() {
-		let temp_dir = create_temp_dir();
+	fn get_previous_block_returns_none_when_no_block<F: StorageFixture>() {
+		let fixture = F::new();
 		let shard = H256::from_low_u64_be(1);
 		{
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			sidechain_db.store_blocks(vec![create_signed_block(1, shard)]).unwrap();
 
 			let no_block = sidechain_db.get_previous_block(&shard, 1).unwrap();
@@ -963,16 +1552,15 @@ mod test {
 		}
 	}
 
-	#[test]
-	fn purge_shard_works() {
-		let temp_dir = create_temp_dir();
+	fn purge_shard_works<F: StorageFixture>() {
+		let fixture = F::new();
 		let shard = H256::from_low_u64_be(1);
 		let block_one = create_signed_block(1, shard);
-		let block_two = create_signed_block(2, shard);
-		let block_three = create_signed_block(3, shard);
+		let block_two = create_child_block(&block_one);
+		let block_three = create_child_block(&block_two);
 		{
 			// create sidechain_db
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			sidechain_db.store_blocks(vec![block_one.clone()]).unwrap();
 			sidechain_db.store_blocks(vec![block_two.clone()]).unwrap();
 			sidechain_db.store_blocks(vec![block_three.clone()]).unwrap();
@@ -985,7 +1573,7 @@ mod test {
 		}
 
 		{
-			let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let updated_sidechain_db = fixture.storage();
 			// test if local storage is still clean
 			assert!(!updated_sidechain_db.shards.contains(&shard));
 			assert!(updated_sidechain_db.last_blocks.get(&shard).is_none());
@@ -1000,13 +1588,12 @@ mod test {
 		}
 	}
 
-	#[test]
-	fn purge_shard_from_block_works() {
-		let temp_dir = create_temp_dir();
+	fn purge_shard_from_block_works<F: StorageFixture>() {
+		let fixture = F::new();
 		let shard = H256::from_low_u64_be(1);
 		let block_one = create_signed_block(1, shard);
-		let block_two = create_signed_block(2, shard);
-		let block_three = create_signed_block(3, shard);
+		let block_two = create_child_block(&block_one);
+		let block_three = create_child_block(&block_two);
 		let last_block = LastSidechainBlock {
 			hash: block_three.hash(),
 			number: block_three.block().header().block_number(),
@@ -1014,7 +1601,7 @@ mod test {
 
 		{
 			// create sidechain_db
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			sidechain_db.store_blocks(vec![block_one.clone()]).unwrap();
 			sidechain_db.store_blocks(vec![block_two.clone()]).unwrap();
 			sidechain_db.store_blocks(vec![block_three.clone()]).unwrap();
@@ -1023,7 +1610,7 @@ mod test {
 		}
 
 		{
-			let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let updated_sidechain_db = fixture.storage();
 			// test local memory
 			assert!(updated_sidechain_db.shards.contains(&shard));
 			assert_eq!(*updated_sidechain_db.last_blocks.get(&shard).unwrap(), last_block);
@@ -1045,16 +1632,15 @@ mod test {
 		}
 	}
 
-	#[test]
-	fn purge_shard_from_block_works_for_last_block() {
-		let temp_dir = create_temp_dir();
+	fn purge_shard_from_block_works_for_last_block<F: StorageFixture>() {
+		let fixture = F::new();
 		let shard = H256::from_low_u64_be(1);
 		let block_one = create_signed_block(1, shard);
-		let block_two = create_signed_block(2, shard);
-		let block_three = create_signed_block(3, shard);
+		let block_two = create_child_block(&block_one);
+		let block_three = create_child_block(&block_two);
 		{
 			// create sidechain_db
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			sidechain_db.store_blocks(vec![block_one.clone()]).unwrap();
 			sidechain_db.store_blocks(vec![block_two.clone()]).unwrap();
 			sidechain_db.store_blocks(vec![block_three.clone()]).unwrap();
@@ -1067,7 +1653,7 @@ mod test {
 		}
 
 		{
-			let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let updated_sidechain_db = fixture.storage();
 			// test if local storage is still clean
 			assert!(!updated_sidechain_db.shards.contains(&shard));
 			assert!(updated_sidechain_db.last_blocks.get(&shard).is_none());
@@ -1082,14 +1668,13 @@ mod test {
 		}
 	}
 
-	#[test]
-	fn prune_shards_works_for_multiple_shards() {
-		let temp_dir = create_temp_dir();
+	fn prune_shards_works_for_multiple_shards<F: StorageFixture>() {
+		let fixture = F::new();
 		// shard one
 		let shard_one = H256::from_low_u64_be(1);
 		let block_one = create_signed_block(1, shard_one);
-		let block_two = create_signed_block(2, shard_one);
-		let block_three = create_signed_block(3, shard_one);
+		let block_two = create_child_block(&block_one);
+		let block_three = create_child_block(&block_two);
 		let last_block_one = LastSidechainBlock {
 			hash: block_three.hash(),
 			number: block_three.block().header().block_number(),
@@ -1097,16 +1682,16 @@ mod test {
 		// shard two
 		let shard_two = H256::from_low_u64_be(2);
 		let block_one_s = create_signed_block(1, shard_two);
-		let block_two_s = create_signed_block(2, shard_two);
-		let block_three_s = create_signed_block(3, shard_two);
-		let block_four_s = create_signed_block(4, shard_two);
+		let block_two_s = create_child_block(&block_one_s);
+		let block_three_s = create_child_block(&block_two_s);
+		let block_four_s = create_child_block(&block_three_s);
 		let last_block_two = LastSidechainBlock {
 			hash: block_four_s.hash(),
 			number: block_four_s.block().header().block_number(),
 		};
 		{
 			// create sidechain_db
-			let mut sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let mut sidechain_db = fixture.storage();
 			sidechain_db.store_blocks(vec![block_one.clone(), block_one_s.clone()]).unwrap();
 			sidechain_db.store_blocks(vec![block_two.clone(), block_two_s.clone()]).unwrap();
 			sidechain_db
@@ -1118,7 +1703,7 @@ mod test {
 		}
 
 		{
-			let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+			let updated_sidechain_db = fixture.storage();
 			// test if shard one has been cleansed of block 1, with 2 and 3 still beeing there:
 			assert_eq!(
 				*updated_sidechain_db.last_block_of_shard(&shard_one).unwrap(),
@@ -1169,4 +1754,32 @@ mod test {
 			assert!(updated_sidechain_db.get_block(&block_two_s.hash()).unwrap().is_none());
 		}
 	}
+
+	test_all_backends!(
+		load_shards_from_db_works,
+		load_last_block_from_db_works,
+		create_new_sidechain_storage_works,
+		add_last_block_works,
+		delete_block_works,
+		delete_last_block_works,
+		verify_block_ancestry_returns_true_if_correct_successor,
+		verify_block_ancestry_returns_false_if_not_correct_successor,
+		verify_block_ancestry_returns_false_if_parent_hash_does_not_match,
+		verify_block_ancestry_returns_false_no_last_block_registered,
+		verify_block_ancestry_returns_false_if_no_shard,
+		add_block_to_batch_works_with_new_shard,
+		add_block_to_batch_does_not_add_shard_if_existent,
+		add_block_to_batch_does_not_add_block_if_not_ancestor,
+		store_block_works,
+		store_blocks_on_multi_sharding_works,
+		store_mulitple_block_on_one_shard_works,
+		wrong_succession_order_does_not_get_accepted,
+		block_with_wrong_parent_hash_is_not_appended_to_last_block,
+		get_previous_block_returns_correct_block,
+		get_previous_block_returns_none_when_no_block,
+		purge_shard_works,
+		purge_shard_from_block_works,
+		purge_shard_from_block_works_for_last_block,
+		prune_shards_works_for_multiple_shards,
+	);
 }
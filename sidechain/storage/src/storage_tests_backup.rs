@@ -0,0 +1,195 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	backup::{SidechainBackup, BACKUP_FILE_MAGIC},
+	error::Error,
+	test_utils::{
+		create_signed_block_with_parenthash as create_signed_block, create_temp_dir, default_shard,
+		fill_storage_with_blocks, test_all_backends, StorageFixture,
+	},
+};
+use itp_types::{BlockHash, ShardIdentifier};
+use its_primitives::{traits::SignedBlock as SignedBlockT, types::SignedBlock};
+use std::assert_matches::assert_matches;
+
+type TestBackup = SidechainBackup<ShardIdentifier, SignedBlock>;
+
+fn create_chain(number_of_blocks: u64) -> Vec<SignedBlock> {
+	let mut blocks: Vec<SignedBlock> = Vec::new();
+	for block_number in 1..=number_of_blocks {
+		let parent_hash = blocks.last().map(|b| b.hash()).unwrap_or_default();
+		blocks.push(create_signed_block(block_number, parent_hash));
+	}
+	blocks
+}
+
+fn export_and_import_backup_restores_storage<F: StorageFixture>() {
+	let blocks = create_chain(3);
+	let source = fill_storage_with_blocks::<F>(blocks.clone());
+	let backup = source.storage().export_backup().unwrap();
+
+	assert_eq!(1, backup.shards.len());
+	assert_eq!(blocks, backup.shards[0].blocks);
+
+	let target = F::new();
+	{
+		let mut sidechain_db = target.storage();
+		sidechain_db.import_backup(backup).unwrap();
+	}
+
+	{
+		let restored_sidechain_db = target.storage();
+		assert_eq!(vec![default_shard()], *restored_sidechain_db.shards());
+		assert_eq!(
+			restored_sidechain_db.last_block_of_shard(&default_shard()).unwrap().hash,
+			blocks[2].hash()
+		);
+		for block in blocks {
+			assert_eq!(restored_sidechain_db.get_block(&block.hash()).unwrap(), Some(block));
+		}
+	}
+}
+
+fn export_backup_of_pruned_storage_starts_at_oldest_block<F: StorageFixture>() {
+	let blocks = create_chain(4);
+	let fixture = fill_storage_with_blocks::<F>(blocks.clone());
+
+	let mut sidechain_db = fixture.storage();
+	sidechain_db.prune_shards(2);
+	let backup = sidechain_db.export_backup().unwrap();
+
+	assert_eq!(blocks[2..].to_vec(), backup.shards[0].blocks);
+
+	// A pruned chain can be imported as well, since the first block is not checked against a parent.
+	let target = F::new();
+	assert!(target.storage().import_backup(backup).is_ok());
+}
+
+fn import_backup_into_non_empty_storage_fails<F: StorageFixture>() {
+	let blocks = create_chain(2);
+	let fixture = fill_storage_with_blocks::<F>(blocks);
+	let mut sidechain_db = fixture.storage();
+	let backup = sidechain_db.export_backup().unwrap();
+
+	assert_matches!(sidechain_db.import_backup(backup), Err(Error::StorageNotEmpty));
+}
+
+fn import_backup_with_broken_ancestry_fails_and_writes_nothing<F: StorageFixture>() {
+	let blocks = create_chain(3);
+	let source = fill_storage_with_blocks::<F>(blocks.clone());
+	let mut backup = source.storage().export_backup().unwrap();
+	// Remove the block in the middle.
+	backup.shards[0].blocks.remove(1);
+
+	let target = F::new();
+	{
+		let mut sidechain_db = target.storage();
+		assert_matches!(sidechain_db.import_backup(backup), Err(Error::HeaderAncestryMismatch));
+		assert!(sidechain_db.shards().is_empty());
+	}
+
+	{
+		let sidechain_db = target.storage();
+		assert!(sidechain_db.shards().is_empty());
+		assert!(sidechain_db.get_block(&blocks[0].hash()).unwrap().is_none());
+	}
+}
+
+fn import_backup_with_wrong_parent_hash_fails_and_writes_nothing<F: StorageFixture>() {
+	let blocks = create_chain(3);
+	let source = fill_storage_with_blocks::<F>(blocks.clone());
+	let mut backup = source.storage().export_backup().unwrap();
+	// Replace the last block by one with the same number, but on another parent.
+	backup.shards[0].blocks[2] = create_signed_block(3, BlockHash::random());
+
+	let target = F::new();
+	{
+		let mut sidechain_db = target.storage();
+		assert_matches!(sidechain_db.import_backup(backup), Err(Error::HeaderAncestryMismatch));
+	}
+
+	let sidechain_db = target.storage();
+	assert!(sidechain_db.shards().is_empty());
+	assert!(sidechain_db.get_block(&blocks[0].hash()).unwrap().is_none());
+}
+
+fn import_backup_with_invalid_signature_fails_and_writes_nothing<F: StorageFixture>() {
+	let blocks = create_chain(2);
+	let source = fill_storage_with_blocks::<F>(blocks.clone());
+	let mut backup = source.storage().export_backup().unwrap();
+	// Sign the second block with the signature of the first one.
+	backup.shards[0].blocks[1].signature = backup.shards[0].blocks[0].signature.clone();
+
+	let target = F::new();
+	{
+		let mut sidechain_db = target.storage();
+		assert_matches!(sidechain_db.import_backup(backup), Err(Error::InvalidBackup(_)));
+	}
+
+	let sidechain_db = target.storage();
+	assert!(sidechain_db.shards().is_empty());
+	assert!(sidechain_db.get_block(&blocks[0].hash()).unwrap().is_none());
+}
+
+fn import_backup_with_wrong_last_block_fails<F: StorageFixture>() {
+	let blocks = create_chain(2);
+	let source = fill_storage_with_blocks::<F>(blocks);
+	let mut backup = source.storage().export_backup().unwrap();
+	backup.shards[0].last_block.hash = BlockHash::random();
+
+	let target = F::new();
+	let mut sidechain_db = target.storage();
+
+	assert_matches!(sidechain_db.import_backup(backup), Err(Error::InvalidBackup(_)));
+}
+
+fn backup_file_round_trip_works<F: StorageFixture>() {
+	let blocks = create_chain(2);
+	let source = fill_storage_with_blocks::<F>(blocks);
+	let backup = source.storage().export_backup().unwrap();
+
+	let file_dir = create_temp_dir();
+	let file_path = file_dir.path().join("sidechain_backup.bin");
+	backup.write_to_file(&file_path).unwrap();
+
+	assert_eq!(backup, TestBackup::read_from_file(&file_path).unwrap());
+}
+
+#[test]
+fn decoding_backup_with_invalid_magic_or_version_fails() {
+	let backup = TestBackup { shards: vec![] };
+
+	let mut bytes = backup.to_bytes();
+	bytes[0] = 0;
+	assert_matches!(TestBackup::from_bytes(&bytes), Err(Error::InvalidBackup(_)));
+
+	let mut bytes = BACKUP_FILE_MAGIC.to_vec();
+	bytes.extend(2u32.to_le_bytes());
+	assert_matches!(TestBackup::from_bytes(&bytes), Err(Error::UnsupportedBackupVersion(2)));
+}
+
+test_all_backends!(
+	export_and_import_backup_restores_storage,
+	export_backup_of_pruned_storage_starts_at_oldest_block,
+	import_backup_into_non_empty_storage_fails,
+	import_backup_with_broken_ancestry_fails_and_writes_nothing,
+	import_backup_with_wrong_parent_hash_fails_and_writes_nothing,
+	import_backup_with_invalid_signature_fails_and_writes_nothing,
+	import_backup_with_wrong_last_block_fails,
+	backup_file_round_trip_works,
+);
@@ -0,0 +1,175 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+use crate::{
+	test_utils::{
+		create_signed_block_with_timestamp as create_signed_block, default_shard,
+		fill_storage_with_blocks, test_all_backends, StorageFixture,
+	},
+	Error,
+};
+use itp_types::BlockHash;
+use its_primitives::traits::SignedBlock;
+use std::assert_matches::assert_matches;
+
+fn finalized_block_is_persisted<F: StorageFixture>() {
+	let block_1 = create_signed_block(1, BlockHash::default(), 1000);
+	let block_2 = create_signed_block(2, block_1.hash(), 2000);
+
+	let fixture = fill_storage_with_blocks::<F>(vec![block_1.clone(), block_2]);
+
+	{
+		let mut sidechain_db = fixture.storage();
+		assert!(sidechain_db.finalized_block_of_shard(&default_shard()).is_none());
+		sidechain_db.finalize_block(&default_shard(), &block_1.hash()).unwrap();
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+		let finalized_block = updated_sidechain_db.finalized_block_of_shard(&default_shard());
+
+		assert_eq!(finalized_block.unwrap().hash, block_1.hash());
+		assert_eq!(finalized_block.unwrap().number, 1);
+	}
+}
+
+fn finalizing_an_older_block_has_no_effect<F: StorageFixture>() {
+	let block_1 = create_signed_block(1, BlockHash::default(), 1000);
+	let block_2 = create_signed_block(2, block_1.hash(), 2000);
+
+	let fixture = fill_storage_with_blocks::<F>(vec![block_1.clone(), block_2.clone()]);
+	let mut sidechain_db = fixture.storage();
+
+	sidechain_db.finalize_block(&default_shard(), &block_2.hash()).unwrap();
+	sidechain_db.finalize_block(&default_shard(), &block_1.hash()).unwrap();
+
+	assert_eq!(
+		sidechain_db.finalized_block_of_shard(&default_shard()).unwrap().hash,
+		block_2.hash()
+	);
+}
+
+fn finalizing_a_non_canonical_block_fails<F: StorageFixture>() {
+	let block_1 = create_signed_block(1, BlockHash::default(), 1000);
+	let block_2 = create_signed_block(2, block_1.hash(), 2000);
+	let block_3 = create_signed_block(3, block_2.hash(), 3000);
+	let fork_block_2 = create_signed_block(2, block_1.hash(), 2001);
+
+	let fixture =
+		fill_storage_with_blocks::<F>(vec![block_1, block_2, block_3, fork_block_2.clone()]);
+	let mut sidechain_db = fixture.storage();
+
+	assert_matches!(
+		sidechain_db.finalize_block(&default_shard(), &fork_block_2.hash()),
+		Err(Error::NonCanonicalBlock(hash)) if hash == fork_block_2.hash()
+	);
+	assert!(sidechain_db.finalized_block_of_shard(&default_shard()).is_none());
+}
+
+fn finalizing_deletes_conflicting_fork_blocks<F: StorageFixture>() {
+	let block_1 = create_signed_block(1, BlockHash::default(), 1000);
+	let block_2 = create_signed_block(2, block_1.hash(), 2000);
+	let block_3 = create_signed_block(3, block_2.hash(), 3000);
+	let fork_block_2 = create_signed_block(2, block_1.hash(), 2001);
+
+	let fixture = fill_storage_with_blocks::<F>(vec![
+		block_1,
+		block_2.clone(),
+		block_3,
+		fork_block_2.clone(),
+	]);
+
+	{
+		let mut sidechain_db = fixture.storage();
+		assert_eq!(sidechain_db.fork_heads_of_shard(&default_shard()).len(), 1);
+		sidechain_db.finalize_block(&default_shard(), &block_2.hash()).unwrap();
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+
+		assert!(updated_sidechain_db.get_block(&fork_block_2.hash()).unwrap().is_none());
+		assert!(updated_sidechain_db.fork_heads_of_shard(&default_shard()).is_empty());
+	}
+}
+
+fn fork_retracting_a_finalized_block_is_not_stored<F: StorageFixture>() {
+	let block_1 = create_signed_block(1, BlockHash::default(), 1000);
+	let block_2 = create_signed_block(2, block_1.hash(), 2000);
+	let block_3 = create_signed_block(3, block_2.hash(), 3000);
+	let fork_block_2 = create_signed_block(2, block_1.hash(), 2001);
+	let fork_block_3 = create_signed_block(3, fork_block_2.hash(), 3001);
+	let fork_block_4 = create_signed_block(4, fork_block_3.hash(), 4001);
+
+	let fixture = fill_storage_with_blocks::<F>(vec![block_1, block_2.clone(), block_3.clone()]);
+
+	{
+		let mut sidechain_db = fixture.storage();
+		sidechain_db.finalize_block(&default_shard(), &block_2.hash()).unwrap();
+		sidechain_db
+			.store_blocks(vec![fork_block_2.clone(), fork_block_3, fork_block_4])
+			.unwrap();
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+
+		assert_eq!(
+			updated_sidechain_db.last_block_of_shard(&default_shard()).unwrap().hash,
+			block_3.hash()
+		);
+		assert!(updated_sidechain_db.get_block(&fork_block_2.hash()).unwrap().is_none());
+		assert!(updated_sidechain_db.fork_heads_of_shard(&default_shard()).is_empty());
+	}
+}
+
+fn pruning_keeps_blocks_that_are_not_final<F: StorageFixture>() {
+	let block_1 = create_signed_block(1, BlockHash::default(), 1000);
+	let block_2 = create_signed_block(2, block_1.hash(), 2000);
+	let block_3 = create_signed_block(3, block_2.hash(), 3000);
+	let block_4 = create_signed_block(4, block_3.hash(), 4000);
+
+	let fixture = fill_storage_with_blocks::<F>(vec![
+		block_1.clone(),
+		block_2.clone(),
+		block_3.clone(),
+		block_4.clone(),
+	]);
+
+	{
+		let mut sidechain_db = fixture.storage();
+		sidechain_db.finalize_block(&default_shard(), &block_2.hash()).unwrap();
+		sidechain_db.prune_shards(1);
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+
+		assert!(updated_sidechain_db.get_block(&block_1.hash()).unwrap().is_none());
+		assert!(updated_sidechain_db.get_block(&block_2.hash()).unwrap().is_none());
+		assert_eq!(updated_sidechain_db.get_block(&block_3.hash()).unwrap().unwrap(), block_3);
+		assert_eq!(updated_sidechain_db.get_block(&block_4.hash()).unwrap().unwrap(), block_4);
+	}
+}
+
+test_all_backends!(
+	finalized_block_is_persisted,
+	finalizing_an_older_block_has_no_effect,
+	finalizing_a_non_canonical_block_fails,
+	finalizing_deletes_conflicting_fork_blocks,
+	fork_retracting_a_finalized_block_is_not_stored,
+	pruning_keeps_blocks_that_are_not_final,
+);
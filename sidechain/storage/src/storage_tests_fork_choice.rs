@@ -0,0 +1,264 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+use crate::test_utils::{
+	create_signed_block_with_timestamp as create_signed_block, create_signed_block_with_top_hashes,
+	default_shard, fill_storage_with_blocks, test_all_backends, StorageFixture,
+};
+use itp_types::BlockHash;
+use its_primitives::{fork_choice::author_slot_hash, traits::SignedBlock};
+use sp_core::H256;
+
+fn competing_block_of_same_height_is_stored_as_fork<F: StorageFixture>() {
+	let block_1 = create_signed_block(1, BlockHash::default(), 1000);
+	let (winner, loser) = order_by_fork_choice(
+		create_signed_block(2, block_1.hash(), 2000),
+		create_signed_block(2, block_1.hash(), 3000),
+	);
+
+	let fixture = fill_storage_with_blocks::<F>(vec![block_1, winner.clone()]);
+
+	{
+		let mut sidechain_db = fixture.storage();
+		sidechain_db.store_blocks(vec![loser.clone()]).unwrap();
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+		let shard = default_shard();
+
+		assert_eq!(updated_sidechain_db.last_block_of_shard(&shard).unwrap().hash, winner.hash());
+		assert_eq!(updated_sidechain_db.get_block_by_number(&shard, 2).unwrap().unwrap(), winner);
+		assert_eq!(updated_sidechain_db.get_block(&loser.hash()).unwrap().unwrap(), loser);
+		assert_eq!(updated_sidechain_db.fork_heads_of_shard(&shard).len(), 1);
+		assert_eq!(updated_sidechain_db.fork_heads_of_shard(&shard)[0].hash, loser.hash());
+	}
+}
+
+fn preferred_block_of_same_height_reorganizes_shard<F: StorageFixture>() {
+	let block_1 = create_signed_block(1, BlockHash::default(), 1000);
+	let (winner, loser) = order_by_fork_choice(
+		create_signed_block(2, block_1.hash(), 2000),
+		create_signed_block(2, block_1.hash(), 3000),
+	);
+
+	let fixture = fill_storage_with_blocks::<F>(vec![block_1, loser.clone()]);
+
+	{
+		let mut sidechain_db = fixture.storage();
+		sidechain_db.store_blocks(vec![winner.clone()]).unwrap();
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+		let shard = default_shard();
+
+		assert_eq!(updated_sidechain_db.last_block_of_shard(&shard).unwrap().hash, winner.hash());
+		assert_eq!(updated_sidechain_db.get_block_by_number(&shard, 2).unwrap().unwrap(), winner);
+		assert_eq!(updated_sidechain_db.fork_heads_of_shard(&shard)[0].hash, loser.hash());
+	}
+}
+
+fn longer_fork_reorganizes_shard<F: StorageFixture>() {
+	let block_1 = create_signed_block(1, BlockHash::default(), 1000);
+	let block_2 = create_signed_block(2, block_1.hash(), 2000);
+	let block_3 = create_signed_block(3, block_2.hash(), 3000);
+	let fork_block_2 = create_signed_block(2, block_1.hash(), 2001);
+	let fork_block_3 = create_signed_block(3, fork_block_2.hash(), 3001);
+	let fork_block_4 = create_signed_block(4, fork_block_3.hash(), 4001);
+
+	let fixture = fill_storage_with_blocks::<F>(vec![block_1.clone(), block_2, block_3.clone()]);
+
+	{
+		let mut sidechain_db = fixture.storage();
+		sidechain_db
+			.store_blocks(vec![fork_block_2.clone(), fork_block_3.clone(), fork_block_4.clone()])
+			.unwrap();
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+		let shard = default_shard();
+
+		assert_eq!(
+			updated_sidechain_db.last_block_of_shard(&shard).unwrap().hash,
+			fork_block_4.hash()
+		);
+		assert_eq!(
+			updated_sidechain_db.get_blocks_by_number(&shard, 1, 10).unwrap(),
+			vec![block_1, fork_block_2, fork_block_3, fork_block_4]
+		);
+		assert_eq!(updated_sidechain_db.fork_heads_of_shard(&shard).len(), 1);
+		assert_eq!(updated_sidechain_db.fork_heads_of_shard(&shard)[0].hash, block_3.hash());
+	}
+}
+
+fn child_of_fork_head_is_not_appended_to_canonical_chain<F: StorageFixture>() {
+	let block_1 = create_signed_block(1, BlockHash::default(), 1000);
+	let (winner, loser) = order_by_fork_choice(
+		create_signed_block(2, block_1.hash(), 2000),
+		create_signed_block(2, block_1.hash(), 3000),
+	);
+	let child_of_loser = create_signed_block(3, loser.hash(), 4000);
+
+	let fixture = fill_storage_with_blocks::<F>(vec![block_1, winner.clone(), loser.clone()]);
+
+	{
+		let mut sidechain_db = fixture.storage();
+		sidechain_db.store_blocks(vec![child_of_loser.clone()]).unwrap();
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+		let shard = default_shard();
+
+		// The fork is longer now, so the shard is reorganized onto it.
+		assert_eq!(
+			updated_sidechain_db.last_block_of_shard(&shard).unwrap().hash,
+			child_of_loser.hash()
+		);
+		assert_eq!(updated_sidechain_db.get_block_by_number(&shard, 2).unwrap().unwrap(), loser);
+		assert_eq!(updated_sidechain_db.fork_heads_of_shard(&shard)[0].hash, winner.hash());
+	}
+}
+
+fn block_with_unknown_parent_is_not_stored<F: StorageFixture>() {
+	let block_1 = create_signed_block(1, BlockHash::default(), 1000);
+	let block_2 = create_signed_block(2, block_1.hash(), 2000);
+	let orphan_block = create_signed_block(2, H256::random(), 2001);
+
+	let fixture = fill_storage_with_blocks::<F>(vec![block_1, block_2.clone()]);
+
+	{
+		let mut sidechain_db = fixture.storage();
+		sidechain_db.store_blocks(vec![orphan_block.clone()]).unwrap();
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+
+		assert!(updated_sidechain_db.get_block(&orphan_block.hash()).unwrap().is_none());
+		assert!(updated_sidechain_db.fork_heads_of_shard(&default_shard()).is_empty());
+		assert_eq!(
+			updated_sidechain_db.last_block_of_shard(&default_shard()).unwrap().hash,
+			block_2.hash()
+		);
+	}
+}
+
+fn reorganization_updates_operation_index<F: StorageFixture>() {
+	let retracted_operation_hash = H256::random();
+	let enacted_operation_hash = H256::random();
+	let block_1 = create_signed_block_with_top_hashes(1, BlockHash::default(), vec![]);
+	let block_2 =
+		create_signed_block_with_top_hashes(2, block_1.hash(), vec![retracted_operation_hash]);
+	let fork_block_2 =
+		create_signed_block_with_top_hashes(2, block_1.hash(), vec![enacted_operation_hash]);
+	let fork_block_3 = create_signed_block_with_top_hashes(3, fork_block_2.hash(), vec![]);
+
+	let fixture = fill_storage_with_blocks::<F>(vec![block_1, block_2]);
+
+	{
+		let mut sidechain_db = fixture.storage();
+		sidechain_db.store_blocks(vec![fork_block_2.clone(), fork_block_3]).unwrap();
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+
+		assert!(updated_sidechain_db
+			.get_operation_inclusion(&retracted_operation_hash)
+			.unwrap()
+			.is_none());
+		assert_eq!(
+			updated_sidechain_db
+				.get_operation_inclusion(&enacted_operation_hash)
+				.unwrap()
+				.unwrap()
+				.block_hash,
+			fork_block_2.hash()
+		);
+	}
+}
+
+fn pruning_removes_fork_blocks<F: StorageFixture>() {
+	let block_1 = create_signed_block(1, BlockHash::default(), 1000);
+	let block_2 = create_signed_block(2, block_1.hash(), 2000);
+	let block_3 = create_signed_block(3, block_2.hash(), 3000);
+	let block_4 = create_signed_block(4, block_3.hash(), 4000);
+	let fork_block_2 = create_signed_block(2, block_1.hash(), 2001);
+
+	let fixture = fill_storage_with_blocks::<F>(vec![
+		block_1,
+		block_2,
+		block_3,
+		block_4,
+		fork_block_2.clone(),
+	]);
+
+	{
+		let mut sidechain_db = fixture.storage();
+		assert_eq!(sidechain_db.fork_heads_of_shard(&default_shard()).len(), 1);
+		sidechain_db.prune_shards(2);
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+
+		assert!(updated_sidechain_db.get_block(&fork_block_2.hash()).unwrap().is_none());
+		assert!(updated_sidechain_db.fork_heads_of_shard(&default_shard()).is_empty());
+	}
+}
+
+fn purge_shard_removes_fork_blocks<F: StorageFixture>() {
+	let block_1 = create_signed_block(1, BlockHash::default(), 1000);
+	let block_2 = create_signed_block(2, block_1.hash(), 2000);
+	let fork_block_2 = create_signed_block(2, block_1.hash(), 2001);
+
+	let fixture = fill_storage_with_blocks::<F>(vec![block_1, block_2, fork_block_2.clone()]);
+
+	{
+		let mut sidechain_db = fixture.storage();
+		sidechain_db.purge_shard(&default_shard()).unwrap();
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+
+		assert!(updated_sidechain_db.get_block(&fork_block_2.hash()).unwrap().is_none());
+		assert!(updated_sidechain_db.fork_heads_of_shard(&default_shard()).is_empty());
+	}
+}
+
+/// Returns the given blocks of equal height as (preferred, not preferred).
+fn order_by_fork_choice<B: SignedBlock>(block_a: B, block_b: B) -> (B, B) {
+	if author_slot_hash(block_a.block()) < author_slot_hash(block_b.block()) {
+		(block_a, block_b)
+	} else {
+		(block_b, block_a)
+	}
+}
+
+test_all_backends!(
+	competing_block_of_same_height_is_stored_as_fork,
+	preferred_block_of_same_height_reorganizes_shard,
+	longer_fork_reorganizes_shard,
+	child_of_fork_head_is_not_appended_to_canonical_chain,
+	block_with_unknown_parent_is_not_stored,
+	reorganization_updates_operation_index,
+	pruning_removes_fork_blocks,
+	purge_shard_removes_fork_blocks,
+);
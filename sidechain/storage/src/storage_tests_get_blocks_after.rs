@@ -18,25 +18,29 @@
 use crate::{
 	error::Error,
 	test_utils::{
-		create_signed_block_with_parenthash as create_signed_block, default_shard,
-		fill_storage_with_blocks, get_storage,
+		create_signed_block_with_parenthash as create_signed_block, create_signed_block_with_shard,
+		default_shard, fill_storage_with_blocks, test_all_backends, StorageFixture,
 	},
 };
 use its_primitives::{traits::SignedBlock, types::BlockHash};
+use sp_core::H256;
 use std::assert_matches::assert_matches;
 
-#[test]
-fn get_blocks_after_works_for_regular_case() {
+fn get_blocks_after_works_for_regular_case<F: StorageFixture>() {
 	let block_1 = create_signed_block(1, BlockHash::default());
 	let block_2 = create_signed_block(2, block_1.hash());
 	let block_3 = create_signed_block(3, block_2.hash());
 	let block_4 = create_signed_block(4, block_3.hash());
 
-	let temp_dir =
-		fill_storage_with_blocks(vec![block_1.clone(), block_2.clone(), block_3, block_4.clone()]);
+	let fixture = fill_storage_with_blocks::<F>(vec![
+		block_1.clone(),
+		block_2.clone(),
+		block_3,
+		block_4.clone(),
+	]);
 
 	{
-		let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		let updated_sidechain_db = fixture.storage();
 		let blocks_after_1 = updated_sidechain_db
 			.get_blocks_after(&block_1.hash(), &default_shard())
 			.unwrap();
@@ -47,14 +51,13 @@ fn get_blocks_after_works_for_regular_case() {
 	}
 }
 
-#[test]
-fn get_blocks_after_returns_empty_vec_if_block_not_found() {
+fn get_blocks_after_returns_empty_vec_if_block_not_found<F: StorageFixture>() {
 	let block_1 = create_signed_block(1, BlockHash::random());
 
-	let temp_dir = fill_storage_with_blocks(vec![block_1.clone()]);
+	let fixture = fill_storage_with_blocks::<F>(vec![block_1.clone()]);
 
 	{
-		let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		let updated_sidechain_db = fixture.storage();
 		let block_hash = BlockHash::from_low_u64_be(1);
 		// Off-chance that random() generates exactly the same hash
 		assert_ne!(block_1.hash(), block_hash);
@@ -66,14 +69,13 @@ fn get_blocks_after_returns_empty_vec_if_block_not_found() {
 	}
 }
 
-#[test]
-fn get_blocks_returns_none_if_last_is_already_most_recent_block() {
+fn get_blocks_returns_none_if_last_is_already_most_recent_block<F: StorageFixture>() {
 	let block_1 = create_signed_block(1, BlockHash::random());
 
-	let temp_dir = fill_storage_with_blocks(vec![block_1.clone()]);
+	let fixture = fill_storage_with_blocks::<F>(vec![block_1.clone()]);
 
 	{
-		let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		let updated_sidechain_db = fixture.storage();
 
 		assert_eq!(
 			updated_sidechain_db
@@ -84,18 +86,17 @@ fn get_blocks_returns_none_if_last_is_already_most_recent_block() {
 	}
 }
 
-#[test]
-fn get_blocks_after_returns_all_blocks_if_last_known_is_default() {
+fn get_blocks_after_returns_all_blocks_if_last_known_is_default<F: StorageFixture>() {
 	let block_1 = create_signed_block(1, BlockHash::default());
 	let block_2 = create_signed_block(2, block_1.hash());
 	let block_3 = create_signed_block(3, block_2.hash());
 
 	let blocks = vec![block_1.clone(), block_2.clone(), block_3.clone()];
 
-	let temp_dir = fill_storage_with_blocks(blocks.clone());
+	let fixture = fill_storage_with_blocks::<F>(blocks.clone());
 
 	{
-		let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		let updated_sidechain_db = fixture.storage();
 		let default_hash = BlockHash::default();
 
 		assert_eq!(
@@ -105,16 +106,16 @@ fn get_blocks_after_returns_all_blocks_if_last_known_is_default() {
 	}
 }
 
-#[test]
-fn given_block_with_invalid_ancestry_returns_error() {
-	let block_1 = create_signed_block(1, BlockHash::default());
-	// Should be block_1 hash, but we deliberately introduce an invalid parent hash.
+fn given_block_with_invalid_ancestry_returns_error<F: StorageFixture>() {
+	let block_1 = create_signed_block_with_shard(1, H256::from_low_u64_be(42));
+	// The first block of a shard is stored without its parent, so the chain of the
+	// default shard can not be traversed back to block_1.
 	let block_2 = create_signed_block(2, BlockHash::random());
 
-	let temp_dir = fill_storage_with_blocks(vec![block_1.clone(), block_2]);
+	let fixture = fill_storage_with_blocks::<F>(vec![block_1.clone(), block_2]);
 
 	{
-		let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		let updated_sidechain_db = fixture.storage();
 
 		assert_matches!(
 			updated_sidechain_db.get_blocks_after(&block_1.hash(), &default_shard()),
@@ -122,3 +123,11 @@ fn given_block_with_invalid_ancestry_returns_error() {
 		);
 	}
 }
+
+test_all_backends!(
+	get_blocks_after_works_for_regular_case,
+	get_blocks_after_returns_empty_vec_if_block_not_found,
+	get_blocks_returns_none_if_last_is_already_most_recent_block,
+	get_blocks_after_returns_all_blocks_if_last_known_is_default,
+	given_block_with_invalid_ancestry_returns_error,
+);
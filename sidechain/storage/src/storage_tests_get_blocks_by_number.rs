@@ -0,0 +1,144 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+use crate::test_utils::{
+	create_signed_block_with_parenthash as create_signed_block, create_signed_block_with_shard,
+	default_shard, fill_storage_with_blocks, test_all_backends, StorageFixture,
+};
+use itp_types::BlockHash;
+use its_primitives::traits::SignedBlock;
+use sp_core::H256;
+
+fn get_block_by_number_works<F: StorageFixture>() {
+	let block_1 = create_signed_block(1, BlockHash::default());
+	let block_2 = create_signed_block(2, block_1.hash());
+
+	let fixture = fill_storage_with_blocks::<F>(vec![block_1.clone(), block_2.clone()]);
+
+	{
+		let updated_sidechain_db = fixture.storage();
+
+		assert_eq!(
+			updated_sidechain_db.get_block_by_number(&default_shard(), 2).unwrap(),
+			Some(block_2)
+		);
+		assert!(updated_sidechain_db.get_block_by_number(&default_shard(), 3).unwrap().is_none());
+	}
+}
+
+fn get_blocks_by_number_works_for_regular_case<F: StorageFixture>() {
+	let block_1 = create_signed_block(1, BlockHash::default());
+	let block_2 = create_signed_block(2, block_1.hash());
+	let block_3 = create_signed_block(3, block_2.hash());
+	let block_4 = create_signed_block(4, block_3.hash());
+
+	let fixture =
+		fill_storage_with_blocks::<F>(vec![block_1, block_2.clone(), block_3.clone(), block_4]);
+
+	{
+		let updated_sidechain_db = fixture.storage();
+		let blocks_2_to_3 =
+			updated_sidechain_db.get_blocks_by_number(&default_shard(), 2, 2).unwrap();
+
+		assert_eq!(2, blocks_2_to_3.len());
+		assert_eq!(block_2.hash(), blocks_2_to_3.first().unwrap().hash());
+		assert_eq!(block_3.hash(), blocks_2_to_3.last().unwrap().hash());
+	}
+}
+
+fn get_blocks_by_number_stops_at_last_block<F: StorageFixture>() {
+	let block_1 = create_signed_block(1, BlockHash::default());
+	let block_2 = create_signed_block(2, block_1.hash());
+
+	let fixture = fill_storage_with_blocks::<F>(vec![block_1.clone(), block_2.clone()]);
+
+	{
+		let updated_sidechain_db = fixture.storage();
+
+		assert_eq!(
+			updated_sidechain_db.get_blocks_by_number(&default_shard(), 0, 10).unwrap(),
+			vec![block_1, block_2]
+		);
+		assert!(updated_sidechain_db
+			.get_blocks_by_number(&default_shard(), 3, 10)
+			.unwrap()
+			.is_empty());
+	}
+}
+
+fn get_blocks_by_number_starts_at_oldest_block_after_pruning<F: StorageFixture>() {
+	let mut blocks = vec![create_signed_block(1, BlockHash::default())];
+	for block_number in 2..=6 {
+		blocks.push(create_signed_block(block_number, blocks.last().unwrap().hash()));
+	}
+	let fixture = fill_storage_with_blocks::<F>(blocks.clone());
+
+	{
+		let mut sidechain_db = fixture.storage();
+		sidechain_db.prune_shards(2);
+
+		assert_eq!(
+			sidechain_db.get_blocks_by_number(&default_shard(), 1, 10).unwrap(),
+			blocks[4..].to_vec()
+		);
+		assert_eq!(
+			sidechain_db.get_blocks_by_number(&default_shard(), 0, 1).unwrap(),
+			vec![blocks[4].clone()]
+		);
+	}
+}
+
+fn get_blocks_by_number_returns_empty_vec_for_unknown_shard<F: StorageFixture>() {
+	let block_1 = create_signed_block(1, BlockHash::default());
+
+	let fixture = fill_storage_with_blocks::<F>(vec![block_1]);
+
+	{
+		let updated_sidechain_db = fixture.storage();
+
+		assert!(updated_sidechain_db
+			.get_blocks_by_number(&H256::from_low_u64_be(1), 0, 10)
+			.unwrap()
+			.is_empty());
+	}
+}
+
+fn get_last_blocks_returns_last_block_of_every_shard<F: StorageFixture>() {
+	let shard_one = H256::from_low_u64_be(1);
+	let shard_two = H256::from_low_u64_be(2);
+	let block_one = create_signed_block_with_shard(1, shard_one);
+	let block_two = create_signed_block_with_shard(1, shard_two);
+
+	let fixture = fill_storage_with_blocks::<F>(vec![block_one.clone(), block_two.clone()]);
+
+	{
+		let updated_sidechain_db = fixture.storage();
+		let last_blocks = updated_sidechain_db.get_last_blocks().unwrap();
+
+		assert_eq!(2, last_blocks.len());
+		assert!(last_blocks.contains(&block_one));
+		assert!(last_blocks.contains(&block_two));
+	}
+}
+
+test_all_backends!(
+	get_block_by_number_works,
+	get_blocks_by_number_works_for_regular_case,
+	get_blocks_by_number_stops_at_last_block,
+	get_blocks_by_number_starts_at_oldest_block_after_pruning,
+	get_blocks_by_number_returns_empty_vec_for_unknown_shard,
+	get_last_blocks_returns_last_block_of_every_shard,
+);
@@ -17,20 +17,19 @@
 
 use crate::test_utils::{
 	create_signed_block_with_parenthash as create_signed_block, default_shard,
-	fill_storage_with_blocks, get_storage,
+	fill_storage_with_blocks, test_all_backends, StorageFixture,
 };
 use itp_types::BlockHash;
 use its_primitives::traits::SignedBlock;
 
-#[test]
-fn get_blocks_in_range_works_for_regular_case() {
+fn get_blocks_in_range_works_for_regular_case<F: StorageFixture>() {
 	let block_1 = create_signed_block(1, BlockHash::default());
 	let block_2 = create_signed_block(2, block_1.hash());
 	let block_3 = create_signed_block(3, block_2.hash());
 	let block_4 = create_signed_block(4, block_3.hash());
 	let block_5 = create_signed_block(5, block_4.hash());
 
-	let temp_dir = fill_storage_with_blocks(vec![
+	let fixture = fill_storage_with_blocks::<F>(vec![
 		block_1.clone(),
 		block_2.clone(),
 		block_3,
@@ -39,7 +38,7 @@ fn get_blocks_in_range_works_for_regular_case() {
 	]);
 
 	{
-		let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		let updated_sidechain_db = fixture.storage();
 		let blocks_2_to_4 = updated_sidechain_db
 			.get_blocks_in_range(&block_1.hash(), &block_5.hash(), &default_shard())
 			.unwrap();
@@ -50,14 +49,13 @@ fn get_blocks_in_range_works_for_regular_case() {
 	}
 }
 
-#[test]
-fn get_blocks_in_range_returns_empty_vec_if_from_is_invalid() {
+fn get_blocks_in_range_returns_empty_vec_if_from_is_invalid<F: StorageFixture>() {
 	let block_1 = create_signed_block(1, BlockHash::default());
 	let block_2 = create_signed_block(2, block_1.hash());
 	let block_3 = create_signed_block(3, block_2.hash());
 	let block_4 = create_signed_block(4, block_3.hash());
 
-	let temp_dir = fill_storage_with_blocks(vec![
+	let fixture = fill_storage_with_blocks::<F>(vec![
 		block_1.clone(),
 		block_2.clone(),
 		block_3.clone(),
@@ -65,7 +63,7 @@ fn get_blocks_in_range_returns_empty_vec_if_from_is_invalid() {
 	]);
 
 	{
-		let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		let updated_sidechain_db = fixture.storage();
 		let invalid_block_hash = BlockHash::from_low_u64_be(1);
 
 		assert!(updated_sidechain_db
@@ -75,15 +73,14 @@ fn get_blocks_in_range_returns_empty_vec_if_from_is_invalid() {
 	}
 }
 
-#[test]
-fn get_blocks_in_range_returns_all_blocks_if_upper_bound_is_invalid() {
+fn get_blocks_in_range_returns_all_blocks_if_upper_bound_is_invalid<F: StorageFixture>() {
 	let block_1 = create_signed_block(1, BlockHash::default());
 	let block_2 = create_signed_block(2, block_1.hash());
 	let block_3 = create_signed_block(3, block_2.hash());
 	let block_4 = create_signed_block(4, block_3.hash());
 	let block_5 = create_signed_block(5, block_4.hash());
 
-	let temp_dir = fill_storage_with_blocks(vec![
+	let fixture = fill_storage_with_blocks::<F>(vec![
 		block_1.clone(),
 		block_2.clone(),
 		block_3.clone(),
@@ -92,7 +89,7 @@ fn get_blocks_in_range_returns_all_blocks_if_upper_bound_is_invalid() {
 	]);
 
 	{
-		let updated_sidechain_db = get_storage(temp_dir.path().to_path_buf());
+		let updated_sidechain_db = fixture.storage();
 		let blocks_in_range = updated_sidechain_db
 			.get_blocks_in_range(&block_2.hash(), &BlockHash::from_low_u64_be(1), &default_shard())
 			.unwrap();
@@ -102,3 +99,9 @@ fn get_blocks_in_range_returns_all_blocks_if_upper_bound_is_invalid() {
 		assert_eq!(block_5.hash(), blocks_in_range.last().unwrap().hash());
 	}
 }
+
+test_all_backends!(
+	get_blocks_in_range_works_for_regular_case,
+	get_blocks_in_range_returns_empty_vec_if_from_is_invalid,
+	get_blocks_in_range_returns_all_blocks_if_upper_bound_is_invalid,
+);
@@ -0,0 +1,151 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::{
+	migration::{schema_version, CURRENT_SCHEMA_VERSION, SCHEMA_VERSION_KEY},
+	storage::SidechainStorage,
+	test_utils::{
+		create_signed_block_with_top_hashes as create_signed_block, default_shard,
+		fill_legacy_storage_with_blocks, test_all_backends, StorageFixture,
+	},
+	Error,
+};
+use itp_types::BlockHash;
+use its_primitives::{
+	traits::SignedBlock,
+	types::{block_metadata::OperationInclusion, SignedBlock as SignedSidechainBlock},
+};
+use sp_core::H256;
+use std::assert_matches::assert_matches;
+
+fn stored_schema_version<F: StorageFixture>(fixture: &F) -> u32 {
+	schema_version::<SignedSidechainBlock, _>(&fixture.db()).unwrap()
+}
+
+fn new_storage_stores_current_schema_version<F: StorageFixture>() {
+	let fixture = F::new();
+
+	{
+		let _sidechain_db = fixture.storage();
+	}
+
+	assert_eq!(stored_schema_version(&fixture), CURRENT_SCHEMA_VERSION);
+}
+
+fn opening_version_0_db_builds_operation_index<F: StorageFixture>() {
+	let operation_hash = H256::random();
+	let reincluded_operation_hash = H256::random();
+	let block_1 = create_signed_block(1, BlockHash::default(), vec![reincluded_operation_hash]);
+	let block_2 = create_signed_block(2, block_1.hash(), vec![operation_hash]);
+	let block_3 = create_signed_block(3, block_2.hash(), vec![reincluded_operation_hash]);
+
+	let fixture =
+		fill_legacy_storage_with_blocks::<F>(vec![block_1, block_2.clone(), block_3.clone()], 0);
+	assert_eq!(stored_schema_version(&fixture), 0);
+
+	{
+		let sidechain_db = fixture.storage();
+
+		assert_eq!(sidechain_db.last_block_of_shard(&default_shard()).unwrap().number, 3);
+		assert_eq!(
+			sidechain_db.get_operation_inclusion(&operation_hash).unwrap(),
+			Some(OperationInclusion {
+				shard: default_shard(),
+				block_number: 2,
+				block_hash: block_2.hash()
+			})
+		);
+		assert_eq!(
+			sidechain_db.get_operation_inclusion(&reincluded_operation_hash).unwrap(),
+			Some(OperationInclusion {
+				shard: default_shard(),
+				block_number: 3,
+				block_hash: block_3.hash()
+			})
+		);
+	}
+
+	assert_eq!(stored_schema_version(&fixture), CURRENT_SCHEMA_VERSION);
+}
+
+fn opening_version_0_db_with_pruned_blocks_indexes_remaining_blocks<F: StorageFixture>() {
+	let pruned_operation_hash = H256::random();
+	let kept_operation_hash = H256::random();
+	let block_1 = create_signed_block(1, BlockHash::default(), vec![pruned_operation_hash]);
+	let block_2 = create_signed_block(2, block_1.hash(), vec![]);
+	let block_3 = create_signed_block(3, block_2.hash(), vec![kept_operation_hash]);
+
+	let fixture = fill_legacy_storage_with_blocks::<F>(vec![block_2, block_3.clone()], 0);
+
+	{
+		let sidechain_db = fixture.storage();
+
+		assert!(sidechain_db.get_operation_inclusion(&pruned_operation_hash).unwrap().is_none());
+		assert_eq!(
+			sidechain_db
+				.get_operation_inclusion(&kept_operation_hash)
+				.unwrap()
+				.unwrap()
+				.block_hash,
+			block_3.hash()
+		);
+	}
+}
+
+fn opening_version_1_db_upgrades_to_current_version<F: StorageFixture>() {
+	let block_1 = create_signed_block(1, BlockHash::default(), vec![]);
+	let block_2 = create_signed_block(2, block_1.hash(), vec![]);
+
+	let fixture = fill_legacy_storage_with_blocks::<F>(vec![block_1, block_2.clone()], 1);
+
+	{
+		let mut sidechain_db = fixture.storage();
+
+		assert!(sidechain_db.fork_heads_of_shard(&default_shard()).is_empty());
+		let block_3 = create_signed_block(3, block_2.hash(), vec![]);
+		sidechain_db.store_blocks(vec![block_3]).unwrap();
+	}
+
+	assert_eq!(stored_schema_version(&fixture), CURRENT_SCHEMA_VERSION);
+	assert_eq!(fixture.storage().last_block_of_shard(&default_shard()).unwrap().number, 3);
+}
+
+fn opening_db_of_newer_schema_version_fails<F: StorageFixture>() {
+	let fixture = F::new();
+	{
+		let mut db = fixture.db();
+		db.put(SCHEMA_VERSION_KEY, CURRENT_SCHEMA_VERSION + 1).unwrap();
+	}
+
+	let error = SidechainStorage::<SignedSidechainBlock, _>::new(fixture.backend())
+		.err()
+		.unwrap();
+
+	assert_matches!(
+		error,
+		Error::UnsupportedSchemaVersion(version, CURRENT_SCHEMA_VERSION)
+			if version == CURRENT_SCHEMA_VERSION + 1
+	);
+}
+
+test_all_backends!(
+	new_storage_stores_current_schema_version,
+	opening_version_0_db_builds_operation_index,
+	opening_version_0_db_with_pruned_blocks_indexes_remaining_blocks,
+	opening_version_1_db_upgrades_to_current_version,
+	opening_db_of_newer_schema_version_fails,
+);
@@ -0,0 +1,138 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+use crate::test_utils::{
+	create_signed_block_with_top_hashes as create_signed_block, default_shard,
+	fill_storage_with_blocks, test_all_backends, StorageFixture,
+};
+use itp_types::BlockHash;
+use its_primitives::{traits::SignedBlock, types::block_metadata::OperationInclusion};
+use sp_core::H256;
+
+fn get_operation_inclusion_works<F: StorageFixture>() {
+	let operation_hash = H256::random();
+	let block_1 = create_signed_block(1, BlockHash::default(), vec![H256::random()]);
+	let block_2 = create_signed_block(2, block_1.hash(), vec![operation_hash, H256::random()]);
+
+	let fixture = fill_storage_with_blocks::<F>(vec![block_1, block_2.clone()]);
+
+	{
+		let updated_sidechain_db = fixture.storage();
+
+		assert_eq!(
+			updated_sidechain_db.get_operation_inclusion(&operation_hash).unwrap(),
+			Some(OperationInclusion {
+				shard: default_shard(),
+				block_number: 2,
+				block_hash: block_2.hash()
+			})
+		);
+		assert!(updated_sidechain_db.get_operation_inclusion(&H256::random()).unwrap().is_none());
+	}
+}
+
+fn pruning_removes_operation_index_of_pruned_blocks_only<F: StorageFixture>() {
+	let pruned_operation_hash = H256::random();
+	let kept_operation_hash = H256::random();
+	let block_1 = create_signed_block(1, BlockHash::default(), vec![pruned_operation_hash]);
+	let block_2 = create_signed_block(2, block_1.hash(), vec![]);
+	let block_3 = create_signed_block(3, block_2.hash(), vec![kept_operation_hash]);
+
+	let fixture = fill_storage_with_blocks::<F>(vec![block_1, block_2, block_3.clone()]);
+
+	{
+		let mut sidechain_db = fixture.storage();
+		sidechain_db.prune_shards(1);
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+
+		assert!(updated_sidechain_db
+			.get_operation_inclusion(&pruned_operation_hash)
+			.unwrap()
+			.is_none());
+		assert_eq!(
+			updated_sidechain_db
+				.get_operation_inclusion(&kept_operation_hash)
+				.unwrap()
+				.unwrap()
+				.block_hash,
+			block_3.hash()
+		);
+	}
+}
+
+fn purge_shard_removes_operation_index<F: StorageFixture>() {
+	let operation_hash_1 = H256::random();
+	let operation_hash_2 = H256::random();
+	let block_1 = create_signed_block(1, BlockHash::default(), vec![operation_hash_1]);
+	let block_2 = create_signed_block(2, block_1.hash(), vec![operation_hash_2]);
+
+	let fixture = fill_storage_with_blocks::<F>(vec![block_1, block_2]);
+
+	{
+		let mut sidechain_db = fixture.storage();
+		sidechain_db.purge_shard(&default_shard()).unwrap();
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+
+		assert!(updated_sidechain_db
+			.get_operation_inclusion(&operation_hash_1)
+			.unwrap()
+			.is_none());
+		assert!(updated_sidechain_db
+			.get_operation_inclusion(&operation_hash_2)
+			.unwrap()
+			.is_none());
+	}
+}
+
+fn operation_index_of_reincluded_operation_is_kept_when_pruning_older_block<F: StorageFixture>() {
+	let operation_hash = H256::random();
+	let block_1 = create_signed_block(1, BlockHash::default(), vec![operation_hash]);
+	let block_2 = create_signed_block(2, block_1.hash(), vec![]);
+	let block_3 = create_signed_block(3, block_2.hash(), vec![operation_hash]);
+
+	let fixture = fill_storage_with_blocks::<F>(vec![block_1, block_2, block_3.clone()]);
+
+	{
+		let mut sidechain_db = fixture.storage();
+		sidechain_db.prune_shards(1);
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+
+		assert_eq!(
+			updated_sidechain_db
+				.get_operation_inclusion(&operation_hash)
+				.unwrap()
+				.unwrap()
+				.block_hash,
+			block_3.hash()
+		);
+	}
+}
+
+test_all_backends!(
+	get_operation_inclusion_works,
+	pruning_removes_operation_index_of_pruned_blocks_only,
+	purge_shard_removes_operation_index,
+	operation_index_of_reincluded_operation_is_kept_when_pruning_older_block,
+);
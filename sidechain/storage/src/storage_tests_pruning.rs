@@ -0,0 +1,210 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::test_utils::{
+	default_shard, fill_storage_with_blocks, test_all_backends, StorageFixture,
+};
+use itp_types::BlockHash;
+use its_primitives::{traits::SignedBlock as SignedBlockT, types::SignedBlock};
+use its_test::{
+	sidechain_block_builder::SidechainBlockBuilder,
+	sidechain_block_data_builder::SidechainBlockDataBuilder,
+	sidechain_header_builder::SidechainHeaderBuilder,
+};
+use sp_core::H256;
+
+fn create_signed_block_with_timestamp(
+	block_number: u64,
+	parent_hash: BlockHash,
+	timestamp: u64,
+) -> SignedBlock {
+	let header = SidechainHeaderBuilder::default()
+		.with_parent_hash(parent_hash)
+		.with_block_number(block_number)
+		.with_shard(default_shard())
+		.build();
+	let block_data = SidechainBlockDataBuilder::default()
+		.with_timestamp(timestamp)
+		.with_layer_one_head(H256::random())
+		.build();
+
+	SidechainBlockBuilder::default()
+		.with_header(header)
+		.with_block_data(block_data)
+		.build_signed()
+}
+
+fn create_chain_with_timestamps(timestamps: &[u64]) -> Vec<SignedBlock> {
+	create_chain_with_timestamps_from(1, timestamps)
+}
+
+fn create_chain_with_timestamps_from(
+	first_block_number: u64,
+	timestamps: &[u64],
+) -> Vec<SignedBlock> {
+	let mut blocks: Vec<SignedBlock> = Vec::new();
+	for (index, timestamp) in timestamps.iter().enumerate() {
+		let parent_hash = blocks.last().map(|b| b.hash()).unwrap_or_default();
+		blocks.push(create_signed_block_with_timestamp(
+			first_block_number + index as u64,
+			parent_hash,
+			*timestamp,
+		));
+	}
+	blocks
+}
+
+fn prune_shards_by_age_removes_old_blocks_only<F: StorageFixture>() {
+	let blocks = create_chain_with_timestamps(&[100, 200, 300, 400]);
+	let fixture = fill_storage_with_blocks::<F>(blocks.clone());
+
+	{
+		let mut sidechain_db = fixture.storage();
+		sidechain_db.prune_shards_by_age(250);
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+		assert!(updated_sidechain_db.get_block(&blocks[0].hash()).unwrap().is_none());
+		assert!(updated_sidechain_db.get_block(&blocks[1].hash()).unwrap().is_none());
+		assert_eq!(
+			updated_sidechain_db.get_block(&blocks[2].hash()).unwrap(),
+			Some(blocks[2].clone())
+		);
+		assert_eq!(
+			updated_sidechain_db.get_block(&blocks[3].hash()).unwrap(),
+			Some(blocks[3].clone())
+		);
+	}
+}
+
+fn prune_shards_by_age_always_keeps_last_block<F: StorageFixture>() {
+	let blocks = create_chain_with_timestamps(&[100, 200, 300]);
+	let fixture = fill_storage_with_blocks::<F>(blocks.clone());
+
+	{
+		let mut sidechain_db = fixture.storage();
+		sidechain_db.prune_shards_by_age(1000);
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+		assert!(updated_sidechain_db.get_block(&blocks[0].hash()).unwrap().is_none());
+		assert!(updated_sidechain_db.get_block(&blocks[1].hash()).unwrap().is_none());
+		assert_eq!(updated_sidechain_db.last_block_of_shard(&default_shard()).unwrap().number, 3);
+		assert_eq!(
+			updated_sidechain_db.get_block(&blocks[2].hash()).unwrap(),
+			Some(blocks[2].clone())
+		);
+	}
+}
+
+fn prune_shards_by_age_removes_block_zero<F: StorageFixture>() {
+	let blocks = create_chain_with_timestamps_from(0, &[100, 200, 300]);
+	let fixture = fill_storage_with_blocks::<F>(blocks.clone());
+
+	{
+		let mut sidechain_db = fixture.storage();
+		sidechain_db.prune_shards_by_age(150);
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+		assert!(updated_sidechain_db.get_block(&blocks[0].hash()).unwrap().is_none());
+		for block in blocks[1..].iter() {
+			assert_eq!(updated_sidechain_db.get_block(&block.hash()).unwrap(), Some(block.clone()));
+		}
+	}
+}
+
+fn prune_shards_by_age_removes_all_blocks_down_to_block_zero<F: StorageFixture>() {
+	let blocks = create_chain_with_timestamps_from(0, &[100, 200, 300]);
+	let fixture = fill_storage_with_blocks::<F>(blocks.clone());
+
+	{
+		let mut sidechain_db = fixture.storage();
+		sidechain_db.prune_shards_by_age(1000);
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+		assert!(updated_sidechain_db.get_block(&blocks[0].hash()).unwrap().is_none());
+		assert!(updated_sidechain_db.get_block(&blocks[1].hash()).unwrap().is_none());
+		assert_eq!(updated_sidechain_db.last_block_of_shard(&default_shard()).unwrap().number, 2);
+	}
+}
+
+fn prune_shards_by_age_keeps_block_zero_if_it_is_the_last_block<F: StorageFixture>() {
+	let blocks = create_chain_with_timestamps_from(0, &[100]);
+	let fixture = fill_storage_with_blocks::<F>(blocks.clone());
+
+	{
+		let mut sidechain_db = fixture.storage();
+		sidechain_db.prune_shards_by_age(1000);
+	}
+
+	let updated_sidechain_db = fixture.storage();
+	assert_eq!(updated_sidechain_db.get_block(&blocks[0].hash()).unwrap(), Some(blocks[0].clone()));
+}
+
+fn prune_shards_keeping_more_blocks_than_stored_does_nothing<F: StorageFixture>() {
+	let blocks = create_chain_with_timestamps(&[100, 200]);
+	let fixture = fill_storage_with_blocks::<F>(blocks.clone());
+
+	{
+		let mut sidechain_db = fixture.storage();
+		sidechain_db.prune_shards(100);
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+		for block in blocks {
+			assert_eq!(updated_sidechain_db.get_block(&block.hash()).unwrap(), Some(block));
+		}
+	}
+}
+
+fn prune_shards_keeping_no_blocks_keeps_last_block<F: StorageFixture>() {
+	let blocks = create_chain_with_timestamps(&[100, 200, 300]);
+	let fixture = fill_storage_with_blocks::<F>(blocks.clone());
+
+	{
+		let mut sidechain_db = fixture.storage();
+		sidechain_db.prune_shards(0);
+	}
+
+	{
+		let updated_sidechain_db = fixture.storage();
+		assert!(updated_sidechain_db.get_block(&blocks[0].hash()).unwrap().is_none());
+		assert!(updated_sidechain_db.get_block(&blocks[1].hash()).unwrap().is_none());
+		assert_eq!(updated_sidechain_db.shards(), &vec![default_shard()]);
+		assert_eq!(
+			updated_sidechain_db.get_block(&blocks[2].hash()).unwrap(),
+			Some(blocks[2].clone())
+		);
+	}
+}
+
+test_all_backends!(
+	prune_shards_by_age_removes_old_blocks_only,
+	prune_shards_by_age_always_keeps_last_block,
+	prune_shards_by_age_removes_block_zero,
+	prune_shards_by_age_removes_all_blocks_down_to_block_zero,
+	prune_shards_by_age_keeps_block_zero_if_it_is_the_last_block,
+	prune_shards_keeping_more_blocks_than_stored_does_nothing,
+	prune_shards_keeping_no_blocks_keeps_last_block,
+);
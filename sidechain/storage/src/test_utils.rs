@@ -15,32 +15,143 @@
 
 */
 
-use crate::storage::SidechainStorage;
+#[cfg(feature = "rocksdb")]
+use crate::backend::RocksDbBackend;
+use crate::{
+	backend::{InMemoryBackend, KeyValueBackend},
+	db::SidechainDB,
+	migration::{SchemaVersion, SCHEMA_VERSION_KEY},
+	storage::{LastSidechainBlock, SidechainStorage, LAST_BLOCK_KEY, STORED_SHARDS_KEY},
+};
 use itp_time_utils::now_as_u64;
 use itp_types::ShardIdentifier;
-use its_primitives::types::{BlockHash, SignedBlock as SignedSidechainBlock};
+use its_primitives::{
+	traits::{Block as BlockTrait, Header as HeaderTrait, SignedBlock as SignedBlockTrait},
+	types::{BlockHash, SignedBlock as SignedSidechainBlock},
+};
 use its_test::{
 	sidechain_block_builder::SidechainBlockBuilder,
 	sidechain_block_data_builder::SidechainBlockDataBuilder,
 	sidechain_header_builder::SidechainHeaderBuilder,
 };
 use sp_core::{crypto::Pair, ed25519, H256};
-use std::{path::PathBuf, vec::Vec};
+use std::vec::Vec;
 use temp_dir::TempDir;
 
-pub fn fill_storage_with_blocks(blocks: Vec<SignedSidechainBlock>) -> TempDir {
-	let dir = create_temp_dir();
-	let mut sidechain_db = get_storage(dir.path().to_path_buf());
+/// Provides the storages of a test, backed by one of the storage backends.
+///
+/// Generic tests are run against all backends with [`test_all_backends`].
+pub trait StorageFixture {
+	type Backend: KeyValueBackend;
+
+	fn new() -> Self;
+
+	/// Opens the backend, containing all data written by previously opened ones.
+	fn backend(&self) -> Self::Backend;
+
+	fn storage(&self) -> SidechainStorage<SignedSidechainBlock, Self::Backend> {
+		SidechainStorage::new(self.backend()).unwrap()
+	}
+
+	fn db(&self) -> SidechainDB<Self::Backend> {
+		SidechainDB::new(self.backend())
+	}
+}
+
+#[cfg(feature = "rocksdb")]
+pub struct RocksDbFixture {
+	dir: TempDir,
+}
+
+#[cfg(feature = "rocksdb")]
+impl StorageFixture for RocksDbFixture {
+	type Backend = RocksDbBackend;
+
+	fn new() -> Self {
+		RocksDbFixture { dir: create_temp_dir() }
+	}
+
+	fn backend(&self) -> Self::Backend {
+		RocksDbBackend::open_default(self.dir.path().to_path_buf()).unwrap()
+	}
+}
+
+pub struct InMemoryFixture {
+	backend: InMemoryBackend,
+}
+
+impl StorageFixture for InMemoryFixture {
+	type Backend = InMemoryBackend;
+
+	fn new() -> Self {
+		InMemoryFixture { backend: InMemoryBackend::default() }
+	}
+
+	fn backend(&self) -> Self::Backend {
+		self.backend.clone()
+	}
+}
+
+/// Runs the given generic tests (`fn test<F: StorageFixture>()`) against all storage backends.
+macro_rules! test_all_backends {
+	($($test:ident),* $(,)?) => {
+		#[cfg(feature = "rocksdb")]
+		mod rocksdb_backend {
+			$(
+				#[test]
+				fn $test() {
+					super::$test::<$crate::test_utils::RocksDbFixture>()
+				}
+			)*
+		}
+
+		mod in_memory_backend {
+			$(
+				#[test]
+				fn $test() {
+					super::$test::<$crate::test_utils::InMemoryFixture>()
+				}
+			)*
+		}
+	};
+}
+pub(crate) use test_all_backends;
+
+pub fn fill_storage_with_blocks<F: StorageFixture>(blocks: Vec<SignedSidechainBlock>) -> F {
+	let fixture = F::new();
+	let mut sidechain_db = fixture.storage();
 	sidechain_db.store_blocks(blocks).unwrap();
-	dir
+	fixture
 }
 
-pub fn create_temp_dir() -> TempDir {
-	TempDir::new().unwrap()
+/// Writes the blocks (of the default shard) in the layout of the given schema version,
+/// i.e. without any of the entries added by later versions.
+///
+/// Supports the schema versions 0 (no version stored) and 1.
+pub fn fill_legacy_storage_with_blocks<F: StorageFixture>(
+	blocks: Vec<SignedSidechainBlock>,
+	schema_version: SchemaVersion,
+) -> F {
+	let fixture = F::new();
+	let mut db = fixture.db();
+	let shard = default_shard();
+	db.put(STORED_SHARDS_KEY, vec![shard]).unwrap();
+	for block in blocks.iter() {
+		let hash = block.hash();
+		let block_number = block.block().header().block_number();
+		db.put(hash, block).unwrap();
+		db.put((shard, block_number), hash).unwrap();
+		db.put((LAST_BLOCK_KEY, shard), LastSidechainBlock { hash, number: block_number })
+			.unwrap();
+	}
+	if schema_version > 0 {
+		db.put(SCHEMA_VERSION_KEY, schema_version).unwrap();
+	}
+	fixture
 }
 
-pub fn get_storage(path: PathBuf) -> SidechainStorage<SignedSidechainBlock> {
-	SidechainStorage::<SignedSidechainBlock>::new(path).unwrap()
+pub fn create_temp_dir() -> TempDir {
+	TempDir::new().unwrap()
 }
 
 pub fn default_shard() -> ShardIdentifier {
@@ -61,15 +172,63 @@ pub fn create_signed_block_with_parenthash(
 	SidechainBlockBuilder::default()
 		.with_header(header)
 		.with_block_data(block_data)
+		.with_signer(block_author())
+		.build_signed()
+}
+
+pub fn create_signed_block_with_timestamp(
+	block_number: u64,
+	parent_hash: BlockHash,
+	timestamp: u64,
+) -> SignedSidechainBlock {
+	let header = default_header_builder()
+		.with_parent_hash(parent_hash)
+		.with_block_number(block_number)
+		.build();
+
+	let block_data = default_block_data_builder().with_timestamp(timestamp).build();
+
+	SidechainBlockBuilder::default()
+		.with_header(header)
+		.with_block_data(block_data)
+		.with_signer(block_author())
+		.build_signed()
+}
+
+pub fn create_signed_block_with_top_hashes(
+	block_number: u64,
+	parent_hash: BlockHash,
+	signed_top_hashes: Vec<H256>,
+) -> SignedSidechainBlock {
+	let header = default_header_builder()
+		.with_parent_hash(parent_hash)
+		.with_block_number(block_number)
+		.build();
+
+	let block_data = default_block_data_builder().with_signed_top_hashes(signed_top_hashes).build();
+
+	SidechainBlockBuilder::default()
+		.with_header(header)
+		.with_block_data(block_data)
+		.with_signer(block_author())
 		.build_signed()
 }
 
 pub fn create_signed_block_with_shard(
 	block_number: u64,
 	shard: ShardIdentifier,
+) -> SignedSidechainBlock {
+	create_signed_block_with_shard_and_parenthash(block_number, shard, H256::random())
+}
+
+pub fn create_signed_block_with_shard_and_parenthash(
+	block_number: u64,
+	shard: ShardIdentifier,
+	parent_hash: H256,
 ) -> SignedSidechainBlock {
 	let header = default_header_builder()
 		.with_shard(shard)
+		.with_parent_hash(parent_hash)
 		.with_block_number(block_number)
 		.build();
 
@@ -78,6 +237,7 @@ pub fn create_signed_block_with_shard(
 	SidechainBlockBuilder::default()
 		.with_header(header)
 		.with_block_data(block_data)
+		.with_signer(block_author())
 		.build_signed()
 }
 
@@ -92,5 +252,10 @@ fn default_block_data_builder() -> SidechainBlockDataBuilder {
 	SidechainBlockDataBuilder::default()
 		.with_timestamp(now_as_u64())
 		.with_layer_one_head(H256::random())
-		.with_signer(ed25519::Pair::from_string("//Alice", None).unwrap())
+		.with_signer(block_author())
+}
+
+/// The author of the blocks created here, who signs them too.
+fn block_author() -> ed25519::Pair {
+	ed25519::Pair::from_string("//Alice", None).unwrap()
 }
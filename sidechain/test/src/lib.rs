@@ -24,6 +24,8 @@ compile_error!("feature \"std\" and feature \"sgx\" cannot be disabled at the sa
 #[macro_use]
 extern crate sgx_tstd as std;
 
+pub mod notify_finalized_blocks_mock;
+pub mod notify_heads_mock;
 pub mod sidechain_block_builder;
 pub mod sidechain_block_data_builder;
 pub mod sidechain_header_builder;
@@ -0,0 +1,49 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+	Copyright (C) 2017-2019 Baidu, Inc. All Rights Reserved.
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Mock that records all notified finalized sidechain blocks.
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use its_primitives::{traits::NotifyFinalizedBlocks, types::BlockHash};
+use std::vec::Vec;
+
+#[derive(Default)]
+pub struct NotifyFinalizedBlocksMock<ShardIdentifier> {
+	pub finalized_blocks: RwLock<Vec<(ShardIdentifier, BlockHash)>>,
+}
+
+impl<ShardIdentifier: Clone> NotifyFinalizedBlocksMock<ShardIdentifier> {
+	pub fn finalized_blocks(&self) -> Vec<(ShardIdentifier, BlockHash)> {
+		self.finalized_blocks.read().unwrap().clone()
+	}
+}
+
+impl<ShardIdentifier> NotifyFinalizedBlocks<ShardIdentifier>
+	for NotifyFinalizedBlocksMock<ShardIdentifier>
+where
+	ShardIdentifier: Clone + Send + Sync,
+{
+	fn notify_finalized_block(&self, shard: &ShardIdentifier, block_hash: &BlockHash) {
+		self.finalized_blocks.write().unwrap().push((shard.clone(), *block_hash));
+	}
+}
@@ -0,0 +1,51 @@
+/*
+	Copyright 2021 Integritee AG and Supercomputing Systems AG
+	Copyright (C) 2017-2019 Baidu, Inc. All Rights Reserved.
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+//! Mock that records all notified sidechain headers.
+
+#[cfg(all(not(feature = "std"), feature = "sgx"))]
+use std::sync::SgxRwLock as RwLock;
+
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use its_primitives::traits::{Header as HeaderTrait, NotifyHeads};
+use std::vec::Vec;
+
+#[derive(Default)]
+pub struct NotifyHeadsMock<Header> {
+	pub notified_heads: RwLock<Vec<Header>>,
+}
+
+impl<Header> NotifyHeadsMock<Header> {
+	pub fn notified_heads(&self) -> Vec<Header>
+	where
+		Header: Clone,
+	{
+		self.notified_heads.read().unwrap().clone()
+	}
+}
+
+impl<Header> NotifyHeads<Header> for NotifyHeadsMock<Header>
+where
+	Header: HeaderTrait + Send + Sync,
+{
+	fn notify_new_head(&self, header: &Header) {
+		self.notified_heads.write().unwrap().push(header.clone());
+	}
+}
@@ -18,7 +18,9 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 mod error;
+mod slot_duration;
 mod validateer;
 
 pub use error::Error;
+pub use slot_duration::*;
 pub use validateer::*;
@@ -0,0 +1,98 @@
+/*
+	Copyright 2022 Encointer Association, Integritee AG and Supercomputing Systems AG
+
+	Licensed under the Apache License, Version 2.0 (the "License");
+	you may not use this file except in compliance with the License.
+	You may obtain a copy of the License at
+
+		http://www.apache.org/licenses/LICENSE-2.0
+
+	Unless required by applicable law or agreed to in writing, software
+	distributed under the License is distributed on an "AS IS" BASIS,
+	WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+	See the License for the specific language governing permissions and
+	limitations under the License.
+
+*/
+
+use crate::error::{Error, Result};
+use core::time::Duration;
+use frame_support::ensure;
+use itp_ocall_api::EnclaveOnChainOCallApi;
+use itp_teerex_storage::{SidechainPalletStorage, SidechainStorageKeys};
+use sp_core::H256;
+use sp_runtime::traits::Header as HeaderT;
+
+pub trait SlotDurationFetch {
+	/// Sidechain slot duration configured on the parentchain at the given header.
+	///
+	/// Returns `None` if no slot duration is configured, in which case the default applies.
+	fn slot_duration<Header: HeaderT<Hash = H256>>(
+		&self,
+		header: &Header,
+	) -> Result<Option<Duration>>;
+}
+
+impl<OnchainStorage: EnclaveOnChainOCallApi> SlotDurationFetch for OnchainStorage {
+	fn slot_duration<Header: HeaderT<Hash = H256>>(
+		&self,
+		header: &Header,
+	) -> Result<Option<Duration>> {
+		let maybe_millis: Option<u64> = self
+			.get_storage_verified_and_decoded(SidechainPalletStorage::slot_duration(), header)?
+			.into_tuple()
+			.1;
+
+		match maybe_millis {
+			Some(millis) => {
+				ensure!(millis > 0, Error::Other("Slot duration on chain is zero"));
+				Ok(Some(Duration::from_millis(millis)))
+			},
+			None => Ok(None),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use itc_parentchain_test::parentchain_header_builder::ParentchainHeaderBuilder;
+	use itp_test::mock::onchain_mock::OnchainMock;
+
+	#[test]
+	pub fn slot_duration_is_none_if_not_configured() {
+		let header = ParentchainHeaderBuilder::default().build();
+		let mock = OnchainMock::default();
+
+		assert_eq!(mock.slot_duration(&header).unwrap(), None);
+	}
+
+	#[test]
+	pub fn slot_duration_is_read_at_given_header() {
+		let header = ParentchainHeaderBuilder::default().build();
+		let next_header = ParentchainHeaderBuilder::default()
+			.with_number(2)
+			.with_parent_hash(header.hash())
+			.build();
+		let mut mock = OnchainMock::default();
+		mock.insert_at_header(&header, SidechainPalletStorage::slot_duration(), 300u64.encode());
+		mock.insert_at_header(
+			&next_header,
+			SidechainPalletStorage::slot_duration(),
+			600u64.encode(),
+		);
+
+		assert_eq!(mock.slot_duration(&header).unwrap(), Some(Duration::from_millis(300)));
+		assert_eq!(mock.slot_duration(&next_header).unwrap(), Some(Duration::from_millis(600)));
+	}
+
+	#[test]
+	pub fn zero_slot_duration_returns_err() {
+		let header = ParentchainHeaderBuilder::default().build();
+		let mut mock = OnchainMock::default();
+		mock.insert_at_header(&header, SidechainPalletStorage::slot_duration(), 0u64.encode());
+
+		assert!(mock.slot_duration(&header).is_err());
+	}
+}